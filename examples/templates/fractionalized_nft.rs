@@ -0,0 +1,122 @@
+//! Fractionalized NFT: NEP-171, NEP-177, NEP-178, and NEP-181 tokens that
+//! can be locked in exchange for NEP-141 shares, with NEP-145 storage
+//! management shared by both standards and an owner who may mint NFTs.
+
+use near_sdk::{borsh, env, near_bindgen, AccountId};
+use near_sdk_contract_tools::{
+    fractionalize::*, ft::*, new_project, nft::*, owner::*, standard::nep145::hooks::*,
+};
+
+new_project! {
+    /// NFT contract whose tokens can be fractionalized into fungible shares.
+    #[components(NonFungibleToken, Nep141, Nep148, Escrow, Fractionalize, Owner)]
+    #[nep141(all_hooks = "Nep141StorageAccountingHook")]
+    #[escrow(id = "TokenId", state = "FractionalVault")]
+    pub struct Contract {}
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Initializes the contract with the given owner and metadata. The
+    /// attached deposit registers the contract's own account for storage,
+    /// which pays for holding locked NFTs.
+    #[init]
+    #[payable]
+    pub fn new(
+        owner_id: AccountId,
+        metadata: ContractMetadata,
+        share_metadata: FungibleTokenMetadata,
+    ) -> Self {
+        let mut contract = Self {};
+
+        Owner::init(&mut contract, &owner_id);
+        contract.set_contract_metadata(metadata);
+        contract.set_metadata(&share_metadata);
+        Nep145Controller::deposit_to_storage_account(
+            &mut contract,
+            &env::current_account_id(),
+            env::attached_deposit().into(),
+        )
+        .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+
+        contract
+    }
+
+    /// Mints an NFT to `owner_id`, which must be registered. Owner only.
+    pub fn mint(&mut self, token_id: TokenId, owner_id: AccountId, metadata: TokenMetadata) {
+        Self::require_owner();
+
+        self.mint_with_metadata(token_id, owner_id, metadata)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{json_types::U128, test_utils::VMContextBuilder, testing_env, ONE_NEAR};
+
+    use super::*;
+
+    fn call(predecessor: &AccountId, deposit: u128) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("contract".parse().unwrap())
+            .predecessor_account_id(predecessor.clone())
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    #[test]
+    fn fractionalize_trade_and_redeem() {
+        let owner: AccountId = "owner".parse().unwrap();
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        call(&owner, ONE_NEAR / 10);
+        let mut contract = Contract::new(
+            owner.clone(),
+            ContractMetadata::new("Template NFT".into(), "TNFT".into(), None),
+            FungibleTokenMetadata::new("Template Shares".into(), "TSHR".into(), 0),
+        );
+
+        for account in [&alice, &bob] {
+            call(account, ONE_NEAR / 10);
+            contract.storage_deposit(None, None);
+        }
+
+        call(&owner, 0);
+        contract.mint(
+            "token".to_string(),
+            alice.clone(),
+            TokenMetadata::new().title("Token"),
+        );
+
+        call(&alice, 1);
+        contract.fractionalize_lock("token".to_string(), U128(100));
+        contract.ft_transfer(bob.clone(), U128(30), None);
+
+        assert_eq!(
+            contract
+                .nft_token("token".to_string(), None)
+                .unwrap()
+                .owner_id,
+            env::current_account_id(),
+        );
+        assert_eq!(contract.ft_balance_of(bob.clone()).0, 30);
+
+        call(&bob, 1);
+        contract.ft_transfer(alice.clone(), U128(30), None);
+
+        call(&alice, 1);
+        contract.fractionalize_redeem();
+
+        assert_eq!(
+            contract
+                .nft_token("token".to_string(), None)
+                .unwrap()
+                .owner_id,
+            alice
+        );
+        assert_eq!(contract.ft_total_supply().0, 0);
+        assert_eq!(contract.fractionalize_vault(), None);
+    }
+}
@@ -0,0 +1,99 @@
+//! Fungible token: NEP-141, NEP-145, and NEP-148, with an owner who may mint
+//! tokens.
+
+use near_sdk::{borsh, env, json_types::U128, near_bindgen, AccountId};
+use near_sdk_contract_tools::{ft::*, new_project, owner::*};
+
+new_project! {
+    /// Fungible token contract.
+    #[components(FungibleToken, Owner)]
+    pub struct Contract {}
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Initializes the contract with the given owner and metadata.
+    #[init]
+    pub fn new(owner_id: AccountId, metadata: FungibleTokenMetadata) -> Self {
+        let mut contract = Self {};
+
+        Owner::init(&mut contract, &owner_id);
+        contract.set_metadata(&metadata);
+
+        contract
+    }
+
+    /// Mints `amount` tokens to `account_id`, which must be registered.
+    /// Owner only.
+    pub fn mint(&mut self, account_id: AccountId, amount: U128) {
+        Self::require_owner();
+
+        Nep141Controller::mint(
+            self,
+            &Nep141Mint {
+                amount: amount.0,
+                receiver_id: &account_id,
+                memo: None,
+                origin: ActionOrigin::Internal,
+            },
+        )
+        .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, ONE_NEAR};
+
+    use super::*;
+
+    fn call(predecessor: &AccountId, deposit: u128) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor.clone())
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    #[test]
+    fn mint_and_transfer() {
+        let owner: AccountId = "owner".parse().unwrap();
+        let alice: AccountId = "alice".parse().unwrap();
+
+        call(&owner, 0);
+        let mut contract = Contract::new(
+            owner.clone(),
+            FungibleTokenMetadata::new("Template Token".into(), "TMPL".into(), 24),
+        );
+
+        call(&owner, ONE_NEAR / 100);
+        contract.storage_deposit(None, None);
+        call(&alice, ONE_NEAR / 100);
+        contract.storage_deposit(None, None);
+
+        call(&owner, 0);
+        contract.mint(owner.clone(), U128(100));
+
+        call(&owner, 1);
+        contract.ft_transfer(alice.clone(), U128(40), None);
+
+        assert_eq!(contract.ft_balance_of(owner).0, 60);
+        assert_eq!(contract.ft_balance_of(alice).0, 40);
+        assert_eq!(contract.ft_metadata().symbol, "TMPL");
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner only")]
+    fn mint_owner_only() {
+        let owner: AccountId = "owner".parse().unwrap();
+        let alice: AccountId = "alice".parse().unwrap();
+
+        call(&owner, 0);
+        let mut contract = Contract::new(
+            owner,
+            FungibleTokenMetadata::new("Template Token".into(), "TMPL".into(), 24),
+        );
+
+        call(&alice, 0);
+        contract.mint(alice, U128(100));
+    }
+}
@@ -0,0 +1,168 @@
+//! Multisig DAO: members holding the `Member` role approve requests, which
+//! are executed once enough members have approved. Membership itself is
+//! managed through multisig requests.
+
+use std::fmt;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey, Promise,
+};
+use near_sdk_contract_tools::{
+    approval::{
+        simple_multisig::{ApprovalState, Configuration},
+        Action, ApprovalManager,
+    },
+    new_project,
+    rbac::{Rbac, RbacAction},
+};
+
+/// Roles of the DAO.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    BorshStorageKey,
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum Role {
+    /// May create and approve requests.
+    Member,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Actions that members may request.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum DaoAction {
+    /// Transfers NEAR from the DAO.
+    Transfer {
+        /// Recipient of the transfer.
+        receiver_id: AccountId,
+        /// Amount of NEAR, in yoctoNEAR.
+        amount: near_sdk::json_types::U128,
+    },
+}
+
+impl Action<Contract> for DaoAction {
+    type Output = Promise;
+
+    fn execute(self, _contract: &mut Contract) -> Promise {
+        match self {
+            Self::Transfer {
+                receiver_id,
+                amount,
+            } => Promise::new(receiver_id).transfer(amount.0),
+        }
+    }
+}
+
+new_project! {
+    /// Multisig DAO contract.
+    #[components(Rbac, SimpleMultisig)]
+    #[rbac(roles = "Role")]
+    #[simple_multisig(action = "DaoAction", role = "Role::Member", rbac_actions)]
+    pub struct Contract {}
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Initializes the DAO with the given members. Requests must be approved
+    /// by `threshold` members within `validity_period` nanoseconds.
+    #[init]
+    pub fn new(members: Vec<AccountId>, threshold: u8, validity_period: u64) -> Self {
+        let mut contract = Self {};
+
+        let configuration = || Configuration::new(threshold, validity_period);
+        <Self as ApprovalManager<DaoAction, ApprovalState, _>>::init(configuration());
+        <Self as ApprovalManager<RbacAction<Role>, ApprovalState, _>>::init(configuration());
+
+        for member in members {
+            contract.add_role(member, &Role::Member);
+        }
+
+        contract
+    }
+
+    /// Creates a request, approved by the predecessor. Members only.
+    pub fn request(&mut self, action: DaoAction) -> u32 {
+        let request_id = self
+            .create_request(action, ApprovalState::new())
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        self.approve(request_id);
+        request_id
+    }
+
+    /// Approves a request. Members only.
+    pub fn approve(&mut self, request_id: u32) {
+        <Self as ApprovalManager<DaoAction, _, _>>::approve_request(self, request_id)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    /// Executes an approved request.
+    pub fn execute(&mut self, request_id: u32) -> Promise {
+        <Self as ApprovalManager<DaoAction, _, _>>::execute_request(self, request_id)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()))
+    }
+
+    /// Whether `account_id` is a member.
+    pub fn is_member(&self, account_id: AccountId) -> bool {
+        Self::has_role(&account_id, &Role::Member)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{json_types::U128, test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+
+    fn call(predecessor: &AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor.clone())
+            .build());
+    }
+
+    #[test]
+    fn request_approve_execute() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        let charlie: AccountId = "charlie".parse().unwrap();
+
+        let mut contract = Contract::new(vec![alice.clone(), bob.clone()], 2, u64::MAX);
+
+        call(&alice);
+        let transfer = contract.request(DaoAction::Transfer {
+            receiver_id: charlie.clone(),
+            amount: U128(10),
+        });
+        let membership = contract.rbac_request(
+            RbacAction::Grant {
+                account_id: charlie.clone(),
+                role: Role::Member,
+            },
+            None,
+        );
+        contract.rbac_approve(membership);
+
+        call(&bob);
+        contract.approve(transfer);
+        contract.rbac_approve(membership);
+        contract.execute(transfer);
+        contract.rbac_execute(membership);
+
+        assert!(contract.is_member(charlie));
+    }
+}
@@ -0,0 +1,83 @@
+//! Non-fungible token: NEP-171, NEP-177, NEP-178, and NEP-181 with NEP-145
+//! storage management, with an owner who may mint tokens.
+
+use near_sdk::{borsh, env, near_bindgen, AccountId};
+use near_sdk_contract_tools::{new_project, nft::*, owner::*};
+
+new_project! {
+    /// Non-fungible token contract.
+    #[components(NonFungibleToken, Owner)]
+    pub struct Contract {}
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Initializes the contract with the given owner and metadata.
+    #[init]
+    pub fn new(owner_id: AccountId, metadata: ContractMetadata) -> Self {
+        let mut contract = Self {};
+
+        Owner::init(&mut contract, &owner_id);
+        contract.set_contract_metadata(metadata);
+
+        contract
+    }
+
+    /// Mints a token to `owner_id`, which must be registered. Owner only.
+    pub fn mint(&mut self, token_id: TokenId, owner_id: AccountId, metadata: TokenMetadata) {
+        Self::require_owner();
+
+        self.mint_with_metadata(token_id, owner_id, metadata)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, ONE_NEAR};
+
+    use super::*;
+
+    fn call(predecessor: &AccountId, deposit: u128) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor.clone())
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    #[test]
+    fn mint_and_transfer() {
+        let owner: AccountId = "owner".parse().unwrap();
+        let alice: AccountId = "alice".parse().unwrap();
+
+        call(&owner, 0);
+        let mut contract = Contract::new(
+            owner.clone(),
+            ContractMetadata::new("Template NFT".into(), "TNFT".into(), None),
+        );
+
+        call(&owner, ONE_NEAR / 10);
+        contract.storage_deposit(None, None);
+        call(&alice, ONE_NEAR / 10);
+        contract.storage_deposit(None, None);
+
+        call(&owner, 0);
+        contract.mint(
+            "token".to_string(),
+            owner.clone(),
+            TokenMetadata::new().title("Token"),
+        );
+
+        call(&owner, 1);
+        contract.nft_transfer(alice.clone(), "token".to_string(), None, None);
+
+        assert_eq!(
+            contract
+                .nft_token("token".to_string(), None)
+                .unwrap()
+                .owner_id,
+            alice
+        );
+        assert_eq!(contract.nft_supply_for_owner(alice).0, 1);
+    }
+}
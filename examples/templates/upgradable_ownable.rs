@@ -0,0 +1,96 @@
+//! Upgradable, ownable, pausable contract skeleton. The owner may pause the
+//! contract and upgrade its code.
+
+use near_sdk::{borsh, env, near_bindgen, AccountId};
+use near_sdk_contract_tools::{new_project, owner::*, pause::*};
+
+new_project! {
+    /// Upgradable contract.
+    #[components(Owner, Pause, Upgrade)]
+    #[upgrade(serializer = "borsh", hook = "owner")]
+    pub struct Contract {
+        counter: u64,
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Initializes the contract with the given owner.
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        let mut contract = Self { counter: 0 };
+
+        Owner::init(&mut contract, &owner_id);
+
+        contract
+    }
+
+    /// Increments the counter, unless the contract is paused.
+    pub fn increment(&mut self) -> u64 {
+        Self::require_unpaused();
+        self.counter += 1;
+        self.counter
+    }
+
+    /// Pauses the contract. Owner only.
+    pub fn pause(&mut self) {
+        Self::require_owner();
+        Pause::pause(self);
+    }
+
+    /// Unpauses the contract. Owner only.
+    pub fn unpause(&mut self) {
+        Self::require_owner();
+        Pause::unpause(self);
+    }
+
+    /// Returns the counter.
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Whether the predecessor is the owner.
+    pub fn is_owner(&self) -> bool {
+        Self::slot_owner().read() == Some(env::predecessor_account_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+
+    fn call(predecessor: &AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor.clone())
+            .build());
+    }
+
+    #[test]
+    fn pause_by_owner() {
+        let owner: AccountId = "owner".parse().unwrap();
+
+        call(&owner);
+        let mut contract = Contract::new(owner);
+        assert!(contract.is_owner());
+        assert_eq!(contract.increment(), 1);
+
+        contract.pause();
+        assert!(Contract::is_paused());
+        contract.unpause();
+        assert_eq!(contract.increment(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner only")]
+    fn pause_owner_only() {
+        let owner: AccountId = "owner".parse().unwrap();
+
+        call(&owner);
+        let mut contract = Contract::new(owner);
+
+        call(&"alice".parse().unwrap());
+        contract.pause();
+    }
+}
@@ -0,0 +1,20 @@
+//! Fuzzes the options of every derive macro, both in the macro's own
+//! attribute and in `#[contract_tools(...)]`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use near_sdk_contract_tools_macros::fuzz::DERIVES;
+
+fuzz_target!(|input: (u8, bool, &str)| {
+    let (derive, contract_tools, args) = input;
+    let derive = &DERIVES[derive as usize % DERIVES.len()];
+    let attribute = if contract_tools {
+        "contract_tools"
+    } else {
+        derive.attribute
+    };
+
+    // Errors are fine (they are reported to the user); panics are not.
+    let _ = derive.expand(attribute, args);
+});
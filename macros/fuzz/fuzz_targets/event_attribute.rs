@@ -0,0 +1,13 @@
+//! Fuzzes the options of `#[event(...)]` and the item it is applied to.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use near_sdk_contract_tools_macros::fuzz::event;
+
+fuzz_target!(|input: (&str, &str)| {
+    let (args, item) = input;
+
+    // Errors are fine (they are reported to the user); panics are not.
+    let _ = event(args, item);
+});
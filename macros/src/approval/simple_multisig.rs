@@ -1,17 +1,29 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
+use crate::{invariant::with_invariants, manifest::with_manifest, pause_views::with_paused_views};
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(simple_multisig), supports(struct_named))]
+#[darling(
+    attributes(simple_multisig),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct SimpleMultisigMeta {
     pub storage_key: Option<Expr>,
     pub action: Expr,
     pub role: Expr,
+    pub rbac_actions: Flag,
+    pub rbac_actions_storage_key: Option<Expr>,
+    pub request_views: Flag,
+    pub member_actions: Flag,
+    pub member_actions_storage_key: Option<Expr>,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -25,23 +37,347 @@ pub fn expand(meta: SimpleMultisigMeta) -> Result<TokenStream, darling::Error> {
         storage_key,
         action,
         role,
+        rbac_actions,
+        rbac_actions_storage_key,
+        request_views,
+        member_actions,
+        member_actions_storage_key,
         generics,
         ident,
+        attrs,
         me,
         near_sdk,
     } = meta;
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
+    let root = crate::root_fn(&me, storage_key, &attrs, "ApprovalManager")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let member_actions = if member_actions.is_present() {
+        let root = crate::root_fn(
+            &me,
+            member_actions_storage_key,
+            &attrs,
+            "MemberApprovalManager",
+        )?
+        .unwrap_or_else(|| {
+            quote! {
+                fn root() -> #me::slot::Slot<()> {
+                    #me::slot::Slot::new(#me::DefaultStorageKey::MemberApprovalManager)
+                }
+            }
+        });
+
+        // A threshold change applies to every manager governed by the
+        // multisig that has been initialized.
+        let rbac_threshold = rbac_actions.is_present().then(|| {
+            quote! {
+                set(<Self as #me::approval::ApprovalManagerInternal<
+                    #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                    #me::approval::simple_multisig::ApprovalState,
+                    #me::approval::simple_multisig::Configuration<Self>,
+                >>::slot_config());
+            }
+        });
+
+        Some(quote! {
+            impl #imp #me::approval::ApprovalManagerInternal<
+                    #me::approval::simple_multisig::MemberAction,
+                    #me::approval::simple_multisig::ApprovalState,
+                    #me::approval::simple_multisig::Configuration<Self>,
+                > for #ident #ty #wher {
+                #root
+            }
+
+            impl #imp #me::approval::simple_multisig::MultisigMembers for #ident #ty #wher {
+                fn count_members() -> u32 {
+                    <Self as #me::rbac::Rbac>::count_members(&#role)
+                }
+
+                fn is_member(account_id: &#near_sdk::AccountId) -> bool {
+                    <Self as #me::rbac::Rbac>::has_role(account_id, &#role)
+                }
+
+                fn threshold() -> u8 {
+                    <Self as #me::approval::ApprovalManager<#action, _, _>>::get_config().threshold
+                }
+
+                fn add_member(&mut self, account_id: #near_sdk::AccountId) {
+                    <Self as #me::rbac::Rbac>::add_role(self, account_id, &#role);
+                }
+
+                fn remove_member(&mut self, account_id: &#near_sdk::AccountId) {
+                    <Self as #me::rbac::Rbac>::remove_role(self, account_id, &#role);
+                }
+
+                fn set_threshold(&mut self, threshold: u8) {
+                    let set = |mut slot: #me::slot::Slot<#me::approval::simple_multisig::Configuration<Self>>| {
+                        if let Some(mut config) = slot.read() {
+                            config.threshold = threshold;
+                            slot.write(&config);
+                        }
+                    };
+
+                    set(<Self as #me::approval::ApprovalManagerInternal<
+                        #action,
+                        #me::approval::simple_multisig::ApprovalState,
+                        #me::approval::simple_multisig::Configuration<Self>,
+                    >>::slot_config());
+                    set(<Self as #me::approval::ApprovalManagerInternal<
+                        #me::approval::simple_multisig::MemberAction,
+                        #me::approval::simple_multisig::ApprovalState,
+                        #me::approval::simple_multisig::Configuration<Self>,
+                    >>::slot_config());
+                    #rbac_threshold
+                }
+            }
+
+            #[#near_sdk::near_bindgen]
+            impl #imp #ident #ty #wher {
+                pub fn multisig_member_request(
+                    &mut self,
+                    action: #me::approval::simple_multisig::MemberAction,
+                    metadata: Option<#me::approval::RequestMetadata>,
+                ) -> u32 {
+                    action
+                        .check::<Self>()
+                        .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+
+                    #me::approval::ApprovalManager::create_request_with_metadata(
+                        self,
+                        action,
+                        #me::approval::simple_multisig::ApprovalState::new(),
+                        metadata,
+                    )
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                }
+
+                pub fn multisig_member_approve(&mut self, request_id: u32) {
+                    #me::approval::ApprovalManager::<
+                        #me::approval::simple_multisig::MemberAction,
+                        _,
+                        _,
+                    >::approve_request(self, request_id)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                }
+
+                pub fn multisig_member_execute(&mut self, request_id: u32) {
+                    #me::approval::ApprovalManager::<
+                        #me::approval::simple_multisig::MemberAction,
+                        _,
+                        _,
+                    >::execute_request(self, request_id)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                }
+
+                pub fn multisig_member_get_request(
+                    &self,
+                    request_id: u32,
+                ) -> Option<
+                    #me::approval::ActionRequest<
+                        #me::approval::simple_multisig::MemberAction,
+                        #me::approval::simple_multisig::ApprovalState,
+                    >,
+                > {
+                    <Self as #me::approval::ApprovalManager<
+                        #me::approval::simple_multisig::MemberAction,
+                        _,
+                        _,
+                    >>::get_request(request_id)
+                }
+
+                pub fn multisig_members(&self) -> Vec<#near_sdk::AccountId> {
+                    <Self as #me::rbac::Rbac>::iter_members_of(&#role).collect()
+                }
+
+                pub fn multisig_threshold(&self) -> u8 {
+                    <Self as #me::approval::simple_multisig::MultisigMembers>::threshold()
+                }
+
+                pub fn multisig_member_is_initialized(&self) -> bool {
+                    <Self as #me::approval::ApprovalManager<
+                        #me::approval::simple_multisig::MemberAction,
+                        _,
+                        _,
+                    >>::is_initialized()
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let rbac_actions = if rbac_actions.is_present() {
+        // The default root of `ApprovalManagerInternal` is shared with the
+        // multisig's own requests.
+        let root = crate::root_fn(&me, rbac_actions_storage_key, &attrs, "RbacApprovalManager")?
+            .unwrap_or_else(|| {
+                quote! {
+                    fn root() -> #me::slot::Slot<()> {
+                        #me::slot::Slot::new(#me::DefaultStorageKey::RbacApprovalManager)
+                    }
+                }
+            });
+
+        Some(quote! {
+            impl #imp #me::approval::ApprovalManagerInternal<
+                    #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                    #me::approval::simple_multisig::ApprovalState,
+                    #me::approval::simple_multisig::Configuration<Self>,
+                > for #ident #ty #wher {
+                #root
+            }
+
+            #[#near_sdk::near_bindgen]
+            impl #imp #ident #ty #wher {
+                pub fn rbac_request(
+                    &mut self,
+                    action: #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                    metadata: Option<#me::approval::RequestMetadata>,
+                ) -> u32 {
+                    #me::approval::ApprovalManager::create_request_with_metadata(
+                        self,
+                        action,
+                        #me::approval::simple_multisig::ApprovalState::new(),
+                        metadata,
+                    )
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                }
+
+                pub fn rbac_approve(&mut self, request_id: u32) {
+                    #me::approval::ApprovalManager::<
+                        #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                        _,
+                        _,
+                    >::approve_request(self, request_id)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                }
+
+                pub fn rbac_execute(&mut self, request_id: u32) {
+                    #me::approval::ApprovalManager::<
+                        #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                        _,
+                        _,
+                    >::execute_request(self, request_id)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                }
+
+                pub fn rbac_get_request(
+                    &self,
+                    request_id: u32,
+                ) -> Option<
+                    #me::approval::ActionRequest<
+                        #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                        #me::approval::simple_multisig::ApprovalState,
+                    >,
+                > {
+                    <Self as #me::approval::ApprovalManager<
+                        #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                        _,
+                        _,
+                    >>::get_request(request_id)
+                }
+
+                pub fn rbac_get_request_summary(
+                    &self,
+                    request_id: u32,
+                ) -> Option<#me::approval::RequestSummary> {
+                    <Self as #me::approval::ApprovalManager<
+                        #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                        _,
+                        _,
+                    >>::get_request_summary(request_id)
+                }
+
+                pub fn rbac_list_request_summaries(
+                    &self,
+                    from_index: Option<u32>,
+                    limit: Option<u32>,
+                ) -> Vec<#me::approval::RequestSummary> {
+                    <Self as #me::approval::ApprovalManager<
+                        #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                        _,
+                        _,
+                    >>::list_request_summaries(
+                        from_index.unwrap_or(0),
+                        limit.unwrap_or(u32::MAX),
+                    )
+                }
+
+                pub fn rbac_actions_is_initialized(&self) -> bool {
+                    <Self as #me::approval::ApprovalManager<
+                        #me::rbac::RbacAction<<#ident as #me::rbac::Rbac>::Role>,
+                        _,
+                        _,
+                    >>::is_initialized()
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let governance = (rbac_actions.is_some() || member_actions.is_some())
+        .then(|| quote! { #rbac_actions #member_actions })
+        .map(|tokens| with_invariants(&me, &attrs, tokens))
+        .transpose()?
+        .map(|tokens| with_manifest(&me, "SimpleMultisig", tokens));
+
+    let request_views = request_views.is_present().then(|| {
         quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
+            #[#near_sdk::near_bindgen]
+            impl #imp #ident #ty #wher {
+                pub fn multisig_get_request_summary(
+                    &self,
+                    request_id: u32,
+                ) -> Option<#me::approval::RequestSummary> {
+                    <Self as #me::approval::ApprovalManager<#action, _, _>>::get_request_summary(
+                        request_id,
+                    )
+                }
+
+                pub fn multisig_find_request_by_key(&self, key: String) -> Option<u32> {
+                    <Self as #me::approval::ApprovalManager<#action, _, _>>::find_request_by_key(
+                        &key,
+                    )
+                }
+
+                pub fn multisig_list_request_summaries(
+                    &self,
+                    from_index: Option<u32>,
+                    limit: Option<u32>,
+                ) -> Vec<#me::approval::RequestSummary> {
+                    <Self as #me::approval::ApprovalManager<#action, _, _>>::list_request_summaries(
+                        from_index.unwrap_or(0),
+                        limit.unwrap_or(u32::MAX),
+                    )
+                }
             }
         }
-    });
+    })
+    .map(|tokens| with_paused_views(&me, &attrs, tokens))
+    .transpose()?;
+
+    let is_initialized_view = with_paused_views(
+        &me,
+        &attrs,
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #ident #ty #wher {
+                pub fn multisig_is_initialized(&self) -> bool {
+                    <Self as #me::approval::ApprovalManager<#action, _, _>>::is_initialized()
+                }
+            }
+        },
+    )?;
 
     Ok(quote! {
+        #governance
+        #request_views
+        #is_initialized_view
+
         impl #imp #me::approval::ApprovalManagerInternal<
                 #action,
                 #me::approval::simple_multisig::ApprovalState,
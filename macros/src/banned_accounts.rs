@@ -0,0 +1,86 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(banned_accounts),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct BannedAccountsMeta {
+    pub storage_key: Option<Expr>,
+    pub role: Expr,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: BannedAccountsMeta) -> Result<TokenStream, darling::Error> {
+    let BannedAccountsMeta {
+        storage_key,
+        role,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "BannedAccounts")?;
+
+    let expanded = quote! {
+        impl #imp #me::banned_accounts::BannedAccountsInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::banned_accounts::BannedAccountsExternal for #ident #ty #wher {
+            fn ban_is_banned(&self, account_id: #near_sdk::AccountId) -> bool {
+                <Self as #me::banned_accounts::BannedAccounts>::is_banned(&account_id)
+            }
+
+            fn ban_count(&self) -> u32 {
+                <Self as #me::banned_accounts::BannedAccounts>::banned_count()
+            }
+
+            #[payable]
+            fn ban_add(&mut self, account_id: #near_sdk::AccountId) -> bool {
+                #near_sdk::assert_one_yocto();
+                <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                <Self as #me::banned_accounts::BannedAccounts>::ban(self, &account_id)
+            }
+
+            #[payable]
+            fn ban_remove(&mut self, account_id: #near_sdk::AccountId) -> bool {
+                #near_sdk::assert_one_yocto();
+                <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                <Self as #me::banned_accounts::BannedAccounts>::unban(self, &account_id)
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "BannedAccounts",
+            with_invariants(&me, &attrs, expanded)?,
+        ),
+    ))
+}
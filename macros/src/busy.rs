@@ -0,0 +1,60 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(busy_guard),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct BusyGuardMeta {
+    pub storage_key: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: BusyGuardMeta) -> Result<TokenStream, darling::Error> {
+    let BusyGuardMeta {
+        storage_key,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "BusyGuard")?;
+
+    let expanded = quote! {
+        impl #imp #me::busy::BusyGuardInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::busy::BusyGuardExternal for #ident #ty #wher {
+            fn busy_pending_count(&self) -> u32 {
+                <Self as #me::busy::BusyGuard>::busy_count()
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "BusyGuard", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
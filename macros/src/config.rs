@@ -0,0 +1,71 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, Type};
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim, unitify};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(config),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct ConfigMeta {
+    pub storage_key: Option<Expr>,
+    pub change_hook: Option<Type>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: ConfigMeta) -> Result<TokenStream, darling::Error> {
+    let ConfigMeta {
+        storage_key,
+        change_hook,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Config")?;
+    let change_hook = unitify(change_hook);
+
+    let expanded = quote! {
+        impl #imp #me::config::ConfigInternal for #ident #ty #wher {
+            type ChangeHook = #change_hook;
+
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::config::ConfigExternal for #ident #ty #wher {
+            fn config_get(&self, name: String) -> Option<#near_sdk::serde_json::Value> {
+                <Self as #me::config::Config>::parameter_value(&name)
+            }
+
+            fn config_list(
+                &self,
+            ) -> std::collections::BTreeMap<String, #near_sdk::serde_json::Value> {
+                <Self as #me::config::Config>::parameter_values()
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Config", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
@@ -1,17 +1,23 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(escrow), supports(struct_named))]
+#[darling(
+    attributes(escrow),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct EscrowMeta {
     pub storage_key: Option<Expr>,
     pub id: Expr,
     pub state: Option<Expr>,
+    pub accrual: Flag,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -25,8 +31,10 @@ pub fn expand(meta: EscrowMeta) -> Result<TokenStream, darling::Error> {
         storage_key,
         id,
         state,
+        accrual,
 
         ident,
+        attrs,
         generics,
 
         me,
@@ -35,18 +43,18 @@ pub fn expand(meta: EscrowMeta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
-        quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
-            }
-        }
-    });
+    let root = crate::root_fn(&me, storage_key, &attrs, "Escrow")?;
 
     let state = state
         .map(|state| quote! { #state })
         .unwrap_or_else(|| quote! { () });
 
+    let accrual = accrual.is_present().then(|| {
+        quote! {
+            impl #imp #me::escrow::accrual::EscrowAccrualInternal for #ident #ty #wher {}
+        }
+    });
+
     Ok(quote! {
         impl #imp #me::escrow::EscrowInternal for #ident #ty #wher {
             type Id = #id;
@@ -54,5 +62,7 @@ pub fn expand(meta: EscrowMeta) -> Result<TokenStream, darling::Error> {
 
             #root
         }
+
+        #accrual
     })
 }
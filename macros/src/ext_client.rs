@@ -0,0 +1,225 @@
+use darling::FromMeta;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{FnArg, ItemTrait, Pat, ReturnType, TraitItem, Type};
+
+#[derive(Debug, FromMeta)]
+pub struct ExtClientMeta {
+    pub name: syn::Ident,
+    #[darling(multiple, rename = "method")]
+    pub methods: Vec<MethodMeta>,
+
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+    #[darling(default = "crate::default_serde")]
+    pub serde: syn::Path,
+}
+
+#[derive(Debug, FromMeta)]
+pub struct MethodMeta {
+    pub name: syn::Ident,
+    pub deposit: Option<syn::Expr>,
+    pub gas: Option<syn::Expr>,
+    pub returns: Option<Type>,
+}
+
+/// `PromiseOrValue<T>` resolves to `T`, and no return value to `()`. A
+/// `Promise` does not say what it resolves to, so it requires `returns`.
+fn resolved_type(output: &ReturnType) -> Result<Type, darling::Error> {
+    let ty = match output {
+        ReturnType::Default => return Ok(syn::parse_quote! { () }),
+        ReturnType::Type(_, ty) => ty.as_ref(),
+    };
+
+    let Type::Path(path) = ty else {
+        return Ok(ty.clone());
+    };
+    let Some(last) = path.path.segments.last() else {
+        return Ok(ty.clone());
+    };
+
+    match (last.ident.to_string().as_str(), &last.arguments) {
+        ("PromiseOrValue", syn::PathArguments::AngleBracketed(args)) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => Ok(inner.clone()),
+            _ => Err(darling::Error::custom("Expected `PromiseOrValue<T>`").with_span(ty)),
+        },
+        ("Promise", _) => Err(darling::Error::custom(
+            "Methods that return a `Promise` require `method(name = \"...\", returns = \"...\")`",
+        )
+        .with_span(ty)),
+        _ => Ok(ty.clone()),
+    }
+}
+
+pub fn expand(meta: ExtClientMeta, item: ItemTrait) -> Result<TokenStream, darling::Error> {
+    let ExtClientMeta {
+        name,
+        mut methods,
+        me,
+        near_sdk,
+        serde,
+    } = meta;
+
+    let mut e = darling::Error::accumulator();
+
+    let serde_str = quote! { #serde }.to_string();
+    let trait_ident = &item.ident;
+    let client_doc = format!("Typed off-chain client for [`{trait_ident}`].");
+
+    let mut fns = vec![];
+
+    for trait_item in &item.items {
+        let TraitItem::Fn(f) = trait_item else {
+            continue;
+        };
+
+        let method = &f.sig.ident;
+        let method_str = method.to_string();
+        let docs = f.attrs.iter().filter(|a| a.path().is_ident("doc"));
+
+        let overrides = methods
+            .iter()
+            .position(|m| &m.name == method)
+            .map(|i| methods.remove(i));
+
+        let mutable = match f.sig.inputs.first() {
+            Some(FnArg::Receiver(receiver)) => receiver.mutability.is_some(),
+            _ => {
+                e.push(darling::Error::custom("Expected a `self` receiver").with_span(&f.sig));
+                continue;
+            }
+        };
+
+        let mut arg_idents = vec![];
+        let mut arg_types = vec![];
+        for input in f.sig.inputs.iter().skip(1) {
+            match input {
+                FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                    Pat::Ident(pat) => {
+                        arg_idents.push(pat.ident.clone());
+                        arg_types.push(pat_type.ty.as_ref().clone());
+                    }
+                    pat => {
+                        e.push(darling::Error::custom("Expected an identifier").with_span(pat));
+                    }
+                },
+                FnArg::Receiver(receiver) => {
+                    e.push(darling::Error::unexpected_type("self").with_span(receiver));
+                }
+            }
+        }
+
+        let ret = match overrides.as_ref().and_then(|m| m.returns.clone()) {
+            Some(ty) => ty,
+            None => match resolved_type(&f.sig.output) {
+                Ok(ty) => ty,
+                Err(err) => {
+                    e.push(err);
+                    continue;
+                }
+            },
+        };
+
+        let args_struct = quote! {
+            #[derive(#serde::Serialize)]
+            #[serde(crate = #serde_str)]
+            struct Args { #(#arg_idents: #arg_types),* }
+        };
+
+        if mutable {
+            let gas = overrides
+                .as_ref()
+                .and_then(|m| m.gas.clone())
+                .unwrap_or_else(|| syn::parse_quote! { #me::client::DEFAULT_CALL_GAS });
+            let deposit = overrides
+                .as_ref()
+                .and_then(|m| m.deposit.clone())
+                .unwrap_or_else(|| syn::parse_quote! { 0 });
+
+            fns.push(quote! {
+                #(#docs)*
+                pub fn #method(&self, #(#arg_idents: #arg_types),*) -> #me::client::Call<'_, T, #ret> {
+                    #args_struct
+
+                    #me::client::Call::new(
+                        &self.transport,
+                        &self.contract_id,
+                        #method_str,
+                        Args { #(#arg_idents),* },
+                        #gas,
+                        #deposit,
+                    )
+                }
+            });
+        } else {
+            if let Some(m) = overrides.filter(|m| m.gas.is_some() || m.deposit.is_some()) {
+                e.push(
+                    darling::Error::custom("View methods do not take `gas` or `deposit`")
+                        .with_span(&m.name),
+                );
+            }
+
+            fns.push(quote! {
+                #(#docs)*
+                pub async fn #method(
+                    &self,
+                    #(#arg_idents: #arg_types),*
+                ) -> ::std::result::Result<#ret, #me::client::ClientError<T::Error>> {
+                    #args_struct
+
+                    #me::client::view(
+                        &self.transport,
+                        &self.contract_id,
+                        #method_str,
+                        Args { #(#arg_idents),* },
+                    )
+                    .await
+                }
+            });
+        }
+    }
+
+    for m in methods {
+        e.push(
+            darling::Error::custom(format!("`{}` is not a method of `{trait_ident}`", m.name))
+                .with_span(&m.name),
+        );
+    }
+
+    e.finish()?;
+
+    Ok(quote! {
+        #item
+
+        #[doc = #client_doc]
+        #[derive(Clone, Debug)]
+        pub struct #name<T> {
+            transport: T,
+            contract_id: #near_sdk::AccountId,
+        }
+
+        impl<T: #me::client::Transport> #name<T> {
+            /// Creates a client for the contract at `contract_id`.
+            pub fn new(transport: T, contract_id: #near_sdk::AccountId) -> Self {
+                Self {
+                    transport,
+                    contract_id,
+                }
+            }
+
+            /// The account ID of the contract.
+            pub fn contract_id(&self) -> &#near_sdk::AccountId {
+                &self.contract_id
+            }
+
+            /// The underlying transport.
+            pub fn transport(&self) -> &T {
+                &self.transport
+            }
+
+            #(#fns)*
+        }
+    })
+}
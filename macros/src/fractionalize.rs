@@ -0,0 +1,92 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(fractionalize),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct FractionalizeMeta {
+    pub storage_key: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: FractionalizeMeta) -> Result<TokenStream, darling::Error> {
+    let FractionalizeMeta {
+        storage_key,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Fractionalize")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let expanded = quote! {
+        impl #imp #me::fractionalize::FractionalizeInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::fractionalize::FractionalizeExternal for #ident #ty #wher {
+            #[payable]
+            fn fractionalize_lock(
+                &mut self,
+                token_id: #me::standard::nep171::TokenId,
+                shares: #near_sdk::json_types::U128,
+            ) {
+                #near_sdk::assert_one_yocto();
+                let curator_id = #near_sdk::env::predecessor_account_id();
+
+                #me::fractionalize::Fractionalize::fractionalize(
+                    self,
+                    &curator_id,
+                    &token_id,
+                    shares.into(),
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+
+            #[payable]
+            fn fractionalize_redeem(&mut self) -> #me::standard::nep171::TokenId {
+                #near_sdk::assert_one_yocto();
+                let account_id = #near_sdk::env::predecessor_account_id();
+
+                #me::fractionalize::Fractionalize::redeem(self, &account_id)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+            }
+
+            fn fractionalize_vault(&self) -> Option<#me::fractionalize::FractionalVault> {
+                #me::fractionalize::Fractionalize::vault(self)
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Fractionalize",
+            with_invariants(&me, &attrs, expanded)?,
+        ),
+    ))
+}
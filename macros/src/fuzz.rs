@@ -0,0 +1,200 @@
+//! Entry points for fuzzing the attribute parsing of the macros.
+//!
+//! The fuzz crate (`macros/fuzz`) compiles this crate as a regular library
+//! with `--cfg fuzzing`, which disables the proc-macro entry points, and
+//! feeds arbitrary attribute arguments to [`Derive::expand`] and [`event`].
+//! Malformed input must produce a `darling::Error` (a compile error for the
+//! user), never a panic.
+
+use std::str::FromStr;
+
+use darling::{ast::NestedMeta, FromMeta};
+use proc_macro2::TokenStream;
+use syn::DeriveInput;
+
+use crate::{
+    approval, banned_accounts, busy, config, escrow, expand_derive, fractionalize, gas, manifest,
+    migrate, nft_staking, oracle, owner, pause, raffle, rbac, recovery, replay, sale, standard,
+    stats, storage_dump, storage_report, treasury, upgrade,
+};
+
+/// The item that derives are expanded on.
+const ITEM: &str = "#[near_bindgen] struct Contract { value: u32 }";
+
+/// A derive macro.
+pub struct Derive {
+    /// Name of the derive macro.
+    pub name: &'static str,
+    /// Name of the attribute the derive macro reads its options from.
+    pub attribute: &'static str,
+    expand: fn(&DeriveInput) -> darling::Result<TokenStream>,
+}
+
+impl Derive {
+    /// Expands the derive macro on a struct with `#[<attribute>(<args>)]`.
+    /// Returns `None` if the result is not a valid struct.
+    pub fn expand(&self, attribute: &str, args: &str) -> Option<darling::Result<TokenStream>> {
+        let input =
+            syn::parse_str::<DeriveInput>(&format!("#[{attribute}({args})] {ITEM}")).ok()?;
+        Some((self.expand)(&input))
+    }
+}
+
+macro_rules! derives {
+    ($($name:literal, $attribute:literal => $expand:path;)*) => {
+        &[$(Derive {
+            name: $name,
+            attribute: $attribute,
+            expand: |input| expand_derive(input, $expand),
+        }),*]
+    };
+}
+
+/// Every derive macro.
+pub const DERIVES: &[Derive] = derives! {
+    "Nep297", "nep297" => standard::nep297::expand;
+    "Owner", "owner" => owner::expand;
+    "Pause", "pause" => pause::expand;
+    "Rbac", "rbac" => rbac::expand;
+    "Nep141", "nep141" => standard::nep141::expand;
+    "Nep141Bridge", "nep141_bridge" => standard::nep141_bridge::expand;
+    "Nep141Subscription", "nep141_subscription" => standard::nep141_subscription::expand;
+    "Nep145", "nep145" => standard::nep145::expand;
+    "Nep148", "nep148" => standard::nep148::expand;
+    "FungibleToken", "fungible_token" => standard::fungible_token::expand;
+    "Nep171", "nep171" => standard::nep171::expand;
+    "Nep177", "nep177" => standard::nep177::expand;
+    "Nep178", "nep178" => standard::nep178::expand;
+    "Nep181", "nep181" => standard::nep181::expand;
+    "Nep199", "nep199" => standard::nep199::expand;
+    "NonFungibleToken", "non_fungible_token" => standard::non_fungible_token::expand;
+    "Migrate", "migrate" => migrate::expand;
+    "SimpleMultisig", "simple_multisig" => approval::simple_multisig::expand;
+    "Upgrade", "upgrade" => upgrade::expand;
+    "Escrow", "escrow" => escrow::expand;
+    "Oracle", "oracle" => oracle::expand;
+    "Stats", "stats" => stats::expand;
+    "StorageReport", "storage_report" => storage_report::expand;
+    "Raffle", "raffle" => raffle::expand;
+    "Sale", "sale" => sale::expand;
+    "Fractionalize", "fractionalize" => fractionalize::expand;
+    "StorageDump", "storage_dump" => storage_dump::expand;
+    "Manifest", "manifest" => manifest::expand;
+    "BusyGuard", "busy_guard" => busy::expand;
+    "Treasury", "treasury" => treasury::expand;
+    "GasConfig", "gas_config" => gas::expand;
+    "Recovery", "recovery" => recovery::expand;
+    "BannedAccounts", "banned_accounts" => banned_accounts::expand;
+    "Config", "config" => config::expand;
+    "ReplayGuard", "replay_guard" => replay::expand;
+    "NftStaking", "nft_staking" => nft_staking::expand;
+};
+
+/// Expands `#[event(<args>)]` on `item`. Returns `None` if `args` are not
+/// valid tokens or `item` is not a valid item.
+pub fn event(args: &str, item: &str) -> Option<darling::Result<TokenStream>> {
+    let args = TokenStream::from_str(args).ok()?;
+    let item = syn::parse_str(item).ok()?;
+
+    Some(
+        NestedMeta::parse_meta_list(args)
+            .map_err(darling::Error::from)
+            .and_then(|args| standard::event::EventAttributeMeta::from_list(&args))
+            .and_then(|meta| standard::event::event_attribute(meta, item)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(name: &str) -> &'static Derive {
+        DERIVES.iter().find(|d| d.name == name).unwrap()
+    }
+
+    fn expand_err(name: &str, args: &str) -> String {
+        let derive = derive(name);
+        derive
+            .expand(derive.attribute, args)
+            .expect("valid syntax")
+            .expect_err("invalid input")
+            .to_string()
+    }
+
+    #[test]
+    fn malformed_input_does_not_panic() {
+        let args = [
+            "",
+            "storage_key",
+            "storage_key = 1",
+            r#"storage_key = """#,
+            r#"storage_key = "(""#,
+            r#"storage_key = "b\"~o\" +""#,
+            r#"storage_key = "fn""#,
+            r#"crate = "::""#,
+            r#"crate = "1""#,
+            r#"hook = "any(""#,
+            r#"hook = "any())""#,
+            r#"hook = "any(owner,)""#,
+            r#"hook = "any(role(), self)""#,
+            r#"hook = "role(>)""#,
+            "name, symbol, decimals",
+            r#"name = "", symbol = "", decimals = 256"#,
+            r#"decimals = "x""#,
+            r#"icon = 1, spec = "a", reference = "{""#,
+            r#"transfer_hook = "(", all_hooks = ")""#,
+            r#"transfer_hook = "A B""#,
+            r#"standard = "", version = """#,
+            "key_prefix = 0",
+            r#"components(1, "a")"#,
+            r#"migrate_method_name = "not an ident""#,
+        ];
+
+        for derive in DERIVES {
+            for args in args {
+                derive.expand(derive.attribute, args);
+                derive.expand("contract_tools", args);
+            }
+        }
+
+        for args in args {
+            event(args, "enum Event { A { value: u32 }, B }");
+        }
+    }
+
+    #[test]
+    fn storage_key_expression() {
+        assert!(expand_err("Owner", r#"storage_key = "(""#).contains("storage_key"));
+        assert!(derive("Owner")
+            .expand("owner", r#"storage_key = "b\"~o\"""#)
+            .unwrap()
+            .is_ok());
+    }
+
+    #[test]
+    fn upgrade_hook() {
+        assert!(expand_err("Upgrade", r#"hook = "any(""#).contains("Invalid value"));
+        assert!(expand_err("Upgrade", r#"hook = "role(>)""#).contains("Invalid value"));
+    }
+
+    #[test]
+    fn event_item() {
+        let args = r#"standard = "x", version = "1.0.0""#;
+
+        assert!(event(args, "struct Event { value: u32 }").unwrap().is_ok());
+        assert_eq!(
+            event(args, "fn event() {}")
+                .unwrap()
+                .unwrap_err()
+                .to_string(),
+            "Expected an enum or a struct",
+        );
+    }
+
+    #[test]
+    fn nep148_fields() {
+        assert!(
+            expand_err("Nep148", r#"name = "", symbol = "", decimals = 256"#).contains("decimals")
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(gas_config),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct GasConfigMeta {
+    pub storage_key: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: GasConfigMeta) -> Result<TokenStream, darling::Error> {
+    let GasConfigMeta {
+        storage_key,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "GasConfig")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let expanded = quote! {
+        impl #imp #me::gas::GasConfigInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::gas::GasConfigExternal for #ident #ty #wher {
+            fn gas_config_get(&self, key: #me::gas::GasKey) -> #near_sdk::json_types::U64 {
+                <Self as #me::gas::GasConfig>::gas_for(key).0.into()
+            }
+
+            #[payable]
+            fn gas_config_set(
+                &mut self,
+                key: #me::gas::GasKey,
+                gas: Option<#near_sdk::json_types::U64>,
+            ) {
+                #near_sdk::assert_one_yocto();
+                <Self as #me::owner::Owner>::require_owner();
+
+                <Self as #me::gas::GasConfig>::set_gas_override(
+                    self,
+                    key,
+                    gas.map(|gas| #near_sdk::Gas(gas.0)),
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "GasConfig", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
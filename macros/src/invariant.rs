@@ -0,0 +1,72 @@
+use darling::FromMeta;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{FnArg, ImplItem, Item};
+
+use crate::{mocked_sim::is_near_bindgen, pause_views::with_paused_views, ContractToolsMeta};
+
+/// If the `debug` feature is enabled and the contract opts in with
+/// `#[contract_tools(invariants)]`, checks the contract's invariants at the
+/// end of every mutating external method in `tokens`.
+///
+/// Also applies [`with_paused_views`]. Derives that do not check invariants
+/// apply it directly.
+pub fn with_invariants(
+    me: &syn::Path,
+    attrs: &[syn::Attribute],
+    tokens: TokenStream,
+) -> darling::Result<TokenStream> {
+    let tokens = with_paused_views(me, attrs, tokens)?;
+
+    if !cfg!(feature = "debug") {
+        return Ok(tokens);
+    }
+
+    let mut enabled = false;
+    for attr in attrs {
+        enabled |= ContractToolsMeta::from_meta(&attr.meta)?
+            .invariants
+            .is_present();
+    }
+
+    if !enabled {
+        return Ok(tokens);
+    }
+
+    let mut file: syn::File = syn::parse2(tokens)?;
+
+    for item in &mut file.items {
+        let item = match item {
+            Item::Impl(i) if is_near_bindgen(&i.attrs) => i,
+            _ => continue,
+        };
+
+        for f in &mut item.items {
+            let f = match f {
+                ImplItem::Fn(f) => f,
+                _ => continue,
+            };
+
+            let mutates = f.sig.inputs.iter().any(|input| {
+                matches!(input, FnArg::Receiver(r) if r.reference.is_some() && r.mutability.is_some())
+            });
+
+            if !mutates {
+                continue;
+            }
+
+            let block = &f.block;
+            // The closure catches early returns from the method body.
+            f.block = syn::parse_quote! {
+                {
+                    #[allow(clippy::redundant_closure_call)]
+                    let result = (|| #block)();
+                    <Self as #me::invariant::Invariants>::assert_invariants(self);
+                    result
+                }
+            };
+        }
+    }
+
+    Ok(quote! { #file })
+}
@@ -1,17 +1,51 @@
 //! Macros for near-sdk-contract-tools.
 
+// `cfg(fuzzing)` is set by `cargo fuzz`, see `fuzz/`. The `[lints]` manifest
+// table that would declare it is not supported by the minimum toolchain.
+#![allow(unexpected_cfgs)]
+
 use darling::{ast::NestedMeta, FromDeriveInput, FromMeta};
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, DeriveInput, Item};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, DeriveInput, Item, ItemStruct, ItemTrait};
+
+// The fuzz crate compiles this crate as a regular library, in which the
+// proc-macro entry points are disabled.
+#[cfg(fuzzing)]
+extern crate proc_macro;
 
 mod approval;
+mod banned_accounts;
+mod busy;
+mod config;
 mod escrow;
+mod ext_client;
+mod fractionalize;
+#[cfg(any(test, fuzzing))]
+pub mod fuzz;
+mod gas;
+mod invariant;
+mod manifest;
 mod migrate;
+mod mocked_sim;
+mod new_project;
+mod nft_staking;
+mod oracle;
 mod owner;
 mod pause;
+mod pause_views;
+mod raffle;
 mod rbac;
+mod recovery;
 mod rename;
+mod replay;
+mod sale;
 mod standard;
+mod stats;
+mod storage_dump;
+mod storage_report;
+mod storage_version;
+mod treasury;
 mod upgrade;
 
 fn default_crate_name() -> syn::Path {
@@ -34,6 +68,95 @@ fn unitify(ty: Option<syn::Type>) -> syn::Type {
     ty.unwrap_or_else(|| syn::parse_quote! { () })
 }
 
+/// Composes types into right-nested pairs, e.g. `(A, (B, C))`, which
+/// implement both `Hook` and `LoadTokenMetadata` when their elements do.
+/// No types compose to `()`.
+fn nest(types: &[syn::Type]) -> syn::Type {
+    match types {
+        [] => syn::parse_quote! { () },
+        [ty] => ty.clone(),
+        [first, rest @ ..] => {
+            let rest = nest(rest);
+            syn::parse_quote! { (#first, #rest) }
+        }
+    }
+}
+
+/// Composes an action-specific hook with `all_hooks`. The action-specific
+/// hook runs first (outermost) unless `all_hooks_first` is set.
+fn compose_hooks(
+    action_hook: &impl quote::ToTokens,
+    all_hooks: &impl quote::ToTokens,
+    all_hooks_first: darling::util::Flag,
+) -> proc_macro2::TokenStream {
+    if all_hooks_first.is_present() {
+        quote::quote! { (#all_hooks, #action_hook) }
+    } else {
+        quote::quote! { (#action_hook, #all_hooks) }
+    }
+}
+
+#[derive(Debug, Default, FromMeta)]
+struct ContractToolsMeta {
+    key_prefix: Option<String>,
+    invariants: darling::util::Flag,
+    panic_prefix: Option<String>,
+    pause_views: Option<pause_views::PauseViews>,
+    storage_versions: darling::util::Flag,
+}
+
+/// The contract tag from `#[contract_tools(panic_prefix = "...")]`, as the
+/// `Option<&str>` argument of `tools_panic!`.
+fn panic_prefix(attrs: &[syn::Attribute]) -> darling::Result<proc_macro2::TokenStream> {
+    let mut panic_prefix = None;
+    for attr in attrs {
+        panic_prefix = ContractToolsMeta::from_meta(&attr.meta)?
+            .panic_prefix
+            .or(panic_prefix);
+    }
+
+    Ok(match panic_prefix {
+        Some(panic_prefix) => quote! { Some(#panic_prefix) },
+        None => quote! { None },
+    })
+}
+
+/// Generates the `root()` override for a component: its own `storage_key`
+/// if one is given, otherwise its default key prefixed by
+/// `#[contract_tools(key_prefix = "...")]`, if present.
+fn root_fn(
+    me: &syn::Path,
+    storage_key: Option<syn::Expr>,
+    attrs: &[syn::Attribute],
+    default_key: &str,
+) -> darling::Result<Option<proc_macro2::TokenStream>> {
+    let storage_key = match storage_key {
+        Some(storage_key) => quote! { #storage_key },
+        None => {
+            let mut key_prefix = None;
+            for attr in attrs {
+                key_prefix = ContractToolsMeta::from_meta(&attr.meta)?
+                    .key_prefix
+                    .or(key_prefix);
+            }
+
+            match key_prefix {
+                Some(key_prefix) => {
+                    let default_key = format_ident!("{}", default_key);
+                    quote! { #me::DefaultStorageKey::#default_key.with_prefix(#key_prefix) }
+                }
+                None => return Ok(None),
+            }
+        }
+    };
+
+    Ok(Some(quote! {
+        fn root() -> #me::slot::Slot<()> {
+            #me::slot::Slot::root(#storage_key)
+        }
+    }))
+}
+
 fn make_derive<T>(
     input: TokenStream,
     expand: fn(T) -> Result<proc_macro2::TokenStream, darling::Error>,
@@ -43,12 +166,21 @@ where
 {
     let input = parse_macro_input!(input as DeriveInput);
 
-    FromDeriveInput::from_derive_input(&input)
-        .and_then(expand)
+    expand_derive(&input, expand)
         .map(Into::into)
         .unwrap_or_else(|e| e.write_errors().into())
 }
 
+fn expand_derive<T>(
+    input: &DeriveInput,
+    expand: fn(T) -> Result<proc_macro2::TokenStream, darling::Error>,
+) -> darling::Result<proc_macro2::TokenStream>
+where
+    T: FromDeriveInput,
+{
+    FromDeriveInput::from_derive_input(input).and_then(expand)
+}
+
 /// Use on a struct to emit NEP-297 event strings.
 ///
 /// Specify event standard parameters: `#[nep297(standard = "...", version = "...")]`
@@ -76,7 +208,7 @@ where
 /// For example, `"HelloWorld"`, `"hello_world"`, and `"hello__world"`, when
 /// snake-case-ified, are `"hello_world"`, so if you happened to name distinct
 /// events thusly, the transformed names would collide.
-#[proc_macro_derive(Nep297, attributes(nep297))]
+#[cfg_attr(not(fuzzing), proc_macro_derive(Nep297, attributes(nep297)))]
 pub fn derive_nep297(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep297::expand)
 }
@@ -86,7 +218,14 @@ pub fn derive_nep297(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~o"`) using `#[owner(storage_key = "<expression>")]`.
-#[proc_macro_derive(Owner, attributes(owner))]
+///
+/// The `ownable2step` flag additionally exposes the `Ownable2Step`-style
+/// aliases `owner`, `pending_owner`, `transfer_ownership`,
+/// `accept_ownership`, and `renounce_ownership`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Owner, attributes(owner, contract_tools))
+)]
 pub fn derive_owner(input: TokenStream) -> TokenStream {
     make_derive(input, owner::expand)
 }
@@ -95,12 +234,22 @@ pub fn derive_owner(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~p"`) using `#[pause(storage_key = "<expression>")]`.
-#[proc_macro_derive(Pause, attributes(pause))]
+///
+/// With `#[contract_tools(pause_views)]`, the view methods generated by the
+/// other derive macros on the contract panic while it is paused, except
+/// `paus_is_paused`. `#[contract_tools(pause_views(ft_balance_of, ...))]`
+/// selects the view methods to disable; names that are not generated view
+/// methods are rejected.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Pause, attributes(pause, contract_tools))
+)]
 pub fn derive_pause(input: TokenStream) -> TokenStream {
     make_derive(input, pause::expand)
 }
 
-/// Adds role-based access control. No external methods are exposed.
+/// Adds role-based access control. No external methods are exposed by
+/// default.
 ///
 /// The roles prefix can be specified using `#[rbac(roles = "MyRoles")]`.
 /// Typically `"MyRoles"` is an enum and its variants are the different role
@@ -108,7 +257,15 @@ pub fn derive_pause(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~r"`) using `#[rbac(storage_key = "<expression>")]`.
-#[proc_macro_derive(Rbac, attributes(rbac))]
+///
+/// Fields:
+/// - `roles_of_view`: Flag. Exposes the `rbac_roles_of` view, which lists the
+/// roles of an account. The role type must implement `BorshDeserialize` and
+/// `Serialize`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Rbac, attributes(rbac, contract_tools))
+)]
 pub fn derive_rbac(input: TokenStream) -> TokenStream {
     make_derive(input, rbac::expand)
 }
@@ -119,18 +276,137 @@ pub fn derive_rbac(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~$141"`) using `#[nep141(storage_key = "<expression>")]`.
-#[proc_macro_derive(Nep141, attributes(nep141))]
+///
+/// Fields:
+/// - `all_hooks_first`: Flag. Runs `all_hooks` before the action-specific hook
+/// (e.g. `transfer_hook`), instead of after it. See the `hook` module for
+/// ordering semantics.
+/// - `errors`: `"panic"` (default) or `"result"`. With `"result"`,
+/// `ft_transfer` and `ft_transfer_call` return `Result<_, String>` instead
+/// of panicking when the transfer fails. The methods are then generated as
+/// inherent methods rather than as an implementation of the `Nep141` trait.
+/// - `holder_count`: Flag. Exposes the non-standard `ft_holder_count` view.
+/// - `remove_zero_balances`: Flag. Removes balances that reach zero from
+/// storage instead of storing an explicit `0`. Not recommended with NEP-145
+/// storage accounting, which credits the storage released by the sender of a
+/// transfer to the receiver.
+/// - `zero_balance_sweep_role`: Expression. Exposes the non-standard
+/// `ft_sweep_zero_balances` function, which removes balances stored as an
+/// explicit `0` (e.g. after enabling `remove_zero_balances`). Only accounts
+/// with this role may call it. Requires an `Rbac` implementation.
+/// - `transfer_check`: Flag. Exposes the non-standard `ft_transfer_check`
+/// view, which returns the reason a transfer would fail, if any, without
+/// running hooks. With `dust_role`, this includes the minimum transfer
+/// amount.
+/// - `allowance_views`: Flag. Exposes the non-standard `ft_allowance` and
+/// `ft_allowances_for` views. The contract must implement
+/// `Nep141Allowances`.
+/// - `busy_guard`: Flag. Tracks each `ft_transfer_call` as a pending
+/// operation until `ft_resolve_transfer` runs. The contract must implement
+/// `BusyGuard`.
+/// - `gas_config`: Flag. Reads the gas reserved by `ft_transfer_call` and
+/// `ft_resolve_transfer` from a `GasConfig` implementation instead of the
+/// compile-time defaults.
+/// - `pending_transfers`: Flag. Exposes the non-standard
+/// `ft_transfer_escrowed`, `ft_pending_claim`, `ft_pending_sweep`, and
+/// `ft_pending_transfer` functions, which hold transferred tokens until the
+/// receiver claims them. Storage key prefix: `"~$141p"`.
+/// - `balance_commitment`: Flag. Maintains a Merkle commitment to all
+/// balances, updated after each mint, transfer, and burn, and exposes the
+/// non-standard `ft_balances_commitment` and `ft_balance_proof` views.
+/// Storage key prefix: `"~$141m"`.
+/// - `dust_role`: Expression. Enables a minimum transfer amount, enforced by
+/// `ft_transfer` and `ft_transfer_call`, and exposes the non-standard
+/// `ft_min_transfer`, `ft_set_min_transfer`, and `ft_sweep_dust` functions.
+/// Only accounts with this role may set the minimum or sweep dust. Requires
+/// an `Rbac` implementation. Storage key prefix: `"~$141d"`.
+/// - `batch_mint_role`: Expression. Exposes the non-standard `ft_batch_mint`
+/// function, which mints to many accounts with a single total supply update
+/// and `ft_mint` event. Only accounts with this role may call it. Requires an
+/// `Rbac` implementation.
+/// - `batch_burn_role`: Expression. Like `batch_mint_role`, but exposes
+/// `ft_batch_burn`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep141, attributes(nep141, contract_tools))
+)]
 pub fn derive_nep141(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep141::expand)
 }
 
+/// Adds bridge mint/burn functionality with per-epoch volume caps to a
+/// NEP-141 fungible token. Exposes `ft_bridge_*` functions to the public
+/// blockchain. Requires `Nep141` and `Rbac` implementations.
+///
+/// Fields:
+/// - `role`: Expression. Only accounts with this role may call
+/// `ft_bridge_mint` and `ft_bridge_burn`.
+/// - `pausable`: Flag. Rejects bridge operations while the contract is paused,
+/// as determined by a `Pause` implementation.
+/// - `storage_key`: Storage prefix for bridge data (optional, default:
+/// `"~$141b"`).
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep141Bridge, attributes(nep141_bridge, contract_tools))
+)]
+pub fn derive_nep141_bridge(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep141_bridge::expand)
+}
+
+/// Adds subscription payments to a NEP-141 fungible token: accounts authorize
+/// merchants to charge them up to a fixed amount per period. Exposes
+/// `ft_subscription_*` functions to the public blockchain. Requires a
+/// `Nep141` implementation; charges invoke its transfer hook.
+///
+/// Fields:
+/// - `pausable`: Flag. Rejects charges while the contract is paused, as
+/// determined by a `Pause` implementation.
+/// - `storage_key`: Storage prefix for subscription data (optional, default:
+/// `"~$141s"`).
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep141Subscription, attributes(nep141_subscription, contract_tools))
+)]
+pub fn derive_nep141_subscription(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep141_subscription::expand)
+}
+
 /// Adds NEP-145 fungible token core functionality to a contract. Exposes
 /// `storage_*` functions to the public blockchain, implements internal
 /// controller functionality.
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~$145"`) using `#[nep145(storage_key = "<expression>")]`.
-#[proc_macro_derive(Nep145, attributes(nep145))]
+///
+/// Emits non-standard `x-storage` events on deposit, withdrawal, and
+/// unregistration, unless disabled with `#[nep145(no_events)]`.
+///
+/// `register_hook` runs when an account makes its first deposit. Unlike
+/// `force_unregister_hook`, it does not include `all_hooks`.
+///
+/// Storage accounting uses the protocol storage price by default. Set a
+/// different `StorageByteCost` with `#[nep145(byte_cost = "<type>")]`, e.g.
+/// `FixedStorageByteCost<N>` to pin the price in unit tests.
+///
+/// Optional endpoints:
+/// - `withdraw_to`: Flag. Exposes `storage_withdraw_to`, which withdraws the
+/// predecessor's available storage balance to a named beneficiary.
+/// - `owner_sweep`: Flag. Exposes `storage_sweep`, which allows the owner to
+/// withdraw any account's available storage balance to a named beneficiary.
+/// Requires `Owner`.
+/// - `set_bounds`: Flag. Exposes `storage_set_bounds`, which allows the owner
+/// to change the storage balance bounds, e.g. when protocol storage costs
+/// change. Requires `Owner`, unless `bounds_role` is specified.
+/// - `bounds_role`: Expression. Only accounts with this role may call
+/// `storage_set_bounds`, instead of the owner. Requires `Rbac`.
+///
+/// With `#[nep145(treasury)]`, refunds and withdrawals are rejected if they
+/// would drop the contract's balance below its minimum reserve. Requires
+/// `Treasury`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep145, attributes(nep145, contract_tools))
+)]
 pub fn derive_nep145(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep145::expand)
 }
@@ -140,7 +416,17 @@ pub fn derive_nep145(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~$148"`) using `#[nep148(storage_key = "<expression>")]`.
-#[proc_macro_derive(Nep148, attributes(nep148))]
+///
+/// With `#[nep148(human_views)]`, also exposes `ft_total_supply_human`,
+/// `ft_balance_of_human`, and `ft_metadata_version`, which format amounts
+/// using the metadata decimals. Requires NEP-141.
+///
+/// With `#[nep148(strict_reference)]`, setting metadata panics if it does not
+/// pass `FungibleTokenMetadata::validate_reference`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep148, attributes(nep148, contract_tools))
+)]
 pub fn derive_nep148(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep148::expand)
 }
@@ -151,9 +437,39 @@ pub fn derive_nep148(input: TokenStream) -> TokenStream {
 /// integrate with each other correctly.
 ///
 /// Attributes are generally the union of those from the constituent derive
-/// macros.
+/// macros. Storage events are disabled with `no_storage_events`, and the
+/// NEP-145 `withdraw_to` and `owner_sweep` endpoints are enabled with
+/// `storage_withdraw_to` and `storage_owner_sweep`. NEP-145 `treasury` is
+/// enabled with `storage_treasury`, and `set_bounds` and `bounds_role` with
+/// `storage_set_bounds` and `storage_bounds_role`. The NEP-145
+/// `register_hook` and `byte_cost` are set with `storage_register_hook` and
+/// `storage_byte_cost`. With
+/// `pending_transfers`, held tokens are transferred to the contract's own
+/// account, which must be registered for storage. With `dust_role`, the
+/// treasury of `ft_sweep_dust` must be registered for storage, and with
+/// `batch_mint_role`, so must the receivers of `ft_batch_mint`. With
+/// `balance_commitment`, the storage of new commitment tree nodes is charged
+/// to the receiver of the mint or transfer.
+/// NEP-148 reference hash validation is enforced with `strict_reference`.
+/// With `all_hooks_first`, `all_hooks` runs before the action-specific hooks;
+/// NEP-145 storage accounting always runs after both.
+/// Standards implemented outside this crate are integrated with
+/// `extra = "<type>"`, which may be repeated: the type must implement `Hook`
+/// for `Nep141Mint`, `Nep141Transfer`, and `Nep141Burn`, and runs inside
+/// storage accounting, after the other hooks. The standard's own derive
+/// macro, if any, is added to the contract alongside `FungibleToken`.
+/// With `zero_balance_sweep_role`, the storage released by
+/// `ft_sweep_zero_balances` is credited to the storage balance of each
+/// account, if it is registered.
+/// As with `Nep141`, balances that reach zero are kept in storage unless
+/// `remove_zero_balances` is specified, in which case NEP-145 storage
+/// accounting credits the storage released by removing the sender's balance
+/// to the receiver.
 /// Specify attributes with `#[fungible_token(...)]`.
-#[proc_macro_derive(FungibleToken, attributes(fungible_token))]
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(FungibleToken, attributes(fungible_token, contract_tools))
+)]
 pub fn derive_fungible_token(input: TokenStream) -> TokenStream {
     make_derive(input, standard::fungible_token::expand)
 }
@@ -168,9 +484,25 @@ pub fn derive_fungible_token(input: TokenStream) -> TokenStream {
 /// Fields:
 /// - `no_hooks`: Flag. Removes the requirement for the contract to implement
 /// transfer hooks.
+/// - `all_hooks_first`: Flag. Runs `all_hooks` before the action-specific hook
+/// (e.g. `transfer_hook`), instead of after it.
 /// - `token_data`: specify the token metadata loading extensions invoked by
 /// `nft_token`.
-#[proc_macro_derive(Nep171, attributes(nep171))]
+/// - `transfer_check`: Flag. Exposes the non-standard `nft_transfer_check`
+/// view, which returns the reason a transfer would fail, if any, without
+/// running hooks.
+/// - `gas_config`: Flag. Reads the gas reserved by `nft_transfer_call` and
+/// `nft_resolve_transfer` from a `GasConfig` implementation instead of the
+/// compile-time defaults.
+/// - `owner_token_count`: Flag. Exposes each account's NEP-181 token count
+/// to hooks via `Nep171Controller::owned_token_count`. Requires `Nep181`.
+/// - `mint_limit_per_account`: Rejects mints that would leave the receiver
+/// holding more than the given number of tokens, using
+/// `PerAccountMintLimitHook`. Implies `owner_token_count`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep171, attributes(nep171, contract_tools))
+)]
 pub fn derive_nep171(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep171::expand)
 }
@@ -179,7 +511,15 @@ pub fn derive_nep171(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~$177"`) using `#[nep177(storage_key = "<expression>")]`.
-#[proc_macro_derive(Nep177, attributes(nep177))]
+///
+/// With `#[nep177(strict_metadata)]`, minting a token or updating its metadata
+/// panics if the metadata does not pass `TokenMetadata::validate`, and setting
+/// the contract metadata panics if it does not pass
+/// `ContractMetadata::validate_reference`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep177, attributes(nep177, contract_tools))
+)]
 pub fn derive_nep177(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep177::expand)
 }
@@ -188,7 +528,23 @@ pub fn derive_nep177(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~$178"`) using `#[nep178(storage_key = "<expression>")]`.
-#[proc_macro_derive(Nep178, attributes(nep178))]
+///
+/// `nft_approve` measures the storage used by the approval and charges
+/// exactly its cost, but at least 1 yoctoNEAR, which can be changed with
+/// `#[nep178(approve_min_deposit = "<expression>")]`. Calls with an
+/// insufficient deposit fail with an `InsufficientApprovalDepositError`
+/// specifying the required deposit. The rest of the deposit is refunded;
+/// specify `#[nep178(no_deposit_refund)]` to keep the full deposit instead.
+///
+/// Approving and revoking emit `nep178` events; disable them with
+/// `#[nep178(no_events)]`.
+///
+/// `all_hooks` runs after the action-specific hook (e.g. `approve_hook`),
+/// unless `#[nep178(all_hooks_first)]` is specified.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep178, attributes(nep178, contract_tools))
+)]
 pub fn derive_nep178(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep178::expand)
 }
@@ -197,13 +553,74 @@ pub fn derive_nep178(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~$181"`) using `#[nep181(storage_key = "<expression>")]`.
-#[proc_macro_derive(Nep181, attributes(nep181))]
+///
+/// With `#[nep181(hide_role = "<expression>")]`, accounts with the role can
+/// hide tokens from enumeration and reveal them again with the non-standard
+/// `nft_hide_token` and `nft_reveal_token` methods. Requires `Rbac`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep181, attributes(nep181, contract_tools))
+)]
 pub fn derive_nep181(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep181::expand)
 }
 
+/// Adds NEP-199 non-fungible token royalties and payouts to a contract:
+/// `nft_payout` and `nft_transfer_payout`. Requires NEP-171. Add
+/// `TokenRoyalties` to the NEP-171 burn hooks to remove the royalties of
+/// burned tokens.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$199"`) using `#[nep199(storage_key = "<expression>")]`.
+///
+/// The maximum number of royalty recipients per token can be optionally
+/// specified (default: 10) using `#[nep199(max_royalties = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Nep199, attributes(nep199, contract_tools))
+)]
+pub fn derive_nep199(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep199::expand)
+}
+
 /// Implements all NFT functionality at once, like `#[derive(Nep171, Nep177, Nep178, Nep181)]`.
-#[proc_macro_derive(NonFungibleToken, attributes(non_fungible_token))]
+///
+/// NEP-145 storage events are disabled with `#[non_fungible_token(no_storage_events)]`.
+/// The NEP-145 `withdraw_to` and `owner_sweep` endpoints are enabled with
+/// `storage_withdraw_to` and `storage_owner_sweep`, NEP-145 `treasury`
+/// with `storage_treasury`, and NEP-145 `set_bounds` and `bounds_role` with
+/// `storage_set_bounds` and `storage_bounds_role`. The NEP-145
+/// `register_hook` and `byte_cost` are set with `storage_register_hook` and
+/// `storage_byte_cost`.
+/// NEP-178 deposit refunds are disabled with `#[non_fungible_token(no_deposit_refund)]`,
+/// and the minimum approval deposit is set with `approve_min_deposit`.
+/// NEP-178 approval events are disabled with `no_approval_events`.
+/// The NEP-171 `nft_transfer_check` view is enabled with `transfer_check`,
+/// and `GasConfig` overrides with `gas_config`.
+/// The number of tokens an account may hold is limited by minting with
+/// `mint_limit_per_account = "<expression>"`.
+/// NEP-177 metadata validation is enforced with `strict_metadata`.
+/// NEP-181 `hide_role` is set with `enumeration_hide_role`.
+/// NEP-199 royalties and payouts are enabled with `payouts`, which also
+/// removes the royalties of burned tokens. Their storage key and maximum
+/// number of recipients per token are set with `payouts_storage_key` and
+/// `payouts_max_royalties`.
+/// With `sale`, listings of the `Sale` derive are removed when their token is
+/// transferred or burned outside a sale.
+/// With `all_hooks_first`, `all_hooks` runs before the action-specific hooks;
+/// the integration hooks (storage accounting, approvals, enumeration) always
+/// run after both.
+/// Standards implemented outside this crate are integrated with
+/// `extra = "<type>"`, which may be repeated: the type must implement `Hook`
+/// for `Nep171Mint`, `Nep171Transfer`, and `Nep171Burn`, and runs after the
+/// integration hooks, inside storage accounting. It must also implement
+/// `LoadTokenMetadata`, whose fields are added to `nft_token`. The
+/// standard's own derive macro, if any, is added to the contract alongside
+/// `NonFungibleToken`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(NonFungibleToken, attributes(non_fungible_token, contract_tools))
+)]
 pub fn derive_non_fungible_token(input: TokenStream) -> TokenStream {
     make_derive(input, standard::non_fungible_token::expand)
 }
@@ -215,6 +632,10 @@ pub fn derive_non_fungible_token(input: TokenStream) -> TokenStream {
 /// Fields include:
 ///  - `from` Old default struct type to convert from. (required)
 ///  - `to` New default struct type to convert into. (optional, default: `Self`)
+///  - `args` Type of the JSON arguments of the generated `migrate` function.
+///     If specified, the conversion is performed by `MigrateHookWithArgs<args>`
+///     instead of `MigrateHook`, and `MigrateExternal` is not implemented.
+///     (optional)
 ///  - `convert` Identifier of a function that converts from the old schema to
 ///     the new schema. Mutually exclusive with `convert_with_args`. (optional,
 ///     default: `<Self::NewSchema as From<Self::OldSchema>>::from`)
@@ -222,14 +643,15 @@ pub fn derive_non_fungible_token(input: TokenStream) -> TokenStream {
 ///     schema to the new schema and accepts a single `String` argument.
 ///     Mutually exclusive with `convert`. (optional)
 ///  - `allow` Expression to evaluate before allowing
-#[proc_macro_derive(Migrate, attributes(migrate))]
+#[cfg_attr(not(fuzzing), proc_macro_derive(Migrate, attributes(migrate)))]
 pub fn derive_migrate(input: TokenStream) -> TokenStream {
     make_derive(input, migrate::expand)
 }
 
-/// Create a simple multisig component. Does not expose any functions to the
-/// blockchain. Creates implementations for `ApprovalManager` and
-/// `AccountApprover` for the target contract struct.
+/// Create a simple multisig component. Exposes only the
+/// `multisig_is_initialized` view unless more functions are requested below.
+/// Creates implementations for `ApprovalManager` and `AccountApprover` for
+/// the target contract struct.
 ///
 /// Fields may be specified in the `#[simple_multisig(...)]` attribute.
 ///
@@ -238,7 +660,39 @@ pub fn derive_migrate(input: TokenStream) -> TokenStream {
 ///  - `action` What sort of approval `Action` can be approved by the multisig
 ///     component?
 ///  - `role` Approving accounts are required to have this `Rbac` role.
-#[proc_macro_derive(SimpleMultisig, attributes(simple_multisig))]
+///  - `rbac_actions` Flag. Also implements `ApprovalManager` for
+///     `RbacAction<Role>` and exposes `rbac_request`, `rbac_approve`,
+///     `rbac_execute`, `rbac_get_request`, `rbac_get_request_summary`,
+///     `rbac_list_request_summaries`, and `rbac_actions_is_initialized`
+///     functions, so that role changes
+///     require multisig approval. The `Rbac` role type must implement
+///     `BorshDeserialize` and serde `Serialize` and `Deserialize`, and the
+///     manager must be initialized separately. (optional)
+///  - `rbac_actions_storage_key` Storage prefix for role change requests
+///     (optional, default: `b"~ram"`)
+///  - `member_actions` Flag. Also implements `ApprovalManager` for
+///     `MemberAction` and `MultisigMembers`, and exposes
+///     `multisig_member_request`, `multisig_member_approve`,
+///     `multisig_member_execute`, `multisig_member_get_request`,
+///     `multisig_members`, `multisig_threshold`, and
+///     `multisig_member_is_initialized` functions, so that
+///     members (accounts with `role`) are added and removed, and the
+///     threshold is changed, with the approval of the multisig itself.
+///     Actions that would leave fewer members than the threshold are
+///     rejected. A threshold change applies to every initialized manager
+///     of the multisig. The manager must be initialized separately.
+///     (optional)
+///  - `member_actions_storage_key` Storage prefix for member change requests
+///     (optional, default: `b"~smm"`)
+///  - `request_views` Flag. Exposes `multisig_get_request_summary` and
+///     `multisig_list_request_summaries` view functions, which return the
+///     metadata of requests for `action` without deserializing the action,
+///     and `multisig_find_request_by_key`, which looks up a request by its
+///     idempotency key. (optional)
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(SimpleMultisig, attributes(simple_multisig, contract_tools))
+)]
 pub fn derive_simple_multisig(input: TokenStream) -> TokenStream {
     make_derive(input, approval::simple_multisig::expand)
 }
@@ -246,7 +700,7 @@ pub fn derive_simple_multisig(input: TokenStream) -> TokenStream {
 /// Smart `#[event]` macro.
 ///
 /// See documentation on the [`derive@Nep297`] derive macro for more details.
-#[proc_macro_attribute]
+#[cfg_attr(not(fuzzing), proc_macro_attribute)]
 pub fn event(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attr = match NestedMeta::parse_meta_list(attr.into()) {
         Ok(v) => v,
@@ -262,6 +716,53 @@ pub fn event(attr: TokenStream, item: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.write_errors().into())
 }
 
+/// Generates a typed off-chain client for an `#[ext_contract]` trait. Place
+/// the attribute above `#[ext_contract]`. Requires the `client` feature of
+/// `near-sdk-contract-tools`.
+///
+/// The client is a struct with a `new(transport, contract_id)` constructor
+/// and one method per trait method, with the same arguments and
+/// documentation:
+///
+/// - `&self` methods are view methods, which are `async` and return the
+/// result.
+/// - `&mut self` methods are change methods, which return a `client::Call`
+/// that attaches `client::DEFAULT_CALL_GAS` and no deposit, unless
+/// overridden. A `PromiseOrValue<T>` return type resolves to `T`.
+///
+/// Fields:
+/// - `name`: Name of the client struct.
+/// - `method(name = "...", ...)`: Overrides for the method `name`. May be
+/// repeated. Takes `deposit` (in yoctoNEAR) and `gas` expressions for change
+/// methods, and `returns`, the type that the call resolves to, which is
+/// required for methods that return a `Promise`.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[ext_client(name = "Nep141Client", method(name = "ft_transfer", deposit = 1))]
+/// #[ext_contract(ext_nep141)]
+/// pub trait Nep141 {
+///     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+///     fn ft_balance_of(&self, account_id: AccountId) -> U128;
+/// }
+/// ```
+#[cfg_attr(not(fuzzing), proc_macro_attribute)]
+pub fn ext_client(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = match NestedMeta::parse_meta_list(attr.into()) {
+        Ok(v) => v,
+        Err(e) => {
+            return TokenStream::from(darling::Error::from(e).write_errors());
+        }
+    };
+    let item = parse_macro_input!(item as ItemTrait);
+
+    ext_client::ExtClientMeta::from_list(&attr)
+        .and_then(|meta| ext_client::expand(meta, item))
+        .map(Into::into)
+        .unwrap_or_else(|e| e.write_errors().into())
+}
+
 /// Create an upgrade component. Does not expose any functions to the
 /// blockchain.
 ///
@@ -269,14 +770,22 @@ pub fn event(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Fields include:
 ///  - `hook` - If included, provides an implementation of `UpgradeHook`. An implementation must be explicity provided otherwise. Options include:
-///     - `"none"` - Empty upgrade hook.
+///     - `"empty"` - Empty upgrade hook.
 ///     - `"owner"` - The upgrade function may only be called by the owner of the contract as specified by an `Owner` implementation.
 ///     - `"role(r)"` - The upgrade function may only be called by an account that has been assigned the role `r` as determined by an `Rbac` implementation.
+///     - `"self"` - The upgrade function may only be called by the contract itself (e.g. by a multisig action).
+///     - `"any(a, b, ...)"` - The upgrade function may be called by any account that satisfies at least one of the listed authorizers, e.g. `"any(owner, role(Role::Upgrader))"`.
 ///  - `serializer` - `"borsh"` or `"jsonbase64"` (default). Indicates the serialization format of code the `upgrade` function will accept.
 ///  - `migrate_method_name` - The name of the method to call after the upgrade. Default `"migrate"`.
 ///  - `migrate_method_args` - The input to send to the migrate function. Default empty vector.
 ///  - `migrate_minimum_gas` - How much gas to guarantee the migrate function, otherwise reject. Default 15T.
-#[proc_macro_derive(Upgrade, attributes(upgrade))]
+///  - `busy_guard` - If present, the upgrade is rejected while any operations tracked by a `BusyGuard` implementation are pending.
+///  - `history` - If present, records each upgrade (code hash and timestamp) and exposes the `upgrade_history` view. Storage key prefix: `"~up"`, or `history_storage_key`.
+///  - `throttle` - An `UpgradeThrottle` expression, e.g. `"UpgradeThrottle::min_interval(DAY)"`. Rejects upgrades that the policy does not allow yet. Implies `history`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Upgrade, attributes(upgrade, contract_tools))
+)]
 pub fn derive_upgrade(input: TokenStream) -> TokenStream {
     make_derive(input, upgrade::expand)
 }
@@ -288,7 +797,331 @@ pub fn derive_upgrade(input: TokenStream) -> TokenStream {
 ///  - `id` - the type required for id, must be `borsh::BorshSerialize` & `serde::Serialize`, for events
 ///  - `state` - the type required for id, must be `borsh::BorshSerialize` & `borsh::BorshSerialize`
 ///  - `storage_key` Storage prefix for escrow data (optional, default: `b"~es"`)
-#[proc_macro_derive(Escrow, attributes(escrow))]
+///  - `accrual` - Flag. Implements `EscrowAccrualInternal` with the policy
+///    configured at init, for interest and penalties settled in the
+///    contract's `Nep141` token on unlock
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Escrow, attributes(escrow, contract_tools))
+)]
 pub fn derive_escrow(input: TokenStream) -> TokenStream {
     make_derive(input, escrow::expand)
 }
+
+/// Creates a managed, lazily-loaded `Oracle` implementation for the targeted
+/// `#[near_bindgen]` struct.
+///
+/// Fields include:
+///  - `storage_key` - Storage prefix for oracle data (optional, default: `b"~or"`)
+///  - `role` - If specified, accounts that have been assigned this role (as
+///    determined by an `Rbac` implementation) may also push prices, in
+///    addition to the oracle account.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Oracle, attributes(oracle, contract_tools))
+)]
+pub fn derive_oracle(input: TokenStream) -> TokenStream {
+    make_derive(input, oracle::expand)
+}
+
+/// Creates a managed, lazily-loaded `Stats` implementation for the targeted
+/// `#[near_bindgen]` struct, and exposes the `contract_stats` view. Counters
+/// are maintained by the hooks in the `stats` module.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~st"`) using `#[stats(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Stats, attributes(stats, contract_tools))
+)]
+pub fn derive_stats(input: TokenStream) -> TokenStream {
+    make_derive(input, stats::expand)
+}
+
+/// Creates a managed, lazily-loaded `StorageReport` implementation for the
+/// targeted `#[near_bindgen]` struct, and exposes the `storage_report` view.
+/// Per-component byte counts are maintained by `StorageUsageHook`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~sr"`) using `#[storage_report(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(StorageReport, attributes(storage_report, contract_tools))
+)]
+pub fn derive_storage_report(input: TokenStream) -> TokenStream {
+    make_derive(input, storage_report::expand)
+}
+
+/// Creates a managed, lazily-loaded `Raffle` implementation for the targeted
+/// `#[near_bindgen]` struct, and exposes the `raffle_get_status`,
+/// `raffle_entry_count`, and `raffle_winners` views. Entering, closing, and
+/// drawing are left to the contract, since who may perform them is
+/// application-specific.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~rf"`) using `#[raffle(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Raffle, attributes(raffle, contract_tools))
+)]
+pub fn derive_raffle(input: TokenStream) -> TokenStream {
+    make_derive(input, raffle::expand)
+}
+
+/// Creates a managed, lazily-loaded `Sale` implementation for the targeted
+/// `#[near_bindgen]` struct, and exposes the `sale_get_listing`, `sale_list`,
+/// `sale_delist`, `sale_buy` (native NEAR), and `sale_buy_ft` (the contract's
+/// own NEP-141 token) functions. Requires `Nep141` and `Nep171`
+/// implementations. Add the `SaleListings` hook to `Nep171` (or set
+/// `#[non_fungible_token(sale)]`) to remove listings of tokens that are
+/// transferred or burned outside a sale.
+///
+/// Fields:
+/// - `royalties`: Type. A `SaleRoyalties` implementation that determines how
+/// the price of each sale is split. By default, the seller receives the full
+/// price.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~sale"`) using `#[sale(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Sale, attributes(sale, contract_tools))
+)]
+pub fn derive_sale(input: TokenStream) -> TokenStream {
+    make_derive(input, sale::expand)
+}
+
+/// Creates a managed, lazily-loaded `Fractionalize` implementation for the
+/// targeted `#[near_bindgen]` struct, which locks an NFT in exchange for
+/// fungible shares. Exposes `fractionalize_lock` and `fractionalize_redeem`,
+/// which require 1 yoctoNEAR, and the `fractionalize_vault` view.
+///
+/// Requires `Nep141`, `Nep171`, and `Escrow` implementations, with
+/// `#[escrow(id = "TokenId", state = "FractionalVault")]`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~fr"`) using `#[fractionalize(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Fractionalize, attributes(fractionalize, contract_tools))
+)]
+pub fn derive_fractionalize(input: TokenStream) -> TokenStream {
+    make_derive(input, fractionalize::expand)
+}
+
+/// Exposes the `__tools_storage_dump(prefix, from, limit)` view method, which
+/// lists the storage keys written by contract tools components under
+/// `prefix` (base64, optional), starting at registry position `from`
+/// (default: 0), returning at most `limit` keys (default: 100).
+///
+/// Requires the `debug` feature of `near-sdk-contract-tools`. Not intended
+/// for production builds.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(StorageDump, attributes(storage_dump, contract_tools))
+)]
+pub fn derive_storage_dump(input: TokenStream) -> TokenStream {
+    make_derive(input, storage_dump::expand)
+}
+
+/// Collects the method manifests of the listed components into a
+/// `MANIFEST: ContractManifest` const on the contract, and exposes it as
+/// JSON via the `__tools_manifest` view method. The manifest describes each
+/// external method generated by the components: view or change method,
+/// expected deposit, minimum gas, and argument and return types.
+///
+/// Also exposes the NEAR standards implemented by the components, with their
+/// versions, via the `supported_standards` view method (e.g.
+/// `[{"standard":"nep141","version":"1.0.0"}, ...]`).
+///
+/// List components by the name of their derive macro, e.g.
+/// `#[manifest(components(FungibleToken, Owner, Pause))]`. `FungibleToken`
+/// and `NonFungibleToken` include their constituent standards.
+///
+/// Requires the `manifest` feature of `near-sdk-contract-tools` (enabled by
+/// default), without which components do not describe their methods.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Manifest, attributes(manifest, contract_tools))
+)]
+pub fn derive_manifest(input: TokenStream) -> TokenStream {
+    make_derive(input, manifest::expand)
+}
+
+/// Tracks pending multi-receipt operations. Provides an implementation of the
+/// `BusyGuard` trait, and exposes the number of pending operations as
+/// `busy_pending_count`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~bg"`) using `#[busy_guard(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(BusyGuard, attributes(busy_guard, contract_tools))
+)]
+pub fn derive_busy_guard(input: TokenStream) -> TokenStream {
+    make_derive(input, busy::expand)
+}
+
+/// Enforces a minimum native balance. Provides an implementation of the
+/// `Treasury` trait, and exposes the minimum reserve and the balance
+/// available above it as `treasury_min_reserve` and
+/// `treasury_available_balance`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~tr"`) using `#[treasury(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Treasury, attributes(treasury, contract_tools))
+)]
+pub fn derive_treasury(input: TokenStream) -> TokenStream {
+    make_derive(input, treasury::expand)
+}
+
+/// Stores overrides for the gas amounts reserved by generated methods.
+/// Provides an implementation of the `GasConfig` trait, and exposes
+/// `gas_config_get` and the owner-only `gas_config_set`. Requires `Owner`.
+/// Enable the overrides with `#[nep141(gas_config)]` or
+/// `#[nep171(gas_config)]`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~gas"`) using `#[gas_config(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(GasConfig, attributes(gas_config, contract_tools))
+)]
+pub fn derive_gas_config(input: TokenStream) -> TokenStream {
+    make_derive(input, gas::expand)
+}
+
+/// Lets a designated recovery account claim the contract if the owner stops
+/// sending heartbeats. Provides an implementation of the `Recovery` trait,
+/// and exposes the `recovery_get_config`, `recovery_get_last_heartbeat`, and
+/// `recovery_get_available_at` views, the owner-only `recovery_set_config`
+/// and `recovery_heartbeat`, and `recovery_claim`. Requires `Owner`.
+///
+/// Claiming transfers ownership to the recovery account by default. Run a
+/// different `RecoveryAction` with `#[recovery(action = "<type>")]`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~rec"`) using `#[recovery(storage_key = "<expression>")]`.
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Recovery, attributes(recovery, contract_tools))
+)]
+pub fn derive_recovery(input: TokenStream) -> TokenStream {
+    make_derive(input, recovery::expand)
+}
+
+/// Adds a contract-wide account ban list. Provides an implementation of the
+/// `BannedAccounts` trait, and exposes the `ban_is_banned` and `ban_count`
+/// views, and `ban_add` and `ban_remove`, which are only callable by
+/// accounts with the configured role. Requires `Rbac`.
+///
+/// The ban list is enforced by `BannedAccountsHook`, which should be added to
+/// the `all_hooks` and NEP-145 `register_hook` of the token components.
+///
+/// Fields:
+/// - `role`: Expression. Only accounts with this role may manage the ban
+/// list.
+/// - `storage_key`: Storage prefix for the ban list (optional, default:
+/// `"~ban"`).
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(BannedAccounts, attributes(banned_accounts, contract_tools))
+)]
+pub fn derive_banned_accounts(input: TokenStream) -> TokenStream {
+    make_derive(input, banned_accounts::expand)
+}
+
+/// Records processed transfers to reject duplicates, e.g. in
+/// `ft_on_transfer`. Provides an implementation of the `ReplayGuard` trait,
+/// and exposes the `replay_is_processed` and `replay_processed_count` views,
+/// and `replay_cleanup`, which removes expired records and is callable by
+/// anyone.
+///
+/// Fields:
+/// - `ttl`: Expression. Time to live of a record, in nanoseconds (optional,
+/// default: 7 days).
+/// - `cleanup_limit`: Expression. Number of expired records removed by each
+/// new record (optional, default: 4).
+/// - `storage_key`: Storage prefix for the records (optional, default:
+/// `"~rp"`).
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(ReplayGuard, attributes(replay_guard, contract_tools))
+)]
+pub fn derive_replay_guard(input: TokenStream) -> TokenStream {
+    make_derive(input, replay::expand)
+}
+
+/// Adds staking of NEP-171 tokens for rewards. Provides an implementation of
+/// the `NftStaking` trait, and exposes `nft_stake`, `nft_unstake`, and
+/// `nft_claim_staking_rewards`, which require 1 yoctoNEAR, and the
+/// `nft_stake_info`, `nft_pending_staking_rewards`, and
+/// `nft_staking_reward_balance` views. Requires NEP-171. Staked tokens are
+/// locked by `NftStakingHook`, which must be added as a transfer and burn
+/// hook, e.g. with `#[non_fungible_token(extra = "NftStakingHook")]`.
+///
+/// Fields:
+/// - `epoch`: Expression. Length of an epoch, in nanoseconds (optional,
+/// default: 1 day).
+/// - `reward_per_epoch`: Expression. Rewards accrued by a staked token per
+/// epoch (optional, default: 0).
+/// - `cooldown`: Expression. Time a token stays locked after it is unstaked,
+/// in nanoseconds (optional, default: 1 day).
+/// - `storage_key`: Storage prefix for the stakes and reward balances
+/// (optional, default: `"~nstk"`).
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(NftStaking, attributes(nft_staking, contract_tools))
+)]
+pub fn derive_nft_staking(input: TokenStream) -> TokenStream {
+    make_derive(input, nft_staking::expand)
+}
+
+/// Adds governed configuration parameters. Provides an implementation of the
+/// `Config` trait, and exposes the `config_get` and `config_list` views.
+/// Parameters are changed by executing `ConfigChange` actions, e.g. with
+/// `#[simple_multisig(action = "ConfigChange")]`.
+///
+/// Fields:
+/// - `change_hook`: Type. A `Hook<Self, ConfigChange>` that runs around
+/// every change, e.g. to validate the new value (optional).
+/// - `storage_key`: Storage prefix for the parameters (optional, default:
+/// `"~cfg"`).
+#[cfg_attr(
+    not(fuzzing),
+    proc_macro_derive(Config, attributes(config, contract_tools))
+)]
+pub fn derive_config(input: TokenStream) -> TokenStream {
+    make_derive(input, config::expand)
+}
+
+/// Declares a contract struct with the usual derives and attributes of a
+/// contract built with this crate: `BorshSerialize`, `BorshDeserialize`,
+/// `PanicOnDefault`, `#[near_bindgen]`, and the derive macros listed in
+/// `#[components(...)]`. Storage keys of the components are namespaced with
+/// `#[contract_tools(key_prefix = "...")]`, which defaults to the struct name
+/// in snake case and can be changed with `#[key_prefix = "..."]`.
+///
+/// Bare component names refer to this crate's derive macros. The `borsh`
+/// crate must be in scope (`use near_sdk::borsh;`).
+///
+/// # Examples
+///
+/// ```ignore
+/// new_project! {
+///     /// My token.
+///     #[components(FungibleToken, Owner, Pause)]
+///     #[fungible_token(transfer_hook = "PausableHook")]
+///     pub struct MyToken {}
+/// }
+/// ```
+#[cfg_attr(not(fuzzing), proc_macro)]
+pub fn new_project(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as ItemStruct);
+
+    new_project::expand(item)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
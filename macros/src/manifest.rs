@@ -0,0 +1,321 @@
+use darling::{util::PathList, FromDeriveInput};
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    Attribute, FnArg, GenericArgument, ImplItem, ImplItemFn, Item, Pat, PathArguments, ReturnType,
+    Type, Visibility,
+};
+
+use crate::{mocked_sim::is_near_bindgen, pause_views::with_paused_views};
+
+/// If the `manifest` feature is enabled, appends an implementation of
+/// `HasManifest<component::#component>` that describes every external method
+/// of every `#[near_bindgen]` impl block in `tokens`.
+pub fn with_manifest(me: &syn::Path, component: &str, tokens: TokenStream) -> TokenStream {
+    if !cfg!(feature = "manifest") {
+        return tokens;
+    }
+
+    let file: syn::File = match syn::parse2(tokens.clone()) {
+        Ok(file) => file,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let impls = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Impl(i) if is_near_bindgen(&i.attrs) => Some(i),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let first = match impls.first() {
+        Some(first) => first,
+        None => return tokens,
+    };
+
+    let (imp, _, wher) = first.generics.split_for_impl();
+    let self_ty = &first.self_ty;
+    let marker = format_ident!("{}", component);
+
+    let methods = impls.iter().flat_map(|i| {
+        let is_trait_impl = i.trait_.is_some();
+        i.items.iter().filter_map(move |item| match item {
+            ImplItem::Fn(f) if is_trait_impl || matches!(f.vis, Visibility::Public(_)) => {
+                Some(method_manifest(me, f))
+            }
+            _ => None,
+        })
+    });
+
+    let standards = standards(component).iter().map(|(standard, version)| {
+        quote! {
+            #me::manifest::StandardManifest { standard: #standard, version: #version }
+        }
+    });
+
+    quote! {
+        #tokens
+
+        impl #imp #me::manifest::HasManifest<#me::manifest::component::#marker> for #self_ty #wher {
+            const MANIFEST: #me::manifest::ComponentManifest = #me::manifest::ComponentManifest {
+                name: #component,
+                methods: &[#(#methods),*],
+                standards: &[#(#standards),*],
+            };
+        }
+    }
+}
+
+/// NEAR standards (name and version) implemented by each component.
+fn standards(component: &str) -> &'static [(&'static str, &'static str)] {
+    match component {
+        "Nep141" => &[("nep141", "1.0.0"), ("nep297", "1.0.0")],
+        "Nep145" => &[("nep145", "1.0.0")],
+        "Nep148" => &[("nep148", "1.0.0")],
+        "Nep171" => &[("nep171", "1.2.0"), ("nep297", "1.0.0")],
+        "Nep177" => &[("nep177", "2.0.0")],
+        "Nep178" => &[("nep178", "1.0.0"), ("nep297", "1.0.0")],
+        "Nep181" => &[("nep181", "1.0.0")],
+        "Nep199" => &[("nep199", "2.0.0")],
+        _ => &[],
+    }
+}
+
+fn has_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|a| a.path().is_ident(name))
+}
+
+fn method_manifest(me: &syn::Path, f: &ImplItemFn) -> TokenStream {
+    let name = f.sig.ident.to_string();
+    let payable = has_attr(&f.attrs, "payable");
+    let private = has_attr(&f.attrs, "private");
+
+    let mut kind = if has_attr(&f.attrs, "init") {
+        quote! { Init }
+    } else {
+        quote! { Call }
+    };
+
+    let mut args = vec![];
+
+    for input in &f.sig.inputs {
+        match input {
+            FnArg::Receiver(r) => {
+                if r.reference.is_some() && r.mutability.is_none() {
+                    kind = quote! { View };
+                }
+            }
+            FnArg::Typed(t) => {
+                // Callback results are not part of the JSON arguments.
+                let is_callback = t.attrs.iter().any(|a| {
+                    a.path()
+                        .get_ident()
+                        .is_some_and(|i| i.to_string().starts_with("callback"))
+                });
+                if is_callback {
+                    continue;
+                }
+
+                let arg_name = match &*t.pat {
+                    Pat::Ident(p) => p.ident.to_string(),
+                    pat => pat.to_token_stream().to_string(),
+                };
+                let ty = render_type(&t.ty);
+                args.push(quote! {
+                    #me::manifest::ArgumentManifest { name: #arg_name, ty: #ty }
+                });
+            }
+        }
+    }
+
+    let body = f.block.to_token_stream();
+
+    let deposit = if find_call(body.clone(), "assert_one_yocto").is_some() {
+        quote! { OneYocto }
+    } else if payable {
+        quote! { Attached }
+    } else {
+        quote! { None }
+    };
+
+    // Configurable amounts are described by their defaults.
+    let gas = match (
+        find_call(body.clone(), "require_gas"),
+        find_call(body, "require_gas_for"),
+    ) {
+        (Some(gas), _) => quote! { Some(#gas) },
+        (None, Some(key)) => quote! { Some(#key.default_gas()) },
+        (None, None) => quote! { None },
+    };
+
+    let returns = match &f.sig.output {
+        ReturnType::Default => quote! { None },
+        ReturnType::Type(_, ty) => {
+            let ty = render_type(ty);
+            quote! { Some(#ty) }
+        }
+    };
+
+    quote! {
+        #me::manifest::MethodManifest {
+            name: #name,
+            kind: #me::manifest::MethodKind::#kind,
+            payable: #payable,
+            private: #private,
+            deposit: #me::manifest::Deposit::#deposit,
+            gas: #gas,
+            args: &[#(#args),*],
+            returns: #returns,
+        }
+    }
+}
+
+/// Finds the first call to a function named `name` in `tokens`, and returns
+/// its arguments.
+fn find_call(tokens: TokenStream, name: &str) -> Option<TokenStream> {
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Ident(ident) if ident == name => {
+                if let Some(TokenTree::Group(group)) = tokens.peek() {
+                    if group.delimiter() == Delimiter::Parenthesis {
+                        return Some(group.stream());
+                    }
+                }
+            }
+            TokenTree::Group(group) => {
+                if let Some(found) = find_call(group.stream(), name) {
+                    return Some(found);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Renders a type without its path prefix, e.g. `Option<U128>` instead of
+/// `Option<::near_sdk::json_types::U128>`.
+fn render_type(ty: &Type) -> String {
+    match ty {
+        Type::Path(p) if p.qself.is_none() => {
+            let segment = match p.path.segments.last() {
+                Some(segment) => segment,
+                None => return String::new(),
+            };
+            let mut rendered = segment.ident.to_string();
+            if let PathArguments::AngleBracketed(a) = &segment.arguments {
+                let args = a
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        GenericArgument::Type(ty) => render_type(ty),
+                        arg => arg.to_token_stream().to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                rendered.push('<');
+                rendered.push_str(&args.join(", "));
+                rendered.push('>');
+            }
+            rendered
+        }
+        Type::Reference(r) => render_type(&r.elem),
+        Type::Tuple(t) => {
+            let elems = t.elems.iter().map(render_type).collect::<Vec<_>>();
+            format!("({})", elems.join(", "))
+        }
+        ty => ty.to_token_stream().to_string(),
+    }
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(manifest),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct ManifestMeta {
+    pub components: PathList,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+/// Components included by the combined derive macros.
+fn expand_alias(component: &str) -> Vec<&str> {
+    match component {
+        "FungibleToken" => vec!["Nep141", "Nep145", "Nep148"],
+        "NonFungibleToken" => vec![
+            "Nep145",
+            "Nep171",
+            "Nep177",
+            "Nep178",
+            "Nep181",
+            "NonFungibleToken",
+        ],
+        component => vec![component],
+    }
+}
+
+pub fn expand(meta: ManifestMeta) -> Result<TokenStream, darling::Error> {
+    let ManifestMeta {
+        components,
+        generics,
+        ident,
+        attrs,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let mut markers = vec![];
+    for path in components.iter() {
+        let component = path
+            .get_ident()
+            .ok_or_else(|| darling::Error::custom("Expected a derive macro name").with_span(path))?
+            .to_string();
+        for component in expand_alias(&component) {
+            let marker = format_ident!("{}", component, span = path.get_ident().unwrap().span());
+            markers.push(quote! {
+                <Self as #me::manifest::HasManifest<#me::manifest::component::#marker>>::MANIFEST
+            });
+        }
+    }
+
+    let expanded = quote! {
+        impl #imp #ident #ty #wher {
+            /// Description of the external methods generated by contract
+            /// tools components.
+            pub const MANIFEST: #me::manifest::ContractManifest = #me::manifest::ContractManifest {
+                version: #me::manifest::ContractManifest::VERSION,
+                components: &[#(#markers),*],
+            };
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #ident #ty #wher {
+            pub fn __tools_manifest(&self) -> #me::manifest::ContractManifest {
+                Self::MANIFEST
+            }
+
+            pub fn supported_standards(&self) -> Vec<#me::manifest::StandardManifest> {
+                Self::MANIFEST.standards()
+            }
+        }
+    };
+
+    with_paused_views(&me, &attrs, expanded)
+}
@@ -2,11 +2,14 @@ use darling::FromDeriveInput;
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 
+use crate::{manifest::with_manifest, mocked_sim::with_sim};
+
 #[derive(Debug, FromDeriveInput)]
 #[darling(attributes(migrate), supports(struct_named))]
 pub struct MigrateMeta {
     pub from: syn::Type,
     pub to: Option<syn::Type>,
+    pub args: Option<syn::Type>,
 
     pub ident: syn::Ident,
     pub generics: syn::Generics,
@@ -22,6 +25,7 @@ pub fn expand(meta: MigrateMeta) -> Result<TokenStream, darling::Error> {
     let MigrateMeta {
         from,
         to,
+        args,
 
         ident,
         generics,
@@ -36,21 +40,42 @@ pub fn expand(meta: MigrateMeta) -> Result<TokenStream, darling::Error> {
         .map(|t| t.to_token_stream())
         .unwrap_or_else(|| quote! { Self }.to_token_stream());
 
-    Ok(quote! {
+    let migrate = match args {
+        Some(args) => quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #ident #ty #wh {
+                #[init(ignore_state)]
+                pub fn migrate(args: #args) -> Self {
+                    let old_state = <#ident as #me::migrate::MigrateController>::deserialize_old_schema();
+                    <#ident as #me::migrate::MigrateHookWithArgs<#args>>::on_migrate_with_args(
+                        old_state,
+                        args,
+                    )
+                }
+            }
+        },
+        None => quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::migrate::MigrateExternal for #ident #ty #wh {
+                #[init(ignore_state)]
+                fn migrate() -> Self {
+                    let old_state = <#ident as #me::migrate::MigrateController>::deserialize_old_schema();
+                    <#ident as #me::migrate::MigrateHook>::on_migrate(
+                        old_state,
+                    )
+                }
+            }
+        },
+    };
+
+    let expanded = quote! {
         impl #imp #me::migrate::MigrateController for #ident #ty #wh {
             type OldSchema = #from;
             type NewSchema = #to;
         }
 
-        #[#near_sdk::near_bindgen]
-        impl #imp #me::migrate::MigrateExternal for #ident #ty #wh {
-            #[init(ignore_state)]
-            fn migrate() -> Self {
-                let old_state = <#ident as #me::migrate::MigrateController>::deserialize_old_schema();
-                <#ident as #me::migrate::MigrateHook>::on_migrate(
-                    old_state,
-                )
-            }
-        }
-    })
+        #migrate
+    };
+
+    Ok(with_sim(&me, with_manifest(&me, "Migrate", expanded)))
 }
@@ -0,0 +1,100 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{FnArg, ImplItem, Item, Pat};
+
+/// Appends `sim_*` wrappers for every method of every impl block in `tokens`
+/// to the output. The wrappers install the given `sim::Env` into near-sdk's
+/// mocked blockchain before calling the method. Enabled by the `mocked-sim`
+/// feature.
+pub fn with_sim(me: &syn::Path, tokens: TokenStream) -> TokenStream {
+    if !cfg!(feature = "mocked-sim") {
+        return tokens;
+    }
+
+    let file: syn::File = match syn::parse2(tokens.clone()) {
+        Ok(file) => file,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let wrappers = file.items.iter().filter_map(|item| match item {
+        Item::Impl(i) if is_near_bindgen(&i.attrs) => Some(wrap_impl(me, i)),
+        _ => None,
+    });
+
+    quote! {
+        #tokens
+        #(#wrappers)*
+    }
+}
+
+pub fn is_near_bindgen(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|a| {
+        a.path()
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "near_bindgen")
+    })
+}
+
+fn wrap_impl(me: &syn::Path, item: &syn::ItemImpl) -> TokenStream {
+    let (imp, _, wher) = item.generics.split_for_impl();
+    let self_ty = &item.self_ty;
+
+    let target = match &item.trait_ {
+        Some((_, path, _)) => quote! { <Self as #path> },
+        None => quote! { Self },
+    };
+
+    let methods = item.items.iter().filter_map(|i| match i {
+        ImplItem::Fn(f) => Some(f),
+        _ => None,
+    });
+
+    let wrappers = methods.map(|f| {
+        let ident = &f.sig.ident;
+        let sim_ident = format_ident!("sim_{}", ident);
+        let output = &f.sig.output;
+
+        let mut receiver = None;
+        let mut params = vec![];
+        let mut args = vec![];
+
+        for input in &f.sig.inputs {
+            match input {
+                FnArg::Receiver(r) => {
+                    let reference = r.reference.as_ref().map(|_| quote! { & });
+                    let mutability = &r.mutability;
+                    receiver = Some(quote! { #reference #mutability self, });
+                    args.push(quote! { self });
+                }
+                FnArg::Typed(t) => {
+                    let ty = &t.ty;
+                    let name = match &*t.pat {
+                        Pat::Ident(p) => p.ident.clone(),
+                        _ => format_ident!("arg{}", args.len()),
+                    };
+                    params.push(quote! { #name: #ty });
+                    args.push(quote! { #name });
+                }
+            }
+        }
+
+        let doc = format!(
+            "Calls `{ident}` with `env` installed as near-sdk's mocked blockchain context. Generated by the `mocked-sim` feature."
+        );
+
+        quote! {
+            #[doc = #doc]
+            #[allow(clippy::too_many_arguments)]
+            pub fn #sim_ident(#receiver env: &#me::sim::Env, #(#params),*) #output {
+                env.with(|| #target::#ident(#(#args),*))
+            }
+        }
+    });
+
+    quote! {
+        impl #imp #self_ty #wher {
+            #(#wrappers)*
+        }
+    }
+}
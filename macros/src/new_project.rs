@@ -0,0 +1,70 @@
+use heck::ToSnakeCase;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{punctuated::Punctuated, Expr, ExprLit, ItemStruct, Lit, LitStr, Path, Token};
+
+pub fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
+    let ItemStruct {
+        attrs,
+        vis,
+        struct_token,
+        ident,
+        generics,
+        fields,
+        semi_token,
+    } = item;
+
+    let mut components: Vec<Path> = vec![];
+    let mut key_prefix = None;
+    let mut rest = vec![];
+
+    for attr in attrs {
+        if attr.path().is_ident("components") {
+            components
+                .extend(attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?);
+        } else if attr.path().is_ident("key_prefix") {
+            match &attr.meta.require_name_value()?.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => key_prefix = Some(s.clone()),
+                value => return Err(syn::Error::new_spanned(value, "Expected a string literal")),
+            }
+        } else {
+            rest.push(attr);
+        }
+    }
+
+    let key_prefix =
+        key_prefix.unwrap_or_else(|| LitStr::new(&ident.to_string().to_snake_case(), ident.span()));
+
+    // Derives without storage do not accept `#[contract_tools(...)]`.
+    let has_storage = components.iter().any(|path| {
+        !path.segments.last().is_some_and(|s| {
+            ["Migrate", "Manifest", "Nep297", "StorageDump", "Upgrade"]
+                .iter()
+                .any(|name| s.ident == name)
+        })
+    });
+    let key_prefix = has_storage.then(|| quote! { #[contract_tools(key_prefix = #key_prefix)] });
+
+    // Bare names refer to this crate's derive macros.
+    let components = components.into_iter().map(|path| match path.get_ident() {
+        Some(ident) => quote! { ::near_sdk_contract_tools::#ident },
+        None => quote! { #path },
+    });
+
+    let (_, _, wher) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[derive(
+            ::near_sdk::borsh::BorshSerialize,
+            ::near_sdk::borsh::BorshDeserialize,
+            ::near_sdk::PanicOnDefault,
+            #(#components),*
+        )]
+        #(#rest)*
+        #key_prefix
+        #[::near_sdk::near_bindgen]
+        #vis #struct_token #ident #generics #wher #fields #semi_token
+    })
+}
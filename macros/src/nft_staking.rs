@@ -0,0 +1,127 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(nft_staking),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct NftStakingMeta {
+    pub storage_key: Option<Expr>,
+    pub epoch: Option<Expr>,
+    pub reward_per_epoch: Option<Expr>,
+    pub cooldown: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: NftStakingMeta) -> Result<TokenStream, darling::Error> {
+    let NftStakingMeta {
+        storage_key,
+        epoch,
+        reward_per_epoch,
+        cooldown,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "NftStaking")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+    let epoch = epoch.map(|epoch| quote! { const EPOCH: u64 = #epoch; });
+    let reward_per_epoch =
+        reward_per_epoch.map(|reward| quote! { const REWARD_PER_EPOCH: u128 = #reward; });
+    let cooldown = cooldown.map(|cooldown| quote! { const COOLDOWN: u64 = #cooldown; });
+
+    let expanded = quote! {
+        impl #imp #me::staking::nft::NftStakingInternal for #ident #ty #wher {
+            #epoch
+            #reward_per_epoch
+            #cooldown
+
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::staking::nft::NftStakingExternal for #ident #ty #wher {
+            #[payable]
+            fn nft_stake(&mut self, token_id: #me::standard::nep171::TokenId) {
+                #near_sdk::assert_one_yocto();
+                let owner_id = #near_sdk::env::predecessor_account_id();
+
+                #me::staking::nft::NftStaking::stake(self, &owner_id, &token_id)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+
+            #[payable]
+            fn nft_unstake(
+                &mut self,
+                token_id: #me::standard::nep171::TokenId,
+            ) -> #near_sdk::json_types::U64 {
+                #near_sdk::assert_one_yocto();
+                let owner_id = #near_sdk::env::predecessor_account_id();
+
+                #me::staking::nft::NftStaking::unstake(self, &owner_id, &token_id)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                    .into()
+            }
+
+            #[payable]
+            fn nft_claim_staking_rewards(
+                &mut self,
+                token_id: #me::standard::nep171::TokenId,
+            ) -> #near_sdk::json_types::U128 {
+                #near_sdk::assert_one_yocto();
+                let owner_id = #near_sdk::env::predecessor_account_id();
+
+                #me::staking::nft::NftStaking::claim_rewards(self, &owner_id, &token_id)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                    .into()
+            }
+
+            fn nft_stake_info(
+                &self,
+                token_id: #me::standard::nep171::TokenId,
+            ) -> Option<#me::staking::nft::StakeInfo> {
+                <Self as #me::staking::nft::NftStaking>::stake_info(&token_id)
+            }
+
+            fn nft_pending_staking_rewards(
+                &self,
+                token_id: #me::standard::nep171::TokenId,
+            ) -> #near_sdk::json_types::U128 {
+                <Self as #me::staking::nft::NftStaking>::pending_rewards(&token_id).into()
+            }
+
+            fn nft_staking_reward_balance(
+                &self,
+                account_id: #near_sdk::AccountId,
+            ) -> #near_sdk::json_types::U128 {
+                <Self as #me::staking::nft::NftStaking>::reward_balance(&account_id).into()
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "NftStaking", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
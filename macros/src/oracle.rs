@@ -0,0 +1,89 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(oracle),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct OracleMeta {
+    pub storage_key: Option<Expr>,
+    pub role: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: OracleMeta) -> Result<TokenStream, darling::Error> {
+    let OracleMeta {
+        storage_key,
+        role,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Oracle")?;
+
+    let require_authorized = match role {
+        Some(role) => quote! {
+            if !<Self as #me::rbac::Rbac>::has_role(
+                &#near_sdk::env::predecessor_account_id(),
+                &#role,
+            ) {
+                <Self as #me::oracle::Oracle>::require_oracle();
+            }
+        },
+        None => quote! {
+            <Self as #me::oracle::Oracle>::require_oracle();
+        },
+    };
+
+    let expanded = quote! {
+        impl #imp #me::oracle::OracleInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::oracle::OracleExternal for #ident #ty #wher {
+            fn oracle_get_account_id(&self) -> Option<#near_sdk::AccountId> {
+                #me::oracle::Oracle::oracle_account_id(self)
+            }
+
+            fn oracle_is_initialized(&self) -> bool {
+                #me::oracle::Oracle::is_initialized(self)
+            }
+
+            fn oracle_get_price(&self, asset_id: String) -> Option<#me::oracle::PriceEntry> {
+                #me::oracle::Oracle::get_price(self, &asset_id)
+            }
+
+            fn oracle_on_price_update(&mut self, prices: Vec<#me::oracle::AssetPrice>) {
+                #require_authorized
+                #me::oracle::Oracle::record_prices(self, &prices);
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Oracle", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
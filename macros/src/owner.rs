@@ -3,13 +3,25 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version,
+};
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(owner), supports(struct_named))]
+#[darling(
+    attributes(owner),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct OwnerMeta {
     pub storage_key: Option<Expr>,
+    #[darling(default)]
+    pub ownable2step: bool,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -21,7 +33,9 @@ pub struct OwnerMeta {
 pub fn expand(meta: OwnerMeta) -> Result<TokenStream, darling::Error> {
     let OwnerMeta {
         storage_key,
+        ownable2step,
         ident,
+        attrs,
         generics,
 
         me,
@@ -30,15 +44,42 @@ pub fn expand(meta: OwnerMeta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
+    let root = crate::root_fn(&me, storage_key, &attrs, "Owner")?;
+
+    let ownable2step = ownable2step.then(|| {
         quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::owner::Ownable2StepExternal for #ident #ty #wher {
+                fn owner(&self) -> Option<#near_sdk::AccountId> {
+                    <Self as #me::owner::OwnerInternal>::slot_owner().read()
+                }
+
+                fn pending_owner(&self) -> Option<#near_sdk::AccountId> {
+                    <Self as #me::owner::OwnerInternal>::slot_proposed_owner().read()
+                }
+
+                #[payable]
+                fn transfer_ownership(&mut self, new_owner: #near_sdk::AccountId) {
+                    #near_sdk::assert_one_yocto();
+                    #me::owner::Ownable2Step::transfer_ownership(self, new_owner);
+                }
+
+                #[payable]
+                fn accept_ownership(&mut self) {
+                    #near_sdk::assert_one_yocto();
+                    #me::owner::Ownable2Step::accept_ownership(self);
+                }
+
+                #[payable]
+                fn renounce_ownership(&mut self) {
+                    #near_sdk::assert_one_yocto();
+                    #me::owner::Ownable2Step::renounce_ownership(self);
+                }
             }
         }
     });
 
-    Ok(quote! {
+    let expanded = quote! {
         impl #imp #me::owner::OwnerInternal for #ident #ty #wher {
             #root
         }
@@ -53,6 +94,10 @@ pub fn expand(meta: OwnerMeta) -> Result<TokenStream, darling::Error> {
                 <Self as #me::owner::OwnerInternal>::slot_proposed_owner().read()
             }
 
+            fn own_is_initialized(&self) -> bool {
+                #me::owner::Owner::is_initialized(self)
+            }
+
             #[payable]
             fn own_renounce_owner(&mut self) {
                 #near_sdk::assert_one_yocto();
@@ -71,5 +116,25 @@ pub fn expand(meta: OwnerMeta) -> Result<TokenStream, darling::Error> {
                 #me::owner::Owner::accept_owner(self);
             }
         }
-    })
+
+        #ownable2step
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Owner",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::owner },
+                    quote! { #me::owner::OwnerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
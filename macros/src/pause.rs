@@ -3,13 +3,23 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version,
+};
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(pause), supports(struct_named))]
+#[darling(
+    attributes(pause),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct PauseMeta {
     pub storage_key: Option<Expr>,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -22,6 +32,7 @@ pub fn expand(meta: PauseMeta) -> Result<TokenStream, darling::Error> {
     let PauseMeta {
         storage_key,
         ident,
+        attrs,
         generics,
 
         me,
@@ -30,15 +41,9 @@ pub fn expand(meta: PauseMeta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
-        quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::new(#storage_key)
-            }
-        }
-    });
+    let root = crate::root_fn(&me, storage_key, &attrs, "Pause")?;
 
-    Ok(quote! {
+    let expanded = quote! {
         impl #imp #me::pause::PauseInternal for #ident #ty #wher {
             #root
         }
@@ -49,5 +54,23 @@ pub fn expand(meta: PauseMeta) -> Result<TokenStream, darling::Error> {
                 <Self as #me::pause::Pause>::is_paused()
             }
         }
-    })
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Pause",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::pause },
+                    quote! { #me::pause::PauseInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
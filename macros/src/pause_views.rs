@@ -0,0 +1,197 @@
+use darling::{ast::NestedMeta, util::PathList, FromMeta};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{FnArg, ImplItem, Item};
+
+use crate::{mocked_sim::is_near_bindgen, ContractToolsMeta};
+
+/// The generated view methods that `#[contract_tools(pause_views)]` disables
+/// while the contract is paused.
+#[derive(Debug, Clone)]
+pub enum PauseViews {
+    /// `#[contract_tools(pause_views)]`: every generated view method, except
+    /// the pause status itself.
+    All,
+    /// `#[contract_tools(pause_views(ft_balance_of, ...))]`: only the listed
+    /// methods.
+    Only(Vec<syn::Ident>),
+}
+
+impl FromMeta for PauseViews {
+    fn from_word() -> darling::Result<Self> {
+        Ok(Self::All)
+    }
+
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        let mut errors = darling::Error::accumulator();
+
+        let idents = PathList::from_list(items)?
+            .iter()
+            .filter_map(|path| {
+                let ident = errors.handle(path.require_ident().cloned().map_err(Into::into))?;
+                if !GENERATED_VIEWS.iter().any(|view| ident == view) {
+                    errors.push(
+                        darling::Error::custom(format!(
+                            "`{ident}` is not a view method generated by the derive macros"
+                        ))
+                        .with_span(&ident),
+                    );
+                    return None;
+                }
+                Some(ident)
+            })
+            .collect();
+
+        errors.finish_with(Self::Only(idents))
+    }
+}
+
+/// The view methods generated by the derive macros that
+/// `#[contract_tools(pause_views(...))]` may select. `paus_is_paused` is not
+/// selectable.
+const GENERATED_VIEWS: &[&str] = &[
+    "__tools_manifest",
+    "__tools_storage_dump",
+    "ban_count",
+    "ban_is_banned",
+    "busy_pending_count",
+    "config_get",
+    "contract_stats",
+    "fractionalize_vault",
+    "ft_allowance",
+    "ft_allowances_for",
+    "ft_balance_of",
+    "ft_balance_of_human",
+    "ft_balance_proof",
+    "ft_balances_commitment",
+    "ft_bridge_caps",
+    "ft_bridge_epoch_volume",
+    "ft_holder_count",
+    "ft_metadata",
+    "ft_metadata_version",
+    "ft_min_transfer",
+    "ft_pending_transfer",
+    "ft_subscription",
+    "ft_subscription_remaining",
+    "ft_total_supply",
+    "ft_total_supply_human",
+    "ft_transfer_check",
+    "gas_config_get",
+    "multisig_find_request_by_key",
+    "multisig_get_request_summary",
+    "multisig_is_initialized",
+    "multisig_list_request_summaries",
+    "multisig_member_get_request",
+    "multisig_member_is_initialized",
+    "multisig_members",
+    "multisig_threshold",
+    "nft_is_approved",
+    "nft_is_metadata_frozen",
+    "nft_is_token_hidden",
+    "nft_metadata",
+    "nft_payout",
+    "nft_pending_staking_rewards",
+    "nft_stake_info",
+    "nft_staking_reward_balance",
+    "nft_supply_for_owner",
+    "nft_token",
+    "nft_token_owners",
+    "nft_tokens",
+    "nft_tokens_for_owner",
+    "nft_total_supply",
+    "nft_transfer_check",
+    "oracle_get_account_id",
+    "oracle_get_price",
+    "oracle_is_initialized",
+    "own_get_owner",
+    "own_get_proposed_owner",
+    "own_is_initialized",
+    "owner",
+    "pending_owner",
+    "raffle_entry_count",
+    "raffle_get_status",
+    "raffle_winners",
+    "rbac_actions_is_initialized",
+    "rbac_get_request",
+    "rbac_get_request_summary",
+    "rbac_list_request_summaries",
+    "rbac_roles_of",
+    "recovery_get_available_at",
+    "recovery_get_config",
+    "recovery_get_last_heartbeat",
+    "replay_is_processed",
+    "replay_processed_count",
+    "sale_get_listing",
+    "storage_balance_bounds",
+    "storage_balance_of",
+    "storage_report",
+    "supported_standards",
+    "treasury_available_balance",
+    "treasury_min_reserve",
+    "upgrade_history",
+];
+
+impl PauseViews {
+    fn includes(&self, ident: &syn::Ident) -> bool {
+        match self {
+            // The pause status must stay readable while paused.
+            Self::All => ident != "paus_is_paused",
+            Self::Only(idents) => idents.contains(ident),
+        }
+    }
+}
+
+/// If the contract opts in with `#[contract_tools(pause_views)]`, rejects
+/// calls to the selected external view methods in `tokens` while the
+/// contract is paused.
+pub fn with_paused_views(
+    me: &syn::Path,
+    attrs: &[syn::Attribute],
+    tokens: TokenStream,
+) -> darling::Result<TokenStream> {
+    let mut pause_views = None;
+    for attr in attrs {
+        pause_views = ContractToolsMeta::from_meta(&attr.meta)?
+            .pause_views
+            .or(pause_views);
+    }
+
+    let pause_views = match pause_views {
+        Some(pause_views) => pause_views,
+        None => return Ok(tokens),
+    };
+
+    let mut file: syn::File = syn::parse2(tokens)?;
+
+    for item in &mut file.items {
+        let item = match item {
+            Item::Impl(i) if is_near_bindgen(&i.attrs) => i,
+            _ => continue,
+        };
+
+        for f in &mut item.items {
+            let f = match f {
+                ImplItem::Fn(f) => f,
+                _ => continue,
+            };
+
+            let view = f.sig.inputs.iter().any(|input| {
+                matches!(input, FnArg::Receiver(r) if r.reference.is_some() && r.mutability.is_none())
+            });
+
+            if !view || !pause_views.includes(&f.sig.ident) {
+                continue;
+            }
+
+            let block = &f.block;
+            f.block = syn::parse_quote! {
+                {
+                    <Self as #me::pause::Pause>::require_unpaused();
+                    #block
+                }
+            };
+        }
+    }
+
+    Ok(quote! { #file })
+}
@@ -0,0 +1,68 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(raffle),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct RaffleMeta {
+    pub storage_key: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: RaffleMeta) -> Result<TokenStream, darling::Error> {
+    let RaffleMeta {
+        storage_key,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Raffle")?;
+
+    let expanded = quote! {
+        impl #imp #me::raffle::RaffleInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::raffle::RaffleExternal for #ident #ty #wher {
+            fn raffle_get_status(&self) -> #me::raffle::RaffleStatus {
+                #me::raffle::Raffle::raffle_status(self)
+            }
+
+            fn raffle_entry_count(&self) -> u32 {
+                #me::raffle::Raffle::entry_count(self)
+            }
+
+            fn raffle_winners(&self) -> Vec<#near_sdk::AccountId> {
+                #me::raffle::Raffle::winners(self)
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Raffle", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
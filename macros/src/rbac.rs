@@ -1,49 +1,99 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
+use crate::{
+    manifest::with_manifest, mocked_sim::with_sim, pause_views::with_paused_views,
+    storage_version::with_storage_version,
+};
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(rbac), supports(struct_named))]
+#[darling(
+    attributes(rbac),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct RbacMeta {
     pub storage_key: Option<Expr>,
     pub roles: Expr,
+    pub roles_of_view: Flag,
 
     // darling
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
     pub generics: syn::Generics,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
     pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
 }
 
 pub fn expand(meta: RbacMeta) -> Result<TokenStream, darling::Error> {
     let RbacMeta {
         storage_key,
         roles,
+        roles_of_view,
 
         ident,
+        attrs,
         generics,
 
         me,
+        near_sdk,
     } = meta;
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
+    let root = crate::root_fn(&me, storage_key, &attrs, "Rbac")?;
+
+    let roles_of_view = roles_of_view.is_present().then(|| {
         quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::new(#storage_key)
+            #[#near_sdk::near_bindgen]
+            impl #imp #ident #ty #wher {
+                pub fn rbac_roles_of(
+                    &self,
+                    account_id: #near_sdk::AccountId,
+                    offset: Option<u32>,
+                    limit: Option<u32>,
+                ) -> Vec<#roles> {
+                    <Self as #me::rbac::Rbac>::roles_of(
+                        &account_id,
+                        offset.unwrap_or(0),
+                        limit.unwrap_or(u32::MAX),
+                    )
+                }
             }
         }
     });
 
-    Ok(quote! {
+    let expanded = quote! {
         impl #imp #me::rbac::RbacInternal for #ident #ty #wher {
             type Role = #roles;
 
             #root
         }
-    })
+
+        #roles_of_view
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Rbac",
+            with_paused_views(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::rbac },
+                    quote! { #me::rbac::RbacInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
@@ -0,0 +1,103 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Expr, Type};
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(recovery),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct RecoveryMeta {
+    pub storage_key: Option<Expr>,
+    pub action: Option<Type>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: RecoveryMeta) -> Result<TokenStream, darling::Error> {
+    let RecoveryMeta {
+        storage_key,
+        action,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Recovery")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let action = action.unwrap_or_else(|| parse_quote! { #me::recovery::ClaimOwnership });
+
+    let expanded = quote! {
+        impl #imp #me::recovery::RecoveryInternal for #ident #ty #wher {
+            type Action = #action;
+
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::recovery::RecoveryExternal for #ident #ty #wher {
+            fn recovery_get_config(&self) -> Option<#me::recovery::RecoveryConfig> {
+                <Self as #me::recovery::Recovery>::recovery_config(self)
+            }
+
+            fn recovery_get_last_heartbeat(&self) -> Option<#near_sdk::json_types::U64> {
+                <Self as #me::recovery::Recovery>::last_heartbeat(self).map(Into::into)
+            }
+
+            fn recovery_get_available_at(&self) -> Option<#near_sdk::json_types::U64> {
+                <Self as #me::recovery::Recovery>::recovery_available_at(self).map(Into::into)
+            }
+
+            #[payable]
+            fn recovery_set_config(&mut self, config: Option<#me::recovery::RecoveryConfig>) {
+                #near_sdk::assert_one_yocto();
+                <Self as #me::owner::Owner>::require_owner();
+
+                <Self as #me::recovery::Recovery>::set_recovery_config(self, config)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+
+            #[payable]
+            fn recovery_heartbeat(&mut self) {
+                #near_sdk::assert_one_yocto();
+                <Self as #me::owner::Owner>::require_owner();
+
+                <Self as #me::recovery::Recovery>::heartbeat(self);
+            }
+
+            #[payable]
+            fn recovery_claim(&mut self) {
+                #near_sdk::assert_one_yocto();
+
+                <Self as #me::recovery::Recovery>::recover(
+                    self,
+                    &#near_sdk::env::predecessor_account_id(),
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Recovery", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
@@ -0,0 +1,79 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(replay_guard),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct ReplayGuardMeta {
+    pub storage_key: Option<Expr>,
+    pub ttl: Option<Expr>,
+    pub cleanup_limit: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: ReplayGuardMeta) -> Result<TokenStream, darling::Error> {
+    let ReplayGuardMeta {
+        storage_key,
+        ttl,
+        cleanup_limit,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "ReplayGuard")?;
+    let ttl = ttl.map(|ttl| quote! { const TTL: u64 = #ttl; });
+    let cleanup_limit = cleanup_limit.map(|limit| quote! { const CLEANUP_LIMIT: u32 = #limit; });
+
+    let expanded = quote! {
+        impl #imp #me::replay::ReplayGuardInternal for #ident #ty #wher {
+            #ttl
+            #cleanup_limit
+
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::replay::ReplayGuardExternal for #ident #ty #wher {
+            fn replay_is_processed(&self, sender_id: #near_sdk::AccountId, nonce: String) -> bool {
+                <Self as #me::replay::ReplayGuard>::is_processed(
+                    &#me::replay::ReplayKey::new(&sender_id, &nonce),
+                )
+            }
+
+            fn replay_processed_count(&self) -> #near_sdk::json_types::U64 {
+                <Self as #me::replay::ReplayGuard>::processed_count().into()
+            }
+
+            fn replay_cleanup(&mut self, limit: u32) -> u32 {
+                #me::replay::ReplayGuard::cleanup_processed(self, limit)
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "ReplayGuard", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
@@ -0,0 +1,132 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, Type};
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim, unitify};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(sale),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct SaleMeta {
+    pub storage_key: Option<Expr>,
+    pub royalties: Option<Type>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: SaleMeta) -> Result<TokenStream, darling::Error> {
+    let SaleMeta {
+        storage_key,
+        royalties,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Sale")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let royalties = unitify(royalties);
+
+    let expanded = quote! {
+        impl #imp #me::sale::SaleInternal for #ident #ty #wher {
+            type Royalties = #royalties;
+
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::sale::SaleExternal for #ident #ty #wher {
+            fn sale_get_listing(
+                &self,
+                token_id: #me::standard::nep171::TokenId,
+            ) -> Option<#me::sale::SaleListing> {
+                #me::sale::Sale::listing(self, &token_id)
+            }
+
+            #[payable]
+            fn sale_list(
+                &mut self,
+                token_id: #me::standard::nep171::TokenId,
+                price: #near_sdk::json_types::U128,
+                currency: #me::sale::SaleCurrency,
+            ) {
+                #near_sdk::assert_one_yocto();
+
+                #me::sale::Sale::list(
+                    self,
+                    &#near_sdk::env::predecessor_account_id(),
+                    &token_id,
+                    price.into(),
+                    currency,
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+
+            #[payable]
+            fn sale_delist(&mut self, token_id: #me::standard::nep171::TokenId) {
+                #near_sdk::assert_one_yocto();
+
+                #me::sale::Sale::delist(
+                    self,
+                    &#near_sdk::env::predecessor_account_id(),
+                    &token_id,
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+
+            #[payable]
+            fn sale_buy(
+                &mut self,
+                token_id: #me::standard::nep171::TokenId,
+            ) -> #me::sale::Payout {
+                #me::sale::Sale::buy_with_near(
+                    self,
+                    &#near_sdk::env::predecessor_account_id(),
+                    &token_id,
+                    #near_sdk::env::attached_deposit(),
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+            }
+
+            #[payable]
+            fn sale_buy_ft(
+                &mut self,
+                token_id: #me::standard::nep171::TokenId,
+                max_price: #near_sdk::json_types::U128,
+            ) -> #me::sale::Payout {
+                #near_sdk::assert_one_yocto();
+
+                #me::sale::Sale::buy_with_ft(
+                    self,
+                    &#near_sdk::env::predecessor_account_id(),
+                    &token_id,
+                    max_price.into(),
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Sale", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
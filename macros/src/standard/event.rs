@@ -36,6 +36,10 @@ pub fn event_attribute(
         macros,
     } = attr;
 
+    if !matches!(item, Item::Enum(_) | Item::Struct(_)) {
+        return Err(darling::Error::custom("Expected an enum or a struct").with_span(&item));
+    }
+
     let serde_untagged = matches!(item, Item::Enum(_)).then_some(quote! { #[serde(untagged)] });
 
     let default_rename = if rename.is_none() && rename_all.is_none() {
@@ -6,7 +6,11 @@ use syn::{Expr, Type};
 use super::{nep141, nep145, nep148};
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(fungible_token), supports(struct_named))]
+#[darling(
+    attributes(fungible_token),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct FungibleTokenMeta {
     // NEP-141 fields
     pub core_storage_key: Option<Expr>,
@@ -14,17 +18,45 @@ pub struct FungibleTokenMeta {
     pub mint_hook: Option<Type>,
     pub transfer_hook: Option<Type>,
     pub burn_hook: Option<Type>,
+    pub all_hooks_first: darling::util::Flag,
+    #[darling(multiple)]
+    pub extra: Vec<Type>,
+    #[darling(default)]
+    pub errors: nep141::ErrorStrategy,
+    pub holder_count: darling::util::Flag,
+    pub remove_zero_balances: darling::util::Flag,
+    pub zero_balance_sweep_role: Option<Expr>,
+    pub transfer_check: darling::util::Flag,
+    pub gas_config: darling::util::Flag,
+    pub allowance_views: darling::util::Flag,
+    pub busy_guard: darling::util::Flag,
+    pub pending_transfers: darling::util::Flag,
+    pub balance_commitment: darling::util::Flag,
+    pub dust_role: Option<Expr>,
+    pub batch_mint_role: Option<Expr>,
+    pub batch_burn_role: Option<Expr>,
 
     // NEP-148 fields
     pub metadata_storage_key: Option<Expr>,
+    pub human_views: darling::util::Flag,
+    pub strict_reference: darling::util::Flag,
 
     // NEP-145 fields
     pub storage_management_storage_key: Option<Expr>,
     pub force_unregister_hook: Option<Type>,
+    pub storage_register_hook: Option<Type>,
+    pub storage_byte_cost: Option<Type>,
+    pub no_storage_events: darling::util::Flag,
+    pub storage_withdraw_to: darling::util::Flag,
+    pub storage_owner_sweep: darling::util::Flag,
+    pub storage_treasury: darling::util::Flag,
+    pub storage_set_bounds: darling::util::Flag,
+    pub storage_bounds_role: Option<Expr>,
 
     // darling
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -40,14 +72,40 @@ pub fn expand(meta: FungibleTokenMeta) -> Result<TokenStream, darling::Error> {
         mint_hook,
         transfer_hook,
         burn_hook,
+        all_hooks_first,
+        extra,
+        errors,
+        holder_count,
+        remove_zero_balances,
+        zero_balance_sweep_role,
+        transfer_check,
+        gas_config,
+        allowance_views,
+        busy_guard,
+        pending_transfers,
+        balance_commitment,
+        dust_role,
+        batch_mint_role,
+        batch_burn_role,
 
         metadata_storage_key,
+        human_views,
+        strict_reference,
 
         storage_management_storage_key,
         force_unregister_hook,
+        storage_register_hook,
+        storage_byte_cost,
+        no_storage_events,
+        storage_withdraw_to,
+        storage_owner_sweep,
+        storage_treasury,
+        storage_set_bounds,
+        storage_bounds_role,
 
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
@@ -56,20 +114,60 @@ pub fn expand(meta: FungibleTokenMeta) -> Result<TokenStream, darling::Error> {
     let all_hooks_or_unit = all_hooks
         .clone()
         .unwrap_or_else(|| syn::parse_quote! { () });
+    // Extra standards run inside storage accounting, so the storage they use
+    // is charged like that of the built-in standards.
+    let extra = crate::nest(&extra);
+
+    // The storage accounting hook always runs last, so with `all_hooks_first`,
+    // `all_hooks` is folded into each action-specific hook instead of being
+    // reordered around it.
+    let (nep141_all_hooks, mint_hook, transfer_hook, burn_hook) = if all_hooks_first.is_present() {
+        let first = |h: Option<Type>| {
+            let h = crate::unitify(h);
+            Some(syn::parse_quote! { (#all_hooks_or_unit, #h) })
+        };
+        (
+            syn::parse_quote! { (#me::standard::nep145::hooks::Nep141StorageAccountingHook, #extra) },
+            first(mint_hook),
+            first(transfer_hook),
+            first(burn_hook),
+        )
+    } else {
+        (
+            syn::parse_quote! { (#all_hooks_or_unit, (#me::standard::nep145::hooks::Nep141StorageAccountingHook, #extra)) },
+            mint_hook,
+            transfer_hook,
+            burn_hook,
+        )
+    };
     let force_unregister_hook_or_unit =
         force_unregister_hook.unwrap_or_else(|| syn::parse_quote! { () });
 
     let expand_nep141 = nep141::expand(nep141::Nep141Meta {
         storage_key: core_storage_key,
-        all_hooks: Some(
-            syn::parse_quote! { (#all_hooks_or_unit, #me::standard::nep145::hooks::Nep141StorageAccountingHook) },
-        ),
+        all_hooks: Some(nep141_all_hooks),
         mint_hook,
         transfer_hook,
         burn_hook,
+        all_hooks_first: darling::util::Flag::default(),
+        errors,
+        holder_count,
+        remove_zero_balances,
+        zero_balance_sweep_role,
+        zero_balance_sweep_storage_refund: true,
+        transfer_check,
+        gas_config,
+        allowance_views,
+        busy_guard,
+        pending_transfers,
+        balance_commitment,
+        dust_role,
+        batch_mint_role,
+        batch_burn_role,
 
         generics: generics.clone(),
         ident: ident.clone(),
+        attrs: attrs.clone(),
 
         me: me.clone(),
         near_sdk: near_sdk.clone(),
@@ -81,8 +179,17 @@ pub fn expand(meta: FungibleTokenMeta) -> Result<TokenStream, darling::Error> {
         force_unregister_hook: Some(
             syn::parse_quote! { (#force_unregister_hook_or_unit, #me::standard::nep141::hooks::BurnNep141OnForceUnregisterHook) },
         ),
+        register_hook: storage_register_hook,
+        byte_cost: storage_byte_cost,
+        no_events: no_storage_events,
+        withdraw_to: storage_withdraw_to,
+        owner_sweep: storage_owner_sweep,
+        treasury: storage_treasury,
+        set_bounds: storage_set_bounds,
+        bounds_role: storage_bounds_role,
         generics: generics.clone(),
         ident: ident.clone(),
+        attrs: attrs.clone(),
 
         me: me.clone(),
         near_sdk: near_sdk.clone(),
@@ -90,8 +197,11 @@ pub fn expand(meta: FungibleTokenMeta) -> Result<TokenStream, darling::Error> {
 
     let expand_nep148 = nep148::expand(nep148::Nep148Meta {
         storage_key: metadata_storage_key,
+        human_views,
+        strict_reference,
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
@@ -1,18 +1,66 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput, FromMeta};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{Expr, Type};
 
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ErrorStrategy {
+    #[default]
+    Panic,
+    Result,
+}
+
+impl FromMeta for ErrorStrategy {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "panic" => Ok(Self::Panic),
+            "result" => Ok(Self::Result),
+            _ => Err(darling::Error::custom(format!(
+                r#"Invalid value "{value}", expected "panic" or "result""#
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(nep141), supports(struct_named))]
+#[darling(
+    attributes(nep141),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct Nep141Meta {
     pub storage_key: Option<Expr>,
     pub all_hooks: Option<Type>,
     pub mint_hook: Option<Type>,
     pub transfer_hook: Option<Type>,
     pub burn_hook: Option<Type>,
+    pub all_hooks_first: Flag,
+    #[darling(default)]
+    pub errors: ErrorStrategy,
+    pub holder_count: Flag,
+    pub remove_zero_balances: Flag,
+    pub zero_balance_sweep_role: Option<Expr>,
+    /// Set by `FungibleToken` to credit the storage released by
+    /// `ft_sweep_zero_balances` to NEP-145 storage balances.
+    #[darling(skip)]
+    pub zero_balance_sweep_storage_refund: bool,
+    pub transfer_check: Flag,
+    pub allowance_views: Flag,
+    pub busy_guard: Flag,
+    pub gas_config: Flag,
+    pub pending_transfers: Flag,
+    pub balance_commitment: Flag,
+    pub dust_role: Option<Expr>,
+    pub batch_mint_role: Option<Expr>,
+    pub batch_burn_role: Option<Expr>,
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -28,8 +76,24 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
         mint_hook,
         transfer_hook,
         burn_hook,
+        all_hooks_first,
+        errors,
+        holder_count,
+        remove_zero_balances,
+        zero_balance_sweep_role,
+        zero_balance_sweep_storage_refund,
+        transfer_check,
+        allowance_views,
+        busy_guard,
+        gas_config,
+        pending_transfers,
+        balance_commitment,
+        dust_role,
+        batch_mint_role,
+        batch_burn_role,
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
@@ -37,13 +101,8 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
-        quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
-            }
-        }
-    });
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep141")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
 
     let mint_hook = mint_hook
         .map(|h| quote! { #h })
@@ -59,29 +118,485 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
         .map(|h| quote! { #h })
         .unwrap_or_else(|| quote! { () });
 
-    Ok(quote! {
+    let mint_hook = crate::compose_hooks(&mint_hook, &default_hook, all_hooks_first);
+    let transfer_hook = crate::compose_hooks(&transfer_hook, &default_hook, all_hooks_first);
+    let burn_hook = crate::compose_hooks(&burn_hook, &default_hook, all_hooks_first);
+
+    // The commitment hook runs last, so it commits the balances after every
+    // other hook has run.
+    let (mint_hook, transfer_hook, burn_hook) = if balance_commitment.is_present() {
+        let commit = quote! { #me::standard::nep141::commitment::Nep141CommitmentHook };
+        (
+            quote! { (#mint_hook, #commit) },
+            quote! { (#transfer_hook, #commit) },
+            quote! { (#burn_hook, #commit) },
+        )
+    } else {
+        (mint_hook, transfer_hook, burn_hook)
+    };
+
+    // With `errors = "result"`, the external methods are generated as inherent
+    // methods returning `Result`, since the `Nep141` trait signatures are
+    // fixed.
+    let (nep141_impl, vis, handle_result, ft_transfer_output, ft_transfer_call_output) =
+        match errors {
+            ErrorStrategy::Panic => (
+                quote! { #me::standard::nep141::Nep141 for },
+                quote! {},
+                quote! {},
+                quote! {},
+                quote! { -> #near_sdk::Promise },
+            ),
+            ErrorStrategy::Result => (
+                quote! {},
+                quote! { pub },
+                quote! { #[handle_result] },
+                quote! { -> Result<(), String> },
+                quote! { -> Result<#near_sdk::Promise, String> },
+            ),
+        };
+
+    let (on_transfer_error, ok_unit, ok_promise) = match errors {
+        ErrorStrategy::Panic => (
+            quote! { .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e)) },
+            quote! {},
+            quote! { promise },
+        ),
+        ErrorStrategy::Result => (
+            quote! { .map_err(|e| e.to_string())? },
+            quote! { Ok(()) },
+            quote! { Ok(promise) },
+        ),
+    };
+
+    // With `gas_config`, the gas amounts are read from `GasConfig` storage
+    // instead of the compile-time defaults.
+    let (require_transfer_call_gas, resolve_transfer_gas) = if gas_config.is_present() {
+        (
+            quote! {
+                <Self as #me::gas::GasConfig>::require_gas_for(#me::gas::GasKey::FtTransferCall)
+            },
+            quote! {
+                <Self as #me::gas::GasConfig>::gas_for(#me::gas::GasKey::FtResolveTransfer)
+            },
+        )
+    } else {
+        (
+            quote! {{
+                #me::utils::preflight::require_gas(#me::standard::nep141::GAS_FOR_FT_TRANSFER_CALL);
+                #me::standard::nep141::GAS_FOR_FT_TRANSFER_CALL
+            }},
+            quote! { #me::standard::nep141::GAS_FOR_RESOLVE_TRANSFER },
+        )
+    };
+
+    let holder_count = holder_count.is_present().then(|| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::Nep141HolderCount for #ident #ty #wher {
+                fn ft_holder_count(&self) -> #near_sdk::json_types::U64 {
+                    #me::standard::nep141::Nep141Controller::holder_count(self).into()
+                }
+            }
+        }
+    });
+
+    let remove_zero_balances = remove_zero_balances.is_present().then(|| {
+        quote! {
+            const REMOVE_ZERO_BALANCES: bool = true;
+        }
+    });
+
+    let zero_balance_sweep = zero_balance_sweep_role.map(|role| {
+        let sweep = if zero_balance_sweep_storage_refund {
+            quote! {
+                #me::standard::nep141::hooks::sweep_zero_balances_with_storage_refund(
+                    self,
+                    &account_ids,
+                )
+            }
+        } else {
+            quote! {
+                #me::standard::nep141::Nep141Controller::sweep_zero_balances(self, &account_ids)
+            }
+        };
+
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::Nep141ZeroBalanceSweep for #ident #ty #wher {
+                #[payable]
+                fn ft_sweep_zero_balances(
+                    &mut self,
+                    account_ids: Vec<#near_sdk::AccountId>,
+                ) -> u32 {
+                    #near_sdk::assert_one_yocto();
+                    <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                    #sweep
+                }
+            }
+        }
+    });
+
+    let batch_mint = batch_mint_role.map(|role| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::Nep141BatchMint for #ident #ty #wher {
+                #[payable]
+                fn ft_batch_mint(
+                    &mut self,
+                    mints: Vec<(#near_sdk::AccountId, #near_sdk::json_types::U128)>,
+                    memo: Option<String>,
+                ) {
+                    #near_sdk::assert_one_yocto();
+                    <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                    let mints = mints
+                        .into_iter()
+                        .map(|(account_id, amount)| (account_id, amount.0))
+                        .collect::<Vec<_>>();
+
+                    #me::standard::nep141::Nep141Controller::mint_batch(
+                        self,
+                        &mints,
+                        memo.as_deref(),
+                        #me::hook::ActionOrigin::External("ft_batch_mint"),
+                    )
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+                }
+            }
+        }
+    });
+
+    let batch_burn = batch_burn_role.map(|role| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::Nep141BatchBurn for #ident #ty #wher {
+                #[payable]
+                fn ft_batch_burn(
+                    &mut self,
+                    burns: Vec<(#near_sdk::AccountId, #near_sdk::json_types::U128)>,
+                    memo: Option<String>,
+                ) {
+                    #near_sdk::assert_one_yocto();
+                    <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                    let burns = burns
+                        .into_iter()
+                        .map(|(account_id, amount)| (account_id, amount.0))
+                        .collect::<Vec<_>>();
+
+                    #me::standard::nep141::Nep141Controller::burn_batch(
+                        self,
+                        &burns,
+                        memo.as_deref(),
+                        #me::hook::ActionOrigin::External("ft_batch_burn"),
+                    )
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+                }
+            }
+        }
+    });
+
+    let transfer_check = transfer_check.is_present().then(|| {
+        // Mirrors the minimum transfer amount check of `ft_transfer`.
+        let check_dust = dust_role.is_some().then(|| {
+            quote! {
+                if let Err(e) =
+                    #me::standard::nep141::dust::Nep141Dust::check_transfer_amount(self, amount.0)
+                {
+                    return Some(e.to_string());
+                }
+            }
+        });
+
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::Nep141TransferCheck for #ident #ty #wher {
+                fn ft_transfer_check(
+                    &self,
+                    sender_id: #near_sdk::AccountId,
+                    receiver_id: #near_sdk::AccountId,
+                    amount: #near_sdk::json_types::U128,
+                ) -> Option<String> {
+                    use #me::standard::nep141::*;
+
+                    #check_dust
+
+                    let transfer = Nep141Transfer {
+                        sender_id: &sender_id,
+                        receiver_id: &receiver_id,
+                        amount: amount.into(),
+                        memo: None,
+                        msg: None,
+                        revert: false,
+                        origin: #me::hook::ActionOrigin::External("ft_transfer_check"),
+                    };
+
+                    Nep141Controller::can_transfer(self, &transfer)
+                        .err()
+                        .map(|e| e.to_string())
+                }
+            }
+        }
+    });
+
+    let (enter_busy, exit_busy) = if busy_guard.is_present() {
+        (
+            quote! { #me::busy::BusyGuard::enter_busy(self); },
+            quote! { #me::busy::BusyGuard::exit_busy(self); },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    let allowance_views = allowance_views.is_present().then(|| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::allowance::Nep141AllowanceViews for #ident #ty #wher {
+                fn ft_allowance(
+                    &self,
+                    owner_id: #near_sdk::AccountId,
+                    spender_id: #near_sdk::AccountId,
+                ) -> #near_sdk::json_types::U128 {
+                    #me::standard::nep141::allowance::Nep141Allowances::allowance(
+                        self,
+                        &owner_id,
+                        &spender_id,
+                    )
+                    .into()
+                }
+
+                fn ft_allowances_for(
+                    &self,
+                    owner_id: #near_sdk::AccountId,
+                    offset: Option<u32>,
+                    limit: Option<u32>,
+                ) -> Vec<#me::standard::nep141::allowance::Allowance> {
+                    use #me::standard::nep141::allowance::*;
+
+                    Nep141Allowances::allowances_for(
+                        self,
+                        &owner_id,
+                        offset.unwrap_or(0),
+                        limit.unwrap_or(DEFAULT_ALLOWANCES_LIMIT),
+                    )
+                }
+            }
+        }
+    });
+
+    let pending_transfers = if pending_transfers.is_present() {
+        let pending_root = crate::root_fn(&me, None, &attrs, "Nep141Pending")?;
+
+        Some(quote! {
+            impl #imp #me::standard::nep141::pending::Nep141PendingTransfersInternal for #ident #ty #wher {
+                #pending_root
+            }
+
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::pending::Nep141PendingTransfersExternal for #ident #ty #wher {
+                #[payable]
+                fn ft_transfer_escrowed(
+                    &mut self,
+                    receiver_id: #near_sdk::AccountId,
+                    amount: #near_sdk::json_types::U128,
+                    expiry: #near_sdk::json_types::U64,
+                    memo: Option<String>,
+                ) -> #near_sdk::json_types::U64 {
+                    use #me::standard::nep141::pending::*;
+
+                    #me::utils::assert_nonzero_deposit();
+
+                    let storage_usage_start = #near_sdk::env::storage_usage();
+
+                    let id = Nep141PendingTransfers::create_pending_transfer(
+                        self,
+                        &Nep141PendingTransferCreate {
+                            sender_id: &#near_sdk::env::predecessor_account_id(),
+                            receiver_id: &receiver_id,
+                            amount: amount.into(),
+                            expiry: expiry.into(),
+                            memo: memo.as_deref(),
+                            origin: #me::hook::ActionOrigin::External("ft_transfer_escrowed"),
+                        },
+                    )
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+
+                    #me::utils::apply_storage_fee_and_refund(storage_usage_start, 0);
+
+                    id.into()
+                }
+
+                #[payable]
+                fn ft_pending_claim(
+                    &mut self,
+                    id: #near_sdk::json_types::U64,
+                ) -> #near_sdk::json_types::U128 {
+                    #near_sdk::assert_one_yocto();
+
+                    #me::standard::nep141::pending::Nep141PendingTransfers::claim_pending_transfer(
+                        self,
+                        id.into(),
+                        &#near_sdk::env::predecessor_account_id(),
+                        #me::hook::ActionOrigin::External("ft_pending_claim"),
+                    )
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                    .amount
+                }
+
+                fn ft_pending_sweep(
+                    &mut self,
+                    ids: Vec<#near_sdk::json_types::U64>,
+                ) -> Vec<#near_sdk::json_types::U64> {
+                    use #me::standard::nep141::pending::*;
+
+                    ids.into_iter()
+                        .filter(|id| {
+                            match Nep141PendingTransfers::refund_pending_transfer(
+                                self,
+                                id.0,
+                                #me::hook::ActionOrigin::External("ft_pending_sweep"),
+                            ) {
+                                Ok(_) => true,
+                                Err(
+                                    PendingTransferError::NotFound(_)
+                                    | PendingTransferError::NotExpired { .. },
+                                ) => false,
+                                Err(e) => #me::tools_panic!(#panic_prefix, "{}", e),
+                            }
+                        })
+                        .collect()
+                }
+
+                fn ft_pending_transfer(
+                    &self,
+                    id: #near_sdk::json_types::U64,
+                ) -> Option<#me::standard::nep141::pending::PendingTransfer> {
+                    #me::standard::nep141::pending::Nep141PendingTransfers::pending_transfer(
+                        self,
+                        id.into(),
+                    )
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let balance_commitment = if balance_commitment.is_present() {
+        let commitment_root = crate::root_fn(&me, None, &attrs, "Nep141Commitment")?;
+
+        Some(quote! {
+            impl #imp #me::standard::nep141::commitment::Nep141CommitmentInternal for #ident #ty #wher {
+                #commitment_root
+            }
+
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::commitment::Nep141CommitmentExternal for #ident #ty #wher {
+                fn ft_balances_commitment(
+                    &self,
+                ) -> #me::standard::nep141::commitment::BalancesCommitment {
+                    #me::standard::nep141::commitment::Nep141Commitment::balances_commitment(self)
+                }
+
+                fn ft_balance_proof(
+                    &self,
+                    account_id: #near_sdk::AccountId,
+                ) -> Option<#me::standard::nep141::commitment::BalanceProof> {
+                    #me::standard::nep141::commitment::Nep141Commitment::balance_proof(
+                        self,
+                        &account_id,
+                    )
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let (check_dust, dust) = match dust_role {
+        Some(role) => {
+            let dust_root = crate::root_fn(&me, None, &attrs, "Nep141Dust")?;
+
+            (
+                quote! {
+                    #me::standard::nep141::dust::Nep141Dust::check_transfer_amount(self, amount)
+                        #on_transfer_error;
+                },
+                Some(quote! {
+                    impl #imp #me::standard::nep141::dust::Nep141DustInternal for #ident #ty #wher {
+                        #dust_root
+                    }
+
+                    #[#near_sdk::near_bindgen]
+                    impl #imp #me::standard::nep141::dust::Nep141DustExternal for #ident #ty #wher {
+                        fn ft_min_transfer(&self) -> #near_sdk::json_types::U128 {
+                            #me::standard::nep141::dust::Nep141Dust::min_transfer(self).into()
+                        }
+
+                        #[payable]
+                        fn ft_set_min_transfer(&mut self, minimum: #near_sdk::json_types::U128) {
+                            #near_sdk::assert_one_yocto();
+                            <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                            #me::standard::nep141::dust::Nep141Dust::set_min_transfer(
+                                self,
+                                minimum.into(),
+                            );
+                        }
+
+                        #[payable]
+                        fn ft_sweep_dust(
+                            &mut self,
+                            account_ids: Vec<#near_sdk::AccountId>,
+                            treasury_id: #near_sdk::AccountId,
+                        ) -> #near_sdk::json_types::U128 {
+                            #near_sdk::assert_one_yocto();
+                            <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                            #me::standard::nep141::dust::Nep141Dust::sweep_dust(
+                                self,
+                                &account_ids,
+                                &treasury_id,
+                                #me::hook::ActionOrigin::External("ft_sweep_dust"),
+                            )
+                            .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+                            .into()
+                        }
+                    }
+                }),
+            )
+        }
+        None => (quote! {}, None),
+    };
+
+    let expanded = quote! {
         impl #imp #me::standard::nep141::Nep141ControllerInternal for #ident #ty #wher {
-            type MintHook = (#mint_hook, #default_hook);
-            type TransferHook = (#transfer_hook, #default_hook);
-            type BurnHook = (#burn_hook, #default_hook);
+            type MintHook = #mint_hook;
+            type TransferHook = #transfer_hook;
+            type BurnHook = #burn_hook;
+            #remove_zero_balances
 
             #root
         }
 
         #[#near_sdk::near_bindgen]
-        impl #imp #me::standard::nep141::Nep141 for #ident #ty #wher {
+        impl #imp #nep141_impl #ident #ty #wher {
             #[payable]
-            fn ft_transfer(
+            #handle_result
+            #vis fn ft_transfer(
                 &mut self,
                 receiver_id: #near_sdk::AccountId,
                 amount: #near_sdk::json_types::U128,
                 memo: Option<String>,
-            ) {
+            ) #ft_transfer_output {
                 use #me::standard::nep141::*;
 
                 #near_sdk::assert_one_yocto();
                 let sender_id = #near_sdk::env::predecessor_account_id();
                 let amount: u128 = amount.into();
+                #check_dust
 
                 let transfer = Nep141Transfer {
                     sender_id: &sender_id,
@@ -90,32 +605,31 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
                     memo: memo.as_deref(),
                     msg: None,
                     revert: false,
+                    origin: #me::hook::ActionOrigin::External("ft_transfer"),
                 };
 
-                Nep141Controller::transfer(self, &transfer)
-                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+                Nep141Controller::transfer(self, &transfer) #on_transfer_error;
+                #ok_unit
             }
 
             #[payable]
-            fn ft_transfer_call(
+            #handle_result
+            #vis fn ft_transfer_call(
                 &mut self,
                 receiver_id: #near_sdk::AccountId,
                 amount: #near_sdk::json_types::U128,
                 memo: Option<String>,
                 msg: String,
-            ) -> #near_sdk::Promise {
+            ) #ft_transfer_call_output {
                 use #me::standard::nep141::*;
 
+                let transfer_call_gas = #require_transfer_call_gas;
                 let prepaid_gas = #near_sdk::env::prepaid_gas();
 
-                #near_sdk::require!(
-                    prepaid_gas >= GAS_FOR_FT_TRANSFER_CALL,
-                    MORE_GAS_FAIL_MESSAGE,
-                );
-
                 #near_sdk::assert_one_yocto();
                 let sender_id = #near_sdk::env::predecessor_account_id();
                 let amount: u128 = amount.into();
+                #check_dust
 
                 let transfer = Nep141Transfer {
                     sender_id: &sender_id,
@@ -124,36 +638,39 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
                     memo: memo.as_deref(),
                     msg: Some(&msg),
                     revert: false,
+                    origin: #me::hook::ActionOrigin::External("ft_transfer_call"),
                 };
 
-                Nep141Controller::transfer(self, &transfer)
-                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+                Nep141Controller::transfer(self, &transfer) #on_transfer_error;
+                #enter_busy
 
                 let receiver_gas = prepaid_gas
                     .0
-                    .checked_sub(GAS_FOR_FT_TRANSFER_CALL.0)
-                    .unwrap_or_else(|| #near_sdk::env::panic_str("Prepaid gas underflow."));
+                    .checked_sub(transfer_call_gas.0)
+                    .unwrap_or_else(|| #me::tools_panic!(#panic_prefix, "Prepaid gas underflow."));
 
                 // Initiating receiver's call and the callback
-                ext_nep141_receiver::ext(transfer.receiver_id.clone())
+                let promise = ext_nep141_receiver::ext(transfer.receiver_id.clone())
                     .with_static_gas(receiver_gas.into())
                     .ft_on_transfer(transfer.sender_id.clone(), transfer.amount.into(), msg.clone())
                     .then(
                         ext_nep141_resolver::ext(#near_sdk::env::current_account_id())
-                            .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                            .with_static_gas(#resolve_transfer_gas)
                             .ft_resolve_transfer(
                                 transfer.sender_id.clone(),
                                 transfer.receiver_id.clone(),
                                 transfer.amount.into(),
                             ),
-                    )
+                    );
+
+                #ok_promise
             }
 
-            fn ft_total_supply(&self) -> #near_sdk::json_types::U128 {
+            #vis fn ft_total_supply(&self) -> #near_sdk::json_types::U128 {
                 #me::standard::nep141::Nep141Controller::total_supply(self).into()
             }
 
-            fn ft_balance_of(&self, account_id: #near_sdk::AccountId) -> #near_sdk::json_types::U128 {
+            #vis fn ft_balance_of(&self, account_id: #near_sdk::AccountId) -> #near_sdk::json_types::U128 {
                 #me::standard::nep141::Nep141Controller::balance_of(self, &account_id).into()
             }
         }
@@ -170,6 +687,11 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
                 use #near_sdk::{env, PromiseResult, serde_json, json_types::U128};
                 use #me::standard::nep141::*;
 
+                #me::utils::check_self_callback()
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+
+                #exit_busy
+
                 let amount = amount.0;
 
                 let ft_on_transfer_promise_result = env::promise_result(0);
@@ -197,10 +719,11 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
                             memo: None,
                             msg: None,
                             revert: true,
+                            origin: #me::hook::ActionOrigin::Callback("ft_resolve_transfer"),
                         };
 
                         Nep141Controller::transfer(self, &transfer)
-                            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+                            .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
 
                         refund_amount
                     } else {
@@ -214,5 +737,39 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
                 U128(amount - refunded_amount)
             }
         }
-    })
+
+        #holder_count
+        #zero_balance_sweep
+
+        #transfer_check
+
+        #allowance_views
+
+        #pending_transfers
+        #balance_commitment
+
+        #dust
+
+        #batch_mint
+
+        #batch_burn
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep141",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::standard::nep141 },
+                    quote! { #me::standard::nep141::Nep141ControllerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
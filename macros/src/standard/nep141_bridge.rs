@@ -0,0 +1,124 @@
+use darling::{util::Flag, FromDeriveInput};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(nep141_bridge),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct Nep141BridgeMeta {
+    pub storage_key: Option<Expr>,
+    pub role: Expr,
+    pub pausable: Flag,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep141BridgeMeta) -> Result<TokenStream, darling::Error> {
+    let Nep141BridgeMeta {
+        storage_key,
+        role,
+        pausable,
+
+        generics,
+        ident,
+        attrs,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep141Bridge")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let require_unpaused = pausable.is_present().then(|| {
+        quote! {
+            <Self as #me::pause::Pause>::require_unpaused();
+        }
+    });
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #imp #me::standard::nep141::bridge::Nep141BridgeControllerInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep141::bridge::Nep141BridgeExternal for #ident #ty #wher {
+            fn ft_bridge_mint(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                amount: #near_sdk::json_types::U128,
+                foreign_tx_hash: String,
+                memo: Option<String>,
+            ) {
+                use #me::standard::nep141::bridge::*;
+
+                #require_unpaused
+                <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                Nep141BridgeController::bridge_mint(
+                    self,
+                    &Nep141BridgeMint {
+                        amount: amount.into(),
+                        receiver_id: &receiver_id,
+                        foreign_tx_hash: &foreign_tx_hash,
+                        memo: memo.as_deref(),
+                    },
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+
+            fn ft_bridge_burn(
+                &mut self,
+                owner_id: #near_sdk::AccountId,
+                amount: #near_sdk::json_types::U128,
+                foreign_tx_hash: String,
+                memo: Option<String>,
+            ) {
+                use #me::standard::nep141::bridge::*;
+
+                #require_unpaused
+                <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                Nep141BridgeController::bridge_burn(
+                    self,
+                    &Nep141BridgeBurn {
+                        amount: amount.into(),
+                        owner_id: &owner_id,
+                        foreign_tx_hash: &foreign_tx_hash,
+                        memo: memo.as_deref(),
+                    },
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            }
+
+            fn ft_bridge_caps(&self) -> #me::standard::nep141::bridge::BridgeCaps {
+                #me::standard::nep141::bridge::Nep141BridgeController::bridge_caps(self)
+            }
+
+            fn ft_bridge_epoch_volume(&self) -> #me::standard::nep141::bridge::EpochVolume {
+                #me::standard::nep141::bridge::Nep141BridgeController::bridge_epoch_volume(self)
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Nep141Bridge", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
@@ -0,0 +1,165 @@
+use darling::{util::Flag, FromDeriveInput};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(nep141_subscription),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct Nep141SubscriptionMeta {
+    pub storage_key: Option<Expr>,
+    pub pausable: Flag,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep141SubscriptionMeta) -> Result<TokenStream, darling::Error> {
+    let Nep141SubscriptionMeta {
+        storage_key,
+        pausable,
+
+        generics,
+        ident,
+        attrs,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep141Subscription")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let require_unpaused = pausable.is_present().then(|| {
+        quote! {
+            <Self as #me::pause::Pause>::require_unpaused();
+        }
+    });
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #imp #me::standard::nep141::subscription::Nep141SubscriptionControllerInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep141::subscription::Nep141SubscriptionExternal for #ident #ty #wher {
+            #[payable]
+            fn ft_subscription_authorize(
+                &mut self,
+                merchant_id: #near_sdk::AccountId,
+                max_amount: #near_sdk::json_types::U128,
+                period: #near_sdk::json_types::U64,
+            ) {
+                use #me::standard::nep141::subscription::*;
+
+                #me::utils::assert_nonzero_deposit();
+
+                let storage_usage_start = #near_sdk::env::storage_usage();
+
+                Nep141SubscriptionController::authorize_subscription(
+                    self,
+                    &#near_sdk::env::predecessor_account_id(),
+                    &merchant_id,
+                    max_amount.into(),
+                    period.into(),
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+
+                #me::utils::apply_storage_fee_and_refund(storage_usage_start, 0);
+            }
+
+            #[payable]
+            fn ft_subscription_revoke(&mut self, merchant_id: #near_sdk::AccountId) -> bool {
+                #near_sdk::assert_one_yocto();
+
+                #me::standard::nep141::subscription::Nep141SubscriptionController::revoke_subscription(
+                    self,
+                    &#near_sdk::env::predecessor_account_id(),
+                    &merchant_id,
+                )
+            }
+
+            #[payable]
+            fn ft_subscription_charge(
+                &mut self,
+                account_id: #near_sdk::AccountId,
+                amount: Option<#near_sdk::json_types::U128>,
+                memo: Option<String>,
+            ) -> #near_sdk::json_types::U128 {
+                use #me::standard::nep141::subscription::*;
+
+                #near_sdk::assert_one_yocto();
+                #require_unpaused
+
+                let merchant_id = #near_sdk::env::predecessor_account_id();
+
+                let amount = amount.map(u128::from).unwrap_or_else(|| {
+                    Nep141SubscriptionController::subscription(self, &account_id, &merchant_id)
+                        .map_or(0, |s| s.remaining_at(#near_sdk::env::block_timestamp()))
+                });
+
+                Nep141SubscriptionController::charge_subscription(
+                    self,
+                    &Nep141SubscriptionCharge {
+                        account_id: &account_id,
+                        merchant_id: &merchant_id,
+                        amount,
+                        memo: memo.as_deref(),
+                    },
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+
+                amount.into()
+            }
+
+            fn ft_subscription(
+                &self,
+                account_id: #near_sdk::AccountId,
+                merchant_id: #near_sdk::AccountId,
+            ) -> Option<#me::standard::nep141::subscription::Subscription> {
+                #me::standard::nep141::subscription::Nep141SubscriptionController::subscription(
+                    self,
+                    &account_id,
+                    &merchant_id,
+                )
+            }
+
+            fn ft_subscription_remaining(
+                &self,
+                account_id: #near_sdk::AccountId,
+                merchant_id: #near_sdk::AccountId,
+            ) -> #near_sdk::json_types::U128 {
+                #me::standard::nep141::subscription::Nep141SubscriptionController::subscription(
+                    self,
+                    &account_id,
+                    &merchant_id,
+                )
+                .map_or(0, |s| s.remaining_at(#near_sdk::env::block_timestamp()))
+                .into()
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep141Subscription",
+            with_invariants(&me, &attrs, expanded)?,
+        ),
+    ))
+}
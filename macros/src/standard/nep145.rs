@@ -1,16 +1,34 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{Expr, Type};
 
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version,
+};
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(nep145), supports(struct_named))]
+#[darling(
+    attributes(nep145),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct Nep145Meta {
     pub storage_key: Option<Expr>,
     pub all_hooks: Option<Type>,
     pub force_unregister_hook: Option<Type>,
+    pub register_hook: Option<Type>,
+    pub byte_cost: Option<Type>,
+    pub no_events: Flag,
+    pub withdraw_to: Flag,
+    pub owner_sweep: Flag,
+    pub treasury: Flag,
+    pub set_bounds: Flag,
+    pub bounds_role: Option<Expr>,
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -24,8 +42,17 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
         storage_key,
         all_hooks,
         force_unregister_hook,
+        register_hook,
+        byte_cost,
+        no_events,
+        withdraw_to,
+        owner_sweep,
+        treasury,
+        set_bounds,
+        bounds_role,
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
@@ -33,13 +60,8 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
-        quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
-            }
-        }
-    });
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep145")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
 
     let all_hooks = all_hooks
         .map(|h| quote! { #h })
@@ -47,10 +69,217 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
     let force_unregister_hook = force_unregister_hook
         .map(|h| quote! { #h })
         .unwrap_or_else(|| quote! { () });
+    let register_hook = register_hook
+        .map(|h| quote! { #h })
+        .unwrap_or_else(|| quote! { () });
+    let byte_cost = byte_cost
+        .map(|c| quote! { #c })
+        .unwrap_or_else(|| quote! { #me::standard::nep145::EnvStorageByteCost });
+
+    let emit = |event: TokenStream| {
+        (!no_events.is_present()).then(|| {
+            quote! {
+                {
+                    use #me::standard::{nep145::event::*, nep297::Event};
+                    #event.emit();
+                }
+            }
+        })
+    };
+
+    let emit_deposit = emit(quote! {
+        Nep145Event::StorageDeposit(vec![StorageDepositData {
+            account_id: account_id.clone(),
+            amount: U128(amount),
+            balance: storage_balance.clone(),
+        }])
+    });
+    let emit_withdraw = emit(quote! {
+        Nep145Event::StorageWithdraw(vec![StorageWithdrawData {
+            account_id: predecessor.clone(),
+            amount,
+            balance: new_balance.clone(),
+        }])
+    });
+    let emit_unregister = emit(quote! {
+        Nep145Event::StorageUnregister(vec![StorageUnregisterData {
+            account_id: predecessor.clone(),
+            refund,
+            force: force.unwrap_or(false),
+        }])
+    });
+
+    let emit_withdraw_from = emit(quote! {
+        Nep145Event::StorageWithdraw(vec![StorageWithdrawData {
+            account_id: account_id.clone(),
+            amount,
+            balance: new_balance.clone(),
+        }])
+    });
+
+    let require_transfer = |amount: TokenStream| {
+        treasury.is_present().then(|| {
+            quote! {
+                <Self as #me::treasury::Treasury>::require_transfer(#amount);
+            }
+        })
+    };
+    let require_transfer_withdraw = require_transfer(quote! { amount.0 });
+    let require_transfer_unregister = require_transfer(quote! { refund.0 });
+    // Resolves the default amount up front, so that it can be checked before
+    // the controller creates the transfer.
+    let require_transfer_beneficiary = treasury.is_present().then(|| {
+        let require_transfer = require_transfer(quote! { amount.0 });
+        quote! {
+            let amount = amount.unwrap_or_else(|| {
+                Nep145Controller::get_storage_balance(self, &account_id)
+                    .map_or(#near_sdk::json_types::U128(0), |balance| balance.available)
+            });
+            #require_transfer
+            let amount = Some(amount);
+        }
+    });
+
+    let emit_bounds_update = emit(quote! {
+        Nep145Event::StorageBoundsUpdate(StorageBoundsUpdateData {
+            updated_by: env::predecessor_account_id(),
+            old_bounds,
+            new_bounds: new_bounds.clone(),
+        })
+    });
+
+    let require_bounds_authorized = match bounds_role {
+        Some(role) => quote! {
+            <Self as #me::rbac::Rbac>::require_role(&#role);
+        },
+        None => quote! {
+            <Self as #me::owner::Owner>::require_owner();
+        },
+    };
+
+    let set_bounds = set_bounds.is_present().then(|| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep145::Nep145SetBounds for #ident #ty #wher {
+                #[payable]
+                fn storage_set_bounds(
+                    &mut self,
+                    min: #near_sdk::json_types::U128,
+                    max: Option<#near_sdk::json_types::U128>,
+                ) -> #me::standard::nep145::StorageBalanceBounds {
+                    use #me::standard::nep145::*;
+                    use #near_sdk::env;
+
+                    #near_sdk::assert_one_yocto();
+                    #require_bounds_authorized
+
+                    if let Some(max) = max {
+                        if max.0 < min.0 {
+                            #me::tools_panic!(
+                                #panic_prefix,
+                                "{}",
+                                error::InvalidBoundsError { min, max }
+                            );
+                        }
+                    }
+
+                    let old_bounds = Nep145Controller::get_storage_balance_bounds(self);
+                    let new_bounds = StorageBalanceBounds { min, max };
+                    Nep145Controller::set_storage_balance_bounds(self, &new_bounds);
+
+                    #emit_bounds_update
+
+                    new_bounds
+                }
+            }
+        }
+    });
+
+    let withdraw_to = withdraw_to.is_present().then(|| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep145::Nep145WithdrawTo for #ident #ty #wher {
+                #[payable]
+                fn storage_withdraw_to(
+                    &mut self,
+                    beneficiary_id: #near_sdk::AccountId,
+                    amount: Option<#near_sdk::json_types::U128>,
+                ) -> #me::standard::nep145::StorageBalance {
+                    use #me::standard::nep145::*;
+                    use #near_sdk::env;
+
+                    #near_sdk::assert_one_yocto();
+
+                    let account_id = env::predecessor_account_id();
+
+                    #require_transfer_beneficiary
+
+                    let (new_balance, amount) = Nep145Controller::withdraw_to_beneficiary(
+                        self,
+                        &account_id,
+                        &beneficiary_id,
+                        amount,
+                    )
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "Storage withdraw error: {}", e));
+
+                    if amount.0 > 0 {
+                        #emit_withdraw_from
+                    }
+
+                    new_balance
+                }
+            }
+        }
+    });
+
+    let owner_sweep = owner_sweep.is_present().then(|| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep145::Nep145OwnerSweep for #ident #ty #wher {
+                #[payable]
+                fn storage_sweep(
+                    &mut self,
+                    account_id: #near_sdk::AccountId,
+                    beneficiary_id: #near_sdk::AccountId,
+                    amount: Option<#near_sdk::json_types::U128>,
+                ) -> #me::standard::nep145::StorageBalance {
+                    use #me::standard::nep145::*;
+                    use #near_sdk::env;
+
+                    #near_sdk::assert_one_yocto();
+                    <Self as #me::owner::Owner>::require_owner();
+
+                    #require_transfer_beneficiary
+
+                    let (new_balance, amount) = Nep145Controller::withdraw_to_beneficiary(
+                        self,
+                        &account_id,
+                        &beneficiary_id,
+                        amount,
+                    )
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "Storage withdraw error: {}", e));
+
+                    if amount.0 > 0 {
+                        #emit_withdraw_from
+                    }
+
+                    new_balance
+                }
+            }
+        }
+    });
+
+    let expanded = quote! {
+        #withdraw_to
+
+        #owner_sweep
+
+        #set_bounds
 
-    Ok(quote! {
         impl #imp #me::standard::nep145::Nep145ControllerInternal for #ident #ty #wher {
             type ForceUnregisterHook = (#force_unregister_hook, #all_hooks);
+            type RegisterHook = #register_hook;
+            type ByteCost = #byte_cost;
 
             #root
         }
@@ -64,36 +293,41 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
                 registration_only: Option<bool>,
             ) -> #me::standard::nep145::StorageBalance {
                 use #me::standard::nep145::*;
-                use #near_sdk::{env, json_types::U128, Promise};
+                use #near_sdk::{env, json_types::U128};
 
                 let bounds = Nep145Controller::get_storage_balance_bounds(self);
 
                 let attached = env::attached_deposit();
+                let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+                let existing = Nep145Controller::get_storage_balance(self, &account_id).ok();
+
+                if let (true, Some(existing)) = (registration_only.unwrap_or(false), &existing) {
+                    // Already registered: refund the full deposit.
+                    #me::utils::preflight::refund_excess_deposit(0);
+                    return existing.clone();
+                }
+
                 let amount = if registration_only.unwrap_or(false) {
                     bounds.min.0
                 } else if let Some(U128(max)) = bounds.max {
-                    u128::min(max, attached)
+                    let headroom = max.saturating_sub(existing.map_or(0, |b| b.total.0));
+                    u128::min(headroom, attached)
                 } else {
                     attached
                 };
-                let refund = attached.checked_sub(amount).unwrap_or_else(|| {
-                    env::panic_str(&format!(
-                        "Attached deposit {} is less than required {}",
-                        attached, amount,
-                    ))
-                });
-                let predecessor = env::predecessor_account_id();
+                #me::utils::preflight::require_deposit(amount);
 
-                let storage_balance = Nep145Controller::deposit_to_storage_account(
+                let storage_balance = Nep145Controller::deposit_to_storage_account_with_origin(
                     self,
-                    &account_id.unwrap_or_else(|| predecessor.clone()),
+                    &account_id,
                     U128(amount),
+                    #me::hook::ActionOrigin::External("storage_deposit"),
                 )
-                .unwrap_or_else(|e| env::panic_str(&format!("Storage deposit error: {}", e)));
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "Storage deposit error: {}", e));
 
-                if refund > 0 {
-                    Promise::new(predecessor).transfer(amount);
-                }
+                #emit_deposit
+
+                #me::utils::preflight::refund_excess_deposit(amount);
 
                 storage_balance
             }
@@ -108,7 +342,7 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
                 let predecessor = env::predecessor_account_id();
 
                 let balance = Nep145Controller::get_storage_balance(self, &predecessor)
-                    .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
 
                 let amount = amount.unwrap_or(balance.available);
 
@@ -117,7 +351,11 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
                 }
 
                 let new_balance = Nep145Controller::withdraw_from_storage_account(self, &predecessor, amount)
-                    .unwrap_or_else(|e| env::panic_str(&format!("Storage withdraw error: {}", e)));
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "Storage withdraw error: {}", e));
+
+                #emit_withdraw
+
+                #require_transfer_withdraw
 
                 Promise::new(predecessor).transfer(amount.0);
 
@@ -133,7 +371,11 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
                 let predecessor = env::predecessor_account_id();
 
                 let refund = if force.unwrap_or(false) {
-                    match Nep145Controller::force_unregister_storage_account(self, &predecessor) {
+                    match Nep145Controller::force_unregister_storage_account_with_origin(
+                        self,
+                        &predecessor,
+                        #me::hook::ActionOrigin::External("storage_unregister"),
+                    ) {
                         Ok(refund) => refund,
                         Err(error::StorageForceUnregisterError::AccountNotRegistered(_)) => return false,
                     }
@@ -141,14 +383,20 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
                     match Nep145Controller::unregister_storage_account(self, &predecessor) {
                         Ok(refund) => refund,
                         Err(error::StorageUnregisterError::UnregisterWithLockedBalance(e)) => {
-                            env::panic_str(&format!(
-                                "Attempt to unregister from storage with locked balance: {}", e
-                            ));
+                            #me::tools_panic!(
+                                #panic_prefix,
+                                "Attempt to unregister from storage with locked balance: {}",
+                                e
+                            );
                         }
                         Err(error::StorageUnregisterError::AccountNotRegistered(_)) => return false,
                     }
                 };
 
+                #emit_unregister
+
+                #require_transfer_unregister
+
                 Promise::new(predecessor).transfer(refund.0);
                 true
             }
@@ -161,5 +409,23 @@ pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
                 #me::standard::nep145::Nep145Controller::get_storage_balance_bounds(self)
             }
         }
-    })
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep145",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::standard::nep145 },
+                    quote! { #me::standard::nep145::Nep145ControllerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
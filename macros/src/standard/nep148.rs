@@ -1,14 +1,26 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version,
+};
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(nep148), supports(struct_named))]
+#[darling(
+    attributes(nep148),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct Nep148Meta {
     pub storage_key: Option<Expr>,
+    pub human_views: Flag,
+    pub strict_reference: Flag,
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -20,26 +32,56 @@ pub struct Nep148Meta {
 pub fn expand(meta: Nep148Meta) -> Result<TokenStream, darling::Error> {
     let Nep148Meta {
         storage_key,
+        human_views,
+        strict_reference,
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
     } = meta;
 
-    let root = storage_key.map(|storage_key| {
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep148")?;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let strict_reference = strict_reference.is_present().then(|| {
         quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
+            fn strict_reference() -> bool {
+                true
             }
         }
     });
 
-    let (imp, ty, wher) = generics.split_for_impl();
+    let human_views = human_views.is_present().then(|| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep148::Nep148HumanViews for #ident #ty #wher {
+                fn ft_total_supply_human(&self) -> String {
+                    use #me::standard::{nep141::Nep141Controller, nep148::Nep148Controller};
+
+                    self.get_metadata().format_amount(self.total_supply())
+                }
+
+                fn ft_balance_of_human(&self, account_id: #near_sdk::AccountId) -> String {
+                    use #me::standard::{nep141::Nep141Controller, nep148::Nep148Controller};
 
-    Ok(quote! {
+                    self.get_metadata().format_amount(self.balance_of(&account_id))
+                }
+
+                fn ft_metadata_version(&self) -> u32 {
+                    #me::standard::nep148::Nep148Controller::get_metadata_version(self)
+                }
+            }
+        }
+    });
+
+    let expanded = quote! {
         impl #imp #me::standard::nep148::Nep148ControllerInternal for #ident #ty #wher {
             #root
+
+            #strict_reference
         }
 
         #[#near_sdk::near_bindgen]
@@ -48,5 +90,25 @@ pub fn expand(meta: Nep148Meta) -> Result<TokenStream, darling::Error> {
                 #me::standard::nep148::Nep148Controller::get_metadata(self)
             }
         }
-    })
+
+        #human_views
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep148",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::standard::nep148 },
+                    quote! { #me::standard::nep148::Nep148ControllerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
@@ -1,23 +1,36 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{parse_quote, Expr, Type};
 
-use crate::unitify;
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version, unitify,
+};
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(nep171), supports(struct_named))]
+#[darling(
+    attributes(nep171),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct Nep171Meta {
     pub storage_key: Option<Expr>,
     pub all_hooks: Option<Type>,
     pub mint_hook: Option<Type>,
     pub transfer_hook: Option<Type>,
     pub burn_hook: Option<Type>,
+    pub all_hooks_first: Flag,
     pub check_external_transfer: Option<Type>,
     pub token_data: Option<Type>,
+    pub transfer_check: Flag,
+    pub gas_config: Flag,
+    pub owner_token_count: Flag,
+    pub mint_limit_per_account: Option<Expr>,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -33,11 +46,17 @@ pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
         mint_hook,
         transfer_hook,
         burn_hook,
+        all_hooks_first,
         check_external_transfer,
         token_data,
+        transfer_check,
+        gas_config,
+        owner_token_count,
+        mint_limit_per_account,
 
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
@@ -51,29 +70,110 @@ pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
         parse_quote! { #me::standard::nep171::DefaultCheckExternalTransfer }
     });
 
-    let root = storage_key.map(|storage_key| {
-        quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
-            }
-        }
-    });
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep171")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
 
     let all_hooks = unitify(all_hooks);
     let mint_hook = unitify(mint_hook);
     let transfer_hook = unitify(transfer_hook);
     let burn_hook = unitify(burn_hook);
 
-    Ok(quote! {
+    let mint_hook = crate::compose_hooks(&mint_hook, &all_hooks, all_hooks_first);
+    let transfer_hook = crate::compose_hooks(&transfer_hook, &all_hooks, all_hooks_first);
+    let burn_hook = crate::compose_hooks(&burn_hook, &all_hooks, all_hooks_first);
+
+    // With `gas_config`, the gas amounts are read from `GasConfig` storage
+    // instead of the compile-time defaults.
+    let (require_transfer_call_gas, resolve_transfer_gas) = if gas_config.is_present() {
+        (
+            quote! {
+                <Self as #me::gas::GasConfig>::require_gas_for(#me::gas::GasKey::NftTransferCall)
+            },
+            quote! {
+                <Self as #me::gas::GasConfig>::gas_for(#me::gas::GasKey::NftResolveTransfer)
+            },
+        )
+    } else {
+        (
+            quote! {{
+                #me::utils::preflight::require_gas(#me::standard::nep171::GAS_FOR_NFT_TRANSFER_CALL);
+                #me::standard::nep171::GAS_FOR_NFT_TRANSFER_CALL
+            }},
+            quote! { #me::standard::nep171::GAS_FOR_RESOLVE_TRANSFER },
+        )
+    };
+
+    // The limit hook runs outermost, so it vetoes before any other hook.
+    let mint_hook_type = match &mint_limit_per_account {
+        Some(limit) => quote! {
+            (
+                #me::standard::nep171::hooks::PerAccountMintLimitHook<{ #limit }>,
+                #mint_hook,
+            )
+        },
+        None => mint_hook,
+    };
+
+    let owner_token_count = (owner_token_count.is_present()
+        || mint_limit_per_account.is_some())
+    .then(|| {
+        quote! {
+            fn owner_token_count(owner_id: &#near_sdk::AccountId) -> Option<u64> {
+                Some(
+                    <Self as #me::standard::nep181::Nep181ControllerInternal>::slot_owner_tokens(owner_id)
+                        .read()
+                        .map_or(0, |tokens| u64::from(tokens.len())),
+                )
+            }
+        }
+    });
+
+    let transfer_check = transfer_check.is_present().then(|| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep171::Nep171TransferCheck for #ident #ty #wher {
+                fn nft_transfer_check(
+                    &self,
+                    sender_id: #near_sdk::AccountId,
+                    receiver_id: #near_sdk::AccountId,
+                    token_id: #me::standard::nep171::TokenId,
+                    approval_id: Option<u32>,
+                ) -> Option<String> {
+                    use #me::standard::nep171::*;
+
+                    let transfer = action::Nep171Transfer {
+                        token_id: &token_id,
+                        authorization: approval_id
+                            .map(Nep171TransferAuthorization::ApprovalId)
+                            .unwrap_or(Nep171TransferAuthorization::Owner),
+                        sender_id: &sender_id,
+                        receiver_id: &receiver_id,
+                        memo: None,
+                        msg: None,
+                        revert: false,
+                        origin: #me::hook::ActionOrigin::External("nft_transfer_check"),
+                    };
+
+                    Nep171Controller::can_transfer(self, &transfer)
+                        .err()
+                        .map(|e| e.to_string())
+                }
+            }
+        }
+    });
+
+    let expanded = quote! {
         impl #imp #me::standard::nep171::Nep171ControllerInternal for #ident #ty #wher {
-            type MintHook = (#mint_hook, #all_hooks);
-            type TransferHook = (#transfer_hook, #all_hooks);
-            type BurnHook = (#burn_hook, #all_hooks);
+            type MintHook = #mint_hook_type;
+            type TransferHook = #transfer_hook;
+            type BurnHook = #burn_hook;
 
             type CheckExternalTransfer = #check_external_transfer;
             type LoadTokenMetadata = #token_data;
 
             #root
+
+            #owner_token_count
         }
 
         #[#near_sdk::near_bindgen]
@@ -85,15 +185,18 @@ pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
                 receiver_id: #near_sdk::AccountId,
                 token_id: #me::standard::nep171::TokenId,
                 approved_account_ids: Option<std::collections::HashMap<#near_sdk::AccountId, u64>>,
+                memo: Option<String>,
             ) -> bool {
                 use #me::standard::nep171::*;
 
                 let _ = approved_account_ids; // #[near_bindgen] cares about parameter names
 
-                #near_sdk::require!(
-                    #near_sdk::env::promise_results_count() == 1,
-                    "Requires exactly one promise result.",
-                );
+                #me::utils::check_self_callback()
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+
+                if #near_sdk::env::promise_results_count() != 1 {
+                    #me::tools_panic!(#panic_prefix, "Requires exactly one promise result.");
+                }
 
                 let should_revert =
                     if let #near_sdk::PromiseResult::Successful(value) = #near_sdk::env::promise_result(0) {
@@ -111,9 +214,10 @@ pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
                         authorization: Nep171TransferAuthorization::Owner,
                         sender_id: &receiver_id,
                         receiver_id: &previous_owner_id,
-                        memo: None,
+                        memo: memo.as_deref(),
                         msg: None,
                         revert: true,
+                        origin: #me::hook::ActionOrigin::Callback("nft_resolve_transfer"),
                     };
 
                     <Self as Nep171Controller>::external_transfer(self, &transfer)
@@ -150,10 +254,11 @@ pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
                     memo: memo.as_deref(),
                     msg: None,
                     revert: false,
+                    origin: #me::hook::ActionOrigin::External("nft_transfer"),
                 };
 
                 <Self as Nep171Controller>::external_transfer(self, &transfer)
-                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
             }
 
             #[payable]
@@ -169,10 +274,7 @@ pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
 
                 #near_sdk::assert_one_yocto();
 
-                #near_sdk::require!(
-                    #near_sdk::env::prepaid_gas() >= GAS_FOR_NFT_TRANSFER_CALL,
-                    INSUFFICIENT_GAS_MESSAGE,
-                );
+                let transfer_call_gas = #require_transfer_call_gas;
 
                 let sender_id = #near_sdk::env::predecessor_account_id();
 
@@ -186,25 +288,27 @@ pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
                     memo: memo.as_deref(),
                     msg: Some(&msg),
                     revert: false,
+                    origin: #me::hook::ActionOrigin::External("nft_transfer_call"),
                 };
 
                 <Self as Nep171Controller>::external_transfer(self, &transfer)
-                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
 
                 let [token_id] = token_ids;
 
-                ext_nep171_receiver::ext(receiver_id.clone())
-                    .with_static_gas(#near_sdk::env::prepaid_gas() - GAS_FOR_NFT_TRANSFER_CALL)
+                ext_nep171_memo_receiver::ext(receiver_id.clone())
+                    .with_static_gas(#near_sdk::env::prepaid_gas() - transfer_call_gas)
                     .nft_on_transfer(
                         sender_id.clone(),
                         sender_id.clone(),
                         token_id.clone(),
                         msg.clone(),
+                        memo.clone(),
                     )
                     .then(
                         ext_nep171_resolver::ext(#near_sdk::env::current_account_id())
-                            .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
-                            .nft_resolve_transfer(sender_id.clone(), receiver_id.clone(), token_id.clone(), None),
+                            .with_static_gas(#resolve_transfer_gas)
+                            .nft_resolve_transfer(sender_id.clone(), receiver_id.clone(), token_id.clone(), None, memo),
                     )
                     .into()
             }
@@ -212,9 +316,43 @@ pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
             fn nft_token(
                 &self,
                 token_id: #me::standard::nep171::TokenId,
+                fields: Option<Vec<String>>,
             ) -> Option<#me::standard::nep171::Token> {
-                <Self as #me::standard::nep171::Nep171Controller>::load_token(self, &token_id)
+                let fields = fields
+                    .as_deref()
+                    .map_or(#me::standard::nep171::TokenFields::All, #me::standard::nep171::TokenFields::Only);
+                <Self as #me::standard::nep171::Nep171Controller>::load_token_with(self, &token_id, fields)
+            }
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep171::Nep171TokenOwners for #ident #ty #wher {
+            fn nft_token_owners(
+                &self,
+                token_ids: Vec<#me::standard::nep171::TokenId>,
+            ) -> Vec<Option<#near_sdk::AccountId>> {
+                <Self as #me::standard::nep171::Nep171Controller>::token_owners(self, &token_ids)
             }
         }
-    })
+
+        #transfer_check
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep171",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::standard::nep171 },
+                    quote! { #me::standard::nep171::Nep171ControllerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
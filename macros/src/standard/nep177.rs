@@ -1,15 +1,26 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version,
+};
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(nep177), supports(struct_named))]
+#[darling(
+    attributes(nep177),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct Nep177Meta {
     pub storage_key: Option<Expr>,
+    pub strict_metadata: Flag,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -21,9 +32,11 @@ pub struct Nep177Meta {
 pub fn expand(meta: Nep177Meta) -> Result<TokenStream, darling::Error> {
     let Nep177Meta {
         storage_key,
+        strict_metadata,
 
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
@@ -31,17 +44,21 @@ pub fn expand(meta: Nep177Meta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep177")?;
+
+    let strict_metadata = strict_metadata.is_present().then(|| {
         quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
+            fn strict_metadata() -> bool {
+                true
             }
         }
     });
 
-    Ok(quote! {
+    let expanded = quote! {
         impl #imp #me::standard::nep177::Nep177ControllerInternal for #ident #ty #wher {
             #root
+
+            #strict_metadata
         }
 
         #[#near_sdk::near_bindgen]
@@ -50,5 +67,38 @@ pub fn expand(meta: Nep177Meta) -> Result<TokenStream, darling::Error> {
                 #me::standard::nep177::Nep177Controller::contract_metadata(self)
             }
         }
-    })
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep177::Nep177Freeze for #ident #ty #wher {
+            fn nft_is_metadata_frozen(
+                &self,
+                token_id: Option<#me::standard::nep171::TokenId>,
+            ) -> bool {
+                use #me::standard::nep177::Nep177Controller;
+
+                match token_id {
+                    Some(token_id) => self.is_token_metadata_frozen(&token_id),
+                    None => self.is_contract_metadata_frozen(),
+                }
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep177",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::standard::nep177 },
+                    quote! { #me::standard::nep177::Nep177ControllerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
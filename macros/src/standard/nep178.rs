@@ -1,21 +1,33 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{Expr, Type};
 
-use crate::unitify;
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version, unitify,
+};
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(nep178), supports(struct_named))]
+#[darling(
+    attributes(nep178),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct Nep178Meta {
     pub storage_key: Option<Expr>,
     pub all_hooks: Option<Type>,
     pub approve_hook: Option<Type>,
     pub revoke_hook: Option<Type>,
     pub revoke_all_hook: Option<Type>,
+    pub all_hooks_first: Flag,
+    pub no_deposit_refund: Flag,
+    pub no_events: Flag,
+    pub approve_min_deposit: Option<Expr>,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -31,9 +43,14 @@ pub fn expand(meta: Nep178Meta) -> Result<TokenStream, darling::Error> {
         approve_hook,
         revoke_hook,
         revoke_all_hook,
+        all_hooks_first,
+        no_deposit_refund,
+        no_events,
+        approve_min_deposit,
 
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
@@ -41,26 +58,40 @@ pub fn expand(meta: Nep178Meta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
-        quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
-            }
-        }
-    });
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep178")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
 
     let all_hooks = unitify(all_hooks);
     let approve_hook = unitify(approve_hook);
     let revoke_hook = unitify(revoke_hook);
     let revoke_all_hook = unitify(revoke_all_hook);
 
-    Ok(quote! {
+    let approve_hook = crate::compose_hooks(&approve_hook, &all_hooks, all_hooks_first);
+    let revoke_hook = crate::compose_hooks(&revoke_hook, &all_hooks, all_hooks_first);
+    let revoke_all_hook = crate::compose_hooks(&revoke_all_hook, &all_hooks, all_hooks_first);
+
+    let refund = !no_deposit_refund.is_present();
+    let approve_min_deposit = approve_min_deposit
+        .map(|e| quote! { #e })
+        .unwrap_or_else(|| quote! { #me::standard::nep178::DEFAULT_APPROVE_MIN_DEPOSIT });
+
+    let emit_events = no_events.is_present().then(|| {
+        quote! {
+            fn emit_events() -> bool {
+                false
+            }
+        }
+    });
+
+    let expanded = quote! {
         impl #imp #me::standard::nep178::Nep178ControllerInternal for #ident #ty #wher {
-            type ApproveHook = (#approve_hook, #all_hooks);
-            type RevokeHook = (#revoke_hook, #all_hooks);
-            type RevokeAllHook = (#revoke_all_hook, #all_hooks);
+            type ApproveHook = #approve_hook;
+            type RevokeHook = #revoke_hook;
+            type RevokeAllHook = #revoke_all_hook;
 
             #root
+
+            #emit_events
         }
 
         #[#near_sdk::near_bindgen]
@@ -74,18 +105,22 @@ pub fn expand(meta: Nep178Meta) -> Result<TokenStream, darling::Error> {
             ) -> #near_sdk::PromiseOrValue<()> {
                 use #me::standard::nep178::*;
 
-                #me::utils::assert_nonzero_deposit();
-
                 let predecessor = #near_sdk::env::predecessor_account_id();
 
+                let storage_usage_start = #near_sdk::env::storage_usage();
+
                 let action = action::Nep178Approve {
                     token_id: &token_id,
                     current_owner_id: &predecessor,
                     account_id: &account_id,
+                    origin: #me::hook::ActionOrigin::External("nft_approve"),
                 };
 
                 let approval_id = Nep178Controller::approve(self, &action)
-                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+
+                charge_approval_storage(storage_usage_start, #approve_min_deposit, #refund)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
 
                 msg.map_or(#near_sdk::PromiseOrValue::Value(()), |msg| {
                     ext_nep178_receiver::ext(account_id)
@@ -110,10 +145,11 @@ pub fn expand(meta: Nep178Meta) -> Result<TokenStream, darling::Error> {
                     token_id: &token_id,
                     current_owner_id: &predecessor,
                     account_id: &account_id,
+                    origin: #me::hook::ActionOrigin::External("nft_revoke"),
                 };
 
                 Nep178Controller::revoke(self, &action)
-                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
             }
 
             #[payable]
@@ -127,10 +163,11 @@ pub fn expand(meta: Nep178Meta) -> Result<TokenStream, darling::Error> {
                 let action = action::Nep178RevokeAll {
                     token_id: &token_id,
                     current_owner_id: &predecessor,
+                    origin: #me::hook::ActionOrigin::External("nft_revoke_all"),
                 };
 
                 Nep178Controller::revoke_all(self, &action)
-                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
             }
 
             fn nft_is_approved(
@@ -153,5 +190,23 @@ pub fn expand(meta: Nep178Meta) -> Result<TokenStream, darling::Error> {
                 }
             }
         }
-    })
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep178",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::standard::nep178 },
+                    quote! { #me::standard::nep178::Nep178ControllerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
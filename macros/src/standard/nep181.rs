@@ -3,13 +3,24 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version,
+};
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(nep181), supports(struct_named))]
+#[darling(
+    attributes(nep181),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct Nep181Meta {
     pub storage_key: Option<Expr>,
+    pub hide_role: Option<Expr>,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -21,9 +32,11 @@ pub struct Nep181Meta {
 pub fn expand(meta: Nep181Meta) -> Result<TokenStream, darling::Error> {
     let Nep181Meta {
         storage_key,
+        hide_role,
 
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
@@ -31,15 +44,39 @@ pub fn expand(meta: Nep181Meta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
-    let root = storage_key.map(|storage_key| {
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep181")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let visibility = hide_role.map(|role| {
         quote! {
-            fn root() -> #me::slot::Slot<()> {
-                #me::slot::Slot::root(#storage_key)
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep181::Nep181Visibility for #ident #ty #wher {
+                fn nft_is_token_hidden(&self, token_id: #me::standard::nep171::TokenId) -> bool {
+                    #me::standard::nep181::Nep181Controller::is_token_hidden(self, &token_id)
+                }
+
+                #[payable]
+                fn nft_hide_token(&mut self, token_id: #me::standard::nep171::TokenId) {
+                    #near_sdk::assert_one_yocto();
+                    <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                    #me::standard::nep181::Nep181Controller::hide_token(self, &token_id)
+                        .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+                }
+
+                #[payable]
+                fn nft_reveal_token(&mut self, token_id: #me::standard::nep171::TokenId) {
+                    #near_sdk::assert_one_yocto();
+                    <Self as #me::rbac::Rbac>::require_role(&#role);
+
+                    #me::standard::nep181::Nep181Controller::reveal_token(self, &token_id)
+                        .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+                }
             }
         }
     });
 
-    Ok(quote! {
+    let expanded = quote! {
         impl #imp #me::standard::nep181::Nep181ControllerInternal for #ident #ty #wher {
             #root
         }
@@ -66,7 +103,7 @@ pub fn expand(meta: Nep181Meta) -> Result<TokenStream, darling::Error> {
                         .iter()
                         .skip(from_index)
                         .map(|token_id| Nep171Controller::load_token(self, token_id).unwrap_or_else(|| {
-                            #near_sdk::env::panic_str(&format!("Inconsistent state: Token `{}` is in the enumeration set but its metadata could not be loaded.", token_id))
+                            #me::tools_panic!(#panic_prefix, "Inconsistent state: Token `{}` is in the enumeration set but its metadata could not be loaded.", token_id)
                         }));
 
                     if let Some(limit) = limit {
@@ -101,7 +138,7 @@ pub fn expand(meta: Nep181Meta) -> Result<TokenStream, darling::Error> {
                         .iter()
                         .skip(from_index)
                         .map(|token_id| Nep171Controller::load_token(self, token_id).unwrap_or_else(|| {
-                            #near_sdk::env::panic_str(&format!("Inconsistent state: Token `{}` is in the enumeration set but its metadata could not be loaded.", token_id))
+                            #me::tools_panic!(#panic_prefix, "Inconsistent state: Token `{}` is in the enumeration set but its metadata could not be loaded.", token_id)
                         }));
 
                     if let Some(limit) = limit {
@@ -112,5 +149,25 @@ pub fn expand(meta: Nep181Meta) -> Result<TokenStream, darling::Error> {
                 })
             }
         }
-    })
+
+        #visibility
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep181",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::standard::nep181 },
+                    quote! { #me::standard::nep181::Nep181ControllerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
 }
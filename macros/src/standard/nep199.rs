@@ -0,0 +1,130 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{
+    invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim,
+    storage_version::with_storage_version,
+};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(nep199),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct Nep199Meta {
+    pub storage_key: Option<Expr>,
+    pub max_royalties: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep199Meta) -> Result<TokenStream, darling::Error> {
+    let Nep199Meta {
+        storage_key,
+        max_royalties,
+
+        generics,
+        ident,
+        attrs,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Nep199")?;
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+    let max_royalties = max_royalties.map(|max| quote! { const MAX_ROYALTIES: u32 = #max; });
+
+    let expanded = quote! {
+        impl #imp #me::standard::nep199::Nep199ControllerInternal for #ident #ty #wher {
+            #max_royalties
+
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep199::Nep199 for #ident #ty #wher {
+            fn nft_payout(
+                &self,
+                token_id: #me::standard::nep171::TokenId,
+                balance: #near_sdk::json_types::U128,
+                max_len_payout: Option<u32>,
+            ) -> #me::standard::nep199::Payout {
+                #me::standard::nep199::Nep199Controller::payout(
+                    self,
+                    &token_id,
+                    balance.0,
+                    max_len_payout,
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+            }
+
+            #[payable]
+            fn nft_transfer_payout(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                token_id: #me::standard::nep171::TokenId,
+                approval_id: Option<u32>,
+                memo: Option<String>,
+                balance: #near_sdk::json_types::U128,
+                max_len_payout: Option<u32>,
+            ) -> #me::standard::nep199::Payout {
+                use #me::standard::nep171::*;
+
+                #near_sdk::assert_one_yocto();
+
+                let sender_id = #near_sdk::env::predecessor_account_id();
+
+                let transfer = action::Nep171Transfer {
+                    token_id: &token_id,
+                    authorization: approval_id.map(Nep171TransferAuthorization::ApprovalId).unwrap_or(Nep171TransferAuthorization::Owner),
+                    sender_id: &sender_id,
+                    receiver_id: &receiver_id,
+                    memo: memo.as_deref(),
+                    msg: None,
+                    revert: false,
+                    origin: #me::hook::ActionOrigin::External("nft_transfer_payout"),
+                };
+
+                #me::standard::nep199::Nep199Controller::transfer_payout(
+                    self,
+                    &transfer,
+                    balance.0,
+                    max_len_payout,
+                )
+                .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e))
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "Nep199",
+            with_invariants(
+                &me,
+                &attrs,
+                with_storage_version(
+                    &attrs,
+                    quote! { #me::standard::nep199 },
+                    quote! { #me::standard::nep199::Nep199ControllerInternal },
+                    expanded,
+                )?,
+            )?,
+        ),
+    ))
+}
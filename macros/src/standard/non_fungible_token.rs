@@ -3,40 +3,72 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{parse_quote, Expr, Type};
 
-use crate::unitify;
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim, unitify};
 
-use super::{nep145, nep171, nep177, nep178, nep181};
+use super::{nep145, nep171, nep177, nep178, nep181, nep199};
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(non_fungible_token), supports(struct_named))]
+#[darling(
+    attributes(non_fungible_token),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct NonFungibleTokenMeta {
     pub all_hooks: Option<Type>,
+    pub all_hooks_first: darling::util::Flag,
+    #[darling(multiple)]
+    pub extra: Vec<Type>,
 
     // NEP-145 fields
     pub storage_management_storage_key: Option<Expr>,
     pub force_unregister_hook: Option<Type>,
+    pub storage_register_hook: Option<Type>,
+    pub storage_byte_cost: Option<Type>,
+    pub no_storage_events: darling::util::Flag,
+    pub storage_withdraw_to: darling::util::Flag,
+    pub storage_owner_sweep: darling::util::Flag,
+    pub storage_treasury: darling::util::Flag,
+    pub storage_set_bounds: darling::util::Flag,
+    pub storage_bounds_role: Option<Expr>,
 
     // NEP-171 fields
     pub core_storage_key: Option<Expr>,
     pub mint_hook: Option<Type>,
     pub transfer_hook: Option<Type>,
     pub burn_hook: Option<Type>,
+    pub transfer_check: darling::util::Flag,
+    pub gas_config: darling::util::Flag,
+    pub mint_limit_per_account: Option<Expr>,
 
     // NEP-177 fields
     pub metadata_storage_key: Option<Expr>,
+    pub strict_metadata: darling::util::Flag,
 
     // NEP-178 fields
     pub approval_storage_key: Option<Expr>,
     pub approve_hook: Option<Type>,
     pub revoke_hook: Option<Type>,
     pub revoke_all_hook: Option<Type>,
+    pub no_deposit_refund: darling::util::Flag,
+    pub no_approval_events: darling::util::Flag,
+    pub approve_min_deposit: Option<Expr>,
 
     // NEP-181 fields
     pub enumeration_storage_key: Option<Expr>,
+    pub enumeration_hide_role: Option<Expr>,
+
+    // NEP-199 fields
+    pub payouts: darling::util::Flag,
+    pub payouts_storage_key: Option<Expr>,
+    pub payouts_max_royalties: Option<Expr>,
+
+    // Sale fields
+    pub sale: darling::util::Flag,
 
     // darling
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -48,33 +80,90 @@ pub struct NonFungibleTokenMeta {
 pub fn expand(meta: NonFungibleTokenMeta) -> Result<TokenStream, darling::Error> {
     let NonFungibleTokenMeta {
         all_hooks,
+        all_hooks_first,
+        extra,
 
         storage_management_storage_key,
         force_unregister_hook,
+        storage_register_hook,
+        storage_byte_cost,
+        no_storage_events,
+        storage_withdraw_to,
+        storage_owner_sweep,
+        storage_treasury,
+        storage_set_bounds,
+        storage_bounds_role,
 
         core_storage_key,
         mint_hook,
         transfer_hook,
         burn_hook,
+        transfer_check,
+        gas_config,
+        mint_limit_per_account,
 
         metadata_storage_key,
+        strict_metadata,
 
         approval_storage_key,
         approve_hook,
         revoke_hook,
         revoke_all_hook,
+        no_deposit_refund,
+        no_approval_events,
+        approve_min_deposit,
 
         enumeration_storage_key,
+        enumeration_hide_role,
+
+        payouts,
+        payouts_storage_key,
+        payouts_max_royalties,
+
+        sale,
 
         generics,
         ident,
+        attrs,
 
         me,
         near_sdk,
     } = meta;
 
     let all_hooks_inner = unitify(all_hooks.clone());
+    // Extra standards run inside storage accounting, so the storage they use
+    // is charged like that of the built-in standards.
+    let extra = crate::nest(&extra);
     let force_unregister_hook = unitify(force_unregister_hook);
+    let royalties_hook = if payouts.is_present() {
+        quote! { #me::standard::nep199::TokenRoyalties }
+    } else {
+        quote! { () }
+    };
+    // Outside storage accounting: listings are not charged to the owner.
+    let sale_hook = if sale.is_present() {
+        quote! { #me::sale::SaleListings }
+    } else {
+        quote! { () }
+    };
+
+    // The integration hooks always run last, so with `all_hooks_first`,
+    // `all_hooks` is folded into each action-specific hook instead of being
+    // reordered around it.
+    let (nep171_all_hooks, mint_hook, transfer_hook, burn_hook) = if all_hooks_first.is_present() {
+        let first = |h: Option<Type>| {
+            let h = unitify(h);
+            Some(parse_quote! { (#all_hooks_inner, #h) })
+        };
+        (
+            unitify(None),
+            first(mint_hook),
+            first(transfer_hook),
+            first(burn_hook),
+        )
+    } else {
+        (all_hooks_inner.clone(), mint_hook, transfer_hook, burn_hook)
+    };
 
     let expand_nep145 = nep145::expand(nep145::Nep145Meta {
         storage_key: storage_management_storage_key,
@@ -82,8 +171,17 @@ pub fn expand(meta: NonFungibleTokenMeta) -> Result<TokenStream, darling::Error>
         force_unregister_hook: Some(
             parse_quote! { (#force_unregister_hook, #me::standard::nep171::hooks::BurnNep171OnForceUnregisterHook) },
         ),
+        register_hook: storage_register_hook,
+        byte_cost: storage_byte_cost,
+        no_events: no_storage_events,
+        withdraw_to: storage_withdraw_to,
+        owner_sweep: storage_owner_sweep,
+        treasury: storage_treasury,
+        set_bounds: storage_set_bounds,
+        bounds_role: storage_bounds_role,
         generics: generics.clone(),
         ident: ident.clone(),
+        attrs: attrs.clone(),
         me: me.clone(),
         near_sdk: near_sdk.clone(),
     });
@@ -91,26 +189,36 @@ pub fn expand(meta: NonFungibleTokenMeta) -> Result<TokenStream, darling::Error>
     let expand_nep171 = nep171::expand(nep171::Nep171Meta {
         storage_key: core_storage_key,
         all_hooks: Some(parse_quote! { (
-            #all_hooks_inner,
+            #nep171_all_hooks,
+            #sale_hook,
             (
                 #me::standard::nep145::hooks::Nep171StorageAccountingHook,
                 (
                     #me::standard::nep178::TokenApprovals,
-                    #me::standard::nep181::TokenEnumeration,
+                    (
+                        #me::standard::nep181::TokenEnumeration,
+                        (#royalties_hook, #extra),
+                    ),
                 ),
             ),
         ) }),
         mint_hook,
         transfer_hook,
         burn_hook,
+        all_hooks_first: darling::util::Flag::default(),
         check_external_transfer: Some(syn::parse_quote! { #me::standard::nep178::TokenApprovals }),
 
         token_data: Some(
-            syn::parse_quote! { (#me::standard::nep177::TokenMetadata, #me::standard::nep178::TokenApprovals) },
+            syn::parse_quote! { (#me::standard::nep177::TokenMetadata, (#me::standard::nep178::TokenApprovals, #extra)) },
         ),
+        transfer_check,
+        gas_config,
+        owner_token_count: darling::util::Flag::present(),
+        mint_limit_per_account,
 
         generics: generics.clone(),
         ident: ident.clone(),
+        attrs: attrs.clone(),
 
         me: me.clone(),
         near_sdk: near_sdk.clone(),
@@ -118,9 +226,11 @@ pub fn expand(meta: NonFungibleTokenMeta) -> Result<TokenStream, darling::Error>
 
     let expand_nep177 = nep177::expand(nep177::Nep177Meta {
         storage_key: metadata_storage_key,
+        strict_metadata,
 
         generics: generics.clone(),
         ident: ident.clone(),
+        attrs: attrs.clone(),
 
         me: me.clone(),
         near_sdk: near_sdk.clone(),
@@ -132,19 +242,68 @@ pub fn expand(meta: NonFungibleTokenMeta) -> Result<TokenStream, darling::Error>
         approve_hook,
         revoke_hook,
         revoke_all_hook,
+        all_hooks_first,
+        no_deposit_refund,
+        no_events: no_approval_events,
+        approve_min_deposit,
 
         generics: generics.clone(),
         ident: ident.clone(),
+        attrs: attrs.clone(),
         me: me.clone(),
         near_sdk: near_sdk.clone(),
     });
 
+    let (imp, ty, wher) = generics.split_for_impl();
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
+    let compaction = quote! {
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep181::Nep181Compaction for #ident #ty #wher {
+            fn nft_compact_owner_index(&mut self, account_id: #near_sdk::AccountId, limit: u32) {
+                use #me::standard::{nep145::Nep145Controller, nep181::Nep181Controller};
+
+                let storage_usage_start = #near_sdk::env::storage_usage();
+
+                Nep181Controller::compact_owner_index(self, &account_id, limit)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+
+                if Nep145Controller::get_storage_balance(self, &account_id).is_ok() {
+                    Nep145Controller::storage_refund(self, &account_id, storage_usage_start)
+                        .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+                }
+            }
+        }
+    };
+    let compaction = with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "NonFungibleToken",
+            with_invariants(&me, &attrs, compaction)?,
+        ),
+    );
+
     let expand_nep181 = nep181::expand(nep181::Nep181Meta {
         storage_key: enumeration_storage_key,
-        generics,
-        ident,
-        me,
-        near_sdk,
+        hide_role: enumeration_hide_role,
+        generics: generics.clone(),
+        ident: ident.clone(),
+        attrs: attrs.clone(),
+        me: me.clone(),
+        near_sdk: near_sdk.clone(),
+    });
+
+    let expand_nep199 = payouts.is_present().then(|| {
+        nep199::expand(nep199::Nep199Meta {
+            storage_key: payouts_storage_key,
+            max_royalties: payouts_max_royalties,
+            generics: generics.clone(),
+            ident: ident.clone(),
+            attrs: attrs.clone(),
+            me,
+            near_sdk,
+        })
     });
 
     let mut e = darling::Error::accumulator();
@@ -154,6 +313,7 @@ pub fn expand(meta: NonFungibleTokenMeta) -> Result<TokenStream, darling::Error>
     let nep177 = e.handle(expand_nep177);
     let nep178 = e.handle(expand_nep178);
     let nep181 = e.handle(expand_nep181);
+    let nep199 = expand_nep199.and_then(|expanded| e.handle(expanded));
 
     e.finish_with(quote! {
         #nep145
@@ -161,5 +321,7 @@ pub fn expand(meta: NonFungibleTokenMeta) -> Result<TokenStream, darling::Error>
         #nep177
         #nep178
         #nep181
+        #nep199
+        #compaction
     })
 }
@@ -0,0 +1,60 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(stats),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct StatsMeta {
+    pub storage_key: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: StatsMeta) -> Result<TokenStream, darling::Error> {
+    let StatsMeta {
+        storage_key,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Stats")?;
+
+    let expanded = quote! {
+        impl #imp #me::stats::StatsInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::stats::StatsExternal for #ident #ty #wher {
+            fn contract_stats(&self) -> #me::stats::ContractStats {
+                #me::stats::Stats::stats(self)
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Stats", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
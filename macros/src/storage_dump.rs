@@ -0,0 +1,60 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::{manifest::with_manifest, pause_views::with_paused_views};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(storage_dump),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct StorageDumpMeta {
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: StorageDumpMeta) -> Result<TokenStream, darling::Error> {
+    let StorageDumpMeta {
+        generics,
+        ident,
+        attrs,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let expanded = quote! {
+        #[#near_sdk::near_bindgen]
+        impl #imp #ident #ty #wher {
+            pub fn __tools_storage_dump(
+                &self,
+                prefix: Option<#near_sdk::json_types::Base64VecU8>,
+                from: Option<u32>,
+                limit: Option<u32>,
+            ) -> #me::debug::StorageDump {
+                #me::debug::storage_dump(
+                    &prefix.map(|p| p.0).unwrap_or_default(),
+                    from.unwrap_or(0),
+                    limit.unwrap_or(100),
+                )
+            }
+        }
+    };
+
+    Ok(with_manifest(
+        &me,
+        "StorageDump",
+        with_paused_views(&me, &attrs, expanded)?,
+    ))
+}
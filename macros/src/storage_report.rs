@@ -0,0 +1,64 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(storage_report),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct StorageReportMeta {
+    pub storage_key: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: StorageReportMeta) -> Result<TokenStream, darling::Error> {
+    let StorageReportMeta {
+        storage_key,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "StorageReport")?;
+
+    let expanded = quote! {
+        impl #imp #me::storage_report::StorageReportInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::storage_report::StorageReportExternal for #ident #ty #wher {
+            fn storage_report(&self) -> #me::storage_report::StorageUsageReport {
+                #me::storage_report::StorageReport::storage_usage_report(self)
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(
+            &me,
+            "StorageReport",
+            with_invariants(&me, &attrs, expanded)?,
+        ),
+    ))
+}
@@ -0,0 +1,62 @@
+use darling::FromMeta;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{FnArg, ImplItem, Item};
+
+use crate::{mocked_sim::is_near_bindgen, ContractToolsMeta};
+
+/// If the contract opts in with `#[contract_tools(storage_versions)]`, checks
+/// the stored version of a component at the start of every external method
+/// in `tokens`. Change methods also write the version if none is stored.
+///
+/// `module` is the path of the component's module (which declares
+/// `STORAGE_VERSION`), and `internal` is its internal trait (which declares
+/// `root`).
+pub fn with_storage_version(
+    attrs: &[syn::Attribute],
+    module: TokenStream,
+    internal: TokenStream,
+    tokens: TokenStream,
+) -> darling::Result<TokenStream> {
+    let mut enabled = false;
+    for attr in attrs {
+        enabled |= ContractToolsMeta::from_meta(&attr.meta)?
+            .storage_versions
+            .is_present();
+    }
+
+    if !enabled {
+        return Ok(tokens);
+    }
+
+    let mut file: syn::File = syn::parse2(tokens)?;
+
+    for item in &mut file.items {
+        let item = match item {
+            Item::Impl(i) if is_near_bindgen(&i.attrs) => i,
+            _ => continue,
+        };
+
+        for f in &mut item.items {
+            let f = match f {
+                ImplItem::Fn(f) => f,
+                _ => continue,
+            };
+
+            let check = match f.sig.inputs.first() {
+                Some(FnArg::Receiver(r)) if r.mutability.is_some() => quote! { ensure },
+                Some(FnArg::Receiver(_)) => quote! { require },
+                _ => continue,
+            };
+
+            f.block.stmts.insert(
+                0,
+                syn::parse_quote! {
+                    #module::STORAGE_VERSION.#check(&<Self as #internal>::root());
+                },
+            );
+        }
+    }
+
+    Ok(quote! { #file })
+}
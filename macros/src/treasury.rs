@@ -0,0 +1,64 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+use crate::{invariant::with_invariants, manifest::with_manifest, mocked_sim::with_sim};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(treasury),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
+pub struct TreasuryMeta {
+    pub storage_key: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: TreasuryMeta) -> Result<TokenStream, darling::Error> {
+    let TreasuryMeta {
+        storage_key,
+        ident,
+        attrs,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = crate::root_fn(&me, storage_key, &attrs, "Treasury")?;
+
+    let expanded = quote! {
+        impl #imp #me::treasury::TreasuryInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::treasury::TreasuryExternal for #ident #ty #wher {
+            fn treasury_min_reserve(&self) -> #near_sdk::json_types::U128 {
+                <Self as #me::treasury::Treasury>::min_reserve().into()
+            }
+
+            fn treasury_available_balance(&self) -> #near_sdk::json_types::U128 {
+                <Self as #me::treasury::Treasury>::available_balance().into()
+            }
+        }
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Treasury", with_invariants(&me, &attrs, expanded)?),
+    ))
+}
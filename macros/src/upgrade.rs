@@ -1,14 +1,80 @@
-use darling::{FromDeriveInput, FromMeta};
+use darling::{util::Flag, FromDeriveInput, FromMeta};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Expr;
 
+use crate::{manifest::with_manifest, mocked_sim::with_sim, pause_views::with_paused_views};
+
 #[derive(Debug, Clone)]
 pub enum HookBody {
     Empty,
     Custom,
     Owner,
     Role(Box<syn::Expr>),
+    SelfCall,
+    Any(Vec<HookBody>),
+}
+
+impl HookBody {
+    fn parse_authorizer(value: &str) -> Option<Self> {
+        match value.trim() {
+            "owner" => Some(HookBody::Owner),
+            "self" => Some(HookBody::SelfCall),
+            value => value
+                .strip_prefix("role(")
+                .and_then(|s| s.strip_suffix(')'))
+                .and_then(|s| syn::parse_str::<Expr>(s).ok())
+                .map(|e| HookBody::Role(Box::new(e))),
+        }
+    }
+
+    fn parse_any(value: &str) -> Option<Self> {
+        let inner = value.strip_prefix("any(")?.strip_suffix(')')?;
+
+        // Split on top-level commas only, since role expressions may contain
+        // commas inside parentheses.
+        let mut authorizers = vec![];
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (i, c) in inner.char_indices() {
+            match c {
+                '(' | '[' | '{' | '<' => depth += 1,
+                ')' | ']' | '}' | '>' => depth = depth.checked_sub(1)?,
+                ',' if depth == 0 => {
+                    authorizers.push(Self::parse_authorizer(&inner[start..i])?);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if !inner[start..].trim().is_empty() {
+            authorizers.push(Self::parse_authorizer(&inner[start..])?);
+        }
+
+        (!authorizers.is_empty()).then_some(HookBody::Any(authorizers))
+    }
+
+    /// Expression that is true if the predecessor is authorized. Only
+    /// defined for authorizers.
+    fn condition(&self, me: &syn::Path, near_sdk: &syn::Path) -> TokenStream {
+        match self {
+            HookBody::Owner => quote! {
+                <Self as #me::owner::OwnerInternal>::slot_owner().read().as_ref()
+                    == Some(&#near_sdk::env::predecessor_account_id())
+            },
+            HookBody::Role(role) => quote! {
+                <Self as #me::rbac::Rbac>::has_role(&#near_sdk::env::predecessor_account_id(), &#role)
+            },
+            HookBody::SelfCall => quote! {
+                #near_sdk::env::predecessor_account_id() == #near_sdk::env::current_account_id()
+            },
+            HookBody::Any(authorizers) => {
+                let conditions = authorizers.iter().map(|a| a.condition(me, near_sdk));
+                quote! { #( (#conditions) )||* }
+            }
+            HookBody::Empty | HookBody::Custom => unreachable!(),
+        }
+    }
 }
 
 impl FromMeta for HookBody {
@@ -19,18 +85,11 @@ impl FromMeta for HookBody {
     fn from_string(value: &str) -> darling::Result<Self> {
         if value == "empty" {
             Ok(HookBody::Empty)
-        } else if value == "owner" {
-            Ok(HookBody::Owner)
-        } else if let Some(b) = value
-            .strip_prefix("role(")
-            .and_then(|s| s.strip_suffix(')'))
-            .and_then(|s| syn::parse_str::<Expr>(s).ok())
-            .map(|e| HookBody::Role(Box::new(e)))
-        {
+        } else if let Some(b) = Self::parse_authorizer(value).or_else(|| Self::parse_any(value)) {
             Ok(b)
         } else {
             Err(darling::Error::custom(format!(
-                r#"Invalid value "{value}", expected "empty", "owner", or "role(...)""#,
+                r#"Invalid value "{value}", expected "empty", "owner", "self", "role(...)", or "any(...)""#,
             )))
         }
     }
@@ -55,16 +114,25 @@ impl FromMeta for Serializer {
 }
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(upgrade), supports(struct_named))]
+#[darling(
+    attributes(upgrade),
+    forward_attrs(contract_tools),
+    supports(struct_named)
+)]
 pub struct UpgradeMeta {
     pub hook: HookBody,
     pub serializer: Option<Serializer>,
     pub migrate_method_name: Option<String>,
     pub migrate_method_args: Option<Expr>,
     pub migrate_minimum_gas: Option<Expr>,
+    pub busy_guard: Flag,
+    pub history: Flag,
+    pub history_storage_key: Option<Expr>,
+    pub throttle: Option<Expr>,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
+    pub attrs: Vec<syn::Attribute>,
 
     // crates
     #[darling(rename = "crate", default = "crate::default_crate_name")]
@@ -80,9 +148,14 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
         migrate_method_name,
         migrate_method_args,
         migrate_minimum_gas,
+        busy_guard,
+        history,
+        history_storage_key,
+        throttle,
 
         ident,
         generics,
+        attrs,
 
         me,
         near_sdk,
@@ -90,6 +163,8 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
 
     let (imp, ty, wher) = generics.split_for_impl();
 
+    let panic_prefix = crate::panic_prefix(&attrs)?;
+
     // Defaults are defined in main crate.
     // I don't think these defaults can be easily defined using
     // #[darling(default = "...")] because they are different types.
@@ -113,6 +188,26 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
         HookBody::Role(role) => Some(quote! {
             #me::rbac::Rbac::require_role(self, &#role);
         }),
+        HookBody::SelfCall => {
+            let condition = hook.condition(&me, &near_sdk);
+            Some(quote! {
+                if !(#condition) {
+                    #me::tools_panic!(#panic_prefix, "Method is private");
+                }
+            })
+        }
+        HookBody::Any(_) => {
+            let condition = hook.condition(&me, &near_sdk);
+            Some(quote! {
+                if !(#condition) {
+                    #me::tools_panic!(
+                        #panic_prefix,
+                        "{}",
+                        #me::upgrade::UNAUTHORIZED_UPGRADE_MESSAGE,
+                    );
+                }
+            })
+        }
     }
     .map(|body| {
         // Interpolate body if implementation is to be generated.
@@ -125,6 +220,44 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
         }
     });
 
+    let require_not_busy = busy_guard.is_present().then(|| {
+        quote! {
+            <Self as #me::busy::BusyGuard>::require_not_busy();
+        }
+    });
+
+    // `throttle` implies `history`.
+    let (record_upgrade, history) = if history.is_present() || throttle.is_some() {
+        let root = crate::root_fn(&me, history_storage_key, &attrs, "UpgradeHistory")?;
+        let throttle = throttle.map(|throttle| {
+            quote! {
+                const THROTTLE: #me::upgrade::history::UpgradeThrottle = #throttle;
+            }
+        });
+
+        (
+            quote! {
+                #me::upgrade::history::UpgradeHistory::record_upgrade(self, &code)
+                    .unwrap_or_else(|e| #me::tools_panic!(#panic_prefix, "{}", e));
+            },
+            Some(quote! {
+                impl #imp #me::upgrade::history::UpgradeHistoryInternal for #ident #ty #wher {
+                    #throttle
+                    #root
+                }
+
+                #[#near_sdk::near_bindgen]
+                impl #imp #me::upgrade::history::UpgradeHistoryExternal for #ident #ty #wher {
+                    fn upgrade_history(&self) -> Vec<#me::upgrade::history::UpgradeRecord> {
+                        <Self as #me::upgrade::history::UpgradeHistory>::upgrade_history()
+                    }
+                }
+            }),
+        )
+    } else {
+        (quote! {}, None)
+    };
+
     let (serializer_attribute, code_type, code_conversion) =
         match serializer.unwrap_or(Serializer::JsonBase64) {
             Serializer::Borsh => (
@@ -139,12 +272,14 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
             ),
         };
 
-    Ok(quote! {
+    let expanded = quote! {
         #[#near_sdk::near_bindgen]
         impl #imp #ident #ty #wher {
             pub fn upgrade(&mut self, #serializer_attribute code: #code_type) {
                 #me::upgrade::serialized::UpgradeHook::on_upgrade(self);
+                #require_not_busy
                 #code_conversion
+                #record_upgrade
                 #me::upgrade::serialized::upgrade(
                     code,
                     #me::upgrade::PostUpgrade {
@@ -157,5 +292,12 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
         }
 
         #hook_implementation
-    })
+
+        #history
+    };
+
+    Ok(with_sim(
+        &me,
+        with_manifest(&me, "Upgrade", with_paused_views(&me, &attrs, expanded)?),
+    ))
 }
@@ -0,0 +1,269 @@
+//! Approve actions by hash.
+//!
+//! Storing a large action (e.g. an upgrade containing a full WASM blob) in an
+//! action request is expensive. Instead, use [`HashedAction<A>`] as the
+//! approval manager's action type: only the SHA-256 hash of the
+//! Borsh-serialized action is stored, and the full action is supplied when
+//! the request is executed using
+//! [`HashedApprovalManager::execute_request_with_payload`].
+
+use std::marker::PhantomData;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    serde::{Deserialize, Serialize},
+};
+use thiserror::Error;
+
+use super::{
+    Action, ApprovalConfiguration, ApprovalManager, ApprovalManagerInternal, ExecutionError,
+    UnauthorizedAccountError,
+};
+
+/// Error message emitted when a hashed action is executed without its payload
+pub const PAYLOAD_REQUIRED: &str = "Hashed actions must be executed with their payload";
+
+/// Stands in for an action of type `A`, storing only the SHA-256 hash of its
+/// Borsh serialization.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HashedAction<A> {
+    /// SHA-256 hash of the Borsh-serialized action
+    pub hash: [u8; 32],
+    #[borsh_skip]
+    #[serde(skip)]
+    _action: PhantomData<A>,
+}
+
+impl<A: BorshSerialize> HashedAction<A> {
+    /// Hashes an action
+    pub fn new(action: &A) -> Self {
+        Self::from_hash(env::sha256_array(&action.try_to_vec().unwrap()))
+    }
+}
+
+impl<A> HashedAction<A> {
+    /// Creates a hashed action from a precomputed hash, e.g. when the
+    /// payload is too large to be sent to the contract more than once
+    pub fn from_hash(hash: [u8; 32]) -> Self {
+        Self {
+            hash,
+            _action: PhantomData,
+        }
+    }
+
+    /// Does the payload match the hash?
+    pub fn matches(&self, payload: &[u8]) -> bool {
+        env::sha256_array(payload) == self.hash
+    }
+}
+
+/// Hashed actions cannot be executed by
+/// [`ApprovalManager::execute_request`], since the payload is not
+/// available. Use [`HashedApprovalManager::execute_request_with_payload`]
+/// instead.
+impl<A, C> Action<C> for HashedAction<A> {
+    type Output = ();
+
+    fn execute(self, _contract: &mut C) -> Self::Output {
+        env::panic_str(PAYLOAD_REQUIRED)
+    }
+}
+
+/// Errors that may occur when trying to execute a hashed request
+#[derive(Error, Clone, Debug)]
+pub enum PayloadExecutionError<AuthErr, ExecErr> {
+    /// The request could not be executed
+    #[error(transparent)]
+    Execution(#[from] ExecutionError<AuthErr, ExecErr>),
+    /// The payload does not match the hash in the request
+    #[error("Action payload does not match the approved hash")]
+    PayloadMismatch,
+    /// The payload matches the hash, but is not a valid action
+    #[error("Invalid action payload: {0}")]
+    InvalidPayload(String),
+}
+
+/// Executes requests whose actions are stored as hashes
+pub trait HashedApprovalManager<A, S, C>
+where
+    A: Action<Self> + BorshSerialize + BorshDeserialize,
+    S: BorshSerialize + BorshDeserialize + Serialize,
+    C: ApprovalConfiguration<HashedAction<A>, S> + BorshDeserialize + BorshSerialize,
+{
+    /// Verifies that `payload` is the Borsh serialization of the approved
+    /// action, then executes it and removes the request.
+    fn execute_request_with_payload(
+        &mut self,
+        request_id: u32,
+        payload: &[u8],
+    ) -> Result<A::Output, PayloadExecutionError<C::AuthorizationError, C::ExecutionEligibilityError>>;
+}
+
+impl<T, A, S, C> HashedApprovalManager<A, S, C> for T
+where
+    T: ApprovalManagerInternal<HashedAction<A>, S, C>,
+    A: Action<T> + BorshSerialize + BorshDeserialize,
+    S: BorshSerialize + BorshDeserialize + Serialize,
+    C: ApprovalConfiguration<HashedAction<A>, S> + BorshDeserialize + BorshSerialize,
+{
+    fn execute_request_with_payload(
+        &mut self,
+        request_id: u32,
+        payload: &[u8],
+    ) -> Result<A::Output, PayloadExecutionError<C::AuthorizationError, C::ExecutionEligibilityError>>
+    {
+        Self::is_approved_for_execution(request_id)
+            .map_err(ExecutionError::ExecutionEligibility)?;
+
+        let predecessor = env::predecessor_account_id();
+        let config = Self::get_config();
+
+        let mut request_slot = Self::slot_request(request_id);
+        let request = request_slot.read().unwrap();
+
+        config
+            .is_account_authorized(&predecessor, &request)
+            .map_err(|e| ExecutionError::from(UnauthorizedAccountError(predecessor, e)))?;
+
+        if !request.action.matches(payload) {
+            return Err(PayloadExecutionError::PayloadMismatch);
+        }
+
+        let action = A::try_from_slice(payload)
+            .map_err(|e| PayloadExecutionError::InvalidPayload(e.to_string()))?;
+
+        let result = action.execute(self);
+        request_slot.remove();
+        Self::slot_request_summary(request_id).remove();
+        super::remove_request_key::<Self, HashedAction<A>, S, C>(request_id);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId,
+    };
+
+    use crate::{
+        approval::{
+            simple_multisig::{AccountAuthorizer, ApprovalState, Configuration},
+            ApprovalManager, ApprovalManagerInternal,
+        },
+        slot::Slot,
+    };
+
+    use super::*;
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct Upgrade {
+        code: Vec<u8>,
+    }
+
+    impl Action<Contract> for Upgrade {
+        type Output = usize;
+
+        fn execute(self, _contract: &mut Contract) -> Self::Output {
+            self.code.len()
+        }
+    }
+
+    #[near_bindgen]
+    struct Contract {}
+
+    impl ApprovalManagerInternal<HashedAction<Upgrade>, ApprovalState, Configuration<Self>>
+        for Contract
+    {
+        fn root() -> Slot<()> {
+            Slot::new(b"h")
+        }
+    }
+
+    impl AccountAuthorizer for Contract {
+        type AuthorizationError = ();
+
+        fn is_account_authorized(_account_id: &AccountId) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    fn predecessor(account_id: &str) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.parse().unwrap())
+            .build());
+    }
+
+    #[test]
+    fn execute_with_payload() {
+        let mut contract = Contract {};
+        <Contract as ApprovalManager<_, _, _>>::init(Configuration::new(2, 0));
+
+        let upgrade = Upgrade {
+            code: vec![0; 10_000],
+        };
+        let payload = upgrade.try_to_vec().unwrap();
+
+        predecessor("alice");
+        let request_id = contract
+            .create_request(HashedAction::new(&upgrade), ApprovalState::new())
+            .unwrap();
+        contract.approve_request(request_id).unwrap();
+        predecessor("bob");
+        contract.approve_request(request_id).unwrap();
+
+        let wrong_payload = Upgrade { code: vec![1; 10] }.try_to_vec().unwrap();
+        assert!(matches!(
+            contract.execute_request_with_payload(request_id, &wrong_payload),
+            Err(PayloadExecutionError::PayloadMismatch),
+        ));
+
+        assert_eq!(
+            contract
+                .execute_request_with_payload(request_id, &payload)
+                .unwrap(),
+            10_000,
+        );
+        assert!(<Contract as ApprovalManager<_, _, _>>::get_request(request_id).is_none());
+        assert!(<Contract as ApprovalManager<_, _, _>>::get_request_summary(request_id).is_none());
+    }
+
+    #[test]
+    fn execute_with_payload_releases_key() {
+        let mut contract = Contract {};
+        <Contract as ApprovalManager<_, _, _>>::init(Configuration::new(1, 0));
+
+        let upgrade = Upgrade { code: vec![0; 10] };
+        let payload = upgrade.try_to_vec().unwrap();
+
+        predecessor("alice");
+        let request_id = contract
+            .create_request_idempotent(
+                "upgrade".to_string(),
+                HashedAction::new(&upgrade),
+                ApprovalState::new(),
+                None,
+            )
+            .unwrap();
+        contract.approve_request(request_id).unwrap();
+        contract
+            .execute_request_with_payload(request_id, &payload)
+            .unwrap();
+
+        let next_id = contract
+            .create_request_idempotent(
+                "upgrade".to_string(),
+                HashedAction::new(&upgrade),
+                ApprovalState::new(),
+                None,
+            )
+            .unwrap();
+        assert_ne!(next_id, request_id);
+    }
+}
@@ -8,14 +8,18 @@ use near_sdk::{
 };
 use thiserror::Error;
 
-use crate::{slot::Slot, DefaultStorageKey};
+use crate::{slot::Slot, utils::log::debug, DefaultStorageKey};
+
+pub use crate::utils::AlreadyInitializedError;
 
 /// Error message emitted when the component is used before it is initialized
 pub const NOT_INITIALIZED: &str = "init must be called before use";
 /// Error message emitted when the init function is called multiple times
 pub const ALREADY_INITIALIZED: &str = "init can only be called once";
 
+pub mod hashed;
 pub mod native_transaction_action;
+pub mod queue;
 pub mod simple_multisig;
 
 /// Actions can be executed after they are approved
@@ -74,11 +78,74 @@ pub struct ActionRequest<A, S> {
     pub approval_state: S,
 }
 
+/// Maximum length of [`RequestMetadata::title`], in bytes
+pub const MAX_TITLE_LENGTH: usize = 128;
+/// Maximum length of [`RequestMetadata::description`], in bytes
+pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+/// Maximum length of [`RequestMetadata::url`], in bytes
+pub const MAX_URL_LENGTH: usize = 256;
+/// Maximum length of a request idempotency key, in bytes
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 64;
+
+/// Human-readable context for an action request, e.g. for display by a
+/// multisig frontend
+#[derive(
+    Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RequestMetadata {
+    /// Short title of the request
+    pub title: String,
+    /// Longer description of the request
+    pub description: Option<String>,
+    /// Link to further information, e.g. a forum discussion
+    pub url: Option<String>,
+}
+
+impl RequestMetadata {
+    /// Checks that the fields do not exceed their maximum lengths
+    pub fn validate(&self) -> Result<(), RequestMetadataTooLongError> {
+        let check = |field: &'static str, value: Option<&String>, max_length: usize| match value {
+            Some(value) if value.len() > max_length => {
+                Err(RequestMetadataTooLongError { field, max_length })
+            }
+            _ => Ok(()),
+        };
+
+        check("title", Some(&self.title), MAX_TITLE_LENGTH)?;
+        check(
+            "description",
+            self.description.as_ref(),
+            MAX_DESCRIPTION_LENGTH,
+        )?;
+        check("url", self.url.as_ref(), MAX_URL_LENGTH)?;
+
+        Ok(())
+    }
+}
+
+/// Summary of an action request, readable without deserializing the action
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RequestSummary {
+    /// ID of the request
+    pub request_id: u32,
+    /// Account that created the request
+    pub created_by: AccountId,
+    /// Network timestamp when the request was created
+    pub created_at_nanoseconds: u64,
+    /// Metadata attached to the request, if any
+    pub metadata: Option<RequestMetadata>,
+}
+
 #[derive(BorshSerialize, BorshStorageKey)]
-enum ApprovalStorageKey {
+enum ApprovalStorageKey<'a> {
     NextRequestId,
     Config,
     Request(u32),
+    Summary(u32),
+    RequestByKey(&'a str),
+    RequestKey(u32),
 }
 
 /// The account is ineligile to perform an action for some reason
@@ -108,12 +175,38 @@ pub enum ExecutionError<AuthErr, ExecErr> {
     ExecutionEligibility(ExecErr),
 }
 
+/// A field of the request metadata exceeds its maximum length
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Request metadata field '{field}' exceeds maximum length of {max_length} bytes")]
+pub struct RequestMetadataTooLongError {
+    /// Name of the field
+    pub field: &'static str,
+    /// Maximum length of the field, in bytes
+    pub max_length: usize,
+}
+
 /// Errors that may occur when trying to create a request
 #[derive(Error, Clone, Debug)]
 pub enum CreationError<AuthErr> {
     /// The account is not allowed to act on requests
     #[error(transparent)]
     UnauthorizedAccount(#[from] UnauthorizedAccountError<AuthErr>),
+    /// The request metadata is invalid
+    #[error(transparent)]
+    InvalidMetadata(#[from] RequestMetadataTooLongError),
+    /// The idempotency key is empty or exceeds
+    /// [`MAX_IDEMPOTENCY_KEY_LENGTH`]
+    #[error("Idempotency key must be between 1 and {MAX_IDEMPOTENCY_KEY_LENGTH} bytes")]
+    InvalidIdempotencyKey,
+    /// The idempotency key belongs to a pending request created by another
+    /// account
+    #[error("Idempotency key is used by request {request_id} created by '{created_by}'")]
+    IdempotencyKeyConflict {
+        /// ID of the existing request
+        request_id: u32,
+        /// Account that created the existing request
+        created_by: AccountId,
+    },
 }
 
 /// Errors that may occur when trying to remove a request
@@ -154,6 +247,22 @@ where
     fn slot_request(request_id: u32) -> Slot<ActionRequest<A, S>> {
         Self::root().field(ApprovalStorageKey::Request(request_id))
     }
+
+    /// Summaries of pending action requests, stored separately so that they
+    /// can be read without deserializing the action.
+    fn slot_request_summary(request_id: u32) -> Slot<RequestSummary> {
+        Self::root().field(ApprovalStorageKey::Summary(request_id))
+    }
+
+    /// ID of the pending request created with an idempotency key.
+    fn slot_request_by_key(key: &str) -> Slot<u32> {
+        Self::root().field(ApprovalStorageKey::RequestByKey(key))
+    }
+
+    /// Idempotency key of a pending request, if it was created with one.
+    fn slot_request_key(request_id: u32) -> Slot<String> {
+        Self::root().field(ApprovalStorageKey::RequestKey(request_id))
+    }
 }
 
 /// Collection of action requests that manages their approval state and
@@ -171,15 +280,63 @@ where
     /// Get a request by ID
     fn get_request(request_id: u32) -> Option<ActionRequest<A, S>>;
 
+    /// Get the summary of a request by ID. Returns `None` for requests that
+    /// do not exist, or were created before summaries were recorded.
+    fn get_request_summary(request_id: u32) -> Option<RequestSummary>;
+
+    /// Summaries of the existing requests with IDs in the range
+    /// `[from_index, from_index + limit)`, in ascending order of ID.
+    fn list_request_summaries(from_index: u32, limit: u32) -> Vec<RequestSummary>;
+
+    /// ID of the pending request created with the idempotency key `key`, if
+    /// any. See [`ApprovalManager::create_request_idempotent`].
+    fn find_request_by_key(key: &str) -> Option<u32>;
+
     /// Must be called before using the Approval construct. Can only be called
     /// once.
     fn init(config: C);
 
+    /// Like [`ApprovalManager::init`], but returns an error instead of
+    /// panicking if the component is already initialized.
+    fn try_init(config: C) -> Result<(), AlreadyInitializedError>;
+
+    /// Whether [`ApprovalManager::init`] has been called.
+    fn is_initialized() -> bool;
+
     /// Creates a new action request initialized with the given approval state
     fn create_request(
         &mut self,
         action: A,
         approval_state: S,
+    ) -> Result<u32, CreationError<C::AuthorizationError>> {
+        self.create_request_with_metadata(action, approval_state, None)
+    }
+
+    /// Like [`ApprovalManager::create_request`], but attaches metadata to
+    /// the request, which is returned by the summary views.
+    fn create_request_with_metadata(
+        &mut self,
+        action: A,
+        approval_state: S,
+        metadata: Option<RequestMetadata>,
+    ) -> Result<u32, CreationError<C::AuthorizationError>>;
+
+    /// Like [`ApprovalManager::create_request_with_metadata`], but with a
+    /// client-supplied idempotency key. If a pending request was already
+    /// created with the same key by the predecessor, its ID is returned and
+    /// no new request is created, so a client can safely resubmit a request
+    /// (e.g. after a timeout) without creating duplicates. The existing
+    /// request is not compared with the resubmitted one.
+    ///
+    /// Keys are shared by all accounts: using a key of another account's
+    /// pending request fails. A key can be reused once its request is
+    /// executed or removed.
+    fn create_request_idempotent(
+        &mut self,
+        key: String,
+        action: A,
+        approval_state: S,
+        metadata: Option<RequestMetadata>,
     ) -> Result<u32, CreationError<C::AuthorizationError>>;
 
     /// Executes an action request and removes it from the collection if the
@@ -223,18 +380,54 @@ where
         Self::slot_request(request_id).read()
     }
 
+    fn get_request_summary(request_id: u32) -> Option<RequestSummary> {
+        Self::slot_request_summary(request_id).read()
+    }
+
+    fn list_request_summaries(from_index: u32, limit: u32) -> Vec<RequestSummary> {
+        let end = from_index
+            .saturating_add(limit)
+            .min(Self::slot_next_request_id().read().unwrap_or(0));
+
+        (from_index..end)
+            .filter_map(Self::get_request_summary)
+            .collect()
+    }
+
+    fn find_request_by_key(key: &str) -> Option<u32> {
+        Self::slot_request_by_key(key).read()
+    }
+
     fn init(config: C) {
-        require!(
-            Self::slot_config().swap(&config).is_none(),
-            ALREADY_INITIALIZED,
-        );
+        require!(Self::try_init(config).is_ok(), ALREADY_INITIALIZED);
     }
 
-    fn create_request(
+    fn try_init(config: C) -> Result<(), AlreadyInitializedError> {
+        if Self::is_initialized() {
+            return Err(AlreadyInitializedError {
+                component: "Approval manager",
+            });
+        }
+
+        Self::slot_config().write(&config);
+
+        Ok(())
+    }
+
+    fn is_initialized() -> bool {
+        Self::slot_config().exists()
+    }
+
+    fn create_request_with_metadata(
         &mut self,
         action: A,
         approval_state: S,
+        metadata: Option<RequestMetadata>,
     ) -> Result<u32, CreationError<C::AuthorizationError>> {
+        if let Some(metadata) = &metadata {
+            metadata.validate()?;
+        }
+
         let request_id = Self::slot_next_request_id().read().unwrap_or(0);
 
         let request = ActionRequest {
@@ -247,10 +440,62 @@ where
 
         config
             .is_account_authorized(&predecessor, &request)
-            .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
+            .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
 
         Self::slot_next_request_id().write(&(request_id + 1));
         Self::slot_request(request_id).write(&request);
+        Self::slot_request_summary(request_id).write(&RequestSummary {
+            request_id,
+            created_by: predecessor,
+            created_at_nanoseconds: env::block_timestamp(),
+            metadata,
+        });
+
+        Ok(request_id)
+    }
+
+    fn create_request_idempotent(
+        &mut self,
+        key: String,
+        action: A,
+        approval_state: S,
+        metadata: Option<RequestMetadata>,
+    ) -> Result<u32, CreationError<C::AuthorizationError>> {
+        if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+            return Err(CreationError::InvalidIdempotencyKey);
+        }
+
+        let request_id = match Self::find_request_by_key(&key) {
+            Some(request_id) => request_id,
+            None => {
+                let request_id =
+                    self.create_request_with_metadata(action, approval_state, metadata)?;
+                Self::slot_request_by_key(&key).write(&request_id);
+                Self::slot_request_key(request_id).write(&key);
+                return Ok(request_id);
+            }
+        };
+
+        let request = ActionRequest {
+            action,
+            approval_state,
+        };
+        let predecessor = env::predecessor_account_id();
+
+        Self::get_config()
+            .is_account_authorized(&predecessor, &request)
+            .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
+
+        let created_by = Self::get_request_summary(request_id)
+            .map(|summary| summary.created_by)
+            .unwrap_or_else(|| env::panic_str("Request summary is missing"));
+
+        if created_by != predecessor {
+            return Err(CreationError::IdempotencyKeyConflict {
+                request_id,
+                created_by,
+            });
+        }
 
         Ok(request_id)
     }
@@ -271,10 +516,14 @@ where
 
         config
             .is_account_authorized(&predecessor, &request)
-            .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
+            .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
+
+        debug!("execution of request {} by {}", request_id, predecessor);
 
         let result = request.action.execute(self);
         request_slot.remove();
+        Self::slot_request_summary(request_id).remove();
+        remove_request_key::<Self, A, S, C>(request_id);
 
         Ok(result)
     }
@@ -328,11 +577,25 @@ where
             .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
 
         request_slot.remove();
+        Self::slot_request_summary(request_id).remove();
+        remove_request_key::<Self, A, S, C>(request_id);
 
         Ok(())
     }
 }
 
+fn remove_request_key<I, A, S, C>(request_id: u32)
+where
+    I: ApprovalManagerInternal<A, S, C>,
+    A: Action<I> + BorshSerialize + BorshDeserialize,
+    S: BorshSerialize + BorshDeserialize + Serialize,
+    C: ApprovalConfiguration<A, S> + BorshDeserialize + BorshSerialize,
+{
+    if let Some(key) = I::slot_request_key(request_id).take() {
+        I::slot_request_by_key(&key).remove();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use near_sdk::{
@@ -348,6 +611,7 @@ mod tests {
 
     use super::{
         Action, ActionRequest, ApprovalConfiguration, ApprovalManager, ApprovalManagerInternal,
+        CreationError,
     };
 
     #[derive(BorshSerialize, BorshStorageKey)]
@@ -576,6 +840,77 @@ mod tests {
         contract.remove_request(request_id).unwrap();
     }
 
+    #[test]
+    fn idempotency_key() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob_acct".parse().unwrap();
+
+        let mut contract = Contract::new(1);
+
+        contract.add_role(alice.clone(), &Role::Multisig);
+        contract.add_role(bob.clone(), &Role::Multisig);
+
+        predecessor(&alice);
+        let request_id = contract
+            .create_request_idempotent(
+                "k".to_string(),
+                MyAction::SayHello,
+                Default::default(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            contract
+                .create_request_idempotent(
+                    "k".to_string(),
+                    MyAction::SayHello,
+                    Default::default(),
+                    None,
+                )
+                .unwrap(),
+            request_id,
+        );
+        assert_eq!(Contract::find_request_by_key("k"), Some(request_id));
+        assert!(matches!(
+            contract.create_request_idempotent(
+                String::new(),
+                MyAction::SayHello,
+                Default::default(),
+                None,
+            ),
+            Err(CreationError::InvalidIdempotencyKey),
+        ));
+
+        predecessor(&bob);
+        assert!(matches!(
+            contract.create_request_idempotent(
+                "k".to_string(),
+                MyAction::SayGoodbye,
+                Default::default(),
+                None,
+            ),
+            Err(CreationError::IdempotencyKeyConflict { request_id: 0, .. }),
+        ));
+
+        contract.approve_request(request_id).unwrap();
+        contract.execute_request(request_id).unwrap();
+        assert_eq!(Contract::find_request_by_key("k"), None);
+
+        let request_id = contract
+            .create_request_idempotent(
+                "k".to_string(),
+                MyAction::SayGoodbye,
+                Default::default(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(request_id, 1);
+        assert_eq!(Contract::find_request_by_key("k"), Some(1));
+
+        contract.remove_request(request_id).unwrap();
+        assert_eq!(Contract::find_request_by_key("k"), None);
+    }
+
     #[test]
     fn dynamic_eligibility() {
         let alice: AccountId = "alice".parse().unwrap();
@@ -609,4 +944,32 @@ mod tests {
 
         assert!(Contract::is_approved_for_execution(request_id).is_ok());
     }
+
+    #[test]
+    fn try_init() {
+        assert!(!<Contract as ApprovalManager<
+            MyAction,
+            MultisigApprovalState,
+            MultisigConfig,
+        >>::is_initialized());
+
+        let _contract = Contract::new(2);
+
+        assert!(<Contract as ApprovalManager<
+            MyAction,
+            MultisigApprovalState,
+            MultisigConfig,
+        >>::is_initialized());
+        assert!(<Contract as ApprovalManager<
+            MyAction,
+            MultisigApprovalState,
+            MultisigConfig,
+        >>::try_init(MultisigConfig { threshold: 3 })
+        .is_err());
+        assert_eq!(
+            <Contract as ApprovalManager<MyAction, MultisigApprovalState, MultisigConfig>>::get_config()
+                .threshold,
+            2,
+        );
+    }
 }
@@ -1,13 +1,31 @@
 //! Approval action type for native NEAR transaction actions (create account,
 //! delete account, add key, delete key, deploy contract, function call, stake,
-//! transfer)
+//! transfer), and [`NativeBatch`], a validating builder that turns these
+//! actions into a [`Promise`] outside of the approval flow.
+//!
+//! ```
+//! use near_sdk::{env, Gas, Promise};
+//! use near_sdk_contract_tools::approval::native_transaction_action::NativeBatch;
+//!
+//! fn create_and_call(public_key: String) -> Promise {
+//!     NativeBatch::new("sub.contract.near".parse().unwrap())
+//!         .create_account()
+//!         .transfer(10u128.pow(24))
+//!         .add_full_access_key(public_key)
+//!         .function_call("init", vec![], 0, Gas(5_000_000_000_000))
+//!         .build()
+//!         .unwrap_or_else(|e| env::panic_str(&e.to_string()))
+//! }
+//! ```
 
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
     json_types::{Base64VecU8, U128, U64},
     serde::{Deserialize, Serialize},
-    AccountId, Gas, Promise,
+    AccountId, Balance, Gas, Promise, PublicKey,
 };
+use thiserror::Error;
 
 /// Every native NEAR action can be mapped to a Promise action.
 /// NOTE: The native ADD_KEY action is split into two: one for adding a
@@ -88,13 +106,176 @@ pub struct NativeTransactionAction {
     pub actions: Vec<PromiseAction>,
 }
 
+impl NativeTransactionAction {
+    /// Checks that the actions can be executed. See [`NativeBatch::validate`].
+    pub fn validate(&self) -> Result<(), NativeBatchError> {
+        validate(&self.receiver_id, &self.actions)
+    }
+}
+
 impl<C> super::Action<C> for NativeTransactionAction {
     type Output = Promise;
 
     fn execute(self, _contract: &mut C) -> Self::Output {
+        NativeBatch::from(self)
+            .build()
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()))
+    }
+}
+
+/// Errors that may occur when validating native actions.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum NativeBatchError {
+    /// A public key could not be parsed.
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(String),
+    /// STAKE actions are only valid on the current account.
+    #[error("Cannot stake on behalf of {0}")]
+    StakeNotOnSelf(AccountId),
+    /// Function names of access keys are comma-separated, so they may not be
+    /// empty or contain commas.
+    #[error("Invalid access key function name: {0:?}")]
+    InvalidFunctionName(String),
+}
+
+/// Builds a [`Promise`] from a batch of [`PromiseAction`]s on a single
+/// receiver, validating the actions first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NativeBatch {
+    /// Receiver of the actions
+    pub receiver_id: AccountId,
+    /// Actions to perform on receiver, in order
+    pub actions: Vec<PromiseAction>,
+}
+
+impl From<NativeTransactionAction> for NativeBatch {
+    fn from(value: NativeTransactionAction) -> Self {
+        Self {
+            receiver_id: value.receiver_id,
+            actions: value.actions,
+        }
+    }
+}
+
+impl From<NativeBatch> for NativeTransactionAction {
+    fn from(value: NativeBatch) -> Self {
+        Self {
+            receiver_id: value.receiver_id,
+            actions: value.actions,
+        }
+    }
+}
+
+impl NativeBatch {
+    /// Creates an empty batch on `receiver_id`.
+    pub fn new(receiver_id: AccountId) -> Self {
+        Self {
+            receiver_id,
+            actions: vec![],
+        }
+    }
+
+    /// Appends an arbitrary action.
+    pub fn action(mut self, action: PromiseAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Appends a CREATE_ACCOUNT action.
+    pub fn create_account(self) -> Self {
+        self.action(PromiseAction::CreateAccount)
+    }
+
+    /// Appends a DEPLOY_CONTRACT action.
+    pub fn deploy_contract(self, code: Vec<u8>) -> Self {
+        self.action(PromiseAction::DeployContract { code: code.into() })
+    }
+
+    /// Appends a FUNCTION_CALL action.
+    pub fn function_call(
+        self,
+        function_name: impl Into<String>,
+        arguments: Vec<u8>,
+        amount: Balance,
+        gas: Gas,
+    ) -> Self {
+        self.action(PromiseAction::FunctionCall {
+            function_name: function_name.into(),
+            arguments: arguments.into(),
+            amount: amount.into(),
+            gas: gas.0.into(),
+        })
+    }
+
+    /// Appends a TRANSFER action.
+    pub fn transfer(self, amount: Balance) -> Self {
+        self.action(PromiseAction::Transfer {
+            amount: amount.into(),
+        })
+    }
+
+    /// Appends a STAKE action. Only valid if the receiver is the current
+    /// account.
+    pub fn stake(self, amount: Balance, public_key: impl Into<String>) -> Self {
+        self.action(PromiseAction::Stake {
+            amount: amount.into(),
+            public_key: public_key.into(),
+        })
+    }
+
+    /// Appends an ADD_KEY action for a full-access key with nonce 0.
+    pub fn add_full_access_key(self, public_key: impl Into<String>) -> Self {
+        self.action(PromiseAction::AddFullAccessKey {
+            public_key: public_key.into(),
+            nonce: None,
+        })
+    }
+
+    /// Appends an ADD_KEY action for a function call access key with nonce
+    /// 0.
+    pub fn add_access_key(
+        self,
+        public_key: impl Into<String>,
+        allowance: Balance,
+        receiver_id: AccountId,
+        function_names: Vec<String>,
+    ) -> Self {
+        self.action(PromiseAction::AddAccessKey {
+            public_key: public_key.into(),
+            allowance: allowance.into(),
+            receiver_id,
+            function_names,
+            nonce: None,
+        })
+    }
+
+    /// Appends a DELETE_KEY action.
+    pub fn delete_key(self, public_key: impl Into<String>) -> Self {
+        self.action(PromiseAction::DeleteKey {
+            public_key: public_key.into(),
+        })
+    }
+
+    /// Appends a DELETE_ACCOUNT action.
+    pub fn delete_account(self, beneficiary_id: AccountId) -> Self {
+        self.action(PromiseAction::DeleteAccount { beneficiary_id })
+    }
+
+    /// Checks that the actions can be executed:
+    ///
+    /// - All public keys parse.
+    /// - STAKE actions are only performed on the current account.
+    /// - Access key function names are not empty and do not contain commas.
+    pub fn validate(&self) -> Result<(), NativeBatchError> {
+        validate(&self.receiver_id, &self.actions)
+    }
+
+    /// Validates the actions and constructs the promise.
+    pub fn build(self) -> Result<Promise, NativeBatchError> {
+        self.validate()?;
+
         let mut promise = Promise::new(self.receiver_id);
 
-        // Construct promise
         for action in self.actions {
             promise = match action {
                 PromiseAction::AddAccessKey {
@@ -104,7 +285,7 @@ impl<C> super::Action<C> for NativeTransactionAction {
                     function_names,
                     nonce,
                 } => promise.add_access_key_with_nonce(
-                    public_key.parse().unwrap(),
+                    parse_public_key(&public_key)?,
                     allowance.into(),
                     receiver_id,
                     function_names.join(","),
@@ -112,7 +293,7 @@ impl<C> super::Action<C> for NativeTransactionAction {
                 ),
                 PromiseAction::AddFullAccessKey { public_key, nonce } => promise
                     .add_full_access_key_with_nonce(
-                        public_key.parse().unwrap(),
+                        parse_public_key(&public_key)?,
                         nonce.map(Into::into).unwrap_or(0),
                     ),
                 PromiseAction::CreateAccount => promise.create_account(),
@@ -130,10 +311,10 @@ impl<C> super::Action<C> for NativeTransactionAction {
                 ),
                 PromiseAction::Transfer { amount } => promise.transfer(amount.into()),
                 PromiseAction::Stake { amount, public_key } => {
-                    promise.stake(amount.into(), public_key.parse().unwrap())
+                    promise.stake(amount.into(), parse_public_key(&public_key)?)
                 }
                 PromiseAction::DeleteKey { public_key } => {
-                    promise.delete_key(public_key.parse().unwrap())
+                    promise.delete_key(parse_public_key(&public_key)?)
                 }
                 PromiseAction::DeleteAccount { beneficiary_id } => {
                     promise.delete_account(beneficiary_id)
@@ -141,6 +322,95 @@ impl<C> super::Action<C> for NativeTransactionAction {
             };
         }
 
-        promise
+        Ok(promise)
+    }
+}
+
+fn parse_public_key(public_key: &str) -> Result<PublicKey, NativeBatchError> {
+    public_key
+        .parse()
+        .map_err(|_| NativeBatchError::InvalidPublicKey(public_key.to_string()))
+}
+
+fn validate(receiver_id: &AccountId, actions: &[PromiseAction]) -> Result<(), NativeBatchError> {
+    for action in actions {
+        match action {
+            PromiseAction::AddAccessKey {
+                public_key,
+                function_names,
+                ..
+            } => {
+                parse_public_key(public_key)?;
+                if let Some(name) = function_names
+                    .iter()
+                    .find(|name| name.is_empty() || name.contains(','))
+                {
+                    return Err(NativeBatchError::InvalidFunctionName(name.clone()));
+                }
+            }
+            PromiseAction::Stake { public_key, .. } => {
+                if receiver_id != &env::current_account_id() {
+                    return Err(NativeBatchError::StakeNotOnSelf(receiver_id.clone()));
+                }
+                parse_public_key(public_key)?;
+            }
+            PromiseAction::AddFullAccessKey { public_key, .. }
+            | PromiseAction::DeleteKey { public_key } => {
+                parse_public_key(public_key)?;
+            }
+            PromiseAction::CreateAccount
+            | PromiseAction::DeployContract { .. }
+            | PromiseAction::FunctionCall { .. }
+            | PromiseAction::Transfer { .. }
+            | PromiseAction::DeleteAccount { .. } => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+
+    const KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+
+    fn contract() -> AccountId {
+        "contract".parse().unwrap()
+    }
+
+    #[test]
+    fn validate() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(contract())
+            .build());
+
+        let batch = NativeBatch::new(contract())
+            .stake(1, KEY)
+            .add_access_key(KEY, 1, contract(), vec!["a".into(), "b".into()])
+            .delete_key(KEY);
+        assert_eq!(batch.validate(), Ok(()));
+        assert!(batch.build().is_ok());
+
+        assert_eq!(
+            NativeBatch::new("other".parse().unwrap())
+                .stake(1, KEY)
+                .validate(),
+            Err(NativeBatchError::StakeNotOnSelf("other".parse().unwrap())),
+        );
+        assert_eq!(
+            NativeBatch::new(contract())
+                .add_full_access_key("not a key")
+                .validate(),
+            Err(NativeBatchError::InvalidPublicKey("not a key".to_string())),
+        );
+        assert_eq!(
+            NativeBatch::new(contract())
+                .add_access_key(KEY, 1, contract(), vec!["a,b".into()])
+                .validate(),
+            Err(NativeBatchError::InvalidFunctionName("a,b".to_string())),
+        );
     }
 }
@@ -0,0 +1,428 @@
+//! Instance-scoped approval queues.
+//!
+//! [`ApprovalManager`](super::ApprovalManager) is implemented on the contract
+//! type and stores its state under a single root, so a contract can only
+//! host one queue per action type. An [`ApprovalQueue`] stores its state
+//! under a root of its own choosing, so a contract may host any number of
+//! independent queues (e.g. an operations multisig and a treasury multisig).
+//!
+//! A queue uses the same storage layout as `ApprovalManager`, so
+//! `ApprovalQueue::new(DefaultStorageKey::ApprovalManager)` operates on the
+//! same requests as the default `ApprovalManager` implementation.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{borsh::{self, BorshSerialize, BorshDeserialize}, near_bindgen, PanicOnDefault};
+//! use near_sdk_contract_tools::approval::{
+//!     queue::ApprovalQueue,
+//!     simple_multisig::{AccountAuthorizer, ApprovalState, Configuration},
+//!     Action,
+//! };
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! enum Transfer {
+//!     All,
+//! }
+//!
+//! impl<C> Action<C> for Transfer {
+//!     type Output = ();
+//!
+//!     fn execute(self, _contract: &mut C) {}
+//! }
+//!
+//! #[derive(BorshSerialize, BorshDeserialize, PanicOnDefault)]
+//! #[near_bindgen]
+//! struct Contract {
+//!     ops: ApprovalQueue<Transfer, ApprovalState, Configuration<Contract>>,
+//!     treasury: ApprovalQueue<Transfer, ApprovalState, Configuration<Contract>>,
+//! }
+//!
+//! impl AccountAuthorizer for Contract {
+//!     type AuthorizationError = ();
+//!
+//!     fn is_account_authorized(_account_id: &near_sdk::AccountId) -> Result<(), ()> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[init]
+//!     pub fn new() -> Self {
+//!         let mut ops = ApprovalQueue::new(b"o");
+//!         ops.init(Configuration::new(2, 0));
+//!         let mut treasury = ApprovalQueue::new(b"t");
+//!         treasury.init(Configuration::new(3, 0));
+//!
+//!         Self { ops, treasury }
+//!     }
+//!
+//!     pub fn execute_treasury(&mut self, request_id: u32) {
+//!         let action = self.treasury.take_approved_action(request_id).unwrap();
+//!         action.execute(self);
+//!     }
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, require,
+    serde::Serialize,
+    IntoStorageKey,
+};
+
+use crate::slot::Slot;
+
+use super::{
+    Action, ActionRequest, AlreadyInitializedError, ApprovalConfiguration, ApprovalError,
+    ApprovalStorageKey, CreationError, ExecutionError, RemovalError, RequestSummary,
+    UnauthorizedAccountError, ALREADY_INITIALIZED, NOT_INITIALIZED,
+};
+
+/// An independent collection of action requests, stored under its own root.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ApprovalQueue<A, S, C> {
+    root: Slot<()>,
+    #[borsh_skip]
+    _marker: PhantomData<(A, S, C)>,
+}
+
+impl<A, S, C> ApprovalQueue<A, S, C>
+where
+    A: BorshSerialize + BorshDeserialize,
+    S: BorshSerialize + BorshDeserialize + Serialize,
+    C: ApprovalConfiguration<A, S> + BorshDeserialize + BorshSerialize,
+{
+    /// Creates a handle to the queue stored under `root`. Does not
+    /// initialize the queue.
+    pub fn new(root: impl IntoStorageKey) -> Self {
+        Self {
+            root: Slot::root(root),
+            _marker: PhantomData,
+        }
+    }
+
+    fn slot_next_request_id(&self) -> Slot<u32> {
+        self.root.field(ApprovalStorageKey::NextRequestId)
+    }
+
+    fn slot_config(&self) -> Slot<C> {
+        self.root.field(ApprovalStorageKey::Config)
+    }
+
+    fn slot_request(&self, request_id: u32) -> Slot<ActionRequest<A, S>> {
+        self.root.field(ApprovalStorageKey::Request(request_id))
+    }
+
+    fn slot_request_summary(&self, request_id: u32) -> Slot<RequestSummary> {
+        self.root.field(ApprovalStorageKey::Summary(request_id))
+    }
+
+    fn slot_request_by_key(&self, key: &str) -> Slot<u32> {
+        self.root.field(ApprovalStorageKey::RequestByKey(key))
+    }
+
+    fn slot_request_key(&self, request_id: u32) -> Slot<String> {
+        self.root.field(ApprovalStorageKey::RequestKey(request_id))
+    }
+
+    /// Releases the idempotency key of a request created through
+    /// [`ApprovalManager`](super::ApprovalManager) on the same root.
+    fn remove_request_key(&self, request_id: u32) {
+        if let Some(key) = self.slot_request_key(request_id).take() {
+            self.slot_request_by_key(&key).remove();
+        }
+    }
+
+    /// Must be called before using the queue. Can only be called once.
+    pub fn init(&mut self, config: C) {
+        require!(self.try_init(config).is_ok(), ALREADY_INITIALIZED);
+    }
+
+    /// Like [`ApprovalQueue::init`], but returns an error instead of
+    /// panicking if the queue is already initialized.
+    pub fn try_init(&mut self, config: C) -> Result<(), AlreadyInitializedError> {
+        if self.is_initialized() {
+            return Err(AlreadyInitializedError {
+                component: "Approval queue",
+            });
+        }
+
+        self.slot_config().write(&config);
+
+        Ok(())
+    }
+
+    /// Whether [`ApprovalQueue::init`] has been called.
+    pub fn is_initialized(&self) -> bool {
+        self.slot_config().exists()
+    }
+
+    /// Reads config from storage. Panics if the queue has not been
+    /// initialized.
+    pub fn get_config(&self) -> C {
+        self.slot_config()
+            .read()
+            .unwrap_or_else(|| env::panic_str(NOT_INITIALIZED))
+    }
+
+    /// Get a request by ID
+    pub fn get_request(&self, request_id: u32) -> Option<ActionRequest<A, S>> {
+        self.slot_request(request_id).read()
+    }
+
+    /// Creates a new action request initialized with the given approval state
+    pub fn create_request(
+        &mut self,
+        action: A,
+        approval_state: S,
+    ) -> Result<u32, CreationError<C::AuthorizationError>> {
+        let request_id = self.slot_next_request_id().read().unwrap_or(0);
+
+        let request = ActionRequest {
+            action,
+            approval_state,
+        };
+
+        let predecessor = env::predecessor_account_id();
+
+        self.get_config()
+            .is_account_authorized(&predecessor, &request)
+            .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
+
+        self.slot_next_request_id().write(&(request_id + 1));
+        self.slot_request(request_id).write(&request);
+
+        Ok(request_id)
+    }
+
+    /// Is the given request ID able to be executed if such a request were to
+    /// be initiated by an authorized account?
+    pub fn is_approved_for_execution(
+        &self,
+        request_id: u32,
+    ) -> Result<(), C::ExecutionEligibilityError> {
+        let request = self.slot_request(request_id).read().unwrap();
+
+        self.get_config().is_approved_for_execution(&request)
+    }
+
+    /// Removes an approved request and returns its action, to be executed by
+    /// the caller. Useful when the queue is a field of the contract that the
+    /// action is executed on.
+    pub fn take_approved_action(
+        &mut self,
+        request_id: u32,
+    ) -> Result<A, ExecutionError<C::AuthorizationError, C::ExecutionEligibilityError>> {
+        self.is_approved_for_execution(request_id)
+            .map_err(ExecutionError::ExecutionEligibility)?;
+
+        let predecessor = env::predecessor_account_id();
+
+        let mut request_slot = self.slot_request(request_id);
+        let request = request_slot.read().unwrap();
+
+        self.get_config()
+            .is_account_authorized(&predecessor, &request)
+            .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
+
+        request_slot.remove();
+        self.slot_request_summary(request_id).remove();
+        self.remove_request_key(request_id);
+
+        Ok(request.action)
+    }
+
+    /// Executes an action request on `contract` and removes it from the
+    /// queue if the approval state of the request is fulfilled.
+    pub fn execute_request<Cont>(
+        &mut self,
+        contract: &mut Cont,
+        request_id: u32,
+    ) -> Result<A::Output, ExecutionError<C::AuthorizationError, C::ExecutionEligibilityError>>
+    where
+        A: Action<Cont>,
+    {
+        Ok(self.take_approved_action(request_id)?.execute(contract))
+    }
+
+    /// Tries to approve the action request designated by the given request ID
+    /// with the given arguments. Panics if the request ID does not exist.
+    pub fn approve_request(
+        &mut self,
+        request_id: u32,
+    ) -> Result<(), ApprovalError<C::AuthorizationError, C::ApprovalError>> {
+        let mut request_slot = self.slot_request(request_id);
+        let mut request = request_slot.read().unwrap();
+
+        let predecessor = env::predecessor_account_id();
+        let config = self.get_config();
+
+        config
+            .is_account_authorized(&predecessor, &request)
+            .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
+
+        config
+            .try_approve_with_authorized_account(predecessor, &mut request)
+            .map_err(ApprovalError::ApprovalError)?;
+
+        request_slot.write(&request);
+
+        Ok(())
+    }
+
+    /// Tries to remove the action request indicated by request_id.
+    pub fn remove_request(
+        &mut self,
+        request_id: u32,
+    ) -> Result<(), RemovalError<C::AuthorizationError, C::RemovalError>> {
+        let mut request_slot = self.slot_request(request_id);
+        let request = request_slot.read().unwrap();
+        let predecessor = env::predecessor_account_id();
+
+        let config = self.get_config();
+
+        config
+            .is_removable(&request)
+            .map_err(RemovalError::RemovalNotAllowed)?;
+
+        config
+            .is_account_authorized(&predecessor, &request)
+            .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
+
+        request_slot.remove();
+        self.slot_request_summary(request_id).remove();
+        self.remove_request_key(request_id);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId};
+
+    use crate::approval::{
+        simple_multisig::{AccountAuthorizer, ApprovalState, Configuration},
+        ApprovalManager, ApprovalManagerInternal,
+    };
+
+    use super::*;
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+    enum Greeting {
+        Hello,
+    }
+
+    impl<C> Action<C> for Greeting {
+        type Output = &'static str;
+
+        fn execute(self, _contract: &mut C) -> Self::Output {
+            "hello"
+        }
+    }
+
+    struct Everyone;
+
+    impl AccountAuthorizer for Everyone {
+        type AuthorizationError = ();
+
+        fn is_account_authorized(_account_id: &AccountId) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    type Queue = ApprovalQueue<Greeting, ApprovalState, Configuration<Everyone>>;
+
+    struct Contract;
+
+    impl ApprovalManagerInternal<Greeting, ApprovalState, Configuration<Everyone>> for Contract {
+        fn root() -> Slot<()> {
+            Slot::new(b"m")
+        }
+    }
+
+    fn predecessor(account_id: &str) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.parse().unwrap())
+            .build());
+    }
+
+    #[test]
+    fn independent_queues() {
+        let mut ops = Queue::new(b"o");
+        let mut treasury = Queue::new(b"t");
+
+        assert!(!ops.is_initialized());
+        ops.init(Configuration::new(1, 0));
+        treasury.init(Configuration::new(2, 0));
+        assert!(ops.try_init(Configuration::new(3, 0)).is_err());
+
+        predecessor("alice");
+        let ops_request = ops
+            .create_request(Greeting::Hello, ApprovalState::new())
+            .unwrap();
+        let treasury_request = treasury
+            .create_request(Greeting::Hello, ApprovalState::new())
+            .unwrap();
+        assert_eq!(ops_request, 0);
+        assert_eq!(treasury_request, 0);
+
+        ops.approve_request(ops_request).unwrap();
+        treasury.approve_request(treasury_request).unwrap();
+
+        assert!(ops.is_approved_for_execution(ops_request).is_ok());
+        assert!(treasury
+            .is_approved_for_execution(treasury_request)
+            .is_err());
+
+        assert_eq!(ops.execute_request(&mut (), ops_request).unwrap(), "hello");
+        assert!(ops.get_request(ops_request).is_none());
+        assert!(treasury.get_request(treasury_request).is_some());
+
+        predecessor("bob");
+        treasury.approve_request(treasury_request).unwrap();
+        assert_eq!(
+            treasury.take_approved_action(treasury_request).unwrap(),
+            Greeting::Hello,
+        );
+    }
+
+    #[test]
+    fn shared_root_releases_key() {
+        let mut contract = Contract;
+        let mut queue = Queue::new(b"m");
+        <Contract as ApprovalManager<_, _, _>>::init(Configuration::new(1, 10));
+
+        predecessor("alice");
+        let create = |contract: &mut Contract| {
+            contract
+                .create_request_idempotent(
+                    "greet".to_string(),
+                    Greeting::Hello,
+                    ApprovalState::new(),
+                    None,
+                )
+                .unwrap()
+        };
+
+        let executed = create(&mut contract);
+        queue.approve_request(executed).unwrap();
+        queue.take_approved_action(executed).unwrap();
+        assert!(<Contract as ApprovalManager<_, _, _>>::get_request_summary(executed).is_none());
+
+        let removed = create(&mut contract);
+        assert_ne!(removed, executed);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("alice".parse().unwrap())
+            .block_timestamp(20)
+            .build());
+        queue.remove_request(removed).unwrap();
+        assert!(<Contract as ApprovalManager<_, _, _>>::get_request_summary(removed).is_none());
+
+        assert_ne!(create(&mut contract), removed);
+    }
+}
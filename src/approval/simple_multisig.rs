@@ -11,7 +11,7 @@ use near_sdk::{
 };
 use thiserror::Error;
 
-use super::{ActionRequest, ApprovalConfiguration};
+use super::{Action, ActionRequest, ApprovalConfiguration};
 
 /// Check which accounts are eligible to submit approvals to an
 /// [ApprovalManager](super::ApprovalManager)
@@ -27,7 +27,7 @@ pub trait AccountAuthorizer {
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Configuration<Au: AccountAuthorizer> {
-    /// How many approvals are required?
+    /// How many approvals from currently authorized accounts are required?
     pub threshold: u8,
     /// A request cannot be executed, and can be deleted by any
     /// approval-eligible member after this period has elapsed.
@@ -144,7 +144,14 @@ where
             return Err(RequestExpiredError.into());
         }
 
-        let current = action_request.approval_state.approved_by.len();
+        // Approvals of accounts that have since lost authorization (e.g.
+        // removed members) do not count.
+        let current = action_request
+            .approval_state
+            .approved_by
+            .iter()
+            .filter(|account_id| Au::is_account_authorized(account_id).is_ok())
+            .count();
         let required = self.threshold as usize;
 
         if current < required {
@@ -196,6 +203,122 @@ where
     }
 }
 
+/// Members and threshold of a simple multisig that governs itself with
+/// [`MemberAction`]s.
+pub trait MultisigMembers {
+    /// The number of members.
+    fn count_members() -> u32;
+
+    /// Whether `account_id` is a member.
+    fn is_member(account_id: &AccountId) -> bool;
+
+    /// The number of approvals required to execute a request.
+    fn threshold() -> u8;
+
+    /// Adds `account_id` to the members. Does not check the threshold.
+    fn add_member(&mut self, account_id: AccountId);
+
+    /// Removes `account_id` from the members. Does not check the threshold.
+    fn remove_member(&mut self, account_id: &AccountId);
+
+    /// Sets the threshold. Does not check the number of members.
+    fn set_threshold(&mut self, threshold: u8);
+}
+
+/// A change to the members or threshold of a simple multisig, which must be
+/// approved by the multisig itself.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum MemberAction {
+    /// Add a member.
+    AddMember {
+        /// Account to add.
+        account_id: AccountId,
+    },
+    /// Remove a member.
+    RemoveMember {
+        /// Account to remove.
+        account_id: AccountId,
+    },
+    /// Change the number of approvals required to execute a request.
+    SetThreshold {
+        /// New threshold.
+        threshold: u8,
+    },
+}
+
+/// Errors that prevent a [`MemberAction`] from being executed
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum MemberActionError {
+    /// The account to add is already a member
+    #[error("Account {0} is already a member")]
+    AlreadyMember(AccountId),
+    /// The account to remove is not a member
+    #[error("Account {0} is not a member")]
+    NotMember(AccountId),
+    /// The threshold must be at least 1
+    #[error("Threshold must be at least 1")]
+    ZeroThreshold,
+    /// The action would leave fewer members than the threshold, locking the
+    /// multisig
+    #[error("Threshold {threshold} cannot be reached by {members} members")]
+    ThresholdUnreachable {
+        /// Required number of approvals
+        threshold: u8,
+        /// Number of members after the action
+        members: u32,
+    },
+}
+
+impl MemberAction {
+    /// Checks whether the action can be executed against the current members
+    /// and threshold.
+    pub fn check<C: MultisigMembers>(&self) -> Result<(), MemberActionError> {
+        let (threshold, members) = match self {
+            Self::AddMember { account_id } => {
+                if C::is_member(account_id) {
+                    return Err(MemberActionError::AlreadyMember(account_id.clone()));
+                }
+                return Ok(());
+            }
+            Self::RemoveMember { account_id } => {
+                if !C::is_member(account_id) {
+                    return Err(MemberActionError::NotMember(account_id.clone()));
+                }
+                (C::threshold(), C::count_members() - 1)
+            }
+            Self::SetThreshold { threshold } => {
+                if *threshold == 0 {
+                    return Err(MemberActionError::ZeroThreshold);
+                }
+                (*threshold, C::count_members())
+            }
+        };
+
+        if members < threshold as u32 {
+            return Err(MemberActionError::ThresholdUnreachable { threshold, members });
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: MultisigMembers> Action<C> for MemberAction {
+    type Output = Result<(), MemberActionError>;
+
+    fn execute(self, contract: &mut C) -> Self::Output {
+        self.check::<C>()?;
+
+        match self {
+            Self::AddMember { account_id } => contract.add_member(account_id),
+            Self::RemoveMember { account_id } => contract.remove_member(&account_id),
+            Self::SetThreshold { threshold } => contract.set_threshold(threshold),
+        }
+
+        Ok(())
+    }
+}
+
 /// Types used by near-sdk-contract-tools-macros
 pub mod macro_types {
     use thiserror::Error;
@@ -0,0 +1,333 @@
+//! Contract-wide account ban list.
+//!
+//! Banned accounts are rejected by the hooks in [`hooks`], which integrate
+//! the ban list with the token standards, so that a single list applies to
+//! every component it is wired into:
+//!
+//! * NEP-141 and NEP-171 transfers (either party) and mints (receiver).
+//! * NEP-178 approvals (token owner or approved account).
+//! * NEP-145 storage registration.
+//!
+//! Burns, revocations, and forced unregistration are not restricted, so that
+//! the holdings of a banned account can still be cleaned up.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::BannedAccounts)
+//! derives a default implementation, where the ban list is managed by
+//! accounts with a configured `Rbac` role.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The banned accounts root storage slot is not used or modified. The
+//!     default key is `~ban`.
+//! * (ERR) Banned accounts cannot take part in hooked actions.
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    env, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+use thiserror::Error;
+
+use crate::{slot::Slot, standard::nep297::Event, DefaultStorageKey};
+
+pub use ext::*;
+
+/// Events emitted when the ban list changes.
+#[event(
+    standard = "x-banned-accounts",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum BannedAccountsEvent {
+    /// An account was banned.
+    Ban {
+        /// The banned account.
+        account_id: AccountId,
+    },
+    /// An account was unbanned.
+    Unban {
+        /// The unbanned account.
+        account_id: AccountId,
+    },
+}
+
+/// Occurs when a banned account takes part in a restricted action.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Account {0} is banned")]
+pub struct AccountBannedError(pub AccountId);
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Count,
+    Account(&'a AccountId),
+}
+
+/// Internal functions for [`BannedAccounts`]. Using these methods may result in unexpected behavior.
+pub trait BannedAccountsInternal {
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::BannedAccounts)
+    }
+
+    /// Storage slot for the number of banned accounts
+    fn slot_count() -> Slot<u32> {
+        Self::root().field(StorageKey::Count)
+    }
+
+    /// Storage slot for the ban flag of an account
+    fn slot_banned(account_id: &AccountId) -> Slot<bool> {
+        Self::root().field(StorageKey::Account(account_id))
+    }
+}
+
+/// A list of accounts that are barred from interacting with the contract's
+/// token standards.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{borsh::{self, BorshSerialize}, near_bindgen, BorshStorageKey};
+/// use near_sdk_contract_tools::{banned_accounts::hooks::BannedAccountsHook, *};
+///
+/// #[derive(BorshSerialize, BorshStorageKey)]
+/// enum Role {
+///     Compliance,
+/// }
+///
+/// #[derive(BannedAccounts, FungibleToken, Rbac)]
+/// #[banned_accounts(role = "Role::Compliance")]
+/// #[fungible_token(all_hooks = "BannedAccountsHook", storage_register_hook = "BannedAccountsHook")]
+/// #[rbac(roles = "Role")]
+/// #[near_bindgen]
+/// struct Contract {}
+/// ```
+pub trait BannedAccounts {
+    /// Whether `account_id` is banned.
+    fn is_banned(account_id: &AccountId) -> bool;
+
+    /// The number of banned accounts.
+    fn banned_count() -> u32;
+
+    /// Bans `account_id`. Returns `false` if it was already banned. Emits
+    /// [`BannedAccountsEvent::Ban`].
+    fn ban(&mut self, account_id: &AccountId) -> bool;
+
+    /// Unbans `account_id`. Returns `false` if it was not banned. Emits
+    /// [`BannedAccountsEvent::Unban`].
+    fn unban(&mut self, account_id: &AccountId) -> bool;
+
+    /// Fails if `account_id` is banned.
+    fn check_not_banned(account_id: &AccountId) -> Result<(), AccountBannedError> {
+        if Self::is_banned(account_id) {
+            Err(AccountBannedError(account_id.clone()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Panics if `account_id` is banned.
+    fn require_not_banned(account_id: &AccountId) {
+        Self::check_not_banned(account_id).unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+}
+
+impl<T: BannedAccountsInternal> BannedAccounts for T {
+    fn is_banned(account_id: &AccountId) -> bool {
+        Self::slot_banned(account_id).exists()
+    }
+
+    fn banned_count() -> u32 {
+        Self::slot_count().read().unwrap_or(0)
+    }
+
+    fn ban(&mut self, account_id: &AccountId) -> bool {
+        let mut slot = Self::slot_banned(account_id);
+        if slot.exists() {
+            return false;
+        }
+
+        slot.write(&true);
+        Self::slot_count().write(&(Self::banned_count() + 1));
+
+        BannedAccountsEvent::Ban {
+            account_id: account_id.clone(),
+        }
+        .emit();
+
+        true
+    }
+
+    fn unban(&mut self, account_id: &AccountId) -> bool {
+        if !Self::slot_banned(account_id).remove() {
+            return false;
+        }
+
+        Self::slot_count().write(&Self::banned_count().saturating_sub(1));
+
+        BannedAccountsEvent::Unban {
+            account_id: account_id.clone(),
+        }
+        .emit();
+
+        true
+    }
+}
+
+pub mod hooks {
+    //! Hooks to enforce [`BannedAccounts`](super::BannedAccounts) in other
+    //! components.
+    //!
+    //! [`BannedAccountsHook`] implements [`Hook`](crate::hook::Hook) for the actions of every
+    //! enabled token standard, so it can be used as `all_hooks` on the
+    //! `FungibleToken` and `NonFungibleToken` derive macros. Storage
+    //! registration is not covered by `all_hooks`, so it must also be set as
+    //! the NEP-145 `register_hook` (`storage_register_hook` on the token
+    //! derive macros).
+
+    /// Rejects actions in which a banned account takes part.
+    pub struct BannedAccountsHook;
+
+    #[cfg(feature = "nep141")]
+    mod nep141 {
+        use crate::{
+            banned_accounts::BannedAccounts,
+            hook::Hook,
+            standard::nep141::{Nep141Burn, Nep141Mint, Nep141Transfer},
+        };
+
+        use super::BannedAccountsHook;
+
+        impl<C: BannedAccounts> Hook<C, Nep141Transfer<'_>> for BannedAccountsHook {
+            fn hook<R>(
+                contract: &mut C,
+                args: &Nep141Transfer<'_>,
+                f: impl FnOnce(&mut C) -> R,
+            ) -> R {
+                C::require_not_banned(args.sender_id);
+                C::require_not_banned(args.receiver_id);
+                f(contract)
+            }
+        }
+
+        impl<C: BannedAccounts> Hook<C, Nep141Mint<'_>> for BannedAccountsHook {
+            fn hook<R>(contract: &mut C, args: &Nep141Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+                C::require_not_banned(args.receiver_id);
+                f(contract)
+            }
+        }
+
+        impl<C> Hook<C, Nep141Burn<'_>> for BannedAccountsHook {}
+    }
+
+    #[cfg(feature = "nep145")]
+    mod nep145 {
+        use crate::{
+            banned_accounts::BannedAccounts,
+            hook::Hook,
+            standard::nep145::{Nep145ForceUnregister, Nep145Register},
+        };
+
+        use super::BannedAccountsHook;
+
+        impl<C: BannedAccounts> Hook<C, Nep145Register<'_>> for BannedAccountsHook {
+            fn hook<R>(
+                contract: &mut C,
+                args: &Nep145Register<'_>,
+                f: impl FnOnce(&mut C) -> R,
+            ) -> R {
+                C::require_not_banned(args.account_id);
+                f(contract)
+            }
+        }
+
+        impl<C> Hook<C, Nep145ForceUnregister<'_>> for BannedAccountsHook {}
+    }
+
+    #[cfg(feature = "nep171")]
+    mod nep171 {
+        use crate::{
+            banned_accounts::BannedAccounts,
+            hook::Hook,
+            standard::nep171::action::{Nep171Burn, Nep171Mint, Nep171Transfer},
+        };
+
+        use super::BannedAccountsHook;
+
+        impl<C: BannedAccounts> Hook<C, Nep171Transfer<'_>> for BannedAccountsHook {
+            fn hook<R>(
+                contract: &mut C,
+                args: &Nep171Transfer<'_>,
+                f: impl FnOnce(&mut C) -> R,
+            ) -> R {
+                C::require_not_banned(args.sender_id);
+                C::require_not_banned(args.receiver_id);
+                f(contract)
+            }
+        }
+
+        impl<C: BannedAccounts> Hook<C, Nep171Mint<'_>> for BannedAccountsHook {
+            fn hook<R>(contract: &mut C, args: &Nep171Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+                C::require_not_banned(args.receiver_id);
+                f(contract)
+            }
+        }
+
+        impl<C> Hook<C, Nep171Burn<'_>> for BannedAccountsHook {}
+    }
+
+    #[cfg(feature = "nep178")]
+    mod nep178 {
+        use crate::{
+            banned_accounts::BannedAccounts,
+            hook::Hook,
+            standard::nep178::action::{Nep178Approve, Nep178Revoke, Nep178RevokeAll},
+        };
+
+        use super::BannedAccountsHook;
+
+        impl<C: BannedAccounts> Hook<C, Nep178Approve<'_>> for BannedAccountsHook {
+            fn hook<R>(
+                contract: &mut C,
+                args: &Nep178Approve<'_>,
+                f: impl FnOnce(&mut C) -> R,
+            ) -> R {
+                C::require_not_banned(args.current_owner_id);
+                C::require_not_banned(args.account_id);
+                f(contract)
+            }
+        }
+
+        impl<C> Hook<C, Nep178Revoke<'_>> for BannedAccountsHook {}
+
+        impl<C> Hook<C, Nep178RevokeAll<'_>> for BannedAccountsHook {}
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, AccountId};
+
+    /// External (public) methods for [`super::BannedAccounts`]
+    #[ext_contract(ext_banned_accounts)]
+    pub trait BannedAccountsExternal {
+        /// Returns whether `account_id` is banned.
+        fn ban_is_banned(&self, account_id: AccountId) -> bool;
+
+        /// Returns the number of banned accounts.
+        fn ban_count(&self) -> u32;
+
+        /// Bans `account_id`. Only callable by accounts with the configured
+        /// role. Requires exactly 1 yoctoNEAR.
+        fn ban_add(&mut self, account_id: AccountId) -> bool;
+
+        /// Unbans `account_id`. Only callable by accounts with the configured
+        /// role. Requires exactly 1 yoctoNEAR.
+        fn ban_remove(&mut self, account_id: AccountId) -> bool;
+    }
+}
@@ -0,0 +1,307 @@
+//! Blue/green storage namespaces for migrations too large for one call.
+//!
+//! A component's state lives under its root storage key. To migrate a large
+//! collection, [`BlueGreen`] copies the state from the active ("blue")
+//! namespace into a fresh staging ("green") namespace over as many keeper
+//! calls as necessary, using a gas-aware [`Resumable`] batch. When the last
+//! batch completes, the pointer to the active namespace is flipped in the
+//! same call, so readers see either the old state or the fully migrated
+//! state, never a mix.
+//!
+//! A component reads its root through the switch by setting its
+//! `storage_key` to the active namespace, e.g.
+//! `#[nep171(storage_key = "BlueGreen::new(DefaultStorageKey::Nep171).active()")]`.
+//! Generation 0 is the original root key itself, so existing contracts can
+//! adopt the switch without moving any state. Later generations are stored
+//! under `~ns`.
+//!
+//! Writes to the active namespace while a migration is in progress are not
+//! copied unless the step function revisits them, so the component should
+//! be paused during the migration. The previous namespace is left in place
+//! after the flip; clear it (e.g. with another batch over
+//! [`BlueGreen::previous`]) to release its storage.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{near_bindgen, store::Vector, Gas};
+//! use near_sdk_contract_tools::{
+//!     blue_green::BlueGreen,
+//!     utils::batch::{BatchStatus, Step},
+//! };
+//!
+//! const ROOT: &[u8] = b"prices";
+//!
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     /// Converts prices from cents to micro-units, a few at a time.
+//!     pub fn migrate_step(&mut self) -> bool {
+//!         let mut switch = BlueGreen::new(ROOT);
+//!         let status = switch.migrate(Gas(10_000_000_000_000), || 0u32, |i, from, to| {
+//!             let old = Vector::<u64>::new(from.key.clone());
+//!             let mut new = Vector::<u64>::new(to.key.clone());
+//!             match old.get(*i) {
+//!                 Some(cents) => {
+//!                     new.push(cents * 10_000);
+//!                     new.flush();
+//!                     *i += 1;
+//!                     Step::Continue
+//!                 }
+//!                 None => Step::Done,
+//!             }
+//!         });
+//!         status == BatchStatus::Done
+//!     }
+//! }
+//! ```
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, BorshStorageKey, Gas, IntoStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+
+use crate::{
+    slot::Slot,
+    standard::nep297::Event,
+    utils::batch::{BatchStatus, Resumable},
+    DefaultStorageKey,
+};
+
+pub use crate::utils::batch::Step;
+
+/// The active namespace of a root was flipped to a new generation.
+#[event(
+    standard = "x-blue-green",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+pub struct NamespaceFlipped {
+    /// The original root key, lossily decoded as UTF-8.
+    pub root: String,
+    /// The newly active generation.
+    pub generation: u32,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Generation(&'a [u8]),
+    Cursor(&'a [u8]),
+    Namespace(u32, &'a [u8]),
+}
+
+/// Switches the storage namespace of a root key between generations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlueGreen {
+    root: Vec<u8>,
+}
+
+impl BlueGreen {
+    /// Creates a switch for the state under `root`.
+    pub fn new(root: impl IntoStorageKey) -> Self {
+        Self {
+            root: root.into_storage_key(),
+        }
+    }
+
+    fn slot_generation(&self) -> Slot<u32> {
+        Slot::root(DefaultStorageKey::Namespace).field(StorageKey::Generation(&self.root))
+    }
+
+    fn slot_cursor<C>(&self) -> Slot<C> {
+        Slot::root(DefaultStorageKey::Namespace).field(StorageKey::Cursor(&self.root))
+    }
+
+    /// The namespace of `generation`. Generation 0 is the original root.
+    pub fn namespace(&self, generation: u32) -> Slot<()> {
+        if generation == 0 {
+            Slot::root(self.root.clone())
+        } else {
+            Slot::root(DefaultStorageKey::Namespace)
+                .ns(StorageKey::Namespace(generation, &self.root))
+        }
+    }
+
+    /// The active generation.
+    pub fn generation(&self) -> u32 {
+        self.slot_generation().read().unwrap_or(0)
+    }
+
+    /// The namespace that currently holds the state.
+    pub fn active(&self) -> Slot<()> {
+        self.namespace(self.generation())
+    }
+
+    /// The namespace that a migration writes to.
+    pub fn staging(&self) -> Slot<()> {
+        self.namespace(self.generation() + 1)
+    }
+
+    /// The namespace that was active before the last flip, if any.
+    pub fn previous(&self) -> Option<Slot<()>> {
+        self.generation()
+            .checked_sub(1)
+            .map(|generation| self.namespace(generation))
+    }
+
+    /// Whether a migration has been started and not yet completed.
+    pub fn is_migrating(&self) -> bool {
+        self.slot_cursor::<()>().exists()
+    }
+
+    /// Runs a batch of a migration from the active namespace to the staging
+    /// namespace. `step` receives the cursor and the two namespaces, and
+    /// follows the rules of [`Resumable::run`]. When `step` returns
+    /// [`Step::Done`], the staging namespace becomes active. Emits
+    /// [`NamespaceFlipped`].
+    pub fn migrate<C: BorshSerialize + BorshDeserialize>(
+        &mut self,
+        gas_reserve: Gas,
+        start: impl FnOnce() -> C,
+        mut step: impl FnMut(&mut C, &Slot<()>, &Slot<()>) -> Step,
+    ) -> BatchStatus {
+        let from = self.active();
+        let to = self.staging();
+
+        let status = Resumable::new(self.slot_cursor(), gas_reserve)
+            .run(start, |cursor| step(cursor, &from, &to));
+
+        if status == BatchStatus::Done {
+            self.flip();
+        }
+
+        status
+    }
+
+    /// Abandons a migration in progress. Anything already written to the
+    /// staging namespace remains, and is overwritten by the next migration
+    /// only where it writes the same keys.
+    pub fn abort(&mut self) {
+        self.slot_cursor::<()>().remove();
+    }
+
+    /// Returns the slot under `to` at the same relative key as `slot` under
+    /// `from`. Useful for copying state with the slot functions of a
+    /// component, which resolve against the active namespace. Panics if
+    /// `slot` is not under `from`.
+    pub fn rebase<T>(slot: &Slot<T>, from: &Slot<()>, to: &Slot<()>) -> Slot<T> {
+        let relative = slot
+            .key
+            .strip_prefix(from.key.as_slice())
+            .unwrap_or_else(|| env::panic_str("Slot is not in the source namespace"));
+
+        to.field(relative.to_vec())
+    }
+
+    fn flip(&mut self) {
+        let generation = self.generation() + 1;
+        self.slot_generation().write(&generation);
+
+        NamespaceFlipped {
+            root: String::from_utf8_lossy(&self.root).into_owned(),
+            generation,
+        }
+        .emit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{
+        test_utils::{get_logs, VMContextBuilder},
+        testing_env,
+    };
+
+    use super::*;
+
+    fn with_prepaid_gas(gas: Gas) {
+        testing_env!(VMContextBuilder::new().prepaid_gas(gas).build());
+    }
+
+    fn item(namespace: &Slot<()>, i: u32) -> Slot<u32> {
+        namespace.field(i.to_le_bytes().to_vec())
+    }
+
+    #[test]
+    fn migrate_and_flip() {
+        let mut switch = BlueGreen::new(b"items".to_vec());
+        assert_eq!(switch.generation(), 0);
+        assert_eq!(switch.active().key, b"items");
+        assert_eq!(switch.previous(), None);
+
+        let active = switch.active();
+        for i in 0..200 {
+            item(&active, i).write(&i);
+        }
+
+        let step = |i: &mut u32, from: &Slot<()>, to: &Slot<()>| match item(from, *i).read() {
+            Some(value) => {
+                item(to, *i).write(&(value * 2));
+                *i += 1;
+                Step::Continue
+            }
+            None => Step::Done,
+        };
+
+        with_prepaid_gas(Gas(10_000_000_000_000));
+        assert_eq!(
+            switch.migrate(Gas(5_000_000_000_000), || 0, step),
+            BatchStatus::Paused,
+        );
+        assert!(switch.is_migrating());
+        assert_eq!(switch.generation(), 0);
+        assert_eq!(item(&switch.active(), 1).read(), Some(1));
+
+        loop {
+            with_prepaid_gas(Gas(20_000_000_000_000));
+            if switch.migrate(Gas(5_000_000_000_000), || 0, step) == BatchStatus::Done {
+                break;
+            }
+        }
+
+        assert!(!switch.is_migrating());
+        assert_eq!(switch.generation(), 1);
+        assert_eq!(switch.previous().unwrap().key, b"items");
+        assert_ne!(switch.active().key, b"items");
+        assert_eq!(item(&switch.active(), 199).read(), Some(398));
+        assert_eq!(item(&switch.previous().unwrap(), 199).read(), Some(199));
+        assert_eq!(
+            get_logs(),
+            vec![NamespaceFlipped {
+                root: "items".to_string(),
+                generation: 1,
+            }
+            .to_event_string()],
+        );
+    }
+
+    #[test]
+    fn generations_do_not_collide() {
+        let a = BlueGreen::new(b"a".to_vec());
+        let b = BlueGreen::new(b"b".to_vec());
+
+        assert_ne!(a.namespace(1), a.namespace(2));
+        assert_ne!(a.namespace(1), b.namespace(1));
+        assert_ne!(a.slot_generation(), b.slot_generation());
+    }
+
+    #[test]
+    fn abort() {
+        let mut switch = BlueGreen::new(b"items".to_vec());
+
+        with_prepaid_gas(Gas(10_000_000_000_000));
+        assert_eq!(
+            switch.migrate(Gas(20_000_000_000_000), || 0u32, |_, _, _| Step::Done),
+            BatchStatus::Paused,
+        );
+        assert!(switch.is_migrating());
+
+        switch.abort();
+        assert!(!switch.is_migrating());
+        assert_eq!(switch.generation(), 0);
+    }
+}
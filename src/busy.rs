@@ -0,0 +1,175 @@
+//! Busy guard pattern tracks operations that span multiple receipts, so that
+//! other components can refuse to proceed while any are in flight.
+//!
+//! Some flows leave the contract in an intermediate state until a later
+//! callback completes them, e.g. an `ft_transfer_call` awaiting
+//! `ft_resolve_transfer`, or a migration staged over several transactions.
+//! Deploying new code in the middle of such a flow can leave the callback
+//! running against a state schema (or logic) it does not expect. [`BusyGuard`]
+//! maintains a counter of pending operations: flows call
+//! [`BusyGuard::enter_busy`] when they start and [`BusyGuard::exit_busy`]
+//! when they complete, and the upgrade component (`#[upgrade(busy_guard)]`)
+//! refuses to upgrade while the counter is nonzero.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::BusyGuard)
+//! derives a default implementation.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The busy guard root storage slot is not used or modified. The
+//!     default key is `~bg`.
+//! * (UB) Every call to [`BusyGuard::enter_busy`] is eventually matched by a
+//!     call to [`BusyGuard::exit_busy`], including when the flow fails.
+//! * (ERR) [`BusyGuard::exit_busy`] may only be called when an operation is
+//!     pending.
+//! * (ERR) [`BusyGuard::require_not_busy`] may only be called when no
+//!     operations are pending.
+use crate::{slot::Slot, DefaultStorageKey};
+use near_sdk::require;
+
+/// Error message emitted when an action is refused due to pending operations.
+pub const BUSY_FAIL_MESSAGE: &str = "Disallowed while operations are pending";
+const NOT_BUSY_FAIL_MESSAGE: &str = "No operations are pending";
+
+/// Internal functions for [`BusyGuard`]. Using these methods may result in unexpected behavior.
+pub trait BusyGuardInternal {
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::BusyGuard)
+    }
+
+    /// Storage slot for the number of pending operations
+    fn slot_busy_count() -> Slot<u32> {
+        Self::root().transmute()
+    }
+}
+
+/// Tracks pending multi-receipt operations.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{env, near_bindgen, Promise};
+/// use near_sdk_contract_tools::{busy::BusyGuard, BusyGuard};
+///
+/// #[derive(BusyGuard)]
+/// #[near_bindgen]
+/// struct Contract {}
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn start(&mut self) -> Promise {
+///         self.enter_busy();
+///         Self::ext(env::current_account_id()).finish()
+///     }
+///
+///     #[private]
+///     pub fn finish(&mut self) {
+///         self.exit_busy();
+///     }
+/// }
+/// ```
+pub trait BusyGuard {
+    /// Returns the number of pending operations.
+    fn busy_count() -> u32;
+
+    /// Returns `true` if any operations are pending.
+    fn is_busy() -> bool {
+        Self::busy_count() > 0
+    }
+
+    /// Records the start of an operation.
+    fn enter_busy(&mut self);
+
+    /// Records the completion of an operation. Panics if no operations are
+    /// pending.
+    fn exit_busy(&mut self);
+
+    /// Rejects if any operations are pending.
+    fn require_not_busy() {
+        require!(!Self::is_busy(), BUSY_FAIL_MESSAGE);
+    }
+}
+
+impl<T: BusyGuardInternal> BusyGuard for T {
+    fn busy_count() -> u32 {
+        Self::slot_busy_count().read().unwrap_or(0)
+    }
+
+    fn enter_busy(&mut self) {
+        Self::slot_busy_count().write(&(Self::busy_count() + 1));
+    }
+
+    fn exit_busy(&mut self) {
+        let count = Self::busy_count();
+        require!(count > 0, NOT_BUSY_FAIL_MESSAGE);
+        Self::slot_busy_count().set(Some(&(count - 1)).filter(|c| **c > 0));
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::ext_contract;
+
+    /// External (public) methods for [`BusyGuard`]
+    #[ext_contract(ext_busy_guard)]
+    pub trait BusyGuardExternal {
+        /// Returns the number of pending operations
+        fn busy_pending_count(&self) -> u32;
+    }
+}
+
+pub use ext::*;
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::near_bindgen;
+    use near_sdk_contract_tools_macros::BusyGuard;
+
+    use super::*;
+
+    #[derive(BusyGuard)]
+    #[busy_guard(crate = "crate")]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn enter_and_exit() {
+        let mut c = Contract {};
+
+        assert!(!Contract::is_busy());
+        Contract::require_not_busy();
+
+        c.enter_busy();
+        c.enter_busy();
+        assert_eq!(Contract::busy_count(), 2);
+
+        c.exit_busy();
+        assert!(Contract::is_busy());
+        c.exit_busy();
+        assert!(!Contract::is_busy());
+        assert!(!Contract::slot_busy_count().exists());
+    }
+
+    #[test]
+    #[should_panic = "Disallowed while operations are pending"]
+    fn require_not_busy_fail() {
+        let mut c = Contract {};
+
+        c.enter_busy();
+        Contract::require_not_busy();
+    }
+
+    #[test]
+    #[should_panic = "No operations are pending"]
+    fn exit_without_enter() {
+        let mut c = Contract {};
+
+        c.exit_busy();
+    }
+}
@@ -0,0 +1,276 @@
+//! Helpers for requesting signatures from the NEAR chain signatures MPC
+//! signer contract.
+//!
+//! Build a [`SignRequest`] from a 32-byte payload hash and a
+//! [`DerivationPath`], turn it into a [`Promise`] with
+//! [`SignRequest::sign`], and parse the result in a callback with
+//! [`parse_signature_result`].
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{near_bindgen, Promise, borsh::{self, BorshSerialize, BorshDeserialize}};
+//! use near_sdk_contract_tools::{
+//!     chainsig::{self, keccak256_payload, SignRequest},
+//!     utils::foreign_address::DerivationPath,
+//! };
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[payable]
+//!     pub fn sign_message(&mut self, message: String) -> Promise {
+//!         SignRequest::new(
+//!             keccak256_payload(message.as_bytes()),
+//!             DerivationPath::indexed("ethereum", 1),
+//!         )
+//!         .sign(
+//!             chainsig::DEFAULT_SIGNER_ACCOUNT_ID.parse().unwrap(),
+//!             near_sdk::env::attached_deposit(),
+//!             chainsig::GAS_FOR_SIGN,
+//!         )
+//!     }
+//! }
+//! ```
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    serde::{Deserialize, Serialize},
+    serde_json, AccountId, Balance, Gas, Promise, PromiseResult,
+};
+use thiserror::Error;
+
+use crate::utils::foreign_address::DerivationPath;
+
+pub use ext::*;
+
+/// Account ID of the canonical MPC signer contract on mainnet.
+pub const DEFAULT_SIGNER_ACCOUNT_ID: &str = "v1.signer";
+/// Account ID of the canonical MPC signer contract on testnet.
+pub const TESTNET_SIGNER_ACCOUNT_ID: &str = "v1.signer-prod.testnet";
+/// Gas to attach to a `sign` call. Signing spans several blocks, so the
+/// signer contract requires a generous amount.
+pub const GAS_FOR_SIGN: Gas = Gas(250_000_000_000_000);
+/// Minimum deposit the signer contract accepts for a `sign` call. The actual
+/// fee may be higher under load; any excess is refunded by the signer.
+pub const MIN_SIGN_DEPOSIT: Balance = 1;
+
+/// Errors that can occur when parsing the result of a `sign` call.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum SignatureResultError {
+    /// The callback was not invoked with exactly one promise result.
+    #[error("Expected exactly one promise result, got {0}")]
+    UnexpectedPromiseResultCount(u64),
+    /// The `sign` call failed.
+    #[error("Signature request failed")]
+    Failed,
+    /// The `sign` call returned something that is not a signature.
+    #[error("Invalid signature response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Arguments for the signer contract's `sign` method.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignRequest {
+    /// 32-byte hash of the data to sign.
+    pub payload: [u8; 32],
+    /// Derivation path of the key to sign with.
+    pub path: String,
+    /// Version of the MPC root key to derive from.
+    pub key_version: u32,
+}
+
+impl SignRequest {
+    /// Creates a request to sign `payload` with the key derived from `path`,
+    /// using key version 0.
+    pub fn new(payload: [u8; 32], path: DerivationPath) -> Self {
+        Self {
+            payload,
+            path: path.0,
+            key_version: 0,
+        }
+    }
+
+    /// Sets the key version.
+    pub fn key_version(mut self, key_version: u32) -> Self {
+        self.key_version = key_version;
+        self
+    }
+
+    /// Creates a promise calling `sign` on `signer_account_id`. The result
+    /// can be parsed with [`parse_signature_result`] in a callback.
+    ///
+    /// # Panics
+    ///
+    /// - If `deposit` is less than [`MIN_SIGN_DEPOSIT`].
+    pub fn sign(self, signer_account_id: AccountId, deposit: Balance, gas: Gas) -> Promise {
+        near_sdk::require!(
+            deposit >= MIN_SIGN_DEPOSIT,
+            "Signature request requires a deposit",
+        );
+
+        ext_signer::ext(signer_account_id)
+            .with_attached_deposit(deposit)
+            .with_static_gas(gas)
+            .sign(self)
+    }
+}
+
+/// Hashes `data` with Keccak-256 to produce a payload, as used by EVM chains.
+pub fn keccak256_payload(data: &[u8]) -> [u8; 32] {
+    env::keccak256_array(data)
+}
+
+/// Hashes `data` with SHA-256 to produce a payload.
+pub fn sha256_payload(data: &[u8]) -> [u8; 32] {
+    env::sha256_array(data)
+}
+
+/// Hex-encoded secp256k1 point.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SerializableAffinePoint {
+    /// Hex-encoded compressed point.
+    pub affine_point: String,
+}
+
+/// Hex-encoded secp256k1 scalar.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SerializableScalar {
+    /// Hex-encoded scalar.
+    pub scalar: String,
+}
+
+/// Signature returned by the signer contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureResponse {
+    /// The `R` point of the signature.
+    pub big_r: SerializableAffinePoint,
+    /// The `s` scalar of the signature.
+    pub s: SerializableScalar,
+    /// Recovery ID (parity of `R`).
+    pub recovery_id: u8,
+}
+
+impl SignatureResponse {
+    /// Returns the signature as 65 bytes: `r || s || recovery_id`, the
+    /// format expected by `ecrecover` and most EVM tooling (add 27 to the
+    /// last byte for legacy `v`).
+    pub fn to_bytes(&self) -> Result<[u8; 65], SignatureResultError> {
+        let big_r = decode_hex(&self.big_r.affine_point)
+            .filter(|b| b.len() == 33)
+            .ok_or_else(|| SignatureResultError::InvalidResponse("invalid `big_r`".to_string()))?;
+        let s = decode_hex(&self.s.scalar)
+            .filter(|b| b.len() == 32)
+            .ok_or_else(|| SignatureResultError::InvalidResponse("invalid `s`".to_string()))?;
+
+        let mut bytes = [0u8; 65];
+        // Compressed point: parity byte followed by the x-coordinate, which is `r`
+        bytes[..32].copy_from_slice(&big_r[1..]);
+        bytes[32..64].copy_from_slice(&s);
+        bytes[64] = self.recovery_id;
+        Ok(bytes)
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parses the result of a `sign` call. Call from within the callback of the
+/// promise returned by [`SignRequest::sign`].
+pub fn parse_signature_result() -> Result<SignatureResponse, SignatureResultError> {
+    let count = env::promise_results_count();
+    if count != 1 {
+        return Err(SignatureResultError::UnexpectedPromiseResultCount(count));
+    }
+
+    match env::promise_result(0) {
+        PromiseResult::Successful(value) => serde_json::from_slice(&value)
+            .map_err(|e| SignatureResultError::InvalidResponse(e.to_string())),
+        _ => Err(SignatureResultError::Failed),
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // ext_contract doesn't play well
+
+    use near_sdk::ext_contract;
+
+    use super::{SignRequest, SignatureResponse};
+
+    /// Interface of the chain signatures MPC signer contract.
+    #[ext_contract(ext_signer)]
+    pub trait Signer {
+        /// Requests a signature of `request.payload` with the key derived
+        /// from the predecessor and `request.path`.
+        fn sign(&mut self, request: SignRequest) -> SignatureResponse;
+
+        /// Returns the root public key of the MPC network.
+        fn public_key(&self) -> near_sdk::PublicKey;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+
+    const RESPONSE: &str = r#"{"big_r":{"affine_point":"0231D9E5B6A7B0A5F1E4A5D8F8C1B7A4E6E2A1C9D3F0E8B7A6C5D4E3F2A1B0C9D8"},"s":{"scalar":"1F2E3D4C5B6A79880796A5B4C3D2E1F00F1E2D3C4B5A69788796A5B4C3D2E1F0"},"recovery_id":1}"#;
+
+    #[test]
+    fn request_serialization() {
+        let request = SignRequest::new([1; 32], DerivationPath::indexed("ethereum", 1));
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["path"], "ethereum-1");
+        assert_eq!(json["key_version"], 0);
+        assert_eq!(json["payload"].as_array().unwrap().len(), 32);
+    }
+
+    #[test]
+    fn parse_result() {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(RESPONSE.as_bytes().to_vec())],
+        );
+
+        let response = parse_signature_result().unwrap();
+        assert_eq!(response.recovery_id, 1);
+
+        let bytes = response.to_bytes().unwrap();
+        assert_eq!(bytes[0], 0x31);
+        assert_eq!(bytes[32], 0x1f);
+        assert_eq!(bytes[64], 1);
+    }
+
+    #[test]
+    fn parse_failed_result() {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+
+        assert_eq!(parse_signature_result(), Err(SignatureResultError::Failed));
+    }
+}
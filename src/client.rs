@@ -0,0 +1,391 @@
+//! Typed off-chain clients for contracts built with this crate. Requires the
+//! `client` feature.
+//!
+//! Each client is generated by the [`ext_client`](crate::ext_client)
+//! attribute from the `#[ext_contract]` trait of a standard (e.g.
+//! [`Nep141Client`] from [`Nep141`](crate::standard::nep141::Nep141)), so it
+//! serializes arguments and deserializes return values with the same types
+//! that the contract uses, and backend services do not need to hand-write
+//! JSON.
+//!
+//! The clients do not depend on any particular RPC library. Implement
+//! [`Transport`] for a wrapper around `near-jsonrpc-client`, a
+//! `near-workspaces` account, or anything else that can submit function
+//! calls.
+//!
+//! View methods are plain `async` functions. Change methods return a [`Call`],
+//! which attaches the deposit required by the standard (e.g. 1 yoctoNEAR for
+//! `ft_transfer`) and a default amount of gas, both of which can be overridden
+//! before calling [`Call::transact`].
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use near_sdk::json_types::U128;
+//! use near_sdk_contract_tools::client::*;
+//!
+//! struct Workspaces(near_workspaces::Account);
+//!
+//! impl Transport for Workspaces {
+//!     type Error = near_workspaces::error::Error;
+//!
+//!     fn view<'a>(
+//!         &'a self,
+//!         contract_id: &'a near_sdk::AccountId,
+//!         method_name: &'a str,
+//!         args: Vec<u8>,
+//!     ) -> BoxFuture<'a, Result<Vec<u8>, Self::Error>> {
+//!         Box::pin(async move {
+//!             let contract_id = contract_id.as_str().parse().unwrap();
+//!             Ok(self.0.view(&contract_id, method_name).args(args).await?.result)
+//!         })
+//!     }
+//!
+//!     fn call<'a>(
+//!         &'a self,
+//!         contract_id: &'a near_sdk::AccountId,
+//!         call: FunctionCall,
+//!     ) -> BoxFuture<'a, Result<Vec<u8>, Self::Error>> {
+//!         Box::pin(async move {
+//!             let contract_id = contract_id.as_str().parse().unwrap();
+//!             let outcome = self
+//!                 .0
+//!                 .call(&contract_id, &call.method_name)
+//!                 .args(call.args)
+//!                 .deposit(call.deposit)
+//!                 .gas(call.gas.0)
+//!                 .transact()
+//!                 .await?
+//!                 .into_result()?;
+//!             Ok(outcome.raw_bytes()?)
+//!         })
+//!     }
+//! }
+//!
+//! # async fn example(account: near_workspaces::Account) {
+//! let client = Nep141Client::new(Workspaces(account), "token.near".parse().unwrap());
+//! client
+//!     .ft_transfer("bob.near".parse().unwrap(), U128(100), None)
+//!     .transact()
+//!     .await
+//!     .unwrap();
+//! let balance = client.ft_balance_of("bob.near".parse().unwrap()).await.unwrap();
+//! # }
+//! ```
+
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+use near_sdk::{
+    serde::{de::DeserializeOwned, Serialize},
+    serde_json, AccountId, Balance, Gas,
+};
+use thiserror::Error;
+
+/// Default amount of gas attached to change method calls.
+pub const DEFAULT_CALL_GAS: Gas = Gas(30_000_000_000_000);
+/// Default amount of gas attached to `*_transfer_call` calls, which must
+/// cover the receiver and resolver callbacks.
+pub const DEFAULT_TRANSFER_CALL_GAS: Gas = Gas(100_000_000_000_000);
+
+/// A boxed, sendable future, as returned by [`Transport`] methods.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A function call to submit in a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionCall {
+    /// Name of the contract method.
+    pub method_name: String,
+    /// Serialized (JSON) arguments.
+    pub args: Vec<u8>,
+    /// Gas to attach.
+    pub gas: Gas,
+    /// Deposit to attach, in yoctoNEAR.
+    pub deposit: Balance,
+}
+
+/// Submits view calls and transactions to the network.
+pub trait Transport {
+    /// Error returned by the underlying RPC library.
+    type Error;
+
+    /// Calls a view method, returning the raw result.
+    fn view<'a>(
+        &'a self,
+        contract_id: &'a AccountId,
+        method_name: &'a str,
+        args: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Self::Error>>;
+
+    /// Signs and submits a function call, waits for the final outcome, and
+    /// returns the raw result. Failed executions should be reported as errors.
+    fn call<'a>(
+        &'a self,
+        contract_id: &'a AccountId,
+        call: FunctionCall,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Self::Error>>;
+}
+
+impl<T: Transport> Transport for &T {
+    type Error = T::Error;
+
+    fn view<'a>(
+        &'a self,
+        contract_id: &'a AccountId,
+        method_name: &'a str,
+        args: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Self::Error>> {
+        T::view(self, contract_id, method_name, args)
+    }
+
+    fn call<'a>(
+        &'a self,
+        contract_id: &'a AccountId,
+        call: FunctionCall,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Self::Error>> {
+        T::call(self, contract_id, call)
+    }
+}
+
+/// Errors that may occur when calling a contract through a client.
+#[derive(Error, Debug)]
+pub enum ClientError<E> {
+    /// The transport failed to submit the call, or the call failed.
+    #[error("Transport error: {0}")]
+    Transport(E),
+    /// The arguments could not be serialized, or the result could not be
+    /// deserialized.
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+fn decode<R: DeserializeOwned>(bytes: &[u8]) -> Result<R, serde_json::Error> {
+    // Methods without a return value produce an empty result.
+    serde_json::from_slice(if bytes.is_empty() { b"null" } else { bytes })
+}
+
+/// A change method call, ready to be submitted with [`Call::transact`].
+#[must_use = "Call::transact must be called to submit the call"]
+pub struct Call<'a, T, R> {
+    transport: &'a T,
+    contract_id: &'a AccountId,
+    call: Result<FunctionCall, serde_json::Error>,
+    _return: PhantomData<fn() -> R>,
+}
+
+impl<'a, T: Transport, R: DeserializeOwned> Call<'a, T, R> {
+    /// Creates a call of the change method `method_name` with
+    /// JSON-serialized `args`.
+    pub fn new(
+        transport: &'a T,
+        contract_id: &'a AccountId,
+        method_name: &str,
+        args: impl Serialize,
+        gas: Gas,
+        deposit: Balance,
+    ) -> Self {
+        Self {
+            transport,
+            contract_id,
+            call: serde_json::to_vec(&args).map(|args| FunctionCall {
+                method_name: method_name.to_string(),
+                args,
+                gas,
+                deposit,
+            }),
+            _return: PhantomData,
+        }
+    }
+
+    /// Overrides the attached deposit.
+    pub fn deposit(mut self, deposit: Balance) -> Self {
+        if let Ok(call) = &mut self.call {
+            call.deposit = deposit;
+        }
+        self
+    }
+
+    /// Overrides the attached gas.
+    pub fn gas(mut self, gas: Gas) -> Self {
+        if let Ok(call) = &mut self.call {
+            call.gas = gas;
+        }
+        self
+    }
+
+    /// Submits the call and deserializes its result.
+    pub async fn transact(self) -> Result<R, ClientError<T::Error>> {
+        let result = self
+            .transport
+            .call(self.contract_id, self.call?)
+            .await
+            .map_err(ClientError::Transport)?;
+
+        Ok(decode(&result)?)
+    }
+}
+
+/// Calls the view method `method_name` with JSON-serialized `args`, and
+/// deserializes its result.
+pub async fn view<T: Transport, R: DeserializeOwned>(
+    transport: &T,
+    contract_id: &AccountId,
+    method_name: &str,
+    args: impl Serialize,
+) -> Result<R, ClientError<T::Error>> {
+    let args = serde_json::to_vec(&args)?;
+    let result = transport
+        .view(contract_id, method_name, args)
+        .await
+        .map_err(ClientError::Transport)?;
+
+    Ok(decode(&result)?)
+}
+
+pub use crate::standard::{
+    nep141::Nep141Client, nep145::Nep145Client, nep148::Nep148Client, nep171::Nep171Client,
+    nep177::Nep177Client, nep178::Nep178Client, nep181::Nep181Client, nep199::Nep199Client,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Mutex,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    use near_sdk::{json_types::U128, serde_json::json};
+
+    use super::*;
+
+    /// Polls a future that is ready immediately.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        fn raw() -> RawWaker {
+            fn clone(_: *const ()) -> RawWaker {
+                raw()
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+
+        let waker = unsafe { Waker::from_raw(raw()) };
+        let mut future = Box::pin(future);
+        match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future not ready"),
+        }
+    }
+
+    #[derive(Default)]
+    struct Mock {
+        response: Mutex<Vec<u8>>,
+        calls: Mutex<Vec<(AccountId, FunctionCall)>>,
+    }
+
+    impl Transport for Mock {
+        type Error = String;
+
+        fn view<'a>(
+            &'a self,
+            contract_id: &'a AccountId,
+            method_name: &'a str,
+            args: Vec<u8>,
+        ) -> BoxFuture<'a, Result<Vec<u8>, Self::Error>> {
+            self.calls.lock().unwrap().push((
+                contract_id.clone(),
+                FunctionCall {
+                    method_name: method_name.to_string(),
+                    args,
+                    gas: Gas(0),
+                    deposit: 0,
+                },
+            ));
+            let response = self.response.lock().unwrap().clone();
+            Box::pin(async move { Ok(response) })
+        }
+
+        fn call<'a>(
+            &'a self,
+            contract_id: &'a AccountId,
+            call: FunctionCall,
+        ) -> BoxFuture<'a, Result<Vec<u8>, Self::Error>> {
+            self.calls.lock().unwrap().push((contract_id.clone(), call));
+            let response = self.response.lock().unwrap().clone();
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    #[test]
+    fn typed_calls() {
+        let mock = Mock::default();
+        let token: AccountId = "token.near".parse().unwrap();
+        let bob: AccountId = "bob.near".parse().unwrap();
+        let client = Nep141Client::new(&mock, token.clone());
+
+        *mock.response.lock().unwrap() = b"\"42\"".to_vec();
+        let balance = block_on(client.ft_balance_of(bob.clone())).unwrap();
+        assert_eq!(balance, U128(42));
+
+        *mock.response.lock().unwrap() = vec![];
+        block_on(
+            client
+                .ft_transfer(bob.clone(), U128(10), None)
+                .gas(Gas(5_000_000_000_000))
+                .transact(),
+        )
+        .unwrap();
+
+        let calls = mock.calls.lock().unwrap();
+        assert_eq!(calls[0].0, token);
+        assert_eq!(calls[0].1.method_name, "ft_balance_of");
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&calls[0].1.args).unwrap(),
+            json!({ "account_id": "bob.near" }),
+        );
+        assert_eq!(calls[1].1.method_name, "ft_transfer");
+        assert_eq!(calls[1].1.gas, Gas(5_000_000_000_000));
+        assert_eq!(calls[1].1.deposit, 1);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&calls[1].1.args).unwrap(),
+            json!({ "receiver_id": "bob.near", "amount": "10", "memo": null }),
+        );
+    }
+
+    #[test]
+    fn generated_call_defaults() {
+        let mock = Mock::default();
+        let client = Nep171Client::new(&mock, "nft.near".parse().unwrap());
+
+        *mock.response.lock().unwrap() = b"true".to_vec();
+        let transferred = block_on(
+            client
+                .nft_transfer_call(
+                    "bob.near".parse().unwrap(),
+                    "token".to_string(),
+                    None,
+                    None,
+                    "msg".to_string(),
+                )
+                .transact(),
+        )
+        .unwrap();
+        assert!(transferred);
+
+        let calls = mock.calls.lock().unwrap();
+        assert_eq!(calls[0].1.method_name, "nft_transfer_call");
+        assert_eq!(calls[0].1.gas, DEFAULT_TRANSFER_CALL_GAS);
+        assert_eq!(calls[0].1.deposit, 1);
+    }
+
+    #[test]
+    fn decode_error() {
+        let mock = Mock::default();
+        *mock.response.lock().unwrap() = b"true".to_vec();
+        let client = Nep181Client::new(&mock, "nft.near".parse().unwrap());
+
+        assert!(matches!(
+            block_on(client.nft_total_supply()),
+            Err(ClientError::Serialization(_)),
+        ));
+    }
+}
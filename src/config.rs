@@ -0,0 +1,384 @@
+//! Governed configuration parameters.
+//!
+//! Contracts often have parameters (fees, caps, limits) that should only
+//! change with the agreement of their governance. This component stores
+//! named parameters as JSON values, exposes them with the `config_get` and
+//! `config_list` views, and changes them only by executing [`ConfigChange`]
+//! actions, so changes go through the [approval](crate::approval)
+//! subsystem (e.g. a `SimpleMultisig` vote) like any other action. Every
+//! change emits a [`ConfigEvent::Change`] event with the old and new
+//! values, which records the change history.
+//!
+//! Parameters are declared as [`Parameter`] constants, which give them a
+//! type. A change hook (`#[config(change_hook = "...")]`) can validate
+//! changes before they are applied, by panicking.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::Config) derives a
+//! default implementation.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The config root storage slot is not used or modified. The default
+//!     key is `~cfg`.
+//! * (ERR) Parameter names are between 1 and [`MAX_NAME_LENGTH`] bytes long.
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
+    serde_json::{self, Value},
+    BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+use thiserror::Error;
+
+use crate::{approval::Action, hook::Hook, slot::Slot, standard::nep297::Event, DefaultStorageKey};
+
+pub use ext::*;
+
+/// Maximum length of a parameter name, in bytes.
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// Events emitted when a parameter changes.
+#[event(
+    standard = "x-config",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum ConfigEvent {
+    /// A parameter was set or removed.
+    Change {
+        /// Name of the parameter.
+        name: String,
+        /// Value before the change. `None` if the parameter was not set.
+        old_value: Option<Value>,
+        /// Value after the change. `None` if the parameter was removed.
+        new_value: Option<Value>,
+    },
+}
+
+/// The name of a parameter is empty or longer than [`MAX_NAME_LENGTH`].
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Config parameter name must be between 1 and {MAX_NAME_LENGTH} bytes")]
+pub struct InvalidParameterNameError;
+
+/// The stored value of a parameter does not match its type.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Config parameter `{name}` has an invalid value: {message}")]
+pub struct InvalidParameterValueError {
+    /// Name of the parameter.
+    pub name: &'static str,
+    /// Why the value could not be deserialized.
+    pub message: String,
+}
+
+/// A change of one parameter. Execute it through an approval manager, or
+/// directly with [`Config::apply_change`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigChange {
+    /// Name of the parameter.
+    pub name: String,
+    /// New value of the parameter. `None` removes the parameter.
+    pub value: Option<Value>,
+}
+
+// Values are stored as JSON text, since `Value` does not implement Borsh.
+impl BorshSerialize for ConfigChange {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        BorshSerialize::serialize(&self.name, writer)?;
+        BorshSerialize::serialize(&self.value.as_ref().map(Value::to_string), writer)
+    }
+}
+
+impl BorshDeserialize for ConfigChange {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let name: String = BorshDeserialize::deserialize(buf)?;
+        let value = <Option<String> as BorshDeserialize>::deserialize(buf)?
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        Ok(Self { name, value })
+    }
+}
+
+impl<C: Config> Action<C> for ConfigChange {
+    type Output = ();
+
+    fn execute(self, contract: &mut C) {
+        contract
+            .apply_change(&self)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+}
+
+/// A typed parameter.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::near_bindgen;
+/// use near_sdk_contract_tools::{config::*, Config};
+///
+/// const FEE_BPS: Parameter<u16> = Parameter::new("fee_bps");
+///
+/// #[derive(Config)]
+/// #[near_bindgen]
+/// struct Contract {}
+///
+/// let mut contract = Contract {};
+/// assert_eq!(Contract::get(&FEE_BPS), None);
+///
+/// // Usually executed through an approval manager.
+/// contract.apply_change(&FEE_BPS.change(&25)).unwrap();
+/// assert_eq!(Contract::get(&FEE_BPS), Some(25));
+/// ```
+pub struct Parameter<T> {
+    /// Name of the parameter.
+    pub name: &'static str,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> Parameter<T> {
+    /// Declares a parameter.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<T: Serialize> Parameter<T> {
+    /// A change that sets the parameter to `value`.
+    pub fn change(&self, value: &T) -> ConfigChange {
+        ConfigChange {
+            name: self.name.to_string(),
+            value: Some(
+                serde_json::to_value(value)
+                    .unwrap_or_else(|e| env::panic_str(&format!("Failed to serialize: {e}"))),
+            ),
+        }
+    }
+
+    /// A change that removes the parameter.
+    pub fn remove(&self) -> ConfigChange {
+        ConfigChange {
+            name: self.name.to_string(),
+            value: None,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Parameter<T> {
+    /// Deserializes a value of the parameter, e.g. the value of a
+    /// [`ConfigChange`] in a change hook.
+    pub fn parse(&self, value: &Value) -> Result<T, InvalidParameterValueError> {
+        T::deserialize(value).map_err(|e| InvalidParameterValueError {
+            name: self.name,
+            message: e.to_string(),
+        })
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Names,
+    Value(&'a str),
+}
+
+/// Internal functions for [`Config`]. Using these methods may result in unexpected behavior.
+pub trait ConfigInternal {
+    /// Hook for parameter changes.
+    type ChangeHook: Hook<Self, ConfigChange>
+    where
+        Self: Sized;
+
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Config)
+    }
+
+    /// Storage slot for the names of the parameters that are set
+    fn slot_names() -> Slot<Vec<String>> {
+        Self::root().field(StorageKey::Names)
+    }
+
+    /// Storage slot for the value of a parameter, as JSON text
+    fn slot_value(name: &str) -> Slot<String> {
+        Self::root().field(StorageKey::Value(name))
+    }
+}
+
+/// Named parameters that change through governance.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{
+///     borsh::{self, BorshSerialize},
+///     near_bindgen, BorshStorageKey,
+/// };
+/// use near_sdk_contract_tools::{
+///     approval::{
+///         simple_multisig::{ApprovalState, Configuration},
+///         ApprovalManager,
+///     },
+///     config::*,
+///     rbac::Rbac,
+///     Config, Rbac, SimpleMultisig,
+/// };
+///
+/// const MAX_SUPPLY: Parameter<u64> = Parameter::new("max_supply");
+///
+/// #[derive(BorshSerialize, BorshStorageKey, Debug)]
+/// enum Role {
+///     Council,
+/// }
+///
+/// #[derive(Config, Rbac, SimpleMultisig)]
+/// #[rbac(roles = "Role")]
+/// #[simple_multisig(action = "ConfigChange", role = "Role::Council")]
+/// #[near_bindgen]
+/// struct Contract {}
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     #[init]
+///     pub fn new() -> Self {
+///         <Self as ApprovalManager<ConfigChange, _, _>>::init(Configuration::new(2, 0));
+///         Self {}
+///     }
+///
+///     pub fn propose_max_supply(&mut self, max_supply: u64) -> u32 {
+///         self.create_request(MAX_SUPPLY.change(&max_supply), ApprovalState::new())
+///             .unwrap()
+///     }
+///
+///     pub fn approve(&mut self, request_id: u32) {
+///         self.approve_request(request_id).unwrap();
+///     }
+///
+///     pub fn execute(&mut self, request_id: u32) {
+///         self.execute_request(request_id).unwrap();
+///     }
+///
+///     pub fn max_supply(&self) -> u64 {
+///         Self::get(&MAX_SUPPLY).unwrap_or(u64::MAX)
+///     }
+/// }
+/// ```
+pub trait Config {
+    /// Value of the parameter `name`, if it is set.
+    fn parameter_value(name: &str) -> Option<Value>;
+
+    /// Values of all parameters that are set, by name.
+    fn parameter_values() -> BTreeMap<String, Value>;
+
+    /// Value of a typed parameter, if it is set.
+    fn try_get<T: DeserializeOwned>(
+        parameter: &Parameter<T>,
+    ) -> Result<Option<T>, InvalidParameterValueError> {
+        Self::parameter_value(parameter.name)
+            .map(|value| parameter.parse(&value))
+            .transpose()
+    }
+
+    /// Like [`Config::try_get`], but panics if the stored value does not
+    /// match the type of the parameter.
+    fn get<T: DeserializeOwned>(parameter: &Parameter<T>) -> Option<T> {
+        Self::try_get(parameter).unwrap_or_else(|e| env::panic_str(&e.to_string()))
+    }
+
+    /// Applies a change and emits [`ConfigEvent::Change`]. Performs no
+    /// access checks: call only when executing an approved action.
+    fn apply_change(&mut self, change: &ConfigChange) -> Result<(), InvalidParameterNameError>;
+}
+
+impl<T: ConfigInternal> Config for T {
+    fn parameter_value(name: &str) -> Option<Value> {
+        let value = Self::slot_value(name).read()?;
+
+        Some(serde_json::from_str(&value).unwrap_or_else(|_| {
+            env::panic_str(&format!(
+                "Inconsistent state: config parameter `{name}` is not valid JSON"
+            ))
+        }))
+    }
+
+    fn parameter_values() -> BTreeMap<String, Value> {
+        Self::slot_names()
+            .read()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|name| Self::parameter_value(&name).map(|value| (name, value)))
+            .collect()
+    }
+
+    fn apply_change(&mut self, change: &ConfigChange) -> Result<(), InvalidParameterNameError> {
+        if change.name.is_empty() || change.name.len() > MAX_NAME_LENGTH {
+            return Err(InvalidParameterNameError);
+        }
+
+        T::ChangeHook::hook(self, change, |_| {
+            let old_value = Self::parameter_value(&change.name);
+            if old_value == change.value {
+                return;
+            }
+
+            let mut names_slot = Self::slot_names();
+            let mut names = names_slot.read().unwrap_or_default();
+            let mut value_slot = Self::slot_value(&change.name);
+
+            match &change.value {
+                Some(value) => {
+                    if old_value.is_none() {
+                        names.push(change.name.clone());
+                    }
+                    value_slot.write(&value.to_string());
+                }
+                None => {
+                    names.retain(|name| name != &change.name);
+                    value_slot.remove();
+                }
+            }
+
+            names_slot.set(Some(&names).filter(|names| !names.is_empty()));
+
+            ConfigEvent::Change {
+                name: change.name.clone(),
+                old_value,
+                new_value: change.value.clone(),
+            }
+            .emit();
+        });
+
+        Ok(())
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use std::collections::BTreeMap;
+
+    use near_sdk::{ext_contract, serde_json::Value};
+
+    /// External (public) methods for [`Config`](super::Config)
+    #[ext_contract(ext_config)]
+    pub trait ConfigExternal {
+        /// Returns the value of a parameter, if it is set
+        fn config_get(&self, name: String) -> Option<Value>;
+
+        /// Returns the values of all parameters that are set, by name
+        fn config_list(&self) -> BTreeMap<String, Value>;
+    }
+}
@@ -0,0 +1,483 @@
+//! Conformance checks against reference implementations of the token
+//! standards. Requires the `conformance` feature.
+//!
+//! A conformance suite deploys a token contract built with this crate next to
+//! a reference implementation (e.g. from `near-contract-standards`), sets up
+//! the same initial state on both, and runs each [`Case`] against both
+//! contracts. The suite records an [`Observation`] for each contract: whether
+//! each call succeeded, and the results of the views listed by the case.
+//! [`check_conformance`] then compares the observations.
+//!
+//! Cases are transport-agnostic: they describe calls as method names and
+//! JSON arguments, so they can be run with any testing framework. Error
+//! messages are not compared, since they are not part of the standards.
+//!
+//! Initial state expected by the cases:
+//!
+//! * Fungible token: the [`Accounts::owner`], [`Accounts::receiver`], and
+//!     [`Accounts::receiver_contract`] are registered for storage, and the
+//!     owner holds [`FT_INITIAL_BALANCE`] tokens, which is also the total
+//!     supply.
+//! * Non-fungible token: the owner owns the tokens [`NFT_TOKEN_ID`] and
+//!     [`NFT_OTHER_TOKEN_ID`].
+//!
+//! The receiver contract is expected to behave like the receivers in this
+//! repository's workspaces tests: when called with the message `"return"`,
+//! it returns all tokens, and when called with the message `"panic"`, it
+//! panics.
+
+use near_sdk::{
+    serde::Serialize,
+    serde_json::{json, Value},
+    AccountId, Balance, ONE_NEAR, ONE_YOCTO,
+};
+use thiserror::Error;
+
+/// Initial token balance of the owner in the fungible token cases.
+pub const FT_INITIAL_BALANCE: Balance = 1_000;
+/// Token owned by the owner in the non-fungible token cases.
+pub const NFT_TOKEN_ID: &str = "token_0";
+/// Second token owned by the owner in the non-fungible token cases.
+pub const NFT_OTHER_TOKEN_ID: &str = "token_1";
+/// Deposit attached to calls that pay for storage.
+pub const STORAGE_DEPOSIT: Balance = ONE_NEAR / 100;
+
+/// Accounts that take part in the cases.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Accounts {
+    /// Account that holds the tokens.
+    pub owner: AccountId,
+    /// Account that receives the tokens.
+    pub receiver: AccountId,
+    /// Contract implementing the receiver interface of the standard.
+    pub receiver_contract: AccountId,
+    /// Account that is not registered with the token contract.
+    pub unregistered: AccountId,
+}
+
+/// Which account signs a call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signer {
+    /// [`Accounts::owner`]
+    Owner,
+    /// [`Accounts::receiver`]
+    Receiver,
+}
+
+/// A function call made against both contracts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Call {
+    /// Account that signs the call.
+    pub signer: Signer,
+    /// Name of the method to call.
+    pub method: &'static str,
+    /// JSON arguments.
+    pub args: Value,
+    /// Attached deposit.
+    pub deposit: Balance,
+}
+
+impl Call {
+    fn new(signer: Signer, method: &'static str, args: Value, deposit: Balance) -> Self {
+        Self {
+            signer,
+            method,
+            args,
+            deposit,
+        }
+    }
+}
+
+/// A view whose result must be the same for both contracts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct View {
+    /// Name of the view method.
+    pub method: &'static str,
+    /// JSON arguments.
+    pub args: Value,
+    /// JSON pointer selecting the part of the result to compare. The whole
+    /// result is compared if `None`.
+    pub pointer: Option<&'static str>,
+}
+
+impl View {
+    fn new(method: &'static str, args: Value) -> Self {
+        Self {
+            method,
+            args,
+            pointer: None,
+        }
+    }
+
+    fn pointer(self, pointer: &'static str) -> Self {
+        Self {
+            pointer: Some(pointer),
+            ..self
+        }
+    }
+
+    /// Selects the part of a view result to compare.
+    pub fn select(&self, result: Value) -> Value {
+        match self.pointer {
+            Some(pointer) => result.pointer(pointer).cloned().unwrap_or(Value::Null),
+            None => result,
+        }
+    }
+}
+
+/// An edge case to cross-check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Case {
+    /// Name of the case.
+    pub name: &'static str,
+    /// Calls made in order. Later calls are made even if earlier ones fail.
+    pub calls: Vec<Call>,
+    /// Views compared after the calls.
+    pub views: Vec<View>,
+    /// If this crate deliberately behaves differently from the reference,
+    /// a description of the difference. The success of the calls is then
+    /// not compared, but the views are.
+    pub known_divergence: Option<&'static str>,
+}
+
+/// What happened when a case was run against one contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Observation {
+    /// Whether each call succeeded, in order.
+    pub succeeded: Vec<bool>,
+    /// Results of the views, in order, after [`View::select`].
+    pub views: Vec<Value>,
+}
+
+/// The contract under test behaves differently from the reference.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum NonconformanceError {
+    /// A call succeeded on one contract but failed on the other.
+    #[error("Case `{case}`: call {index} (`{method}`) succeeded on reference: {reference}, on subject: {subject}")]
+    Outcome {
+        /// Name of the case.
+        case: &'static str,
+        /// Index of the call.
+        index: usize,
+        /// Method of the call.
+        method: &'static str,
+        /// Whether the call succeeded on the reference.
+        reference: bool,
+        /// Whether the call succeeded on the subject.
+        subject: bool,
+    },
+    /// A view returned different results.
+    #[error("Case `{case}`: view {index} (`{method}`) returned {reference} on reference, {subject} on subject")]
+    View {
+        /// Name of the case.
+        case: &'static str,
+        /// Index of the view.
+        index: usize,
+        /// Method of the view.
+        method: &'static str,
+        /// Result from the reference.
+        reference: Value,
+        /// Result from the subject.
+        subject: Value,
+    },
+}
+
+/// Compares the observations of the reference and the subject for a case.
+pub fn check_conformance(
+    case: &Case,
+    reference: &Observation,
+    subject: &Observation,
+) -> Result<(), NonconformanceError> {
+    if case.known_divergence.is_none() {
+        for (index, call) in case.calls.iter().enumerate() {
+            let (reference, subject) = (reference.succeeded[index], subject.succeeded[index]);
+            if reference != subject {
+                return Err(NonconformanceError::Outcome {
+                    case: case.name,
+                    index,
+                    method: call.method,
+                    reference,
+                    subject,
+                });
+            }
+        }
+    }
+
+    for (index, view) in case.views.iter().enumerate() {
+        let (reference, subject) = (&reference.views[index], &subject.views[index]);
+        if reference != subject {
+            return Err(NonconformanceError::View {
+                case: case.name,
+                index,
+                method: view.method,
+                reference: reference.clone(),
+                subject: subject.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Panics if the subject does not conform to the reference for a case.
+pub fn assert_conforms(case: &Case, reference: &Observation, subject: &Observation) {
+    if let Err(e) = check_conformance(case, reference, subject) {
+        panic!("{e}");
+    }
+}
+
+fn ft_views(accounts: &Accounts) -> Vec<View> {
+    [
+        &accounts.owner,
+        &accounts.receiver,
+        &accounts.receiver_contract,
+    ]
+    .into_iter()
+    .map(|account_id| View::new("ft_balance_of", json!({ "account_id": account_id })))
+    .chain([View::new("ft_total_supply", json!({}))])
+    .collect()
+}
+
+fn ft_transfer(receiver_id: &AccountId, amount: Balance, deposit: Balance) -> Call {
+    Call::new(
+        Signer::Owner,
+        "ft_transfer",
+        json!({ "receiver_id": receiver_id, "amount": amount.to_string() }),
+        deposit,
+    )
+}
+
+fn ft_transfer_call(receiver_id: &AccountId, amount: Balance, msg: &str) -> Call {
+    Call::new(
+        Signer::Owner,
+        "ft_transfer_call",
+        json!({ "receiver_id": receiver_id, "amount": amount.to_string(), "msg": msg }),
+        ONE_YOCTO,
+    )
+}
+
+/// Edge cases for NEP-141 fungible tokens with NEP-145 storage management.
+pub fn ft_cases(accounts: &Accounts) -> Vec<Case> {
+    let case = |name, calls, known_divergence| Case {
+        name,
+        calls,
+        views: ft_views(accounts),
+        known_divergence,
+    };
+
+    vec![
+        case(
+            "zero-amount transfer",
+            vec![ft_transfer(&accounts.receiver, 0, ONE_YOCTO)],
+            Some("the reference rejects zero-amount transfers; this crate accepts them as no-ops"),
+        ),
+        case(
+            "transfer to self",
+            vec![ft_transfer(&accounts.owner, 10, ONE_YOCTO)],
+            Some("the reference rejects transfers to self; this crate accepts them as no-ops"),
+        ),
+        case(
+            "transfer without deposit",
+            vec![ft_transfer(&accounts.receiver, 10, 0)],
+            None,
+        ),
+        case(
+            "transfer exceeding balance",
+            vec![ft_transfer(
+                &accounts.receiver,
+                FT_INITIAL_BALANCE + 1,
+                ONE_YOCTO,
+            )],
+            None,
+        ),
+        case(
+            "transfer to unregistered account",
+            vec![ft_transfer(&accounts.unregistered, 10, ONE_YOCTO)],
+            None,
+        ),
+        case(
+            "transfer call fully refunded by resolver",
+            vec![ft_transfer_call(&accounts.receiver_contract, 100, "return")],
+            None,
+        ),
+        case(
+            "transfer call refunded after receiver panics",
+            vec![ft_transfer_call(&accounts.receiver_contract, 100, "panic")],
+            None,
+        ),
+        case(
+            "transfer call kept by receiver",
+            vec![ft_transfer_call(&accounts.receiver_contract, 100, "")],
+            None,
+        ),
+    ]
+}
+
+fn nft_views(accounts: &Accounts) -> Vec<View> {
+    [NFT_TOKEN_ID, NFT_OTHER_TOKEN_ID]
+        .into_iter()
+        .map(|token_id| {
+            View::new("nft_token", json!({ "token_id": token_id })).pointer("/owner_id")
+        })
+        .chain(
+            [&accounts.owner, &accounts.receiver]
+                .into_iter()
+                .map(|account_id| {
+                    View::new(
+                        "nft_is_approved",
+                        json!({ "token_id": NFT_TOKEN_ID, "approved_account_id": account_id }),
+                    )
+                }),
+        )
+        .collect()
+}
+
+fn nft_approve(account_id: &AccountId) -> Call {
+    Call::new(
+        Signer::Owner,
+        "nft_approve",
+        json!({ "token_id": NFT_TOKEN_ID, "account_id": account_id }),
+        STORAGE_DEPOSIT,
+    )
+}
+
+fn nft_transfer(signer: Signer, receiver_id: &AccountId, approval_id: Option<u32>) -> Call {
+    Call::new(
+        signer,
+        "nft_transfer",
+        json!({ "receiver_id": receiver_id, "token_id": NFT_TOKEN_ID, "approval_id": approval_id }),
+        ONE_YOCTO,
+    )
+}
+
+fn nft_transfer_call(receiver_id: &AccountId, msg: &str) -> Call {
+    Call::new(
+        Signer::Owner,
+        "nft_transfer_call",
+        json!({ "receiver_id": receiver_id, "token_id": NFT_TOKEN_ID, "msg": msg }),
+        ONE_YOCTO,
+    )
+}
+
+/// Edge cases for NEP-171 non-fungible tokens with NEP-178 approvals.
+pub fn nft_cases(accounts: &Accounts) -> Vec<Case> {
+    let case = |name, calls, known_divergence| Case {
+        name,
+        calls,
+        views: nft_views(accounts),
+        known_divergence,
+    };
+
+    vec![
+        case("self-approval", vec![nft_approve(&accounts.owner)], None),
+        case(
+            "repeated approval",
+            vec![
+                nft_approve(&accounts.receiver),
+                nft_approve(&accounts.receiver),
+            ],
+            Some("the reference replaces the approval ID; this crate rejects repeated approvals"),
+        ),
+        case(
+            "transfer to self",
+            vec![nft_transfer(Signer::Owner, &accounts.owner, None)],
+            None,
+        ),
+        case(
+            "transfer by unapproved account",
+            vec![nft_transfer(Signer::Receiver, &accounts.receiver, None)],
+            None,
+        ),
+        case(
+            "transfer with wrong approval ID",
+            vec![
+                nft_approve(&accounts.receiver),
+                nft_transfer(Signer::Receiver, &accounts.receiver, Some(99)),
+            ],
+            None,
+        ),
+        case(
+            "approved transfer clears approvals",
+            vec![
+                nft_approve(&accounts.receiver),
+                nft_transfer(Signer::Receiver, &accounts.receiver, None),
+            ],
+            None,
+        ),
+        case(
+            "transfer call returned by receiver",
+            vec![nft_transfer_call(&accounts.receiver_contract, "return")],
+            None,
+        ),
+        case(
+            "transfer call returned after receiver panics",
+            vec![nft_transfer_call(&accounts.receiver_contract, "panic")],
+            None,
+        ),
+        case(
+            "transfer call kept by receiver",
+            vec![nft_transfer_call(&accounts.receiver_contract, "")],
+            None,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accounts() -> Accounts {
+        Accounts {
+            owner: "owner".parse().unwrap(),
+            receiver: "receiver".parse().unwrap(),
+            receiver_contract: "receiver_contract".parse().unwrap(),
+            unregistered: "unregistered".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn known_divergence_only_skips_outcomes() {
+        let cases = ft_cases(&accounts());
+        let zero_amount = &cases[0];
+        assert!(zero_amount.known_divergence.is_some());
+
+        let reference = Observation {
+            succeeded: vec![false],
+            views: vec![json!("1000"), json!("0"), json!("0"), json!("1000")],
+        };
+        let mut subject = Observation {
+            succeeded: vec![true],
+            ..reference.clone()
+        };
+        assert_eq!(check_conformance(zero_amount, &reference, &subject), Ok(()));
+
+        subject.views[1] = json!("10");
+        assert!(matches!(
+            check_conformance(zero_amount, &reference, &subject),
+            Err(NonconformanceError::View { index: 1, .. }),
+        ));
+
+        assert!(matches!(
+            check_conformance(
+                &cases[2],
+                &reference,
+                &Observation {
+                    succeeded: vec![true],
+                    ..reference.clone()
+                }
+            ),
+            Err(NonconformanceError::Outcome { index: 0, .. }),
+        ));
+    }
+
+    #[test]
+    fn view_pointer() {
+        let view = &nft_views(&accounts())[0];
+        assert_eq!(
+            view.select(json!({ "token_id": NFT_TOKEN_ID, "owner_id": "owner", "metadata": {} })),
+            json!("owner"),
+        );
+        assert_eq!(view.select(Value::Null), Value::Null);
+    }
+}
@@ -0,0 +1,186 @@
+//! Storage inspection for development builds. Requires the `debug` feature.
+//!
+//! Contracts cannot enumerate their own storage keys, so with this feature
+//! enabled, every key written through a [`Slot`](crate::slot::Slot) (which
+//! includes the storage of every component in this crate) is recorded in a
+//! registry under the `"~dbg"` prefix. [`storage_dump`] lists the recorded
+//! keys that are still present, along with the size of their values, which
+//! helps track down storage key collisions and unexpected storage usage.
+//!
+//! The [`StorageDump`](near_sdk_contract_tools_macros::StorageDump) derive
+//! macro exposes the same listing as the `__tools_storage_dump` view method.
+//!
+//! # Warning
+//!
+//! The registry itself consumes storage, which is included in
+//! `env::storage_usage` and therefore in storage fees. Keys written by
+//! `near_sdk` collections or directly through `env::storage_write` are not
+//! recorded. Do not enable this feature in production builds.
+
+use near_sdk::{
+    env,
+    json_types::Base64VecU8,
+    serde::{Deserialize, Serialize},
+};
+
+const REGISTRY_COUNT: &[u8] = b"~dbg#";
+const REGISTRY_INDEX: &[u8] = b"~dbgi";
+const REGISTRY_POSITION: &[u8] = b"~dbgp";
+
+fn index_key(index: u32) -> Vec<u8> {
+    [REGISTRY_INDEX, &index.to_le_bytes()].concat()
+}
+
+fn position_key(key: &[u8]) -> Vec<u8> {
+    [REGISTRY_POSITION, key].concat()
+}
+
+fn read_u32(key: &[u8]) -> Option<u32> {
+    env::storage_read(key).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn count() -> u32 {
+    read_u32(REGISTRY_COUNT).unwrap_or(0)
+}
+
+/// Records `key` in the registry. Called by [`Slot`](crate::slot::Slot) on
+/// every write.
+pub fn register(key: &[u8]) {
+    let position_key = position_key(key);
+    if env::storage_has_key(&position_key) {
+        return;
+    }
+
+    let index = count();
+    env::storage_write(&index_key(index), key);
+    env::storage_write(&position_key, &index.to_le_bytes());
+    env::storage_write(REGISTRY_COUNT, &(index + 1).to_le_bytes());
+}
+
+/// Removes `key` from the registry. Called by [`Slot`](crate::slot::Slot)
+/// when a key is removed.
+pub fn unregister(key: &[u8]) {
+    let position_key = position_key(key);
+    let index = match read_u32(&position_key) {
+        Some(index) => index,
+        None => return,
+    };
+    env::storage_remove(&position_key);
+
+    let last = count() - 1;
+    if index != last {
+        let last_key = env::storage_read(&index_key(last)).unwrap();
+        env::storage_write(&index_key(index), &last_key);
+        env::storage_write(&self::position_key(&last_key), &index.to_le_bytes());
+    }
+    env::storage_remove(&index_key(last));
+    env::storage_write(REGISTRY_COUNT, &last.to_le_bytes());
+}
+
+/// A storage key and the size of its value.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageDumpEntry {
+    /// Raw storage key.
+    pub key: Base64VecU8,
+    /// Length of the stored value, in bytes.
+    pub size: u32,
+}
+
+/// A page of registered storage keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageDump {
+    /// Registered keys that match the prefix.
+    pub entries: Vec<StorageDumpEntry>,
+    /// Registry position to pass as `from` to continue the listing, if the
+    /// listing was cut short by `limit`.
+    pub next: Option<u32>,
+}
+
+/// Lists up to `limit` registered keys that start with `prefix`, scanning
+/// the registry from position `from`.
+pub fn storage_dump(prefix: &[u8], from: u32, limit: u32) -> StorageDump {
+    let mut entries = Vec::new();
+    let count = count();
+
+    for index in from..count {
+        if entries.len() == limit as usize {
+            return StorageDump {
+                entries,
+                next: Some(index),
+            };
+        }
+
+        let key = env::storage_read(&index_key(index)).unwrap();
+        if !key.starts_with(prefix) {
+            continue;
+        }
+
+        let size = env::storage_read(&key).map_or(0, |value| value.len() as u32);
+        entries.push(StorageDumpEntry {
+            key: key.into(),
+            size,
+        });
+    }
+
+    StorageDump {
+        entries,
+        next: None,
+    }
+}
+
+/// Renders a key with non-printable bytes escaped, e.g. `~o\x00`.
+pub fn render_key(key: &[u8]) -> String {
+    key.iter()
+        .flat_map(|b| std::ascii::escape_default(*b))
+        .map(char::from)
+        .collect()
+}
+
+/// Renders a dump as one `key (size bytes)` line per entry, sorted by key.
+pub fn render(dump: &StorageDump) -> String {
+    let mut entries = dump.entries.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.key.0.cmp(&b.key.0));
+
+    entries
+        .into_iter()
+        .map(|entry| format!("{} ({} bytes)\n", render_key(&entry.key.0), entry.size))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::slot::Slot;
+
+    use super::*;
+
+    #[test]
+    fn dump_slots() {
+        let mut a = Slot::<u32>::new(b"a".to_vec());
+        let mut b1 = Slot::<u64>::new(b"b\x01".to_vec());
+        let mut b2 = Slot::<u8>::new(b"b\x02".to_vec());
+        a.write(&1);
+        b1.write(&2);
+        b2.write(&3);
+        b1.write(&4);
+        env::storage_write(b"untracked", b"");
+
+        assert_eq!(
+            render(&storage_dump(b"", 0, 10)),
+            "a (4 bytes)\nb\\x01 (8 bytes)\nb\\x02 (1 bytes)\n",
+        );
+
+        let page = storage_dump(b"b", 0, 1);
+        assert_eq!(render(&page), "b\\x01 (8 bytes)\n");
+        let page = storage_dump(b"b", page.next.unwrap(), 1);
+        assert_eq!(render(&page), "b\\x02 (1 bytes)\n");
+        assert_eq!(page.next, None);
+
+        a.remove();
+        assert_eq!(
+            render(&storage_dump(b"", 0, 10)),
+            "b\\x01 (8 bytes)\nb\\x02 (1 bytes)\n",
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! Interest and penalty accrual for escrow locks.
+//!
+//! A lock created with [`EscrowAccrual::lock_with_accrual`] records a
+//! principal amount, a beneficiary, and the time it was created. When it is
+//! unlocked with [`EscrowAccrual::unlock_with_accrual`], the contract's
+//! accrual policy computes an amount from the principal and the time the lock
+//! was held, and settles it in the contract's own NEP-141 token: interest is
+//! minted to the beneficiary, and penalties are burned from the beneficiary.
+//!
+//! The policy is configured once, at init, with
+//! [`EscrowAccrual::init_accrual_policy`]. The built-in policies are
+//! [`LinearAccrual`] and [`StepwiseAccrual`]. Contracts that need a different
+//! formula implement [`EscrowAccrualInternal`] manually and override
+//! [`EscrowAccrualInternal::accrual_policy`] to return their own
+//! [`AccrualPolicy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{env, json_types::U64, near_bindgen, AccountId, PanicOnDefault};
+//! use near_sdk_contract_tools::{escrow::accrual::*, Escrow, Nep141};
+//!
+//! #[derive(Nep141, Escrow, PanicOnDefault)]
+//! #[escrow(id = "U64", state = "()", accrual)]
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[init]
+//!     pub fn new() -> Self {
+//!         let mut contract = Self {};
+//!         // 5% per day, pro rata.
+//!         contract.init_accrual_policy(&AccrualConfig::Linear(LinearAccrual {
+//!             kind: AccrualKind::Interest,
+//!             rate_bps: 500,
+//!             period_ns: U64(86_400_000_000_000),
+//!         }));
+//!         contract
+//!     }
+//!
+//!     pub fn deposit(&mut self, id: U64, amount: near_sdk::json_types::U128) {
+//!         let beneficiary_id = env::predecessor_account_id();
+//!         self.lock_with_accrual(&id, &(), &beneficiary_id, amount.0)
+//!             .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+//!     }
+//!
+//!     pub fn withdraw(&mut self, id: U64) {
+//!         self.unlock_with_accrual(&id, |_| true)
+//!             .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+//!     }
+//! }
+//! ```
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::{U128, U64},
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+use thiserror::Error;
+
+use crate::{
+    event,
+    hook::ActionOrigin,
+    slot::Slot,
+    standard::{
+        nep141::{DepositError, Nep141Burn, Nep141Controller, Nep141Mint, WithdrawError},
+        nep297::Event,
+    },
+    utils::amount::{Amount, AmountError, Rounding},
+};
+
+use super::{Escrow, EscrowInternal, StorageKey};
+
+const ACCRUAL_POLICY_ALREADY_INITIALIZED_MESSAGE: &str = "Accrual policy already initialized";
+const ACCRUAL_POLICY_ZERO_PERIOD_MESSAGE: &str = "Accrual period must be nonzero";
+
+/// Rates are expressed in basis points of the principal.
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Whether an accrual is paid to or taken from the beneficiary.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum AccrualKind {
+    /// Minted to the beneficiary.
+    Interest,
+    /// Burned from the beneficiary.
+    Penalty,
+}
+
+/// An amount accrued by a lock.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Accrual {
+    /// Whether the amount is interest or a penalty.
+    pub kind: AccrualKind,
+    /// The accrued amount.
+    pub amount: U128,
+}
+
+/// Computes the amount accrued by a lock.
+pub trait AccrualPolicy {
+    /// The amount accrued on `principal` over `elapsed_ns` nanoseconds.
+    fn accrue(&self, principal: u128, elapsed_ns: u64) -> Result<Accrual, AmountError>;
+}
+
+/// Accrues `rate_bps` basis points of the principal per `period_ns`, pro rata.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LinearAccrual {
+    /// Whether the amount is interest or a penalty.
+    pub kind: AccrualKind,
+    /// Basis points of the principal accrued per period.
+    pub rate_bps: u32,
+    /// Length of a period in nanoseconds.
+    pub period_ns: U64,
+}
+
+impl AccrualPolicy for LinearAccrual {
+    fn accrue(&self, principal: u128, elapsed_ns: u64) -> Result<Accrual, AmountError> {
+        let amount = Amount::new(principal).mul_div(
+            u128::from(self.rate_bps) * u128::from(elapsed_ns),
+            BPS_DENOMINATOR * u128::from(self.period_ns.0),
+            Rounding::Down,
+        )?;
+
+        Ok(Accrual {
+            kind: self.kind,
+            amount: amount.raw().into(),
+        })
+    }
+}
+
+/// A step of a [`StepwiseAccrual`].
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccrualStep {
+    /// How long the lock must be held for the step to apply, in nanoseconds.
+    pub after_ns: U64,
+    /// Basis points of the principal accrued.
+    pub rate_bps: u32,
+}
+
+/// Accrues the rate of the latest step that the lock has reached, or nothing
+/// before the first step.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StepwiseAccrual {
+    /// Whether the amount is interest or a penalty.
+    pub kind: AccrualKind,
+    /// The steps, in any order.
+    pub steps: Vec<AccrualStep>,
+}
+
+impl AccrualPolicy for StepwiseAccrual {
+    fn accrue(&self, principal: u128, elapsed_ns: u64) -> Result<Accrual, AmountError> {
+        let rate_bps = self
+            .steps
+            .iter()
+            .filter(|step| step.after_ns.0 <= elapsed_ns)
+            .max_by_key(|step| step.after_ns.0)
+            .map_or(0, |step| step.rate_bps);
+
+        let amount = Amount::new(principal).mul_div(
+            u128::from(rate_bps),
+            BPS_DENOMINATOR,
+            Rounding::Down,
+        )?;
+
+        Ok(Accrual {
+            kind: self.kind,
+            amount: amount.raw().into(),
+        })
+    }
+}
+
+/// A built-in accrual policy, as stored by
+/// [`EscrowAccrual::init_accrual_policy`].
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum AccrualConfig {
+    /// See [`LinearAccrual`].
+    Linear(LinearAccrual),
+    /// See [`StepwiseAccrual`].
+    Stepwise(StepwiseAccrual),
+}
+
+impl AccrualConfig {
+    /// The policy as a trait object.
+    pub fn into_policy(self) -> Box<dyn AccrualPolicy> {
+        match self {
+            Self::Linear(policy) => Box::new(policy),
+            Self::Stepwise(policy) => Box::new(policy),
+        }
+    }
+}
+
+/// Accrual terms of a lock.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccrualRecord {
+    /// Account that receives interest or pays penalties.
+    pub beneficiary_id: AccountId,
+    /// Amount on which the accrual is computed.
+    pub principal: U128,
+    /// Block timestamp at which the lock was created, in nanoseconds.
+    pub locked_at: U64,
+}
+
+/// Emitted when a lock's accrual is settled by
+/// [`EscrowAccrual::unlock_with_accrual`].
+#[event(
+    standard = "x-escrow",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct AccrualSettled<Id: Serialize> {
+    /// The identifier for the lock
+    pub id: Id,
+    /// The account that received the interest or paid the penalty
+    pub beneficiary_id: AccountId,
+    /// The settled amount
+    pub accrual: Accrual,
+}
+
+/// Errors that may occur when locking or unlocking with accrual.
+#[derive(Error, Debug)]
+pub enum AccrualError {
+    /// No accrual policy is configured.
+    #[error("No accrual policy is configured")]
+    NoPolicy,
+    /// The accrued amount could not be computed.
+    #[error(transparent)]
+    Amount(#[from] AmountError),
+    /// The interest could not be minted.
+    #[error(transparent)]
+    Mint(#[from] DepositError),
+    /// The penalty could not be burned.
+    #[error(transparent)]
+    Burn(#[from] WithdrawError),
+}
+
+/// Internal functions for [`EscrowAccrual`]. Using these methods may result in unexpected behavior.
+pub trait EscrowAccrualInternal: EscrowInternal {
+    /// Storage slot for the configured accrual policy.
+    fn slot_accrual_policy() -> Slot<AccrualConfig> {
+        Self::root().field(StorageKey::<()>::AccrualPolicy)
+    }
+
+    /// Storage slot for the accrual terms of the lock at `id`.
+    fn slot_accrual(id: &Self::Id) -> Slot<AccrualRecord> {
+        Self::root().field(StorageKey::Accrual(id))
+    }
+
+    /// The accrual policy in effect, if any. Reads the policy configured with
+    /// [`EscrowAccrual::init_accrual_policy`] by default.
+    fn accrual_policy(&self) -> Option<Box<dyn AccrualPolicy>> {
+        Self::slot_accrual_policy()
+            .read()
+            .map(AccrualConfig::into_policy)
+    }
+}
+
+/// Escrow locks that accrue interest or penalties, settled in the contract's
+/// NEP-141 token.
+pub trait EscrowAccrual: Escrow {
+    /// Configures the accrual policy. Only succeeds once, so it should be
+    /// called at init. Panics if the policy is a [`LinearAccrual`] with a
+    /// zero period.
+    fn init_accrual_policy(&mut self, policy: &AccrualConfig);
+
+    /// Lock some `Self::State` by its `Self::Id`, accruing on `principal` in
+    /// favor of (or at the expense of) `beneficiary_id` from now until it is
+    /// unlocked.
+    fn lock_with_accrual(
+        &mut self,
+        id: &Self::Id,
+        state: &Self::State,
+        beneficiary_id: &AccountId,
+        principal: u128,
+    ) -> Result<(), AccrualError>;
+
+    /// The accrual terms of the lock at `id`, if any.
+    fn accrual_of(&self, id: &Self::Id) -> Option<AccrualRecord>;
+
+    /// The amount the lock at `id` would settle if it were unlocked now, if
+    /// it was created with accrual. A penalty is capped at the beneficiary's
+    /// balance.
+    fn accrued(&self, id: &Self::Id) -> Result<Option<Accrual>, AccrualError>;
+
+    /// Unlock some `Self::State` by its `Self::Id`, first minting the accrued
+    /// interest to, or burning the accrued penalty from, the beneficiary.
+    /// Locks created without accrual are unlocked without settlement. Emits
+    /// [`AccrualSettled`].
+    fn unlock_with_accrual(
+        &mut self,
+        id: &Self::Id,
+        unlock_handler: impl FnOnce(&Self::State) -> bool,
+    ) -> Result<Option<Accrual>, AccrualError>;
+}
+
+impl<T> EscrowAccrual for T
+where
+    T: EscrowAccrualInternal + Nep141Controller,
+    <T as EscrowInternal>::Id: Serialize,
+{
+    fn init_accrual_policy(&mut self, policy: &AccrualConfig) {
+        let mut slot = Self::slot_accrual_policy();
+        require!(!slot.exists(), ACCRUAL_POLICY_ALREADY_INITIALIZED_MESSAGE);
+        if let AccrualConfig::Linear(linear) = policy {
+            require!(linear.period_ns.0 != 0, ACCRUAL_POLICY_ZERO_PERIOD_MESSAGE);
+        }
+        slot.write(policy);
+    }
+
+    fn lock_with_accrual(
+        &mut self,
+        id: &Self::Id,
+        state: &Self::State,
+        beneficiary_id: &AccountId,
+        principal: u128,
+    ) -> Result<(), AccrualError> {
+        if self.accrual_policy().is_none() {
+            return Err(AccrualError::NoPolicy);
+        }
+
+        self.lock(id, state);
+        Self::slot_accrual(id).write(&AccrualRecord {
+            beneficiary_id: beneficiary_id.clone(),
+            principal: principal.into(),
+            locked_at: env::block_timestamp().into(),
+        });
+
+        Ok(())
+    }
+
+    fn accrual_of(&self, id: &Self::Id) -> Option<AccrualRecord> {
+        Self::slot_accrual(id).read()
+    }
+
+    fn accrued(&self, id: &Self::Id) -> Result<Option<Accrual>, AccrualError> {
+        let record = match self.accrual_of(id) {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+
+        let policy = self.accrual_policy().ok_or(AccrualError::NoPolicy)?;
+        let elapsed_ns = env::block_timestamp().saturating_sub(record.locked_at.0);
+
+        let mut accrual = policy.accrue(record.principal.0, elapsed_ns)?;
+
+        if accrual.kind == AccrualKind::Penalty {
+            let balance = self.balance_of(&record.beneficiary_id);
+            accrual.amount = u128::min(accrual.amount.0, balance).into();
+        }
+
+        Ok(Some(accrual))
+    }
+
+    fn unlock_with_accrual(
+        &mut self,
+        id: &Self::Id,
+        unlock_handler: impl FnOnce(&Self::State) -> bool,
+    ) -> Result<Option<Accrual>, AccrualError> {
+        let accrual = self.accrued(id)?;
+        let mut record_slot = Self::slot_accrual(id);
+        let record = record_slot.read();
+
+        if let (Some(accrual), Some(record)) = (accrual, &record) {
+            match accrual.kind {
+                AccrualKind::Interest => Nep141Controller::mint(
+                    self,
+                    &Nep141Mint {
+                        amount: accrual.amount.0,
+                        receiver_id: &record.beneficiary_id,
+                        memo: Some("escrow interest"),
+                        origin: ActionOrigin::Internal,
+                    },
+                )?,
+                AccrualKind::Penalty => Nep141Controller::burn(
+                    self,
+                    &Nep141Burn {
+                        amount: accrual.amount.0,
+                        owner_id: &record.beneficiary_id,
+                        memo: Some("escrow penalty"),
+                        origin: ActionOrigin::Internal,
+                    },
+                )?,
+            }
+        }
+
+        self.unlock(id, unlock_handler);
+        record_slot.remove();
+
+        if let (Some(accrual), Some(record)) = (accrual, record) {
+            AccrualSettled {
+                id,
+                beneficiary_id: record.beneficiary_id,
+                accrual,
+            }
+            .emit();
+        }
+
+        Ok(accrual)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400_000_000_000;
+
+    #[test]
+    fn linear() {
+        let policy = LinearAccrual {
+            kind: AccrualKind::Interest,
+            rate_bps: 500,
+            period_ns: U64(DAY),
+        };
+
+        assert_eq!(policy.accrue(1_000, 0).unwrap().amount, U128(0));
+        assert_eq!(policy.accrue(1_000, DAY / 2).unwrap().amount, U128(25));
+        assert_eq!(policy.accrue(1_000, 3 * DAY).unwrap().amount, U128(150));
+        assert_eq!(
+            policy.accrue(u128::MAX / 2, DAY).unwrap().amount,
+            U128(u128::MAX / 2 / 20),
+        );
+    }
+
+    #[test]
+    fn stepwise() {
+        let policy = StepwiseAccrual {
+            kind: AccrualKind::Penalty,
+            steps: vec![
+                AccrualStep {
+                    after_ns: U64(7 * DAY),
+                    rate_bps: 100,
+                },
+                AccrualStep {
+                    after_ns: U64(DAY),
+                    rate_bps: 1_000,
+                },
+            ],
+        };
+
+        assert_eq!(policy.accrue(1_000, 0).unwrap().amount, U128(0));
+        assert_eq!(policy.accrue(1_000, DAY).unwrap().amount, U128(100));
+        assert_eq!(policy.accrue(1_000, 6 * DAY).unwrap().amount, U128(100));
+        assert_eq!(
+            policy.accrue(1_000, 30 * DAY).unwrap(),
+            Accrual {
+                kind: AccrualKind::Penalty,
+                amount: U128(10),
+            },
+        );
+    }
+}
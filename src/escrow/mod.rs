@@ -10,17 +10,38 @@
 //! The state for this contract is stored under the [root][EscrowInternal::root], make sure you dont
 //! accidentally collide these storage entries in your contract.
 //! You can change the key this is stored under by providing [storage_key] to the macro.
+//!
+//! # Storage accounting
+//! If the contract also implements [NEP-145](crate::standard::nep145), use
+//! [`Nep145Escrow`] to charge the cost of a lock's storage to the account
+//! that created it. The locker is recorded alongside the lock, and the
+//! storage cost is released back to the locker's storage balance when the
+//! lock is removed. These functions also emit [`EscrowLocked`] and
+//! [`EscrowUnlocked`] events.
+//!
+//! # Accrual
+//! The [`accrual`] module settles interest or penalties on a lock,
+//! depending on how long it was held, in a NEP-141 token managed by the same
+//! contract. Requires the `nep141` feature.
 use crate::{event, standard::nep297::Event};
-use crate::{slot::Slot, DefaultStorageKey};
+use crate::{
+    slot::Slot,
+    standard::nep145::{error::StorageLockError, Nep145Controller},
+    DefaultStorageKey,
+};
 use near_sdk::{
     borsh::BorshSerialize,
     borsh::{self, BorshDeserialize},
-    env::panic_str,
+    env::{self, panic_str},
+    json_types::U128,
     require,
     serde::Serialize,
-    BorshStorageKey,
+    AccountId, BorshStorageKey,
 };
 
+#[cfg(feature = "nep141")]
+pub mod accrual;
+
 const ESCROW_ALREADY_LOCKED_MESSAGE: &str = "Already locked";
 const ESCROW_NOT_LOCKED_MESSAGE: &str = "Lock required";
 const ESCROW_UNLOCK_HANDLER_FAILED_MESSAGE: &str = "Unlock handler failed";
@@ -28,6 +49,11 @@ const ESCROW_UNLOCK_HANDLER_FAILED_MESSAGE: &str = "Unlock handler failed";
 #[derive(BorshSerialize, BorshStorageKey)]
 enum StorageKey<'a, T> {
     Locked(&'a T),
+    Locker(&'a T),
+    #[cfg(feature = "nep141")]
+    AccrualPolicy,
+    #[cfg(feature = "nep141")]
+    Accrual(&'a T),
 }
 
 /// Emit the state of an escrow lock and whether it was locked or unlocked
@@ -44,6 +70,38 @@ pub struct Lock<Id: Serialize, State: Serialize> {
     pub locked: Option<State>,
 }
 
+/// Emitted when a lock is created by [`Nep145Escrow::lock_with_storage`]
+#[event(
+    standard = "x-escrow",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct EscrowLocked<Id: Serialize, State: Serialize> {
+    /// The identifier for the lock
+    pub id: Id,
+    /// The account charged for the lock's storage
+    pub locker_id: AccountId,
+    /// The state stored in the lock
+    pub state: State,
+}
+
+/// Emitted when a lock is removed by [`Nep145Escrow::unlock_with_storage`]
+#[event(
+    standard = "x-escrow",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct EscrowUnlocked<Id: Serialize> {
+    /// The identifier for the lock
+    pub id: Id,
+    /// The account that was charged for the lock's storage, if any
+    pub locker_id: Option<AccountId>,
+    /// Storage balance released back to the locker
+    pub storage_refund: U128,
+}
+
 /// Inner storage modifiers and functionality required for escrow to succeed
 pub trait EscrowInternal {
     /// Identifier over which the escrow exists
@@ -75,6 +133,12 @@ pub trait EscrowInternal {
     fn set_unlocked(&mut self, id: &Self::Id) {
         self.locked_slot(id).remove();
     }
+
+    /// Inner function to retrieve the slot storing the account charged for
+    /// the lock at `id`
+    fn locker_slot(&self, id: &Self::Id) -> Slot<AccountId> {
+        Self::root().field(StorageKey::Locker(id))
+    }
 }
 
 /// Some escrowable capabilities, with a simple locking/unlocking mechanism
@@ -161,6 +225,102 @@ where
     }
 }
 
+/// Escrow with NEP-145 storage accounting. The storage used by a lock is
+/// charged to the account that created it, and released when it is
+/// unlocked.
+pub trait Nep145Escrow: Escrow {
+    /// Lock some `Self::State` by its `Self::Id`, charging the storage cost
+    /// to `locker_id`'s storage balance. If the locker cannot pay, the lock
+    /// is not created.
+    fn lock_with_storage(
+        &mut self,
+        locker_id: &AccountId,
+        id: &Self::Id,
+        state: &Self::State,
+    ) -> Result<(), StorageLockError>;
+
+    /// Unlock some `Self::State` by its `Self::Id`, releasing the storage
+    /// cost back to the account that created the lock. Returns the amount
+    /// released, which is zero if the lock was created without storage
+    /// accounting or the locker is no longer registered.
+    fn unlock_with_storage(
+        &mut self,
+        id: &Self::Id,
+        unlock_handler: impl FnOnce(&Self::State) -> bool,
+    ) -> U128;
+
+    /// The account charged for the lock at `id`, if any
+    fn locker_of(&self, id: &Self::Id) -> Option<AccountId>;
+}
+
+impl<T> Nep145Escrow for T
+where
+    T: EscrowInternal + Nep145Controller,
+    <T as EscrowInternal>::Id: Serialize,
+    <T as EscrowInternal>::State: Serialize,
+{
+    fn lock_with_storage(
+        &mut self,
+        locker_id: &AccountId,
+        id: &Self::Id,
+        state: &Self::State,
+    ) -> Result<(), StorageLockError> {
+        let storage_usage_start = env::storage_usage();
+
+        self.lock(id, state);
+        self.locker_slot(id).write(locker_id);
+
+        let storage_consumed = env::storage_usage().saturating_sub(storage_usage_start);
+        let storage_fee = env::storage_byte_cost() * storage_consumed as u128;
+
+        if let Err(e) = self.lock_storage(locker_id, storage_fee.into()) {
+            self.set_unlocked(id);
+            self.locker_slot(id).remove();
+            return Err(e);
+        }
+
+        EscrowLocked {
+            id,
+            locker_id: locker_id.clone(),
+            state,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn unlock_with_storage(
+        &mut self,
+        id: &Self::Id,
+        unlock_handler: impl FnOnce(&Self::State) -> bool,
+    ) -> U128 {
+        let storage_usage_start = env::storage_usage();
+
+        self.unlock(id, unlock_handler);
+
+        let mut locker_slot = self.locker_slot(id);
+        let locker_id = locker_slot.take();
+
+        let storage_refund = locker_id
+            .as_ref()
+            .and_then(|locker_id| self.storage_refund(locker_id, storage_usage_start).ok())
+            .unwrap_or(U128(0));
+
+        EscrowUnlocked {
+            id,
+            locker_id,
+            storage_refund,
+        }
+        .emit();
+
+        storage_refund
+    }
+
+    fn locker_of(&self, id: &Self::Id) -> Option<AccountId> {
+        self.locker_slot(id).read()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Escrow;
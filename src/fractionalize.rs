@@ -0,0 +1,340 @@
+//! Fractional ownership of a non-fungible token.
+//!
+//! An NFT owner locks a NEP-171 token in the contract, which mints a chosen
+//! supply of NEP-141 shares to the owner. The shares can then be traded like
+//! any fungible token. An account that holds every share can redeem them:
+//! the shares are burned and the NFT is released to the redeemer.
+//!
+//! The contract implements both token standards itself, so the NFT is held
+//! by the contract's own account while it is fractionalized. The lock is
+//! recorded with [`Escrow`], keyed by the token ID. All token movements go
+//! through [`Nep141Controller`] and [`Nep171Controller`], so they run the
+//! tokens' usual hooks and emit the usual events. If the tokens use NEP-145
+//! storage accounting, the contract's own account must be registered to
+//! receive the NFT.
+//!
+//! The NEP-141 token represents the shares of one NFT, so only one token can
+//! be fractionalized at a time, and the fungible token supply must be zero
+//! when it is locked.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{near_bindgen, AccountId};
+//! use near_sdk_contract_tools::{
+//!     fractionalize::*, standard::nep171::TokenId, Escrow, Fractionalize, Nep141, Nep171,
+//! };
+//!
+//! #[derive(Nep141, Nep171, Escrow, Fractionalize)]
+//! #[escrow(id = "TokenId", state = "FractionalVault")]
+//! #[near_bindgen]
+//! struct Contract {}
+//! ```
+//!
+//! The derive macro exposes `fractionalize_lock`, `fractionalize_redeem`,
+//! and `fractionalize_vault`. See the `fractionalized_nft` template for a
+//! complete contract.
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use thiserror::Error;
+
+use crate::{
+    escrow::{Escrow, EscrowInternal},
+    event,
+    hook::ActionOrigin,
+    slot::Slot,
+    standard::{
+        nep141::{DepositError, Nep141Burn, Nep141Controller, Nep141Mint, WithdrawError},
+        nep171::{
+            action::Nep171Transfer, error::Nep171TransferError, Nep171Controller,
+            Nep171TransferAuthorization, TokenId,
+        },
+        nep297::Event,
+    },
+    DefaultStorageKey,
+};
+
+pub use ext::*;
+
+/// A fractionalized NFT.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FractionalVault {
+    /// The locked token.
+    pub token_id: TokenId,
+    /// Account that locked the token.
+    pub curator_id: AccountId,
+    /// Number of shares minted for the token. All of them must be burned to
+    /// redeem it.
+    pub shares: U128,
+}
+
+/// An NFT was locked and shares were minted.
+#[event(
+    standard = "x-fractionalize",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct Fractionalized {
+    /// The locked token.
+    pub token_id: TokenId,
+    /// Account that locked the token and received the shares.
+    pub curator_id: AccountId,
+    /// Number of shares minted.
+    pub shares: U128,
+}
+
+/// Shares were burned and the NFT was released.
+#[event(
+    standard = "x-fractionalize",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct Redeemed {
+    /// The released token.
+    pub token_id: TokenId,
+    /// Account that burned the shares and received the token.
+    pub account_id: AccountId,
+    /// Number of shares burned.
+    pub shares: U128,
+}
+
+/// Errors that may occur when fractionalizing a token.
+#[derive(Error, Debug)]
+pub enum FractionalizeError {
+    /// Another token is already fractionalized.
+    #[error("Token `{0}` is already fractionalized")]
+    AlreadyFractionalized(TokenId),
+    /// At least one share must be minted.
+    #[error("Cannot fractionalize into zero shares")]
+    ZeroShares,
+    /// The fungible token has a nonzero supply.
+    #[error("Fungible token supply must be zero, but is {0}")]
+    SharesOutstanding(u128),
+    /// The token could not be transferred to the contract.
+    #[error(transparent)]
+    Transfer(#[from] Nep171TransferError),
+    /// The shares could not be minted.
+    #[error(transparent)]
+    Mint(#[from] DepositError),
+}
+
+/// Errors that may occur when redeeming a token.
+#[derive(Error, Debug)]
+pub enum RedeemError {
+    /// No token is fractionalized.
+    #[error("No token is fractionalized")]
+    NotFractionalized,
+    /// The account does not hold every share.
+    #[error("Account `{account_id}` holds {balance} of {shares} shares")]
+    InsufficientShares {
+        /// Redeeming account.
+        account_id: AccountId,
+        /// Shares held by the account.
+        balance: u128,
+        /// Shares required to redeem.
+        shares: u128,
+    },
+    /// The shares could not be burned.
+    #[error(transparent)]
+    Burn(#[from] WithdrawError),
+    /// The token could not be released.
+    #[error(transparent)]
+    Transfer(#[from] Nep171TransferError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    TokenId,
+}
+
+/// Internal functions for [`Fractionalize`]. Using these methods may result in unexpected behavior.
+pub trait FractionalizeInternal {
+    /// Storage root.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Fractionalize)
+    }
+
+    /// Storage slot for the ID of the fractionalized token.
+    fn slot_token_id() -> Slot<TokenId> {
+        Self::root().field(StorageKey::TokenId)
+    }
+}
+
+/// Locks an NFT in exchange for fungible shares, and releases it in exchange
+/// for all of them.
+pub trait Fractionalize {
+    /// The fractionalized token, if any.
+    fn vault(&self) -> Option<FractionalVault>;
+
+    /// Transfers `token_id` from `curator_id` to the contract and mints
+    /// `shares` fungible tokens to `curator_id`. Emits [`Fractionalized`].
+    fn fractionalize(
+        &mut self,
+        curator_id: &AccountId,
+        token_id: &TokenId,
+        shares: u128,
+    ) -> Result<(), FractionalizeError>;
+
+    /// Burns every share from `account_id` and transfers the fractionalized
+    /// token to it. Returns the token ID. Emits [`Redeemed`].
+    fn redeem(&mut self, account_id: &AccountId) -> Result<TokenId, RedeemError>;
+}
+
+impl<T> Fractionalize for T
+where
+    T: FractionalizeInternal
+        + Nep141Controller
+        + Nep171Controller
+        + EscrowInternal<Id = TokenId, State = FractionalVault>,
+{
+    fn vault(&self) -> Option<FractionalVault> {
+        Self::slot_token_id()
+            .read()
+            .and_then(|token_id| self.get_locked(&token_id))
+    }
+
+    fn fractionalize(
+        &mut self,
+        curator_id: &AccountId,
+        token_id: &TokenId,
+        shares: u128,
+    ) -> Result<(), FractionalizeError> {
+        if let Some(locked) = Self::slot_token_id().read() {
+            return Err(FractionalizeError::AlreadyFractionalized(locked));
+        }
+
+        if shares == 0 {
+            return Err(FractionalizeError::ZeroShares);
+        }
+
+        let total_supply = Nep141Controller::total_supply(self);
+        if total_supply != 0 {
+            return Err(FractionalizeError::SharesOutstanding(total_supply));
+        }
+
+        let contract_id = env::current_account_id();
+        self.external_transfer(&Nep171Transfer {
+            authorization: Nep171TransferAuthorization::Owner,
+            sender_id: curator_id,
+            receiver_id: &contract_id,
+            token_id,
+            memo: Some("fractionalize"),
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        })?;
+
+        Nep141Controller::mint(
+            self,
+            &Nep141Mint {
+                amount: shares,
+                receiver_id: curator_id,
+                memo: Some("fractionalize"),
+                origin: ActionOrigin::Internal,
+            },
+        )?;
+
+        let vault = FractionalVault {
+            token_id: token_id.clone(),
+            curator_id: curator_id.clone(),
+            shares: shares.into(),
+        };
+        Escrow::lock(self, token_id, &vault);
+        Self::slot_token_id().write(token_id);
+
+        Fractionalized {
+            token_id: vault.token_id,
+            curator_id: vault.curator_id,
+            shares: vault.shares,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn redeem(&mut self, account_id: &AccountId) -> Result<TokenId, RedeemError> {
+        let vault = self.vault().ok_or(RedeemError::NotFractionalized)?;
+        let shares = vault.shares.0;
+
+        let balance = Nep141Controller::balance_of(self, account_id);
+        if balance < shares {
+            return Err(RedeemError::InsufficientShares {
+                account_id: account_id.clone(),
+                balance,
+                shares,
+            });
+        }
+
+        let contract_id = env::current_account_id();
+        let transfer = Nep171Transfer {
+            authorization: Nep171TransferAuthorization::Owner,
+            sender_id: &contract_id,
+            receiver_id: account_id,
+            token_id: &vault.token_id,
+            memo: Some("redeem"),
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        };
+
+        // Validate the release before burning anything.
+        Nep171Controller::can_transfer(self, &transfer)?;
+
+        Nep141Controller::burn(
+            self,
+            &Nep141Burn {
+                amount: shares,
+                owner_id: account_id,
+                memo: Some("redeem"),
+                origin: ActionOrigin::Internal,
+            },
+        )?;
+
+        Escrow::unlock(self, &vault.token_id, |_| true);
+        Self::slot_token_id().remove();
+
+        self.external_transfer(&transfer)?;
+
+        Redeemed {
+            token_id: vault.token_id.clone(),
+            account_id: account_id.clone(),
+            shares: vault.shares,
+        }
+        .emit();
+
+        Ok(vault.token_id)
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U128};
+
+    use super::FractionalVault;
+    use crate::standard::nep171::TokenId;
+
+    /// Externally-accessible functions for [`super::Fractionalize`].
+    #[ext_contract(ext_fractionalize)]
+    pub trait FractionalizeExternal {
+        /// Locks a token owned by the predecessor and mints `shares` fungible
+        /// tokens to it. Requires exactly 1 yoctoNEAR.
+        fn fractionalize_lock(&mut self, token_id: TokenId, shares: U128);
+
+        /// Burns every share held by the predecessor and releases the locked
+        /// token to it. Requires exactly 1 yoctoNEAR.
+        fn fractionalize_redeem(&mut self) -> TokenId;
+
+        /// Returns the fractionalized token, if any.
+        fn fractionalize_vault(&self) -> Option<FractionalVault>;
+    }
+}
@@ -0,0 +1,345 @@
+//! Runtime-configurable gas amounts for generated methods.
+//!
+//! Methods that create promise chains, like `ft_transfer_call` and
+//! `nft_transfer_call`, reserve fixed amounts of gas for themselves and for
+//! their resolution callbacks. The defaults (e.g.
+//! [`nep141::GAS_FOR_FT_TRANSFER_CALL`]) match the reference implementations
+//! in `near-contract-standards` for the near-sdk version this crate is built
+//! against, but protocol gas costs change over time. A contract that derives
+//! [`GasConfig`] and opts into it with `#[nep141(gas_config)]` or
+//! `#[nep171(gas_config)]` reads these amounts from storage instead, so the
+//! owner can adjust them without redeploying the contract.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::GasConfig) derives a
+//! default implementation, and exposes `gas_config_get` and the owner-only
+//! `gas_config_set`. Requires `Owner`.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The gas config root storage slot is not used or modified. The
+//!     default key is `~gas`.
+//! * (ERR) Overrides must be nonzero and at most [`MAX_GAS_OVERRIDE`].
+//! * (ERR) The gas reserved for a transfer call must exceed the gas reserved
+//!     for its resolution callback, which it includes.
+//!
+//! [`nep141::GAS_FOR_FT_TRANSFER_CALL`]: crate::standard::nep141::GAS_FOR_FT_TRANSFER_CALL
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+    BorshStorageKey, Gas,
+};
+use near_sdk_contract_tools_macros::event;
+use thiserror::Error;
+
+use crate::{
+    slot::Slot,
+    standard::{nep141, nep171, nep297::Event},
+    utils::preflight::require_gas,
+    DefaultStorageKey,
+};
+
+pub use ext::*;
+
+/// Maximum gas that may be attached to a single function call.
+pub const MAX_GAS_OVERRIDE: Gas = Gas(300_000_000_000_000);
+
+/// A configurable gas amount.
+#[derive(
+    BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum GasKey {
+    /// Gas reserved by `ft_transfer_call`, including the resolution callback.
+    FtTransferCall,
+    /// Gas attached to `ft_resolve_transfer`.
+    FtResolveTransfer,
+    /// Gas reserved by `nft_transfer_call`, including the resolution callback.
+    NftTransferCall,
+    /// Gas attached to `nft_resolve_transfer`.
+    NftResolveTransfer,
+}
+
+impl GasKey {
+    /// The compile-time default for this amount.
+    pub const fn default_gas(self) -> Gas {
+        match self {
+            Self::FtTransferCall => nep141::GAS_FOR_FT_TRANSFER_CALL,
+            Self::FtResolveTransfer => nep141::GAS_FOR_RESOLVE_TRANSFER,
+            Self::NftTransferCall => nep171::GAS_FOR_NFT_TRANSFER_CALL,
+            Self::NftResolveTransfer => nep171::GAS_FOR_RESOLVE_TRANSFER,
+        }
+    }
+
+    /// The transfer call and resolution callback pair that this key belongs
+    /// to, as `(transfer_call, resolve_transfer)`.
+    pub const fn pair(self) -> (GasKey, GasKey) {
+        match self {
+            Self::FtTransferCall | Self::FtResolveTransfer => {
+                (Self::FtTransferCall, Self::FtResolveTransfer)
+            }
+            Self::NftTransferCall | Self::NftResolveTransfer => {
+                (Self::NftTransferCall, Self::NftResolveTransfer)
+            }
+        }
+    }
+}
+
+/// A gas amount was overridden, or an override was removed.
+#[event(
+    standard = "x-gas-config",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+pub struct GasOverrideSet {
+    /// The configured amount.
+    pub key: GasKey,
+    /// The new override, or `None` if the default is restored.
+    pub gas: Option<U64>,
+}
+
+/// Errors that may occur when overriding a gas amount.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum GasOverrideError {
+    /// The override is zero.
+    #[error("Gas override for {0:?} must be nonzero")]
+    Zero(GasKey),
+    /// The override exceeds [`MAX_GAS_OVERRIDE`].
+    #[error("Gas override for {key:?} of {gas} exceeds the maximum of {}", MAX_GAS_OVERRIDE.0)]
+    TooLarge {
+        /// The configured amount.
+        key: GasKey,
+        /// The rejected override.
+        gas: u64,
+    },
+    /// The transfer call would not reserve enough gas for its callback.
+    #[error("Gas for {transfer_call:?} ({transfer_call_gas}) must exceed gas for {resolve:?} ({resolve_gas})")]
+    TransferCallBelowResolve {
+        /// The transfer call amount.
+        transfer_call: GasKey,
+        /// Gas for the transfer call after the change.
+        transfer_call_gas: u64,
+        /// The resolution callback amount.
+        resolve: GasKey,
+        /// Gas for the resolution callback after the change.
+        resolve_gas: u64,
+    },
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Override(GasKey),
+}
+
+/// Internal functions for [`GasConfig`]. Using these methods may result in unexpected behavior.
+pub trait GasConfigInternal {
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::GasConfig)
+    }
+
+    /// Storage slot for the override of a gas amount
+    fn slot_override(key: GasKey) -> Slot<u64> {
+        Self::root().field(StorageKey::Override(key))
+    }
+}
+
+/// Reads gas amounts from storage, falling back to the compile-time defaults.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{near_bindgen, Gas};
+/// use near_sdk_contract_tools::{gas::*, owner::Owner, GasConfig, Nep141, Owner};
+///
+/// #[derive(Owner, GasConfig, Nep141)]
+/// #[nep141(gas_config)]
+/// #[near_bindgen]
+/// struct Contract {}
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn raise_resolve_gas(&mut self) {
+///         Self::require_owner();
+///         self.set_gas_override(GasKey::FtResolveTransfer, Some(Gas(10_000_000_000_000)))
+///             .unwrap();
+///     }
+/// }
+/// ```
+pub trait GasConfig {
+    /// Returns the override for `key`, if any.
+    fn gas_override(key: GasKey) -> Option<Gas>;
+
+    /// Returns the gas amount in effect for `key`.
+    fn gas_for(key: GasKey) -> Gas {
+        Self::gas_override(key).unwrap_or_else(|| key.default_gas())
+    }
+
+    /// Panics if less than the gas amount in effect for `key` was attached
+    /// to the call, and returns that amount.
+    fn require_gas_for(key: GasKey) -> Gas {
+        let gas = Self::gas_for(key);
+        require_gas(gas);
+        gas
+    }
+
+    /// Overrides the gas amount for `key`, or restores the default if `gas`
+    /// is `None`. Emits [`GasOverrideSet`].
+    fn set_gas_override(&mut self, key: GasKey, gas: Option<Gas>) -> Result<(), GasOverrideError>;
+}
+
+impl<T: GasConfigInternal> GasConfig for T {
+    fn gas_override(key: GasKey) -> Option<Gas> {
+        Self::slot_override(key).read().map(Gas)
+    }
+
+    fn set_gas_override(&mut self, key: GasKey, gas: Option<Gas>) -> Result<(), GasOverrideError> {
+        if let Some(gas) = gas {
+            if gas.0 == 0 {
+                return Err(GasOverrideError::Zero(key));
+            }
+
+            if gas > MAX_GAS_OVERRIDE {
+                return Err(GasOverrideError::TooLarge { key, gas: gas.0 });
+            }
+        }
+
+        let (transfer_call, resolve) = key.pair();
+        let effective = |k: GasKey| {
+            if k == key {
+                gas.unwrap_or_else(|| k.default_gas())
+            } else {
+                Self::gas_for(k)
+            }
+        };
+        let transfer_call_gas = effective(transfer_call);
+        let resolve_gas = effective(resolve);
+
+        if transfer_call_gas <= resolve_gas {
+            return Err(GasOverrideError::TransferCallBelowResolve {
+                transfer_call,
+                transfer_call_gas: transfer_call_gas.0,
+                resolve,
+                resolve_gas: resolve_gas.0,
+            });
+        }
+
+        Self::slot_override(key).set(gas.map(|g| g.0).as_ref());
+
+        GasOverrideSet {
+            key,
+            gas: gas.map(|g| g.0.into()),
+        }
+        .emit();
+
+        Ok(())
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U64};
+
+    use super::GasKey;
+
+    /// External (public) methods for [`super::GasConfig`]
+    #[ext_contract(ext_gas_config)]
+    pub trait GasConfigExternal {
+        /// Returns the gas amount in effect for `key`
+        fn gas_config_get(&self, key: GasKey) -> U64;
+
+        /// Overrides the gas amount for `key`, or restores the default if
+        /// `gas` is `null`. Only callable by the owner. Requires exactly 1
+        /// yoctoNEAR.
+        fn gas_config_set(&mut self, key: GasKey, gas: Option<U64>);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{near_bindgen, test_utils::get_logs};
+    use near_sdk_contract_tools_macros::{GasConfig, Owner};
+
+    use super::*;
+
+    #[derive(Owner, GasConfig)]
+    #[owner(crate = "crate")]
+    #[gas_config(crate = "crate")]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn defaults_and_overrides() {
+        let mut contract = Contract {};
+
+        assert_eq!(
+            Contract::gas_for(GasKey::FtResolveTransfer),
+            nep141::GAS_FOR_RESOLVE_TRANSFER,
+        );
+        assert_eq!(Contract::gas_override(GasKey::FtResolveTransfer), None);
+
+        contract
+            .set_gas_override(GasKey::FtResolveTransfer, Some(Gas(7_000_000_000_000)))
+            .unwrap();
+
+        assert_eq!(
+            Contract::gas_for(GasKey::FtResolveTransfer),
+            Gas(7_000_000_000_000),
+        );
+        assert_eq!(
+            Contract::gas_for(GasKey::NftResolveTransfer),
+            nep171::GAS_FOR_RESOLVE_TRANSFER,
+        );
+        assert_eq!(
+            get_logs(),
+            vec![GasOverrideSet {
+                key: GasKey::FtResolveTransfer,
+                gas: Some(U64(7_000_000_000_000)),
+            }
+            .to_event_string()],
+        );
+
+        contract
+            .set_gas_override(GasKey::FtResolveTransfer, None)
+            .unwrap();
+
+        assert_eq!(
+            Contract::gas_for(GasKey::FtResolveTransfer),
+            nep141::GAS_FOR_RESOLVE_TRANSFER,
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_overrides() {
+        let mut contract = Contract {};
+        let transfer_call_gas = nep171::GAS_FOR_NFT_TRANSFER_CALL;
+
+        assert_eq!(
+            contract.set_gas_override(GasKey::NftTransferCall, Some(Gas(0))),
+            Err(GasOverrideError::Zero(GasKey::NftTransferCall)),
+        );
+        assert_eq!(
+            contract.set_gas_override(GasKey::NftTransferCall, Some(Gas(MAX_GAS_OVERRIDE.0 + 1))),
+            Err(GasOverrideError::TooLarge {
+                key: GasKey::NftTransferCall,
+                gas: MAX_GAS_OVERRIDE.0 + 1,
+            }),
+        );
+        assert_eq!(
+            contract.set_gas_override(GasKey::NftResolveTransfer, Some(transfer_call_gas)),
+            Err(GasOverrideError::TransferCallBelowResolve {
+                transfer_call: GasKey::NftTransferCall,
+                transfer_call_gas: transfer_call_gas.0,
+                resolve: GasKey::NftResolveTransfer,
+                resolve_gas: transfer_call_gas.0,
+            }),
+        );
+        assert_eq!(Contract::gas_override(GasKey::NftResolveTransfer), None);
+    }
+}
@@ -34,6 +34,73 @@
 //! #[near_bindgen]
 //! struct MyContract {}
 //! ```
+//!
+//! ## Ordering
+//!
+//! Hooks are composed with tuples of up to eight elements, which are treated
+//! as an ordered list: each element wraps all of the elements after it. For
+//! `(A, B, C)`, the execution order is:
+//!
+//! 1. `A` (before)
+//! 2. `B` (before)
+//! 3. `C` (before)
+//! 4. the wrapped function
+//! 5. `C` (after)
+//! 6. `B` (after)
+//! 7. `A` (after)
+//!
+//! A hook that panics before calling the wrapped function therefore prevents
+//! every later hook from running, so checks (e.g. pause or ban checks) should
+//! be listed before hooks that modify state (e.g. fee collection):
+//!
+//! ```ignore
+//! #[nep141(transfer_hook = "(PausableHook, FeeHook)")]
+//! ```
+//!
+//! The component derive macros compose the hooks they are given in a fixed,
+//! documented order: the action-specific hook (e.g. `transfer_hook`) runs
+//! before `all_hooks`, and both run before any integration hooks added by the
+//! macro itself (e.g. storage accounting). The `all_hooks_first` flag reverses
+//! the order of the first two.
+//!
+//! ## Origin
+//!
+//! The same action may be requested by `ft_transfer`, `ft_transfer_call`, or a
+//! direct call to the controller. The `origin` field of the action structs of
+//! the standards (e.g. [`Nep141Transfer::origin`]) tells them apart: the
+//! external methods generated by the standard derive macros set it to
+//! [`ActionOrigin::External`] with their name, or to
+//! [`ActionOrigin::Callback`] if the method is a callback (e.g.
+//! `ft_resolve_transfer`). Actions constructed by other code (e.g. a
+//! contract's own methods) should use [`ActionOrigin::Internal`]. The origin
+//! is not serialized.
+//!
+//! [`Nep141Transfer::origin`]: crate::standard::nep141::Nep141Transfer::origin
+//!
+//! ```
+//! use near_sdk::{env, near_bindgen};
+//! use near_sdk_contract_tools::{
+//!     hook::{ActionOrigin, Hook},
+//!     standard::nep141::*,
+//!     Nep141,
+//! };
+//!
+//! pub struct NoTransferCallHook;
+//!
+//! impl<C> Hook<C, Nep141Transfer<'_>> for NoTransferCallHook {
+//!     fn hook<R>(contract: &mut C, transfer: &Nep141Transfer<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+//!         if transfer.origin == ActionOrigin::External("ft_transfer_call") {
+//!             env::panic_str("ft_transfer_call is disabled");
+//!         }
+//!         f(contract)
+//!     }
+//! }
+//!
+//! #[derive(Nep141)]
+//! #[nep141(transfer_hook = "NoTransferCallHook")]
+//! #[near_bindgen]
+//! struct MyContract {}
+//! ```
 
 /// Generic hook trait for injecting code before and after component functions.
 pub trait Hook<C, A = ()> {
@@ -45,12 +112,39 @@ pub trait Hook<C, A = ()> {
 
 impl<C, A> Hook<C, A> for () {}
 
-impl<C, A, T, U> Hook<C, A> for (T, U)
-where
-    T: Hook<C, A>,
-    U: Hook<C, A>,
-{
-    fn hook<R>(contract: &mut C, args: &A, f: impl FnOnce(&mut C) -> R) -> R {
-        T::hook(contract, args, |contract| U::hook(contract, args, f))
-    }
+macro_rules! impl_hook_tuple {
+    ($first:ident $(, $rest:ident)*) => {
+        impl<C, A, $first $(, $rest)*> Hook<C, A> for ($first, $($rest,)*)
+        where
+            $first: Hook<C, A>,
+            $($rest: Hook<C, A>,)*
+        {
+            fn hook<R>(contract: &mut C, args: &A, f: impl FnOnce(&mut C) -> R) -> R {
+                $first::hook(contract, args, |contract| {
+                    <($($rest,)*) as Hook<C, A>>::hook(contract, args, f)
+                })
+            }
+        }
+    };
+}
+
+impl_hook_tuple!(T1);
+impl_hook_tuple!(T1, T2);
+impl_hook_tuple!(T1, T2, T3);
+impl_hook_tuple!(T1, T2, T3, T4);
+impl_hook_tuple!(T1, T2, T3, T4, T5);
+impl_hook_tuple!(T1, T2, T3, T4, T5, T6);
+impl_hook_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_hook_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+/// Which method requested an action. See the [module documentation](self).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ActionOrigin {
+    /// An external method generated by a derive macro, by name.
+    External(&'static str),
+    /// A callback (`#[private]` method) generated by a derive macro, by name.
+    Callback(&'static str),
+    /// Any other code, e.g. a contract calling a controller directly.
+    #[default]
+    Internal,
 }
@@ -0,0 +1,139 @@
+//! Contract-level invariant checks for development builds.
+//!
+//! Controller functions such as [`Nep141Controller::deposit_unchecked`]
+//! perform no consistency checks, and misusing them can silently corrupt
+//! contract state. A contract can declare invariants (properties that must
+//! hold after every external call) by implementing [`Invariants`], and opt
+//! into checking them with the contract-level
+//! `#[contract_tools(invariants)]` attribute. When the `debug` feature is
+//! enabled, every mutating external method generated by the derive macros in
+//! this crate then checks the invariants before returning, and panics if any
+//! are violated. Without the `debug` feature, no checks are performed.
+//!
+//! Checks may be expensive, so invariants over unbounded collections should
+//! sample them. This module provides sampled checks for common standards.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{near_bindgen, AccountId};
+//! use near_sdk_contract_tools::{invariant::*, Nep141};
+//!
+//! #[derive(Nep141)]
+//! #[contract_tools(invariants)]
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! impl Invariants for Contract {
+//!     fn invariants() -> &'static [Invariant<Self>] {
+//!         &[Invariant {
+//!             name: "sampled balances within total supply",
+//!             check: |contract| {
+//!                 nep141_balances_within_supply(contract, &["alice".parse().unwrap()])
+//!             },
+//!         }]
+//!     }
+//! }
+//! ```
+//!
+//! [`Nep141Controller::deposit_unchecked`]: crate::standard::nep141::Nep141Controller::deposit_unchecked
+
+use near_sdk::env;
+use thiserror::Error;
+
+#[cfg(feature = "nep141")]
+use near_sdk::AccountId;
+
+#[cfg(feature = "nep141")]
+use crate::standard::nep141::Nep141Controller;
+#[cfg(feature = "nep181")]
+use crate::standard::{nep171::Nep171Controller, nep181::Nep181Controller};
+
+/// A named property of a contract's state.
+pub struct Invariant<C> {
+    /// Name of the invariant, included in the panic message.
+    pub name: &'static str,
+    /// Returns an error describing the violation if the invariant does not
+    /// hold.
+    pub check: fn(&C) -> Result<(), String>,
+}
+
+/// An invariant does not hold.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Invariant violated: {name}: {message}")]
+pub struct InvariantViolationError {
+    /// Name of the invariant.
+    pub name: &'static str,
+    /// Description of the violation.
+    pub message: String,
+}
+
+/// Declares the invariants of a contract.
+pub trait Invariants: Sized + 'static {
+    /// Invariants of the contract.
+    fn invariants() -> &'static [Invariant<Self>];
+
+    /// Checks every invariant, returning the first violation.
+    fn check_invariants(&self) -> Result<(), InvariantViolationError> {
+        for invariant in Self::invariants() {
+            (invariant.check)(self).map_err(|message| InvariantViolationError {
+                name: invariant.name,
+                message,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Panics if any invariant is violated.
+    fn assert_invariants(&self) {
+        self.check_invariants()
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+}
+
+/// Checks that the balances of the sampled `accounts` sum to at most the
+/// total supply of a NEP-141 token. If the sample includes every holder, the
+/// sum must equal the total supply.
+#[cfg(feature = "nep141")]
+pub fn nep141_balances_within_supply<C: Nep141Controller>(
+    contract: &C,
+    accounts: &[AccountId],
+) -> Result<(), String> {
+    let total_supply = contract.total_supply();
+    let sum = accounts
+        .iter()
+        .try_fold(0u128, |sum, account_id| {
+            sum.checked_add(contract.balance_of(account_id))
+        })
+        .ok_or_else(|| "sum of sampled balances overflows".to_string())?;
+
+    if sum > total_supply {
+        return Err(format!(
+            "sum of sampled balances {sum} exceeds total supply {total_supply}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the first `limit` tokens in the NEP-181 enumeration have an
+/// owner.
+#[cfg(feature = "nep181")]
+pub fn nep181_tokens_have_owners<C: Nep171Controller + Nep181Controller>(
+    contract: &C,
+    limit: usize,
+) -> Result<(), String> {
+    let orphan = contract.with_tokens(|tokens| {
+        tokens
+            .iter()
+            .take(limit)
+            .find(|token_id| contract.token_owner(token_id).is_none())
+            .cloned()
+    });
+
+    match orphan {
+        Some(token_id) => Err(format!("token {token_id} has no owner")),
+        None => Ok(()),
+    }
+}
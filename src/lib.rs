@@ -1,16 +1,39 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+// `mocked-sim` enables near-sdk's `unit-testing` feature, which must not end
+// up in a deployed contract.
+#[cfg(all(feature = "mocked-sim", target_arch = "wasm32"))]
+compile_error!("The `mocked-sim` feature wraps near-sdk's mocked blockchain and is not available for Wasm builds.");
+
 use near_sdk::IntoStorageKey;
+// Allows the templates to refer to this crate by name, like user code does.
+#[cfg(all(feature = "templates", not(target_arch = "wasm32")))]
+extern crate self as near_sdk_contract_tools;
 pub use near_sdk_contract_tools_macros::*;
 
 /// Default storage keys used by various traits' `root()` functions.
+///
+/// The derive macros accept a contract-level
+/// `#[contract_tools(key_prefix = "...")]` attribute, which prepends the
+/// prefix to every default key (e.g. `"myapp~o"` for [`DefaultStorageKey::Owner`]).
+/// A component's own `storage_key` attribute takes precedence.
 #[derive(Clone, Debug)]
 pub enum DefaultStorageKey {
     /// Default storage key for [`approval::ApprovalManagerInternal::root`].
     ApprovalManager,
     /// Default storage key for [`standard::nep141::Nep141ControllerInternal::root`].
     Nep141,
+    /// Default storage key for [`standard::nep141::bridge::Nep141BridgeControllerInternal::root`].
+    Nep141Bridge,
+    /// Default storage key for [`standard::nep141::commitment::Nep141CommitmentInternal::root`].
+    Nep141Commitment,
+    /// Default storage key for [`standard::nep141::dust::Nep141DustInternal::root`].
+    Nep141Dust,
+    /// Default storage key for [`standard::nep141::pending::Nep141PendingTransfersInternal::root`].
+    Nep141Pending,
+    /// Default storage key for [`standard::nep141::subscription::Nep141SubscriptionControllerInternal::root`].
+    Nep141Subscription,
     /// Default storage key for [`standard::nep145::Nep145ControllerInternal::root`]
     Nep145,
     /// Default storage key for [`standard::nep148::Nep148ControllerInternal::root`].
@@ -23,6 +46,8 @@ pub enum DefaultStorageKey {
     Nep178,
     /// Default storage key for [`standard::nep181::Nep181ControllerInternal::root`].
     Nep181,
+    /// Default storage key for [`standard::nep199::Nep199ControllerInternal::root`].
+    Nep199,
     /// Default storage key for [`owner::OwnerInternal::root`].
     Owner,
     /// Default storage key for [`pause::PauseInternal::root`].
@@ -31,6 +56,47 @@ pub enum DefaultStorageKey {
     Rbac,
     /// Default storage key for [`escrow::EscrowInternal::root`]
     Escrow,
+    /// Default storage key for [`oracle::OracleInternal::root`].
+    Oracle,
+    /// Default storage key for [`stats::StatsInternal::root`].
+    Stats,
+    /// Default storage key for [`busy::BusyGuardInternal::root`].
+    BusyGuard,
+    /// Default storage key for the [`approval::ApprovalManagerInternal::root`]
+    /// of [`rbac::RbacAction`] requests generated by the `SimpleMultisig`
+    /// derive macro.
+    RbacApprovalManager,
+    /// Default storage key for the [`approval::ApprovalManagerInternal::root`]
+    /// of [`approval::simple_multisig::MemberAction`] requests generated by
+    /// the `SimpleMultisig` derive macro.
+    MemberApprovalManager,
+    /// Default storage key for [`raffle::RaffleInternal::root`].
+    Raffle,
+    /// Default storage key for [`treasury::TreasuryInternal::root`].
+    Treasury,
+    /// Default storage key for [`fractionalize::FractionalizeInternal::root`].
+    Fractionalize,
+    /// Default storage key for [`gas::GasConfigInternal::root`].
+    GasConfig,
+    /// Default storage key for [`storage_report::StorageReportInternal::root`].
+    StorageReport,
+    /// Default storage key for the generations of [`blue_green::BlueGreen`]
+    /// namespaces.
+    Namespace,
+    /// Default storage key for [`recovery::RecoveryInternal::root`].
+    Recovery,
+    /// Default storage key for [`banned_accounts::BannedAccountsInternal::root`].
+    BannedAccounts,
+    /// Default storage key for [`sale::SaleInternal::root`].
+    Sale,
+    /// Default storage key for [`config::ConfigInternal::root`].
+    Config,
+    /// Default storage key for [`upgrade::history::UpgradeHistoryInternal::root`].
+    UpgradeHistory,
+    /// Default storage key for [`replay::ReplayGuardInternal::root`].
+    ReplayGuard,
+    /// Default storage key for [`staking::nft::NftStakingInternal::root`].
+    NftStaking,
 }
 
 impl IntoStorageKey for DefaultStorageKey {
@@ -38,80 +104,189 @@ impl IntoStorageKey for DefaultStorageKey {
         match self {
             DefaultStorageKey::ApprovalManager => b"~am".to_vec(),
             DefaultStorageKey::Nep141 => b"~$141".to_vec(),
+            DefaultStorageKey::Nep141Bridge => b"~$141b".to_vec(),
+            DefaultStorageKey::Nep141Commitment => b"~$141m".to_vec(),
+            DefaultStorageKey::Nep141Dust => b"~$141d".to_vec(),
+            DefaultStorageKey::Nep141Pending => b"~$141p".to_vec(),
+            DefaultStorageKey::Nep141Subscription => b"~$141s".to_vec(),
             DefaultStorageKey::Nep145 => b"~$145".to_vec(),
             DefaultStorageKey::Nep148 => b"~$148".to_vec(),
             DefaultStorageKey::Nep171 => b"~$171".to_vec(),
             DefaultStorageKey::Nep177 => b"~$177".to_vec(),
             DefaultStorageKey::Nep178 => b"~$178".to_vec(),
             DefaultStorageKey::Nep181 => b"~$181".to_vec(),
+            DefaultStorageKey::Nep199 => b"~$199".to_vec(),
             DefaultStorageKey::Owner => b"~o".to_vec(),
             DefaultStorageKey::Pause => b"~p".to_vec(),
             DefaultStorageKey::Rbac => b"~r".to_vec(),
             DefaultStorageKey::Escrow => b"~es".to_vec(),
+            DefaultStorageKey::Oracle => b"~or".to_vec(),
+            DefaultStorageKey::Stats => b"~st".to_vec(),
+            DefaultStorageKey::BusyGuard => b"~bg".to_vec(),
+            DefaultStorageKey::RbacApprovalManager => b"~ram".to_vec(),
+            DefaultStorageKey::MemberApprovalManager => b"~smm".to_vec(),
+            DefaultStorageKey::Raffle => b"~rf".to_vec(),
+            DefaultStorageKey::Treasury => b"~tr".to_vec(),
+            DefaultStorageKey::Fractionalize => b"~fr".to_vec(),
+            DefaultStorageKey::GasConfig => b"~gas".to_vec(),
+            DefaultStorageKey::StorageReport => b"~sr".to_vec(),
+            DefaultStorageKey::Namespace => b"~ns".to_vec(),
+            DefaultStorageKey::Recovery => b"~rec".to_vec(),
+            DefaultStorageKey::BannedAccounts => b"~ban".to_vec(),
+            DefaultStorageKey::Sale => b"~sale".to_vec(),
+            DefaultStorageKey::Config => b"~cfg".to_vec(),
+            DefaultStorageKey::UpgradeHistory => b"~up".to_vec(),
+            DefaultStorageKey::ReplayGuard => b"~rp".to_vec(),
+            DefaultStorageKey::NftStaking => b"~nstk".to_vec(),
         }
     }
 }
 
+impl DefaultStorageKey {
+    /// The default storage key with `prefix` prepended, e.g. `"myapp~o"` for
+    /// [`DefaultStorageKey::Owner`] with prefix `"myapp"`.
+    pub fn with_prefix(self, prefix: &str) -> Vec<u8> {
+        [prefix.as_bytes(), &self.into_storage_key()].concat()
+    }
+}
+
 pub mod standard;
 
+#[cfg(feature = "approval")]
 pub mod approval;
+#[cfg(feature = "banned-accounts")]
+pub mod banned_accounts;
+pub mod blue_green;
+#[cfg(feature = "busy")]
+pub mod busy;
+pub mod chainsig;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+#[cfg(feature = "debug")]
+pub mod debug;
+#[cfg(feature = "escrow")]
 pub mod escrow;
 pub mod fast_account_id;
+#[cfg(feature = "fractionalize")]
+pub mod fractionalize;
+#[cfg(feature = "gas")]
+pub mod gas;
 pub mod hook;
+pub mod invariant;
+#[cfg(feature = "manifest")]
+pub mod manifest;
+#[cfg(feature = "migrate")]
 pub mod migrate;
+#[cfg(feature = "oracle")]
+pub mod oracle;
+#[cfg(feature = "owner")]
 pub mod owner;
+#[cfg(feature = "pause")]
 pub mod pause;
+#[cfg(feature = "proxy-admin")]
+pub mod proxy_admin;
+#[cfg(feature = "raffle")]
+pub mod raffle;
+#[cfg(feature = "rbac")]
 pub mod rbac;
+#[cfg(feature = "recovery")]
+pub mod recovery;
+#[cfg(feature = "replay")]
+pub mod replay;
+#[cfg(feature = "sale")]
+pub mod sale;
+#[cfg(feature = "mocked-sim")]
+pub mod sim;
 pub mod slot;
+#[cfg(feature = "staking")]
+pub mod staking;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(feature = "storage-report")]
+pub mod storage_report;
+pub mod storage_version;
+#[cfg(all(feature = "templates", not(target_arch = "wasm32")))]
+pub mod templates;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transaction;
+#[cfg(feature = "treasury")]
+pub mod treasury;
+#[cfg(feature = "upgrade")]
 pub mod upgrade;
 pub mod utils;
 
 /// Re-exports of the NFT standard traits.
+#[cfg(all(
+    feature = "nep145",
+    feature = "nep171",
+    feature = "nep177",
+    feature = "nep178",
+    feature = "nep181",
+))]
 pub mod nft {
     pub use crate::{
+        hook::ActionOrigin,
         standard::{
             nep145::{
                 self, ext_nep145, Nep145, Nep145Controller, Nep145ControllerInternal,
                 StorageBalance, StorageBalanceBounds,
             },
             nep171::{
-                self, action::*, ext_nep171, ext_nep171_receiver, ext_nep171_resolver, Nep171,
-                Nep171Controller, Nep171ControllerInternal, Nep171Receiver, Nep171Resolver, Token,
-                TokenId,
+                self, action::*, ext_nep171, ext_nep171_receiver, ext_nep171_resolver,
+                ext_nep171_token_owners, ext_nep171_transfer_check, LoadTokenMetadata, Nep171,
+                Nep171Controller, Nep171ControllerInternal, Nep171Receiver, Nep171Resolver,
+                Nep171TokenOwners, Nep171TransferCheck, Token, TokenFields, TokenId, TokenV1,
             },
             nep177::{
-                self, ext_nep177, ContractMetadata, Nep177, Nep177Controller,
-                Nep177ControllerInternal, TokenMetadata,
+                self, ext_nep177, ext_nep177_freeze, ContractMetadata, InvalidTokenMetadataError,
+                Nep177, Nep177Controller, Nep177ControllerInternal, Nep177Freeze, TokenMetadata,
             },
             nep178::{
                 self, action::*, ext_nep178, ext_nep178_receiver, ApprovalId, Nep178,
                 Nep178Controller, Nep178ControllerInternal, Nep178Receiver, TokenApprovals,
             },
             nep181::{
-                self, ext_nep181, Nep181, Nep181Controller, Nep181ControllerInternal,
+                self, ext_nep181, ext_nep181_compaction, ext_nep181_visibility, Nep181,
+                Nep181Compaction, Nep181Controller, Nep181ControllerInternal, Nep181Visibility,
                 TokenEnumeration,
             },
         },
         Nep171, Nep177, Nep178, Nep181, NonFungibleToken,
     };
+
+    #[cfg(feature = "nep199")]
+    pub use crate::{
+        standard::nep199::{
+            self, ext_nep199, Nep199, Nep199Controller, Nep199ControllerInternal, Payout,
+            TokenRoyalties,
+        },
+        Nep199,
+    };
 }
 
 /// Re-exports of the FT standard traits.
+#[cfg(all(feature = "nep141", feature = "nep145", feature = "nep148"))]
 pub mod ft {
     pub use crate::{
+        hook::ActionOrigin,
         standard::{
             nep141::{
                 self, ext_nep141, ext_nep141_receiver, ext_nep141_resolver, Nep141, Nep141Burn,
                 Nep141Controller, Nep141ControllerInternal, Nep141Mint, Nep141Receiver,
-                Nep141Resolver, Nep141Transfer,
+                Nep141Resolver, Nep141Transfer, Nep141TransferAction, Nep141TransferOwned,
             },
             nep145::{
                 self, ext_nep145, Nep145, Nep145Controller, Nep145ControllerInternal,
                 StorageBalance, StorageBalanceBounds,
             },
             nep148::{
-                self, ext_nep148, FungibleTokenMetadata, Nep148, Nep148Controller,
-                Nep148ControllerInternal,
+                self, ext_nep148, ext_nep148_human_views, BridgedAsset, FungibleTokenMetadata,
+                Nep148, Nep148Controller, Nep148ControllerInternal, Nep148HumanViews,
             },
         },
         FungibleToken, Nep141, Nep145, Nep148,
@@ -0,0 +1,217 @@
+//! Machine-readable descriptions of the external methods generated by the
+//! macros in this crate.
+//!
+//! Every derive macro that exposes external methods also implements
+//! [`HasManifest`] for the contract, describing each method it generates:
+//! whether it is a view or a change method, whether it accepts a deposit,
+//! how much gas it requires, and its argument and return types. The
+//! [`Manifest`](near_sdk_contract_tools_macros::Manifest) derive macro
+//! collects the manifests of the listed components into a `MANIFEST` const
+//! on the contract, and exposes it as JSON via the `__tools_manifest` view,
+//! so that frontends can configure transaction prompts (attached deposit,
+//! gas) automatically.
+//!
+//! Components that implement a NEAR standard also list it in their manifest.
+//! The `supported_standards` view aggregates these from all listed
+//! components, so that wallets, explorers, and other contracts can discover
+//! which standards (and versions) the contract implements.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{
+//!     borsh::{self, BorshDeserialize, BorshSerialize},
+//!     near_bindgen,
+//! };
+//! use near_sdk_contract_tools::{manifest::*, Manifest, Owner};
+//!
+//! #[derive(Owner, Manifest, BorshSerialize, BorshDeserialize)]
+//! #[manifest(components(Owner))]
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! let owner = &Contract::MANIFEST.components[0];
+//! let renounce = owner
+//!     .methods
+//!     .iter()
+//!     .find(|m| m.name == "own_renounce_owner")
+//!     .unwrap();
+//!
+//! assert_eq!(renounce.kind, MethodKind::Call);
+//! assert_eq!(renounce.deposit, Deposit::OneYocto);
+//! ```
+
+use near_sdk::{serde::Serialize, Gas};
+
+/// Marker types identifying the components that implement [`HasManifest`].
+/// Each marker is named after its derive macro.
+pub mod component {
+    macro_rules! markers {
+        ($($name:ident),* $(,)?) => {
+            $(
+                #[doc = concat!("Methods generated by `#[derive(", stringify!($name), ")]`.")]
+                #[derive(Clone, Copy, Debug)]
+                pub struct $name;
+            )*
+        };
+    }
+
+    markers!(
+        BannedAccounts,
+        BusyGuard,
+        Config,
+        Fractionalize,
+        GasConfig,
+        Migrate,
+        Nep141,
+        Nep141Bridge,
+        Nep141Subscription,
+        Nep145,
+        Nep148,
+        Nep171,
+        Nep177,
+        Nep178,
+        Nep181,
+        Nep199,
+        NftStaking,
+        NonFungibleToken,
+        Oracle,
+        Owner,
+        Pause,
+        Raffle,
+        Rbac,
+        Recovery,
+        ReplayGuard,
+        Sale,
+        SimpleMultisig,
+        Stats,
+        StorageDump,
+        StorageReport,
+        Treasury,
+        Upgrade,
+    );
+}
+
+/// How a method is invoked.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum MethodKind {
+    /// Read-only; may be called without a transaction.
+    View,
+    /// Modifies state; must be called in a transaction.
+    Call,
+    /// Initializes the contract.
+    Init,
+}
+
+/// The deposit a method expects.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum Deposit {
+    /// The method rejects attached deposits.
+    None,
+    /// The method requires exactly 1 yoctoNEAR.
+    OneYocto,
+    /// The method accepts a deposit (e.g. for storage fees). The required
+    /// amount depends on the contract's state.
+    Attached,
+}
+
+/// An argument of a method.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ArgumentManifest {
+    /// Name of the argument in the JSON arguments object.
+    pub name: &'static str,
+    /// Rust type of the argument, e.g. `Option<U128>`.
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+}
+
+/// Description of an external method.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MethodManifest {
+    /// Name of the method.
+    pub name: &'static str,
+    /// How the method is invoked.
+    pub kind: MethodKind,
+    /// Whether the method is marked `#[payable]`.
+    pub payable: bool,
+    /// Whether the method may only be called by the contract itself.
+    pub private: bool,
+    /// The deposit the method expects.
+    pub deposit: Deposit,
+    /// The minimum amount of gas the method requires, if it checks. For
+    /// amounts configurable with [`crate::gas::GasConfig`], this is the
+    /// default.
+    pub gas: Option<Gas>,
+    /// Arguments of the method.
+    pub args: &'static [ArgumentManifest],
+    /// Rust return type of the method, if any.
+    pub returns: Option<&'static str>,
+}
+
+/// A NEAR standard implemented by a component.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StandardManifest {
+    /// Name of the standard, e.g. `nep141`.
+    pub standard: &'static str,
+    /// Implemented version of the standard, e.g. `1.0.0`.
+    pub version: &'static str,
+}
+
+/// Description of the external methods generated by a component.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ComponentManifest {
+    /// Name of the component's derive macro.
+    pub name: &'static str,
+    /// Methods generated by the component.
+    pub methods: &'static [MethodManifest],
+    /// Standards implemented by the component.
+    pub standards: &'static [StandardManifest],
+}
+
+/// Description of the external methods of a contract.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractManifest {
+    /// Version of `near-sdk-contract-tools` that generated the methods.
+    pub version: &'static str,
+    /// Components of the contract.
+    pub components: &'static [ComponentManifest],
+}
+
+impl ContractManifest {
+    /// Version of `near-sdk-contract-tools`.
+    pub const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    /// Finds a method by name in any component.
+    pub fn method(&self, name: &str) -> Option<&'static MethodManifest> {
+        self.components
+            .iter()
+            .flat_map(|c| c.methods.iter())
+            .find(|m| m.name == name)
+    }
+
+    /// Standards implemented by any component, without duplicates, in the
+    /// order in which they first appear.
+    pub fn standards(&self) -> Vec<StandardManifest> {
+        let mut standards = Vec::<StandardManifest>::new();
+        for standard in self.components.iter().flat_map(|c| c.standards.iter()) {
+            if !standards.contains(standard) {
+                standards.push(*standard);
+            }
+        }
+        standards
+    }
+}
+
+/// Implemented by the derive macros for every component that exposes
+/// external methods. `C` is the component's marker type from [`component`].
+pub trait HasManifest<C> {
+    /// Description of the methods generated by the component.
+    const MANIFEST: ComponentManifest;
+}
@@ -53,6 +53,19 @@ pub trait MigrateHook: MigrateController {
     ) -> <Self as MigrateController>::NewSchema;
 }
 
+/// Called on migration with arguments from the caller, e.g. the post-upgrade
+/// arguments of an [`UpgradeAction`](crate::upgrade::action::UpgradeAction).
+/// Used instead of [`MigrateHook`] when the derive macro is given an `args`
+/// type. Must be implemented by the user.
+pub trait MigrateHookWithArgs<Args>: MigrateController {
+    /// Receives the old schema deserialized from storage as well as the
+    /// arguments from the caller, and replaces it with the new schema.
+    fn on_migrate_with_args(
+        old_schema: <Self as MigrateController>::OldSchema,
+        args: Args,
+    ) -> <Self as MigrateController>::NewSchema;
+}
+
 /// Migrate-able contracts expose this trait publicly
 #[ext_contract(ext_migrate)]
 pub trait MigrateExternal {
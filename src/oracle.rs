@@ -0,0 +1,415 @@
+//! Consumer component for push-style price oracles.
+//!
+//! A trusted oracle account pushes prices to the contract by calling
+//! [`OracleExternal::oracle_on_price_update`]. Each price is stored with the
+//! block timestamp at which it was received. Other components read prices
+//! with [`Oracle::get_price_or_err`], which rejects prices older than the
+//! configured staleness window.
+//!
+//! The oracle account and staleness window must be initialized (e.g. at
+//! contract creation) using [`Oracle::init`].
+//!
+//! The pattern consists of methods in [`Oracle`] and [`OracleExternal`]. The
+//! latter exposes methods externally. This
+//! [derive macro](near_sdk_contract_tools_macros::Oracle) derives default
+//! implementations of both traits.
+//!
+//! # Safety
+//!
+//! * (UB) The oracle root storage slot is not used or modified. The default key is `~or`.
+//! * (ERR) [`Oracle::init`] may be called a maximum of one time.
+//! * (ERR) Only the oracle account may call [`OracleExternal::oracle_on_price_update`].
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::{U128, U64},
+    require,
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+use thiserror::Error;
+
+use crate::{slot::Slot, standard::nep297::Event, DefaultStorageKey};
+
+pub use crate::utils::AlreadyInitializedError;
+pub use ext::*;
+
+const NO_ORACLE_FAIL_MESSAGE: &str = "No oracle";
+const ONLY_ORACLE_FAIL_MESSAGE: &str = "Oracle only";
+
+/// Events emitted by the oracle component.
+#[event(
+    standard = "x-oracle",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum OracleEvent {
+    /// Emitted when the trusted oracle account changes.
+    OracleChange {
+        /// Former oracle account.
+        old: Option<AccountId>,
+        /// New oracle account.
+        new: AccountId,
+    },
+    /// Emitted when a price is updated.
+    PriceUpdate {
+        /// Asset identifier.
+        asset_id: String,
+        /// New price.
+        price: Price,
+    },
+}
+
+/// A price expressed as `multiplier * 10^-decimals`.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Price {
+    /// Price multiplier.
+    pub multiplier: U128,
+    /// Number of decimal places.
+    pub decimals: u8,
+}
+
+/// A price as pushed by the oracle.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AssetPrice {
+    /// Asset identifier.
+    pub asset_id: String,
+    /// Price of the asset.
+    pub price: Price,
+}
+
+/// A stored price and the time it was received.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceEntry {
+    /// The price.
+    pub price: Price,
+    /// Block timestamp at which the price was received.
+    pub updated_at_nanoseconds: U64,
+}
+
+/// Errors that can occur when reading a price.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum OracleError {
+    /// No price has been received for the asset.
+    #[error("No price for asset `{0}`")]
+    PriceNotFound(String),
+    /// The most recent price is older than the staleness window.
+    #[error("Price for asset `{asset_id}` is stale: {age_nanoseconds}ns old, maximum {staleness_window_nanoseconds}ns")]
+    StalePrice {
+        /// Asset identifier.
+        asset_id: String,
+        /// Age of the price.
+        age_nanoseconds: u64,
+        /// Maximum permitted age.
+        staleness_window_nanoseconds: u64,
+    },
+}
+
+#[derive(BorshSerialize, BorshStorageKey, Debug, Clone)]
+enum StorageKey<'a> {
+    IsInitialized,
+    OracleAccountId,
+    StalenessWindow,
+    Price(&'a str),
+}
+
+/// Internal functions for [`Oracle`]. Using these methods may result in unexpected behavior.
+pub trait OracleInternal {
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Oracle)
+    }
+
+    /// Storage slot for initialization state
+    fn slot_is_initialized() -> Slot<bool> {
+        Self::root().field(StorageKey::IsInitialized)
+    }
+
+    /// Storage slot for the trusted oracle account ID
+    fn slot_oracle_account_id() -> Slot<AccountId> {
+        Self::root().field(StorageKey::OracleAccountId)
+    }
+
+    /// Storage slot for the staleness window, in nanoseconds
+    fn slot_staleness_window() -> Slot<u64> {
+        Self::root().field(StorageKey::StalenessWindow)
+    }
+
+    /// Storage slot for the most recent price of an asset
+    fn slot_price(asset_id: &str) -> Slot<PriceEntry> {
+        Self::root().field(StorageKey::Price(asset_id))
+    }
+}
+
+/// A contract that consumes prices from a trusted oracle.
+pub trait Oracle {
+    /// Initializes the oracle account and the staleness window. Can only be
+    /// called once.
+    ///
+    /// Emits an `OracleEvent::OracleChange` event.
+    fn init(&mut self, oracle_account_id: &AccountId, staleness_window_nanoseconds: u64);
+
+    /// Like [`Oracle::init`], but returns an error instead of panicking if
+    /// the oracle has already been initialized.
+    fn try_init(
+        &mut self,
+        oracle_account_id: &AccountId,
+        staleness_window_nanoseconds: u64,
+    ) -> Result<(), AlreadyInitializedError>;
+
+    /// Whether [`Oracle::init`] has been called.
+    fn is_initialized(&self) -> bool;
+
+    /// Returns the trusted oracle account ID.
+    fn oracle_account_id(&self) -> Option<AccountId>;
+
+    /// Changes the trusted oracle account. Performs no access checks.
+    ///
+    /// Emits an `OracleEvent::OracleChange` event.
+    fn set_oracle_account_id(&mut self, oracle_account_id: &AccountId);
+
+    /// Returns the maximum permitted age of a price, in nanoseconds.
+    fn staleness_window(&self) -> u64;
+
+    /// Sets the maximum permitted age of a price, in nanoseconds. Performs no
+    /// access checks.
+    fn set_staleness_window(&mut self, staleness_window_nanoseconds: u64);
+
+    /// Requires the predecessor to be the trusted oracle account.
+    fn require_oracle();
+
+    /// Records prices as of the current block. Performs no access checks.
+    ///
+    /// Emits an `OracleEvent::PriceUpdate` event for each price.
+    fn record_prices(&mut self, prices: &[AssetPrice]);
+
+    /// Returns the most recent price of an asset, regardless of its age.
+    fn get_price(&self, asset_id: &str) -> Option<PriceEntry>;
+
+    /// Returns the most recent price of an asset if it is within the
+    /// staleness window.
+    fn get_price_or_err(&self, asset_id: &str) -> Result<Price, OracleError>;
+}
+
+impl<T: OracleInternal> Oracle for T {
+    fn init(&mut self, oracle_account_id: &AccountId, staleness_window_nanoseconds: u64) {
+        self.try_init(oracle_account_id, staleness_window_nanoseconds)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    fn try_init(
+        &mut self,
+        oracle_account_id: &AccountId,
+        staleness_window_nanoseconds: u64,
+    ) -> Result<(), AlreadyInitializedError> {
+        if self.is_initialized() {
+            return Err(AlreadyInitializedError {
+                component: "Oracle",
+            });
+        }
+
+        Self::slot_is_initialized().write(&true);
+        Self::slot_staleness_window().write(&staleness_window_nanoseconds);
+        self.set_oracle_account_id(oracle_account_id);
+
+        Ok(())
+    }
+
+    fn is_initialized(&self) -> bool {
+        Self::slot_is_initialized().exists()
+    }
+
+    fn oracle_account_id(&self) -> Option<AccountId> {
+        Self::slot_oracle_account_id().read()
+    }
+
+    fn set_oracle_account_id(&mut self, oracle_account_id: &AccountId) {
+        let old = Self::slot_oracle_account_id().swap(oracle_account_id);
+
+        OracleEvent::OracleChange {
+            old,
+            new: oracle_account_id.clone(),
+        }
+        .emit();
+    }
+
+    fn staleness_window(&self) -> u64 {
+        Self::slot_staleness_window().read().unwrap_or(0)
+    }
+
+    fn set_staleness_window(&mut self, staleness_window_nanoseconds: u64) {
+        Self::slot_staleness_window().write(&staleness_window_nanoseconds);
+    }
+
+    fn require_oracle() {
+        require!(
+            env::predecessor_account_id()
+                == Self::slot_oracle_account_id()
+                    .read()
+                    .unwrap_or_else(|| env::panic_str(NO_ORACLE_FAIL_MESSAGE)),
+            ONLY_ORACLE_FAIL_MESSAGE,
+        );
+    }
+
+    fn record_prices(&mut self, prices: &[AssetPrice]) {
+        let updated_at_nanoseconds = env::block_timestamp().into();
+
+        for AssetPrice { asset_id, price } in prices {
+            Self::slot_price(asset_id).write(&PriceEntry {
+                price: *price,
+                updated_at_nanoseconds,
+            });
+
+            OracleEvent::PriceUpdate {
+                asset_id: asset_id.clone(),
+                price: *price,
+            }
+            .emit();
+        }
+    }
+
+    fn get_price(&self, asset_id: &str) -> Option<PriceEntry> {
+        Self::slot_price(asset_id).read()
+    }
+
+    fn get_price_or_err(&self, asset_id: &str) -> Result<Price, OracleError> {
+        let entry = self
+            .get_price(asset_id)
+            .ok_or_else(|| OracleError::PriceNotFound(asset_id.to_string()))?;
+
+        let age_nanoseconds = env::block_timestamp().saturating_sub(entry.updated_at_nanoseconds.0);
+        let staleness_window_nanoseconds = self.staleness_window();
+
+        if age_nanoseconds > staleness_window_nanoseconds {
+            return Err(OracleError::StalePrice {
+                asset_id: asset_id.to_string(),
+                age_nanoseconds,
+                staleness_window_nanoseconds,
+            });
+        }
+
+        Ok(entry.price)
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, AccountId};
+
+    use super::{AssetPrice, PriceEntry};
+
+    /// Externally-accessible functions for `Oracle`.
+    #[ext_contract(ext_oracle)]
+    pub trait OracleExternal {
+        /// Returns the account ID of the trusted oracle.
+        fn oracle_get_account_id(&self) -> Option<AccountId>;
+
+        /// Returns whether the oracle has been initialized.
+        fn oracle_is_initialized(&self) -> bool;
+
+        /// Returns the most recent price of an asset and when it was received.
+        fn oracle_get_price(&self, asset_id: String) -> Option<PriceEntry>;
+
+        /// Receives prices pushed by the oracle. Can only be called by the
+        /// oracle account.
+        fn oracle_on_price_update(&mut self, prices: Vec<AssetPrice>);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId};
+
+    use super::*;
+
+    #[derive(crate::Oracle)]
+    #[oracle(crate = "crate")]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn oracle() -> AccountId {
+        "oracle".parse().unwrap()
+    }
+
+    fn price(multiplier: u128) -> AssetPrice {
+        AssetPrice {
+            asset_id: "wrap.near".to_string(),
+            price: Price {
+                multiplier: multiplier.into(),
+                decimals: 2,
+            },
+        }
+    }
+
+    fn context(predecessor: AccountId, block_timestamp: u64) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .block_timestamp(block_timestamp)
+            .build());
+    }
+
+    #[test]
+    fn price_update_and_staleness() {
+        let mut c = Contract {};
+        c.init(&oracle(), 100);
+
+        assert_eq!(
+            c.get_price_or_err("wrap.near"),
+            Err(OracleError::PriceNotFound("wrap.near".to_string())),
+        );
+
+        context(oracle(), 1000);
+        c.oracle_on_price_update(vec![price(250)]);
+        assert_eq!(c.get_price_or_err("wrap.near"), Ok(price(250).price));
+
+        context(oracle(), 1100);
+        assert_eq!(c.get_price_or_err("wrap.near"), Ok(price(250).price));
+
+        context(oracle(), 1101);
+        assert_eq!(
+            c.get_price_or_err("wrap.near"),
+            Err(OracleError::StalePrice {
+                asset_id: "wrap.near".to_string(),
+                age_nanoseconds: 101,
+                staleness_window_nanoseconds: 100,
+            }),
+        );
+        assert_eq!(
+            c.oracle_get_price("wrap.near".to_string()),
+            Some(PriceEntry {
+                price: price(250).price,
+                updated_at_nanoseconds: 1000.into(),
+            }),
+        );
+    }
+
+    #[test]
+    #[should_panic = "Oracle only"]
+    fn price_update_requires_oracle() {
+        let mut c = Contract {};
+        c.init(&oracle(), 100);
+
+        context("alice".parse().unwrap(), 0);
+        c.oracle_on_price_update(vec![price(1)]);
+    }
+
+    #[test]
+    #[should_panic = "Oracle already initialized"]
+    fn init_twice() {
+        let mut c = Contract {};
+        c.init(&oracle(), 100);
+        c.init(&oracle(), 100);
+    }
+}
@@ -30,6 +30,15 @@
 //! * (ERR) Only the proposed owner can call [`Owner::accept_owner`].
 //! * (ERR) The external functions exposed in [`OwnerExternal`] call their
 //!   respective [`Owner`] methods and expect the same invariants.
+//!
+//! # Ownable2Step aliases
+//!
+//! Teams porting processes built around OpenZeppelin's `Ownable2Step` may opt
+//! into [`Ownable2StepExternal`] with `#[owner(ownable2step)]`. It exposes
+//! `owner`, `pending_owner`, `transfer_ownership`, `accept_ownership`, and
+//! `renounce_ownership`, which map to the owner flow above via
+//! [`Ownable2Step`], and additionally emit [`Ownable2StepEvent`]s named after
+//! their Solidity counterparts.
 
 use near_sdk::{
     borsh::{self, BorshSerialize},
@@ -37,10 +46,13 @@ use near_sdk::{
 };
 use near_sdk_contract_tools_macros::event;
 
-use crate::{slot::Slot, standard::nep297::Event, DefaultStorageKey};
+use crate::{
+    slot::Slot, standard::nep297::Event, storage_version::StorageVersion, DefaultStorageKey,
+};
+
+pub use crate::utils::AlreadyInitializedError;
 
 const ONLY_OWNER_FAIL_MESSAGE: &str = "Owner only";
-const OWNER_INIT_FAIL_MESSAGE: &str = "Owner already initialized";
 const NO_OWNER_FAIL_MESSAGE: &str = "No owner";
 const ONLY_PROPOSED_OWNER_FAIL_MESSAGE: &str = "Proposed owner only";
 const NO_PROPOSED_OWNER_FAIL_MESSAGE: &str = "No proposed owner";
@@ -70,6 +82,33 @@ pub enum OwnerEvent {
     },
 }
 
+/// Events emitted by the [`Ownable2Step`] aliases, in addition to
+/// [`OwnerEvent`]s. Named after the events of OpenZeppelin's `Ownable2Step`.
+#[event(
+    standard = "x-ownable2step",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum Ownable2StepEvent {
+    /// Emitted when the current owner proposes a new owner.
+    OwnershipTransferStarted {
+        /// Current owner of the contract.
+        previous_owner: AccountId,
+        /// Proposed owner of the contract.
+        new_owner: AccountId,
+    },
+    /// Emitted when ownership is accepted or renounced.
+    OwnershipTransferred {
+        /// Former owner of the contract.
+        previous_owner: AccountId,
+        /// The new owner of the contract. Will be `None` if ownership is
+        /// renounced.
+        new_owner: Option<AccountId>,
+    },
+}
+
 #[derive(BorshSerialize, BorshStorageKey, Debug, Clone)]
 enum StorageKey {
     IsInitialized,
@@ -77,6 +116,10 @@ enum StorageKey {
     ProposedOwner,
 }
 
+/// Storage layout version of the Owner component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Owner", 1);
+
 /// Internal functions for [`Owner`]. Using these methods may result in unexpected behavior.
 pub trait OwnerInternal {
     /// Storage root
@@ -144,6 +187,15 @@ pub trait Owner {
     /// ```
     fn init(&mut self, owner_id: &AccountId);
 
+    /// Like [`Owner::init`], but returns an error instead of panicking if the
+    /// owner has already been initialized. Useful for factories and
+    /// migrations that may run against already-initialized state.
+    fn try_init(&mut self, owner_id: &AccountId) -> Result<(), AlreadyInitializedError>;
+
+    /// Whether [`Owner::init`] has been called. Remains `true` after
+    /// ownership is renounced.
+    fn is_initialized(&self) -> bool;
+
     /// Requires the predecessor to be the owner.
     ///
     /// # Examples
@@ -239,10 +291,14 @@ impl<T: OwnerInternal> Owner for T {
     }
 
     fn init(&mut self, owner_id: &AccountId) {
-        require!(
-            !Self::slot_is_initialized().exists(),
-            OWNER_INIT_FAIL_MESSAGE,
-        );
+        self.try_init(owner_id)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    fn try_init(&mut self, owner_id: &AccountId) -> Result<(), AlreadyInitializedError> {
+        if self.is_initialized() {
+            return Err(AlreadyInitializedError { component: "Owner" });
+        }
 
         Self::slot_is_initialized().write(&true);
         Self::slot_owner().write(owner_id);
@@ -252,6 +308,12 @@ impl<T: OwnerInternal> Owner for T {
             new: Some(owner_id.clone()),
         }
         .emit();
+
+        Ok(())
+    }
+
+    fn is_initialized(&self) -> bool {
+        Self::slot_is_initialized().exists()
     }
 
     fn require_owner() {
@@ -298,6 +360,63 @@ impl<T: OwnerInternal> Owner for T {
     }
 }
 
+/// `Ownable2Step`-style aliases for the [`Owner`] flow.
+pub trait Ownable2Step {
+    /// Proposes `new_owner` as the next owner. Can only be called by the
+    /// current owner. Equivalent to [`Owner::propose_owner`].
+    ///
+    /// Emits [`Ownable2StepEvent::OwnershipTransferStarted`].
+    fn transfer_ownership(&mut self, new_owner: AccountId);
+
+    /// Accepts ownership. Can only be called by the proposed owner.
+    /// Equivalent to [`Owner::accept_owner`].
+    ///
+    /// Emits [`Ownable2StepEvent::OwnershipTransferred`].
+    fn accept_ownership(&mut self);
+
+    /// Renounces ownership. Can only be called by the current owner.
+    /// Equivalent to [`Owner::renounce_owner`].
+    ///
+    /// Emits [`Ownable2StepEvent::OwnershipTransferred`].
+    fn renounce_ownership(&mut self);
+}
+
+impl<T: OwnerInternal> Ownable2Step for T {
+    fn transfer_ownership(&mut self, new_owner: AccountId) {
+        self.propose_owner(Some(new_owner.clone()));
+
+        Ownable2StepEvent::OwnershipTransferStarted {
+            previous_owner: env::predecessor_account_id(),
+            new_owner,
+        }
+        .emit();
+    }
+
+    fn accept_ownership(&mut self) {
+        let previous_owner = Self::slot_owner()
+            .read()
+            .unwrap_or_else(|| env::panic_str(NO_OWNER_FAIL_MESSAGE));
+
+        self.accept_owner();
+
+        Ownable2StepEvent::OwnershipTransferred {
+            previous_owner,
+            new_owner: Some(env::predecessor_account_id()),
+        }
+        .emit();
+    }
+
+    fn renounce_ownership(&mut self) {
+        self.renounce_owner();
+
+        Ownable2StepEvent::OwnershipTransferred {
+            previous_owner: env::predecessor_account_id(),
+            new_owner: None,
+        }
+        .emit();
+    }
+}
+
 pub mod hooks {
     //! Hooks for integrating the owner component with other components.
 
@@ -333,6 +452,9 @@ mod ext {
         /// Returns the account ID that the current owner has proposed take over ownership.
         fn own_get_proposed_owner(&self) -> Option<AccountId>;
 
+        /// Returns whether the owner has been initialized.
+        fn own_is_initialized(&self) -> bool;
+
         /// Current owner may call this function to renounce ownership, setting
         /// current owner to `None`.
         ///
@@ -347,6 +469,28 @@ mod ext {
         /// previous owner.
         fn own_accept_owner(&mut self);
     }
+
+    /// `Ownable2Step`-style aliases for `OwnerExternal`.
+    #[ext_contract(ext_ownable2step)]
+    pub trait Ownable2StepExternal {
+        /// Returns the account ID of the current owner.
+        fn owner(&self) -> Option<AccountId>;
+
+        /// Returns the account ID of the proposed owner.
+        fn pending_owner(&self) -> Option<AccountId>;
+
+        /// Propose a new owner. Can only be called by the current owner.
+        fn transfer_ownership(&mut self, new_owner: AccountId);
+
+        /// The proposed owner may call this function to accept ownership.
+        fn accept_ownership(&mut self);
+
+        /// Current owner may call this function to renounce ownership.
+        ///
+        /// **WARNING**: There is no way to recover ownership of a renounced
+        /// contract.
+        fn renounce_ownership(&mut self);
+    }
 }
 pub use ext::*;
 
@@ -355,7 +499,7 @@ mod tests {
     use near_sdk::{near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId};
 
     use crate::{
-        owner::{Owner, OwnerExternal},
+        owner::{AlreadyInitializedError, Owner, OwnerExternal},
         Owner,
     };
 
@@ -409,6 +553,33 @@ mod tests {
         contract.owner_only();
     }
 
+    #[test]
+    fn try_init() {
+        let owner_id: AccountId = "owner".parse().unwrap();
+        let alice: AccountId = "alice".parse().unwrap();
+
+        let mut contract = Contract {};
+        assert!(!contract.own_is_initialized());
+
+        contract.try_init(&owner_id).unwrap();
+        assert!(contract.own_is_initialized());
+
+        assert_eq!(
+            contract.try_init(&alice),
+            Err(AlreadyInitializedError { component: "Owner" }),
+        );
+        assert_eq!(contract.own_get_owner(), Some(owner_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner already initialized")]
+    fn init_twice() {
+        let owner_id: AccountId = "owner".parse().unwrap();
+
+        let mut contract = Contract::new(owner_id.clone());
+        Owner::init(&mut contract, &owner_id);
+    }
+
     #[test]
     fn renounce_owner() {
         let owner_id: AccountId = "owner".parse().unwrap();
@@ -9,6 +9,13 @@
 //! This [derive macro](near_sdk_contract_tools_macros::Pause)
 //! derives a default implementation for both these traits.
 //!
+//! Generated view methods (e.g. `ft_balance_of`) remain callable while the
+//! contract is paused. With `#[contract_tools(pause_views)]` on the contract,
+//! the derive macros make their view methods call
+//! [`Pause::require_unpaused`] first, except `paus_is_paused`.
+//! `#[contract_tools(pause_views(ft_balance_of, nft_token))]` disables only
+//! the listed methods, and rejects names that are not generated view methods.
+//!
 //! # Safety
 //! The default implementation assumes or enforces the following invariants.
 //! Violating assumed invariants may corrupt contract state and show unexpected
@@ -22,7 +29,9 @@
 //! * (ERR) [`Pause::require_paused`] may only be called when the contract is paused.
 //! * (ERR) [`Pause::require_unpaused`] may only be called when the contract is unpaused.
 
-use crate::{slot::Slot, standard::nep297::Event, DefaultStorageKey};
+use crate::{
+    slot::Slot, standard::nep297::Event, storage_version::StorageVersion, DefaultStorageKey,
+};
 use near_sdk::require;
 use near_sdk_contract_tools_macros::event;
 
@@ -44,6 +53,10 @@ pub enum PauseEvent {
     Unpause,
 }
 
+/// Storage layout version of the Pause component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Pause", 1);
+
 /// Internal functions for [`Pause`]. Using these methods may result in unexpected behavior.
 pub trait PauseInternal {
     /// Storage root
@@ -0,0 +1,198 @@
+//! Administration of other contracts built with this crate ("child"
+//! contracts), e.g. the fungible and non-fungible tokens of an organization,
+//! from a single administration hub contract.
+//!
+//! The hub becomes the owner of each child (see [`crate::owner`]), and
+//! administers them with [`ProxyAdminAction`]s approved through the approval
+//! subsystem (e.g. a [`SimpleMultisig`](crate::approval::simple_multisig)).
+//! Executing an action calls the child:
+//!
+//! * [`ChildCall::ProposeOwner`], [`ChildCall::AcceptOwner`], and
+//!     [`ChildCall::RenounceOwner`] call the `own_*` methods of
+//!     [`OwnerExternal`](crate::owner::OwnerExternal), attaching 1 yoctoNEAR.
+//! * [`ChildCall::Pause`] and [`ChildCall::Unpause`] call the `pause` and
+//!     `unpause` methods of [`PauseAdminExternal`], which contracts using
+//!     [`Pause`](crate::pause::Pause) conventionally expose to their owner.
+//! * [`ChildCall::Upgrade`] calls the `upgrade` method generated by the
+//!     [`Upgrade`](near_sdk_contract_tools_macros::Upgrade) derive macro
+//!     with the default (JSON) serializer, which the child must restrict to
+//!     its owner (e.g. with `#[upgrade(hook = "owner")]`).
+//!
+//! [`ChildContract`] makes the same calls outside of the approval flow.
+//!
+//! To take over an existing child, its owner proposes the hub as the new
+//! owner, and the hub executes [`ChildCall::AcceptOwner`].
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{near_bindgen, AccountId, Promise};
+//! use near_sdk_contract_tools::proxy_admin::ChildContract;
+//!
+//! #[near_bindgen]
+//! struct Hub {}
+//!
+//! #[near_bindgen]
+//! impl Hub {
+//!     pub fn pause_token(&mut self, token_id: AccountId) -> Promise {
+//!         // Check that the predecessor is authorized...
+//!         ChildContract::new(token_id).pause()
+//!     }
+//! }
+//! ```
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::Base64VecU8,
+    serde::{Deserialize, Serialize},
+    AccountId, Promise,
+};
+
+use crate::{approval::Action, owner::ext_owner};
+
+/// A call to a child contract.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ChildCall {
+    /// Proposes a new owner of the child, or `None` to cancel the proposal.
+    ProposeOwner {
+        /// The proposed owner.
+        account_id: Option<AccountId>,
+    },
+    /// Accepts ownership of the child, which must have proposed this
+    /// contract as its owner.
+    AcceptOwner,
+    /// Renounces ownership of the child.
+    ///
+    /// **WARNING**: The child cannot have an owner again.
+    RenounceOwner,
+    /// Pauses the child.
+    Pause,
+    /// Unpauses the child.
+    Unpause,
+    /// Deploys new code to the child, which then migrates its state.
+    Upgrade {
+        /// WASM binary blob
+        code: Base64VecU8,
+    },
+}
+
+/// Approval action that calls a child contract.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk_contract_tools::proxy_admin::{ChildCall, ProxyAdminAction};
+///
+/// let action = ProxyAdminAction::new("token.example.near".parse().unwrap(), ChildCall::Pause);
+/// ```
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProxyAdminAction {
+    /// The child contract.
+    pub contract_id: AccountId,
+    /// The call to make.
+    pub call: ChildCall,
+}
+
+impl ProxyAdminAction {
+    /// Creates an action that makes `call` to `contract_id`.
+    pub fn new(contract_id: AccountId, call: ChildCall) -> Self {
+        Self { contract_id, call }
+    }
+}
+
+impl<C> Action<C> for ProxyAdminAction {
+    type Output = Promise;
+
+    fn execute(self, _contract: &mut C) -> Self::Output {
+        ChildContract::new(self.contract_id).call(self.call)
+    }
+}
+
+/// Typed calls to the administration methods of a child contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChildContract {
+    /// The child contract.
+    pub contract_id: AccountId,
+}
+
+impl ChildContract {
+    /// Calls to `contract_id`.
+    pub fn new(contract_id: AccountId) -> Self {
+        Self { contract_id }
+    }
+
+    /// Makes `call` to the child.
+    pub fn call(self, call: ChildCall) -> Promise {
+        match call {
+            ChildCall::ProposeOwner { account_id } => self.propose_owner(account_id),
+            ChildCall::AcceptOwner => self.accept_owner(),
+            ChildCall::RenounceOwner => self.renounce_owner(),
+            ChildCall::Pause => self.pause(),
+            ChildCall::Unpause => self.unpause(),
+            ChildCall::Upgrade { code } => self.upgrade(code),
+        }
+    }
+
+    /// Proposes a new owner of the child.
+    pub fn propose_owner(self, account_id: Option<AccountId>) -> Promise {
+        ext_owner::ext(self.contract_id)
+            .with_attached_deposit(1)
+            .own_propose_owner(account_id)
+    }
+
+    /// Accepts ownership of the child.
+    pub fn accept_owner(self) -> Promise {
+        ext_owner::ext(self.contract_id)
+            .with_attached_deposit(1)
+            .own_accept_owner()
+    }
+
+    /// Renounces ownership of the child.
+    pub fn renounce_owner(self) -> Promise {
+        ext_owner::ext(self.contract_id)
+            .with_attached_deposit(1)
+            .own_renounce_owner()
+    }
+
+    /// Pauses the child.
+    pub fn pause(self) -> Promise {
+        ext_pause_admin::ext(self.contract_id).pause()
+    }
+
+    /// Unpauses the child.
+    pub fn unpause(self) -> Promise {
+        ext_pause_admin::ext(self.contract_id).unpause()
+    }
+
+    /// Upgrades the child.
+    pub fn upgrade(self, code: Base64VecU8) -> Promise {
+        ext_upgrade::ext(self.contract_id).upgrade(code)
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::Base64VecU8};
+
+    /// Methods that contracts using `Pause` conventionally expose to their
+    /// owner.
+    #[ext_contract(ext_pause_admin)]
+    pub trait PauseAdminExternal {
+        /// Pauses the contract.
+        fn pause(&mut self);
+
+        /// Unpauses the contract.
+        fn unpause(&mut self);
+    }
+
+    /// The method generated by the `Upgrade` derive macro with the default
+    /// serializer.
+    #[ext_contract(ext_upgrade)]
+    pub trait UpgradeExternal {
+        /// Deploys `code` to the contract, then migrates its state.
+        fn upgrade(&mut self, code: Base64VecU8);
+    }
+}
+pub use ext::*;
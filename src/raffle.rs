@@ -0,0 +1,656 @@
+//! Weighted raffles drawn with a commit-reveal scheme.
+//!
+//! Accounts are registered as entries with a weight (e.g. one ticket each,
+//! or their token balance at the time of entry). When entries are closed,
+//! the organizer commits to `sha256(secret)`. In a later block, the
+//! organizer reveals the secret, which is mixed with the block's random
+//! seed to draw winners (see [`crate::utils::rng::CommitReveal`]). Each
+//! entry can win at most once, with probability proportional to its weight.
+//!
+//! Prizes can be paid out through the token controllers with
+//! [`Nep141RafflePrize`] and [`Nep171RafflePrize`], which run the token's
+//! usual hooks and emit the usual events.
+//!
+//! An account entered with [`Nep141WeightedRaffle::enter_with_balance`]
+//! could otherwise move its tokens to another account and enter again with
+//! the same tokens. [`RaffleBalanceLock`], a NEP-141 transfer and burn hook,
+//! keeps the entered balance in the account until winners are drawn, e.g.
+//! `#[nep141(transfer_hook = "RaffleBalanceLock", burn_hook = "RaffleBalanceLock")]`.
+//!
+//! A raffle is single-use: once drawn, entries cannot be added again. Use a
+//! different storage key for each raffle.
+//!
+//! # Gas
+//!
+//! Drawing reads every entry and scans them once per winner, so the number
+//! of entries a raffle can support is limited by the gas available to a
+//! single call.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{env, near_bindgen, AccountId};
+//! use near_sdk_contract_tools::{raffle::*, Raffle};
+//!
+//! #[derive(Raffle)]
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     pub fn buy_ticket(&mut self) {
+//!         Raffle::enter(self, &env::predecessor_account_id(), 1).unwrap();
+//!     }
+//!
+//!     pub fn close(&mut self, commitment: near_sdk::json_types::Base64VecU8) {
+//!         let commitment = commitment.0.try_into().unwrap();
+//!         Raffle::close(self, commitment).unwrap();
+//!     }
+//!
+//!     pub fn draw(&mut self, secret: String) -> Vec<AccountId> {
+//!         Raffle::draw(self, secret.as_bytes(), 3).unwrap()
+//!     }
+//! }
+//! ```
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use thiserror::Error;
+
+use crate::{
+    event,
+    hook::{ActionOrigin, Hook},
+    slot::Slot,
+    standard::{
+        nep141::{
+            BalanceUnderflowError, Nep141Burn, Nep141Controller, Nep141Mint, Nep141Transfer,
+            TransferError,
+        },
+        nep171::{
+            action::Nep171Transfer, error::Nep171TransferError, Nep171Controller,
+            Nep171TransferAuthorization, TokenId,
+        },
+        nep297::Event,
+    },
+    transaction::Transaction,
+    utils::rng::{CommitReveal, RevealError},
+    DefaultStorageKey,
+};
+
+pub use ext::*;
+
+/// An account registered in a raffle.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RaffleEntry {
+    /// Entrant.
+    pub account_id: AccountId,
+    /// Relative chance of winning.
+    pub weight: U128,
+}
+
+/// Stage of a raffle.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum RaffleStatus {
+    /// Accepting entries.
+    #[default]
+    Open,
+    /// No longer accepting entries. Waiting for `committer` to reveal the
+    /// secret.
+    Closed {
+        /// Account that committed to the draw secret.
+        committer: AccountId,
+    },
+    /// Winners have been drawn.
+    Drawn,
+    /// Prizes have been paid out.
+    PaidOut,
+}
+
+/// An account entered a raffle.
+#[event(
+    standard = "x-raffle",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct RaffleEntered {
+    /// Entrant.
+    pub account_id: AccountId,
+    /// Relative chance of winning.
+    pub weight: U128,
+}
+
+/// Winners were drawn.
+#[event(
+    standard = "x-raffle",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct RaffleDrawn {
+    /// Winning accounts, in the order they were drawn.
+    pub winners: Vec<AccountId>,
+}
+
+/// Errors that may occur when entering a raffle.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum EntryError {
+    /// The raffle is no longer accepting entries.
+    #[error("Raffle is not accepting entries")]
+    NotOpen,
+    /// The account has already entered.
+    #[error("Account '{0}' has already entered the raffle")]
+    AlreadyEntered(AccountId),
+    /// Entries must have a nonzero weight.
+    #[error("Raffle entries must have a nonzero weight")]
+    ZeroWeight,
+}
+
+/// Errors that may occur when closing a raffle.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum CloseError {
+    /// The raffle is not open.
+    #[error("Raffle is not open")]
+    NotOpen,
+    /// The raffle has no entries.
+    #[error("Raffle has no entries")]
+    NoEntries,
+}
+
+/// Errors that may occur when drawing winners.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DrawError {
+    /// The raffle has not been closed, or has already been drawn.
+    #[error("Raffle is not closed")]
+    NotClosed,
+    /// Only the account that committed to the secret may draw.
+    #[error("Only '{0}' may draw the raffle")]
+    WrongCommitter(AccountId),
+    /// The secret could not be revealed.
+    #[error(transparent)]
+    Reveal(#[from] RevealError),
+}
+
+/// Errors that may occur when paying out prizes.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum PayoutError<E> {
+    /// Winners have not been drawn, or prizes have already been paid out.
+    #[error("Raffle prizes are not payable")]
+    NotDrawn,
+    /// The number of prizes does not match the number of winners.
+    #[error("Expected {expected} prizes but got {actual}")]
+    PrizeCountMismatch {
+        /// Number of winners.
+        expected: u32,
+        /// Number of prizes provided.
+        actual: u32,
+    },
+    /// A prize transfer failed.
+    #[error(transparent)]
+    Transfer(E),
+}
+
+/// An account tried to move tokens it entered into a raffle.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Account '{account_id}' has {locked} tokens locked in a raffle until it is drawn")]
+pub struct BalanceLockedError {
+    /// The entrant.
+    pub account_id: AccountId,
+    /// The balance it entered with.
+    pub locked: u128,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Status,
+    EntryCount,
+    Entry(u32),
+    EntryIndex(&'a AccountId),
+    TotalWeight,
+    Winners,
+    CommitReveal,
+    LockedBalance(&'a AccountId),
+}
+
+/// Internal functions for [`Raffle`]. Using these methods may result in unexpected behavior.
+pub trait RaffleInternal {
+    /// Storage root.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Raffle)
+    }
+
+    /// Storage slot for the raffle status.
+    fn slot_status() -> Slot<RaffleStatus> {
+        Self::root().field(StorageKey::Status)
+    }
+
+    /// Storage slot for the number of entries.
+    fn slot_entry_count() -> Slot<u32> {
+        Self::root().field(StorageKey::EntryCount)
+    }
+
+    /// Storage slot for the entry at `index`.
+    fn slot_entry(index: u32) -> Slot<RaffleEntry> {
+        Self::root().field(StorageKey::Entry(index))
+    }
+
+    /// Storage slot for the index of the entry of `account_id`.
+    fn slot_entry_index(account_id: &AccountId) -> Slot<u32> {
+        Self::root().field(StorageKey::EntryIndex(account_id))
+    }
+
+    /// Storage slot for the sum of all entry weights.
+    fn slot_total_weight() -> Slot<u128> {
+        Self::root().field(StorageKey::TotalWeight)
+    }
+
+    /// Storage slot for the drawn winners.
+    fn slot_winners() -> Slot<Vec<AccountId>> {
+        Self::root().field(StorageKey::Winners)
+    }
+
+    /// Commitments to the draw secret.
+    fn commit_reveal() -> CommitReveal {
+        CommitReveal::new(Self::root().ns(StorageKey::CommitReveal))
+    }
+
+    /// Storage slot for the balance `account_id` entered with, if it entered
+    /// with [`Nep141WeightedRaffle::enter_with_balance`].
+    fn slot_locked_balance(account_id: &AccountId) -> Slot<u128> {
+        Self::root().field(StorageKey::LockedBalance(account_id))
+    }
+}
+
+/// A weighted raffle.
+pub trait Raffle {
+    /// Current stage of the raffle.
+    fn raffle_status(&self) -> RaffleStatus;
+
+    /// Number of entries.
+    fn entry_count(&self) -> u32;
+
+    /// The entry at `index`, if it exists.
+    fn entry(&self, index: u32) -> Option<RaffleEntry>;
+
+    /// The weight of the entry of `account_id`, if it has entered.
+    fn weight_of(&self, account_id: &AccountId) -> Option<u128>;
+
+    /// Sum of all entry weights.
+    fn total_weight(&self) -> u128;
+
+    /// Winners, in the order they were drawn. Empty until drawn.
+    fn winners(&self) -> Vec<AccountId>;
+
+    /// Registers `account_id` with the given weight. Emits [`RaffleEntered`].
+    fn enter(&mut self, account_id: &AccountId, weight: u128) -> Result<(), EntryError>;
+
+    /// Stops accepting entries. The predecessor commits to the SHA-256 hash
+    /// of the secret that will be used to draw winners.
+    fn close(&mut self, commitment: [u8; 32]) -> Result<(), CloseError>;
+
+    /// Reveals the committed secret and draws up to `count` distinct
+    /// winners. Must be called by the account that closed the raffle, in a
+    /// later block. Emits [`RaffleDrawn`].
+    fn draw(&mut self, secret: &[u8], count: u32) -> Result<Vec<AccountId>, DrawError>;
+}
+
+impl<T: RaffleInternal> Raffle for T {
+    fn raffle_status(&self) -> RaffleStatus {
+        Self::slot_status().read().unwrap_or_default()
+    }
+
+    fn entry_count(&self) -> u32 {
+        Self::slot_entry_count().read().unwrap_or(0)
+    }
+
+    fn entry(&self, index: u32) -> Option<RaffleEntry> {
+        Self::slot_entry(index).read()
+    }
+
+    fn weight_of(&self, account_id: &AccountId) -> Option<u128> {
+        Self::slot_entry_index(account_id)
+            .read()
+            .and_then(|index| self.entry(index))
+            .map(|entry| entry.weight.0)
+    }
+
+    fn total_weight(&self) -> u128 {
+        Self::slot_total_weight().read().unwrap_or(0)
+    }
+
+    fn winners(&self) -> Vec<AccountId> {
+        Self::slot_winners().read().unwrap_or_default()
+    }
+
+    fn enter(&mut self, account_id: &AccountId, weight: u128) -> Result<(), EntryError> {
+        if self.raffle_status() != RaffleStatus::Open {
+            return Err(EntryError::NotOpen);
+        }
+
+        if weight == 0 {
+            return Err(EntryError::ZeroWeight);
+        }
+
+        let mut index_slot = Self::slot_entry_index(account_id);
+        if index_slot.exists() {
+            return Err(EntryError::AlreadyEntered(account_id.clone()));
+        }
+
+        let index = self.entry_count();
+        let entry = RaffleEntry {
+            account_id: account_id.clone(),
+            weight: U128(weight),
+        };
+
+        Self::slot_entry(index).write(&entry);
+        index_slot.write(&index);
+        Self::slot_entry_count().write(&(index + 1));
+        Self::slot_total_weight().write(
+            &self
+                .total_weight()
+                .checked_add(weight)
+                .unwrap_or_else(|| env::panic_str("Raffle total weight overflow")),
+        );
+
+        RaffleEntered {
+            account_id: entry.account_id,
+            weight: entry.weight,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn close(&mut self, commitment: [u8; 32]) -> Result<(), CloseError> {
+        if self.raffle_status() != RaffleStatus::Open {
+            return Err(CloseError::NotOpen);
+        }
+
+        if self.entry_count() == 0 {
+            return Err(CloseError::NoEntries);
+        }
+
+        let committer = env::predecessor_account_id();
+        Self::commit_reveal().commit(&committer, commitment);
+        Self::slot_status().write(&RaffleStatus::Closed { committer });
+
+        Ok(())
+    }
+
+    fn draw(&mut self, secret: &[u8], count: u32) -> Result<Vec<AccountId>, DrawError> {
+        let committer = match self.raffle_status() {
+            RaffleStatus::Closed { committer } => committer,
+            _ => return Err(DrawError::NotClosed),
+        };
+
+        if env::predecessor_account_id() != committer {
+            return Err(DrawError::WrongCommitter(committer));
+        }
+
+        let mut rng = Self::commit_reveal().reveal(&committer, secret)?;
+
+        let entries = (0..self.entry_count())
+            .filter_map(|index| self.entry(index))
+            .collect::<Vec<_>>();
+        let mut drawn = vec![false; entries.len()];
+        let mut remaining_weight = self.total_weight();
+        let mut winners = Vec::new();
+
+        while winners.len() < count as usize && remaining_weight > 0 {
+            let mut target = rng.gen_range_u128(0..remaining_weight);
+
+            for (entry, drawn) in entries.iter().zip(drawn.iter_mut()) {
+                if *drawn {
+                    continue;
+                }
+                if target < entry.weight.0 {
+                    *drawn = true;
+                    remaining_weight -= entry.weight.0;
+                    winners.push(entry.account_id.clone());
+                    break;
+                }
+                target -= entry.weight.0;
+            }
+        }
+
+        Self::slot_winners().write(&winners);
+        Self::slot_status().write(&RaffleStatus::Drawn);
+
+        RaffleDrawn {
+            winners: winners.clone(),
+        }
+        .emit();
+
+        Ok(winners)
+    }
+}
+
+/// Enters accounts into a raffle weighted by their NEP-141 balance.
+pub trait Nep141WeightedRaffle: Raffle {
+    /// Enters `account_id` with a weight equal to its current token balance,
+    /// and locks that balance until winners are drawn. The lock is enforced
+    /// by [`RaffleBalanceLock`], which must be a transfer and burn hook of
+    /// the contract. Returns the weight.
+    fn enter_with_balance(&mut self, account_id: &AccountId) -> Result<u128, EntryError>;
+
+    /// The balance of `account_id` that is locked in the raffle, if any.
+    fn locked_balance(&self, account_id: &AccountId) -> u128;
+}
+
+impl<T: RaffleInternal + Nep141Controller> Nep141WeightedRaffle for T {
+    fn enter_with_balance(&mut self, account_id: &AccountId) -> Result<u128, EntryError> {
+        let weight = self.balance_of(account_id);
+        self.enter(account_id, weight)?;
+        Self::slot_locked_balance(account_id).write(&weight);
+        Ok(weight)
+    }
+
+    fn locked_balance(&self, account_id: &AccountId) -> u128 {
+        match self.raffle_status() {
+            RaffleStatus::Open | RaffleStatus::Closed { .. } => {
+                Self::slot_locked_balance(account_id).read().unwrap_or(0)
+            }
+            RaffleStatus::Drawn | RaffleStatus::PaidOut => 0,
+        }
+    }
+}
+
+/// NEP-141 hook that rejects transfers and burns that would leave an account
+/// with less than the balance it entered the raffle with, until winners are
+/// drawn. See [`Nep141WeightedRaffle::enter_with_balance`].
+pub struct RaffleBalanceLock;
+
+impl RaffleBalanceLock {
+    fn check<C: Nep141WeightedRaffle + Nep141Controller>(
+        contract: &C,
+        account_id: &AccountId,
+        amount: u128,
+    ) -> Result<(), BalanceLockedError> {
+        let locked = contract.locked_balance(account_id);
+
+        if locked > 0 && contract.balance_of(account_id).saturating_sub(amount) < locked {
+            return Err(BalanceLockedError {
+                account_id: account_id.clone(),
+                locked,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<C> Hook<C, Nep141Mint<'_>> for RaffleBalanceLock {}
+
+impl<C: Nep141WeightedRaffle + Nep141Controller> Hook<C, Nep141Transfer<'_>> for RaffleBalanceLock {
+    fn hook<R>(contract: &mut C, transfer: &Nep141Transfer<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        if transfer.sender_id != transfer.receiver_id {
+            Self::check(contract, transfer.sender_id, transfer.amount)
+                .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        }
+        f(contract)
+    }
+}
+
+impl<C: Nep141WeightedRaffle + Nep141Controller> Hook<C, Nep141Burn<'_>> for RaffleBalanceLock {
+    fn hook<R>(contract: &mut C, burn: &Nep141Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        Self::check(contract, burn.owner_id, burn.amount)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        f(contract)
+    }
+}
+
+fn check_payable<E>(raffle: &impl Raffle) -> Result<Vec<AccountId>, PayoutError<E>> {
+    if raffle.raffle_status() != RaffleStatus::Drawn {
+        return Err(PayoutError::NotDrawn);
+    }
+
+    Ok(raffle.winners())
+}
+
+/// Pays raffle prizes in NEP-141 tokens.
+pub trait Nep141RafflePrize: Raffle {
+    /// Transfers `amounts[i]` tokens from `sender_id` to the `i`th winner,
+    /// and marks the raffle as paid out. Either every prize is paid or, on
+    /// error, none is: the balances are checked before any transfer.
+    fn pay_ft_prizes(
+        &mut self,
+        sender_id: &AccountId,
+        amounts: &[u128],
+    ) -> Result<(), PayoutError<TransferError>>;
+}
+
+impl<T: RaffleInternal + Nep141Controller> Nep141RafflePrize for T {
+    fn pay_ft_prizes(
+        &mut self,
+        sender_id: &AccountId,
+        amounts: &[u128],
+    ) -> Result<(), PayoutError<TransferError>> {
+        let winners = check_payable(self)?;
+
+        if winners.len() != amounts.len() {
+            return Err(PayoutError::PrizeCountMismatch {
+                expected: winners.len() as u32,
+                actual: amounts.len() as u32,
+            });
+        }
+
+        let transfers = winners
+            .iter()
+            .zip(amounts)
+            .map(|(winner, amount)| Nep141Transfer {
+                sender_id,
+                receiver_id: winner,
+                amount: *amount,
+                memo: Some("raffle prize"),
+                msg: None,
+                revert: false,
+                origin: ActionOrigin::Internal,
+            })
+            .collect::<Vec<_>>();
+
+        // Check every transfer up front, so that none is performed (and no
+        // event is emitted) if one of them would fail.
+        let mut sender_balance = self.balance_of(sender_id);
+        for transfer in &transfers {
+            if sender_balance < transfer.amount {
+                return Err(PayoutError::Transfer(
+                    BalanceUnderflowError {
+                        account_id: sender_id.clone(),
+                        balance: sender_balance,
+                        amount: transfer.amount,
+                    }
+                    .into(),
+                ));
+            }
+            self.can_transfer(transfer).map_err(PayoutError::Transfer)?;
+            if transfer.receiver_id != sender_id {
+                sender_balance -= transfer.amount;
+            }
+        }
+
+        Transaction::run(|| {
+            for transfer in &transfers {
+                Nep141Controller::transfer(self, transfer).map_err(PayoutError::Transfer)?;
+            }
+
+            Self::slot_status().write(&RaffleStatus::PaidOut);
+
+            Ok(())
+        })
+    }
+}
+
+/// Pays raffle prizes in NEP-171 tokens.
+pub trait Nep171RafflePrize: Raffle {
+    /// Transfers token `token_ids[i]` from its owner to the `i`th winner.
+    fn pay_nft_prizes(
+        &mut self,
+        token_ids: &[TokenId],
+    ) -> Result<(), PayoutError<Nep171TransferError>>;
+}
+
+impl<T: RaffleInternal + Nep171Controller> Nep171RafflePrize for T {
+    fn pay_nft_prizes(
+        &mut self,
+        token_ids: &[TokenId],
+    ) -> Result<(), PayoutError<Nep171TransferError>> {
+        let winners = check_payable(self)?;
+
+        if winners.len() != token_ids.len() {
+            return Err(PayoutError::PrizeCountMismatch {
+                expected: winners.len() as u32,
+                actual: token_ids.len() as u32,
+            });
+        }
+
+        for (winner, token_id) in winners.iter().zip(token_ids) {
+            let owner_id = self.token_owner(token_id).unwrap_or_else(|| winner.clone());
+
+            self.external_transfer(&Nep171Transfer {
+                authorization: Nep171TransferAuthorization::Owner,
+                sender_id: &owner_id,
+                receiver_id: winner,
+                token_id,
+                memo: Some("raffle prize"),
+                msg: None,
+                revert: false,
+                origin: ActionOrigin::Internal,
+            })
+            .map_err(PayoutError::Transfer)?;
+        }
+
+        Self::slot_status().write(&RaffleStatus::PaidOut);
+
+        Ok(())
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, AccountId};
+
+    use super::RaffleStatus;
+
+    /// Externally-accessible functions for [`super::Raffle`].
+    #[ext_contract(ext_raffle)]
+    pub trait RaffleExternal {
+        /// Returns the current stage of the raffle.
+        fn raffle_get_status(&self) -> RaffleStatus;
+
+        /// Returns the number of entries.
+        fn raffle_entry_count(&self) -> u32;
+
+        /// Returns the drawn winners.
+        fn raffle_winners(&self) -> Vec<AccountId>;
+    }
+}
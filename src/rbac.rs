@@ -25,25 +25,128 @@
 //!     account has the specified role.
 //! * (ERR) [`Rbac::prohibit_role`] may only be called when the predecessor
 //!     account does not have the specified role.
-use std::iter::FusedIterator;
+//!
+//! # Suspending roles
+//! [`Rbac::suspend_role`] temporarily disables a role without changing its
+//! members, e.g. during incident response when one class of operators is
+//! compromised. While a role is suspended, [`Rbac::has_role`] returns `false`
+//! for every account, so guards like [`Rbac::require_role`] reject it.
+//! [`Rbac::resume_role`] restores it. Members can still be added, removed,
+//! listed, and counted while a role is suspended. [`Rbac::prohibit_role`]
+//! ignores suspension, so suspending a deny-list role (e.g. "Banned") does
+//! not let its members through.
+//!
+//! # Approvable role changes
+//! [`RbacAction`] implements the approval [`Action`] trait, so role
+//! administration can be placed behind an approval manager such as a
+//! multisig. The [`SimpleMultisig`](near_sdk_contract_tools_macros::SimpleMultisig)
+//! derive macro can expose request/approve/execute endpoints for it with the
+//! `rbac_actions` flag.
+//!
+//! # Batch role changes
+//! [`Rbac::add_roles`] and [`Rbac::remove_roles`] change many roles at once,
+//! e.g. to onboard a set of operators, reading and writing the member count
+//! of each role once and emitting a single event. [`RbacBatchAction`] makes
+//! them approvable.
+//!
+//! # Storage layout
+//! The members of a role are stored as a dense list alongside an index from
+//! account ID to list position and a member count. Membership checks,
+//! [`Rbac::count_members`], insertion, and removal are all O(1), and removal
+//! swaps the last member into the vacated position, so no tombstones are
+//! left behind.
+//!
+//! Contracts deployed with an earlier version of this crate stored the
+//! members of each role in a single `UnorderedSet`. Those sets are still
+//! read: an account in the legacy set is considered to have the role, and
+//! is removed from it by [`Rbac::remove_role`]. Call
+//! [`Rbac::migrate_legacy_members`] (e.g. from a migration or maintenance
+//! method) to move legacy members to the current layout in batches. The
+//! legacy set is deleted once it is empty.
+//!
+//! The roles of each account are indexed the same way, so
+//! [`Rbac::roles_of`] can list them without probing every role. Roles
+//! assigned by an earlier version of this crate are not in this index until
+//! they are migrated (legacy members) or assigned again with
+//! [`Rbac::add_role`].
+use std::{collections::HashSet, iter::FusedIterator};
 
 use near_sdk::{
-    borsh::{self, BorshSerialize},
+    borsh::{self, BorshDeserialize, BorshSerialize},
     env, require,
+    serde::{Deserialize, Serialize},
     store::UnorderedSet,
     AccountId, BorshStorageKey, IntoStorageKey,
 };
+use near_sdk_contract_tools_macros::event;
 
-use crate::{slot::Slot, DefaultStorageKey};
+use crate::{
+    approval::Action, slot::Slot, standard::nep297::Event, storage_version::StorageVersion,
+    DefaultStorageKey,
+};
 
 const REQUIRE_ROLE_FAIL_MESSAGE: &str = "Unauthorized role";
 const PROHIBIT_ROLE_FAIL_MESSAGE: &str = "Prohibited role";
 
 #[derive(BorshSerialize, BorshStorageKey)]
-enum StorageKey<R> {
-    Role(R),
+enum StorageKey<'a, R> {
+    Role(&'a R),
+    MemberIndex(&'a R, &'a AccountId),
+    Members(&'a R),
+    MemberCount(&'a R),
+    RoleIndexOf(&'a AccountId, &'a [u8]),
+    RolesOf(&'a AccountId),
+    RoleCountOf(&'a AccountId),
+    Suspended(&'a R),
+    Scratch,
+}
+
+/// Events emitted when roles are suspended or resumed.
+#[event(
+    standard = "x-rbac",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum RbacEvent<R> {
+    /// Emitted when a role is suspended.
+    RoleSuspended {
+        /// The suspended role.
+        role: R,
+    },
+    /// Emitted when a suspended role is resumed.
+    RoleResumed {
+        /// The resumed role.
+        role: R,
+    },
+    /// Emitted when roles are assigned in a batch.
+    RolesGranted {
+        /// The newly assigned roles.
+        assignments: Vec<RoleAssignment<R>>,
+    },
+    /// Emitted when roles are removed in a batch.
+    RolesRevoked {
+        /// The removed roles.
+        assignments: Vec<RoleAssignment<R>>,
+    },
+}
+
+/// A role of an account, as listed in [`RbacEvent::RolesGranted`] and
+/// [`RbacEvent::RolesRevoked`].
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleAssignment<R> {
+    /// The account.
+    pub account_id: AccountId,
+    /// The role.
+    pub role: R,
 }
 
+/// Storage layout version of the Rbac component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Rbac", 1);
+
 /// Internal functions for [`Rbac`]. Using these methods may result in unexpected behavior.
 pub trait RbacInternal {
     /// Roles type (probably an enum).
@@ -54,10 +157,268 @@ pub trait RbacInternal {
         Slot::new(DefaultStorageKey::Rbac)
     }
 
+    /// Storage slot for the legacy `UnorderedSet` of accounts assigned to a
+    /// role. New members are never added to this set.
+    fn slot_legacy_members_of(role: &Self::Role) -> Slot<UnorderedSet<AccountId>> {
+        Self::root().field(StorageKey::Role(role))
+    }
+
     /// Storage slot for the backing `UnorderedSet` of all accounts assigned
     /// to a role.
+    #[deprecated(
+        note = "Only legacy members are stored in this set. Use `slot_legacy_members_of`, or `Rbac::iter_members_of` to list all members."
+    )]
     fn slot_members_of(role: &Self::Role) -> Slot<UnorderedSet<AccountId>> {
-        Self::root().field::<UnorderedSet<AccountId>>(StorageKey::Role(role))
+        Self::slot_legacy_members_of(role)
+    }
+
+    /// Storage slot for the position of an account in the list of members of
+    /// a role.
+    fn slot_member_index(role: &Self::Role, account_id: &AccountId) -> Slot<u32> {
+        Self::root().field(StorageKey::MemberIndex(role, account_id))
+    }
+
+    /// Storage namespace for the list of members of a role.
+    fn slot_members(role: &Self::Role) -> Slot<()> {
+        Self::root().ns(StorageKey::Members(role))
+    }
+
+    /// Storage slot for the number of members of a role, excluding legacy
+    /// members.
+    fn slot_member_count(role: &Self::Role) -> Slot<u32> {
+        Self::root().field(StorageKey::MemberCount(role))
+    }
+
+    /// Storage slot for the position of a role in the list of roles of an
+    /// account. `role` is the Borsh serialization of the role.
+    fn slot_role_index_of(account_id: &AccountId, role: &[u8]) -> Slot<u32> {
+        Self::root().field(StorageKey::<Self::Role>::RoleIndexOf(account_id, role))
+    }
+
+    /// Storage namespace for the list of Borsh-serialized roles of an
+    /// account.
+    fn slot_roles_of(account_id: &AccountId) -> Slot<()> {
+        Self::root().ns(StorageKey::<Self::Role>::RolesOf(account_id))
+    }
+
+    /// Storage slot for the number of indexed roles of an account.
+    fn slot_role_count_of(account_id: &AccountId) -> Slot<u32> {
+        Self::root().field(StorageKey::<Self::Role>::RoleCountOf(account_id))
+    }
+
+    /// Storage slot for the suspension flag of a role. The role is suspended
+    /// if the slot exists.
+    fn slot_suspended(role: &Self::Role) -> Slot<()> {
+        Self::root().field(StorageKey::Suspended(role))
+    }
+}
+
+fn member_at(members: &Slot<()>, index: u32) -> Slot<AccountId> {
+    members.field(index.to_le_bytes().to_vec())
+}
+
+fn role_at(roles: &Slot<()>, index: u32) -> Slot<Vec<u8>> {
+    roles.field(index.to_le_bytes().to_vec())
+}
+
+fn serialize_role<R: BorshSerialize>(role: &R) -> Vec<u8> {
+    role.try_to_vec()
+        .unwrap_or_else(|_| env::panic_str("Failed to serialize role"))
+}
+
+fn index_role<I: RbacInternal>(account_id: &AccountId, role: &I::Role) {
+    let role = serialize_role(role);
+    let mut index_slot = I::slot_role_index_of(account_id, &role);
+    if index_slot.exists() {
+        return;
+    }
+
+    let mut count_slot = I::slot_role_count_of(account_id);
+    let index = count_slot.read().unwrap_or(0);
+
+    role_at(&I::slot_roles_of(account_id), index).write(&role);
+    index_slot.write(&index);
+    count_slot.write(&(index + 1));
+}
+
+fn unindex_role<I: RbacInternal>(account_id: &AccountId, role: &I::Role) {
+    let role = serialize_role(role);
+    let index = match I::slot_role_index_of(account_id, &role).take() {
+        Some(index) => index,
+        None => return,
+    };
+
+    let mut count_slot = I::slot_role_count_of(account_id);
+    let last = count_slot.read().unwrap_or(0) - 1;
+    let roles = I::slot_roles_of(account_id);
+    let mut last_slot = role_at(&roles, last);
+
+    if index != last {
+        let moved = last_slot.read().unwrap();
+        role_at(&roles, index).write(&moved);
+        I::slot_role_index_of(account_id, &moved).write(&index);
+    }
+
+    last_slot.remove();
+    count_slot.set(Some(&last).filter(|c| **c > 0));
+}
+
+fn is_member<I: RbacInternal>(account_id: &AccountId, role: &I::Role) -> bool {
+    I::slot_member_index(role, account_id).exists()
+        || I::slot_legacy_members_of(role)
+            .read()
+            .map(|set| set.contains(account_id))
+            .unwrap_or(false)
+}
+
+/// Inserts a member of a role whose member count is `count`, without
+/// writing the count.
+fn insert_member_counted<I: RbacInternal>(
+    role: &I::Role,
+    account_id: &AccountId,
+    count: &mut u32,
+) -> bool {
+    let mut index_slot = I::slot_member_index(role, account_id);
+    if index_slot.exists() {
+        return false;
+    }
+
+    let index = *count;
+
+    member_at(&I::slot_members(role), index).write(account_id);
+    index_slot.write(&index);
+    *count = index + 1;
+    index_role::<I>(account_id, role);
+
+    true
+}
+
+fn insert_member<I: RbacInternal>(role: &I::Role, account_id: &AccountId) -> bool {
+    let mut count_slot = I::slot_member_count(role);
+    let mut count = count_slot.read().unwrap_or(0);
+
+    let inserted = insert_member_counted::<I>(role, account_id, &mut count);
+    if inserted {
+        count_slot.write(&count);
+    }
+
+    inserted
+}
+
+/// Removes a member of a role whose member count is `count`, without
+/// writing the count.
+fn remove_member_counted<I: RbacInternal>(
+    role: &I::Role,
+    account_id: &AccountId,
+    count: &mut u32,
+) -> bool {
+    let index = match I::slot_member_index(role, account_id).take() {
+        Some(index) => index,
+        None => return false,
+    };
+
+    let last = *count - 1;
+    let members = I::slot_members(role);
+    let mut last_slot = member_at(&members, last);
+
+    if index != last {
+        let moved = last_slot.read().unwrap();
+        member_at(&members, index).write(&moved);
+        I::slot_member_index(role, &moved).write(&index);
+    }
+
+    last_slot.remove();
+    *count = last;
+    unindex_role::<I>(account_id, role);
+
+    true
+}
+
+fn write_member_count<I: RbacInternal>(role: &I::Role, count: u32) {
+    I::slot_member_count(role).set(Some(&count).filter(|c| **c > 0));
+}
+
+fn remove_member<I: RbacInternal>(role: &I::Role, account_id: &AccountId) -> bool {
+    let mut count = I::slot_member_count(role).read().unwrap_or(0);
+
+    let removed = remove_member_counted::<I>(role, account_id, &mut count);
+    if removed {
+        write_member_count::<I>(role, count);
+    }
+
+    removed
+}
+
+/// Removes an account from the legacy members of a role. Also unindexes
+/// the role, which may be indexed without being in the current layout.
+fn remove_legacy_member<I: RbacInternal>(role: &I::Role, account_id: &AccountId) -> bool {
+    unindex_role::<I>(account_id, role);
+
+    let mut slot = I::slot_legacy_members_of(role);
+    let mut set = match slot.read() {
+        Some(set) => set,
+        None => return false,
+    };
+
+    let removed = set.remove(account_id);
+    if removed {
+        write_legacy(&mut slot, set);
+    }
+    removed
+}
+
+/// Member counts of the roles changed by a batch, so that each count is read
+/// and written once.
+struct MemberCounts<'a, R> {
+    counts: Vec<(Vec<u8>, &'a R, u32)>,
+}
+
+impl<'a, R: BorshSerialize> MemberCounts<'a, R> {
+    fn new() -> Self {
+        Self { counts: Vec::new() }
+    }
+
+    fn get<I: RbacInternal<Role = R>>(&mut self, role: &'a R) -> &mut u32 {
+        let key = serialize_role(role);
+        let position = match self.counts.iter().position(|(k, ..)| *k == key) {
+            Some(position) => position,
+            None => {
+                let count = I::slot_member_count(role).read().unwrap_or(0);
+                self.counts.push((key, role, count));
+                self.counts.len() - 1
+            }
+        };
+
+        &mut self.counts[position].2
+    }
+
+    fn write<I: RbacInternal<Role = R>>(self) {
+        for (_, role, count) in self.counts {
+            write_member_count::<I>(role, count);
+        }
+    }
+}
+
+/// Copies the members of a role, in both storage layouts, into a set under
+/// a scratch prefix. The set must be passed to [`discard_scratch`].
+fn scratch_members<I: RbacInternal>(members: &[AccountId]) -> UnorderedSet<AccountId> {
+    let mut set = UnorderedSet::new(I::root().field::<()>(StorageKey::<I::Role>::Scratch).key);
+    set.extend(members.iter().cloned());
+    set
+}
+
+/// Empties a scratch set before it is dropped, so that nothing is flushed
+/// to storage.
+fn discard_scratch(mut set: UnorderedSet<AccountId>) {
+    set.clear();
+}
+
+fn write_legacy(slot: &mut Slot<UnorderedSet<AccountId>>, mut set: UnorderedSet<AccountId>) {
+    if set.is_empty() {
+        set.clear();
+        slot.remove();
+    } else {
+        slot.write(&set);
     }
 }
 
@@ -69,6 +430,12 @@ pub trait Rbac {
     /// Deserializes the backing `UnorderedSet` structure, executes predicate
     /// `f` on it, reserializes the structure, and writes it back into storage,
     /// returning the return value of `f`.
+    ///
+    /// The members are now stored in a list (see the [module
+    /// documentation](self)), so `f` receives a temporary copy of the members,
+    /// and the accounts it inserts and removes are then added to and removed
+    /// from the role. Costs O(n) in the number of members.
+    #[deprecated(note = "Use `add_role`, `remove_role`, `add_roles`, or `remove_roles`.")]
     fn with_members_of_mut<T>(
         role: &Self::Role,
         f: impl FnOnce(&mut UnorderedSet<AccountId>) -> T,
@@ -76,24 +443,81 @@ pub trait Rbac {
 
     /// Deserializes the backing `UnorderedSet` structure and executes predicate
     /// `f` on it. Returns the return value of `f`.
+    ///
+    /// The members are now stored in a list (see the [module
+    /// documentation](self)), so `f` receives a temporary copy of the members.
+    /// Costs O(n) in the number of members.
+    #[deprecated(note = "Use `iter_members_of`, `count_members`, or `has_role`.")]
     fn with_members_of<T>(role: &Self::Role, f: impl FnOnce(&UnorderedSet<AccountId>) -> T) -> T;
 
     /// Iterates over all accounts that have been assigned a role.
     fn iter_members_of(role: &Self::Role) -> Iter;
 
-    /// Returns whether a given account has been given a certain role.
+    /// Returns the number of accounts that have been assigned a role.
+    fn count_members(role: &Self::Role) -> u32;
+
+    /// Returns whether a given account has been given a certain role, and
+    /// the role is not suspended.
     fn has_role(account_id: &AccountId, role: &Self::Role) -> bool;
 
-    /// Assigns a role to an account.
+    /// Returns whether a role is suspended.
+    fn is_suspended(role: &Self::Role) -> bool;
+
+    /// Suspends a role, so that no account has it until it is resumed.
+    /// Returns `false` if the role was already suspended. Emits
+    /// [`RbacEvent::RoleSuspended`].
+    fn suspend_role(&mut self, role: Self::Role) -> bool
+    where
+        Self::Role: Serialize;
+
+    /// Resumes a suspended role. Returns `false` if the role was not
+    /// suspended. Emits [`RbacEvent::RoleResumed`].
+    fn resume_role(&mut self, role: Self::Role) -> bool
+    where
+        Self::Role: Serialize;
+
+    /// Lists up to `limit` roles of an account, starting at `offset`. Roles
+    /// that are not yet indexed (see the [module documentation](self)) are
+    /// not listed.
+    fn roles_of(account_id: &AccountId, offset: u32, limit: u32) -> Vec<Self::Role>
+    where
+        Self::Role: BorshDeserialize;
+
+    /// Returns the number of indexed roles of an account.
+    fn count_roles_of(account_id: &AccountId) -> u32;
+
+    /// Assigns a role to an account. If the account already has the role,
+    /// ensures the role is indexed for [`Rbac::roles_of`].
     fn add_role(&mut self, account_id: AccountId, role: &Self::Role);
 
     /// Removes a role from an account.
     fn remove_role(&mut self, account_id: &AccountId, role: &Self::Role);
 
+    /// Assigns roles to accounts, like [`Rbac::add_role`] for each pair, but
+    /// reads and writes the member count of each role once. Emits a single
+    /// [`RbacEvent::RolesGranted`] listing the roles that were not already
+    /// assigned, and returns their number.
+    fn add_roles(&mut self, roles: &[(AccountId, Self::Role)]) -> u32
+    where
+        Self::Role: Serialize;
+
+    /// Removes roles from accounts, like [`Rbac::remove_role`] for each
+    /// pair, but reads and writes the member count of each role once. Emits
+    /// a single [`RbacEvent::RolesRevoked`] listing the roles that were
+    /// assigned, and returns their number.
+    fn remove_roles(&mut self, roles: &[(AccountId, Self::Role)]) -> u32
+    where
+        Self::Role: Serialize;
+
+    /// Moves up to `limit` members of a role from the legacy storage layout
+    /// to the current one. Returns the number of legacy members remaining.
+    fn migrate_legacy_members(&mut self, role: &Self::Role, limit: u32) -> u32;
+
     /// Requires transaction predecessor to have a given role.
     fn require_role(role: &Self::Role);
 
-    /// Requires transaction predecessor to not have a given role.
+    /// Requires transaction predecessor to not have a given role, even if
+    /// the role is suspended.
     fn prohibit_role(role: &Self::Role);
 }
 
@@ -104,43 +528,195 @@ impl<I: RbacInternal> Rbac for I {
         role: &Self::Role,
         f: impl FnOnce(&mut UnorderedSet<AccountId>) -> T,
     ) -> T {
-        let mut slot = Self::slot_members_of(role);
-        let mut set = slot
-            .read()
-            .unwrap_or_else(|| UnorderedSet::new(slot.key.clone()));
+        let before = Self::iter_members_of(role).collect::<Vec<_>>();
+        let mut set = scratch_members::<Self>(&before);
         let value = f(&mut set);
-        slot.write(&set);
+        let after = set.iter().cloned().collect::<Vec<_>>();
+        discard_scratch(set);
+
+        let before_set = before.iter().collect::<HashSet<_>>();
+        let after_set = after.iter().collect::<HashSet<_>>();
+
+        for account_id in before.iter().filter(|a| !after_set.contains(a)) {
+            if !remove_member::<Self>(role, account_id) {
+                remove_legacy_member::<Self>(role, account_id);
+            }
+        }
+
+        for account_id in after.iter().filter(|a| !before_set.contains(a)) {
+            insert_member::<Self>(role, account_id);
+        }
+
         value
     }
 
     fn with_members_of<T>(role: &Self::Role, f: impl FnOnce(&UnorderedSet<AccountId>) -> T) -> T {
-        let slot = Self::slot_members_of(role);
-        let set = slot
-            .read()
-            .unwrap_or_else(|| UnorderedSet::new(slot.key.clone()));
-        f(&set)
+        let set = scratch_members::<Self>(&Self::iter_members_of(role).collect::<Vec<_>>());
+        let value = f(&set);
+        discard_scratch(set);
+        value
     }
 
     fn iter_members_of(role: &Self::Role) -> Iter {
-        let slot = Self::slot_members_of(role);
-        let set = slot.read().unwrap_or_else(|| UnorderedSet::new(slot.key));
-        // Cannot use with_members_of because Iter must be owned
-        Iter::new(set)
+        Iter::from_layout(
+            Self::slot_members(role),
+            Self::slot_member_count(role).read().unwrap_or(0),
+            Self::slot_legacy_members_of(role).read(),
+        )
     }
 
-    fn has_role(account_id: &AccountId, role: &Self::Role) -> bool {
-        Self::slot_members_of(role)
+    fn count_members(role: &Self::Role) -> u32 {
+        let legacy = Self::slot_legacy_members_of(role)
             .read()
-            .map(|set| set.contains(account_id))
-            .unwrap_or(false)
+            .map_or(0, |set| set.len());
+
+        Self::slot_member_count(role).read().unwrap_or(0) + legacy
+    }
+
+    fn has_role(account_id: &AccountId, role: &Self::Role) -> bool {
+        !Self::is_suspended(role) && is_member::<Self>(account_id, role)
+    }
+
+    fn is_suspended(role: &Self::Role) -> bool {
+        Self::slot_suspended(role).exists()
+    }
+
+    fn suspend_role(&mut self, role: Self::Role) -> bool
+    where
+        Self::Role: Serialize,
+    {
+        let mut slot = Self::slot_suspended(&role);
+        if slot.exists() {
+            return false;
+        }
+
+        slot.write(&());
+        RbacEvent::RoleSuspended { role }.emit();
+        true
+    }
+
+    fn resume_role(&mut self, role: Self::Role) -> bool
+    where
+        Self::Role: Serialize,
+    {
+        if !Self::slot_suspended(&role).remove() {
+            return false;
+        }
+
+        RbacEvent::RoleResumed { role }.emit();
+        true
+    }
+
+    fn roles_of(account_id: &AccountId, offset: u32, limit: u32) -> Vec<Self::Role>
+    where
+        Self::Role: BorshDeserialize,
+    {
+        let roles = Self::slot_roles_of(account_id);
+        let end = Self::count_roles_of(account_id).min(offset.saturating_add(limit));
+
+        (offset..end)
+            .filter_map(|index| role_at(&roles, index).read())
+            .map(|role| {
+                Self::Role::try_from_slice(&role)
+                    .unwrap_or_else(|_| env::panic_str("Failed to deserialize role"))
+            })
+            .collect()
+    }
+
+    fn count_roles_of(account_id: &AccountId) -> u32 {
+        Self::slot_role_count_of(account_id).read().unwrap_or(0)
     }
 
     fn add_role(&mut self, account_id: AccountId, role: &Self::Role) {
-        Self::with_members_of_mut(role, |set| set.insert(account_id));
+        if is_member::<Self>(&account_id, role) {
+            index_role::<Self>(&account_id, role);
+        } else {
+            insert_member::<Self>(role, &account_id);
+        }
     }
 
     fn remove_role(&mut self, account_id: &AccountId, role: &Self::Role) {
-        Self::with_members_of_mut(role, |set| set.remove(account_id));
+        if !remove_member::<Self>(role, account_id) {
+            remove_legacy_member::<Self>(role, account_id);
+        }
+    }
+
+    fn add_roles(&mut self, roles: &[(AccountId, Self::Role)]) -> u32
+    where
+        Self::Role: Serialize,
+    {
+        let mut counts = MemberCounts::new();
+        let mut assignments = Vec::new();
+
+        for (account_id, role) in roles {
+            if is_member::<Self>(account_id, role) {
+                index_role::<Self>(account_id, role);
+            } else {
+                insert_member_counted::<Self>(role, account_id, counts.get::<Self>(role));
+                assignments.push(RoleAssignment {
+                    account_id: account_id.clone(),
+                    role,
+                });
+            }
+        }
+
+        counts.write::<Self>();
+
+        let granted = assignments.len() as u32;
+        if granted > 0 {
+            RbacEvent::RolesGranted { assignments }.emit();
+        }
+        granted
+    }
+
+    fn remove_roles(&mut self, roles: &[(AccountId, Self::Role)]) -> u32
+    where
+        Self::Role: Serialize,
+    {
+        let mut counts = MemberCounts::new();
+        let mut assignments = Vec::new();
+
+        for (account_id, role) in roles {
+            let removed = if Self::slot_member_index(role, account_id).exists() {
+                remove_member_counted::<Self>(role, account_id, counts.get::<Self>(role))
+            } else {
+                remove_legacy_member::<Self>(role, account_id)
+            };
+
+            if removed {
+                assignments.push(RoleAssignment {
+                    account_id: account_id.clone(),
+                    role,
+                });
+            }
+        }
+
+        counts.write::<Self>();
+
+        let revoked = assignments.len() as u32;
+        if revoked > 0 {
+            RbacEvent::RolesRevoked { assignments }.emit();
+        }
+        revoked
+    }
+
+    fn migrate_legacy_members(&mut self, role: &Self::Role, limit: u32) -> u32 {
+        let mut slot = Self::slot_legacy_members_of(role);
+        let mut set = match slot.read() {
+            Some(set) => set,
+            None => return 0,
+        };
+
+        let batch = set.iter().take(limit as usize).cloned().collect::<Vec<_>>();
+
+        for account_id in batch {
+            set.remove(&account_id);
+            insert_member::<Self>(role, &account_id);
+        }
+
+        let remaining = set.len();
+        write_legacy(&mut slot, set);
+        remaining
     }
 
     fn require_role(role: &Self::Role) {
@@ -154,41 +730,143 @@ impl<I: RbacInternal> Rbac for I {
     fn prohibit_role(role: &Self::Role) {
         let predecessor = env::predecessor_account_id();
         require!(
-            !Self::has_role(&predecessor, role),
+            !is_member::<Self>(&predecessor, role),
             PROHIBIT_ROLE_FAIL_MESSAGE,
         );
     }
 }
 
-/// An iterator for `AccountId`s.
+/// A role change that can be approved before it is executed.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum RbacAction<R> {
+    /// Assign a role to an account.
+    Grant {
+        /// Account to assign the role to.
+        account_id: AccountId,
+        /// Role to assign.
+        role: R,
+    },
+    /// Remove a role from an account.
+    Revoke {
+        /// Account to remove the role from.
+        account_id: AccountId,
+        /// Role to remove.
+        role: R,
+    },
+}
+
+impl<C: Rbac> Action<C> for RbacAction<C::Role> {
+    type Output = ();
+
+    fn execute(self, contract: &mut C) {
+        match self {
+            Self::Grant { account_id, role } => contract.add_role(account_id, &role),
+            Self::Revoke { account_id, role } => contract.remove_role(&account_id, &role),
+        }
+    }
+}
+
+/// Role changes in a batch that can be approved before they are executed,
+/// e.g. to onboard a set of operators in one request. Roles are revoked
+/// before they are granted, so the same action can rotate operators.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk_contract_tools::rbac::RbacBatchAction;
+///
+/// let action = RbacBatchAction {
+///     grant: vec![
+///         ("alice.near".parse().unwrap(), "operator".to_string()),
+///         ("bob.near".parse().unwrap(), "operator".to_string()),
+///     ],
+///     revoke: vec![("carol.near".parse().unwrap(), "operator".to_string())],
+/// };
+/// ```
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RbacBatchAction<R> {
+    /// Roles to assign.
+    #[serde(default)]
+    pub grant: Vec<(AccountId, R)>,
+    /// Roles to remove.
+    #[serde(default)]
+    pub revoke: Vec<(AccountId, R)>,
+}
+
+impl<C: Rbac> Action<C> for RbacBatchAction<C::Role>
+where
+    C::Role: Serialize,
+{
+    type Output = ();
+
+    fn execute(self, contract: &mut C) {
+        contract.remove_roles(&self.revoke);
+        contract.add_roles(&self.grant);
+    }
+}
+
+/// An iterator for `AccountId`s. Yields the members of a role in the current
+/// storage layout, followed by any members remaining in the legacy layout.
 pub struct Iter {
-    inner_collection: UnorderedSet<AccountId>,
-    index: usize,
+    members: Slot<()>,
+    index: u32,
+    count: u32,
+    legacy: Option<UnorderedSet<AccountId>>,
+    legacy_index: usize,
 }
 
 impl Iter {
     /// Creates a new iterator from an `UnorderedSet`.
+    #[deprecated(note = "Use `Rbac::iter_members_of`.")]
     pub fn new(s: UnorderedSet<AccountId>) -> Self {
+        Self::from_layout(Slot::new(Vec::<u8>::new()), 0, Some(s))
+    }
+
+    fn from_layout(members: Slot<()>, count: u32, legacy: Option<UnorderedSet<AccountId>>) -> Self {
         Self {
-            inner_collection: s,
+            members,
             index: 0,
+            count,
+            legacy,
+            legacy_index: 0,
         }
     }
+
+    fn legacy_len(&self) -> usize {
+        self.legacy.as_ref().map_or(0, |set| set.len() as usize)
+    }
 }
 
 impl Iterator for Iter {
     type Item = AccountId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let value = self.inner_collection.iter().nth(self.index).cloned();
-        if value.is_some() {
+        if self.index < self.count {
+            let value = member_at(&self.members, self.index).read();
             self.index += 1;
+            return value;
+        }
+
+        let value = self
+            .legacy
+            .as_ref()
+            .and_then(|set| set.iter().nth(self.legacy_index).cloned());
+        if value.is_some() {
+            self.legacy_index += 1;
         }
         value
     }
 
     fn nth(&mut self, n: usize) -> Option<Self::Item> {
-        self.index = usize::min(self.inner_collection.len() as usize, self.index + n);
+        let remaining = (self.count - self.index) as usize;
+        if n < remaining {
+            self.index += n as u32;
+        } else {
+            self.index = self.count;
+            self.legacy_index = usize::min(self.legacy_len(), self.legacy_index + n - remaining);
+        }
         self.next()
     }
 
@@ -197,12 +875,13 @@ impl Iterator for Iter {
     where
         Self: Sized,
     {
-        self.inner_collection.len() as usize - self.index
+        self.size_hint().0
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let s = (self.inner_collection.len() as usize).saturating_sub(self.index);
+        let s = (self.count - self.index) as usize
+            + self.legacy_len().saturating_sub(self.legacy_index);
         (s, Some(s))
     }
 }
@@ -213,19 +892,25 @@ impl ExactSizeIterator for Iter {}
 #[cfg(test)]
 mod tests {
     use near_sdk::{
-        borsh::{self, BorshSerialize},
+        borsh::{self, BorshDeserialize, BorshSerialize},
         near_bindgen,
-        test_utils::VMContextBuilder,
+        serde::Serialize,
+        test_utils::{get_logs, VMContextBuilder},
         testing_env, AccountId, BorshStorageKey,
     };
+    use near_sdk::{env, store::UnorderedSet};
     use near_sdk_contract_tools_macros::Rbac;
 
-    use super::Rbac;
+    use super::{Rbac, RbacInternal};
 
-    #[derive(BorshSerialize, BorshStorageKey)]
+    #[derive(
+        BorshSerialize, BorshDeserialize, BorshStorageKey, Serialize, Debug, PartialEq, Eq,
+    )]
+    #[serde(crate = "near_sdk::serde")]
     enum Role {
         A,
         B,
+        C,
     }
 
     #[derive(Rbac)]
@@ -362,4 +1047,287 @@ mod tests {
 
         Contract::prohibit_role(&Role::B);
     }
+
+    #[test]
+    pub fn suspend_role() {
+        let mut r = Contract {};
+        let a: AccountId = "account".parse().unwrap();
+
+        r.add_role(a.clone(), &Role::A);
+        r.add_role(a.clone(), &Role::B);
+
+        assert!(r.suspend_role(Role::A));
+        assert!(!r.suspend_role(Role::A));
+        assert!(Contract::is_suspended(&Role::A));
+        assert!(!Contract::has_role(&a, &Role::A));
+        assert!(Contract::has_role(&a, &Role::B));
+        assert_eq!(Contract::count_members(&Role::A), 1);
+
+        r.add_role(a.clone(), &Role::A);
+        assert!(!Contract::has_role(&a, &Role::A));
+        assert_eq!(Contract::count_members(&Role::A), 1);
+
+        assert!(r.resume_role(Role::A));
+        assert!(!r.resume_role(Role::A));
+        assert!(!Contract::is_suspended(&Role::A));
+        assert!(Contract::has_role(&a, &Role::A));
+
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"x-rbac","version":"1.0.0","event":"role_suspended","data":{"role":"A"}}"#,
+                r#"EVENT_JSON:{"standard":"x-rbac","version":"1.0.0","event":"role_resumed","data":{"role":"A"}}"#,
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic = "Prohibited role"]
+    pub fn prohibit_role_fail_suspended() {
+        let mut r = Contract {};
+        let a: AccountId = "account".parse().unwrap();
+
+        r.add_role(a.clone(), &Role::A);
+        r.suspend_role(Role::A);
+
+        testing_env!(VMContextBuilder::new().predecessor_account_id(a).build());
+
+        Contract::prohibit_role(&Role::A);
+    }
+
+    #[test]
+    #[should_panic = "Unauthorized role"]
+    pub fn require_role_fail_suspended() {
+        let mut r = Contract {};
+        let a: AccountId = "account".parse().unwrap();
+
+        r.add_role(a.clone(), &Role::A);
+        r.suspend_role(Role::A);
+
+        testing_env!(VMContextBuilder::new().predecessor_account_id(a).build());
+
+        Contract::require_role(&Role::A);
+    }
+
+    #[test]
+    pub fn count_and_swap_remove() {
+        let mut r = Contract {};
+        let accounts: Vec<AccountId> = (0..4)
+            .map(|i| format!("account_{i}").parse().unwrap())
+            .collect();
+
+        for account in &accounts {
+            r.add_role(account.clone(), &Role::A);
+        }
+        r.add_role(accounts[0].clone(), &Role::A);
+
+        assert_eq!(Contract::count_members(&Role::A), 4);
+        assert_eq!(Contract::count_members(&Role::B), 0);
+
+        r.remove_role(&accounts[1], &Role::A);
+
+        assert_eq!(Contract::count_members(&Role::A), 3);
+        assert_eq!(
+            Contract::iter_members_of(&Role::A).collect::<Vec<_>>(),
+            vec![
+                accounts[0].clone(),
+                accounts[3].clone(),
+                accounts[2].clone(),
+            ],
+        );
+
+        let storage_usage = env::storage_usage();
+        r.add_role(accounts[1].clone(), &Role::A);
+        r.remove_role(&accounts[1], &Role::A);
+        assert_eq!(env::storage_usage(), storage_usage);
+
+        for account in &accounts {
+            r.remove_role(account, &Role::A);
+        }
+        assert_eq!(Contract::count_members(&Role::A), 0);
+        assert!(!Contract::slot_member_count(&Role::A).exists());
+    }
+
+    #[test]
+    pub fn roles_of() {
+        let mut r = Contract {};
+        let a: AccountId = "account_a".parse().unwrap();
+        let b: AccountId = "account_b".parse().unwrap();
+
+        assert!(Contract::roles_of(&a, 0, u32::MAX).is_empty());
+
+        r.add_role(a.clone(), &Role::A);
+        r.add_role(a.clone(), &Role::B);
+        r.add_role(a.clone(), &Role::C);
+        r.add_role(a.clone(), &Role::B);
+        r.add_role(b.clone(), &Role::C);
+
+        assert_eq!(Contract::count_roles_of(&a), 3);
+        assert_eq!(
+            Contract::roles_of(&a, 0, u32::MAX),
+            vec![Role::A, Role::B, Role::C],
+        );
+        assert_eq!(Contract::roles_of(&a, 1, 1), vec![Role::B]);
+        assert!(Contract::roles_of(&a, 3, 1).is_empty());
+        assert_eq!(Contract::roles_of(&b, 0, u32::MAX), vec![Role::C]);
+
+        let storage_usage = env::storage_usage();
+        r.remove_role(&a, &Role::A);
+        assert_eq!(Contract::roles_of(&a, 0, u32::MAX), vec![Role::C, Role::B]);
+
+        r.remove_role(&a, &Role::C);
+        r.remove_role(&a, &Role::B);
+        assert_eq!(Contract::count_roles_of(&a), 0);
+        assert!(!Contract::slot_role_count_of(&a).exists());
+        assert!(env::storage_usage() < storage_usage);
+
+        r.add_role(a.clone(), &Role::A);
+        assert_eq!(Contract::roles_of(&a, 0, u32::MAX), vec![Role::A]);
+    }
+
+    #[test]
+    pub fn legacy_migration() {
+        let mut r = Contract {};
+        let accounts: Vec<AccountId> = (0..5)
+            .map(|i| format!("account_{i}").parse().unwrap())
+            .collect();
+
+        let mut legacy_slot = Contract::slot_legacy_members_of(&Role::A);
+        let mut legacy = UnorderedSet::new(legacy_slot.key.clone());
+        for account in &accounts[..4] {
+            legacy.insert(account.clone());
+        }
+        legacy_slot.write(&legacy);
+        drop(legacy);
+
+        assert!(Contract::has_role(&accounts[0], &Role::A));
+        assert_eq!(Contract::count_members(&Role::A), 4);
+
+        r.add_role(accounts[4].clone(), &Role::A);
+        r.add_role(accounts[0].clone(), &Role::A);
+        r.remove_role(&accounts[1], &Role::A);
+
+        assert_eq!(Contract::count_members(&Role::A), 4);
+        assert_eq!(
+            Contract::iter_members_of(&Role::A).collect::<Vec<_>>(),
+            vec![
+                accounts[4].clone(),
+                accounts[0].clone(),
+                accounts[2].clone(),
+                accounts[3].clone(),
+            ],
+        );
+
+        assert_eq!(r.migrate_legacy_members(&Role::A, 2), 1);
+        assert_eq!(Contract::count_members(&Role::A), 4);
+        assert!(Contract::has_role(&accounts[0], &Role::A));
+
+        assert_eq!(r.migrate_legacy_members(&Role::A, 2), 0);
+        assert!(!legacy_slot.exists());
+        assert_eq!(Contract::slot_member_count(&Role::A).read(), Some(4));
+        assert_eq!(Contract::iter_members_of(&Role::A).count(), 4);
+        assert!(!Contract::has_role(&accounts[1], &Role::A));
+        assert_eq!(r.migrate_legacy_members(&Role::A, 2), 0);
+
+        for account in [&accounts[0], &accounts[2], &accounts[3], &accounts[4]] {
+            assert_eq!(Contract::roles_of(account, 0, u32::MAX), vec![Role::A]);
+        }
+        assert!(Contract::roles_of(&accounts[1], 0, u32::MAX).is_empty());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    pub fn deprecated_member_set() {
+        let mut r = Contract {};
+        let a: AccountId = "account_a".parse().unwrap();
+        let b: AccountId = "account_b".parse().unwrap();
+        let c: AccountId = "account_c".parse().unwrap();
+
+        let mut legacy_slot = Contract::slot_members_of(&Role::A);
+        let mut legacy = UnorderedSet::new(legacy_slot.key.clone());
+        legacy.insert(a.clone());
+        legacy_slot.write(&legacy);
+        drop(legacy);
+
+        r.add_role(b.clone(), &Role::A);
+
+        let storage_usage = env::storage_usage();
+        let members = Contract::with_members_of(&Role::A, |set| {
+            let mut members = set.iter().cloned().collect::<Vec<_>>();
+            members.sort();
+            members
+        });
+        assert_eq!(members, vec![a.clone(), b.clone()]);
+        assert_eq!(env::storage_usage(), storage_usage);
+
+        let removed = Contract::with_members_of_mut(&Role::A, |set| {
+            set.insert(c.clone());
+            set.remove(&a) && set.remove(&b)
+        });
+        assert!(removed);
+        assert_eq!(
+            Contract::iter_members_of(&Role::A).collect::<Vec<_>>(),
+            vec![c.clone()],
+        );
+        assert_eq!(Contract::count_members(&Role::A), 1);
+        assert!(!Contract::has_role(&a, &Role::A));
+        assert!(!Contract::has_role(&b, &Role::A));
+        assert!(!legacy_slot.exists());
+        assert_eq!(Contract::roles_of(&c, 0, u32::MAX), vec![Role::A]);
+    }
+
+    #[test]
+    pub fn batch() {
+        let mut r = Contract {};
+        let a: AccountId = "account_a".parse().unwrap();
+        let b: AccountId = "account_b".parse().unwrap();
+        let c: AccountId = "account_c".parse().unwrap();
+
+        r.add_role(a.clone(), &Role::A);
+
+        let granted = r.add_roles(&[
+            (a.clone(), Role::A),
+            (b.clone(), Role::A),
+            (b.clone(), Role::B),
+            (c.clone(), Role::A),
+            (c.clone(), Role::A),
+        ]);
+
+        assert_eq!(granted, 3);
+        assert_eq!(Contract::count_members(&Role::A), 3);
+        assert_eq!(Contract::count_members(&Role::B), 1);
+        assert_eq!(
+            Contract::iter_members_of(&Role::A).collect::<Vec<_>>(),
+            vec![a.clone(), b.clone(), c.clone()],
+        );
+        assert_eq!(Contract::roles_of(&b, 0, u32::MAX), vec![Role::A, Role::B]);
+
+        let revoked = r.remove_roles(&[
+            (a.clone(), Role::A),
+            (b.clone(), Role::B),
+            (b.clone(), Role::C),
+            (a.clone(), Role::A),
+        ]);
+
+        assert_eq!(revoked, 2);
+        assert_eq!(Contract::count_members(&Role::A), 2);
+        assert_eq!(
+            Contract::iter_members_of(&Role::A).collect::<Vec<_>>(),
+            vec![c.clone(), b.clone()],
+        );
+        assert!(!Contract::has_role(&a, &Role::A));
+        assert!(!Contract::slot_member_count(&Role::B).exists());
+        assert_eq!(Contract::roles_of(&b, 0, u32::MAX), vec![Role::A]);
+
+        assert_eq!(r.add_roles(&[(c.clone(), Role::A)]), 0);
+        assert_eq!(r.remove_roles(&[(a, Role::B)]), 0);
+
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"x-rbac","version":"1.0.0","event":"roles_granted","data":{"assignments":[{"account_id":"account_b","role":"A"},{"account_id":"account_b","role":"B"},{"account_id":"account_c","role":"A"}]}}"#,
+                r#"EVENT_JSON:{"standard":"x-rbac","version":"1.0.0","event":"roles_revoked","data":{"assignments":[{"account_id":"account_a","role":"A"},{"account_id":"account_b","role":"B"}]}}"#,
+            ],
+        );
+    }
 }
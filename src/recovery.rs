@@ -0,0 +1,300 @@
+//! Dead-man switch for recovering an inactive contract.
+//!
+//! The owner proves that it is still active by calling `recovery_heartbeat`
+//! periodically. If no heartbeat is recorded within the configured window,
+//! the designated recovery account may call `recovery_claim`, which runs the
+//! contract's [`RecoveryAction`]. By default, the action is
+//! [`ClaimOwnership`], which transfers ownership to the recovery account.
+//!
+//! The recovery account can be any account, including another contract such
+//! as a multisig (e.g. one deriving `SimpleMultisig`), so that recovery
+//! requires the approval of several parties.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::Recovery) derives a
+//! default implementation. Requires `Owner`.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The recovery root storage slot is not used or modified. The default
+//!     key is `~rec`.
+//! * (ERR) The inactivity window must be nonzero.
+//! * (ERR) Only the recovery account can claim, and only once the window has
+//!     passed since the last heartbeat.
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+use thiserror::Error;
+
+use crate::{owner::Owner, slot::Slot, standard::nep297::Event, DefaultStorageKey};
+
+pub use ext::*;
+
+/// Who may recover the contract, and after how long.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RecoveryConfig {
+    /// Account that may claim the contract.
+    pub recovery_id: AccountId,
+    /// Nanoseconds without a heartbeat after which the contract may be
+    /// claimed.
+    pub window_ns: U64,
+}
+
+/// Events emitted by the recovery component.
+#[event(
+    standard = "x-recovery",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum RecoveryEvent {
+    /// The recovery configuration changed.
+    Configured {
+        /// The new configuration. Will be `None` if recovery is disabled.
+        config: Option<RecoveryConfig>,
+    },
+    /// The owner checked in.
+    Heartbeat {
+        /// Block timestamp of the heartbeat, in nanoseconds.
+        timestamp: U64,
+    },
+    /// The recovery account claimed the contract.
+    Recovered {
+        /// The recovery account.
+        recovery_id: AccountId,
+        /// Block timestamp of the last heartbeat before the claim, in
+        /// nanoseconds.
+        last_heartbeat: U64,
+    },
+}
+
+/// Errors that may occur when configuring or claiming recovery.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RecoveryError {
+    /// Recovery is not configured.
+    #[error("Recovery is not configured")]
+    NotConfigured,
+    /// The inactivity window is zero.
+    #[error("Recovery window must be nonzero")]
+    ZeroWindow,
+    /// The account is not the recovery account.
+    #[error("Account {0} is not the recovery account")]
+    Unauthorized(AccountId),
+    /// The owner sent a heartbeat within the window.
+    #[error("Owner is active; recovery is available at {available_at}")]
+    OwnerActive {
+        /// Block timestamp at which recovery becomes available, in
+        /// nanoseconds.
+        available_at: u64,
+    },
+}
+
+/// What happens when the recovery account claims the contract.
+pub trait RecoveryAction<C> {
+    /// Recovers `contract` on behalf of `recovery_id`.
+    fn recover(contract: &mut C, recovery_id: &AccountId);
+}
+
+/// Transfers ownership of the contract to the recovery account.
+pub struct ClaimOwnership;
+
+impl<C: Owner> RecoveryAction<C> for ClaimOwnership {
+    fn recover(contract: &mut C, recovery_id: &AccountId) {
+        contract.update_proposed(None);
+        contract.update_owner(Some(recovery_id.clone()));
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Config,
+    LastHeartbeat,
+}
+
+/// Internal functions for [`Recovery`]. Using these methods may result in unexpected behavior.
+pub trait RecoveryInternal {
+    /// Invoked when the recovery account claims the contract.
+    type Action: RecoveryAction<Self>
+    where
+        Self: Sized;
+
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Recovery)
+    }
+
+    /// Storage slot for the recovery configuration
+    fn slot_config() -> Slot<RecoveryConfig> {
+        Self::root().field(StorageKey::Config)
+    }
+
+    /// Storage slot for the timestamp of the last heartbeat
+    fn slot_last_heartbeat() -> Slot<u64> {
+        Self::root().field(StorageKey::LastHeartbeat)
+    }
+}
+
+/// Lets a designated account recover the contract if the owner stops
+/// sending heartbeats.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{env, json_types::U64, near_bindgen, AccountId, PanicOnDefault};
+/// use near_sdk_contract_tools::{owner::*, recovery::*, Owner, Recovery};
+///
+/// #[derive(Owner, Recovery, PanicOnDefault)]
+/// #[near_bindgen]
+/// struct Contract {}
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     #[init]
+///     pub fn new(recovery_id: AccountId) -> Self {
+///         let mut contract = Self {};
+///         Owner::init(&mut contract, &env::predecessor_account_id());
+///         // Recoverable after 90 days of inactivity.
+///         contract
+///             .set_recovery_config(Some(RecoveryConfig {
+///                 recovery_id,
+///                 window_ns: U64(90 * 86_400_000_000_000),
+///             }))
+///             .unwrap();
+///         contract
+///     }
+/// }
+/// ```
+pub trait Recovery {
+    /// The recovery configuration, if any.
+    fn recovery_config(&self) -> Option<RecoveryConfig>;
+
+    /// Block timestamp of the last heartbeat, if any.
+    fn last_heartbeat(&self) -> Option<u64>;
+
+    /// Block timestamp at which the contract becomes recoverable, if
+    /// recovery is configured.
+    fn recovery_available_at(&self) -> Option<u64>;
+
+    /// Sets the recovery configuration, or disables recovery if `config` is
+    /// `None`. Also records a heartbeat. Emits [`RecoveryEvent::Configured`].
+    fn set_recovery_config(&mut self, config: Option<RecoveryConfig>) -> Result<(), RecoveryError>;
+
+    /// Records that the owner is active. Emits [`RecoveryEvent::Heartbeat`].
+    fn heartbeat(&mut self);
+
+    /// Runs the recovery action on behalf of `account_id`, if it is the
+    /// recovery account and the window has passed since the last heartbeat.
+    /// Then records a heartbeat, so the window restarts. Emits
+    /// [`RecoveryEvent::Recovered`].
+    fn recover(&mut self, account_id: &AccountId) -> Result<(), RecoveryError>;
+}
+
+impl<T: RecoveryInternal> Recovery for T {
+    fn recovery_config(&self) -> Option<RecoveryConfig> {
+        Self::slot_config().read()
+    }
+
+    fn last_heartbeat(&self) -> Option<u64> {
+        Self::slot_last_heartbeat().read()
+    }
+
+    fn recovery_available_at(&self) -> Option<u64> {
+        let config = self.recovery_config()?;
+        let last_heartbeat = self.last_heartbeat().unwrap_or(0);
+        Some(last_heartbeat.saturating_add(config.window_ns.0))
+    }
+
+    fn set_recovery_config(&mut self, config: Option<RecoveryConfig>) -> Result<(), RecoveryError> {
+        if matches!(&config, Some(c) if c.window_ns.0 == 0) {
+            return Err(RecoveryError::ZeroWindow);
+        }
+
+        Self::slot_config().set(config.as_ref());
+        Self::slot_last_heartbeat().write(&env::block_timestamp());
+
+        RecoveryEvent::Configured { config }.emit();
+
+        Ok(())
+    }
+
+    fn heartbeat(&mut self) {
+        let timestamp = env::block_timestamp();
+        Self::slot_last_heartbeat().write(&timestamp);
+
+        RecoveryEvent::Heartbeat {
+            timestamp: timestamp.into(),
+        }
+        .emit();
+    }
+
+    fn recover(&mut self, account_id: &AccountId) -> Result<(), RecoveryError> {
+        let config = self.recovery_config().ok_or(RecoveryError::NotConfigured)?;
+
+        if account_id != &config.recovery_id {
+            return Err(RecoveryError::Unauthorized(account_id.clone()));
+        }
+
+        let last_heartbeat = self.last_heartbeat().unwrap_or(0);
+        let available_at = last_heartbeat.saturating_add(config.window_ns.0);
+        if env::block_timestamp() < available_at {
+            return Err(RecoveryError::OwnerActive { available_at });
+        }
+
+        T::Action::recover(self, account_id);
+        Self::slot_last_heartbeat().write(&env::block_timestamp());
+
+        RecoveryEvent::Recovered {
+            recovery_id: account_id.clone(),
+            last_heartbeat: last_heartbeat.into(),
+        }
+        .emit();
+
+        Ok(())
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U64};
+
+    use super::RecoveryConfig;
+
+    /// External (public) methods for [`super::Recovery`]
+    #[ext_contract(ext_recovery)]
+    pub trait RecoveryExternal {
+        /// Returns the recovery configuration, if any.
+        fn recovery_get_config(&self) -> Option<RecoveryConfig>;
+
+        /// Returns the block timestamp of the last heartbeat, if any.
+        fn recovery_get_last_heartbeat(&self) -> Option<U64>;
+
+        /// Returns the block timestamp at which the contract becomes
+        /// recoverable, if recovery is configured.
+        fn recovery_get_available_at(&self) -> Option<U64>;
+
+        /// Sets the recovery configuration, or disables recovery if `config`
+        /// is `null`. Only callable by the owner. Requires exactly 1
+        /// yoctoNEAR.
+        fn recovery_set_config(&mut self, config: Option<RecoveryConfig>);
+
+        /// Records that the owner is active. Only callable by the owner.
+        /// Requires exactly 1 yoctoNEAR.
+        fn recovery_heartbeat(&mut self);
+
+        /// Claims the contract after the owner has been inactive for the
+        /// configured window. Only callable by the recovery account.
+        /// Requires exactly 1 yoctoNEAR.
+        fn recovery_claim(&mut self);
+    }
+}
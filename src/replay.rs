@@ -0,0 +1,293 @@
+//! Replay protection for receiver contracts: a registry of processed
+//! transfers.
+//!
+//! Contracts that implement `ft_on_transfer` or `nft_on_transfer` act on the
+//! `msg` of each transfer, e.g. to credit a deposit or fill an order. A user
+//! who resubmits a transaction (e.g. after a wallet timeout) makes the token
+//! contract call the receiver again with the same message, and a receiver
+//! that does not detect the duplicate processes it twice.
+//!
+//! [`ReplayGuard`] records a [`ReplayKey`] for each processed transfer,
+//! derived from the sender and a nonce chosen by the sender (e.g. a field of
+//! `msg`, or the memo). [`ReplayGuard::record_processed`] rejects a key that
+//! has already been recorded. Records expire after
+//! [`ReplayGuardInternal::TTL`] nanoseconds, after which the key may be used
+//! again, and are cleaned up a few at a time by each new record, or by
+//! [`ReplayGuard::cleanup_processed`]. Senders should therefore include a
+//! deadline in their messages, and receivers should reject messages whose
+//! deadline is further away than the TTL.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::ReplayGuard)
+//! derives a default implementation.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The replay guard root storage slot is not used or modified. The
+//!     default key is `~rp`.
+//! * (UB) [`ReplayGuardInternal::TTL`] does not decrease. Records made
+//!     before a decrease are cleaned up late.
+//! * (ERR) [`ReplayGuard::record_processed`] may only be called with a key
+//!     that has not been recorded, or whose record has expired.
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, AccountId, BorshStorageKey, CryptoHash,
+};
+use thiserror::Error;
+
+use crate::{slot::Slot, DefaultStorageKey};
+
+pub use ext::*;
+
+/// Default time to live of a record: 7 days, in nanoseconds.
+pub const DEFAULT_REPLAY_TTL: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+
+/// Default number of expired records cleaned up by each new record.
+pub const DEFAULT_REPLAY_CLEANUP_LIMIT: u32 = 4;
+
+/// Identifies a processed transfer.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplayKey(pub CryptoHash);
+
+impl ReplayKey {
+    /// The key of the transfer from `sender_id` with `nonce`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk_contract_tools::replay::ReplayKey;
+    ///
+    /// let alice = "alice".parse().unwrap();
+    /// let bob = "bob".parse().unwrap();
+    ///
+    /// assert_eq!(ReplayKey::new(&alice, "1"), ReplayKey::new(&alice, "1"));
+    /// assert_ne!(ReplayKey::new(&alice, "1"), ReplayKey::new(&alice, "2"));
+    /// assert_ne!(ReplayKey::new(&alice, "1"), ReplayKey::new(&bob, "1"));
+    /// ```
+    pub fn new(sender_id: &AccountId, nonce: &str) -> Self {
+        let preimage = (sender_id, nonce)
+            .try_to_vec()
+            .unwrap_or_else(|_| env::panic_str("Failed to serialize replay key"));
+        Self(env::sha256_array(&preimage))
+    }
+}
+
+/// The transfer has already been processed.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Transfer already processed (record expires at {expires_at})")]
+pub struct AlreadyProcessedError {
+    /// Timestamp at which the record expires, in nanoseconds.
+    pub expires_at: u64,
+}
+
+/// A record in the cleanup queue.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ReplayRecord {
+    /// The recorded key.
+    pub key: ReplayKey,
+    /// Timestamp at which the record expires, in nanoseconds.
+    pub expires_at: u64,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Record(ReplayKey),
+    QueueHead,
+    QueueTail,
+    QueueEntry(u64),
+}
+
+/// Internal functions for [`ReplayGuard`]. Using these methods may result in
+/// unexpected behavior.
+pub trait ReplayGuardInternal {
+    /// Time to live of a record, in nanoseconds.
+    const TTL: u64 = DEFAULT_REPLAY_TTL;
+
+    /// Number of expired records cleaned up by each new record.
+    const CLEANUP_LIMIT: u32 = DEFAULT_REPLAY_CLEANUP_LIMIT;
+
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::ReplayGuard)
+    }
+
+    /// Storage slot for the expiry of the record of a key
+    fn slot_record(key: &ReplayKey) -> Slot<u64> {
+        Self::root().field(StorageKey::Record(*key))
+    }
+
+    /// Storage slot for the sequence number of the oldest record in the
+    /// cleanup queue
+    fn slot_queue_head() -> Slot<u64> {
+        Self::root().field(StorageKey::QueueHead)
+    }
+
+    /// Storage slot for the sequence number of the next record in the
+    /// cleanup queue
+    fn slot_queue_tail() -> Slot<u64> {
+        Self::root().field(StorageKey::QueueTail)
+    }
+
+    /// Storage slot for an entry of the cleanup queue
+    fn slot_queue_entry(sequence: u64) -> Slot<ReplayRecord> {
+        Self::root().field(StorageKey::QueueEntry(sequence))
+    }
+}
+
+/// Registry of processed transfers.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{env, json_types::U128, near_bindgen, AccountId, PromiseOrValue};
+/// use near_sdk_contract_tools::{
+///     replay::{ReplayGuard, ReplayKey},
+///     standard::nep141::Nep141Receiver,
+///     ReplayGuard,
+/// };
+///
+/// #[derive(ReplayGuard)]
+/// #[near_bindgen]
+/// struct Contract {}
+///
+/// #[near_bindgen]
+/// impl Nep141Receiver for Contract {
+///     fn ft_on_transfer(
+///         &mut self,
+///         sender_id: AccountId,
+///         amount: U128,
+///         msg: String,
+///     ) -> PromiseOrValue<U128> {
+///         // `msg` is a nonce chosen by the sender.
+///         let key = ReplayKey::new(&sender_id, &msg);
+///         self.record_processed(&key)
+///             .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+///
+///         // Process the transfer...
+///
+///         PromiseOrValue::Value(U128(0))
+///     }
+/// }
+/// ```
+pub trait ReplayGuard {
+    /// Returns the expiry of the unexpired record of `key`, if any.
+    fn processed_expiry(key: &ReplayKey) -> Option<u64>;
+
+    /// Returns `true` if `key` has an unexpired record.
+    fn is_processed(key: &ReplayKey) -> bool {
+        Self::processed_expiry(key).is_some()
+    }
+
+    /// Checks that `key` has no unexpired record.
+    fn check_not_processed(key: &ReplayKey) -> Result<(), AlreadyProcessedError> {
+        match Self::processed_expiry(key) {
+            Some(expires_at) => Err(AlreadyProcessedError { expires_at }),
+            None => Ok(()),
+        }
+    }
+
+    /// Rejects if `key` has an unexpired record.
+    fn assert_not_processed(key: &ReplayKey) {
+        Self::check_not_processed(key).unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    /// Returns the number of records, including expired records that have
+    /// not been cleaned up.
+    fn processed_count() -> u64;
+
+    /// Checks that `key` has no unexpired record, and records it. Cleans up
+    /// a few expired records.
+    fn record_processed(&mut self, key: &ReplayKey) -> Result<(), AlreadyProcessedError>;
+
+    /// Removes up to `limit` expired records, oldest first. Returns the
+    /// number of records removed.
+    fn cleanup_processed(&mut self, limit: u32) -> u32;
+}
+
+impl<T: ReplayGuardInternal> ReplayGuard for T {
+    fn processed_expiry(key: &ReplayKey) -> Option<u64> {
+        Self::slot_record(key)
+            .read()
+            .filter(|expires_at| *expires_at > env::block_timestamp())
+    }
+
+    fn processed_count() -> u64 {
+        Self::slot_queue_tail().read().unwrap_or(0) - Self::slot_queue_head().read().unwrap_or(0)
+    }
+
+    fn record_processed(&mut self, key: &ReplayKey) -> Result<(), AlreadyProcessedError> {
+        Self::check_not_processed(key)?;
+
+        let expires_at = env::block_timestamp().saturating_add(Self::TTL);
+        Self::slot_record(key).write(&expires_at);
+
+        let mut tail = Self::slot_queue_tail();
+        let sequence = tail.read().unwrap_or(0);
+        Self::slot_queue_entry(sequence).write(&ReplayRecord {
+            key: *key,
+            expires_at,
+        });
+        tail.write(&(sequence + 1));
+
+        self.cleanup_processed(Self::CLEANUP_LIMIT);
+
+        Ok(())
+    }
+
+    fn cleanup_processed(&mut self, limit: u32) -> u32 {
+        let now = env::block_timestamp();
+        let tail = Self::slot_queue_tail().read().unwrap_or(0);
+        let mut head = Self::slot_queue_head().read().unwrap_or(0);
+        let mut removed = 0;
+
+        while removed < limit && head < tail {
+            let mut slot = Self::slot_queue_entry(head);
+            let entry = match slot.read() {
+                Some(entry) if entry.expires_at <= now => entry,
+                _ => break,
+            };
+
+            // The key may have been recorded again after this record
+            // expired, in which case the newer record is kept.
+            let mut record = Self::slot_record(&entry.key);
+            if record.read() == Some(entry.expires_at) {
+                record.remove();
+            }
+
+            slot.remove();
+            head += 1;
+            removed += 1;
+        }
+
+        if removed > 0 {
+            Self::slot_queue_head().write(&head);
+        }
+
+        removed
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U64, AccountId};
+
+    /// External (public) methods for [`ReplayGuard`](super::ReplayGuard)
+    #[ext_contract(ext_replay_guard)]
+    pub trait ReplayGuardExternal {
+        /// Returns `true` if the transfer from `sender_id` with `nonce` has
+        /// been processed, and its record has not expired.
+        fn replay_is_processed(&self, sender_id: AccountId, nonce: String) -> bool;
+
+        /// Returns the number of records, including expired records that
+        /// have not been cleaned up.
+        fn replay_processed_count(&self) -> U64;
+
+        /// Removes up to `limit` expired records. Anyone may call this.
+        /// Returns the number of records removed.
+        fn replay_cleanup(&mut self, limit: u32) -> u32;
+    }
+}
@@ -0,0 +1,550 @@
+//! Fixed-price sales of NEP-171 tokens, for marketplaces embedded in NFT
+//! contracts.
+//!
+//! The owner of a token lists it at a price in either native NEAR or the
+//! contract's own NEP-141 token. A buyer pays the price, which is split
+//! between the seller and the token's royalty recipients (see
+//! [`SaleRoyalties`]) as a NEP-199 [`Payout`], and receives the token.
+//!
+//! When the price is in the contract's NEP-141 token, the payment and the
+//! token transfer are performed by the token controllers in the same call,
+//! so a sale either completes entirely or not at all. Native NEAR payouts are
+//! sent with transfer promises after the token is transferred.
+//!
+//! A listing must be removed when its token is transferred or burned by
+//! other means, or the old price would become buyable again if the token
+//! returned to the seller. [`SaleListings`] is a NEP-171 hook that does
+//! this: add it to the hooks of `Nep171` (e.g. `all_hooks`), or set
+//! `#[non_fungible_token(sale)]`. Without it, buying a listing whose token
+//! has a new owner fails, and the new owner may list the token again.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::Sale) derives a
+//! default implementation. The contract must implement `Nep141` and
+//! `Nep171`.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The sale root storage slot is not used or modified. The default key
+//!     is `~sale`.
+//! * (ERR) Only the current owner of a token may list or delist it.
+//! * (ERR) A sale pays at least the listed price, and royalties never exceed
+//!     the price.
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey, Promise,
+};
+use thiserror::Error;
+
+use crate::{
+    event,
+    hook::{ActionOrigin, Hook},
+    slot::Slot,
+    standard::{
+        nep141::{Nep141Controller, Nep141Transfer, TransferError},
+        nep171::{
+            action::{Nep171Burn, Nep171Mint, Nep171Transfer, Nep171TransferAction},
+            error::Nep171TransferError,
+            Nep171Controller, Nep171TransferAuthorization, TokenId,
+        },
+        nep297::Event,
+    },
+    DefaultStorageKey,
+};
+
+pub use crate::standard::nep199::{Payout, RoyaltiesExceedPriceError, ROYALTY_BASIS_POINTS};
+pub use ext::*;
+
+/// What a listed token is paid for with.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum SaleCurrency {
+    /// Native NEAR, attached to the purchase.
+    Near,
+    /// The contract's own NEP-141 token.
+    Ft,
+}
+
+/// A token listed for sale.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleListing {
+    /// Owner of the token at the time of listing.
+    pub owner_id: AccountId,
+    /// Price of the token.
+    pub price: U128,
+    /// Currency of the price.
+    pub currency: SaleCurrency,
+}
+
+/// Provides the royalties paid from each sale of a token.
+pub trait SaleRoyalties<C> {
+    /// Royalty recipients of `token_id` and their share of the price, in
+    /// basis points (see [`ROYALTY_BASIS_POINTS`]).
+    fn royalties(contract: &C, token_id: &TokenId) -> Vec<(AccountId, u16)>;
+}
+
+/// No royalties: the seller receives the full price.
+impl<C> SaleRoyalties<C> for () {
+    fn royalties(_contract: &C, _token_id: &TokenId) -> Vec<(AccountId, u16)> {
+        Vec::new()
+    }
+}
+
+/// A token was listed for sale.
+#[event(
+    standard = "x-sale",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct SaleListed {
+    /// Listed token.
+    pub token_id: TokenId,
+    /// Owner of the token.
+    pub owner_id: AccountId,
+    /// Price of the token.
+    pub price: U128,
+    /// Currency of the price.
+    pub currency: SaleCurrency,
+}
+
+/// A listing was removed by its owner, or by [`SaleListings`] because its
+/// token was transferred or burned outside a sale.
+#[event(
+    standard = "x-sale",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct SaleDelisted {
+    /// Delisted token.
+    pub token_id: TokenId,
+}
+
+/// A token was sold.
+#[event(
+    standard = "x-sale",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct SaleCompleted {
+    /// Sold token.
+    pub token_id: TokenId,
+    /// Previous owner of the token.
+    pub seller_id: AccountId,
+    /// New owner of the token.
+    pub buyer_id: AccountId,
+    /// Price paid.
+    pub price: U128,
+    /// Currency of the price.
+    pub currency: SaleCurrency,
+}
+
+/// Errors that may occur when listing or delisting a token.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ListError {
+    /// Only the current owner of a token may list or delist it.
+    #[error("Token '{token_id}' is not owned by '{account_id}'")]
+    NotOwner {
+        /// Token.
+        token_id: TokenId,
+        /// Account that attempted the action.
+        account_id: AccountId,
+    },
+    /// The token is not listed.
+    #[error("Token '{0}' is not listed for sale")]
+    NotListed(TokenId),
+}
+
+/// Errors that may occur when buying a token.
+#[derive(Error, Debug)]
+pub enum BuyError {
+    /// The token is not listed.
+    #[error("Token '{0}' is not listed for sale")]
+    NotListed(TokenId),
+    /// The token was transferred since it was listed.
+    #[error("Listing of token '{0}' is stale")]
+    StaleListing(TokenId),
+    /// The token is priced in a different currency.
+    #[error("Token '{token_id}' is not priced in {currency:?}")]
+    WrongCurrency {
+        /// Token.
+        token_id: TokenId,
+        /// Currency offered by the buyer.
+        currency: SaleCurrency,
+    },
+    /// The payment is less than the price.
+    #[error("Insufficient payment: required {required} but got {provided}")]
+    InsufficientPayment {
+        /// Listed price.
+        required: u128,
+        /// Amount offered by the buyer.
+        provided: u128,
+    },
+    /// The owner of a token cannot buy it.
+    #[error("Account '{0}' cannot buy its own token")]
+    SelfPurchase(AccountId),
+    /// The token's royalties are invalid.
+    #[error(transparent)]
+    Royalties(#[from] RoyaltiesExceedPriceError),
+    /// The NEP-141 payment failed.
+    #[error(transparent)]
+    Payment(#[from] TransferError),
+    /// The NEP-171 transfer failed.
+    #[error(transparent)]
+    Transfer(#[from] Nep171TransferError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Listing(&'a TokenId),
+}
+
+/// Internal functions for [`Sale`]. Using these methods may result in unexpected behavior.
+pub trait SaleInternal {
+    /// Royalties paid from each sale.
+    type Royalties: SaleRoyalties<Self>
+    where
+        Self: Sized;
+
+    /// Storage root.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Sale)
+    }
+
+    /// Storage slot for the listing of `token_id`.
+    fn slot_listing(token_id: &TokenId) -> Slot<SaleListing> {
+        Self::root().field(StorageKey::Listing(token_id))
+    }
+}
+
+/// Fixed-price sales of NEP-171 tokens.
+pub trait Sale {
+    /// The listing of `token_id`, if any.
+    fn listing(&self, token_id: &TokenId) -> Option<SaleListing>;
+
+    /// The payout of a sale of `token_id` by its current owner for `price`.
+    fn sale_payout(&self, token_id: &TokenId, price: u128) -> Result<Payout, BuyError>;
+
+    /// Lists `token_id`, replacing any previous listing. Emits
+    /// [`SaleListed`].
+    fn list(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+        price: u128,
+        currency: SaleCurrency,
+    ) -> Result<(), ListError>;
+
+    /// Removes the listing of `token_id`. Emits [`SaleDelisted`].
+    fn delist(&mut self, owner_id: &AccountId, token_id: &TokenId) -> Result<(), ListError>;
+
+    /// Buys `token_id` with `deposit` native NEAR. The payout is sent with
+    /// transfer promises, and any excess deposit is refunded. Emits
+    /// [`SaleCompleted`].
+    fn buy_with_near(
+        &mut self,
+        buyer_id: &AccountId,
+        token_id: &TokenId,
+        deposit: u128,
+    ) -> Result<Payout, BuyError>;
+
+    /// Buys `token_id` with the contract's NEP-141 token, paying at most
+    /// `max_price`. Emits [`SaleCompleted`].
+    fn buy_with_ft(
+        &mut self,
+        buyer_id: &AccountId,
+        token_id: &TokenId,
+        max_price: u128,
+    ) -> Result<Payout, BuyError>;
+}
+
+fn check_listing<C: SaleInternal + Nep171Controller>(
+    contract: &C,
+    token_id: &TokenId,
+    buyer_id: &AccountId,
+    currency: SaleCurrency,
+) -> Result<SaleListing, BuyError> {
+    let listing = C::slot_listing(token_id)
+        .read()
+        .ok_or_else(|| BuyError::NotListed(token_id.clone()))?;
+
+    if contract.token_owner(token_id).as_ref() != Some(&listing.owner_id) {
+        return Err(BuyError::StaleListing(token_id.clone()));
+    }
+
+    if listing.currency != currency {
+        return Err(BuyError::WrongCurrency {
+            token_id: token_id.clone(),
+            currency,
+        });
+    }
+
+    if &listing.owner_id == buyer_id {
+        return Err(BuyError::SelfPurchase(buyer_id.clone()));
+    }
+
+    Ok(listing)
+}
+
+fn complete_sale<C: SaleInternal + Nep171Controller>(
+    contract: &mut C,
+    token_id: &TokenId,
+    listing: SaleListing,
+    buyer_id: &AccountId,
+) -> Result<(), BuyError> {
+    // Removed first, so that `SaleListings` does not report a delisting.
+    let mut slot = C::slot_listing(token_id);
+    slot.remove();
+
+    if let Err(e) = contract.external_transfer(&Nep171Transfer {
+        token_id,
+        authorization: Nep171TransferAuthorization::Owner,
+        sender_id: &listing.owner_id,
+        receiver_id: buyer_id,
+        memo: Some("sale"),
+        msg: None,
+        revert: false,
+        origin: ActionOrigin::Internal,
+    }) {
+        slot.write(&listing);
+        return Err(e.into());
+    }
+
+    SaleCompleted {
+        token_id: token_id.clone(),
+        seller_id: listing.owner_id,
+        buyer_id: buyer_id.clone(),
+        price: listing.price,
+        currency: listing.currency,
+    }
+    .emit();
+
+    Ok(())
+}
+
+impl<T: SaleInternal + Nep141Controller + Nep171Controller> Sale for T {
+    fn listing(&self, token_id: &TokenId) -> Option<SaleListing> {
+        Self::slot_listing(token_id).read()
+    }
+
+    fn sale_payout(&self, token_id: &TokenId, price: u128) -> Result<Payout, BuyError> {
+        let owner_id = self
+            .token_owner(token_id)
+            .ok_or_else(|| BuyError::NotListed(token_id.clone()))?;
+        let royalties = T::Royalties::royalties(self, token_id);
+
+        Ok(Payout::split(&owner_id, price, &royalties)?)
+    }
+
+    fn list(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+        price: u128,
+        currency: SaleCurrency,
+    ) -> Result<(), ListError> {
+        if self.token_owner(token_id).as_ref() != Some(owner_id) {
+            return Err(ListError::NotOwner {
+                token_id: token_id.clone(),
+                account_id: owner_id.clone(),
+            });
+        }
+
+        let listing = SaleListing {
+            owner_id: owner_id.clone(),
+            price: U128(price),
+            currency,
+        };
+        Self::slot_listing(token_id).write(&listing);
+
+        SaleListed {
+            token_id: token_id.clone(),
+            owner_id: listing.owner_id,
+            price: listing.price,
+            currency,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn delist(&mut self, owner_id: &AccountId, token_id: &TokenId) -> Result<(), ListError> {
+        let mut slot = Self::slot_listing(token_id);
+
+        if !slot.exists() {
+            return Err(ListError::NotListed(token_id.clone()));
+        }
+
+        if self.token_owner(token_id).as_ref() != Some(owner_id) {
+            return Err(ListError::NotOwner {
+                token_id: token_id.clone(),
+                account_id: owner_id.clone(),
+            });
+        }
+
+        slot.remove();
+
+        SaleDelisted {
+            token_id: token_id.clone(),
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn buy_with_near(
+        &mut self,
+        buyer_id: &AccountId,
+        token_id: &TokenId,
+        deposit: u128,
+    ) -> Result<Payout, BuyError> {
+        let listing = check_listing(self, token_id, buyer_id, SaleCurrency::Near)?;
+        let price = listing.price.0;
+
+        if deposit < price {
+            return Err(BuyError::InsufficientPayment {
+                required: price,
+                provided: deposit,
+            });
+        }
+
+        let payout = self.sale_payout(token_id, price)?;
+
+        complete_sale(self, token_id, listing, buyer_id)?;
+
+        for (account_id, amount) in &payout.payout {
+            Promise::new(account_id.clone()).transfer(amount.0);
+        }
+
+        if deposit > price {
+            Promise::new(buyer_id.clone()).transfer(deposit - price);
+        }
+
+        Ok(payout)
+    }
+
+    fn buy_with_ft(
+        &mut self,
+        buyer_id: &AccountId,
+        token_id: &TokenId,
+        max_price: u128,
+    ) -> Result<Payout, BuyError> {
+        let listing = check_listing(self, token_id, buyer_id, SaleCurrency::Ft)?;
+        let price = listing.price.0;
+
+        if max_price < price {
+            return Err(BuyError::InsufficientPayment {
+                required: price,
+                provided: max_price,
+            });
+        }
+
+        let payout = self.sale_payout(token_id, price)?;
+
+        for (account_id, amount) in &payout.payout {
+            if account_id == buyer_id {
+                continue;
+            }
+
+            Nep141Controller::transfer(
+                self,
+                &Nep141Transfer {
+                    sender_id: buyer_id,
+                    receiver_id: account_id,
+                    amount: amount.0,
+                    memo: Some("sale"),
+                    msg: None,
+                    revert: false,
+                    origin: ActionOrigin::Internal,
+                },
+            )?;
+        }
+
+        complete_sale(self, token_id, listing, buyer_id)?;
+
+        Ok(payout)
+    }
+}
+
+/// NEP-171 hook that removes the listing of a token when the token is
+/// transferred or burned outside a sale. Emits [`SaleDelisted`].
+pub struct SaleListings;
+
+fn remove_moved_listing<C: SaleInternal + Nep171Controller>(contract: &C, token_id: &TokenId) {
+    let mut slot = C::slot_listing(token_id);
+
+    let moved = slot
+        .read()
+        .is_some_and(|listing| contract.token_owner(token_id).as_ref() != Some(&listing.owner_id));
+
+    if moved {
+        slot.remove();
+
+        SaleDelisted {
+            token_id: token_id.clone(),
+        }
+        .emit();
+    }
+}
+
+impl<C: SaleInternal + Nep171Controller> Hook<C, Nep171Mint<'_>> for SaleListings {}
+
+impl<C: SaleInternal + Nep171Controller, T: Nep171TransferAction> Hook<C, T> for SaleListings {
+    fn hook<R>(contract: &mut C, args: &T, f: impl FnOnce(&mut C) -> R) -> R {
+        let r = f(contract);
+        remove_moved_listing(contract, args.token_id());
+        r
+    }
+}
+
+impl<C: SaleInternal + Nep171Controller> Hook<C, Nep171Burn<'_>> for SaleListings {
+    fn hook<R>(contract: &mut C, args: &Nep171Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let r = f(contract);
+        for token_id in args.token_ids {
+            remove_moved_listing(contract, token_id);
+        }
+        r
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U128};
+
+    use super::{Payout, SaleCurrency, SaleListing};
+    use crate::standard::nep171::TokenId;
+
+    /// Externally-accessible functions for [`super::Sale`].
+    #[ext_contract(ext_sale)]
+    pub trait SaleExternal {
+        /// Returns the listing of `token_id`, if any.
+        fn sale_get_listing(&self, token_id: TokenId) -> Option<SaleListing>;
+
+        /// Lists a token owned by the predecessor. Requires exactly 1
+        /// yoctoNEAR.
+        fn sale_list(&mut self, token_id: TokenId, price: U128, currency: SaleCurrency);
+
+        /// Removes the listing of a token owned by the predecessor. Requires
+        /// exactly 1 yoctoNEAR.
+        fn sale_delist(&mut self, token_id: TokenId);
+
+        /// Buys a token listed in NEAR with the attached deposit. Any excess
+        /// deposit is refunded.
+        fn sale_buy(&mut self, token_id: TokenId) -> Payout;
+
+        /// Buys a token listed in the contract's NEP-141 token, paying at
+        /// most `max_price`. Requires exactly 1 yoctoNEAR.
+        fn sale_buy_ft(&mut self, token_id: TokenId, max_price: U128) -> Payout;
+    }
+}
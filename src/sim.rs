@@ -0,0 +1,166 @@
+//! Simulation of contract methods on near-sdk's mocked blockchain.
+//!
+//! With the `mocked-sim` feature enabled, the derive macros in this crate
+//! generate a `sim_*` counterpart for every external method they expose. The
+//! `sim_*` functions take an [`Env`] describing the calling context, so
+//! contracts can be exercised natively (e.g. from property-based tests or
+//! fuzzers) without a Wasm runtime or hand-written `testing_env!` calls.
+//!
+//! The generated code still reads the context through `near_sdk::env`: each
+//! call installs its [`Env`] into near-sdk's mocked blockchain (see
+//! [`Env::with`]), which is global to the thread. Contract storage persists
+//! between calls; everything else about the execution context is taken from
+//! the [`Env`] passed to each call.
+//!
+//! The feature enables near-sdk's `unit-testing` feature, so it is only for
+//! native test builds, and does not compile for Wasm.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{near_bindgen, borsh::{self, BorshSerialize, BorshDeserialize}};
+//! use near_sdk_contract_tools::{owner::Owner, sim::Env, Owner};
+//!
+//! #[derive(Owner, BorshSerialize, BorshDeserialize)]
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! let alice: near_sdk::AccountId = "alice".parse().unwrap();
+//! let mut contract = Contract {};
+//! Owner::init(&mut contract, &alice);
+//!
+//! let env = Env::new("contract".parse().unwrap(), alice.clone());
+//! assert_eq!(contract.sim_own_get_owner(&env), Some(alice));
+//! ```
+
+use near_sdk::{env, test_utils::VMContextBuilder, AccountId, Balance, Gas, PromiseResult};
+
+/// Default amount of gas attached to simulated calls.
+pub const DEFAULT_SIM_PREPAID_GAS: Gas = Gas(300_000_000_000_000);
+
+/// Explicit execution context for a simulated contract call.
+#[derive(Clone, Debug)]
+pub struct Env {
+    /// Account ID of the contract being called.
+    pub current_account_id: AccountId,
+    /// Account ID of the immediate caller.
+    pub predecessor_account_id: AccountId,
+    /// Account ID that signed the original transaction.
+    pub signer_account_id: AccountId,
+    /// Deposit attached to the call, in yoctoNEAR.
+    pub attached_deposit: Balance,
+    /// Gas attached to the call.
+    pub prepaid_gas: Gas,
+    /// Balance of the contract account, in yoctoNEAR.
+    pub account_balance: Balance,
+    /// Block timestamp in nanoseconds.
+    pub block_timestamp: u64,
+    /// Block height.
+    pub block_height: u64,
+    /// Epoch height.
+    pub epoch_height: u64,
+    /// Results of the promises this call is a callback for. `None` marks a
+    /// failed promise.
+    pub promise_results: Vec<Option<Vec<u8>>>,
+}
+
+impl Env {
+    /// Creates a new context for a call from `predecessor_account_id` (who
+    /// is also the signer) to `current_account_id`.
+    pub fn new(current_account_id: AccountId, predecessor_account_id: AccountId) -> Self {
+        Self {
+            current_account_id,
+            signer_account_id: predecessor_account_id.clone(),
+            predecessor_account_id,
+            attached_deposit: 0,
+            prepaid_gas: DEFAULT_SIM_PREPAID_GAS,
+            account_balance: 0,
+            block_timestamp: 0,
+            block_height: 0,
+            epoch_height: 0,
+            promise_results: vec![],
+        }
+    }
+
+    /// Sets the signer account ID.
+    pub fn signer_account_id(mut self, signer_account_id: AccountId) -> Self {
+        self.signer_account_id = signer_account_id;
+        self
+    }
+
+    /// Sets the attached deposit.
+    pub fn attached_deposit(mut self, attached_deposit: Balance) -> Self {
+        self.attached_deposit = attached_deposit;
+        self
+    }
+
+    /// Sets the prepaid gas.
+    pub fn prepaid_gas(mut self, prepaid_gas: Gas) -> Self {
+        self.prepaid_gas = prepaid_gas;
+        self
+    }
+
+    /// Sets the contract account balance.
+    pub fn account_balance(mut self, account_balance: Balance) -> Self {
+        self.account_balance = account_balance;
+        self
+    }
+
+    /// Sets the block timestamp.
+    pub fn block_timestamp(mut self, block_timestamp: u64) -> Self {
+        self.block_timestamp = block_timestamp;
+        self
+    }
+
+    /// Sets the block height.
+    pub fn block_height(mut self, block_height: u64) -> Self {
+        self.block_height = block_height;
+        self
+    }
+
+    /// Sets the epoch height.
+    pub fn epoch_height(mut self, epoch_height: u64) -> Self {
+        self.epoch_height = epoch_height;
+        self
+    }
+
+    /// Sets the promise results visible to a callback.
+    pub fn promise_results(mut self, promise_results: Vec<Option<Vec<u8>>>) -> Self {
+        self.promise_results = promise_results;
+        self
+    }
+
+    /// Executes `f` in this context, by replacing the thread's mocked
+    /// blockchain with `testing_env!`. Contract storage (and storage usage) is
+    /// carried over from the previous context.
+    pub fn with<R>(&self, f: impl FnOnce() -> R) -> R {
+        let context = VMContextBuilder::new()
+            .current_account_id(self.current_account_id.clone())
+            .predecessor_account_id(self.predecessor_account_id.clone())
+            .signer_account_id(self.signer_account_id.clone())
+            .attached_deposit(self.attached_deposit)
+            .prepaid_gas(self.prepaid_gas)
+            .account_balance(self.account_balance)
+            .block_timestamp(self.block_timestamp)
+            .block_index(self.block_height)
+            .epoch_height(self.epoch_height)
+            .storage_usage(env::storage_usage())
+            .build();
+
+        near_sdk::testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            self.promise_results
+                .iter()
+                .map(|r| match r {
+                    Some(value) => PromiseResult::Successful(value.clone()),
+                    None => PromiseResult::Failed,
+                })
+                .collect(),
+        );
+
+        f()
+    }
+}
@@ -2,22 +2,89 @@
 //!
 //! Makes it easy to create and manage storage keys and avoid unnecessary
 //! writes to contract storage. This reduces transaction IO  and saves on gas.
+//!
+//! # Codecs
+//!
+//! Values are stored using [Borsh](Borsh) by default. Use
+//! [`Slot::with_codec`] to store a value in a different format, e.g. as
+//! [JSON](Json) so that it is readable by other tooling, or for types that do
+//! not implement Borsh. Custom formats can be added by implementing
+//! [`Encode`] and [`Decode`].
+//!
+//! ```
+//! use near_sdk_contract_tools::slot::{Json, Slot};
+//!
+//! let mut slot = Slot::new(b"config".to_vec()).with_codec::<Json>();
+//! slot.write(&vec!["a".to_string()]);
+//! assert_eq!(slot.read_raw().unwrap(), br#"["a"]"#);
+//!
+//! // Rewrite the stored value as Borsh.
+//! let slot = slot.recode::<near_sdk_contract_tools::slot::Borsh>();
+//! assert_eq!(slot.read(), Some(vec!["a".to_string()]));
+//! ```
 use std::marker::PhantomData;
 
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    env, IntoStorageKey,
+    env,
+    serde::{de::DeserializeOwned, Serialize},
+    serde_json, IntoStorageKey,
 };
 
 use crate::utils::prefix_key;
 
-/// A storage slot, composed of a storage location (key) and a data type
+/// Converts values of type `T` into bytes for storage.
+pub trait Encode<T> {
+    /// Serializes `value`.
+    fn encode(value: &T) -> Vec<u8>;
+}
+
+/// Converts stored bytes back into values of type `T`.
+pub trait Decode<T> {
+    /// Deserializes `bytes`. Panics if the bytes are not a valid encoding.
+    fn decode(bytes: &[u8]) -> T;
+}
+
+/// Borsh codec. The default.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Borsh;
+
+impl<T: BorshSerialize> Encode<T> for Borsh {
+    fn encode(value: &T) -> Vec<u8> {
+        value.try_to_vec().unwrap()
+    }
+}
+
+impl<T: BorshDeserialize> Decode<T> for Borsh {
+    fn decode(bytes: &[u8]) -> T {
+        T::try_from_slice(bytes).unwrap()
+    }
+}
+
+/// JSON codec, using `serde_json`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Json;
+
+impl<T: Serialize> Encode<T> for Json {
+    fn encode(value: &T) -> Vec<u8> {
+        serde_json::to_vec(value).unwrap()
+    }
+}
+
+impl<T: DeserializeOwned> Decode<T> for Json {
+    fn decode(bytes: &[u8]) -> T {
+        serde_json::from_slice(bytes).unwrap()
+    }
+}
+
+/// A storage slot, composed of a storage location (key), a data type, and
+/// the codec used to store values of that type.
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
-pub struct Slot<T> {
+pub struct Slot<T, C = Borsh> {
     /// The storage key this slot controls
     pub key: Vec<u8>,
     #[borsh_skip]
-    _marker: PhantomData<T>,
+    _marker: PhantomData<(T, C)>,
 }
 
 impl Slot<()> {
@@ -38,6 +105,18 @@ impl<T> Slot<T> {
             _marker: PhantomData,
         }
     }
+}
+
+impl<T, C> Slot<T, C> {
+    /// Creates a [`Slot`] that controls the same storage key, but encodes
+    /// values with codec `D`. Does not modify the stored value; see
+    /// [`Slot::recode`].
+    pub fn with_codec<D>(self) -> Slot<T, D> {
+        Slot {
+            key: self.key,
+            _marker: PhantomData,
+        }
+    }
 
     /// Creates a new [`Slot`] that controls the given key namespaced (prefixed)
     /// by the parent key, to be used as a namespace for another subfield.
@@ -64,7 +143,7 @@ impl<T> Slot<T> {
     ///
     /// If the data in the slot is not parsable into the new type, methods like
     /// [`Slot::read`] and [`Slot::take`] will panic.
-    pub fn transmute<U>(&self) -> Slot<U> {
+    pub fn transmute<U>(&self) -> Slot<U, C> {
         Slot {
             key: self.key.clone(),
             _marker: PhantomData,
@@ -73,6 +152,10 @@ impl<T> Slot<T> {
 
     /// Write raw bytes into the storage slot. No type checking.
     pub fn write_raw(&mut self, value: &[u8]) -> bool {
+        #[cfg(feature = "debug")]
+        crate::debug::register(&self.key);
+        crate::transaction::record(&self.key);
+
         env::storage_write(&self.key, value)
     }
 
@@ -89,14 +172,18 @@ impl<T> Slot<T> {
 
     /// Removes the managed key from storage
     pub fn remove(&mut self) -> bool {
+        #[cfg(feature = "debug")]
+        crate::debug::unregister(&self.key);
+        crate::transaction::record(&self.key);
+
         env::storage_remove(&self.key)
     }
 }
 
-impl<T: BorshSerialize> Slot<T> {
+impl<T, C: Encode<T>> Slot<T, C> {
     /// Writes a value to the managed storage slot
     pub fn write(&mut self, value: &T) -> bool {
-        self.write_raw(&value.try_to_vec().unwrap())
+        self.write_raw(&C::encode(value))
     }
 
     /// If the given value is `Some(T)`, writes `T` to storage. Otherwise,
@@ -109,50 +196,64 @@ impl<T: BorshSerialize> Slot<T> {
     }
 }
 
-impl<T: BorshDeserialize> Slot<T> {
+impl<T, C: Decode<T>> Slot<T, C> {
     /// Reads a value from storage, if present.
     pub fn read(&self) -> Option<T> {
-        self.read_raw().map(|v| T::try_from_slice(&v).unwrap())
+        self.read_raw().map(|v| C::decode(&v))
     }
 
     /// Removes a value from storage and returns it if present.
     pub fn take(&mut self) -> Option<T> {
         if self.remove() {
             // unwrap should be safe if remove returns true
-            Some(T::try_from_slice(&env::storage_get_evicted().unwrap()).unwrap())
+            Some(C::decode(&env::storage_get_evicted().unwrap()))
         } else {
             None
         }
     }
+
+    /// Rewrites the stored value, if present, using codec `D`, and returns
+    /// a slot that uses codec `D`. Useful for migrating a value from one
+    /// format to another.
+    pub fn recode<D: Encode<T>>(self) -> Slot<T, D> {
+        let value = self.read();
+        let mut slot = self.with_codec::<D>();
+        if let Some(value) = value {
+            slot.write(&value);
+        }
+        slot
+    }
 }
 
-impl<T: BorshSerialize + BorshDeserialize> Slot<T> {
+impl<T, C: Encode<T> + Decode<T>> Slot<T, C> {
     /// Writes a value to storage and returns the evicted value, if present.
     pub fn swap(&mut self, value: &T) -> Option<T> {
-        if self.write_raw(&value.try_to_vec().unwrap()) {
+        if self.write_raw(&C::encode(value)) {
             // unwrap should be safe because write_raw returned true
-            Some(T::try_from_slice(&env::storage_get_evicted().unwrap()).unwrap())
+            Some(C::decode(&env::storage_get_evicted().unwrap()))
         } else {
             None
         }
     }
 }
 
-impl<T> IntoStorageKey for Slot<T> {
+impl<T, C> IntoStorageKey for Slot<T, C> {
     fn into_storage_key(self) -> Vec<u8> {
         self.key
     }
 }
 
-impl<T, U> PartialEq<Slot<U>> for Slot<T> {
-    fn eq(&self, other: &Slot<U>) -> bool {
+impl<T, C, U, D> PartialEq<Slot<U, D>> for Slot<T, C> {
+    fn eq(&self, other: &Slot<U, D>) -> bool {
         self.key == other.key
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Slot;
+    use near_sdk::serde::{Deserialize, Serialize};
+
+    use super::{Json, Slot};
 
     #[test]
     fn partialeq() {
@@ -162,4 +263,33 @@ mod tests {
         let b = Slot::<u32>::new(b"b");
         assert_ne!(a1, b);
     }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(crate = "near_sdk::serde")]
+    struct NoBorsh {
+        value: u32,
+    }
+
+    #[test]
+    fn json_codec() {
+        let mut slot = Slot::new(b"j").with_codec::<Json>();
+        assert!(slot.read().is_none());
+        slot.write(&NoBorsh { value: 1 });
+        assert_eq!(slot.read_raw().unwrap(), br#"{"value":1}"#);
+        assert_eq!(slot.swap(&NoBorsh { value: 2 }), Some(NoBorsh { value: 1 }));
+        assert_eq!(slot.take(), Some(NoBorsh { value: 2 }));
+    }
+
+    #[test]
+    fn recode() {
+        let mut slot = Slot::<u32>::new(b"r");
+        slot.write(&7);
+
+        let json = slot.recode::<Json>();
+        assert_eq!(json.read_raw().unwrap(), b"7");
+        assert_eq!(json.read(), Some(7));
+
+        let borsh = json.recode::<super::Borsh>();
+        assert_eq!(borsh.read_raw().unwrap(), 7u32.to_le_bytes());
+    }
 }
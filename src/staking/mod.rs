@@ -0,0 +1,9 @@
+//! Staking of tokens held in the contract's own ledgers.
+//!
+//! A staked token stays with its owner, but is locked: it cannot be
+//! transferred or burned until it is unstaked and a cooldown has passed.
+//! While it is staked, it accrues rewards.
+//!
+//! - [`nft`]: staking of NEP-171 non-fungible tokens, e.g. game items.
+
+pub mod nft;
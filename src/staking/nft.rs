@@ -0,0 +1,546 @@
+//! Staking of NEP-171 non-fungible tokens.
+//!
+//! The owner of a token stakes it with [`NftStaking::stake`]. The token stays
+//! with its owner, but [`NftStakingHook`] rejects transfers and burns of it.
+//! The staked token accrues [`NftStakingInternal::reward_per_epoch`] for
+//! every full epoch of [`NftStakingInternal::EPOCH`] nanoseconds.
+//! [`NftStaking::claim_rewards`] credits the accrued rewards to the owner's
+//! reward balance, and the contract pays out the balance as it sees fit
+//! (e.g. by minting fungible tokens) with [`NftStaking::take_rewards`].
+//!
+//! [`NftStaking::unstake`] stops accruing and credits the accrued rewards.
+//! The token stays locked for [`NftStakingInternal::COOLDOWN`] nanoseconds
+//! afterwards.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::NftStaking) derives a
+//! default implementation, and exposes `nft_stake`, `nft_unstake`,
+//! `nft_claim_staking_rewards`, and views.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::near_bindgen;
+//! use near_sdk_contract_tools::{nft::*, staking::nft::*, NftStaking};
+//!
+//! const DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+//!
+//! #[derive(NonFungibleToken, NftStaking)]
+//! #[non_fungible_token(extra = "NftStakingHook")]
+//! #[nft_staking(epoch = "DAY", reward_per_epoch = "10", cooldown = "2 * DAY")]
+//! #[near_bindgen]
+//! struct Contract {}
+//! ```
+//!
+//! With `NonFungibleToken`, `extra = "NftStakingHook"` also adds the stake of
+//! a token to `nft_token` as the `staking` field. With `Nep171`, use
+//! `#[nep171(transfer_hook = "NftStakingHook", burn_hook = "NftStakingHook")]`.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The NFT staking root storage slot is not used or modified. The
+//!     default key is `~nstk`.
+//! * (UB) [`NftStakingHook`] is a transfer and burn hook of the contract.
+//!     Otherwise, staked tokens can be transferred, and
+//!     [`Nep171Controller::can_transfer`] does not report staked tokens,
+//!     because it does not run hooks.
+//! * (ERR) Only the owner of a token may stake it, and only when it is not
+//!     locked.
+//! * (ERR) Only the account that staked a token may unstake it or claim its
+//!     rewards.
+//! * (ERR) A locked token may not be transferred or burned.
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use thiserror::Error;
+
+use crate::{
+    event,
+    hook::Hook,
+    slot::Slot,
+    standard::{
+        nep171::{
+            action::{Nep171Burn, Nep171Mint, Nep171TransferAction},
+            error::{TokenDoesNotExistError, TokenNotOwnedByExpectedOwnerError},
+            LoadTokenMetadata, Nep171Controller, TokenFields, TokenId,
+        },
+        nep297::Event,
+    },
+    DefaultStorageKey,
+};
+
+pub use ext::*;
+
+/// Default length of an epoch: 1 day, in nanoseconds.
+pub const DEFAULT_STAKING_EPOCH: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Default time a token stays locked after it is unstaked: 1 day, in
+/// nanoseconds.
+pub const DEFAULT_UNSTAKE_COOLDOWN: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// The stake of a token.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakeInfo {
+    /// Account that staked the token.
+    pub owner_id: AccountId,
+    /// Timestamp of the stake, in nanoseconds.
+    pub staked_at: U64,
+    /// Start of the first epoch whose rewards have not been claimed, in
+    /// nanoseconds.
+    pub rewards_from: U64,
+    /// Timestamp of the unstake, in nanoseconds, if the token is cooling
+    /// down.
+    pub unstaked_at: Option<U64>,
+}
+
+/// A token was staked.
+#[event(
+    standard = "x-nft-staking",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct NftStaked {
+    /// Account that staked the token.
+    pub owner_id: AccountId,
+    /// The staked token.
+    pub token_id: TokenId,
+}
+
+/// A token was unstaked.
+#[event(
+    standard = "x-nft-staking",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct NftUnstaked {
+    /// Account that staked the token.
+    pub owner_id: AccountId,
+    /// The unstaked token.
+    pub token_id: TokenId,
+    /// Timestamp from which the token may be transferred, in nanoseconds.
+    pub available_at: U64,
+}
+
+/// Rewards of a token were credited to its owner's reward balance.
+#[event(
+    standard = "x-nft-staking",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "crate"
+)]
+pub struct StakingRewardsClaimed {
+    /// Account that staked the token.
+    pub owner_id: AccountId,
+    /// The staked token.
+    pub token_id: TokenId,
+    /// Amount credited.
+    pub amount: U128,
+}
+
+/// The token is staked, or cooling down after an unstake.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Token `{token_id}` is staked{}", .available_at.map(|t| format!(" until {t}")).unwrap_or_default())]
+pub struct TokenLockedError {
+    /// The locked token.
+    pub token_id: TokenId,
+    /// Timestamp from which the token may be transferred, in nanoseconds, if
+    /// it has been unstaked.
+    pub available_at: Option<u64>,
+}
+
+/// Errors that may occur when staking, unstaking, or claiming rewards.
+#[derive(Error, Clone, Debug)]
+pub enum NftStakingError {
+    /// The token does not exist.
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The account does not own the token.
+    #[error(transparent)]
+    NotOwner(#[from] TokenNotOwnedByExpectedOwnerError),
+    /// The token is already staked, or cooling down.
+    #[error(transparent)]
+    Locked(#[from] TokenLockedError),
+    /// The token is not staked.
+    #[error("Token `{0}` is not staked")]
+    NotStaked(TokenId),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Stake(&'a TokenId),
+    RewardBalance(&'a AccountId),
+}
+
+/// Internal functions for [`NftStaking`]. Using these methods may result in
+/// unexpected behavior.
+pub trait NftStakingInternal {
+    /// Length of an epoch, in nanoseconds.
+    const EPOCH: u64 = DEFAULT_STAKING_EPOCH;
+
+    /// Rewards accrued by a staked token per epoch.
+    const REWARD_PER_EPOCH: u128 = 0;
+
+    /// Time a token stays locked after it is unstaked, in nanoseconds.
+    const COOLDOWN: u64 = DEFAULT_UNSTAKE_COOLDOWN;
+
+    /// Rewards accrued by `token_id` per epoch. Override to reward tokens
+    /// differently, e.g. by rarity.
+    fn reward_per_epoch(token_id: &TokenId) -> u128 {
+        let _ = token_id;
+        Self::REWARD_PER_EPOCH
+    }
+
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::NftStaking)
+    }
+
+    /// Storage slot for the stake of a token
+    fn slot_stake(token_id: &TokenId) -> Slot<StakeInfo> {
+        Self::root().field(StorageKey::Stake(token_id))
+    }
+
+    /// Storage slot for the reward balance of an account
+    fn slot_reward_balance(account_id: &AccountId) -> Slot<u128> {
+        Self::root().field(StorageKey::RewardBalance(account_id))
+    }
+}
+
+/// Stakes non-fungible tokens for rewards.
+pub trait NftStaking {
+    /// Returns the stake of `token_id`, if it is staked or cooling down.
+    fn stake_info(token_id: &TokenId) -> Option<StakeInfo>;
+
+    /// Checks that `token_id` is neither staked nor cooling down.
+    fn check_unlocked(token_id: &TokenId) -> Result<(), TokenLockedError>;
+
+    /// Returns `true` if `token_id` is staked or cooling down.
+    fn is_locked(token_id: &TokenId) -> bool {
+        Self::check_unlocked(token_id).is_err()
+    }
+
+    /// Returns the rewards accrued by `token_id` that have not been claimed.
+    fn pending_rewards(token_id: &TokenId) -> u128;
+
+    /// Returns the claimed rewards of `account_id` that have not been taken.
+    fn reward_balance(account_id: &AccountId) -> u128;
+
+    /// Stakes `token_id`, which `owner_id` owns. Emits [`NftStaked`].
+    fn stake(&mut self, owner_id: &AccountId, token_id: &TokenId) -> Result<(), NftStakingError>;
+
+    /// Credits the rewards accrued by `token_id`, which `owner_id` staked,
+    /// to the reward balance of `owner_id`. Returns the amount credited.
+    /// Emits [`StakingRewardsClaimed`] if it is nonzero.
+    fn claim_rewards(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+    ) -> Result<u128, NftStakingError>;
+
+    /// Unstakes `token_id`, which `owner_id` staked, and claims its rewards.
+    /// Returns the timestamp from which the token may be transferred. Emits
+    /// [`NftUnstaked`].
+    fn unstake(&mut self, owner_id: &AccountId, token_id: &TokenId)
+        -> Result<u64, NftStakingError>;
+
+    /// Empties the reward balance of `account_id`, to be paid out by the
+    /// contract. Returns the balance.
+    fn take_rewards(&mut self, account_id: &AccountId) -> u128;
+}
+
+/// The timestamp until which a token with `stake` is locked, if it is
+/// cooling down, or `None` if it is staked.
+fn locked_until<T: NftStakingInternal>(stake: &StakeInfo) -> Option<u64> {
+    stake
+        .unstaked_at
+        .map(|unstaked_at| unstaked_at.0.saturating_add(T::COOLDOWN))
+}
+
+/// Reads the stake of a token that is staked or cooling down, ignoring stakes
+/// whose cooldown has passed.
+fn read_stake<T: NftStakingInternal>(token_id: &TokenId) -> Option<StakeInfo> {
+    T::slot_stake(token_id)
+        .read()
+        .filter(|stake| match locked_until::<T>(stake) {
+            Some(until) => env::block_timestamp() < until,
+            None => true,
+        })
+}
+
+/// Reads the stake of a token that `owner_id` staked.
+fn read_owned_stake<T: NftStakingInternal>(
+    owner_id: &AccountId,
+    token_id: &TokenId,
+) -> Result<StakeInfo, NftStakingError> {
+    let stake =
+        read_stake::<T>(token_id).ok_or_else(|| NftStakingError::NotStaked(token_id.clone()))?;
+
+    if &stake.owner_id != owner_id {
+        return Err(TokenNotOwnedByExpectedOwnerError {
+            expected_owner_id: owner_id.clone(),
+            owner_id: stake.owner_id,
+            token_id: token_id.clone(),
+        }
+        .into());
+    }
+
+    Ok(stake)
+}
+
+/// Number of full epochs accrued by `stake`.
+fn accrued_epochs<T: NftStakingInternal>(stake: &StakeInfo) -> u64 {
+    let end = stake
+        .unstaked_at
+        .map_or_else(env::block_timestamp, |unstaked_at| unstaked_at.0);
+    end.saturating_sub(stake.rewards_from.0)
+        .checked_div(T::EPOCH)
+        .unwrap_or(0)
+}
+
+/// Credits the rewards accrued by `stake` to the reward balance of its owner,
+/// and moves `stake.rewards_from` past the credited epochs. Returns the
+/// amount credited.
+fn settle_rewards<T: NftStakingInternal>(token_id: &TokenId, stake: &mut StakeInfo) -> u128 {
+    let epochs = accrued_epochs::<T>(stake);
+    let amount = u128::from(epochs).saturating_mul(T::reward_per_epoch(token_id));
+
+    stake.rewards_from = stake
+        .rewards_from
+        .0
+        .saturating_add(epochs.saturating_mul(T::EPOCH))
+        .into();
+
+    if amount > 0 {
+        let mut balance = T::slot_reward_balance(&stake.owner_id);
+        balance.write(&balance.read().unwrap_or(0).saturating_add(amount));
+
+        StakingRewardsClaimed {
+            owner_id: stake.owner_id.clone(),
+            token_id: token_id.clone(),
+            amount: amount.into(),
+        }
+        .emit();
+    }
+
+    amount
+}
+
+impl<T: NftStakingInternal + Nep171Controller> NftStaking for T {
+    fn stake_info(token_id: &TokenId) -> Option<StakeInfo> {
+        read_stake::<Self>(token_id)
+    }
+
+    fn check_unlocked(token_id: &TokenId) -> Result<(), TokenLockedError> {
+        match read_stake::<Self>(token_id) {
+            Some(stake) => Err(TokenLockedError {
+                token_id: token_id.clone(),
+                available_at: locked_until::<Self>(&stake),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn pending_rewards(token_id: &TokenId) -> u128 {
+        read_stake::<Self>(token_id).map_or(0, |stake| {
+            u128::from(accrued_epochs::<Self>(&stake))
+                .saturating_mul(Self::reward_per_epoch(token_id))
+        })
+    }
+
+    fn reward_balance(account_id: &AccountId) -> u128 {
+        Self::slot_reward_balance(account_id).read().unwrap_or(0)
+    }
+
+    fn stake(&mut self, owner_id: &AccountId, token_id: &TokenId) -> Result<(), NftStakingError> {
+        let actual_owner_id = self
+            .token_owner(token_id)
+            .ok_or_else(|| TokenDoesNotExistError {
+                token_id: token_id.clone(),
+            })?;
+
+        if &actual_owner_id != owner_id {
+            return Err(TokenNotOwnedByExpectedOwnerError {
+                expected_owner_id: owner_id.clone(),
+                owner_id: actual_owner_id,
+                token_id: token_id.clone(),
+            }
+            .into());
+        }
+
+        Self::check_unlocked(token_id)?;
+
+        let now = env::block_timestamp().into();
+        Self::slot_stake(token_id).write(&StakeInfo {
+            owner_id: owner_id.clone(),
+            staked_at: now,
+            rewards_from: now,
+            unstaked_at: None,
+        });
+
+        NftStaked {
+            owner_id: owner_id.clone(),
+            token_id: token_id.clone(),
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn claim_rewards(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+    ) -> Result<u128, NftStakingError> {
+        let mut stake = read_owned_stake::<Self>(owner_id, token_id)?;
+        let amount = settle_rewards::<Self>(token_id, &mut stake);
+        Self::slot_stake(token_id).write(&stake);
+
+        Ok(amount)
+    }
+
+    fn unstake(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+    ) -> Result<u64, NftStakingError> {
+        let mut stake = read_owned_stake::<Self>(owner_id, token_id)?;
+        if stake.unstaked_at.is_some() {
+            return Err(NftStakingError::NotStaked(token_id.clone()));
+        }
+
+        settle_rewards::<Self>(token_id, &mut stake);
+        let now = env::block_timestamp();
+        stake.unstaked_at = Some(now.into());
+        Self::slot_stake(token_id).write(&stake);
+
+        let available_at = now.saturating_add(Self::COOLDOWN);
+
+        NftUnstaked {
+            owner_id: owner_id.clone(),
+            token_id: token_id.clone(),
+            available_at: available_at.into(),
+        }
+        .emit();
+
+        Ok(available_at)
+    }
+
+    fn take_rewards(&mut self, account_id: &AccountId) -> u128 {
+        Self::slot_reward_balance(account_id).take().unwrap_or(0)
+    }
+}
+
+/// Rejects transfers and burns of locked tokens, and adds the stake of a
+/// token to `nft_token` as the `staking` field.
+pub struct NftStakingHook;
+
+impl<C: NftStaking> Hook<C, Nep171Mint<'_>> for NftStakingHook {}
+
+impl<C, T> Hook<C, T> for NftStakingHook
+where
+    C: NftStaking + NftStakingInternal,
+    T: Nep171TransferAction,
+{
+    fn hook<R>(contract: &mut C, transfer: &T, f: impl FnOnce(&mut C) -> R) -> R {
+        C::check_unlocked(transfer.token_id()).unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        let r = f(contract);
+        // Remove the stake whose cooldown has passed, if any.
+        C::slot_stake(transfer.token_id()).remove();
+        r
+    }
+}
+
+impl<C> Hook<C, Nep171Burn<'_>> for NftStakingHook
+where
+    C: NftStaking + NftStakingInternal,
+{
+    fn hook<R>(contract: &mut C, burn: &Nep171Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        for token_id in burn.token_ids {
+            C::check_unlocked(token_id).unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        }
+        let r = f(contract);
+        for token_id in burn.token_ids {
+            C::slot_stake(token_id).remove();
+        }
+        r
+    }
+}
+
+impl<C: NftStaking> LoadTokenMetadata<C> for NftStakingHook {
+    fn load(
+        _contract: &C,
+        token_id: &TokenId,
+        metadata: &mut std::collections::HashMap<String, near_sdk::serde_json::Value>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        metadata.insert(
+            "staking".to_string(),
+            near_sdk::serde_json::to_value(C::stake_info(token_id))?,
+        );
+        Ok(())
+    }
+
+    fn load_with(
+        contract: &C,
+        token_id: &TokenId,
+        metadata: &mut std::collections::HashMap<String, near_sdk::serde_json::Value>,
+        fields: TokenFields<'_>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if fields.includes("staking") {
+            Self::load(contract, token_id, metadata)?;
+        }
+        Ok(())
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{
+        ext_contract,
+        json_types::{U128, U64},
+        AccountId,
+    };
+
+    use super::StakeInfo;
+    use crate::standard::nep171::TokenId;
+
+    /// External (public) methods for [`NftStaking`](super::NftStaking)
+    #[ext_contract(ext_nft_staking)]
+    pub trait NftStakingExternal {
+        /// Stakes a token owned by the predecessor. Requires exactly 1
+        /// yoctoNEAR.
+        fn nft_stake(&mut self, token_id: TokenId);
+
+        /// Unstakes a token staked by the predecessor, and claims its
+        /// rewards. Returns the timestamp from which the token may be
+        /// transferred. Requires exactly 1 yoctoNEAR.
+        fn nft_unstake(&mut self, token_id: TokenId) -> U64;
+
+        /// Credits the rewards accrued by a token staked by the predecessor
+        /// to its reward balance. Returns the amount credited. Requires
+        /// exactly 1 yoctoNEAR.
+        fn nft_claim_staking_rewards(&mut self, token_id: TokenId) -> U128;
+
+        /// Returns the stake of a token, if it is staked or cooling down.
+        fn nft_stake_info(&self, token_id: TokenId) -> Option<StakeInfo>;
+
+        /// Returns the rewards accrued by a token that have not been
+        /// claimed.
+        fn nft_pending_staking_rewards(&self, token_id: TokenId) -> U128;
+
+        /// Returns the claimed rewards of an account that have not been paid
+        /// out.
+        fn nft_staking_reward_balance(&self, account_id: AccountId) -> U128;
+    }
+}
@@ -1,10 +1,19 @@
 //! Implementations of NEP standards.
 
+#[cfg(feature = "nep141")]
 pub mod nep141;
+#[cfg(feature = "nep145")]
 pub mod nep145;
+#[cfg(feature = "nep148")]
 pub mod nep148;
+#[cfg(feature = "nep171")]
 pub mod nep171;
+#[cfg(feature = "nep177")]
 pub mod nep177;
+#[cfg(feature = "nep178")]
 pub mod nep178;
+#[cfg(feature = "nep181")]
 pub mod nep181;
+#[cfg(feature = "nep199")]
+pub mod nep199;
 pub mod nep297;
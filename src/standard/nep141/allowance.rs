@@ -0,0 +1,62 @@
+//! Allowance views for NEP-141 fungible tokens.
+//!
+//! NEP-141 has no notion of allowances. Contracts that let accounts spend on
+//! behalf of others (e.g. using a custom transfer hook) can expose their
+//! allowances to wallets in a uniform way by implementing
+//! [`Nep141Allowances`] and enabling `#[nep141(allowance_views)]`, which
+//! generates an implementation of [`Nep141AllowanceViews`].
+
+use near_sdk::{
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+pub use ext::*;
+
+/// Number of allowances returned by `ft_allowances_for` if no limit is
+/// specified.
+pub const DEFAULT_ALLOWANCES_LIMIT: u32 = 50;
+
+/// Amount a spender is allowed to spend on behalf of an owner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Allowance {
+    /// Account allowed to spend.
+    pub spender_id: AccountId,
+    /// Remaining amount the spender may spend.
+    pub amount: U128,
+}
+
+/// Source of allowance data, implemented by the contract.
+pub trait Nep141Allowances {
+    /// Returns the amount `spender_id` may spend on behalf of `owner_id`.
+    fn allowance(&self, owner_id: &AccountId, spender_id: &AccountId) -> u128;
+
+    /// Returns up to `limit` allowances granted by `owner_id`, skipping the
+    /// first `offset`. The order must be stable between calls.
+    fn allowances_for(&self, owner_id: &AccountId, offset: u32, limit: u32) -> Vec<Allowance>;
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U128, AccountId};
+
+    use super::Allowance;
+
+    /// Non-standard allowance views for wallets.
+    #[ext_contract(ext_nep141_allowance_views)]
+    pub trait Nep141AllowanceViews {
+        /// Returns the amount `spender_id` may spend on behalf of `owner_id`.
+        fn ft_allowance(&self, owner_id: AccountId, spender_id: AccountId) -> U128;
+
+        /// Returns the allowances granted by `owner_id`, paginated.
+        fn ft_allowances_for(
+            &self,
+            owner_id: AccountId,
+            offset: Option<u32>,
+            limit: Option<u32>,
+        ) -> Vec<Allowance>;
+    }
+}
@@ -0,0 +1,319 @@
+//! Bridge extension for NEP-141 fungible tokens.
+//!
+//! A designated bridge account (or role) mints tokens when they are locked on
+//! a foreign chain, and burns them when they are released there. Every bridge
+//! operation records the hash of the corresponding foreign chain transaction
+//! in an event, and is subject to per-epoch volume caps.
+//!
+//! Access control and pausing are left to the implementing contract; the
+//! [derive macro](near_sdk_contract_tools_macros::Nep141Bridge) generates
+//! external methods restricted to an RBAC role, and optionally guarded by a
+//! `Pause` implementation.
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+use thiserror::Error;
+
+use crate::{hook::ActionOrigin, slot::Slot, standard::nep297::Event, DefaultStorageKey};
+
+use super::{DepositError, Nep141Burn, Nep141Controller, Nep141Mint, WithdrawError};
+
+pub use ext::*;
+
+/// Events emitted by bridge operations, in addition to the regular NEP-141
+/// `ft_mint` and `ft_burn` events.
+#[event(
+    standard = "x-nep141-bridge",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum Nep141BridgeEvent {
+    /// Tokens were minted for a transaction on the foreign chain.
+    BridgeMint {
+        /// Account that received the tokens.
+        receiver_id: AccountId,
+        /// Amount minted.
+        amount: U128,
+        /// Hash of the foreign chain transaction.
+        foreign_tx_hash: String,
+    },
+    /// Tokens were burned for a transaction on the foreign chain.
+    BridgeBurn {
+        /// Account the tokens were burned from.
+        owner_id: AccountId,
+        /// Amount burned.
+        amount: U128,
+        /// Hash of the foreign chain transaction.
+        foreign_tx_hash: String,
+    },
+    /// The per-epoch volume caps changed.
+    CapsUpdate(BridgeCaps),
+}
+
+/// Per-epoch volume caps. `None` means uncapped.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BridgeCaps {
+    /// Maximum amount that may be minted per epoch.
+    pub mint: Option<U128>,
+    /// Maximum amount that may be burned per epoch.
+    pub burn: Option<U128>,
+}
+
+/// Volume bridged during an epoch.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EpochVolume {
+    /// Epoch height.
+    pub epoch_height: u64,
+    /// Amount minted during the epoch.
+    pub minted: U128,
+    /// Amount burned during the epoch.
+    pub burned: U128,
+}
+
+/// Describes a bridge mint operation.
+#[derive(Clone, Debug, Serialize, BorshSerialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Nep141BridgeMint<'a> {
+    /// Amount to mint.
+    pub amount: u128,
+    /// Account ID to mint to.
+    pub receiver_id: &'a AccountId,
+    /// Hash of the foreign chain transaction.
+    pub foreign_tx_hash: &'a str,
+    /// Optional memo string.
+    pub memo: Option<&'a str>,
+}
+
+/// Describes a bridge burn operation.
+#[derive(Clone, Debug, Serialize, BorshSerialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Nep141BridgeBurn<'a> {
+    /// Amount to burn.
+    pub amount: u128,
+    /// Account ID to burn from.
+    pub owner_id: &'a AccountId,
+    /// Hash of the foreign chain transaction.
+    pub foreign_tx_hash: &'a str,
+    /// Optional memo string.
+    pub memo: Option<&'a str>,
+}
+
+/// The operation would exceed the per-epoch volume cap.
+#[derive(Debug, Error)]
+#[error("Bridge volume cap exceeded: {volume} already bridged this epoch, cap {cap}, requested {amount}.")]
+pub struct CapExceededError {
+    /// Volume already bridged during the current epoch.
+    pub volume: u128,
+    /// Per-epoch cap.
+    pub cap: u128,
+    /// Amount of the failed operation.
+    pub amount: u128,
+}
+
+/// Errors that may occur during a bridge mint.
+#[derive(Debug, Error)]
+pub enum BridgeMintError {
+    /// The mint would exceed the per-epoch cap.
+    #[error(transparent)]
+    CapExceeded(#[from] CapExceededError),
+    /// The mint failed.
+    #[error(transparent)]
+    Deposit(#[from] DepositError),
+}
+
+/// Errors that may occur during a bridge burn.
+#[derive(Debug, Error)]
+pub enum BridgeBurnError {
+    /// The burn would exceed the per-epoch cap.
+    #[error(transparent)]
+    CapExceeded(#[from] CapExceededError),
+    /// The burn failed.
+    #[error(transparent)]
+    Withdraw(#[from] WithdrawError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Caps,
+    EpochVolume,
+}
+
+/// Internal functions for [`Nep141BridgeController`]. Using these methods may
+/// result in unexpected behavior.
+pub trait Nep141BridgeControllerInternal {
+    /// Root storage slot.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep141Bridge)
+    }
+
+    /// Slot for the per-epoch volume caps.
+    fn slot_caps() -> Slot<BridgeCaps> {
+        Self::root().field(StorageKey::Caps)
+    }
+
+    /// Slot for the volume bridged during the most recent epoch with bridge
+    /// activity.
+    fn slot_epoch_volume() -> Slot<EpochVolume> {
+        Self::root().field(StorageKey::EpochVolume)
+    }
+}
+
+/// Non-public functions for bridging a fungible token.
+pub trait Nep141BridgeController {
+    /// Returns the per-epoch volume caps.
+    fn bridge_caps(&self) -> BridgeCaps;
+
+    /// Sets the per-epoch volume caps. Performs no access checks.
+    ///
+    /// Emits a `Nep141BridgeEvent::CapsUpdate` event.
+    fn set_bridge_caps(&mut self, caps: BridgeCaps);
+
+    /// Returns the volume bridged during the current epoch.
+    fn bridge_epoch_volume(&self) -> EpochVolume;
+
+    /// Mints tokens for a foreign chain transaction, if the per-epoch mint
+    /// cap allows it. Performs no access checks.
+    ///
+    /// Emits `ft_mint` and `Nep141BridgeEvent::BridgeMint` events.
+    fn bridge_mint(&mut self, mint: &Nep141BridgeMint<'_>) -> Result<(), BridgeMintError>;
+
+    /// Burns tokens for a foreign chain transaction, if the per-epoch burn
+    /// cap allows it. Performs no access checks.
+    ///
+    /// Emits `ft_burn` and `Nep141BridgeEvent::BridgeBurn` events.
+    fn bridge_burn(&mut self, burn: &Nep141BridgeBurn<'_>) -> Result<(), BridgeBurnError>;
+}
+
+fn check_cap(volume: u128, cap: Option<U128>, amount: u128) -> Result<u128, CapExceededError> {
+    let new_volume = volume.saturating_add(amount);
+
+    match cap {
+        Some(U128(cap)) if new_volume > cap => Err(CapExceededError {
+            volume,
+            cap,
+            amount,
+        }),
+        _ => Ok(new_volume),
+    }
+}
+
+impl<T: Nep141BridgeControllerInternal + Nep141Controller> Nep141BridgeController for T {
+    fn bridge_caps(&self) -> BridgeCaps {
+        Self::slot_caps().read().unwrap_or_default()
+    }
+
+    fn set_bridge_caps(&mut self, caps: BridgeCaps) {
+        Self::slot_caps().write(&caps);
+
+        Nep141BridgeEvent::CapsUpdate(caps).emit();
+    }
+
+    fn bridge_epoch_volume(&self) -> EpochVolume {
+        let epoch_height = env::epoch_height();
+
+        Self::slot_epoch_volume()
+            .read()
+            .filter(|v| v.epoch_height == epoch_height)
+            .unwrap_or(EpochVolume {
+                epoch_height,
+                minted: U128(0),
+                burned: U128(0),
+            })
+    }
+
+    fn bridge_mint(&mut self, mint: &Nep141BridgeMint<'_>) -> Result<(), BridgeMintError> {
+        let mut volume = self.bridge_epoch_volume();
+        volume.minted = check_cap(volume.minted.0, self.bridge_caps().mint, mint.amount)?.into();
+
+        self.mint(&Nep141Mint {
+            amount: mint.amount,
+            receiver_id: mint.receiver_id,
+            memo: mint.memo,
+            origin: ActionOrigin::Internal,
+        })?;
+
+        Self::slot_epoch_volume().write(&volume);
+
+        Nep141BridgeEvent::BridgeMint {
+            receiver_id: mint.receiver_id.clone(),
+            amount: mint.amount.into(),
+            foreign_tx_hash: mint.foreign_tx_hash.to_string(),
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn bridge_burn(&mut self, burn: &Nep141BridgeBurn<'_>) -> Result<(), BridgeBurnError> {
+        let mut volume = self.bridge_epoch_volume();
+        volume.burned = check_cap(volume.burned.0, self.bridge_caps().burn, burn.amount)?.into();
+
+        self.burn(&Nep141Burn {
+            amount: burn.amount,
+            owner_id: burn.owner_id,
+            memo: burn.memo,
+            origin: ActionOrigin::Internal,
+        })?;
+
+        Self::slot_epoch_volume().write(&volume);
+
+        Nep141BridgeEvent::BridgeBurn {
+            owner_id: burn.owner_id.clone(),
+            amount: burn.amount.into(),
+            foreign_tx_hash: burn.foreign_tx_hash.to_string(),
+        }
+        .emit();
+
+        Ok(())
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U128, AccountId};
+
+    use super::{BridgeCaps, EpochVolume};
+
+    /// Externally-accessible functions for bridging a fungible token.
+    #[ext_contract(ext_nep141_bridge)]
+    pub trait Nep141BridgeExternal {
+        /// Mints tokens for a foreign chain transaction. Can only be called
+        /// by the bridge.
+        fn ft_bridge_mint(
+            &mut self,
+            receiver_id: AccountId,
+            amount: U128,
+            foreign_tx_hash: String,
+            memo: Option<String>,
+        );
+
+        /// Burns tokens for a foreign chain transaction. Can only be called
+        /// by the bridge.
+        fn ft_bridge_burn(
+            &mut self,
+            owner_id: AccountId,
+            amount: U128,
+            foreign_tx_hash: String,
+            memo: Option<String>,
+        );
+
+        /// Returns the per-epoch volume caps.
+        fn ft_bridge_caps(&self) -> BridgeCaps;
+
+        /// Returns the volume bridged during the current epoch.
+        fn ft_bridge_epoch_volume(&self) -> EpochVolume;
+    }
+}
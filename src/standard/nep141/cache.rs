@@ -0,0 +1,94 @@
+//! Balance cache scoped to a single NEP-141 operation.
+//!
+//! A transfer and its hooks may read the sender's and receiver's balances
+//! several times. While a cache scope is active (see [`with_balance_cache`]),
+//! [`Nep141Controller`](super::Nep141Controller) reads each balance from
+//! storage at most once, and writes a balance only when it changes.
+//! [`Nep141Controller::transfer`](super::Nep141Controller::transfer),
+//! [`mint`](super::Nep141Controller::mint), and
+//! [`burn`](super::Nep141Controller::burn) each run inside a scope, so hooks
+//! share it.
+//!
+//! Writes go to storage immediately, so hooks that measure storage usage
+//! (e.g. NEP-145 storage accounting) still see them. Balances written
+//! directly to [`Nep141ControllerInternal::slot_account`] inside a scope are
+//! not seen by cached reads.
+//!
+//! [`Nep141ControllerInternal::slot_account`]: super::Nep141ControllerInternal::slot_account
+
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::slot::Slot;
+
+thread_local! {
+    static CACHE: RefCell<Option<HashMap<Vec<u8>, u128>>> = const { RefCell::new(None) };
+}
+
+/// Clears the cache when the outermost scope ends, even if it panics.
+struct ScopeGuard;
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        CACHE.with(|cache| *cache.borrow_mut() = None);
+    }
+}
+
+/// Runs `f` with the balance cache active. Nested scopes share the cache of
+/// the outermost scope.
+pub fn with_balance_cache<R>(f: impl FnOnce() -> R) -> R {
+    let is_outermost = CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let is_outermost = cache.is_none();
+        cache.get_or_insert_with(HashMap::new);
+        is_outermost
+    });
+
+    let _guard = is_outermost.then_some(ScopeGuard);
+
+    f()
+}
+
+/// Returns whether a cache scope is active.
+pub fn is_balance_cache_active() -> bool {
+    CACHE.with(|cache| cache.borrow().is_some())
+}
+
+/// Forgets the cached balances, e.g. after a
+/// [`Transaction`](crate::transaction::Transaction) restored them.
+pub(crate) fn clear() {
+    CACHE.with(|cache| {
+        if let Some(cache) = cache.borrow_mut().as_mut() {
+            cache.clear();
+        }
+    });
+}
+
+pub(crate) fn read(slot: &Slot<u128>) -> u128 {
+    CACHE.with(|cache| match cache.borrow_mut().as_mut() {
+        Some(cache) => *cache
+            .entry(slot.key.clone())
+            .or_insert_with(|| slot.read().unwrap_or(0)),
+        None => slot.read().unwrap_or(0),
+    })
+}
+
+pub(crate) fn write(slot: &mut Slot<u128>, balance: u128) {
+    let changed = CACHE.with(|cache| match cache.borrow_mut().as_mut() {
+        Some(cache) => cache.insert(slot.key.clone(), balance) != Some(balance),
+        None => true,
+    });
+
+    if changed {
+        slot.write(&balance);
+    }
+}
+
+pub(crate) fn remove(slot: &mut Slot<u128>) {
+    CACHE.with(|cache| {
+        if let Some(cache) = cache.borrow_mut().as_mut() {
+            cache.insert(slot.key.clone(), 0);
+        }
+    });
+
+    slot.remove();
+}
@@ -0,0 +1,396 @@
+//! Merkle commitments over NEP-141 balances, for light clients and other
+//! chains.
+//!
+//! Each account that has held a balance is assigned a leaf of a binary
+//! Merkle tree of fixed depth, in the order of first appearance. The root of
+//! the tree commits to the balances of all of these accounts, and a
+//! [`BalanceProof`] (the siblings of the account's leaf) proves a balance
+//! against the root. The commitment is stored in contract state, so a NEAR
+//! light client verifies it with a state proof against a block signed by
+//! the validators, and then verifies any number of balances against it
+//! without further state proofs.
+//!
+//! The tree is maintained incrementally: [`Nep141CommitmentHook`] updates
+//! the leaves of the accounts involved in each mint, transfer, and burn,
+//! which costs `2 * depth` storage operations per account. The commitment
+//! records the block height of the last update, so clients can use the
+//! commitment as of the end of a block.
+//!
+//! Enable with `#[nep141(balance_commitment)]`, which adds the hook to the
+//! mint, transfer, and burn hooks, and generates an implementation of
+//! [`Nep141CommitmentExternal`]. Balances held before the commitment was
+//! enabled are committed with [`Nep141Commitment::commit_balances`].
+//!
+//! # Hashing
+//!
+//! All hashes are SHA-256:
+//!
+//! * A leaf is `sha256(0x00 || borsh(account_id) || balance)`, where
+//!     `borsh(account_id)` is the length of the account ID as a 32-bit
+//!     little-endian integer followed by its bytes, and `balance` is a
+//!     128-bit little-endian integer.
+//! * An inner node is `sha256(0x01 || left || right)`.
+//! * A leaf that has not been assigned is 32 zero bytes.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::near_bindgen;
+//! use near_sdk_contract_tools::{
+//!     hook::ActionOrigin,
+//!     standard::nep141::{commitment::*, *},
+//!     Nep141,
+//! };
+//!
+//! #[derive(Nep141)]
+//! #[nep141(balance_commitment)]
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! let mut contract = Contract {};
+//! let alice = "alice".parse().unwrap();
+//!
+//! contract
+//!     .mint(&Nep141Mint {
+//!         amount: 100,
+//!         receiver_id: &alice,
+//!         memo: None,
+//!         origin: ActionOrigin::Internal,
+//!     })
+//!     .unwrap();
+//!
+//! let root = contract.balances_commitment().root.into();
+//! let proof = contract.balance_proof(&alice).unwrap();
+//! assert_eq!(proof.balance.0, 100);
+//! assert!(proof.verify(&root));
+//! ```
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The commitment root storage slot is not used or modified. The
+//!     default key is `~$141m`.
+//! * (UB) [`Nep141CommitmentInternal::TREE_DEPTH`] is at most 32, and does
+//!     not change once balances are committed.
+//! * (UB) Balances are only modified through [`Nep141Controller`] methods
+//!     that invoke the hooks, or committed with
+//!     [`Nep141Commitment::commit_balances`] afterwards. Otherwise, proofs of
+//!     the modified balances do not verify.
+//! * (ERR) The tree has room for the accounts it commits.
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::{Base58CryptoHash, U128, U64},
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey, CryptoHash,
+};
+use thiserror::Error;
+
+use crate::{hook::Hook, slot::Slot, DefaultStorageKey};
+
+use super::{Nep141Burn, Nep141Controller, Nep141Mint, Nep141TransferAction};
+
+pub use ext::*;
+
+/// Default depth of the tree, which has room for 2^24 (about 16.7 million)
+/// accounts.
+pub const DEFAULT_COMMITMENT_TREE_DEPTH: u8 = 24;
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Hash of the leaf that commits to the balance of an account.
+pub fn leaf_hash(account_id: &AccountId, balance: u128) -> CryptoHash {
+    let account_id = account_id.as_bytes();
+    let mut preimage = Vec::with_capacity(1 + 4 + account_id.len() + 16);
+    preimage.push(LEAF_PREFIX);
+    preimage.extend_from_slice(&(account_id.len() as u32).to_le_bytes());
+    preimage.extend_from_slice(account_id);
+    preimage.extend_from_slice(&balance.to_le_bytes());
+    env::sha256_array(&preimage)
+}
+
+/// Hash of an inner node of the tree.
+pub fn node_hash(left: &CryptoHash, right: &CryptoHash) -> CryptoHash {
+    let mut preimage = [0u8; 65];
+    preimage[0] = NODE_PREFIX;
+    preimage[1..33].copy_from_slice(left);
+    preimage[33..].copy_from_slice(right);
+    env::sha256_array(&preimage)
+}
+
+/// Roots of empty subtrees, by height: the first element is an unassigned
+/// leaf, and the last one is the root of an empty tree of depth `depth`.
+pub fn empty_roots(depth: u8) -> Vec<CryptoHash> {
+    let mut roots = Vec::with_capacity(depth as usize + 1);
+    roots.push([0u8; 32]);
+    for height in 0..depth as usize {
+        roots.push(node_hash(&roots[height], &roots[height]));
+    }
+    roots
+}
+
+/// Commitment to the balances of all accounts.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BalancesCommitment {
+    /// Root of the tree.
+    pub root: Base58CryptoHash,
+    /// Depth of the tree, i.e. the number of siblings in a proof.
+    pub depth: u8,
+    /// Number of leaves assigned to accounts.
+    pub leaf_count: u32,
+    /// Height of the block of the last update, or 0 if the tree is empty.
+    pub block_height: U64,
+}
+
+/// Proof of the balance of an account against a [`BalancesCommitment`].
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BalanceProof {
+    /// The account.
+    pub account_id: AccountId,
+    /// The balance of the account.
+    pub balance: U128,
+    /// Index of the account's leaf.
+    pub leaf_index: u32,
+    /// Siblings of the nodes on the path from the leaf to the root, leaf
+    /// level first.
+    pub siblings: Vec<Base58CryptoHash>,
+}
+
+impl BalanceProof {
+    /// Computes the root of the tree from the proof.
+    pub fn compute_root(&self) -> CryptoHash {
+        let mut hash = leaf_hash(&self.account_id, self.balance.0);
+        let mut index = self.leaf_index;
+
+        for sibling in &self.siblings {
+            let sibling = CryptoHash::from(*sibling);
+            hash = if index & 1 == 0 {
+                node_hash(&hash, &sibling)
+            } else {
+                node_hash(&sibling, &hash)
+            };
+            index >>= 1;
+        }
+
+        hash
+    }
+
+    /// Whether the proof is valid for the tree with the given root.
+    pub fn verify(&self, root: &CryptoHash) -> bool {
+        self.compute_root() == *root
+    }
+}
+
+/// The tree has no room for another account.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Balance commitment tree is full ({capacity} accounts)")]
+pub struct CommitmentTreeFullError {
+    /// Number of accounts the tree has room for.
+    pub capacity: u64,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Commitment,
+    LeafIndex(AccountId),
+    Node(u8, u32),
+}
+
+/// Internal functions for [`Nep141Commitment`]. Using these methods may
+/// result in unexpected behavior.
+pub trait Nep141CommitmentInternal {
+    /// Depth of the tree. At most 32.
+    const TREE_DEPTH: u8 = DEFAULT_COMMITMENT_TREE_DEPTH;
+
+    /// Root storage slot.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep141Commitment)
+    }
+
+    /// Slot for the current commitment.
+    fn slot_commitment() -> Slot<BalancesCommitment> {
+        Self::root().field(StorageKey::Commitment)
+    }
+
+    /// Slot for the index of an account's leaf.
+    fn slot_leaf_index(account_id: &AccountId) -> Slot<u32> {
+        Self::root().field(StorageKey::LeafIndex(account_id.clone()))
+    }
+
+    /// Slot for a node of the tree. Level 0 is the leaves. Nodes that are
+    /// not stored are roots of empty subtrees.
+    fn slot_node(level: u8, index: u32) -> Slot<CryptoHash> {
+        Self::root().field(StorageKey::Node(level, index))
+    }
+}
+
+/// Non-public functions for maintaining and proving the balances commitment.
+pub trait Nep141Commitment {
+    /// The current commitment.
+    fn balances_commitment(&self) -> BalancesCommitment;
+
+    /// Proof of the current balance of an account, or `None` if the account
+    /// has never been committed.
+    fn balance_proof(&self, account_id: &AccountId) -> Option<BalanceProof>;
+
+    /// Updates the leaf of an account to its current balance, assigning a
+    /// leaf if the account has none and a nonzero balance.
+    fn commit_balance(&mut self, account_id: &AccountId) -> Result<(), CommitmentTreeFullError>;
+
+    /// Updates the leaves of several accounts, e.g. to commit balances held
+    /// before the commitment was enabled. Performs no access checks.
+    fn commit_balances(
+        &mut self,
+        account_ids: &[AccountId],
+    ) -> Result<(), CommitmentTreeFullError> {
+        for account_id in account_ids {
+            self.commit_balance(account_id)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Nep141CommitmentInternal + Nep141Controller> Nep141Commitment for T {
+    fn balances_commitment(&self) -> BalancesCommitment {
+        Self::slot_commitment()
+            .read()
+            .unwrap_or_else(|| BalancesCommitment {
+                root: empty_roots(Self::TREE_DEPTH)[Self::TREE_DEPTH as usize].into(),
+                depth: Self::TREE_DEPTH,
+                leaf_count: 0,
+                block_height: 0.into(),
+            })
+    }
+
+    fn balance_proof(&self, account_id: &AccountId) -> Option<BalanceProof> {
+        let leaf_index = Self::slot_leaf_index(account_id).read()?;
+        let empty = empty_roots(Self::TREE_DEPTH);
+
+        let siblings = (0..Self::TREE_DEPTH)
+            .map(|level| {
+                Self::slot_node(level, (leaf_index >> level) ^ 1)
+                    .read()
+                    .unwrap_or(empty[level as usize])
+                    .into()
+            })
+            .collect();
+
+        Some(BalanceProof {
+            account_id: account_id.clone(),
+            balance: self.balance_of(account_id).into(),
+            leaf_index,
+            siblings,
+        })
+    }
+
+    fn commit_balance(&mut self, account_id: &AccountId) -> Result<(), CommitmentTreeFullError> {
+        let balance = self.balance_of(account_id);
+        let leaf = leaf_hash(account_id, balance);
+        let mut commitment = self.balances_commitment();
+
+        let mut index = match Self::slot_leaf_index(account_id).read() {
+            Some(index) => {
+                if Self::slot_node(0, index).read() == Some(leaf) {
+                    return Ok(());
+                }
+                index
+            }
+            None if balance == 0 => return Ok(()),
+            None => {
+                let capacity = 1u64 << Self::TREE_DEPTH;
+                let index = commitment.leaf_count;
+                if u64::from(index) >= capacity || index == u32::MAX {
+                    return Err(CommitmentTreeFullError { capacity });
+                }
+                Self::slot_leaf_index(account_id).write(&index);
+                commitment.leaf_count += 1;
+                index
+            }
+        };
+
+        let empty = empty_roots(Self::TREE_DEPTH);
+        let mut hash = leaf;
+
+        for level in 0..Self::TREE_DEPTH {
+            Self::slot_node(level, index).write(&hash);
+            let sibling = Self::slot_node(level, index ^ 1)
+                .read()
+                .unwrap_or(empty[level as usize]);
+            hash = if index & 1 == 0 {
+                node_hash(&hash, &sibling)
+            } else {
+                node_hash(&sibling, &hash)
+            };
+            index >>= 1;
+        }
+
+        commitment.root = hash.into();
+        commitment.block_height = env::block_height().into();
+        Self::slot_commitment().write(&commitment);
+
+        Ok(())
+    }
+}
+
+fn commit_or_panic(contract: &mut impl Nep141Commitment, account_id: &AccountId) {
+    contract
+        .commit_balance(account_id)
+        .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+}
+
+/// Hook that updates the commitment to the balances of the accounts
+/// involved in a mint, transfer, or burn.
+pub struct Nep141CommitmentHook;
+
+impl<C: Nep141Commitment> Hook<C, Nep141Mint<'_>> for Nep141CommitmentHook {
+    fn hook<R>(contract: &mut C, action: &Nep141Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let r = f(contract);
+        commit_or_panic(contract, action.receiver_id);
+        r
+    }
+}
+
+impl<C: Nep141Commitment, T: Nep141TransferAction> Hook<C, T> for Nep141CommitmentHook {
+    fn hook<R>(contract: &mut C, action: &T, f: impl FnOnce(&mut C) -> R) -> R {
+        let r = f(contract);
+        commit_or_panic(contract, action.sender_id());
+        commit_or_panic(contract, action.receiver_id());
+        r
+    }
+}
+
+impl<C: Nep141Commitment> Hook<C, Nep141Burn<'_>> for Nep141CommitmentHook {
+    fn hook<R>(contract: &mut C, action: &Nep141Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let r = f(contract);
+        commit_or_panic(contract, action.owner_id);
+        r
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, AccountId};
+
+    use super::{BalanceProof, BalancesCommitment};
+
+    /// External (public) methods for the balances commitment.
+    #[ext_contract(ext_nep141_commitment)]
+    pub trait Nep141CommitmentExternal {
+        /// Returns the current commitment to all balances.
+        fn ft_balances_commitment(&self) -> BalancesCommitment;
+
+        /// Returns a proof of the current balance of `account_id` against
+        /// the current commitment, or `null` if the account has never held
+        /// a balance.
+        fn ft_balance_proof(&self, account_id: AccountId) -> Option<BalanceProof>;
+    }
+}
@@ -0,0 +1,162 @@
+//! Minimum transfer amounts ("dust" protection) for NEP-141 fungible tokens.
+//!
+//! Tokens with many decimals can be split into arbitrarily small amounts,
+//! and every account holding even a single indivisible unit occupies storage
+//! and counts as a holder. A minimum transfer amount rejects external
+//! transfers (`ft_transfer` and `ft_transfer_call`) below the minimum, and
+//! [`Nep141Dust::sweep_dust`] moves balances below the minimum to a
+//! treasury account. Mints, burns, and internal transfers (e.g. refunds in
+//! `ft_resolve_transfer`) are not restricted.
+//!
+//! Enable with `#[nep141(dust_role = "...")]`, which generates an
+//! implementation of [`Nep141DustExternal`] callable by accounts with the
+//! role. The contract must implement `Rbac`. The minimum is 0 (disabled)
+//! until it is set.
+//!
+//! Swept balances are moved with regular NEP-141 transfers, so they invoke
+//! the contract's transfer hook and emit `ft_transfer` events with the memo
+//! [`DUST_SWEEP_MEMO`]. Contracts that require storage registration (e.g.
+//! NEP-145) must register the treasury account.
+
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    AccountId, BorshStorageKey,
+};
+use thiserror::Error;
+
+use crate::{hook::ActionOrigin, slot::Slot, DefaultStorageKey};
+
+use super::{Nep141Controller, Nep141Transfer, TransferError};
+
+pub use ext::*;
+
+/// Memo of the transfers made by [`Nep141Dust::sweep_dust`].
+pub const DUST_SWEEP_MEMO: &str = "dust";
+
+/// The transfer amount is below the minimum.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Transfer amount {amount} is below the minimum of {minimum}")]
+pub struct DustTransferError {
+    /// The amount of the rejected transfer.
+    pub amount: u128,
+    /// The minimum transfer amount.
+    pub minimum: u128,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    MinTransfer,
+}
+
+/// Internal functions for [`Nep141Dust`]. Using these methods may result in
+/// unexpected behavior.
+pub trait Nep141DustInternal {
+    /// Root storage slot.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep141Dust)
+    }
+
+    /// Slot for the minimum transfer amount.
+    fn slot_min_transfer() -> Slot<u128> {
+        Self::root().field(StorageKey::MinTransfer)
+    }
+}
+
+/// Non-public functions for managing the minimum transfer amount.
+pub trait Nep141Dust {
+    /// The minimum amount of an external transfer. 0 if unrestricted.
+    fn min_transfer(&self) -> u128;
+
+    /// Sets the minimum amount of an external transfer. 0 disables the
+    /// restriction. Performs no access checks.
+    fn set_min_transfer(&mut self, minimum: u128);
+
+    /// Checks that `amount` is not below the minimum transfer amount.
+    fn check_transfer_amount(&self, amount: u128) -> Result<(), DustTransferError>;
+
+    /// Transfers the balances of `account_ids` that are nonzero but below the
+    /// minimum transfer amount to `treasury_id`. Other accounts, and the
+    /// treasury itself, are skipped. Performs no access checks. `origin` is
+    /// the origin of each transfer. Returns the total amount swept.
+    fn sweep_dust(
+        &mut self,
+        account_ids: &[AccountId],
+        treasury_id: &AccountId,
+        origin: ActionOrigin,
+    ) -> Result<u128, TransferError>;
+}
+
+impl<T: Nep141DustInternal + Nep141Controller> Nep141Dust for T {
+    fn min_transfer(&self) -> u128 {
+        Self::slot_min_transfer().read().unwrap_or(0)
+    }
+
+    fn set_min_transfer(&mut self, minimum: u128) {
+        Self::slot_min_transfer().set(Some(&minimum).filter(|m| **m > 0));
+    }
+
+    fn check_transfer_amount(&self, amount: u128) -> Result<(), DustTransferError> {
+        let minimum = self.min_transfer();
+
+        if amount < minimum {
+            return Err(DustTransferError { amount, minimum });
+        }
+
+        Ok(())
+    }
+
+    fn sweep_dust(
+        &mut self,
+        account_ids: &[AccountId],
+        treasury_id: &AccountId,
+        origin: ActionOrigin,
+    ) -> Result<u128, TransferError> {
+        let minimum = self.min_transfer();
+        let mut swept = 0;
+
+        for account_id in account_ids {
+            let balance = self.balance_of(account_id);
+
+            if account_id == treasury_id || balance == 0 || balance >= minimum {
+                continue;
+            }
+
+            self.transfer(&Nep141Transfer {
+                sender_id: account_id,
+                receiver_id: treasury_id,
+                amount: balance,
+                memo: Some(DUST_SWEEP_MEMO),
+                msg: None,
+                revert: false,
+                origin,
+            })?;
+
+            swept += balance;
+        }
+
+        Ok(swept)
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U128, AccountId};
+
+    /// Externally-accessible functions for the minimum transfer amount.
+    #[ext_contract(ext_nep141_dust)]
+    pub trait Nep141DustExternal {
+        /// Returns the minimum amount of `ft_transfer` and
+        /// `ft_transfer_call`. 0 if unrestricted.
+        fn ft_min_transfer(&self) -> U128;
+
+        /// Sets the minimum transfer amount. 0 disables the restriction.
+        /// Requires the dust role and exactly 1 yoctoNEAR.
+        fn ft_set_min_transfer(&mut self, minimum: U128);
+
+        /// Moves the balances of `account_ids` below the minimum transfer
+        /// amount to `treasury_id`. Requires the dust role and exactly 1
+        /// yoctoNEAR. Returns the total amount swept.
+        fn ft_sweep_dust(&mut self, account_ids: Vec<AccountId>, treasury_id: AccountId) -> U128;
+    }
+}
@@ -1,6 +1,10 @@
 #![allow(missing_docs)]
 
-use near_sdk::{ext_contract, json_types::U128, AccountId, Promise, PromiseOrValue};
+use near_sdk::{
+    ext_contract,
+    json_types::{U128, U64},
+    AccountId, Promise, PromiseOrValue,
+};
 
 /// A contract that may be the recipient of an `ft_transfer_call` function
 /// call.
@@ -23,6 +27,10 @@ pub trait Nep141Receiver {
 pub trait Nep141Resolver {
     /// Callback, last in `ft_transfer_call` promise chain. Returns the amount
     /// of tokens refunded to the original sender.
+    ///
+    /// Must only be callable by the contract itself. Manual implementations
+    /// should call [`crate::utils::require_self_callback`] in addition to
+    /// marking the method `#[private]`.
     fn ft_resolve_transfer(
         &mut self,
         sender_id: AccountId,
@@ -32,6 +40,20 @@ pub trait Nep141Resolver {
 }
 
 /// Externally-accessible NEP-141-compatible fungible token interface.
+#[cfg_attr(
+    feature = "client",
+    near_sdk_contract_tools_macros::ext_client(
+        name = "Nep141Client",
+        crate = "crate",
+        method(name = "ft_transfer", deposit = 1),
+        method(
+            name = "ft_transfer_call",
+            deposit = 1,
+            gas = "crate::client::DEFAULT_TRANSFER_CALL_GAS",
+            returns = "U128",
+        ),
+    )
+)]
 #[ext_contract(ext_nep141)]
 pub trait Nep141 {
     /// Performs a token transfer
@@ -54,3 +76,49 @@ pub trait Nep141 {
     /// Returns the amount of tokens controlled by `account_id`
     fn ft_balance_of(&self, account_id: AccountId) -> U128;
 }
+
+/// Non-standard view of the number of accounts holding a nonzero balance.
+#[ext_contract(ext_nep141_holder_count)]
+pub trait Nep141HolderCount {
+    /// Returns the number of accounts with a nonzero balance.
+    fn ft_holder_count(&self) -> U64;
+}
+
+/// Non-standard removal of balances stored as an explicit zero.
+#[ext_contract(ext_nep141_zero_balance_sweep)]
+pub trait Nep141ZeroBalanceSweep {
+    /// Removes the balances of `account_ids` that are stored as an explicit
+    /// `0`, releasing their storage. Requires a role and one yoctoNEAR.
+    /// Returns the number of balances removed.
+    fn ft_sweep_zero_balances(&mut self, account_ids: Vec<AccountId>) -> u32;
+}
+
+/// Non-standard view that pre-validates a transfer.
+#[ext_contract(ext_nep141_transfer_check)]
+pub trait Nep141TransferCheck {
+    /// Returns the reason a transfer of `amount` tokens from `sender_id` to
+    /// `receiver_id` would fail, or `None` if it would succeed.
+    fn ft_transfer_check(
+        &self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> Option<String>;
+}
+
+/// Non-standard batch mint, e.g. for genesis distributions by token
+/// factories.
+#[ext_contract(ext_nep141_batch_mint)]
+pub trait Nep141BatchMint {
+    /// Mints tokens to each account, emitting a single `ft_mint` event.
+    /// Requires the batch mint role and exactly 1 yoctoNEAR.
+    fn ft_batch_mint(&mut self, mints: Vec<(AccountId, U128)>, memo: Option<String>);
+}
+
+/// Non-standard batch burn.
+#[ext_contract(ext_nep141_batch_burn)]
+pub trait Nep141BatchBurn {
+    /// Burns tokens from each account, emitting a single `ft_burn` event.
+    /// Requires the batch burn role and exactly 1 yoctoNEAR.
+    fn ft_batch_burn(&mut self, burns: Vec<(AccountId, U128)>, memo: Option<String>);
+}
@@ -1,12 +1,23 @@
 //! Hooks to integrate NEP-141 with other standards.
 
-use crate::{hook::Hook, standard::nep145::Nep145ForceUnregister};
+#[cfg(feature = "nep145")]
+use near_sdk::{env, AccountId};
 
+#[cfg(feature = "nep145")]
+use crate::{
+    hook::Hook,
+    standard::nep145::{Nep145Controller, Nep145ForceUnregister},
+};
+
+#[cfg(feature = "nep145")]
 use super::{Nep141Burn, Nep141Controller, Nep141ControllerInternal};
 
-/// Hook that burns all tokens on NEP-145 force unregister.
+/// Hook that burns all tokens on NEP-145 force unregister. Requires the
+/// `nep145` feature.
+#[cfg(feature = "nep145")]
 pub struct BurnNep141OnForceUnregisterHook;
 
+#[cfg(feature = "nep145")]
 impl<C: Nep141Controller + Nep141ControllerInternal> Hook<C, Nep145ForceUnregister<'_>>
     for BurnNep141OnForceUnregisterHook
 {
@@ -23,6 +34,7 @@ impl<C: Nep141Controller + Nep141ControllerInternal> Hook<C, Nep145ForceUnregist
                 amount: balance,
                 owner_id: args.account_id,
                 memo: Some("storage forced unregistration"),
+                origin: args.origin,
             })
             .unwrap_or_else(|e| {
                 near_sdk::env::panic_str(&format!(
@@ -30,8 +42,43 @@ impl<C: Nep141Controller + Nep141ControllerInternal> Hook<C, Nep145ForceUnregist
                 ))
             });
 
-        <C as Nep141ControllerInternal>::slot_account(args.account_id).remove();
+        super::cache::remove(&mut <C as Nep141ControllerInternal>::slot_account(
+            args.account_id,
+        ));
+        #[cfg(feature = "hashed-account-keys")]
+        <C as Nep141ControllerInternal>::slot_account_legacy(args.account_id).remove();
 
         r
     }
 }
+
+/// Removes the balances of `account_ids` that are stored as an explicit `0`,
+/// like [`Nep141Controller::sweep_zero_balances`], and credits the storage
+/// released by each to the account's NEP-145 storage balance, if it is
+/// registered. Returns the number of balances removed. Requires the `nep145`
+/// feature.
+#[cfg(feature = "nep145")]
+pub fn sweep_zero_balances_with_storage_refund<C: Nep141Controller + Nep145Controller>(
+    contract: &mut C,
+    account_ids: &[AccountId],
+) -> u32 {
+    let mut removed = 0;
+
+    for account_id in account_ids {
+        let storage_usage_start = env::storage_usage();
+
+        if contract.sweep_zero_balances(std::slice::from_ref(account_id)) == 0 {
+            continue;
+        }
+
+        removed += 1;
+
+        if contract.get_storage_balance(account_id).is_ok() {
+            contract
+                .storage_refund(account_id, storage_usage_start)
+                .unwrap_or_else(|e| env::panic_str(&format!("Storage accounting error: {}", e)));
+        }
+    }
+
+    removed
+}
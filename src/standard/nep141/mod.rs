@@ -2,13 +2,26 @@
 //! <https://github.com/near/NEPs/blob/master/neps/nep-0141.md>
 
 use near_sdk::{
-    borsh::{self, BorshSerialize},
-    serde::Serialize,
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
     AccountId, BorshStorageKey, Gas,
 };
 
-use crate::{hook::Hook, slot::Slot, standard::nep297::*, DefaultStorageKey};
+use crate::{
+    hook::{ActionOrigin, Hook},
+    slot::Slot,
+    standard::nep297::*,
+    storage_version::StorageVersion,
+    transaction::Transaction,
+    utils::log::debug,
+    DefaultStorageKey,
+};
 
+pub mod allowance;
+pub mod bridge;
+pub mod cache;
+pub mod commitment;
+pub mod dust;
 mod error;
 pub use error::*;
 mod event;
@@ -16,21 +29,30 @@ pub use event::*;
 mod ext;
 pub use ext::*;
 pub mod hooks;
+pub mod pending;
+pub mod subscription;
 
 /// Gas value required for [`Nep141Resolver::ft_resolve_transfer`] call,
 /// independent of the amount of gas required for the preceding
-/// [`Nep141::ft_transfer`] call.
+/// [`Nep141::ft_transfer`] call. Matches `near-contract-standards` 4.1, and
+/// may be overridden at runtime with [`crate::gas::GasConfig`].
 pub const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
 /// Gas value required for [`Nep141::ft_transfer_call`] calls (includes gas for
-/// the subsequent [`Nep141Resolver::ft_resolve_transfer`] call).
+/// the subsequent [`Nep141Resolver::ft_resolve_transfer`] call). Matches
+/// `near-contract-standards` 4.1, and may be overridden at runtime with
+/// [`crate::gas::GasConfig`].
 pub const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
-/// Error message for insufficient gas.
+/// Error message for insufficient gas. Generated methods use
+/// [`crate::utils::preflight::require_gas`] instead.
 pub const MORE_GAS_FAIL_MESSAGE: &str = "Insufficient gas attached.";
 
 #[derive(BorshSerialize, BorshStorageKey)]
 enum StorageKey {
     TotalSupply,
     Account(AccountId),
+    HolderCount,
+    #[cfg(feature = "hashed-account-keys")]
+    AccountKey([u8; 32]),
 }
 
 /// Transfer metadata generic over both types of transfer (`ft_transfer` and
@@ -50,6 +72,10 @@ pub struct Nep141Transfer<'a> {
     pub msg: Option<&'a str>,
     /// Is this transfer a revert as a result of a [`Nep141::ft_transfer_call`] -> [`Nep141Receiver::ft_on_transfer`] call?
     pub revert: bool,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
 
 impl<'a> Nep141Transfer<'a> {
@@ -60,6 +86,150 @@ impl<'a> Nep141Transfer<'a> {
     }
 }
 
+/// Owned counterpart of [`Nep141Transfer`], which can be stored (e.g. in a
+/// queue) and executed later with [`Nep141TransferAction::as_transfer`].
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Nep141TransferOwned {
+    /// Sender's account ID.
+    pub sender_id: AccountId,
+    /// Receiver's account ID.
+    pub receiver_id: AccountId,
+    /// Transferred amount.
+    pub amount: u128,
+    /// Optional memo string.
+    pub memo: Option<String>,
+    /// Message passed to contract located at `receiver_id`.
+    pub msg: Option<String>,
+    /// Is this transfer a revert as a result of a [`Nep141::ft_transfer_call`] -> [`Nep141Receiver::ft_on_transfer`] call?
+    pub revert: bool,
+    /// Which method requested the action. Not serialized, so a deserialized
+    /// transfer is [`ActionOrigin::Internal`].
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
+}
+
+impl From<&Nep141Transfer<'_>> for Nep141TransferOwned {
+    fn from(transfer: &Nep141Transfer<'_>) -> Self {
+        Self {
+            sender_id: transfer.sender_id.clone(),
+            receiver_id: transfer.receiver_id.clone(),
+            amount: transfer.amount,
+            memo: transfer.memo.map(ToString::to_string),
+            msg: transfer.msg.map(ToString::to_string),
+            revert: transfer.revert,
+            origin: transfer.origin,
+        }
+    }
+}
+
+impl From<Nep141Transfer<'_>> for Nep141TransferOwned {
+    fn from(transfer: Nep141Transfer<'_>) -> Self {
+        Self::from(&transfer)
+    }
+}
+
+impl<'a> From<&'a Nep141TransferOwned> for Nep141Transfer<'a> {
+    fn from(transfer: &'a Nep141TransferOwned) -> Self {
+        transfer.as_transfer()
+    }
+}
+
+/// A NEP-141 transfer, borrowed ([`Nep141Transfer`]) or owned
+/// ([`Nep141TransferOwned`]). Hooks that implement
+/// `Hook<C, T> where T: Nep141TransferAction` work with both.
+pub trait Nep141TransferAction {
+    /// Sender's account ID.
+    fn sender_id(&self) -> &AccountId;
+    /// Receiver's account ID.
+    fn receiver_id(&self) -> &AccountId;
+    /// Transferred amount.
+    fn amount(&self) -> u128;
+    /// Optional memo string.
+    fn memo(&self) -> Option<&str>;
+    /// Message passed to contract located at `receiver_id`.
+    fn msg(&self) -> Option<&str>;
+    /// Whether the transfer is a revert of a `ft_transfer_call`.
+    fn revert(&self) -> bool;
+    /// Which method requested the transfer.
+    fn origin(&self) -> ActionOrigin;
+
+    /// Borrows the transfer as a [`Nep141Transfer`], e.g. to pass it to
+    /// [`Nep141Controller::transfer`].
+    fn as_transfer(&self) -> Nep141Transfer<'_> {
+        Nep141Transfer {
+            sender_id: self.sender_id(),
+            receiver_id: self.receiver_id(),
+            amount: self.amount(),
+            memo: self.memo(),
+            msg: self.msg(),
+            revert: self.revert(),
+            origin: self.origin(),
+        }
+    }
+}
+
+impl Nep141TransferAction for Nep141Transfer<'_> {
+    fn sender_id(&self) -> &AccountId {
+        self.sender_id
+    }
+
+    fn receiver_id(&self) -> &AccountId {
+        self.receiver_id
+    }
+
+    fn amount(&self) -> u128 {
+        self.amount
+    }
+
+    fn memo(&self) -> Option<&str> {
+        self.memo
+    }
+
+    fn msg(&self) -> Option<&str> {
+        self.msg
+    }
+
+    fn revert(&self) -> bool {
+        self.revert
+    }
+
+    fn origin(&self) -> ActionOrigin {
+        self.origin
+    }
+}
+
+impl Nep141TransferAction for Nep141TransferOwned {
+    fn sender_id(&self) -> &AccountId {
+        &self.sender_id
+    }
+
+    fn receiver_id(&self) -> &AccountId {
+        &self.receiver_id
+    }
+
+    fn amount(&self) -> u128 {
+        self.amount
+    }
+
+    fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
+
+    fn msg(&self) -> Option<&str> {
+        self.msg.as_deref()
+    }
+
+    fn revert(&self) -> bool {
+        self.revert
+    }
+
+    fn origin(&self) -> ActionOrigin {
+        self.origin
+    }
+}
+
 /// Describes a mint operation.
 #[derive(Clone, Debug, Serialize, BorshSerialize, PartialEq, Eq)]
 #[serde(crate = "near_sdk::serde")]
@@ -70,6 +240,10 @@ pub struct Nep141Mint<'a> {
     pub receiver_id: &'a AccountId,
     /// Optional memo string.
     pub memo: Option<&'a str>,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
 
 /// Describes a burn operation.
@@ -82,8 +256,16 @@ pub struct Nep141Burn<'a> {
     pub owner_id: &'a AccountId,
     /// Optional memo string.
     pub memo: Option<&'a str>,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
 
+/// Storage layout version of the Nep141 component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Nep141", 1);
+
 /// Internal functions for [`Nep141Controller`]. Using these methods may result in unexpected behavior.
 pub trait Nep141ControllerInternal {
     /// Hook for mint operations.
@@ -99,20 +281,52 @@ pub trait Nep141ControllerInternal {
     where
         Self: Sized;
 
+    /// Whether to remove an account's balance from storage when it reaches
+    /// zero, instead of storing an explicit `0`. Balances of zero stored
+    /// before this was enabled can be removed with
+    /// [`Nep141Controller::sweep_zero_balances`].
+    ///
+    /// Disabled by default, because storage accounting sees the removal as
+    /// released storage, and NEP-145 storage accounting of a transfer credits
+    /// it to the receiver, not the sender.
+    const REMOVE_ZERO_BALANCES: bool = false;
+
     /// Root storage slot.
     fn root() -> Slot<()> {
         Slot::new(DefaultStorageKey::Nep141)
     }
 
     /// Slot for account data.
+    #[cfg(not(feature = "hashed-account-keys"))]
     fn slot_account(account_id: &AccountId) -> Slot<u128> {
         Self::root().field(StorageKey::Account(account_id.clone()))
     }
 
+    /// Slot for account data, keyed by [`crate::utils::account_key`].
+    #[cfg(feature = "hashed-account-keys")]
+    fn slot_account(account_id: &AccountId) -> Slot<u128> {
+        Self::root().field(StorageKey::AccountKey(crate::utils::account_key(
+            account_id,
+        )))
+    }
+
+    /// Slot for account data written before the `hashed-account-keys`
+    /// feature was enabled. Balances are moved out of it the next time the
+    /// account's balance changes.
+    #[cfg(feature = "hashed-account-keys")]
+    fn slot_account_legacy(account_id: &AccountId) -> Slot<u128> {
+        Self::root().field(StorageKey::Account(account_id.clone()))
+    }
+
     /// Slot for storing total supply.
     fn slot_total_supply() -> Slot<u128> {
         Self::root().field(StorageKey::TotalSupply)
     }
+
+    /// Slot for storing the number of accounts with a nonzero balance.
+    fn slot_holder_count() -> Slot<u64> {
+        Self::root().field(StorageKey::HolderCount)
+    }
 }
 
 /// Non-public implementations of functions for managing a fungible token.
@@ -136,6 +350,14 @@ pub trait Nep141Controller {
     /// Get the total circulating supply of the token.
     fn total_supply(&self) -> u128;
 
+    /// Get the number of accounts with a nonzero balance.
+    ///
+    /// Only balance changes made since this counter was introduced are
+    /// counted, so contracts upgrading from an earlier version must
+    /// initialize [`Nep141ControllerInternal::slot_holder_count`] in a
+    /// migration.
+    fn holder_count(&self) -> u64;
+
     /// Removes tokens from an account and decreases total supply. No event
     /// emission or hook invocation.
     fn withdraw_unchecked(
@@ -162,8 +384,17 @@ pub trait Nep141Controller {
         amount: u128,
     ) -> Result<(), TransferError>;
 
+    /// Checks whether [`Nep141Controller::transfer`] would succeed at the
+    /// controller level, without mutating state. Hooks are not invoked, so
+    /// conditions they enforce (e.g. storage registration) are not checked.
+    fn can_transfer(&self, transfer: &Nep141Transfer<'_>) -> Result<(), TransferError>;
+
     /// Performs an NEP-141 token transfer, with event emission. Invokes
-    /// [`Nep141Controller::TransferHook`].
+    /// [`Nep141Controller::TransferHook`]. Runs in a
+    /// [`Transaction`](crate::transaction::Transaction), so if the transfer
+    /// fails, storage writes made by the hook through
+    /// [`Slot`](crate::slot::Slot) are rolled back. Writes made through
+    /// `near_sdk` collections are not.
     fn transfer(&mut self, transfer: &Nep141Transfer<'_>) -> Result<(), TransferError>;
 
     /// Performs an NEP-141 token mint, with event emission. Invokes
@@ -173,6 +404,146 @@ pub trait Nep141Controller {
     /// Performs an NEP-141 token burn, with event emission. Invokes
     /// [`Nep141Controller::BurnHook`].
     fn burn(&mut self, burn: &Nep141Burn<'_>) -> Result<(), WithdrawError>;
+
+    /// Mints tokens to many accounts, e.g. for a genesis distribution.
+    /// Invokes [`Nep141Controller::MintHook`] for each mint, but checks the
+    /// new total supply once, before any balance is changed, and emits a
+    /// single `ft_mint` event. `origin` is the origin of each [`Nep141Mint`]
+    /// action. On error, some mints may have been performed, so the caller
+    /// should panic.
+    fn mint_batch(
+        &mut self,
+        mints: &[(AccountId, u128)],
+        memo: Option<&str>,
+        origin: ActionOrigin,
+    ) -> Result<(), DepositError>;
+
+    /// Burns tokens from many accounts. Invokes
+    /// [`Nep141Controller::BurnHook`] for each burn, but checks the new total
+    /// supply once, before any balance is changed, and emits a single
+    /// `ft_burn` event. `origin` is the origin of each [`Nep141Burn`] action.
+    /// On error, some burns may have been performed, so the caller should
+    /// panic.
+    fn burn_batch(
+        &mut self,
+        burns: &[(AccountId, u128)],
+        memo: Option<&str>,
+        origin: ActionOrigin,
+    ) -> Result<(), WithdrawError>;
+
+    /// Removes the balances of `account_ids` that are stored as an explicit
+    /// `0`, e.g. by contracts deployed before
+    /// [`Nep141ControllerInternal::REMOVE_ZERO_BALANCES`] was introduced.
+    /// Returns the number of balances removed. Does not emit events or run
+    /// hooks.
+    fn sweep_zero_balances(&mut self, account_ids: &[AccountId]) -> u32;
+}
+
+/// Increases the balance of `account_id`. No change to total supply.
+fn credit<T: Nep141ControllerInternal + ?Sized>(
+    account_id: &AccountId,
+    amount: u128,
+) -> Result<(), BalanceOverflowError> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    migrate_account_key::<T>(account_id);
+    let balance = cache::read(&balance_slot::<T>(account_id));
+    let new_balance = match balance.checked_add(amount) {
+        Some(new_balance) => new_balance,
+        None => {
+            return Err(BalanceOverflowError {
+                account_id: account_id.clone(),
+                balance,
+                amount,
+            })
+        }
+    };
+
+    store_balance::<T>(account_id, new_balance);
+    update_holder_count::<T>(balance, new_balance);
+
+    Ok(())
+}
+
+/// Decreases the balance of `account_id`. No change to total supply.
+fn debit<T: Nep141ControllerInternal + ?Sized>(
+    account_id: &AccountId,
+    amount: u128,
+) -> Result<(), BalanceUnderflowError> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    migrate_account_key::<T>(account_id);
+    let balance = cache::read(&balance_slot::<T>(account_id));
+    let new_balance = match balance.checked_sub(amount) {
+        Some(new_balance) => new_balance,
+        None => {
+            return Err(BalanceUnderflowError {
+                account_id: account_id.clone(),
+                balance,
+                amount,
+            })
+        }
+    };
+
+    store_balance::<T>(account_id, new_balance);
+    update_holder_count::<T>(balance, new_balance);
+
+    Ok(())
+}
+
+/// Writes the balance of `account_id`, or removes it if it is zero and
+/// [`Nep141ControllerInternal::REMOVE_ZERO_BALANCES`] is set.
+fn store_balance<T: Nep141ControllerInternal + ?Sized>(account_id: &AccountId, balance: u128) {
+    let mut slot = T::slot_account(account_id);
+
+    if balance == 0 && T::REMOVE_ZERO_BALANCES {
+        cache::remove(&mut slot);
+    } else {
+        cache::write(&mut slot, balance);
+    }
+}
+
+/// Updates the holder count when a balance changes from `before` to `after`.
+fn update_holder_count<T: Nep141ControllerInternal + ?Sized>(before: u128, after: u128) {
+    let mut slot = T::slot_holder_count();
+    let holder_count = slot.read().unwrap_or(0);
+    let holder_count = match (before, after) {
+        (0, 1..) => holder_count.saturating_add(1),
+        (1.., 0) => holder_count.saturating_sub(1),
+        _ => return,
+    };
+    slot.write(&holder_count);
+}
+
+/// Returns the slot holding the balance of `account_id`. With the
+/// `hashed-account-keys` feature, balances that have not been migrated yet
+/// are read from the legacy slot.
+fn balance_slot<T: Nep141ControllerInternal + ?Sized>(account_id: &AccountId) -> Slot<u128> {
+    let slot = T::slot_account(account_id);
+
+    #[cfg(feature = "hashed-account-keys")]
+    if !slot.exists() {
+        let legacy = T::slot_account_legacy(account_id);
+        if legacy.exists() {
+            return legacy;
+        }
+    }
+
+    slot
+}
+
+/// Moves the balance of `account_id` out of the legacy slot before it
+/// changes. Does nothing without the `hashed-account-keys` feature.
+#[cfg_attr(not(feature = "hashed-account-keys"), allow(unused_variables))]
+fn migrate_account_key<T: Nep141ControllerInternal + ?Sized>(account_id: &AccountId) {
+    #[cfg(feature = "hashed-account-keys")]
+    if let Some(balance) = T::slot_account_legacy(account_id).take() {
+        T::slot_account(account_id).write(&balance);
+    }
 }
 
 impl<T: Nep141ControllerInternal> Nep141Controller for T {
@@ -181,30 +552,24 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
     type BurnHook = T::BurnHook;
 
     fn balance_of(&self, account_id: &AccountId) -> u128 {
-        Self::slot_account(account_id).read().unwrap_or(0)
+        cache::read(&balance_slot::<Self>(account_id))
     }
 
     fn total_supply(&self) -> u128 {
         Self::slot_total_supply().read().unwrap_or(0)
     }
 
+    fn holder_count(&self) -> u64 {
+        Self::slot_holder_count().read().unwrap_or(0)
+    }
+
     fn withdraw_unchecked(
         &mut self,
         account_id: &AccountId,
         amount: u128,
     ) -> Result<(), WithdrawError> {
         if amount != 0 {
-            let balance = self.balance_of(account_id);
-            if let Some(balance) = balance.checked_sub(amount) {
-                Self::slot_account(account_id).write(&balance);
-            } else {
-                return Err(BalanceUnderflowError {
-                    account_id: account_id.clone(),
-                    balance,
-                    amount,
-                }
-                .into());
-            }
+            debit::<Self>(account_id, amount)?;
 
             let total_supply = self.total_supply();
             if let Some(total_supply) = total_supply.checked_sub(amount) {
@@ -227,17 +592,7 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
         amount: u128,
     ) -> Result<(), DepositError> {
         if amount != 0 {
-            let balance = self.balance_of(account_id);
-            if let Some(balance) = balance.checked_add(amount) {
-                Self::slot_account(account_id).write(&balance);
-            } else {
-                return Err(BalanceOverflowError {
-                    account_id: account_id.clone(),
-                    balance,
-                    amount,
-                }
-                .into());
-            }
+            credit::<Self>(account_id, amount)?;
 
             let total_supply = self.total_supply();
             if let Some(total_supply) = total_supply.checked_add(amount) {
@@ -262,11 +617,30 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
     ) -> Result<(), TransferError> {
         let sender_balance = self.balance_of(sender_account_id);
 
-        if let Some(sender_balance) = sender_balance.checked_sub(amount) {
+        if sender_account_id == receiver_account_id {
+            // Reading both balances before writing would credit the amount
+            // twice, so only check that the balance is sufficient.
+            return if sender_balance >= amount {
+                Ok(())
+            } else {
+                Err(BalanceUnderflowError {
+                    account_id: sender_account_id.clone(),
+                    balance: sender_balance,
+                    amount,
+                }
+                .into())
+            };
+        }
+
+        if let Some(new_sender_balance) = sender_balance.checked_sub(amount) {
+            migrate_account_key::<Self>(sender_account_id);
+            migrate_account_key::<Self>(receiver_account_id);
             let receiver_balance = self.balance_of(receiver_account_id);
-            if let Some(receiver_balance) = receiver_balance.checked_add(amount) {
-                Self::slot_account(sender_account_id).write(&sender_balance);
-                Self::slot_account(receiver_account_id).write(&receiver_balance);
+            if let Some(new_receiver_balance) = receiver_balance.checked_add(amount) {
+                store_balance::<Self>(sender_account_id, new_sender_balance);
+                store_balance::<Self>(receiver_account_id, new_receiver_balance);
+                update_holder_count::<Self>(sender_balance, new_sender_balance);
+                update_holder_count::<Self>(receiver_balance, new_receiver_balance);
             } else {
                 return Err(BalanceOverflowError {
                     account_id: receiver_account_id.clone(),
@@ -287,53 +661,216 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
         Ok(())
     }
 
+    fn can_transfer(&self, transfer: &Nep141Transfer<'_>) -> Result<(), TransferError> {
+        let sender_balance = self.balance_of(transfer.sender_id);
+        if sender_balance < transfer.amount {
+            return Err(BalanceUnderflowError {
+                account_id: transfer.sender_id.clone(),
+                balance: sender_balance,
+                amount: transfer.amount,
+            }
+            .into());
+        }
+
+        if transfer.sender_id != transfer.receiver_id {
+            let receiver_balance = self.balance_of(transfer.receiver_id);
+            if receiver_balance.checked_add(transfer.amount).is_none() {
+                return Err(BalanceOverflowError {
+                    account_id: transfer.receiver_id.clone(),
+                    balance: receiver_balance,
+                    amount: transfer.amount,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
     fn transfer(&mut self, transfer: &Nep141Transfer<'_>) -> Result<(), TransferError> {
-        Self::TransferHook::hook(self, transfer, |contract| {
-            contract.transfer_unchecked(
-                transfer.sender_id,
-                transfer.receiver_id,
-                transfer.amount,
-            )?;
-
-            Nep141Event::FtTransfer(vec![FtTransferData {
-                old_owner_id: transfer.sender_id.clone(),
-                new_owner_id: transfer.receiver_id.clone(),
-                amount: transfer.amount.into(),
-                memo: transfer.memo.map(ToString::to_string),
-            }])
-            .emit();
+        cache::with_balance_cache(|| {
+            let result = Transaction::run(|| {
+                Self::TransferHook::hook(self, transfer, |contract| {
+                    contract.transfer_unchecked(
+                        transfer.sender_id,
+                        transfer.receiver_id,
+                        transfer.amount,
+                    )?;
+
+                    debug!(
+                        "transfer of {} from {} to {}",
+                        transfer.amount, transfer.sender_id, transfer.receiver_id,
+                    );
+
+                    Nep141Event::FtTransfer(vec![FtTransferData {
+                        old_owner_id: transfer.sender_id.clone(),
+                        new_owner_id: transfer.receiver_id.clone(),
+                        amount: transfer.amount.into(),
+                        memo: transfer.memo.map(ToString::to_string),
+                    }])
+                    .emit();
+
+                    Ok(())
+                })
+            });
+
+            if result.is_err() {
+                cache::clear();
+            }
 
-            Ok(())
+            result
         })
     }
 
     fn mint(&mut self, mint: &Nep141Mint) -> Result<(), DepositError> {
-        Self::MintHook::hook(self, mint, |contract| {
-            contract.deposit_unchecked(mint.receiver_id, mint.amount)?;
-
-            Nep141Event::FtMint(vec![FtMintData {
-                owner_id: mint.receiver_id.clone(),
-                amount: mint.amount.into(),
-                memo: mint.memo.map(ToString::to_string),
-            }])
+        cache::with_balance_cache(|| {
+            Self::MintHook::hook(self, mint, |contract| {
+                contract.deposit_unchecked(mint.receiver_id, mint.amount)?;
+
+                debug!("mint of {} to {}", mint.amount, mint.receiver_id);
+
+                Nep141Event::FtMint(vec![FtMintData {
+                    owner_id: mint.receiver_id.clone(),
+                    amount: mint.amount.into(),
+                    memo: mint.memo.map(ToString::to_string),
+                }])
+                .emit();
+
+                Ok(())
+            })
+        })
+    }
+
+    fn burn(&mut self, burn: &Nep141Burn) -> Result<(), WithdrawError> {
+        cache::with_balance_cache(|| {
+            Self::BurnHook::hook(self, burn, |contract| {
+                contract.withdraw_unchecked(burn.owner_id, burn.amount)?;
+
+                debug!("burn of {} from {}", burn.amount, burn.owner_id);
+
+                Nep141Event::FtBurn(vec![FtBurnData {
+                    owner_id: burn.owner_id.clone(),
+                    amount: burn.amount.into(),
+                    memo: burn.memo.map(ToString::to_string),
+                }])
+                .emit();
+
+                Ok(())
+            })
+        })
+    }
+
+    fn mint_batch(
+        &mut self,
+        mints: &[(AccountId, u128)],
+        memo: Option<&str>,
+        origin: ActionOrigin,
+    ) -> Result<(), DepositError> {
+        let mut total_supply = self.total_supply();
+        for (_, amount) in mints {
+            total_supply = match total_supply.checked_add(*amount) {
+                Some(total_supply) => total_supply,
+                None => {
+                    return Err(TotalSupplyOverflowError {
+                        total_supply,
+                        amount: *amount,
+                    }
+                    .into())
+                }
+            };
+        }
+
+        cache::with_balance_cache(|| {
+            for (receiver_id, amount) in mints {
+                let mint = Nep141Mint {
+                    amount: *amount,
+                    receiver_id,
+                    memo,
+                    origin,
+                };
+
+                Self::MintHook::hook(self, &mint, |contract| {
+                    contract.deposit_unchecked(receiver_id, *amount)
+                })?;
+            }
+
+            Nep141Event::FtMint(
+                mints
+                    .iter()
+                    .map(|(owner_id, amount)| FtMintData {
+                        owner_id: owner_id.clone(),
+                        amount: (*amount).into(),
+                        memo: memo.map(ToString::to_string),
+                    })
+                    .collect(),
+            )
             .emit();
 
             Ok(())
         })
     }
 
-    fn burn(&mut self, burn: &Nep141Burn) -> Result<(), WithdrawError> {
-        Self::BurnHook::hook(self, burn, |contract| {
-            contract.withdraw_unchecked(burn.owner_id, burn.amount)?;
-
-            Nep141Event::FtBurn(vec![FtBurnData {
-                owner_id: burn.owner_id.clone(),
-                amount: burn.amount.into(),
-                memo: burn.memo.map(ToString::to_string),
-            }])
+    fn burn_batch(
+        &mut self,
+        burns: &[(AccountId, u128)],
+        memo: Option<&str>,
+        origin: ActionOrigin,
+    ) -> Result<(), WithdrawError> {
+        let mut total_supply = self.total_supply();
+        for (_, amount) in burns {
+            total_supply = match total_supply.checked_sub(*amount) {
+                Some(total_supply) => total_supply,
+                None => {
+                    return Err(TotalSupplyUnderflowError {
+                        total_supply,
+                        amount: *amount,
+                    }
+                    .into())
+                }
+            };
+        }
+
+        cache::with_balance_cache(|| {
+            for (owner_id, amount) in burns {
+                let burn = Nep141Burn {
+                    amount: *amount,
+                    owner_id,
+                    memo,
+                    origin,
+                };
+
+                Self::BurnHook::hook(self, &burn, |contract| {
+                    contract.withdraw_unchecked(owner_id, *amount)
+                })?;
+            }
+
+            Nep141Event::FtBurn(
+                burns
+                    .iter()
+                    .map(|(owner_id, amount)| FtBurnData {
+                        owner_id: owner_id.clone(),
+                        amount: (*amount).into(),
+                        memo: memo.map(ToString::to_string),
+                    })
+                    .collect(),
+            )
             .emit();
 
             Ok(())
         })
     }
+
+    fn sweep_zero_balances(&mut self, account_ids: &[AccountId]) -> u32 {
+        let mut removed = 0;
+
+        for account_id in account_ids {
+            let mut slot = balance_slot::<Self>(account_id);
+            if slot.read() == Some(0) {
+                cache::remove(&mut slot);
+                removed += 1;
+            }
+        }
+
+        removed
+    }
 }
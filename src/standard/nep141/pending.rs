@@ -0,0 +1,397 @@
+//! Two-phase ("push-pull") transfers for NEP-141 fungible tokens.
+//!
+//! A regular transfer credits the receiver immediately, so tokens sent to a
+//! mistyped or inaccessible account are lost. A pending transfer instead
+//! moves the tokens from the sender to the token contract's own account,
+//! where they are held until the receiver claims them. If the receiver does
+//! not claim them before the transfer expires, anyone may sweep the transfer,
+//! which refunds the tokens to the sender.
+//!
+//! Each leg is a regular NEP-141 transfer, so it invokes the contract's
+//! transfer hook and emits an `ft_transfer` event. Contracts that require
+//! storage registration (e.g. NEP-145) must register their own account.
+//!
+//! Enable with `#[nep141(pending_transfers)]`, which generates an
+//! implementation of [`Nep141PendingTransfersExternal`].
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+use thiserror::Error;
+
+use crate::{hook::ActionOrigin, slot::Slot, standard::nep297::Event, DefaultStorageKey};
+
+use super::{Nep141Controller, Nep141Transfer, TransferError};
+
+pub use ext::*;
+
+/// Events emitted by pending transfer operations, in addition to the regular
+/// NEP-141 `ft_transfer` events.
+#[event(
+    standard = "x-nep141-pending",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum Nep141PendingTransferEvent {
+    /// Tokens are held for a receiver.
+    PendingTransferCreate {
+        /// Identifier of the transfer.
+        id: U64,
+        /// Account that sent the tokens.
+        sender_id: AccountId,
+        /// Account that may claim the tokens.
+        receiver_id: AccountId,
+        /// Amount held.
+        amount: U128,
+        /// Block timestamp after which the transfer may be refunded, in
+        /// nanoseconds.
+        expiry: U64,
+    },
+    /// The receiver claimed a pending transfer.
+    PendingTransferClaim {
+        /// Identifier of the transfer.
+        id: U64,
+        /// Account that claimed the tokens.
+        receiver_id: AccountId,
+        /// Amount claimed.
+        amount: U128,
+    },
+    /// An expired pending transfer was refunded to the sender.
+    PendingTransferRefund {
+        /// Identifier of the transfer.
+        id: U64,
+        /// Account that received the refund.
+        sender_id: AccountId,
+        /// Amount refunded.
+        amount: U128,
+    },
+}
+
+/// Tokens held for a receiver.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingTransfer {
+    /// Account that sent the tokens.
+    pub sender_id: AccountId,
+    /// Account that may claim the tokens.
+    pub receiver_id: AccountId,
+    /// Amount held.
+    pub amount: U128,
+    /// Block timestamp after which the transfer may be refunded, in
+    /// nanoseconds.
+    pub expiry: U64,
+    /// Optional memo string.
+    pub memo: Option<String>,
+}
+
+impl PendingTransfer {
+    /// Returns `true` if the transfer has expired at `timestamp`.
+    pub fn is_expired_at(&self, timestamp: u64) -> bool {
+        timestamp >= self.expiry.0
+    }
+}
+
+/// Describes a pending transfer to create.
+#[derive(Clone, Debug, Serialize, BorshSerialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Nep141PendingTransferCreate<'a> {
+    /// Account that sends the tokens.
+    pub sender_id: &'a AccountId,
+    /// Account that may claim the tokens.
+    pub receiver_id: &'a AccountId,
+    /// Amount to hold.
+    pub amount: u128,
+    /// Block timestamp after which the transfer may be refunded, in
+    /// nanoseconds.
+    pub expiry: u64,
+    /// Optional memo string.
+    pub memo: Option<&'a str>,
+    /// Which method requested the action, passed on to the transfer. Not
+    /// serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
+}
+
+/// Errors that may occur when creating, claiming, or refunding a pending
+/// transfer.
+#[derive(Debug, Error)]
+pub enum PendingTransferError {
+    /// The expiry is not in the future.
+    #[error("Pending transfer expiry {expiry} is not in the future")]
+    InvalidExpiry {
+        /// The requested expiry.
+        expiry: u64,
+    },
+    /// There is no pending transfer with the identifier.
+    #[error("Pending transfer {0} does not exist")]
+    NotFound(u64),
+    /// The account is not the receiver of the pending transfer.
+    #[error("Account {account_id} is not the receiver of pending transfer {id}")]
+    NotReceiver {
+        /// Identifier of the transfer.
+        id: u64,
+        /// Account that attempted to claim.
+        account_id: AccountId,
+    },
+    /// The pending transfer has expired and may only be refunded.
+    #[error("Pending transfer {0} has expired")]
+    Expired(u64),
+    /// The pending transfer has not expired yet and may only be claimed.
+    #[error("Pending transfer {id} does not expire until {expiry}")]
+    NotExpired {
+        /// Identifier of the transfer.
+        id: u64,
+        /// Block timestamp at which the transfer expires.
+        expiry: u64,
+    },
+    /// The transfer failed.
+    #[error(transparent)]
+    Transfer(#[from] TransferError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    NextId,
+    PendingTransfer(u64),
+}
+
+/// Internal functions for [`Nep141PendingTransfers`]. Using these methods
+/// may result in unexpected behavior.
+pub trait Nep141PendingTransfersInternal {
+    /// Root storage slot.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep141Pending)
+    }
+
+    /// Slot for the identifier of the next pending transfer.
+    fn slot_next_id() -> Slot<u64> {
+        Self::root().field(StorageKey::NextId)
+    }
+
+    /// Slot for the pending transfer `id`.
+    fn slot_pending_transfer(id: u64) -> Slot<PendingTransfer> {
+        Self::root().field(StorageKey::PendingTransfer(id))
+    }
+}
+
+/// Non-public functions for managing pending transfers.
+pub trait Nep141PendingTransfers {
+    /// Returns the pending transfer `id`, if it has not been claimed or
+    /// refunded.
+    fn pending_transfer(&self, id: u64) -> Option<PendingTransfer>;
+
+    /// Moves tokens from the sender to the contract's account, to be held
+    /// until claimed or refunded. Returns the identifier of the transfer.
+    /// Performs no access checks.
+    ///
+    /// Emits `ft_transfer` and
+    /// `Nep141PendingTransferEvent::PendingTransferCreate` events.
+    fn create_pending_transfer(
+        &mut self,
+        create: &Nep141PendingTransferCreate<'_>,
+    ) -> Result<u64, PendingTransferError>;
+
+    /// Releases the tokens of an unexpired pending transfer to its receiver,
+    /// if `account_id` is the receiver. `origin` is the origin of the
+    /// transfer.
+    ///
+    /// Emits `ft_transfer` and
+    /// `Nep141PendingTransferEvent::PendingTransferClaim` events.
+    fn claim_pending_transfer(
+        &mut self,
+        id: u64,
+        account_id: &AccountId,
+        origin: ActionOrigin,
+    ) -> Result<PendingTransfer, PendingTransferError>;
+
+    /// Returns the tokens of an expired pending transfer to its sender.
+    /// Performs no access checks. `origin` is the origin of the transfer.
+    ///
+    /// Emits `ft_transfer` and
+    /// `Nep141PendingTransferEvent::PendingTransferRefund` events.
+    fn refund_pending_transfer(
+        &mut self,
+        id: u64,
+        origin: ActionOrigin,
+    ) -> Result<PendingTransfer, PendingTransferError>;
+}
+
+impl<T: Nep141PendingTransfersInternal + Nep141Controller> Nep141PendingTransfers for T {
+    fn pending_transfer(&self, id: u64) -> Option<PendingTransfer> {
+        Self::slot_pending_transfer(id).read()
+    }
+
+    fn create_pending_transfer(
+        &mut self,
+        create: &Nep141PendingTransferCreate<'_>,
+    ) -> Result<u64, PendingTransferError> {
+        if create.expiry <= env::block_timestamp() {
+            return Err(PendingTransferError::InvalidExpiry {
+                expiry: create.expiry,
+            });
+        }
+
+        self.transfer(&Nep141Transfer {
+            sender_id: create.sender_id,
+            receiver_id: &env::current_account_id(),
+            amount: create.amount,
+            memo: create.memo,
+            msg: None,
+            revert: false,
+            origin: create.origin,
+        })?;
+
+        let mut next_id = Self::slot_next_id();
+        let id = next_id.read().unwrap_or(0);
+        next_id.write(&(id + 1));
+
+        Self::slot_pending_transfer(id).write(&PendingTransfer {
+            sender_id: create.sender_id.clone(),
+            receiver_id: create.receiver_id.clone(),
+            amount: create.amount.into(),
+            expiry: create.expiry.into(),
+            memo: create.memo.map(ToString::to_string),
+        });
+
+        Nep141PendingTransferEvent::PendingTransferCreate {
+            id: id.into(),
+            sender_id: create.sender_id.clone(),
+            receiver_id: create.receiver_id.clone(),
+            amount: create.amount.into(),
+            expiry: create.expiry.into(),
+        }
+        .emit();
+
+        Ok(id)
+    }
+
+    fn claim_pending_transfer(
+        &mut self,
+        id: u64,
+        account_id: &AccountId,
+        origin: ActionOrigin,
+    ) -> Result<PendingTransfer, PendingTransferError> {
+        let mut slot = Self::slot_pending_transfer(id);
+        let pending = slot.read().ok_or(PendingTransferError::NotFound(id))?;
+
+        if account_id != &pending.receiver_id {
+            return Err(PendingTransferError::NotReceiver {
+                id,
+                account_id: account_id.clone(),
+            });
+        }
+
+        if pending.is_expired_at(env::block_timestamp()) {
+            return Err(PendingTransferError::Expired(id));
+        }
+
+        self.transfer(&Nep141Transfer {
+            sender_id: &env::current_account_id(),
+            receiver_id: &pending.receiver_id,
+            amount: pending.amount.0,
+            memo: pending.memo.as_deref(),
+            msg: None,
+            revert: false,
+            origin,
+        })?;
+
+        slot.remove();
+
+        Nep141PendingTransferEvent::PendingTransferClaim {
+            id: id.into(),
+            receiver_id: pending.receiver_id.clone(),
+            amount: pending.amount,
+        }
+        .emit();
+
+        Ok(pending)
+    }
+
+    fn refund_pending_transfer(
+        &mut self,
+        id: u64,
+        origin: ActionOrigin,
+    ) -> Result<PendingTransfer, PendingTransferError> {
+        let mut slot = Self::slot_pending_transfer(id);
+        let pending = slot.read().ok_or(PendingTransferError::NotFound(id))?;
+
+        if !pending.is_expired_at(env::block_timestamp()) {
+            return Err(PendingTransferError::NotExpired {
+                id,
+                expiry: pending.expiry.0,
+            });
+        }
+
+        self.transfer(&Nep141Transfer {
+            sender_id: &env::current_account_id(),
+            receiver_id: &pending.sender_id,
+            amount: pending.amount.0,
+            memo: pending.memo.as_deref(),
+            msg: None,
+            revert: false,
+            origin,
+        })?;
+
+        slot.remove();
+
+        Nep141PendingTransferEvent::PendingTransferRefund {
+            id: id.into(),
+            sender_id: pending.sender_id.clone(),
+            amount: pending.amount,
+        }
+        .emit();
+
+        Ok(pending)
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{
+        ext_contract,
+        json_types::{U128, U64},
+        AccountId,
+    };
+
+    use super::PendingTransfer;
+
+    /// Externally-accessible functions for pending transfers.
+    #[ext_contract(ext_nep141_pending_transfers)]
+    pub trait Nep141PendingTransfersExternal {
+        /// Holds `amount` of the predecessor's tokens until `receiver_id`
+        /// claims them, or until `expiry` (block timestamp in nanoseconds),
+        /// after which they may be refunded. Requires a deposit of at least
+        /// 1 yoctoNEAR, which must cover the storage fee; the excess is
+        /// refunded. Returns the identifier of the transfer.
+        fn ft_transfer_escrowed(
+            &mut self,
+            receiver_id: AccountId,
+            amount: U128,
+            expiry: U64,
+            memo: Option<String>,
+        ) -> U64;
+
+        /// Claims the pending transfer `id`, of which the predecessor must
+        /// be the receiver. Requires exactly 1 yoctoNEAR.
+        fn ft_pending_claim(&mut self, id: U64) -> U128;
+
+        /// Refunds the expired pending transfers among `ids` to their
+        /// senders. Transfers that do not exist or have not expired are
+        /// skipped. Callable by anyone. Returns the identifiers of the
+        /// refunded transfers.
+        fn ft_pending_sweep(&mut self, ids: Vec<U64>) -> Vec<U64>;
+
+        /// Returns the pending transfer `id`, if it has not been claimed or
+        /// refunded.
+        fn ft_pending_transfer(&self, id: U64) -> Option<PendingTransfer>;
+    }
+}
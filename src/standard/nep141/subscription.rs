@@ -0,0 +1,382 @@
+//! Subscription payments for NEP-141 fungible tokens.
+//!
+//! An account authorizes a merchant to pull up to a fixed amount of tokens
+//! per period. The merchant then charges the account whenever payment is due,
+//! without the account signing each payment. Charges are regular NEP-141
+//! transfers from the account to the merchant, so they invoke the contract's
+//! transfer hook and emit `ft_transfer` events.
+//!
+//! Periods are consecutive windows of fixed length, starting when the
+//! subscription is authorized. The amount charged during a period may not
+//! exceed the subscription's cap; unused amounts do not carry over.
+//!
+//! The [derive macro](near_sdk_contract_tools_macros::Nep141Subscription)
+//! generates external methods for authorizing, revoking, and charging
+//! subscriptions.
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+use thiserror::Error;
+
+use crate::{hook::ActionOrigin, slot::Slot, standard::nep297::Event, DefaultStorageKey};
+
+use super::{Nep141Controller, Nep141Transfer, TransferError};
+
+pub use ext::*;
+
+/// Events emitted by subscription operations, in addition to the regular
+/// NEP-141 `ft_transfer` events.
+#[event(
+    standard = "x-nep141-subscription",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum Nep141SubscriptionEvent {
+    /// An account authorized a merchant to charge it.
+    SubscriptionAuthorize {
+        /// Account that will be charged.
+        account_id: AccountId,
+        /// Account that may charge.
+        merchant_id: AccountId,
+        /// Maximum amount that may be charged per period.
+        max_amount: U128,
+        /// Length of a period, in nanoseconds.
+        period: U64,
+    },
+    /// An account revoked a merchant's authorization.
+    SubscriptionRevoke {
+        /// Account that will no longer be charged.
+        account_id: AccountId,
+        /// Account that may no longer charge.
+        merchant_id: AccountId,
+    },
+    /// A merchant charged an account.
+    SubscriptionCharge {
+        /// Account that was charged.
+        account_id: AccountId,
+        /// Account that charged.
+        merchant_id: AccountId,
+        /// Amount charged.
+        amount: U128,
+        /// Index of the period, counting from authorization.
+        period_index: U64,
+    },
+}
+
+/// Authorization for a merchant to charge an account.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Subscription {
+    /// Maximum amount that may be charged per period.
+    pub max_amount: U128,
+    /// Length of a period, in nanoseconds.
+    pub period: U64,
+    /// Block timestamp of the authorization, which starts the first period.
+    pub start: U64,
+    /// Index of the most recent period with a charge.
+    pub period_index: U64,
+    /// Amount charged during the period `period_index`.
+    pub charged: U128,
+}
+
+impl Subscription {
+    /// Returns the index of the period containing `timestamp`.
+    pub fn period_index_at(&self, timestamp: u64) -> u64 {
+        timestamp.saturating_sub(self.start.0) / self.period.0
+    }
+
+    /// Returns the amount that may still be charged during the period
+    /// containing `timestamp`.
+    pub fn remaining_at(&self, timestamp: u64) -> u128 {
+        if self.period_index_at(timestamp) == self.period_index.0 {
+            self.max_amount.0.saturating_sub(self.charged.0)
+        } else {
+            self.max_amount.0
+        }
+    }
+}
+
+/// Describes a subscription charge.
+#[derive(Clone, Debug, Serialize, BorshSerialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Nep141SubscriptionCharge<'a> {
+    /// Account to charge.
+    pub account_id: &'a AccountId,
+    /// Merchant receiving the tokens.
+    pub merchant_id: &'a AccountId,
+    /// Amount to charge.
+    pub amount: u128,
+    /// Optional memo string.
+    pub memo: Option<&'a str>,
+}
+
+/// Subscription periods must be longer than zero.
+#[derive(Debug, Error)]
+#[error("Subscription period must be greater than zero")]
+pub struct ZeroPeriodError;
+
+/// The merchant is not authorized to charge the account.
+#[derive(Debug, Error)]
+#[error("Account {account_id} has no subscription with merchant {merchant_id}")]
+pub struct SubscriptionNotFoundError {
+    /// Account to charge.
+    pub account_id: AccountId,
+    /// Merchant.
+    pub merchant_id: AccountId,
+}
+
+/// The charge would exceed the subscription's cap for the current period.
+#[derive(Debug, Error)]
+#[error("Subscription cap exceeded: {charged} already charged this period, cap {cap}, requested {amount}")]
+pub struct PeriodCapExceededError {
+    /// Amount already charged during the current period.
+    pub charged: u128,
+    /// Per-period cap.
+    pub cap: u128,
+    /// Amount of the failed charge.
+    pub amount: u128,
+}
+
+/// Errors that may occur when charging a subscription.
+#[derive(Debug, Error)]
+pub enum SubscriptionChargeError {
+    /// The merchant is not authorized to charge the account.
+    #[error(transparent)]
+    NotFound(#[from] SubscriptionNotFoundError),
+    /// The charge would exceed the cap for the current period.
+    #[error(transparent)]
+    CapExceeded(#[from] PeriodCapExceededError),
+    /// The transfer failed.
+    #[error(transparent)]
+    Transfer(#[from] TransferError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Subscription(AccountId, AccountId),
+}
+
+/// Internal functions for [`Nep141SubscriptionController`]. Using these
+/// methods may result in unexpected behavior.
+pub trait Nep141SubscriptionControllerInternal {
+    /// Root storage slot.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep141Subscription)
+    }
+
+    /// Slot for the subscription of `account_id` with `merchant_id`.
+    fn slot_subscription(account_id: &AccountId, merchant_id: &AccountId) -> Slot<Subscription> {
+        Self::root().field(StorageKey::Subscription(
+            account_id.clone(),
+            merchant_id.clone(),
+        ))
+    }
+}
+
+/// Non-public functions for managing subscription payments.
+pub trait Nep141SubscriptionController {
+    /// Returns the subscription of `account_id` with `merchant_id`, if any.
+    fn subscription(&self, account_id: &AccountId, merchant_id: &AccountId)
+        -> Option<Subscription>;
+
+    /// Authorizes `merchant_id` to charge `account_id` up to `max_amount`
+    /// per `period` nanoseconds, starting a new first period now. Replaces
+    /// any existing subscription. Performs no access checks.
+    ///
+    /// Emits a `Nep141SubscriptionEvent::SubscriptionAuthorize` event.
+    fn authorize_subscription(
+        &mut self,
+        account_id: &AccountId,
+        merchant_id: &AccountId,
+        max_amount: u128,
+        period: u64,
+    ) -> Result<(), ZeroPeriodError>;
+
+    /// Revokes the subscription of `account_id` with `merchant_id`. Returns
+    /// whether a subscription existed. Performs no access checks.
+    ///
+    /// Emits a `Nep141SubscriptionEvent::SubscriptionRevoke` event if a
+    /// subscription existed.
+    fn revoke_subscription(&mut self, account_id: &AccountId, merchant_id: &AccountId) -> bool;
+
+    /// Transfers tokens from the account to the merchant, if the
+    /// subscription's cap for the current period allows it. Performs no
+    /// access checks.
+    ///
+    /// Emits `ft_transfer` and `Nep141SubscriptionEvent::SubscriptionCharge`
+    /// events.
+    fn charge_subscription(
+        &mut self,
+        charge: &Nep141SubscriptionCharge<'_>,
+    ) -> Result<(), SubscriptionChargeError>;
+}
+
+impl<T: Nep141SubscriptionControllerInternal + Nep141Controller> Nep141SubscriptionController
+    for T
+{
+    fn subscription(
+        &self,
+        account_id: &AccountId,
+        merchant_id: &AccountId,
+    ) -> Option<Subscription> {
+        Self::slot_subscription(account_id, merchant_id).read()
+    }
+
+    fn authorize_subscription(
+        &mut self,
+        account_id: &AccountId,
+        merchant_id: &AccountId,
+        max_amount: u128,
+        period: u64,
+    ) -> Result<(), ZeroPeriodError> {
+        if period == 0 {
+            return Err(ZeroPeriodError);
+        }
+
+        Self::slot_subscription(account_id, merchant_id).write(&Subscription {
+            max_amount: max_amount.into(),
+            period: period.into(),
+            start: env::block_timestamp().into(),
+            period_index: 0.into(),
+            charged: 0.into(),
+        });
+
+        Nep141SubscriptionEvent::SubscriptionAuthorize {
+            account_id: account_id.clone(),
+            merchant_id: merchant_id.clone(),
+            max_amount: max_amount.into(),
+            period: period.into(),
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn revoke_subscription(&mut self, account_id: &AccountId, merchant_id: &AccountId) -> bool {
+        let existed = Self::slot_subscription(account_id, merchant_id)
+            .take()
+            .is_some();
+
+        if existed {
+            Nep141SubscriptionEvent::SubscriptionRevoke {
+                account_id: account_id.clone(),
+                merchant_id: merchant_id.clone(),
+            }
+            .emit();
+        }
+
+        existed
+    }
+
+    fn charge_subscription(
+        &mut self,
+        charge: &Nep141SubscriptionCharge<'_>,
+    ) -> Result<(), SubscriptionChargeError> {
+        let mut slot = Self::slot_subscription(charge.account_id, charge.merchant_id);
+        let mut subscription = slot.read().ok_or_else(|| SubscriptionNotFoundError {
+            account_id: charge.account_id.clone(),
+            merchant_id: charge.merchant_id.clone(),
+        })?;
+
+        let now = env::block_timestamp();
+        let remaining = subscription.remaining_at(now);
+
+        if charge.amount > remaining {
+            return Err(PeriodCapExceededError {
+                charged: subscription.max_amount.0 - remaining,
+                cap: subscription.max_amount.0,
+                amount: charge.amount,
+            }
+            .into());
+        }
+
+        let period_index = subscription.period_index_at(now);
+        subscription.charged = (subscription.max_amount.0 - remaining + charge.amount).into();
+        subscription.period_index = period_index.into();
+
+        self.transfer(&Nep141Transfer {
+            sender_id: charge.account_id,
+            receiver_id: charge.merchant_id,
+            amount: charge.amount,
+            memo: charge.memo,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        })?;
+
+        slot.write(&subscription);
+
+        Nep141SubscriptionEvent::SubscriptionCharge {
+            account_id: charge.account_id.clone(),
+            merchant_id: charge.merchant_id.clone(),
+            amount: charge.amount.into(),
+            period_index: period_index.into(),
+        }
+        .emit();
+
+        Ok(())
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{
+        ext_contract,
+        json_types::{U128, U64},
+        AccountId,
+    };
+
+    use super::Subscription;
+
+    /// Externally-accessible functions for subscription payments.
+    #[ext_contract(ext_nep141_subscription)]
+    pub trait Nep141SubscriptionExternal {
+        /// Authorizes `merchant_id` to charge the predecessor up to
+        /// `max_amount` per `period` nanoseconds. Requires a deposit of at
+        /// least 1 yoctoNEAR, which must cover the storage fee; the excess
+        /// is refunded.
+        fn ft_subscription_authorize(
+            &mut self,
+            merchant_id: AccountId,
+            max_amount: U128,
+            period: U64,
+        );
+
+        /// Revokes the subscription of the predecessor with `merchant_id`.
+        /// Requires exactly 1 yoctoNEAR. Returns whether a subscription
+        /// existed.
+        fn ft_subscription_revoke(&mut self, merchant_id: AccountId) -> bool;
+
+        /// Charges `account_id`, with the predecessor as the merchant.
+        /// Charges the full amount remaining in the current period if
+        /// `amount` is not specified. Requires exactly 1 yoctoNEAR. Returns
+        /// the amount charged.
+        fn ft_subscription_charge(
+            &mut self,
+            account_id: AccountId,
+            amount: Option<U128>,
+            memo: Option<String>,
+        ) -> U128;
+
+        /// Returns the subscription of `account_id` with `merchant_id`, if
+        /// any.
+        fn ft_subscription(
+            &self,
+            account_id: AccountId,
+            merchant_id: AccountId,
+        ) -> Option<Subscription>;
+
+        /// Returns the amount `merchant_id` may still charge `account_id`
+        /// during the current period.
+        fn ft_subscription_remaining(&self, account_id: AccountId, merchant_id: AccountId) -> U128;
+    }
+}
@@ -29,6 +29,23 @@ pub struct AccountNotRegisteredError(pub AccountId);
 #[error("Account {0} cannot unlock more tokens than it has deposited")]
 pub struct ExcessiveUnlockError(pub AccountId);
 
+/// Occurs when an account attempts to withdraw more than its available
+/// storage balance.
+#[derive(Debug, Error)]
+#[error(
+    "Account {account_id} has insufficient balance: {} available, but attempted to withdraw {}", available.0, attempted_to_withdraw.0
+)]
+pub struct ExcessiveWithdrawError {
+    /// The account that attempted to perform the operation.
+    pub account_id: AccountId,
+
+    /// The amount of storage balance available to the account.
+    pub available: U128,
+
+    /// The amount of storage balance the account attempted to withdraw.
+    pub attempted_to_withdraw: U128,
+}
+
 /// Occurs when an account attempts to withdraw more tokens than the contract
 /// allows without unregistering.
 #[derive(Debug, Error)]
@@ -103,6 +120,9 @@ pub enum StorageWithdrawError {
     /// The account is not registered.
     #[error(transparent)]
     AccountNotRegistered(#[from] AccountNotRegisteredError),
+    /// The account has insufficient available balance.
+    #[error(transparent)]
+    ExcessiveWithdraw(#[from] ExcessiveWithdrawError),
     /// The withdrawal does not meet the minimum balance requirement.
     #[error(transparent)]
     MinimumBalanceUnderrun(#[from] MinimumBalanceUnderrunError),
@@ -139,3 +159,16 @@ pub enum StorageAccountingError {
     #[error(transparent)]
     StorageUnlock(#[from] StorageUnlockError),
 }
+
+/// Occurs when storage balance bounds have a maximum below the minimum.
+#[derive(Debug, Error)]
+#[error(
+    "Invalid storage balance bounds: maximum {} is less than minimum {}", max.0, min.0
+)]
+pub struct InvalidBoundsError {
+    /// The requested minimum storage balance.
+    pub min: U128,
+
+    /// The requested maximum storage balance.
+    pub max: U128,
+}
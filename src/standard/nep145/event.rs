@@ -0,0 +1,81 @@
+//! Storage management events.
+//!
+//! NEP-145 does not specify any events, so registration state cannot
+//! otherwise be tracked by indexers. These events are emitted by the
+//! external methods generated by the `Nep145` derive macro.
+
+use near_sdk::{json_types::U128, serde::Serialize, AccountId};
+use near_sdk_contract_tools_macros::event;
+
+use super::{StorageBalance, StorageBalanceBounds};
+
+/// Events emitted by storage management methods.
+#[event(
+    crate = "crate",
+    macros = "crate",
+    standard = "x-storage",
+    version = "1.0.0"
+)]
+#[derive(Debug, Clone)]
+pub enum Nep145Event {
+    /// Storage balance was deposited for an account. Emitted when an account
+    /// registers, too.
+    StorageDeposit(Vec<StorageDepositData>),
+
+    /// Storage balance was withdrawn by an account.
+    StorageWithdraw(Vec<StorageWithdrawData>),
+
+    /// An account unregistered from storage management.
+    StorageUnregister(Vec<StorageUnregisterData>),
+
+    /// The contract's storage balance bounds changed.
+    StorageBoundsUpdate(StorageBoundsUpdateData),
+}
+
+/// Individual deposit metadata
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageDepositData {
+    /// Account for which storage balance was deposited
+    pub account_id: AccountId,
+    /// Amount deposited
+    pub amount: U128,
+    /// Storage balance after the deposit
+    pub balance: StorageBalance,
+}
+
+/// Individual withdrawal metadata
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageWithdrawData {
+    /// Account that withdrew storage balance
+    pub account_id: AccountId,
+    /// Amount withdrawn
+    pub amount: U128,
+    /// Storage balance after the withdrawal
+    pub balance: StorageBalance,
+}
+
+/// Individual unregistration metadata
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageUnregisterData {
+    /// Account that unregistered
+    pub account_id: AccountId,
+    /// Amount refunded to the account
+    pub refund: U128,
+    /// Whether the unregistration was forced
+    pub force: bool,
+}
+
+/// Storage balance bounds update metadata
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBoundsUpdateData {
+    /// Account that changed the bounds
+    pub updated_by: AccountId,
+    /// Bounds before the update
+    pub old_bounds: StorageBalanceBounds,
+    /// Bounds after the update
+    pub new_bounds: StorageBalanceBounds,
+}
@@ -19,6 +19,15 @@ use near_sdk::{ext_contract, json_types::U128, AccountId};
 /// - Withdraw some storage deposit by removing associated account data from
 ///     the contract and then making a call to remove unused deposit.
 /// - Unregister an account to recover full storage balance.
+#[cfg_attr(
+    feature = "client",
+    near_sdk_contract_tools_macros::ext_client(
+        name = "Nep145Client",
+        crate = "crate",
+        method(name = "storage_withdraw", deposit = 1),
+        method(name = "storage_unregister", deposit = 1),
+    )
+)]
 #[ext_contract(ext_nep145)]
 pub trait Nep145 {
     /// Payable method that receives an attached deposit of NEAR for a given account.
@@ -50,3 +59,48 @@ pub trait Nep145 {
     /// contract. See [`StorageBalanceBounds`] for more details.
     fn storage_balance_bounds(&self) -> StorageBalanceBounds;
 }
+
+/// Withdrawal of available storage balance to an account other than the
+/// predecessor, for custodial platforms that manage user deposits.
+#[ext_contract(ext_nep145_withdraw_to)]
+pub trait Nep145WithdrawTo {
+    /// Withdraw specified amount (default: all) of available NEAR for the
+    /// predecessor account, and transfer it to `beneficiary_id`. Requires
+    /// exactly 1 yoctoNEAR.
+    ///
+    /// Returns the updated storage balance record for the predecessor.
+    fn storage_withdraw_to(
+        &mut self,
+        beneficiary_id: AccountId,
+        amount: Option<U128>,
+    ) -> StorageBalance;
+}
+
+/// Owner-initiated withdrawal of available storage balance, for treasury
+/// sweeps.
+#[ext_contract(ext_nep145_owner_sweep)]
+pub trait Nep145OwnerSweep {
+    /// Withdraw specified amount (default: all) of available NEAR from
+    /// `account_id`, and transfer it to `beneficiary_id`. Only callable by the
+    /// owner, and requires exactly 1 yoctoNEAR.
+    ///
+    /// Returns the updated storage balance record for `account_id`.
+    fn storage_sweep(
+        &mut self,
+        account_id: AccountId,
+        beneficiary_id: AccountId,
+        amount: Option<U128>,
+    ) -> StorageBalance;
+}
+
+/// Adjustment of the storage balance bounds after deployment, e.g. when
+/// protocol storage costs change.
+#[ext_contract(ext_nep145_set_bounds)]
+pub trait Nep145SetBounds {
+    /// Sets the minimum and maximum storage balance. Only callable by the
+    /// owner, or by accounts with the configured role. Requires exactly 1
+    /// yoctoNEAR.
+    ///
+    /// Returns the new storage balance bounds.
+    fn storage_set_bounds(&mut self, min: U128, max: Option<U128>) -> StorageBalanceBounds;
+}
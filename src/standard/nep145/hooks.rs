@@ -2,13 +2,11 @@
 
 use near_sdk::{env, AccountId};
 
-use crate::{
-    hook::Hook,
-    standard::{
-        nep141::{Nep141Burn, Nep141Mint, Nep141Transfer},
-        nep171::action::{Nep171Burn, Nep171Mint, Nep171Transfer},
-    },
-};
+use crate::hook::Hook;
+#[cfg(feature = "nep141")]
+use crate::standard::nep141::{Nep141Burn, Nep141Mint, Nep141TransferAction};
+#[cfg(feature = "nep171")]
+use crate::standard::nep171::action::{Nep171Burn, Nep171Mint, Nep171TransferAction};
 
 use super::Nep145Controller;
 
@@ -44,44 +42,63 @@ impl<C: Nep145Controller, A> Hook<C, A> for PredecessorStorageAccountingHook {
     }
 }
 
-/// NEP-141 support for NEP-145.
+/// NEP-141 support for NEP-145. Requires the `nep141` feature.
+#[cfg(feature = "nep141")]
 pub struct Nep141StorageAccountingHook;
 
+#[cfg(feature = "nep141")]
 impl<C: Nep145Controller> Hook<C, Nep141Mint<'_>> for Nep141StorageAccountingHook {
     fn hook<R>(contract: &mut C, action: &Nep141Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
         apply_storage_accounting_hook(contract, action.receiver_id, f)
     }
 }
 
-impl<C: Nep145Controller> Hook<C, Nep141Transfer<'_>> for Nep141StorageAccountingHook {
-    fn hook<R>(contract: &mut C, action: &Nep141Transfer<'_>, f: impl FnOnce(&mut C) -> R) -> R {
-        apply_storage_accounting_hook(contract, action.receiver_id, f)
+#[cfg(feature = "nep141")]
+impl<C: Nep145Controller, T: Nep141TransferAction> Hook<C, T> for Nep141StorageAccountingHook {
+    fn hook<R>(contract: &mut C, action: &T, f: impl FnOnce(&mut C) -> R) -> R {
+        apply_storage_accounting_hook(contract, action.receiver_id(), f)
     }
 }
 
+#[cfg(feature = "nep141")]
 impl<C: Nep145Controller> Hook<C, Nep141Burn<'_>> for Nep141StorageAccountingHook {
     fn hook<R>(contract: &mut C, _action: &Nep141Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
         f(contract)
     }
 }
 
-/// NEP-171 support for NEP-145.
+/// NEP-171 support for NEP-145. Requires the `nep171` feature.
+#[cfg(feature = "nep171")]
 pub struct Nep171StorageAccountingHook;
 
+#[cfg(feature = "nep171")]
 impl<C: Nep145Controller> Hook<C, Nep171Mint<'_>> for Nep171StorageAccountingHook {
     fn hook<R>(contract: &mut C, action: &Nep171Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
         apply_storage_accounting_hook(contract, action.receiver_id, f)
     }
 }
 
-impl<C: Nep145Controller> Hook<C, Nep171Transfer<'_>> for Nep171StorageAccountingHook {
-    fn hook<R>(contract: &mut C, action: &Nep171Transfer<'_>, f: impl FnOnce(&mut C) -> R) -> R {
-        apply_storage_accounting_hook(contract, action.receiver_id, f)
+#[cfg(feature = "nep171")]
+impl<C: Nep145Controller, T: Nep171TransferAction> Hook<C, T> for Nep171StorageAccountingHook {
+    fn hook<R>(contract: &mut C, action: &T, f: impl FnOnce(&mut C) -> R) -> R {
+        apply_storage_accounting_hook(contract, action.receiver_id(), f)
     }
 }
 
+#[cfg(feature = "nep171")]
 impl<C: Nep145Controller> Hook<C, Nep171Burn<'_>> for Nep171StorageAccountingHook {
-    fn hook<R>(contract: &mut C, _action: &Nep171Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
-        f(contract)
+    fn hook<R>(contract: &mut C, action: &Nep171Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let storage_usage_start = env::storage_usage();
+
+        let r = f(contract);
+
+        // Refund the owner, if registered, for the storage released by the burn.
+        if contract.get_storage_balance(action.owner_id).is_ok() {
+            contract
+                .storage_refund(action.owner_id, storage_usage_start)
+                .unwrap_or_else(|e| env::panic_str(&format!("Storage accounting error: {}", e)));
+        }
+
+        r
     }
 }
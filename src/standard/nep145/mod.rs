@@ -8,13 +8,19 @@ use near_sdk::{
     env,
     json_types::U128,
     serde::{Deserialize, Serialize},
-    AccountId, BorshStorageKey,
+    AccountId, BorshStorageKey, Promise,
 };
 
-use crate::{hook::Hook, slot::Slot, DefaultStorageKey};
+use crate::{
+    hook::{ActionOrigin, Hook},
+    slot::Slot,
+    storage_version::StorageVersion,
+    DefaultStorageKey,
+};
 
 pub mod error;
 use error::*;
+pub mod event;
 mod ext;
 pub use ext::*;
 pub mod hooks;
@@ -68,6 +74,8 @@ impl Default for StorageBalanceBounds {
 enum StorageKey<'a> {
     BalanceBounds,
     Account(&'a AccountId),
+    #[cfg(feature = "hashed-account-keys")]
+    AccountKey([u8; 32]),
 }
 
 /// Describes a force unregister action.
@@ -78,8 +86,62 @@ pub struct Nep145ForceUnregister<'a> {
     pub account_id: &'a AccountId,
     /// The account's balance at the time of unregistration.
     pub balance: StorageBalance,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
+}
+
+/// Provides the price of storage used by [`Nep145Controller::storage_accounting`]
+/// and [`Nep145Controller::storage_refund`].
+///
+/// The default, [`EnvStorageByteCost`], uses the protocol price. Unit tests can
+/// pin a price with [`FixedStorageByteCost`], so that expected balances do not
+/// depend on the SDK version or test setup. A contract can also read the price
+/// from its own configuration, so that a change in the protocol price is
+/// applied explicitly rather than silently altering refunds.
+pub trait StorageByteCost {
+    /// Cost of storing one byte, in yoctoNEAR.
+    fn storage_byte_cost() -> u128;
+}
+
+/// The protocol price of storage, [`env::storage_byte_cost`].
+pub struct EnvStorageByteCost;
+
+impl StorageByteCost for EnvStorageByteCost {
+    fn storage_byte_cost() -> u128 {
+        env::storage_byte_cost()
+    }
+}
+
+/// A fixed price of `COST` yoctoNEAR per byte.
+pub struct FixedStorageByteCost<const COST: u128>;
+
+impl<const COST: u128> StorageByteCost for FixedStorageByteCost<COST> {
+    fn storage_byte_cost() -> u128 {
+        COST
+    }
+}
+
+/// Describes the registration of an account, i.e. the first deposit to its
+/// storage balance.
+#[derive(Clone, Debug, Serialize, BorshSerialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Nep145Register<'a> {
+    /// The account to be registered.
+    pub account_id: &'a AccountId,
+    /// The amount deposited.
+    pub amount: U128,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
 
+/// Storage layout version of the Nep145 component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Nep145", 1);
+
 /// NEP-145 Storage Management internal controller interface.
 pub trait Nep145ControllerInternal {
     /// NEP-145 lifecycle hook.
@@ -87,6 +149,14 @@ pub trait Nep145ControllerInternal {
     where
         Self: Sized;
 
+    /// NEP-145 lifecycle hook.
+    type RegisterHook: for<'a> Hook<Self, Nep145Register<'a>>
+    where
+        Self: Sized;
+
+    /// Price of storage used for storage accounting.
+    type ByteCost: StorageByteCost;
+
     /// Root storage slot.
     fn root() -> Slot<()> {
         Slot::new(DefaultStorageKey::Nep145)
@@ -98,11 +168,46 @@ pub trait Nep145ControllerInternal {
     }
 
     /// Storage slot for individual account balance.
+    #[cfg(not(feature = "hashed-account-keys"))]
+    fn slot_account(account_id: &AccountId) -> Slot<StorageBalance> {
+        Slot::new(StorageKey::Account(account_id))
+    }
+
+    /// Storage slot for individual account balance, keyed by
+    /// [`crate::utils::account_key`].
+    #[cfg(feature = "hashed-account-keys")]
     fn slot_account(account_id: &AccountId) -> Slot<StorageBalance> {
+        Self::root().field(StorageKey::AccountKey(crate::utils::account_key(
+            account_id,
+        )))
+    }
+
+    /// Storage slot for individual account balance written before the
+    /// `hashed-account-keys` feature was enabled. Balances are moved out of
+    /// it the next time the account's storage balance changes.
+    #[cfg(feature = "hashed-account-keys")]
+    fn slot_account_legacy(account_id: &AccountId) -> Slot<StorageBalance> {
         Slot::new(StorageKey::Account(account_id))
     }
 }
 
+/// Returns the slot holding the storage balance of `account_id`, moving the
+/// balance out of the legacy slot first. Without the `hashed-account-keys`
+/// feature, this is [`Nep145ControllerInternal::slot_account`].
+fn account_slot<T: Nep145ControllerInternal + ?Sized>(
+    account_id: &AccountId,
+) -> Slot<StorageBalance> {
+    #[allow(unused_mut)]
+    let mut slot = T::slot_account(account_id);
+
+    #[cfg(feature = "hashed-account-keys")]
+    if let Some(balance) = T::slot_account_legacy(account_id).take() {
+        slot.write(&balance);
+    }
+
+    slot
+}
+
 /// NEP-145 Storage Management controller interface. These functions are not directly
 /// exposed to the blockchain.
 pub trait Nep145Controller {
@@ -111,6 +216,14 @@ pub trait Nep145Controller {
     where
         Self: Sized;
 
+    /// NEP-145 lifecycle hook.
+    type RegisterHook: for<'a> Hook<Self, Nep145Register<'a>>
+    where
+        Self: Sized;
+
+    /// Price of storage used for storage accounting.
+    type ByteCost: StorageByteCost;
+
     /// Returns the storage balance of the given account.
     fn get_storage_balance(
         &self,
@@ -132,10 +245,23 @@ pub trait Nep145Controller {
     ) -> Result<StorageBalance, StorageUnlockError>;
 
     /// Deposits the given amount of storage balance for the given account.
+    /// The first deposit to an account registers it, and runs the
+    /// [`Nep145Controller::RegisterHook`].
     fn deposit_to_storage_account(
         &mut self,
         account_id: &AccountId,
         amount: U128,
+    ) -> Result<StorageBalance, StorageDepositError> {
+        self.deposit_to_storage_account_with_origin(account_id, amount, ActionOrigin::Internal)
+    }
+
+    /// Like [`Nep145Controller::deposit_to_storage_account`], with `origin`
+    /// as the origin of the [`Nep145Register`] action.
+    fn deposit_to_storage_account_with_origin(
+        &mut self,
+        account_id: &AccountId,
+        amount: U128,
+        origin: ActionOrigin,
     ) -> Result<StorageBalance, StorageDepositError>;
 
     /// Withdraws the given amount of storage balance for the given account.
@@ -145,6 +271,29 @@ pub trait Nep145Controller {
         amount: U128,
     ) -> Result<StorageBalance, StorageWithdrawError>;
 
+    /// Withdraws `amount` (default: all available) of the available storage
+    /// balance of `account_id`, and transfers it to `beneficiary_id`. Returns
+    /// the new storage balance and the amount withdrawn.
+    fn withdraw_to_beneficiary(
+        &mut self,
+        account_id: &AccountId,
+        beneficiary_id: &AccountId,
+        amount: Option<U128>,
+    ) -> Result<(StorageBalance, U128), StorageWithdrawError> {
+        let amount = match amount {
+            Some(amount) => amount,
+            None => self.get_storage_balance(account_id)?.available,
+        };
+
+        let balance = self.withdraw_from_storage_account(account_id, amount)?;
+
+        if amount.0 > 0 {
+            Promise::new(beneficiary_id.clone()).transfer(amount.0);
+        }
+
+        Ok((balance, amount))
+    }
+
     /// Unregisters the given account, returning the amount of storage balance
     /// that should be refunded.
     fn unregister_storage_account(
@@ -157,6 +306,16 @@ pub trait Nep145Controller {
     fn force_unregister_storage_account(
         &mut self,
         account_id: &AccountId,
+    ) -> Result<U128, StorageForceUnregisterError> {
+        self.force_unregister_storage_account_with_origin(account_id, ActionOrigin::Internal)
+    }
+
+    /// Like [`Nep145Controller::force_unregister_storage_account`], with
+    /// `origin` as the origin of the [`Nep145ForceUnregister`] action.
+    fn force_unregister_storage_account_with_origin(
+        &mut self,
+        account_id: &AccountId,
+        origin: ActionOrigin,
     ) -> Result<U128, StorageForceUnregisterError>;
 
     /// Returns the storage balance bounds for the contract.
@@ -178,13 +337,13 @@ pub trait Nep145Controller {
             Ordering::Equal => {}
             Ordering::Greater => {
                 let storage_consumed = storage_usage_end - storage_usage_start;
-                let storage_fee = env::storage_byte_cost() * storage_consumed as u128;
+                let storage_fee = Self::ByteCost::storage_byte_cost() * storage_consumed as u128;
 
                 Nep145Controller::lock_storage(self, account_id, storage_fee.into())?;
             }
             Ordering::Less => {
                 let storage_released = storage_usage_start - storage_usage_end;
-                let storage_credit = env::storage_byte_cost() * storage_released as u128;
+                let storage_credit = Self::ByteCost::storage_byte_cost() * storage_released as u128;
 
                 Nep145Controller::unlock_storage(self, account_id, storage_credit.into())?;
             }
@@ -192,18 +351,47 @@ pub trait Nep145Controller {
 
         Ok(())
     }
+
+    /// Credits storage released since `storage_usage_start` to the account's
+    /// balance, up to the amount the account currently has locked. Use after
+    /// removing data that may have been paid for by another account (e.g. a
+    /// burned token). Returns the amount credited.
+    fn storage_refund(
+        &mut self,
+        account_id: &AccountId,
+        storage_usage_start: u64,
+    ) -> Result<U128, StorageUnlockError> {
+        let storage_released = storage_usage_start.saturating_sub(env::storage_usage());
+        let balance = self.get_storage_balance(account_id)?;
+        let locked = balance.total.0 - balance.available.0;
+        let storage_credit = std::cmp::min(
+            Self::ByteCost::storage_byte_cost() * storage_released as u128,
+            locked,
+        );
+
+        if storage_credit > 0 {
+            Nep145Controller::unlock_storage(self, account_id, storage_credit.into())?;
+        }
+
+        Ok(storage_credit.into())
+    }
 }
 
 impl<T: Nep145ControllerInternal> Nep145Controller for T {
     type ForceUnregisterHook = <Self as Nep145ControllerInternal>::ForceUnregisterHook;
+    type RegisterHook = <Self as Nep145ControllerInternal>::RegisterHook;
+    type ByteCost = <Self as Nep145ControllerInternal>::ByteCost;
 
     fn get_storage_balance(
         &self,
         account_id: &AccountId,
     ) -> Result<StorageBalance, AccountNotRegisteredError> {
-        Self::slot_account(account_id)
-            .read()
-            .ok_or_else(|| AccountNotRegisteredError(account_id.clone()))
+        let balance = Self::slot_account(account_id).read();
+
+        #[cfg(feature = "hashed-account-keys")]
+        let balance = balance.or_else(|| Self::slot_account_legacy(account_id).read());
+
+        balance.ok_or_else(|| AccountNotRegisteredError(account_id.clone()))
     }
 
     fn lock_storage(
@@ -211,7 +399,7 @@ impl<T: Nep145ControllerInternal> Nep145Controller for T {
         account_id: &AccountId,
         amount: U128,
     ) -> Result<StorageBalance, StorageLockError> {
-        let mut account_slot = Self::slot_account(account_id);
+        let mut account_slot = account_slot::<Self>(account_id);
         let mut balance = account_slot
             .read()
             .ok_or(AccountNotRegisteredError(account_id.clone()))?;
@@ -237,7 +425,7 @@ impl<T: Nep145ControllerInternal> Nep145Controller for T {
         account_id: &AccountId,
         amount: U128,
     ) -> Result<StorageBalance, StorageUnlockError> {
-        let mut account_slot = Self::slot_account(account_id);
+        let mut account_slot = account_slot::<Self>(account_id);
 
         let mut balance = account_slot
             .read()
@@ -263,14 +451,17 @@ impl<T: Nep145ControllerInternal> Nep145Controller for T {
         Ok(balance)
     }
 
-    fn deposit_to_storage_account(
+    fn deposit_to_storage_account_with_origin(
         &mut self,
         account_id: &AccountId,
         amount: U128,
+        origin: ActionOrigin,
     ) -> Result<StorageBalance, StorageDepositError> {
-        let mut account_slot = Self::slot_account(account_id);
+        let mut account_slot = account_slot::<Self>(account_id);
 
-        let mut balance = account_slot.read().unwrap_or_default();
+        let existing = account_slot.read();
+        let is_registration = existing.is_none();
+        let mut balance = existing.unwrap_or_default();
 
         balance.total.0 = {
             let new_total = balance
@@ -304,7 +495,17 @@ impl<T: Nep145ControllerInternal> Nep145Controller for T {
 
         balance.available.0 += amount.0;
 
-        account_slot.write(&balance);
+        if is_registration {
+            let action = Nep145Register {
+                account_id,
+                amount,
+                origin,
+            };
+
+            Self::RegisterHook::hook(self, &action, |_| account_slot.write(&balance));
+        } else {
+            account_slot.write(&balance);
+        }
 
         Ok(balance)
     }
@@ -314,12 +515,23 @@ impl<T: Nep145ControllerInternal> Nep145Controller for T {
         account_id: &AccountId,
         amount: U128,
     ) -> Result<StorageBalance, StorageWithdrawError> {
-        let mut account_slot = Self::slot_account(account_id);
+        let mut account_slot = account_slot::<Self>(account_id);
 
         let mut balance = account_slot
             .read()
             .ok_or_else(|| AccountNotRegisteredError(account_id.clone()))?;
 
+        balance.available.0 =
+            balance
+                .available
+                .0
+                .checked_sub(amount.0)
+                .ok_or_else(|| ExcessiveWithdrawError {
+                    account_id: account_id.clone(),
+                    available: balance.available,
+                    attempted_to_withdraw: amount,
+                })?;
+
         balance.total.0 = {
             let bounds = self.get_storage_balance_bounds();
 
@@ -343,7 +555,7 @@ impl<T: Nep145ControllerInternal> Nep145Controller for T {
         &mut self,
         account_id: &AccountId,
     ) -> Result<U128, StorageUnregisterError> {
-        let mut account_slot = Self::slot_account(account_id);
+        let mut account_slot = account_slot::<Self>(account_id);
 
         let balance = account_slot
             .read()
@@ -366,11 +578,12 @@ impl<T: Nep145ControllerInternal> Nep145Controller for T {
         Ok(balance.total)
     }
 
-    fn force_unregister_storage_account(
+    fn force_unregister_storage_account_with_origin(
         &mut self,
         account_id: &AccountId,
+        origin: ActionOrigin,
     ) -> Result<U128, StorageForceUnregisterError> {
-        let mut account_slot = Self::slot_account(account_id);
+        let mut account_slot = account_slot::<Self>(account_id);
 
         let balance = account_slot
             .read()
@@ -379,6 +592,7 @@ impl<T: Nep145ControllerInternal> Nep145Controller for T {
         let action = Nep145ForceUnregister {
             account_id,
             balance,
+            origin,
         };
 
         Self::ForceUnregisterHook::hook(self, &action, |_| {
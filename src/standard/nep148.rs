@@ -1,15 +1,47 @@
 //! NEP-148 fungible token metadata implementation
 //! <https://github.com/near/NEPs/blob/master/neps/nep-0148.md>
+//!
+//! # Extensions
+//!
+//! Fields beyond those of the standard, e.g. the origin of a bridged token
+//! ([`BridgedAsset`]), are stored as [`FungibleTokenMetadata::extensions`].
+//! `ft_metadata` returns them alongside the standard fields:
+//!
+//! ```json
+//! {
+//!   "spec": "ft-1.0.0",
+//!   "name": "Wrapped Ether",
+//!   "symbol": "WETH",
+//!   "decimals": 18,
+//!   "origin_chain": "ethereum",
+//!   "origin_address": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+//!   "bridge_contract": "factory.bridge.near",
+//!   ...
+//! }
+//! ```
+//!
+//! Extensions are stored separately from the standard fields, so contracts
+//! deployed before they existed need no migration.
+
+use std::collections::HashMap;
 
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     env,
     json_types::Base64VecU8,
     serde::{Deserialize, Serialize},
-    BorshStorageKey,
+    AccountId, BorshStorageKey,
 };
 
-use crate::{slot::Slot, DefaultStorageKey};
+use crate::{
+    slot::Slot,
+    storage_version::StorageVersion,
+    utils::{
+        amount::{Amount, AmountError, Rounding},
+        reference::{self, ReferenceError},
+    },
+    DefaultStorageKey,
+};
 
 pub use ext::*;
 
@@ -39,6 +71,11 @@ pub struct FungibleTokenMetadata {
     /// Cosmetic. Number of base-10 decimal places to shift the floating point.
     /// 24 is a common value.
     pub decimals: u8,
+    /// Fields beyond those of the NEP-148 spec, serialized alongside them.
+    /// Stored separately from the other fields.
+    #[borsh_skip]
+    #[serde(flatten)]
+    pub extensions: HashMap<String, String>,
 }
 
 impl FungibleTokenMetadata {
@@ -52,6 +89,7 @@ impl FungibleTokenMetadata {
             reference: None,
             reference_hash: None,
             decimals,
+            extensions: HashMap::new(),
         }
     }
 
@@ -91,18 +129,118 @@ impl FungibleTokenMetadata {
         self
     }
 
+    /// Checks that `reference`, if set, comes with a `reference_hash`, and
+    /// that `reference_hash` is a SHA-256 hash.
+    pub fn validate_reference(&self) -> Result<(), ReferenceError> {
+        reference::validate_reference(
+            self.reference.as_deref(),
+            self.reference_hash.as_ref().map(|h| h.0.as_slice()),
+        )
+    }
+
+    /// Checks that `content`, the content of `reference`, hashes to
+    /// `reference_hash`.
+    pub fn verify_reference(&self, content: &[u8]) -> Result<(), ReferenceError> {
+        reference::verify_reference(
+            self.reference_hash.as_ref().map(|h| h.0.as_slice()),
+            content,
+        )
+    }
+
     /// Sets the decimals field.
     pub fn decimals(mut self, decimals: u8) -> Self {
         self.decimals = decimals;
         self
     }
+
+    /// Sets an extension field.
+    pub fn extension(mut self, key: String, value: String) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
+
+    /// Sets the extension fields describing a bridged token.
+    pub fn bridged_asset(mut self, bridged_asset: BridgedAsset) -> Self {
+        self.extensions.extend(bridged_asset.into_extensions());
+        self
+    }
+
+    /// Converts a number of whole tokens to an [`Amount`] using this token's
+    /// decimals.
+    ///
+    /// # Errors
+    ///
+    /// - If the resulting amount overflows.
+    pub fn amount_from_whole(&self, whole: u128) -> Result<Amount, AmountError> {
+        Amount::from_whole(whole, self.decimals)
+    }
+
+    /// Formats a raw amount as a decimal string of whole tokens using this
+    /// token's decimals, e.g. `"1.5"`.
+    pub fn format_amount(&self, amount: u128) -> String {
+        Amount::new(amount).to_decimal_string(self.decimals)
+    }
+
+    /// Converts an [`Amount`] to a number of whole tokens using this token's
+    /// decimals.
+    pub fn amount_to_whole(&self, amount: Amount, rounding: Rounding) -> Result<u128, AmountError> {
+        amount.to_whole(self.decimals, rounding)
+    }
+}
+
+/// Origin of a token bridged from another chain.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct BridgedAsset {
+    /// Name of the chain the token originates from, e.g. `"ethereum"`.
+    pub origin_chain: String,
+    /// Address of the token on its origin chain.
+    pub origin_address: String,
+    /// Account of the bridge that locks or mints the token.
+    pub bridge_contract: AccountId,
+}
+
+impl BridgedAsset {
+    /// Extension key of [`BridgedAsset::origin_chain`].
+    pub const ORIGIN_CHAIN: &'static str = "origin_chain";
+    /// Extension key of [`BridgedAsset::origin_address`].
+    pub const ORIGIN_ADDRESS: &'static str = "origin_address";
+    /// Extension key of [`BridgedAsset::bridge_contract`].
+    pub const BRIDGE_CONTRACT: &'static str = "bridge_contract";
+
+    /// Reads the bridged token fields from metadata extensions. Returns
+    /// `None` if any of them is missing or invalid.
+    pub fn from_extensions(extensions: &HashMap<String, String>) -> Option<Self> {
+        Some(Self {
+            origin_chain: extensions.get(Self::ORIGIN_CHAIN)?.clone(),
+            origin_address: extensions.get(Self::ORIGIN_ADDRESS)?.clone(),
+            bridge_contract: extensions.get(Self::BRIDGE_CONTRACT)?.parse().ok()?,
+        })
+    }
+
+    /// Converts to metadata extension fields.
+    pub fn into_extensions(self) -> [(String, String); 3] {
+        [
+            (Self::ORIGIN_CHAIN.to_string(), self.origin_chain),
+            (Self::ORIGIN_ADDRESS.to_string(), self.origin_address),
+            (
+                Self::BRIDGE_CONTRACT.to_string(),
+                self.bridge_contract.into(),
+            ),
+        ]
+    }
 }
 
 #[derive(BorshSerialize, BorshStorageKey)]
 enum StorageKey {
     Metadata,
+    MetadataVersion,
+    Extensions,
 }
 
+/// Storage layout version of the Nep148 component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Nep148", 1);
+
 /// Internal functions for [`Nep148Controller`].
 pub trait Nep148ControllerInternal {
     /// Returns the root storage slot for NEP-148.
@@ -114,6 +252,23 @@ pub trait Nep148ControllerInternal {
     fn metadata() -> Slot<FungibleTokenMetadata> {
         Self::root().field(StorageKey::Metadata)
     }
+
+    /// Returns the storage slot for the number of times the metadata has been
+    /// set.
+    fn metadata_version() -> Slot<u32> {
+        Self::root().field(StorageKey::MetadataVersion)
+    }
+
+    /// Returns the storage slot for NEP-148 metadata extensions.
+    fn metadata_extensions() -> Slot<Vec<(String, String)>> {
+        Self::root().field(StorageKey::Extensions)
+    }
+
+    /// Whether metadata must pass
+    /// [`FungibleTokenMetadata::validate_reference`] when it is set.
+    fn strict_reference() -> bool {
+        false
+    }
 }
 
 /// Management functions for NEP-148.
@@ -125,33 +280,96 @@ pub trait Nep148Controller {
     /// Panics if the metadata has not been set.
     fn get_metadata(&self) -> FungibleTokenMetadata;
 
-    /// Sets the metadata struct for this contract.
+    /// Sets the metadata struct (including extensions) for this contract
+    /// and increments the metadata version.
+    ///
+    /// # Panics
+    ///
+    /// If strict references are enabled and the reference hash is missing or
+    /// malformed.
     fn set_metadata(&mut self, metadata: &FungibleTokenMetadata);
+
+    /// Returns the origin of this token if it is bridged from another chain,
+    /// i.e. if the metadata extensions describe a [`BridgedAsset`].
+    fn get_bridged_asset(&self) -> Option<BridgedAsset>;
+
+    /// Returns the number of times the metadata has been set. Frontends can
+    /// use this to invalidate cached metadata.
+    fn get_metadata_version(&self) -> u32;
 }
 
 impl<T: Nep148ControllerInternal> Nep148Controller for T {
     fn get_metadata(&self) -> FungibleTokenMetadata {
-        Self::metadata()
+        let mut metadata = Self::metadata()
             .read()
-            .unwrap_or_else(|| env::panic_str(ERR_METADATA_UNSET))
+            .unwrap_or_else(|| env::panic_str(ERR_METADATA_UNSET));
+        metadata.extensions = Self::metadata_extensions()
+            .read()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        metadata
     }
 
     fn set_metadata(&mut self, metadata: &FungibleTokenMetadata) {
+        if Self::strict_reference() {
+            if let Err(e) = metadata.validate_reference() {
+                env::panic_str(&format!("Invalid token metadata: {e}"));
+            }
+        }
+
         Self::metadata().set(Some(metadata));
+
+        let mut extensions = metadata
+            .extensions
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        extensions.sort();
+        Self::metadata_extensions().set(Some(&extensions).filter(|e| !e.is_empty()));
+
+        Self::metadata_version().write(&(self.get_metadata_version() + 1));
+    }
+
+    fn get_bridged_asset(&self) -> Option<BridgedAsset> {
+        let extensions = Self::metadata_extensions().read().unwrap_or_default();
+        BridgedAsset::from_extensions(&extensions.into_iter().collect())
+    }
+
+    fn get_metadata_version(&self) -> u32 {
+        Self::metadata_version().read().unwrap_or(0)
     }
 }
 
 mod ext {
     #![allow(missing_docs)] // ext_contract doesn't play well
 
-    use near_sdk::ext_contract;
+    use near_sdk::{ext_contract, AccountId};
 
     use super::FungibleTokenMetadata;
 
     /// Contract that supports the NEP-148 metadata standard
+    #[cfg_attr(
+        feature = "client",
+        near_sdk_contract_tools_macros::ext_client(name = "Nep148Client", crate = "crate")
+    )]
     #[ext_contract(ext_nep148)]
     pub trait Nep148 {
         /// Returns the metadata struct for this contract.
         fn ft_metadata(&self) -> FungibleTokenMetadata;
     }
+
+    /// Views that format NEP-141 amounts using the NEP-148 decimals.
+    #[ext_contract(ext_nep148_human_views)]
+    pub trait Nep148HumanViews {
+        /// Returns the total supply as a decimal string of whole tokens.
+        fn ft_total_supply_human(&self) -> String;
+
+        /// Returns the balance of an account as a decimal string of whole
+        /// tokens.
+        fn ft_balance_of_human(&self, account_id: AccountId) -> String;
+
+        /// Returns the number of times the metadata has been set.
+        fn ft_metadata_version(&self) -> u32;
+    }
 }
@@ -5,8 +5,8 @@
 
 use super::*;
 use near_sdk::{
-    borsh::{self, BorshSerialize},
-    serde::Serialize,
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
 };
 
 /// NEP-171 mint action.
@@ -19,6 +19,10 @@ pub struct Nep171Mint<'a> {
     pub receiver_id: &'a AccountId,
     /// Optional memo string.
     pub memo: Option<&'a str>,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
 
 /// NEP-171 burn action.
@@ -29,8 +33,15 @@ pub struct Nep171Burn<'a> {
     pub token_ids: &'a [TokenId],
     /// Account ID of the owner.
     pub owner_id: &'a AccountId,
+    /// Account ID that performed the burn on behalf of the owner (e.g. using
+    /// a NEP-178 approval), if any. Reported in the `nft_burn` event.
+    pub authorized_id: Option<&'a AccountId>,
     /// Optional memo string.
     pub memo: Option<&'a str>,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
 
 /// Transfer metadata generic over both types of transfer (`nft_transfer` and
@@ -52,4 +63,168 @@ pub struct Nep171Transfer<'a> {
     pub msg: Option<&'a str>,
     /// `true` if the transfer is a revert for a `nft_transfer_call`.
     pub revert: bool,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
+}
+
+/// Owned counterpart of [`Nep171Transfer`], which can be stored (e.g. in a
+/// queue) and executed later with [`Nep171TransferAction::as_transfer`].
+#[derive(
+    Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug, Hash,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Nep171TransferOwned {
+    /// Why is this sender allowed to perform this transfer?
+    pub authorization: Nep171TransferAuthorization,
+    /// Sending account ID.
+    pub sender_id: AccountId,
+    /// Receiving account ID.
+    pub receiver_id: AccountId,
+    /// Token ID.
+    pub token_id: TokenId,
+    /// Optional memo string.
+    pub memo: Option<String>,
+    /// Message passed to contract located at `receiver_id` in the case of `nft_transfer_call`.
+    pub msg: Option<String>,
+    /// `true` if the transfer is a revert for a `nft_transfer_call`.
+    pub revert: bool,
+    /// Which method requested the action. Not serialized, so a deserialized
+    /// transfer is [`ActionOrigin::Internal`].
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
+}
+
+impl From<&Nep171Transfer<'_>> for Nep171TransferOwned {
+    fn from(transfer: &Nep171Transfer<'_>) -> Self {
+        Self {
+            authorization: transfer.authorization.clone(),
+            sender_id: transfer.sender_id.clone(),
+            receiver_id: transfer.receiver_id.clone(),
+            token_id: transfer.token_id.clone(),
+            memo: transfer.memo.map(ToString::to_string),
+            msg: transfer.msg.map(ToString::to_string),
+            revert: transfer.revert,
+            origin: transfer.origin,
+        }
+    }
+}
+
+impl From<Nep171Transfer<'_>> for Nep171TransferOwned {
+    fn from(transfer: Nep171Transfer<'_>) -> Self {
+        Self::from(&transfer)
+    }
+}
+
+impl<'a> From<&'a Nep171TransferOwned> for Nep171Transfer<'a> {
+    fn from(transfer: &'a Nep171TransferOwned) -> Self {
+        transfer.as_transfer()
+    }
+}
+
+/// A NEP-171 transfer, borrowed ([`Nep171Transfer`]) or owned
+/// ([`Nep171TransferOwned`]). Hooks that implement
+/// `Hook<C, T> where T: Nep171TransferAction` work with both.
+pub trait Nep171TransferAction {
+    /// Why is this sender allowed to perform this transfer?
+    fn authorization(&self) -> &Nep171TransferAuthorization;
+    /// Sending account ID.
+    fn sender_id(&self) -> &AccountId;
+    /// Receiving account ID.
+    fn receiver_id(&self) -> &AccountId;
+    /// Token ID.
+    fn token_id(&self) -> &TokenId;
+    /// Optional memo string.
+    fn memo(&self) -> Option<&str>;
+    /// Message passed to contract located at `receiver_id`.
+    fn msg(&self) -> Option<&str>;
+    /// Whether the transfer is a revert of a `nft_transfer_call`.
+    fn revert(&self) -> bool;
+    /// Which method requested the transfer.
+    fn origin(&self) -> ActionOrigin;
+
+    /// Borrows the transfer as a [`Nep171Transfer`], e.g. to pass it to
+    /// [`Nep171Controller::external_transfer`].
+    fn as_transfer(&self) -> Nep171Transfer<'_> {
+        Nep171Transfer {
+            authorization: self.authorization().clone(),
+            sender_id: self.sender_id(),
+            receiver_id: self.receiver_id(),
+            token_id: self.token_id(),
+            memo: self.memo(),
+            msg: self.msg(),
+            revert: self.revert(),
+            origin: self.origin(),
+        }
+    }
+}
+
+impl Nep171TransferAction for Nep171Transfer<'_> {
+    fn authorization(&self) -> &Nep171TransferAuthorization {
+        &self.authorization
+    }
+
+    fn sender_id(&self) -> &AccountId {
+        self.sender_id
+    }
+
+    fn receiver_id(&self) -> &AccountId {
+        self.receiver_id
+    }
+
+    fn token_id(&self) -> &TokenId {
+        self.token_id
+    }
+
+    fn memo(&self) -> Option<&str> {
+        self.memo
+    }
+
+    fn msg(&self) -> Option<&str> {
+        self.msg
+    }
+
+    fn revert(&self) -> bool {
+        self.revert
+    }
+
+    fn origin(&self) -> ActionOrigin {
+        self.origin
+    }
+}
+
+impl Nep171TransferAction for Nep171TransferOwned {
+    fn authorization(&self) -> &Nep171TransferAuthorization {
+        &self.authorization
+    }
+
+    fn sender_id(&self) -> &AccountId {
+        &self.sender_id
+    }
+
+    fn receiver_id(&self) -> &AccountId {
+        &self.receiver_id
+    }
+
+    fn token_id(&self) -> &TokenId {
+        &self.token_id
+    }
+
+    fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
+
+    fn msg(&self) -> Option<&str> {
+        self.msg.as_deref()
+    }
+
+    fn revert(&self) -> bool {
+        self.revert
+    }
+
+    fn origin(&self) -> ActionOrigin {
+        self.origin
+    }
 }
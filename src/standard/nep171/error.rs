@@ -3,8 +3,6 @@
 use near_sdk::AccountId;
 use thiserror::Error;
 
-use crate::standard::nep178::ApprovalId;
-
 use super::TokenId;
 
 /// Potential errors encountered when performing a burn operation.
@@ -87,7 +85,7 @@ pub struct SenderNotApprovedError {
     /// The ID of the token in question.
     pub token_id: TokenId,
     /// The approval ID that the sender tried to use to transfer the token.
-    pub approval_id: ApprovalId,
+    pub approval_id: u32,
 }
 
 /// Occurs when attempting to perform a transfer of a token from one
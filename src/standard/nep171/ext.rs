@@ -7,6 +7,19 @@ use near_sdk::{ext_contract, AccountId, PromiseOrValue};
 use super::TokenId;
 
 /// Interface of contracts that implement NEP-171.
+#[cfg_attr(
+    feature = "client",
+    near_sdk_contract_tools_macros::ext_client(
+        name = "Nep171Client",
+        crate = "crate",
+        method(name = "nft_transfer", deposit = 1),
+        method(
+            name = "nft_transfer_call",
+            deposit = 1,
+            gas = "crate::client::DEFAULT_TRANSFER_CALL_GAS"
+        ),
+    )
+)]
 #[ext_contract(ext_nep171)]
 pub trait Nep171 {
     /// Transfer a token.
@@ -28,21 +41,52 @@ pub trait Nep171 {
         msg: String,
     ) -> PromiseOrValue<bool>;
 
-    /// Get individual token information.
-    fn nft_token(&self, token_id: TokenId) -> Option<super::Token>;
+    /// Get individual token information. If `fields` is provided, only the
+    /// listed extension fields (e.g. `"metadata"`) are loaded, in addition
+    /// to `token_id` and `owner_id`.
+    fn nft_token(&self, token_id: TokenId, fields: Option<Vec<String>>) -> Option<super::Token>;
+}
+
+/// Batched owner lookup, for cheap validation of many tokens at once.
+#[ext_contract(ext_nep171_token_owners)]
+pub trait Nep171TokenOwners {
+    /// Owners of the given tokens, in the same order as `token_ids`. `None`
+    /// if the token does not exist.
+    fn nft_token_owners(&self, token_ids: Vec<TokenId>) -> Vec<Option<AccountId>>;
+}
+
+/// Non-standard view that pre-validates a transfer.
+#[ext_contract(ext_nep171_transfer_check)]
+pub trait Nep171TransferCheck {
+    /// Returns the reason a transfer of `token_id` from `sender_id` to
+    /// `receiver_id` would fail, or `None` if it would succeed. `approval_id`
+    /// is as for [`Nep171::nft_transfer`].
+    fn nft_transfer_check(
+        &self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u32>,
+    ) -> Option<String>;
 }
 
 /// Original token contract follow-up to [`Nep171::nft_transfer_call`].
 #[ext_contract(ext_nep171_resolver)]
 pub trait Nep171Resolver {
     /// Final method call on the original token contract during an
-    /// [`Nep171::nft_transfer_call`] promise chain.
+    /// [`Nep171::nft_transfer_call`] promise chain. `memo` is the memo of the
+    /// original transfer, and is attached to the revert transfer, if any.
+    ///
+    /// Must only be callable by the contract itself. Manual implementations
+    /// should call [`crate::utils::require_self_callback`] in addition to
+    /// marking the method `#[private]`.
     fn nft_resolve_transfer(
         &mut self,
         previous_owner_id: AccountId,
         receiver_id: AccountId,
         token_id: TokenId,
         approved_account_ids: Option<HashMap<AccountId, u64>>,
+        memo: Option<String>,
     ) -> bool;
 }
 
@@ -62,3 +106,21 @@ pub trait Nep171Receiver {
         msg: String,
     ) -> PromiseOrValue<bool>;
 }
+
+/// The arguments actually sent to `nft_on_transfer` by
+/// [`Nep171::nft_transfer_call`]: the standard [`Nep171Receiver`] arguments,
+/// plus the `memo` of the transfer. Receivers that implement
+/// [`Nep171Receiver`] ignore the extra argument; receivers that want the memo
+/// can accept an additional `memo: Option<String>` parameter.
+#[ext_contract(ext_nep171_memo_receiver)]
+pub trait Nep171MemoReceiver {
+    /// [`Nep171Receiver::nft_on_transfer`] with the transfer memo.
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+        memo: Option<String>,
+    ) -> PromiseOrValue<bool>;
+}
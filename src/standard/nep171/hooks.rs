@@ -1,16 +1,20 @@
 //! Hooks to integrate NEP-171 with other components.
 
-use crate::{
-    hook::Hook,
-    standard::{nep145::Nep145ForceUnregister, nep181::Nep181Controller},
-};
+use crate::hook::Hook;
+#[cfg(all(feature = "nep145", feature = "nep181"))]
+use crate::standard::{nep145::Nep145ForceUnregister, nep181::Nep181Controller};
 
-use super::{action::Nep171Burn, Nep171Controller};
+#[cfg(all(feature = "nep145", feature = "nep181"))]
+use super::action::Nep171Burn;
+use super::{action::Nep171Mint, Nep171Controller};
 
 /// Hook that burns all NEP-171 tokens held by an account when the account
-/// performs an NEP-145 force unregister.
+/// performs an NEP-145 force unregister. Requires the `nep145` and `nep181`
+/// features.
+#[cfg(all(feature = "nep145", feature = "nep181"))]
 pub struct BurnNep171OnForceUnregisterHook;
 
+#[cfg(all(feature = "nep145", feature = "nep181"))]
 impl<C> Hook<C, Nep145ForceUnregister<'_>> for BurnNep171OnForceUnregisterHook
 where
     C: Nep171Controller + Nep181Controller,
@@ -28,7 +32,9 @@ where
             .burn(&Nep171Burn {
                 token_ids: &token_ids,
                 owner_id: action.account_id,
+                authorized_id: None,
                 memo: Some("storage forced unregistration"),
+                origin: action.origin,
             })
             .unwrap_or_else(|e| {
                 near_sdk::env::panic_str(&format!(
@@ -39,3 +45,31 @@ where
         f(contract)
     }
 }
+
+/// Hook that rejects mints that would leave the receiver holding more than
+/// `LIMIT` tokens. Requires [`Nep171Controller::owned_token_count`], which
+/// is tracked when the contract enumerates tokens with NEP-181 and derives
+/// `Nep171` with `owner_token_count` (implied by `mint_limit_per_account`,
+/// and always on for `NonFungibleToken`).
+pub struct PerAccountMintLimitHook<const LIMIT: u64>;
+
+impl<C: Nep171Controller, const LIMIT: u64> Hook<C, Nep171Mint<'_>>
+    for PerAccountMintLimitHook<LIMIT>
+{
+    fn hook<R>(contract: &mut C, action: &Nep171Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let held = contract
+            .owned_token_count(action.receiver_id)
+            .unwrap_or_else(|| {
+                near_sdk::env::panic_str("Per-account mint limit requires receiver token counts")
+            });
+
+        if held.saturating_add(action.token_ids.len() as u64) > LIMIT {
+            near_sdk::env::panic_str(&format!(
+                "Account {} may hold at most {LIMIT} tokens",
+                action.receiver_id,
+            ));
+        }
+
+        f(contract)
+    }
+}
@@ -40,12 +40,19 @@
 use std::error::Error;
 
 use near_sdk::{
-    borsh::{self, BorshSerialize},
+    borsh::{self, BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
     AccountId, BorshStorageKey, Gas,
 };
 
-use crate::{hook::Hook, slot::Slot, standard::nep297::Event, DefaultStorageKey};
+use crate::{
+    hook::{ActionOrigin, Hook},
+    slot::Slot,
+    standard::nep297::Event,
+    storage_version::StorageVersion,
+    utils::log::debug,
+    DefaultStorageKey,
+};
 
 pub mod action;
 use action::*;
@@ -59,11 +66,11 @@ mod ext;
 pub use ext::*;
 pub mod hooks;
 
-/// Minimum required gas for [`Nep171Resolver::nft_resolve_transfer`] call in promise chain during [`Nep171::nft_transfer_call`].
+/// Minimum required gas for [`Nep171Resolver::nft_resolve_transfer`] call in promise chain during [`Nep171::nft_transfer_call`]. Matches `near-contract-standards` 4.1, and may be overridden at runtime with [`crate::gas::GasConfig`].
 pub const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
-/// Minimum gas required to execute the main body of [`Nep171::nft_transfer_call`] + gas for [`Nep171Resolver::nft_resolve_transfer`].
+/// Minimum gas required to execute the main body of [`Nep171::nft_transfer_call`] + gas for [`Nep171Resolver::nft_resolve_transfer`]. Matches `near-contract-standards` 4.1, and may be overridden at runtime with [`crate::gas::GasConfig`].
 pub const GAS_FOR_NFT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
-/// Error message when insufficient gas is attached to function calls with a minimum attached gas requirement (i.e. those that produce a promise chain, perform cross-contract calls).
+/// Error message when insufficient gas is attached to function calls with a minimum attached gas requirement (i.e. those that produce a promise chain, perform cross-contract calls). Generated methods use [`crate::utils::preflight::require_gas`] instead.
 pub const INSUFFICIENT_GAS_MESSAGE: &str = "More gas is required";
 
 /// NFT token IDs.
@@ -74,6 +81,10 @@ enum StorageKey<'a> {
     TokenOwner(&'a str),
 }
 
+/// Storage layout version of the Nep171 component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Nep171", 1);
+
 /// Internal (storage location) methods for implementors of [`Nep171Controller`].
 pub trait Nep171ControllerInternal {
     /// Hook for mint operations.
@@ -108,6 +119,13 @@ pub trait Nep171ControllerInternal {
     fn slot_token_owner(token_id: &TokenId) -> Slot<AccountId> {
         Self::root().field(StorageKey::TokenOwner(token_id))
     }
+
+    /// Number of tokens owned by `owner_id`, if tracked. Exposed as
+    /// [`Nep171Controller::owned_token_count`].
+    fn owner_token_count(owner_id: &AccountId) -> Option<u64> {
+        let _ = owner_id;
+        None
+    }
 }
 
 /// Non-public controller interface for NEP-171 implementations.
@@ -138,11 +156,22 @@ pub trait Nep171Controller {
     /// Transfer a token from `sender_id` to `receiver_id`, as for an external
     /// call to `nft_transfer`. Checks that the transfer is valid using
     /// [`CheckExternalTransfer::check_external_transfer`] before performing
-    /// the transfer. Emits events and runs relevant hooks.
+    /// the transfer. Emits events and runs relevant hooks. Unlike NEP-141
+    /// transfers, does not run in a
+    /// [`Transaction`](crate::transaction::Transaction): it cannot fail once
+    /// the hooks have run. See the [transaction
+    /// limits](crate::transaction#limits).
     fn external_transfer(&mut self, transfer: &Nep171Transfer) -> Result<(), Nep171TransferError>
     where
         Self: Sized;
 
+    /// Checks whether [`Nep171Controller::external_transfer`] would succeed,
+    /// using [`CheckExternalTransfer::check_external_transfer`], without
+    /// mutating state. Hooks are not invoked.
+    fn can_transfer(&self, transfer: &Nep171Transfer) -> Result<(), Nep171TransferError>
+    where
+        Self: Sized;
+
     /// Performs a token transfer without running [`CheckExternalTransfer::check_external_transfer`].
     /// Does not emit events or run hooks.
     ///
@@ -176,12 +205,35 @@ pub trait Nep171Controller {
     /// Returns the owner of a token, if it exists.
     fn token_owner(&self, token_id: &TokenId) -> Option<AccountId>;
 
+    /// Returns whether a token exists.
+    fn token_exists(&self, token_id: &TokenId) -> bool;
+
+    /// Returns the number of tokens owned by `owner_id`, if the contract
+    /// tracks it (e.g. with NEP-181 enumeration). Mint hooks run before the
+    /// tokens are recorded, so they see the count before the mint.
+    fn owned_token_count(&self, owner_id: &AccountId) -> Option<u64>;
+
+    /// Returns the owners of several tokens, in the same order as
+    /// `token_ids`. Does not load token metadata.
+    fn token_owners(&self, token_ids: &[TokenId]) -> Vec<Option<AccountId>> {
+        token_ids
+            .iter()
+            .map(|token_id| self.token_owner(token_id))
+            .collect()
+    }
+
     /// Loads the metadata associated with a token.
     fn load_token(&self, token_id: &TokenId) -> Option<Token>;
+
+    /// Loads the metadata associated with a token, skipping extensions
+    /// whose fields are not selected.
+    fn load_token_with(&self, token_id: &TokenId, fields: TokenFields<'_>) -> Option<Token>;
 }
 
 /// Authorization for a transfer.
-#[derive(Serialize, BorshSerialize, PartialEq, Eq, Clone, Debug, Hash)]
+#[derive(
+    Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug, Hash,
+)]
 #[serde(crate = "near_sdk::serde")]
 pub enum Nep171TransferAuthorization {
     /// The sender is the owner of the token.
@@ -267,8 +319,20 @@ impl<T: Nep171ControllerInternal> Nep171Controller for T {
                         transfer.receiver_id,
                     );
 
+                    debug!(
+                        "transfer of {} from {} to {}",
+                        transfer.token_id, current_owner_id, transfer.receiver_id,
+                    );
+
+                    let authorized_id = match transfer.authorization {
+                        Nep171TransferAuthorization::Owner => None,
+                        Nep171TransferAuthorization::ApprovalId(_) => {
+                            Some(transfer.sender_id.clone())
+                        }
+                    };
+
                     Nep171Event::NftTransfer(vec![NftTransferLog {
-                        authorized_id: None,
+                        authorized_id,
                         old_owner_id: current_owner_id,
                         new_owner_id: transfer.receiver_id.clone(),
                         token_ids: vec![transfer.token_id.clone()],
@@ -283,6 +347,10 @@ impl<T: Nep171ControllerInternal> Nep171Controller for T {
         }
     }
 
+    fn can_transfer(&self, transfer: &Nep171Transfer) -> Result<(), Nep171TransferError> {
+        Self::CheckExternalTransfer::check_external_transfer(self, transfer).map(|_| ())
+    }
+
     fn transfer_unchecked(&mut self, token_ids: &[TokenId], receiver_id: &AccountId) {
         for token_id in token_ids {
             let mut slot = Self::slot_token_owner(token_id);
@@ -315,6 +383,8 @@ impl<T: Nep171ControllerInternal> Nep171Controller for T {
         Self::MintHook::hook(self, action, |contract| {
             contract.mint_unchecked(action.token_ids, action.receiver_id);
 
+            debug!("mint of {:?} to {}", action.token_ids, action.receiver_id);
+
             Nep171Event::NftMint(vec![NftMintLog {
                 token_ids: action.token_ids.iter().map(ToString::to_string).collect(),
                 owner_id: action.receiver_id.clone(),
@@ -352,10 +422,12 @@ impl<T: Nep171ControllerInternal> Nep171Controller for T {
         Self::BurnHook::hook(self, action, |contract| {
             contract.burn_unchecked(action.token_ids);
 
+            debug!("burn of {:?} from {}", action.token_ids, action.owner_id);
+
             Nep171Event::NftBurn(vec![NftBurnLog {
                 token_ids: action.token_ids.iter().map(ToString::to_string).collect(),
                 owner_id: action.owner_id.clone(),
-                authorized_id: None,
+                authorized_id: action.authorized_id.cloned(),
                 memo: action.memo.map(ToString::to_string),
             }])
             .emit();
@@ -378,9 +450,21 @@ impl<T: Nep171ControllerInternal> Nep171Controller for T {
         Self::slot_token_owner(token_id).read()
     }
 
+    fn token_exists(&self, token_id: &TokenId) -> bool {
+        Self::slot_token_owner(token_id).exists()
+    }
+
+    fn owned_token_count(&self, owner_id: &AccountId) -> Option<u64> {
+        Self::owner_token_count(owner_id)
+    }
+
     fn load_token(&self, token_id: &TokenId) -> Option<Token> {
+        self.load_token_with(token_id, TokenFields::All)
+    }
+
+    fn load_token_with(&self, token_id: &TokenId, fields: TokenFields<'_>) -> Option<Token> {
         let mut metadata = std::collections::HashMap::new();
-        Self::LoadTokenMetadata::load(self, token_id, &mut metadata).ok()?;
+        Self::LoadTokenMetadata::load_with(self, token_id, &mut metadata, fields).ok()?;
         Some(Token {
             token_id: token_id.clone(),
             owner_id: self.token_owner(token_id)?,
@@ -390,6 +474,19 @@ impl<T: Nep171ControllerInternal> Nep171Controller for T {
 }
 
 /// Token information structure.
+///
+/// # Borsh schema
+///
+/// The Borsh serialization is stable, so tokens can be stored (e.g. as
+/// snapshots) and passed in Borsh-serialized cross-contract calls. It is
+/// the Borsh serialization of the tuple:
+///
+/// 1. `token_id: String`
+/// 2. `owner_id: AccountId` (as a `String`)
+/// 3. `extensions_metadata: Vec<(String, String)>`: each extension field
+///     and its value as compact JSON text, sorted by field name.
+///
+/// A [`TokenV1`] serialization is the same without the third field.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Token {
@@ -402,6 +499,122 @@ pub struct Token {
     pub extensions_metadata: std::collections::HashMap<String, near_sdk::serde_json::Value>,
 }
 
+// Extension values are stored as JSON text, since `Value` does not implement
+// Borsh, and sorted, since `HashMap` iteration order is not stable.
+impl BorshSerialize for Token {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let mut metadata = self
+            .extensions_metadata
+            .iter()
+            .map(|(field, value)| (field, value.to_string()))
+            .collect::<Vec<_>>();
+        metadata.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        BorshSerialize::serialize(&self.token_id, writer)?;
+        BorshSerialize::serialize(&self.owner_id, writer)?;
+        BorshSerialize::serialize(&metadata, writer)
+    }
+}
+
+impl BorshDeserialize for Token {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let token_id: TokenId = BorshDeserialize::deserialize(buf)?;
+        let owner_id: AccountId = BorshDeserialize::deserialize(buf)?;
+        let extensions_metadata = <Vec<(String, String)> as BorshDeserialize>::deserialize(buf)?
+            .into_iter()
+            .map(|(field, value)| Ok((field, near_sdk::serde_json::from_str(&value)?)))
+            .collect::<Result<_, near_sdk::serde_json::Error>>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        Ok(Self {
+            token_id,
+            owner_id,
+            extensions_metadata,
+        })
+    }
+}
+
+impl Token {
+    /// Reads a token serialized with Borsh as either a [`Token`] or a
+    /// [`TokenV1`], e.g. from snapshots stored by an earlier version of a
+    /// contract.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::borsh::BorshSerialize;
+    /// use near_sdk_contract_tools::standard::nep171::{Token, TokenV1};
+    ///
+    /// let v1 = TokenV1 {
+    ///     token_id: "1".to_string(),
+    ///     owner_id: "alice".parse().unwrap(),
+    /// };
+    ///
+    /// let token = Token::try_from_slice_compat(&v1.try_to_vec().unwrap()).unwrap();
+    /// assert_eq!(token, Token::from(v1));
+    /// ```
+    pub fn try_from_slice_compat(bytes: &[u8]) -> std::io::Result<Self> {
+        let mut buf = bytes;
+        let v1 = <TokenV1 as BorshDeserialize>::deserialize(&mut buf)?;
+        if buf.is_empty() {
+            return Ok(v1.into());
+        }
+
+        Self::try_from_slice(bytes)
+    }
+}
+
+/// The shape of [`Token`] before extensions could add metadata to it: only
+/// the token ID and the owner.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenV1 {
+    /// Token ID.
+    pub token_id: TokenId,
+    /// Current owner of the token.
+    pub owner_id: AccountId,
+}
+
+impl From<TokenV1> for Token {
+    fn from(token: TokenV1) -> Self {
+        Self {
+            token_id: token.token_id,
+            owner_id: token.owner_id,
+            extensions_metadata: Default::default(),
+        }
+    }
+}
+
+/// Drops the extension metadata.
+impl From<Token> for TokenV1 {
+    fn from(token: Token) -> Self {
+        Self {
+            token_id: token.token_id,
+            owner_id: token.owner_id,
+        }
+    }
+}
+
+/// Selects the extension fields of a [`Token`] to load, e.g. to skip
+/// loading a large approval map when only the owner is needed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenFields<'a> {
+    /// Every field.
+    All,
+    /// Only the listed fields.
+    Only(&'a [String]),
+}
+
+impl TokenFields<'_> {
+    /// Returns whether `field` is selected.
+    pub fn includes(&self, field: &str) -> bool {
+        match self {
+            TokenFields::All => true,
+            TokenFields::Only(fields) => fields.iter().any(|f| f == field),
+        }
+    }
+}
+
 /// Trait for NFT extensions to load token metadata.
 pub trait LoadTokenMetadata<C> {
     /// Load token metadata into `metadata`.
@@ -410,6 +623,25 @@ pub trait LoadTokenMetadata<C> {
         token_id: &TokenId,
         metadata: &mut std::collections::HashMap<String, near_sdk::serde_json::Value>,
     ) -> Result<(), Box<dyn Error>>;
+
+    /// Load the selected fields of token metadata into `metadata`. The
+    /// default implementation loads every field and discards the others;
+    /// extensions should override it to skip unselected fields entirely.
+    fn load_with(
+        contract: &C,
+        token_id: &TokenId,
+        metadata: &mut std::collections::HashMap<String, near_sdk::serde_json::Value>,
+        fields: TokenFields<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        if fields == TokenFields::All {
+            return Self::load(contract, token_id, metadata);
+        }
+
+        let mut loaded = std::collections::HashMap::new();
+        Self::load(contract, token_id, &mut loaded)?;
+        metadata.extend(loaded.into_iter().filter(|(key, _)| fields.includes(key)));
+        Ok(())
+    }
 }
 
 impl<C> LoadTokenMetadata<C> for () {
@@ -432,6 +664,17 @@ impl<C, T: LoadTokenMetadata<C>, U: LoadTokenMetadata<C>> LoadTokenMetadata<C> f
         U::load(contract, token_id, metadata)?;
         Ok(())
     }
+
+    fn load_with(
+        contract: &C,
+        token_id: &TokenId,
+        metadata: &mut std::collections::HashMap<String, near_sdk::serde_json::Value>,
+        fields: TokenFields<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        T::load_with(contract, token_id, metadata, fields)?;
+        U::load_with(contract, token_id, metadata, fields)?;
+        Ok(())
+    }
 }
 
 // further variations are technically unnecessary: just use (T, (U, V)) or ((T, U), V)
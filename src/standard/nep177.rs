@@ -13,22 +13,26 @@ use near_sdk::{
 use thiserror::Error;
 
 use crate::{
+    hook::ActionOrigin,
     slot::Slot,
     standard::{
         nep171::{
             action::{Nep171Burn, Nep171Mint},
             error::{Nep171BurnError, Nep171MintError, TokenDoesNotExistError},
             event::{Nep171Event, NftContractMetadataUpdateLog, NftMetadataUpdateLog},
-            LoadTokenMetadata, Nep171Controller, TokenId,
+            LoadTokenMetadata, Nep171Controller, TokenFields, TokenId,
         },
         nep297::Event,
     },
+    storage_version::StorageVersion,
+    utils::reference::{self, ReferenceError},
     DefaultStorageKey,
 };
 
 pub use ext::*;
 
 const CONTRACT_METADATA_NOT_INITIALIZED_ERROR: &str = "Contract metadata not initialized";
+const CONTRACT_METADATA_FROZEN_ERROR: &str = "Contract metadata is frozen";
 
 /// Non-fungible token contract metadata.
 #[derive(
@@ -78,6 +82,20 @@ impl ContractMetadata {
             reference_hash: None,
         }
     }
+
+    /// Checks that `reference`, if set, comes with a `reference_hash`, and
+    /// that `reference_hash` is a base64-encoded SHA-256 hash.
+    pub fn validate_reference(&self) -> Result<(), ReferenceError> {
+        let reference_hash = decode_reference_hash(self.reference_hash.as_deref())?;
+        reference::validate_reference(self.reference.as_deref(), reference_hash.as_deref())
+    }
+
+    /// Checks that `content`, the content of `reference`, hashes to
+    /// `reference_hash`.
+    pub fn verify_reference(&self, content: &[u8]) -> Result<(), ReferenceError> {
+        let reference_hash = decode_reference_hash(self.reference_hash.as_deref())?;
+        reference::verify_reference(reference_hash.as_deref(), content)
+    }
 }
 
 /// Non-fungible token metadata.
@@ -200,6 +218,106 @@ impl TokenMetadata {
         self.reference_hash = Some(reference_hash.into());
         self
     }
+
+    /// Checks that the metadata is well-formed:
+    ///
+    /// * `media` and `reference` are URLs (or paths relative to the contract's
+    ///     `base_uri`), and are accompanied by their hashes.
+    /// * `media_hash` and `reference_hash` are base64-encoded SHA-256 hashes.
+    pub fn validate(&self) -> Result<(), InvalidTokenMetadataError> {
+        validate_linked(
+            ("media", self.media.as_deref()),
+            ("media_hash", self.media_hash.as_deref()),
+        )?;
+        validate_linked(
+            ("reference", self.reference.as_deref()),
+            ("reference_hash", self.reference_hash.as_deref()),
+        )
+    }
+
+    /// Checks that `content`, the content of `reference`, hashes to
+    /// `reference_hash`.
+    pub fn verify_reference(&self, content: &[u8]) -> Result<(), ReferenceError> {
+        let reference_hash = decode_reference_hash(self.reference_hash.as_deref())?;
+        reference::verify_reference(reference_hash.as_deref(), content)
+    }
+
+    /// Finishes building the metadata, checking it with
+    /// [`TokenMetadata::validate`].
+    pub fn validated(self) -> Result<Self, InvalidTokenMetadataError> {
+        self.validate()?;
+        Ok(self)
+    }
+}
+
+fn decode_reference_hash(reference_hash: Option<&str>) -> Result<Option<Vec<u8>>, ReferenceError> {
+    reference_hash
+        .map(reference::decode_reference_hash)
+        .transpose()
+}
+
+/// Error returned when token metadata is malformed.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum InvalidTokenMetadataError {
+    /// A URL field is not shaped like a URL.
+    #[error("`{field}` is not a valid URL: {value:?}")]
+    InvalidUrl {
+        /// The URL field.
+        field: &'static str,
+        /// The invalid value.
+        value: String,
+    },
+    /// A URL field is set without its hash, or the hash is malformed.
+    #[error("{}", error.describe(field, hash_field))]
+    InvalidHash {
+        /// The URL field.
+        field: &'static str,
+        /// The hash field.
+        hash_field: &'static str,
+        /// What is wrong with the hash.
+        #[source]
+        error: ReferenceError,
+    },
+}
+
+fn validate_linked(
+    (field, url): (&'static str, Option<&str>),
+    (hash_field, hash): (&'static str, Option<&str>),
+) -> Result<(), InvalidTokenMetadataError> {
+    if let Some(url) = url {
+        if !is_url_shaped(url) {
+            return Err(InvalidTokenMetadataError::InvalidUrl {
+                field,
+                value: url.to_string(),
+            });
+        }
+    }
+
+    decode_reference_hash(hash)
+        .and_then(|hash| reference::validate_reference(url, hash.as_deref()))
+        .map_err(|error| InvalidTokenMetadataError::InvalidHash {
+            field,
+            hash_field,
+            error,
+        })
+}
+
+/// Whether `value` is an absolute URL (`scheme:rest`) or a path relative to a
+/// base URI. Only the shape is checked.
+fn is_url_shaped(value: &str) -> bool {
+    if value.is_empty() || value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+
+    match value.split_once("://") {
+        Some((scheme, rest)) => {
+            let mut scheme_chars = scheme.chars();
+            scheme_chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+                && scheme_chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+                && !rest.is_empty()
+        }
+        None => true,
+    }
 }
 
 /// Error returned when trying to load token metadata that does not exist.
@@ -223,14 +341,32 @@ impl<C: Nep177Controller> LoadTokenMetadata<C> for TokenMetadata {
         );
         Ok(())
     }
+
+    fn load_with(
+        contract: &C,
+        token_id: &TokenId,
+        metadata: &mut std::collections::HashMap<String, near_sdk::serde_json::Value>,
+        fields: TokenFields<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        if fields.includes("metadata") {
+            Self::load(contract, token_id, metadata)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(BorshSerialize, BorshStorageKey)]
 enum StorageKey<'a> {
     ContractMetadata,
     TokenMetadata(&'a TokenId),
+    ContractMetadataFrozen,
+    TokenMetadataFrozen(&'a TokenId),
 }
 
+/// Storage layout version of the Nep177 component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Nep177", 1);
+
 /// Internal functions for [`Nep177Controller`].
 pub trait Nep177ControllerInternal {
     /// Storage root.
@@ -247,11 +383,32 @@ pub trait Nep177ControllerInternal {
     fn slot_token_metadata(token_id: &TokenId) -> Slot<TokenMetadata> {
         Self::root().field(StorageKey::TokenMetadata(token_id))
     }
+
+    /// Storage slot for whether contract metadata is frozen.
+    fn slot_contract_metadata_frozen() -> Slot<bool> {
+        Self::root().field(StorageKey::ContractMetadataFrozen)
+    }
+
+    /// Storage slot for whether token metadata is frozen.
+    fn slot_token_metadata_frozen(token_id: &TokenId) -> Slot<bool> {
+        Self::root().field(StorageKey::TokenMetadataFrozen(token_id))
+    }
+
+    /// Whether token metadata must pass [`TokenMetadata::validate`] when a
+    /// token is minted or its metadata is updated, and contract metadata must
+    /// pass [`ContractMetadata::validate_reference`] when it is set.
+    fn strict_metadata() -> bool {
+        false
+    }
 }
 
 /// Functions for managing non-fungible tokens with attached metadata, NEP-177.
 pub trait Nep177Controller {
     /// Mint a new token with metadata.
+    ///
+    /// # Panics
+    ///
+    /// If strict metadata is enabled and the metadata is malformed.
     fn mint_with_metadata(
         &mut self,
         token_id: TokenId,
@@ -271,6 +428,11 @@ pub trait Nep177Controller {
     fn set_token_metadata_unchecked(&mut self, token_id: TokenId, metadata: Option<TokenMetadata>);
 
     /// Sets the metadata for a token ID and emits an [`Nep171Event::NftMetadataUpdate`] event.
+    /// Fails if the token's metadata is frozen.
+    ///
+    /// # Panics
+    ///
+    /// If strict metadata is enabled and the metadata is malformed.
     fn set_token_metadata(
         &mut self,
         token_id: TokenId,
@@ -278,6 +440,11 @@ pub trait Nep177Controller {
     ) -> Result<(), UpdateTokenMetadataError>;
 
     /// Sets the contract metadata and emits an [`Nep171Event::ContractMetadataUpdate`] event.
+    ///
+    /// # Panics
+    ///
+    /// If the contract metadata is frozen, or if strict metadata is enabled
+    /// and the reference hash is missing or malformed.
     fn set_contract_metadata(&mut self, metadata: ContractMetadata);
 
     /// Returns the contract metadata.
@@ -285,6 +452,26 @@ pub trait Nep177Controller {
 
     /// Returns the metadata for a token ID.
     fn token_metadata(&self, token_id: &TokenId) -> Option<TokenMetadata>;
+
+    /// Permanently prevents further updates to the metadata of a token.
+    fn freeze_token_metadata(&mut self, token_id: &TokenId) -> Result<(), TokenDoesNotExistError>;
+
+    /// Permanently prevents further updates to the contract metadata.
+    fn freeze_contract_metadata(&mut self);
+
+    /// Returns whether the metadata of a token is frozen.
+    fn is_token_metadata_frozen(&self, token_id: &TokenId) -> bool;
+
+    /// Returns whether the contract metadata is frozen.
+    fn is_contract_metadata_frozen(&self) -> bool;
+}
+
+/// Error returned when trying to update frozen token metadata.
+#[derive(Error, Clone, Debug)]
+#[error("Metadata of token {token_id} is frozen")]
+pub struct TokenMetadataFrozenError {
+    /// The token ID.
+    pub token_id: TokenId,
 }
 
 /// Error returned when a token update fails.
@@ -293,6 +480,17 @@ pub enum UpdateTokenMetadataError {
     /// The token does not exist.
     #[error(transparent)]
     TokenNotFound(#[from] TokenDoesNotExistError),
+    /// The token's metadata is frozen.
+    #[error(transparent)]
+    MetadataFrozen(#[from] TokenMetadataFrozenError),
+}
+
+fn require_valid_metadata<T: Nep177ControllerInternal>(metadata: &TokenMetadata) {
+    if T::strict_metadata() {
+        if let Err(e) = metadata.validate() {
+            env::panic_str(&format!("Invalid token metadata: {e}"));
+        }
+    }
 }
 
 impl<T: Nep177ControllerInternal + Nep171Controller> Nep177Controller for T {
@@ -301,15 +499,31 @@ impl<T: Nep177ControllerInternal + Nep171Controller> Nep177Controller for T {
         token_id: TokenId,
         metadata: TokenMetadata,
     ) -> Result<(), UpdateTokenMetadataError> {
-        if self.token_owner(&token_id).is_some() {
-            self.set_token_metadata_unchecked(token_id, Some(metadata));
-            Ok(())
-        } else {
-            Err(TokenDoesNotExistError { token_id }.into())
+        if self.token_owner(&token_id).is_none() {
+            return Err(TokenDoesNotExistError { token_id }.into());
         }
+
+        if self.is_token_metadata_frozen(&token_id) {
+            return Err(TokenMetadataFrozenError { token_id }.into());
+        }
+
+        require_valid_metadata::<Self>(&metadata);
+
+        self.set_token_metadata_unchecked(token_id, Some(metadata));
+        Ok(())
     }
 
     fn set_contract_metadata(&mut self, metadata: ContractMetadata) {
+        if self.is_contract_metadata_frozen() {
+            env::panic_str(CONTRACT_METADATA_FROZEN_ERROR);
+        }
+
+        if Self::strict_metadata() {
+            if let Err(e) = metadata.validate_reference() {
+                env::panic_str(&format!("Invalid contract metadata: {e}"));
+            }
+        }
+
         Self::slot_contract_metadata().set(Some(&metadata));
         Nep171Event::ContractMetadataUpdate(vec![NftContractMetadataUpdateLog { memo: None }])
             .emit();
@@ -321,11 +535,14 @@ impl<T: Nep177ControllerInternal + Nep171Controller> Nep177Controller for T {
         owner_id: AccountId,
         metadata: TokenMetadata,
     ) -> Result<(), Nep171MintError> {
+        require_valid_metadata::<Self>(&metadata);
+
         let token_ids = [token_id];
         let action = Nep171Mint {
             token_ids: &token_ids,
             receiver_id: &owner_id,
             memo: None,
+            origin: ActionOrigin::Internal,
         };
         self.mint(&action)?;
         let [token_id] = token_ids;
@@ -342,10 +559,13 @@ impl<T: Nep177ControllerInternal + Nep171Controller> Nep177Controller for T {
         let action = Nep171Burn {
             token_ids: &token_ids,
             owner_id,
+            authorized_id: None,
             memo: None,
+            origin: ActionOrigin::Internal,
         };
         self.burn(&action)?;
         let [token_id] = token_ids;
+        Self::slot_token_metadata_frozen(&token_id).remove();
         self.set_token_metadata_unchecked(token_id, None);
         Ok(())
     }
@@ -368,6 +588,29 @@ impl<T: Nep177ControllerInternal + Nep171Controller> Nep177Controller for T {
             .read()
             .unwrap_or_else(|| env::panic_str(CONTRACT_METADATA_NOT_INITIALIZED_ERROR))
     }
+
+    fn freeze_token_metadata(&mut self, token_id: &TokenId) -> Result<(), TokenDoesNotExistError> {
+        if self.token_owner(token_id).is_none() {
+            return Err(TokenDoesNotExistError {
+                token_id: token_id.clone(),
+            });
+        }
+
+        Self::slot_token_metadata_frozen(token_id).write(&true);
+        Ok(())
+    }
+
+    fn freeze_contract_metadata(&mut self) {
+        Self::slot_contract_metadata_frozen().write(&true);
+    }
+
+    fn is_token_metadata_frozen(&self, token_id: &TokenId) -> bool {
+        Self::slot_token_metadata_frozen(token_id).exists()
+    }
+
+    fn is_contract_metadata_frozen(&self) -> bool {
+        Self::slot_contract_metadata_frozen().exists()
+    }
 }
 
 // separate module with re-export because ext_contract doesn't play well with #![warn(missing_docs)]
@@ -376,8 +619,22 @@ mod ext {
 
     use super::*;
 
+    /// NEP-177 external interface.
+    #[cfg_attr(
+        feature = "client",
+        near_sdk_contract_tools_macros::ext_client(name = "Nep177Client", crate = "crate")
+    )]
     #[near_sdk::ext_contract(ext_nep177)]
     pub trait Nep177 {
+        /// Returns the contract metadata.
         fn nft_metadata(&self) -> ContractMetadata;
     }
+
+    /// Non-standard extension reporting whether metadata is frozen.
+    #[near_sdk::ext_contract(ext_nep177_freeze)]
+    pub trait Nep177Freeze {
+        /// Returns whether the metadata of `token_id` is frozen, or the
+        /// contract metadata if `token_id` is `None`.
+        fn nft_is_metadata_frozen(&self, token_id: Option<TokenId>) -> bool;
+    }
 }
@@ -20,6 +20,10 @@ pub struct Nep178Approve<'a> {
     /// Account ID of the target account. This account will be able to
     /// transfer the token.
     pub account_id: &'a AccountId,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
 
 /// NEP-178 revoke action.
@@ -34,6 +38,10 @@ pub struct Nep178Revoke<'a> {
     /// Account ID of the target account. This account will no longer be able
     /// to transfer the token.
     pub account_id: &'a AccountId,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
 
 /// NEP-178 revoke all action.
@@ -44,4 +52,8 @@ pub struct Nep178RevokeAll<'a> {
     pub token_id: &'a TokenId,
     /// Account ID of the current owner of the token.
     pub current_owner_id: &'a AccountId,
+    /// Which method requested the action. Not serialized.
+    #[serde(skip)]
+    #[borsh_skip]
+    pub origin: ActionOrigin,
 }
@@ -1,7 +1,7 @@
 //! NEP-178 errors.
 
 use super::{TokenId, MAX_APPROVALS};
-use near_sdk::AccountId;
+use near_sdk::{AccountId, Balance};
 use thiserror::Error;
 
 /// Occurs when an account is not authorized to manage approvals for a token.
@@ -35,6 +35,16 @@ pub struct TooManyApprovalsError {
     pub token_id: TokenId,
 }
 
+/// The attached deposit does not cover the storage cost of an approval.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Insufficient deposit for approval: attached {attached} yoctoNEAR < required {required} yoctoNEAR")]
+pub struct InsufficientApprovalDepositError {
+    /// The deposit required to cover the storage cost of the approval.
+    pub required: Balance,
+    /// The deposit attached to the call.
+    pub attached: Balance,
+}
+
 /// Errors that can occur when managing non-fungible token approvals.
 #[derive(Error, Debug)]
 pub enum Nep178ApproveError {
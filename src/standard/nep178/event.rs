@@ -0,0 +1,63 @@
+//! Event log metadata & associated structures.
+//!
+//! NEP-178 does not specify events. These follow the naming of the NEP-171
+//! events, under the `nep178` standard name.
+
+use near_sdk::{serde::Serialize, AccountId};
+use near_sdk_contract_tools_macros::event;
+
+use super::ApprovalId;
+use crate::standard::nep171::TokenId;
+
+/// NEP-178 approval events.
+#[event(
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros",
+    standard = "nep178",
+    version = "1.0.0"
+)]
+#[derive(Debug, Clone)]
+pub enum Nep178Event {
+    /// Emitted when an account is approved to transfer a token.
+    NftApprove(Vec<NftApproveLog>),
+    /// Emitted when an account's approval for a token is revoked.
+    NftRevoke(Vec<NftRevokeLog>),
+    /// Emitted when all approvals for a token are revoked.
+    NftRevokeAll(Vec<NftRevokeAllLog>),
+}
+
+/// An account is approved to transfer a token.
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftApproveLog {
+    /// ID of the token.
+    pub token_id: TokenId,
+    /// Owner of the token.
+    pub owner_id: AccountId,
+    /// Approved account.
+    pub account_id: AccountId,
+    /// ID of the new approval.
+    pub approval_id: ApprovalId,
+}
+
+/// An account's approval for a token is revoked.
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftRevokeLog {
+    /// ID of the token.
+    pub token_id: TokenId,
+    /// Owner of the token.
+    pub owner_id: AccountId,
+    /// Account that is no longer approved.
+    pub account_id: AccountId,
+}
+
+/// All approvals for a token are revoked.
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftRevokeAllLog {
+    /// ID of the token.
+    pub token_id: TokenId,
+    /// Owner of the token.
+    pub owner_id: AccountId,
+}
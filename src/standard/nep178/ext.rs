@@ -7,8 +7,21 @@ use super::*;
 /// NEP-178 external interface.
 ///
 /// See <https://github.com/near/NEPs/blob/master/neps/nep-0178.md#interface> for more details.
+#[cfg_attr(
+    feature = "client",
+    near_sdk_contract_tools_macros::ext_client(
+        name = "Nep178Client",
+        crate = "crate",
+        method(name = "nft_approve", returns = "near_sdk::serde_json::Value"),
+        method(name = "nft_revoke", deposit = 1),
+        method(name = "nft_revoke_all", deposit = 1),
+    )
+)]
 #[near_sdk::ext_contract(ext_nep178)]
 pub trait Nep178 {
+    /// Approves `account_id` for `token_id`. Requires a deposit that covers
+    /// the storage fee. Resolves to the result of `nft_on_approve`, if `msg`
+    /// was provided.
     fn nft_approve(
         &mut self,
         token_id: TokenId,
@@ -16,10 +29,15 @@ pub trait Nep178 {
         msg: Option<String>,
     ) -> PromiseOrValue<()>;
 
+    /// Revokes the approval of `account_id` for `token_id`. Requires 1
+    /// yoctoNEAR.
     fn nft_revoke(&mut self, token_id: TokenId, account_id: AccountId);
 
+    /// Revokes all approvals for `token_id`. Requires 1 yoctoNEAR.
     fn nft_revoke_all(&mut self, token_id: TokenId);
 
+    /// Returns whether `approved_account_id` is approved for `token_id`
+    /// (optionally, with the given approval ID).
     fn nft_is_approved(
         &self,
         token_id: TokenId,
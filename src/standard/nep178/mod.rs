@@ -1,23 +1,34 @@
 //! NEP-178 non-fungible token approval management implementation.
 //!
 //! Reference: <https://github.com/near/NEPs/blob/master/neps/nep-0178.md>
+//!
+//! [`Nep178Controller::approve`], [`Nep178Controller::revoke`], and
+//! [`Nep178Controller::revoke_all`] emit [`Nep178Event`]s, unless
+//! [`Nep178ControllerInternal::emit_events`] returns `false`. The `*_unchecked`
+//! functions do not emit events.
 use std::{collections::HashMap, error::Error};
 
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
     store::UnorderedMap,
-    AccountId, BorshStorageKey,
+    AccountId, Balance, BorshStorageKey, StorageUsage,
 };
 
 use crate::{
-    hook::Hook,
+    hook::{ActionOrigin, Hook},
     slot::Slot,
-    standard::nep171::{
-        action::{Nep171Burn, Nep171Mint, Nep171Transfer},
-        error::Nep171TransferError,
-        CheckExternalTransfer, DefaultCheckExternalTransfer, LoadTokenMetadata, Nep171Controller,
-        Nep171TransferAuthorization, TokenId,
+    standard::{
+        nep171::{
+            action::{Nep171Burn, Nep171Mint, Nep171Transfer, Nep171TransferAction},
+            error::Nep171TransferError,
+            CheckExternalTransfer, DefaultCheckExternalTransfer, LoadTokenMetadata,
+            Nep171Controller, Nep171TransferAuthorization, TokenFields, TokenId,
+        },
+        nep297::Event,
     },
+    storage_version::StorageVersion,
+    utils::preflight,
     DefaultStorageKey,
 };
 
@@ -25,6 +36,8 @@ pub mod action;
 use action::*;
 pub mod error;
 use error::*;
+pub mod event;
+use event::*;
 // separate module with re-export because ext_contract doesn't play well with #![warn(missing_docs)]
 mod ext;
 pub use ext::*;
@@ -33,6 +46,34 @@ pub use ext::*;
 pub type ApprovalId = u32;
 /// Maximum number of approvals per token.
 pub const MAX_APPROVALS: ApprovalId = 32;
+/// Default minimum deposit for `nft_approve`, in yoctoNEAR. NEP-178 requires
+/// a nonzero deposit, so that approvals cannot be created with a
+/// function-call access key.
+pub const DEFAULT_APPROVE_MIN_DEPOSIT: Balance = 1;
+
+/// Charges the attached deposit for the storage used since
+/// `storage_usage_start` (at least `min_deposit`), refunding the excess to
+/// the predecessor if `refund` is set. Returns the amount charged.
+pub fn charge_approval_storage(
+    storage_usage_start: StorageUsage,
+    min_deposit: Balance,
+    refund: bool,
+) -> Result<Balance, InsufficientApprovalDepositError> {
+    let storage_fee = Balance::from(env::storage_usage().saturating_sub(storage_usage_start))
+        * env::storage_byte_cost();
+    let required = Balance::max(storage_fee, min_deposit);
+    let attached = env::attached_deposit();
+
+    if attached < required {
+        return Err(InsufficientApprovalDepositError { required, attached });
+    }
+
+    if refund {
+        preflight::refund_excess_deposit(required);
+    }
+
+    Ok(required)
+}
 
 /// NFT token approvals. Hooks are implemented on this struct.
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -56,14 +97,25 @@ impl<C: Nep178Controller> LoadTokenMetadata<C> for TokenApprovals {
         );
         Ok(())
     }
+    fn load_with(
+        contract: &C,
+        token_id: &TokenId,
+        metadata: &mut std::collections::HashMap<String, near_sdk::serde_json::Value>,
+        fields: TokenFields<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        if fields.includes("approved_account_ids") {
+            Self::load(contract, token_id, metadata)?;
+        }
+        Ok(())
+    }
 }
 
 impl<C: Nep178Controller> Hook<C, Nep171Mint<'_>> for TokenApprovals {}
 
-impl<C: Nep178Controller> Hook<C, Nep171Transfer<'_>> for TokenApprovals {
-    fn hook<R>(contract: &mut C, args: &Nep171Transfer<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+impl<C: Nep178Controller, T: Nep171TransferAction> Hook<C, T> for TokenApprovals {
+    fn hook<R>(contract: &mut C, args: &T, f: impl FnOnce(&mut C) -> R) -> R {
         let r = f(contract);
-        contract.revoke_all_unchecked(args.token_id);
+        contract.revoke_all_unchecked(args.token_id());
         r
     }
 }
@@ -112,6 +164,10 @@ enum StorageKey<'a> {
     TokenApprovalsUnorderedMap(&'a TokenId),
 }
 
+/// Storage layout version of the Nep178 component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Nep178", 1);
+
 /// Internal functions for [`Nep178Controller`].
 pub trait Nep178ControllerInternal {
     /// Hook for approve operations.
@@ -132,6 +188,11 @@ pub trait Nep178ControllerInternal {
         Slot::root(DefaultStorageKey::Nep178)
     }
 
+    /// Whether the controller emits [`Nep178Event`]s.
+    fn emit_events() -> bool {
+        true
+    }
+
     /// Storage slot for token approvals.
     fn slot_token_approvals(token_id: &TokenId) -> Slot<TokenApprovals> {
         Self::root().field(StorageKey::TokenApprovals(token_id))
@@ -247,6 +308,16 @@ impl<T: Nep178ControllerInternal + Nep171Controller> Nep178Controller for T {
             approvals.next_approval_id += 1; // overflow unrealistic
             slot.write(&approvals);
 
+            if Self::emit_events() {
+                Nep178Event::NftApprove(vec![NftApproveLog {
+                    token_id: action.token_id.clone(),
+                    owner_id: action.current_owner_id.clone(),
+                    account_id: action.account_id.clone(),
+                    approval_id,
+                }])
+                .emit();
+            }
+
             Ok(approval_id)
         })
     }
@@ -294,6 +365,15 @@ impl<T: Nep178ControllerInternal + Nep171Controller> Nep178Controller for T {
             approvals.accounts.flush();
             slot.write(&approvals);
 
+            if Self::emit_events() {
+                Nep178Event::NftRevoke(vec![NftRevokeLog {
+                    token_id: action.token_id.clone(),
+                    owner_id: action.current_owner_id.clone(),
+                    account_id: action.account_id.clone(),
+                }])
+                .emit();
+            }
+
             Ok(())
         })
     }
@@ -311,6 +391,14 @@ impl<T: Nep178ControllerInternal + Nep171Controller> Nep178Controller for T {
         Self::RevokeAllHook::hook(self, action, |contract| {
             contract.revoke_all_unchecked(action.token_id);
 
+            if Self::emit_events() {
+                Nep178Event::NftRevokeAll(vec![NftRevokeAllLog {
+                    token_id: action.token_id.clone(),
+                    owner_id: action.current_owner_id.clone(),
+                }])
+                .emit();
+            }
+
             Ok(())
         })
     }
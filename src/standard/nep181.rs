@@ -1,6 +1,20 @@
 //! NEP-181 non-fungible token contract metadata implementation.
 //!
 //! Reference: <https://github.com/near/NEPs/blob/master/neps/nep-0181.md>
+//!
+//! # Hidden tokens
+//!
+//! Tokens can be hidden from enumeration with
+//! [`Nep181Controller::hide_token`], e.g. soulbound receipts or tokens that
+//! have not been revealed yet. Hidden tokens are removed from the
+//! enumeration index, so they are not returned by `nft_tokens` or
+//! `nft_tokens_for_owner` and not counted by `nft_total_supply` or
+//! `nft_supply_for_owner`. They can still be loaded by ID (`nft_token`),
+//! transferred, and burned. [`Nep181Controller::reveal_token`] adds a hidden
+//! token back to the index.
+//!
+//! With `#[nep181(hide_role = "...")]`, accounts with the role can hide and
+//! reveal tokens through [`Nep181Visibility`].
 use std::borrow::Cow;
 
 use near_sdk::{
@@ -10,7 +24,15 @@ use near_sdk::{
     AccountId, BorshStorageKey,
 };
 
-use crate::{hook::Hook, slot::Slot, standard::nep171::*, DefaultStorageKey};
+use thiserror::Error;
+
+use crate::{
+    hook::Hook,
+    slot::Slot,
+    standard::nep171::{error::TokenDoesNotExistError, *},
+    storage_version::StorageVersion,
+    DefaultStorageKey,
+};
 
 pub use ext::*;
 
@@ -25,26 +47,29 @@ impl<C: Nep171Controller + Nep181Controller> Hook<C, action::Nep171Mint<'_>> for
     }
 }
 
-impl<C: Nep171Controller + Nep181Controller> Hook<C, action::Nep171Transfer<'_>>
-    for TokenEnumeration
+impl<C, T> Hook<C, T> for TokenEnumeration
+where
+    C: Nep171Controller + Nep181Controller,
+    T: action::Nep171TransferAction,
 {
-    fn hook<R>(
-        contract: &mut C,
-        args: &action::Nep171Transfer<'_>,
-        f: impl FnOnce(&mut C) -> R,
-    ) -> R {
+    fn hook<R>(contract: &mut C, args: &T, f: impl FnOnce(&mut C) -> R) -> R {
         let r = f(contract);
-        let owner_id = match args.authorization {
-            Nep171TransferAuthorization::Owner => Cow::Borrowed(args.sender_id),
-            Nep171TransferAuthorization::ApprovalId(_) => Cow::Owned(contract.token_owner(args.token_id).unwrap_or_else(|| {
-                env::panic_str(&format!("Inconsistent state: Enumeration reconciliation should only run after a token has been transferred, but token {} does not exist.", args.token_id))
+
+        if contract.is_token_hidden(args.token_id()) {
+            return r;
+        }
+
+        let owner_id = match args.authorization() {
+            Nep171TransferAuthorization::Owner => Cow::Borrowed(args.sender_id()),
+            Nep171TransferAuthorization::ApprovalId(_) => Cow::Owned(contract.token_owner(args.token_id()).unwrap_or_else(|| {
+                env::panic_str(&format!("Inconsistent state: Enumeration reconciliation should only run after a token has been transferred, but token {} does not exist.", args.token_id()))
             })),
         };
 
         contract.transfer_token_enumeration(
-            std::array::from_ref(args.token_id),
+            std::array::from_ref(args.token_id()),
             owner_id.as_ref(),
-            args.receiver_id,
+            args.receiver_id(),
         );
         r
     }
@@ -54,6 +79,9 @@ impl<C: Nep171Controller + Nep181Controller> Hook<C, action::Nep171Burn<'_>> for
     fn hook<R>(contract: &mut C, args: &action::Nep171Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
         let r = f(contract);
         contract.remove_tokens_from_enumeration(args.token_ids, args.owner_id);
+        for token_id in args.token_ids {
+            contract.reveal_token_unchecked(token_id);
+        }
         r
     }
 }
@@ -62,8 +90,13 @@ impl<C: Nep171Controller + Nep181Controller> Hook<C, action::Nep171Burn<'_>> for
 enum StorageKey<'a> {
     Tokens,
     OwnerTokens(&'a AccountId),
+    Hidden(&'a TokenId),
 }
 
+/// Storage layout version of the Nep181 component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Nep181", 1);
+
 /// Internal functions for [`Nep181Controller`].
 pub trait Nep181ControllerInternal {
     /// Storage root.
@@ -80,6 +113,23 @@ pub trait Nep181ControllerInternal {
     fn slot_owner_tokens(owner_id: &AccountId) -> Slot<UnorderedSet<TokenId>> {
         Self::root().field(StorageKey::OwnerTokens(owner_id))
     }
+
+    /// Storage slot for whether a token is hidden from enumeration.
+    fn slot_hidden(token_id: &TokenId) -> Slot<bool> {
+        Self::root().field(StorageKey::Hidden(token_id))
+    }
+}
+
+/// The per-owner token index is too large to compact in one call.
+#[derive(Error, Clone, Debug)]
+#[error("Token index for {owner_id} contains {len} tokens, more than the limit of {limit}")]
+pub struct OwnerIndexTooLargeError {
+    /// Owner of the index.
+    pub owner_id: AccountId,
+    /// Number of tokens in the index.
+    pub len: u32,
+    /// Maximum number of tokens to rewrite.
+    pub limit: u32,
 }
 
 /// Functions for managing non-fungible tokens with attached metadata, NEP-181.
@@ -128,6 +178,46 @@ pub trait Nep181Controller {
         owner_id: &AccountId,
         f: impl FnOnce(&UnorderedSet<TokenId>) -> T,
     ) -> T;
+
+    /// Rewrites the set of tokens owned by an account, releasing the storage
+    /// left behind by removed tokens. Fails without modifying storage if the
+    /// account owns more than `limit` tokens.
+    fn compact_owner_index(
+        &mut self,
+        owner_id: &AccountId,
+        limit: u32,
+    ) -> Result<(), OwnerIndexTooLargeError>;
+
+    /// Whether a token is hidden from enumeration.
+    fn is_token_hidden(&self, token_id: &TokenId) -> bool;
+
+    /// Hides a token from enumeration. It can still be loaded by ID.
+    /// Hiding a hidden token has no effect. Performs no access checks.
+    fn hide_token(&mut self, token_id: &TokenId) -> Result<(), TokenDoesNotExistError>;
+
+    /// Adds a hidden token back to enumeration. Revealing a token that is
+    /// not hidden has no effect. Performs no access checks.
+    fn reveal_token(&mut self, token_id: &TokenId) -> Result<(), TokenDoesNotExistError>;
+
+    /// Clears the hidden flag of a token without adding it back to
+    /// enumeration.
+    ///
+    /// # Warning
+    ///
+    /// Does not perform consistency checks. May cause inconsistent state if
+    /// the token still exists.
+    fn reveal_token_unchecked(&mut self, token_id: &TokenId);
+}
+
+/// Writes `set` to `slot`, or removes the set entirely if it is empty.
+fn write_or_remove(slot: &mut Slot<UnorderedSet<TokenId>>, mut set: UnorderedSet<TokenId>) {
+    if set.is_empty() {
+        // Also removes vacant entries left behind by removed elements.
+        set.clear();
+        slot.remove();
+    } else {
+        slot.write(&set);
+    }
 }
 
 impl<T: Nep181ControllerInternal + Nep171Controller> Nep181Controller for T {
@@ -157,7 +247,7 @@ impl<T: Nep181ControllerInternal + Nep171Controller> Nep181Controller for T {
             for token_id in token_ids {
                 all_tokens.remove(token_id);
             }
-            all_tokens_slot.write(&all_tokens);
+            write_or_remove(&mut all_tokens_slot, all_tokens);
         }
 
         let mut owner_tokens_slot = Self::slot_owner_tokens(owner_id);
@@ -165,7 +255,7 @@ impl<T: Nep181ControllerInternal + Nep171Controller> Nep181Controller for T {
             for token_id in token_ids {
                 owner_tokens.remove(token_id);
             }
-            owner_tokens_slot.write(&owner_tokens);
+            write_or_remove(&mut owner_tokens_slot, owner_tokens);
         }
     }
 
@@ -180,7 +270,7 @@ impl<T: Nep181ControllerInternal + Nep171Controller> Nep181Controller for T {
             for token_id in token_ids {
                 from_owner_tokens.remove(token_id);
             }
-            from_owner_tokens_slot.write(&from_owner_tokens);
+            write_or_remove(&mut from_owner_tokens_slot, from_owner_tokens);
         }
 
         let mut to_owner_tokens_slot = Self::slot_owner_tokens(to_owner_id);
@@ -216,6 +306,71 @@ impl<T: Nep181ControllerInternal + Nep171Controller> Nep181Controller for T {
             .read()
             .unwrap_or_else(|| UnorderedSet::new(StorageKey::OwnerTokens(owner_id))))
     }
+
+    fn compact_owner_index(
+        &mut self,
+        owner_id: &AccountId,
+        limit: u32,
+    ) -> Result<(), OwnerIndexTooLargeError> {
+        let mut owner_tokens_slot = Self::slot_owner_tokens(owner_id);
+        let mut owner_tokens = match owner_tokens_slot.read() {
+            Some(owner_tokens) => owner_tokens,
+            None => return Ok(()),
+        };
+
+        if owner_tokens.len() > limit {
+            return Err(OwnerIndexTooLargeError {
+                owner_id: owner_id.clone(),
+                len: owner_tokens.len(),
+                limit,
+            });
+        }
+
+        let token_ids = owner_tokens.iter().cloned().collect::<Vec<_>>();
+        owner_tokens.clear();
+        owner_tokens.extend(token_ids);
+        write_or_remove(&mut owner_tokens_slot, owner_tokens);
+
+        Ok(())
+    }
+
+    fn is_token_hidden(&self, token_id: &TokenId) -> bool {
+        Self::slot_hidden(token_id).exists()
+    }
+
+    fn hide_token(&mut self, token_id: &TokenId) -> Result<(), TokenDoesNotExistError> {
+        let owner_id = self
+            .token_owner(token_id)
+            .ok_or_else(|| TokenDoesNotExistError {
+                token_id: token_id.clone(),
+            })?;
+
+        if !self.is_token_hidden(token_id) {
+            self.remove_tokens_from_enumeration(std::array::from_ref(token_id), &owner_id);
+            Self::slot_hidden(token_id).write(&true);
+        }
+
+        Ok(())
+    }
+
+    fn reveal_token(&mut self, token_id: &TokenId) -> Result<(), TokenDoesNotExistError> {
+        let owner_id = self
+            .token_owner(token_id)
+            .ok_or_else(|| TokenDoesNotExistError {
+                token_id: token_id.clone(),
+            })?;
+
+        if self.is_token_hidden(token_id) {
+            self.reveal_token_unchecked(token_id);
+            self.add_tokens_to_enumeration(std::array::from_ref(token_id), &owner_id);
+        }
+
+        Ok(())
+    }
+
+    fn reveal_token_unchecked(&mut self, token_id: &TokenId) {
+        Self::slot_hidden(token_id).remove();
+    }
 }
 
 // separate module with re-export because ext_contract doesn't play well with #![warn(missing_docs)]
@@ -226,11 +381,19 @@ mod ext {
 
     use super::*;
 
+    #[cfg_attr(
+        feature = "client",
+        near_sdk_contract_tools_macros::ext_client(name = "Nep181Client", crate = "crate")
+    )]
     #[near_sdk::ext_contract(ext_nep181)]
     pub trait Nep181 {
+        /// Returns the total number of tokens.
         fn nft_total_supply(&self) -> U128;
+        /// Returns a page of tokens.
         fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u32>) -> Vec<Token>;
+        /// Returns the number of tokens owned by `account_id`.
         fn nft_supply_for_owner(&self, account_id: AccountId) -> U128;
+        /// Returns a page of tokens owned by `account_id`.
         fn nft_tokens_for_owner(
             &self,
             account_id: AccountId,
@@ -238,4 +401,27 @@ mod ext {
             limit: Option<u32>,
         ) -> Vec<Token>;
     }
+
+    /// Non-standard extension for hiding tokens from enumeration.
+    #[near_sdk::ext_contract(ext_nep181_visibility)]
+    pub trait Nep181Visibility {
+        /// Whether a token is hidden from enumeration.
+        fn nft_is_token_hidden(&self, token_id: TokenId) -> bool;
+
+        /// Hides a token from enumeration. Requires the hide role and
+        /// exactly 1 yoctoNEAR.
+        fn nft_hide_token(&mut self, token_id: TokenId);
+
+        /// Adds a hidden token back to enumeration. Requires the hide role
+        /// and exactly 1 yoctoNEAR.
+        fn nft_reveal_token(&mut self, token_id: TokenId);
+    }
+
+    /// Non-standard maintenance extension.
+    #[near_sdk::ext_contract(ext_nep181_compaction)]
+    pub trait Nep181Compaction {
+        /// Compacts the token index of `account_id`, crediting the released
+        /// storage to its NEP-145 storage balance.
+        fn nft_compact_owner_index(&mut self, account_id: AccountId, limit: u32);
+    }
 }
@@ -0,0 +1,344 @@
+//! NEP-199 non-fungible token royalties and payouts implementation.
+//!
+//! Reference: <https://github.com/near/NEPs/blob/master/neps/nep-0199.md>
+//!
+//! Each token may have royalty recipients, each with a share of every payout
+//! in basis points (see [`ROYALTY_BASIS_POINTS`]), set with
+//! [`Nep199Controller::set_royalties`]. `nft_payout` splits a balance between
+//! the recipients and the owner of the token, who receives the remainder.
+//! Marketplaces sell a token with `nft_transfer_payout`, which transfers it
+//! like `nft_transfer` and returns the payout of the sale price to its
+//! previous owner and the royalty recipients.
+//!
+//! [`TokenRoyalties`] removes the royalties of burned tokens. Royalties are
+//! usually set right after minting. The storage they use is not charged by
+//! NEP-145 storage accounting, which only covers the NEP-171 actions.
+use std::collections::HashMap;
+
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId, BorshStorageKey,
+};
+use thiserror::Error;
+
+use crate::{
+    hook::Hook,
+    slot::Slot,
+    standard::nep171::{
+        action::{Nep171Burn, Nep171Mint, Nep171Transfer, Nep171TransferAction},
+        error::{Nep171TransferError, TokenDoesNotExistError},
+        Nep171Controller, TokenId,
+    },
+    storage_version::StorageVersion,
+    DefaultStorageKey,
+};
+
+pub use ext::*;
+
+/// Royalty basis points are out of this total (100%).
+pub const ROYALTY_BASIS_POINTS: u32 = 10_000;
+
+/// Default maximum number of royalty recipients per token.
+pub const DEFAULT_MAX_ROYALTIES: u32 = 10;
+
+/// Storage layout version of the Nep199 component. See
+/// [`crate::storage_version`].
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new("Nep199", 1);
+
+/// The amounts paid to each account from the sale of a token.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+    /// Amount paid to each account.
+    pub payout: HashMap<AccountId, U128>,
+}
+
+impl Payout {
+    /// Splits `price` between `royalties` (account and basis points) and
+    /// `owner_id`, who receives the remainder. Amounts are rounded down in
+    /// favor of the owner, and zero amounts are omitted.
+    pub fn split(
+        owner_id: &AccountId,
+        price: u128,
+        royalties: &[(AccountId, u16)],
+    ) -> Result<Self, RoyaltiesExceedPriceError> {
+        check_total(royalties)?;
+
+        let basis = u128::from(ROYALTY_BASIS_POINTS);
+        let mut payout = HashMap::new();
+        let mut remaining = price;
+
+        for (account_id, basis_points) in royalties {
+            let basis_points = u128::from(*basis_points);
+            // Avoids overflowing `price * basis_points`.
+            let amount = price / basis * basis_points + price % basis * basis_points / basis;
+            remaining -= amount;
+            *payout.entry(account_id.clone()).or_insert(0) += amount;
+        }
+
+        *payout.entry(owner_id.clone()).or_insert(0) += remaining;
+
+        Ok(Self {
+            payout: payout
+                .into_iter()
+                .filter(|(_, amount)| *amount > 0)
+                .map(|(account_id, amount)| (account_id, U128(amount)))
+                .collect(),
+        })
+    }
+}
+
+fn check_total(royalties: &[(AccountId, u16)]) -> Result<(), RoyaltiesExceedPriceError> {
+    let total_basis_points = royalties
+        .iter()
+        .map(|(_, basis_points)| u32::from(*basis_points))
+        .sum::<u32>();
+
+    if total_basis_points > ROYALTY_BASIS_POINTS {
+        return Err(RoyaltiesExceedPriceError(total_basis_points));
+    }
+
+    Ok(())
+}
+
+/// Royalties add up to more than the price.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Royalties total {0} basis points, exceeding {ROYALTY_BASIS_POINTS}")]
+pub struct RoyaltiesExceedPriceError(pub u32);
+
+/// Errors that may occur when setting the royalties of a token.
+#[derive(Error, Clone, Debug)]
+pub enum SetRoyaltiesError {
+    /// The token does not exist.
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The royalties add up to more than 100%.
+    #[error(transparent)]
+    RoyaltiesExceedPrice(#[from] RoyaltiesExceedPriceError),
+    /// The token has too many royalty recipients.
+    #[error("{count} royalty recipients exceed the limit of {limit}")]
+    TooManyRecipients {
+        /// Number of recipients.
+        count: u32,
+        /// Maximum number of recipients.
+        limit: u32,
+    },
+}
+
+/// Errors that may occur when calculating a payout.
+#[derive(Error, Clone, Debug)]
+pub enum Nep199PayoutError {
+    /// The token does not exist.
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The royalties of the token add up to more than 100%.
+    #[error(transparent)]
+    RoyaltiesExceedPrice(#[from] RoyaltiesExceedPriceError),
+    /// The payout has more accounts than the caller can pay out.
+    #[error("Payout to {len} accounts exceeds `max_len_payout` of {max_len_payout}")]
+    PayoutTooLong {
+        /// Number of accounts in the payout.
+        len: u32,
+        /// Maximum number of accounts requested by the caller.
+        max_len_payout: u32,
+    },
+    /// The transfer of the token failed.
+    #[error(transparent)]
+    Transfer(#[from] Nep171TransferError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Royalties(&'a TokenId),
+}
+
+/// Internal functions for [`Nep199Controller`].
+pub trait Nep199ControllerInternal {
+    /// Maximum number of royalty recipients per token.
+    const MAX_ROYALTIES: u32 = DEFAULT_MAX_ROYALTIES;
+
+    /// Storage root.
+    fn root() -> Slot<()> {
+        Slot::root(DefaultStorageKey::Nep199)
+    }
+
+    /// Storage slot for the royalties of a token.
+    fn slot_royalties(token_id: &TokenId) -> Slot<Vec<(AccountId, u16)>> {
+        Self::root().field(StorageKey::Royalties(token_id))
+    }
+}
+
+/// Functions for managing non-fungible token royalties and payouts, NEP-199.
+pub trait Nep199Controller {
+    /// Royalty recipients of `token_id` and their share of each payout, in
+    /// basis points (see [`ROYALTY_BASIS_POINTS`]).
+    fn royalties(&self, token_id: &TokenId) -> Vec<(AccountId, u16)>;
+
+    /// Sets the royalties of `token_id`, replacing any previous royalties.
+    /// Performs no access checks.
+    fn set_royalties(
+        &mut self,
+        token_id: &TokenId,
+        royalties: &[(AccountId, u16)],
+    ) -> Result<(), SetRoyaltiesError>;
+
+    /// Removes the royalties of `token_id`. Performs no access checks.
+    fn remove_royalties(&mut self, token_id: &TokenId);
+
+    /// The payout of `balance` from the sale of `token_id` by its current
+    /// owner. Fails if the payout has more than `max_len_payout` accounts.
+    fn payout(
+        &self,
+        token_id: &TokenId,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, Nep199PayoutError>;
+
+    /// Performs `transfer` like [`Nep171Controller::external_transfer`], and
+    /// returns the payout of `balance` to the previous owner of the token.
+    fn transfer_payout(
+        &mut self,
+        transfer: &Nep171Transfer,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, Nep199PayoutError>;
+}
+
+impl<T: Nep199ControllerInternal + Nep171Controller> Nep199Controller for T {
+    fn royalties(&self, token_id: &TokenId) -> Vec<(AccountId, u16)> {
+        Self::slot_royalties(token_id).read().unwrap_or_default()
+    }
+
+    fn set_royalties(
+        &mut self,
+        token_id: &TokenId,
+        royalties: &[(AccountId, u16)],
+    ) -> Result<(), SetRoyaltiesError> {
+        if self.token_owner(token_id).is_none() {
+            return Err(TokenDoesNotExistError {
+                token_id: token_id.clone(),
+            }
+            .into());
+        }
+
+        let count = u32::try_from(royalties.len()).unwrap_or(u32::MAX);
+        if count > Self::MAX_ROYALTIES {
+            return Err(SetRoyaltiesError::TooManyRecipients {
+                count,
+                limit: Self::MAX_ROYALTIES,
+            });
+        }
+
+        check_total(royalties)?;
+
+        if royalties.is_empty() {
+            Self::slot_royalties(token_id).remove();
+        } else {
+            Self::slot_royalties(token_id).write(&royalties.to_vec());
+        }
+
+        Ok(())
+    }
+
+    fn remove_royalties(&mut self, token_id: &TokenId) {
+        Self::slot_royalties(token_id).remove();
+    }
+
+    fn payout(
+        &self,
+        token_id: &TokenId,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, Nep199PayoutError> {
+        let owner_id = self
+            .token_owner(token_id)
+            .ok_or_else(|| TokenDoesNotExistError {
+                token_id: token_id.clone(),
+            })?;
+
+        let payout = Payout::split(&owner_id, balance, &self.royalties(token_id))?;
+
+        if let Some(max_len_payout) = max_len_payout {
+            let len = u32::try_from(payout.payout.len()).unwrap_or(u32::MAX);
+            if len > max_len_payout {
+                return Err(Nep199PayoutError::PayoutTooLong {
+                    len,
+                    max_len_payout,
+                });
+            }
+        }
+
+        Ok(payout)
+    }
+
+    fn transfer_payout(
+        &mut self,
+        transfer: &Nep171Transfer,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, Nep199PayoutError> {
+        let payout = self.payout(transfer.token_id, balance, max_len_payout)?;
+        self.external_transfer(transfer)?;
+        Ok(payout)
+    }
+}
+
+/// Extension hook for [`Nep171Controller`]. Removes the royalties of burned
+/// tokens.
+pub struct TokenRoyalties;
+
+impl<C: Nep199Controller> Hook<C, Nep171Mint<'_>> for TokenRoyalties {}
+
+impl<C: Nep199Controller, T: Nep171TransferAction> Hook<C, T> for TokenRoyalties {}
+
+impl<C: Nep199Controller> Hook<C, Nep171Burn<'_>> for TokenRoyalties {
+    fn hook<R>(contract: &mut C, args: &Nep171Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let r = f(contract);
+        for token_id in args.token_ids {
+            contract.remove_royalties(token_id);
+        }
+        r
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)]
+
+    use super::*;
+
+    /// NEP-199 external interface.
+    ///
+    /// See <https://github.com/near/NEPs/blob/master/neps/nep-0199.md#reference-level-explanation> for more details.
+    #[cfg_attr(
+        feature = "client",
+        near_sdk_contract_tools_macros::ext_client(
+            name = "Nep199Client",
+            crate = "crate",
+            method(name = "nft_transfer_payout", deposit = 1),
+        )
+    )]
+    #[near_sdk::ext_contract(ext_nep199)]
+    pub trait Nep199 {
+        /// Returns the payout of `balance` from the sale of `token_id`.
+        fn nft_payout(
+            &self,
+            token_id: TokenId,
+            balance: U128,
+            max_len_payout: Option<u32>,
+        ) -> Payout;
+
+        /// Transfers a token to `receiver_id` and returns the payout of
+        /// `balance` to its previous owner. Requires 1 yoctoNEAR.
+        fn nft_transfer_payout(
+            &mut self,
+            receiver_id: AccountId,
+            token_id: TokenId,
+            approval_id: Option<u32>,
+            memo: Option<String>,
+            balance: U128,
+            max_len_payout: Option<u32>,
+        ) -> Payout;
+    }
+}
@@ -32,6 +32,65 @@ pub trait Event {
 
     /// Emits the event string to the blockchain
     fn emit(&self);
+
+    /// Emits several events, merging consecutive events with the same
+    /// standard, version, and event name into a single log when their data
+    /// are arrays (as is the case for e.g. `nft_mint` and `ft_transfer`).
+    /// Other events are emitted individually.
+    fn emit_many(events: &[Self])
+    where
+        Self: Sized;
+}
+
+const EVENT_JSON_PREFIX: &[u8] = b"EVENT_JSON:";
+
+fn serialization_failed(e: serde_json::Error) -> ! {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        panic!("Failed to serialize event: {e}")
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        near_sdk::env::panic_str(&format!("Failed to serialize event: {e}"))
+    }
+}
+
+fn write_json(buf: &mut Vec<u8>, value: &impl Serialize) {
+    serde_json::to_writer(buf, value).unwrap_or_else(|e| serialization_failed(e));
+}
+
+fn log_bytes(buf: Vec<u8>) {
+    // SAFETY: The buffer consists of an ASCII prefix followed by output of
+    // `serde_json`, which is always valid UTF-8.
+    let s = unsafe { String::from_utf8_unchecked(buf) };
+    near_sdk::env::log_str(&s);
+}
+
+/// Consecutive events whose array data are merged into a single log.
+struct Batch {
+    standard: &'static str,
+    version: &'static str,
+    event: &'static str,
+    /// Serialized array elements, without the enclosing brackets.
+    elements: Vec<u8>,
+}
+
+impl Batch {
+    fn emit(self) {
+        let mut buf = Vec::with_capacity(self.elements.len() + 64);
+        buf.extend_from_slice(EVENT_JSON_PREFIX);
+        buf.extend_from_slice(b"{\"standard\":");
+        write_json(&mut buf, &self.standard);
+        buf.extend_from_slice(b",\"version\":");
+        write_json(&mut buf, &self.version);
+        buf.extend_from_slice(b",\"event\":");
+        write_json(&mut buf, &self.event);
+        buf.extend_from_slice(b",\"data\":[");
+        buf.extend_from_slice(&self.elements);
+        buf.extend_from_slice(b"]}");
+        log_bytes(buf);
+    }
 }
 
 impl<T: ToEventLog> Event for T
@@ -39,24 +98,65 @@ where
     T::Data: Serialize,
 {
     fn to_event_string(&self) -> String {
-        format!(
-            "EVENT_JSON:{}",
-            serde_json::to_string(&self.to_event_log()).unwrap_or_else(|e| {
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    panic!("Failed to serialize event: {e}")
+        let mut buf = EVENT_JSON_PREFIX.to_vec();
+        write_json(&mut buf, &self.to_event_log());
+        // SAFETY: See `log_bytes`.
+        unsafe { String::from_utf8_unchecked(buf) }
+    }
+
+    fn emit(&self) {
+        let mut buf = EVENT_JSON_PREFIX.to_vec();
+        write_json(&mut buf, &self.to_event_log());
+        log_bytes(buf);
+    }
+
+    fn emit_many(events: &[Self]) {
+        let mut batch: Option<Batch> = None;
+
+        for e in events {
+            let log = e.to_event_log();
+            let mut data = Vec::new();
+            write_json(&mut data, &log.data);
+
+            let elements = match (data.first(), data.last()) {
+                (Some(b'['), Some(b']')) => &data[1..data.len() - 1],
+                _ => {
+                    if let Some(batch) = batch.take() {
+                        batch.emit();
+                    }
+                    e.emit();
+                    continue;
                 }
+            };
 
-                #[cfg(target_arch = "wasm32")]
+            match batch {
+                Some(ref mut b)
+                    if b.standard == log.standard
+                        && b.version == log.version
+                        && b.event == log.event =>
                 {
-                    near_sdk::env::panic_str(&format!("Failed to serialize event: {e}"))
+                    if !b.elements.is_empty() && !elements.is_empty() {
+                        b.elements.push(b',');
+                    }
+                    b.elements.extend_from_slice(elements);
                 }
-            }),
-        )
-    }
+                _ => {
+                    if let Some(batch) = batch.take() {
+                        batch.emit();
+                    }
+                    batch = Some(Batch {
+                        standard: log.standard,
+                        version: log.version,
+                        event: log.event,
+                        elements: elements.to_vec(),
+                    });
+                }
+            }
+        }
 
-    fn emit(&self) {
-        near_sdk::env::log_str(&self.to_event_string());
+        if let Some(batch) = batch {
+            batch.emit();
+        }
     }
 }
 
@@ -0,0 +1,272 @@
+//! Usage counters for token contracts.
+//!
+//! Maintains running totals of transfers, mints, burns, and token holders in
+//! storage, so that basic usage statistics are available from a view call
+//! without running an indexer.
+//!
+//! Counters are updated by hooks: add [`Nep141StatsHook`] or
+//! [`Nep171StatsHook`] to the token's hooks (e.g. `all_hooks`). Operations
+//! that fail are not counted.
+//!
+//! For NEP-141 tokens, the holder count is copied from
+//! [`Nep141Controller::holder_count`] after each counted operation. It is not
+//! maintained by [`Nep171StatsHook`], since determining whether an account
+//! holds any other token would require enumerating its tokens.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::near_bindgen;
+//! use near_sdk_contract_tools::{stats::Nep141StatsHook, FungibleToken, Stats};
+//!
+//! #[derive(FungibleToken, Stats)]
+//! #[fungible_token(all_hooks = "Nep141StatsHook")]
+//! #[near_bindgen]
+//! struct Contract {}
+//! ```
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+    BorshStorageKey,
+};
+
+use crate::{
+    hook::Hook,
+    slot::Slot,
+    standard::{
+        nep141::{Nep141Burn, Nep141Controller, Nep141Mint, Nep141TransferAction},
+        nep145::Nep145ForceUnregister,
+        nep171::{
+            action::{Nep171Burn, Nep171Mint, Nep171TransferAction},
+            Nep171Controller,
+        },
+    },
+    DefaultStorageKey,
+};
+
+pub use ext::*;
+
+/// Usage counters.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractStats {
+    /// Number of successful transfers.
+    pub transfers: U64,
+    /// Number of successful mint operations.
+    pub mints: U64,
+    /// Number of successful burn operations.
+    pub burns: U64,
+    /// Number of accounts holding a nonzero balance.
+    pub holders: U64,
+}
+
+impl Default for ContractStats {
+    fn default() -> Self {
+        Self {
+            transfers: U64(0),
+            mints: U64(0),
+            burns: U64(0),
+            holders: U64(0),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Stats,
+}
+
+/// Internal functions for [`Stats`]. Using these methods may result in unexpected behavior.
+pub trait StatsInternal {
+    /// Storage root.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Stats)
+    }
+
+    /// Storage slot for the counters.
+    fn slot_stats() -> Slot<ContractStats> {
+        Self::root().field(StorageKey::Stats)
+    }
+}
+
+/// Maintains usage counters for a contract.
+pub trait Stats {
+    /// Returns the current counters.
+    fn stats(&self) -> ContractStats;
+
+    /// Applies `f` to the stored counters.
+    fn update_stats(&mut self, f: impl FnOnce(&mut ContractStats));
+}
+
+impl<T: StatsInternal> Stats for T {
+    fn stats(&self) -> ContractStats {
+        Self::slot_stats().read().unwrap_or_default()
+    }
+
+    fn update_stats(&mut self, f: impl FnOnce(&mut ContractStats)) {
+        let mut stats = self.stats();
+        f(&mut stats);
+        Self::slot_stats().write(&stats);
+    }
+}
+
+fn increment(counter: &mut U64, by: u64) {
+    counter.0 = counter.0.saturating_add(by);
+}
+
+/// Hook that counts NEP-141 transfers, mints, and burns, and copies the
+/// number of holders from [`Nep141Controller::holder_count`]. Zero-amount
+/// operations are not counted.
+pub struct Nep141StatsHook;
+
+impl Nep141StatsHook {
+    fn record<C: Nep141Controller + Stats>(
+        contract: &mut C,
+        counter: impl FnOnce(&mut ContractStats) -> &mut U64,
+    ) {
+        let holders = contract.holder_count();
+        contract.update_stats(|stats| {
+            increment(counter(stats), 1);
+            stats.holders = U64(holders);
+        });
+    }
+}
+
+impl<C: Nep141Controller + Stats, T: Nep141TransferAction> Hook<C, T> for Nep141StatsHook {
+    fn hook<R>(contract: &mut C, args: &T, f: impl FnOnce(&mut C) -> R) -> R {
+        if args.amount() == 0 {
+            return f(contract);
+        }
+
+        let sender_before = contract.balance_of(args.sender_id());
+
+        let r = f(contract);
+
+        let succeeded = if args.sender_id() == args.receiver_id() {
+            sender_before >= args.amount()
+        } else {
+            sender_before.checked_sub(contract.balance_of(args.sender_id())) == Some(args.amount())
+        };
+
+        if succeeded {
+            Self::record(contract, |stats| &mut stats.transfers);
+        }
+
+        r
+    }
+}
+
+impl<C: Nep141Controller + Stats> Hook<C, Nep141Mint<'_>> for Nep141StatsHook {
+    fn hook<R>(contract: &mut C, args: &Nep141Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        if args.amount == 0 {
+            return f(contract);
+        }
+
+        let supply_before = contract.total_supply();
+
+        let r = f(contract);
+
+        if contract.total_supply().checked_sub(supply_before) == Some(args.amount) {
+            Self::record(contract, |stats| &mut stats.mints);
+        }
+
+        r
+    }
+}
+
+impl<C: Nep141Controller + Stats> Hook<C, Nep141Burn<'_>> for Nep141StatsHook {
+    fn hook<R>(contract: &mut C, args: &Nep141Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        if args.amount == 0 {
+            return f(contract);
+        }
+
+        let supply_before = contract.total_supply();
+
+        let r = f(contract);
+
+        if supply_before.checked_sub(contract.total_supply()) == Some(args.amount) {
+            Self::record(contract, |stats| &mut stats.burns);
+        }
+
+        r
+    }
+}
+
+// Tokens burned on forced unregistration are counted by the burn hook, so
+// this is a no-op. Allows the hook to be used as `#[fungible_token(all_hooks)]`.
+impl<C> Hook<C, Nep145ForceUnregister<'_>> for Nep141StatsHook {}
+
+/// Hook that counts NEP-171 transfers, mints, and burns. Does not track the
+/// number of holders.
+pub struct Nep171StatsHook;
+
+impl<C: Nep171Controller + Stats, T: Nep171TransferAction> Hook<C, T> for Nep171StatsHook {
+    fn hook<R>(contract: &mut C, args: &T, f: impl FnOnce(&mut C) -> R) -> R {
+        let owner_before = contract.token_owner(args.token_id());
+
+        let r = f(contract);
+
+        if owner_before.as_ref() != Some(args.receiver_id())
+            && contract.token_owner(args.token_id()).as_ref() == Some(args.receiver_id())
+        {
+            contract.update_stats(|stats| increment(&mut stats.transfers, 1));
+        }
+
+        r
+    }
+}
+
+impl<C: Nep171Controller + Stats> Hook<C, Nep171Mint<'_>> for Nep171StatsHook {
+    fn hook<R>(contract: &mut C, args: &Nep171Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let existed = args
+            .token_ids
+            .first()
+            .map(|token_id| contract.token_owner(token_id).is_some());
+
+        let r = f(contract);
+
+        if let (Some(false), Some(token_id)) = (existed, args.token_ids.first()) {
+            if contract.token_owner(token_id).is_some() {
+                contract.update_stats(|stats| increment(&mut stats.mints, 1));
+            }
+        }
+
+        r
+    }
+}
+
+impl<C: Nep171Controller + Stats> Hook<C, Nep171Burn<'_>> for Nep171StatsHook {
+    fn hook<R>(contract: &mut C, args: &Nep171Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let existed = args
+            .token_ids
+            .first()
+            .map(|token_id| contract.token_owner(token_id).is_some());
+
+        let r = f(contract);
+
+        if let (Some(true), Some(token_id)) = (existed, args.token_ids.first()) {
+            if contract.token_owner(token_id).is_none() {
+                contract.update_stats(|stats| increment(&mut stats.burns, 1));
+            }
+        }
+
+        r
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::ext_contract;
+
+    use super::ContractStats;
+
+    /// Externally-accessible functions for [`super::Stats`].
+    #[ext_contract(ext_stats)]
+    pub trait StatsExternal {
+        /// Returns the usage counters.
+        fn contract_stats(&self) -> ContractStats;
+    }
+}
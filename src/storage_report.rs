@@ -0,0 +1,227 @@
+//! Storage usage reporting per component.
+//!
+//! Contracts cannot enumerate their own storage, so operators planning
+//! storage deposits have only the contract's total usage to go on. This
+//! module keeps a running byte count for each component, maintained by
+//! hooks, and summarizes them along with the NEAR locked for storage in the
+//! `storage_report` view.
+//!
+//! Counters are updated by [`StorageUsageHook`], which measures the change
+//! in [`env::storage_usage`] across each operation it wraps. Add it to the
+//! hooks of each component to track, e.g. [`Nep141StorageUsageHook`] to
+//! NEP-141 `all_hooks`. The measurement includes writes made by hooks that
+//! run inside it, and by other components that the operation touches.
+//!
+//! Usage is only tracked from the time the hook is added, and not for
+//! writes outside of hooked operations (e.g. NEP-145 storage deposits, or
+//! the counters themselves), so the report also includes the untracked
+//! remainder of the contract's total usage.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::near_bindgen;
+//! use near_sdk_contract_tools::{storage_report::Nep141StorageUsageHook, Nep141, StorageReport};
+//!
+//! #[derive(Nep141, StorageReport)]
+//! #[nep141(all_hooks = "Nep141StorageUsageHook")]
+//! #[near_bindgen]
+//! struct Contract {}
+//! ```
+
+use std::marker::PhantomData;
+
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    env,
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    BorshStorageKey,
+};
+
+use crate::{hook::Hook, manifest::component, slot::Slot, DefaultStorageKey};
+
+pub use ext::*;
+
+/// Storage used by one component.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ComponentStorageUsage {
+    /// Name of the component.
+    pub component: String,
+    /// Bytes attributed to the component.
+    pub bytes: U64,
+    /// NEAR locked for those bytes.
+    pub locked: U128,
+}
+
+/// Storage usage of the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageUsageReport {
+    /// Total bytes used by the contract.
+    pub total_bytes: U64,
+    /// NEAR locked for the total usage.
+    pub locked: U128,
+    /// Usage of each tracked component.
+    pub components: Vec<ComponentStorageUsage>,
+    /// Bytes not attributed to any component.
+    pub untracked_bytes: U64,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Components,
+    Bytes(&'a str),
+}
+
+/// Internal functions for [`StorageReport`]. Using these methods may result in unexpected behavior.
+pub trait StorageReportInternal {
+    /// Storage root.
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::StorageReport)
+    }
+
+    /// Storage slot for the names of the tracked components.
+    fn slot_components() -> Slot<Vec<String>> {
+        Self::root().field(StorageKey::Components)
+    }
+
+    /// Storage slot for the bytes attributed to a component.
+    fn slot_bytes(component: &str) -> Slot<u64> {
+        Self::root().field(StorageKey::Bytes(component))
+    }
+}
+
+/// Tracks storage usage per component.
+pub trait StorageReport {
+    /// Bytes attributed to `component`.
+    fn component_bytes(&self, component: &str) -> u64;
+
+    /// Attributes the change in storage usage from `before` to `after` to
+    /// `component`.
+    fn record_storage_usage(&mut self, component: &str, before: u64, after: u64);
+
+    /// Summarizes the storage usage of the contract.
+    fn storage_usage_report(&self) -> StorageUsageReport;
+}
+
+fn locked_for(bytes: u64) -> U128 {
+    u128::from(bytes)
+        .saturating_mul(env::storage_byte_cost())
+        .into()
+}
+
+impl<T: StorageReportInternal> StorageReport for T {
+    fn component_bytes(&self, component: &str) -> u64 {
+        Self::slot_bytes(component).read().unwrap_or(0)
+    }
+
+    fn record_storage_usage(&mut self, component: &str, before: u64, after: u64) {
+        if before == after {
+            return;
+        }
+
+        let mut slot = Self::slot_bytes(component);
+        let bytes = match slot.read() {
+            Some(bytes) => bytes,
+            None => {
+                let mut components = Self::slot_components().read().unwrap_or_default();
+                components.push(component.to_string());
+                Self::slot_components().write(&components);
+                0
+            }
+        };
+
+        // Usage freed by an operation may predate tracking.
+        let bytes = (bytes + after).saturating_sub(before);
+        slot.write(&bytes);
+    }
+
+    fn storage_usage_report(&self) -> StorageUsageReport {
+        let total_bytes = env::storage_usage();
+
+        let components = Self::slot_components()
+            .read()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|component| {
+                let bytes = self.component_bytes(&component);
+                ComponentStorageUsage {
+                    component,
+                    bytes: bytes.into(),
+                    locked: locked_for(bytes),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let tracked = components
+            .iter()
+            .fold(0u64, |sum, c| sum.saturating_add(c.bytes.0));
+
+        StorageUsageReport {
+            total_bytes: total_bytes.into(),
+            locked: locked_for(total_bytes),
+            components,
+            untracked_bytes: total_bytes.saturating_sub(tracked).into(),
+        }
+    }
+}
+
+/// A component whose storage usage can be reported.
+pub trait ReportedComponent {
+    /// Name of the component in the report.
+    const NAME: &'static str;
+}
+
+macro_rules! reported_components {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl ReportedComponent for component::$name {
+                const NAME: &'static str = stringify!($name);
+            }
+        )*
+    };
+}
+
+reported_components!(Nep141, Nep145, Nep171, Nep178);
+
+/// Hook that attributes the storage usage of the operations it wraps to the
+/// component `M`. Works with any hooked operation.
+pub struct StorageUsageHook<M>(PhantomData<M>);
+
+impl<C: StorageReport, A, M: ReportedComponent> Hook<C, A> for StorageUsageHook<M> {
+    fn hook<R>(contract: &mut C, _args: &A, f: impl FnOnce(&mut C) -> R) -> R {
+        let before = env::storage_usage();
+
+        let r = f(contract);
+
+        contract.record_storage_usage(M::NAME, before, env::storage_usage());
+
+        r
+    }
+}
+
+/// Tracks NEP-141 mints, transfers, and burns.
+pub type Nep141StorageUsageHook = StorageUsageHook<component::Nep141>;
+/// Tracks NEP-145 forced unregistrations.
+pub type Nep145StorageUsageHook = StorageUsageHook<component::Nep145>;
+/// Tracks NEP-171 mints, transfers, and burns.
+pub type Nep171StorageUsageHook = StorageUsageHook<component::Nep171>;
+/// Tracks NEP-178 approvals and revocations.
+pub type Nep178StorageUsageHook = StorageUsageHook<component::Nep178>;
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::ext_contract;
+
+    use super::StorageUsageReport;
+
+    /// Externally-accessible functions for [`super::StorageReport`].
+    #[ext_contract(ext_storage_report)]
+    pub trait StorageReportExternal {
+        /// Returns the storage usage of the contract, per component.
+        fn storage_report(&self) -> StorageUsageReport;
+    }
+}
@@ -0,0 +1,172 @@
+//! Storage layout versions of components.
+//!
+//! Each component module declares a [`StorageVersion`] constant,
+//! `STORAGE_VERSION`, which is bumped whenever a release of this crate
+//! changes the component's storage layout incompatibly. The version is
+//! stored in the component's root, so that code reading storage written by
+//! an incompatible version fails with a clear error, like:
+//!
+//! ```text
+//! Nep141 component storage v1 found, code expects v2 — run migration
+//! ```
+//!
+//! instead of a Borsh decoding failure (or silently misinterpreted data).
+//!
+//! Contracts opt in with `#[contract_tools(storage_versions)]`. Every
+//! external method generated by a versioned component then checks the
+//! component's stored version first, and change methods write it on first
+//! use. Storage without a version is treated as [`UNVERSIONED`], the
+//! layout of contracts deployed before versions were recorded, so
+//! contracts deployed with a component at a later version must record it
+//! with [`StorageVersion::init`] in their initialization function:
+//!
+//! ```
+//! use near_sdk::{
+//!     borsh::{self, BorshDeserialize, BorshSerialize},
+//!     near_bindgen, PanicOnDefault,
+//! };
+//! use near_sdk_contract_tools::{
+//!     standard::nep141::{self, Nep141ControllerInternal},
+//!     Nep141,
+//! };
+//!
+//! #[derive(Nep141, BorshSerialize, BorshDeserialize, PanicOnDefault)]
+//! #[contract_tools(storage_versions)]
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[init]
+//!     pub fn new() -> Self {
+//!         nep141::STORAGE_VERSION.init(&Self::root());
+//!         Self {}
+//!     }
+//! }
+//! ```
+//!
+//! After migrating a component's storage to a new layout, record the new
+//! version with [`StorageVersion::init`] as well.
+
+use near_sdk::env;
+use thiserror::Error;
+
+use crate::slot::Slot;
+
+/// Version of storage written before versions were recorded.
+pub const UNVERSIONED: u32 = 1;
+
+/// Key of the version in a component's root. Component storage keys are
+/// Borsh-serialized enums, which start with a small variant index, so they
+/// do not collide with it.
+const VERSION_KEY: &[u8] = b"~v";
+
+/// The stored version of a component does not match the code.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("{component} component storage v{found} found, code expects v{expected} — run migration")]
+pub struct StorageVersionMismatchError {
+    /// Name of the component.
+    pub component: &'static str,
+    /// Version found in storage.
+    pub found: u32,
+    /// Version expected by the code.
+    pub expected: u32,
+}
+
+/// The storage layout version of a component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageVersion {
+    /// Name of the component.
+    pub component: &'static str,
+    /// Version of the storage layout the code reads and writes.
+    pub version: u32,
+}
+
+impl StorageVersion {
+    /// Creates a version constant.
+    pub const fn new(component: &'static str, version: u32) -> Self {
+        Self { component, version }
+    }
+
+    /// Slot for the version of the component stored under `root`.
+    pub fn slot(root: &Slot<()>) -> Slot<u32> {
+        root.field(VERSION_KEY)
+    }
+
+    /// Version of the component stored under `root`, or [`UNVERSIONED`].
+    pub fn stored(root: &Slot<()>) -> u32 {
+        Self::slot(root).read().unwrap_or(UNVERSIONED)
+    }
+
+    /// Checks that the version stored under `root` matches this version.
+    pub fn check(&self, root: &Slot<()>) -> Result<(), StorageVersionMismatchError> {
+        let found = Self::stored(root);
+
+        if found != self.version {
+            return Err(StorageVersionMismatchError {
+                component: self.component,
+                found,
+                expected: self.version,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Like [`StorageVersion::check`], but panics on mismatch.
+    pub fn require(&self, root: &Slot<()>) {
+        self.check(root)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    /// Like [`StorageVersion::require`], but also writes the version if none
+    /// is stored.
+    pub fn ensure(&self, root: &Slot<()>) {
+        self.require(root);
+
+        let mut slot = Self::slot(root);
+        if !slot.exists() {
+            slot.write(&self.version);
+        }
+    }
+
+    /// Records this version under `root`, e.g. when initializing a contract
+    /// or after migrating the component's storage.
+    pub fn init(&self, root: &Slot<()>) {
+        Self::slot(root).write(&self.version);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V2: StorageVersion = StorageVersion::new("Test", 2);
+
+    #[test]
+    fn check() {
+        let root = Slot::root(b"t".to_vec());
+
+        assert_eq!(
+            V2.check(&root),
+            Err(StorageVersionMismatchError {
+                component: "Test",
+                found: UNVERSIONED,
+                expected: 2,
+            }),
+        );
+        assert_eq!(
+            V2.check(&root).unwrap_err().to_string(),
+            "Test component storage v1 found, code expects v2 — run migration",
+        );
+
+        V2.init(&root);
+        assert_eq!(StorageVersion::stored(&root), 2);
+        assert_eq!(V2.check(&root), Ok(()));
+
+        let v1 = StorageVersion::new("Test", UNVERSIONED);
+        let other = Slot::root(b"u".to_vec());
+        v1.ensure(&other);
+        assert_eq!(StorageVersion::slot(&other).read(), Some(UNVERSIONED));
+    }
+}
@@ -0,0 +1,22 @@
+//! Template contracts for common use cases, which double as executable
+//! documentation and test fixtures. Requires the `templates` feature, and is
+//! not available in Wasm builds.
+//!
+//! The sources live in `examples/templates/`; copy one into a new project to
+//! get started. Each template declares its contract with
+//! [`new_project!`](near_sdk_contract_tools_macros::new_project), which
+//! namespaces the storage keys of its components.
+
+// `#[near_bindgen]` generates undocumented items.
+#![allow(missing_docs)]
+
+#[path = "../examples/templates/fractionalized_nft.rs"]
+pub mod fractionalized_nft;
+#[path = "../examples/templates/fungible_token.rs"]
+pub mod fungible_token;
+#[path = "../examples/templates/multisig_dao.rs"]
+pub mod multisig_dao;
+#[path = "../examples/templates/non_fungible_token.rs"]
+pub mod non_fungible_token;
+#[path = "../examples/templates/upgradable_ownable.rs"]
+pub mod upgradable_ownable;
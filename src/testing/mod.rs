@@ -0,0 +1,397 @@
+//! Property-based testing support.
+//!
+//! Provides [`proptest`] strategies that generate sequences of operations
+//! against fungible and non-fungible token controllers, and functions that
+//! check the invariants those controllers are expected to uphold after every
+//! operation. Requires the `testing` feature.
+//!
+//! Operations are applied through the controller traits, not through the
+//! generated external methods. Operations that would panic (e.g. transferring
+//! to an account that is not registered for storage) are skipped, so a
+//! sequence of operations never aborts the test process.
+//!
+//! The [`snapshot`] module compares raw contract state, e.g. before and after
+//! an upgrade.
+
+use near_sdk::{json_types::U128, AccountId};
+use proptest::{prelude::*, sample::select};
+use thiserror::Error;
+
+pub mod snapshot;
+
+use crate::{
+    hook::ActionOrigin,
+    standard::{
+        nep141::{Nep141Burn, Nep141Controller, Nep141Mint, Nep141Transfer},
+        nep145::Nep145Controller,
+        nep171::{
+            action::{Nep171Burn, Nep171Mint, Nep171Transfer},
+            Nep171Controller, Nep171TransferAuthorization, TokenId,
+        },
+        nep181::Nep181Controller,
+    },
+};
+
+/// Generates `n` distinct account IDs.
+pub fn accounts(n: usize) -> Vec<AccountId> {
+    (0..n)
+        .map(|i| format!("account{i}").parse().unwrap())
+        .collect()
+}
+
+/// Generates `n` distinct token IDs.
+pub fn token_ids(n: usize) -> Vec<TokenId> {
+    (0..n).map(|i| format!("token{i}")).collect()
+}
+
+/// Strategy that picks one of the given account IDs.
+pub fn account_id(accounts: Vec<AccountId>) -> impl Strategy<Value = AccountId> + Clone {
+    select(accounts)
+}
+
+/// Strategy that picks one of the given token IDs.
+pub fn token_id(token_ids: Vec<TokenId>) -> impl Strategy<Value = TokenId> + Clone {
+    select(token_ids)
+}
+
+/// An operation against a fungible token contract implementing
+/// [`Nep141Controller`] and [`Nep145Controller`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FungibleTokenOp {
+    /// Mint tokens to an account.
+    Mint {
+        /// Receiving account.
+        receiver_id: AccountId,
+        /// Amount to mint.
+        amount: u128,
+    },
+    /// Transfer tokens between accounts.
+    Transfer {
+        /// Sending account.
+        sender_id: AccountId,
+        /// Receiving account.
+        receiver_id: AccountId,
+        /// Amount to transfer.
+        amount: u128,
+    },
+    /// Burn tokens from an account.
+    Burn {
+        /// Account to burn from.
+        owner_id: AccountId,
+        /// Amount to burn.
+        amount: u128,
+    },
+    /// Deposit storage balance for an account.
+    StorageDeposit {
+        /// Account to deposit to.
+        account_id: AccountId,
+        /// Amount to deposit.
+        amount: u128,
+    },
+    /// Force unregister an account, burning its tokens.
+    StorageForceUnregister {
+        /// Account to unregister.
+        account_id: AccountId,
+    },
+}
+
+impl FungibleTokenOp {
+    /// Applies this operation to the contract. Returns `true` if the operation
+    /// succeeded, `false` if it was rejected or skipped.
+    pub fn apply<C: Nep141Controller + Nep145Controller>(&self, contract: &mut C) -> bool {
+        let is_registered =
+            |c: &C, account_id: &AccountId| c.get_storage_balance(account_id).is_ok();
+
+        match self {
+            Self::Mint {
+                receiver_id,
+                amount,
+            } => {
+                is_registered(contract, receiver_id)
+                    && contract
+                        .mint(&Nep141Mint {
+                            amount: *amount,
+                            receiver_id,
+                            memo: None,
+                            origin: ActionOrigin::Internal,
+                        })
+                        .is_ok()
+            }
+            Self::Transfer {
+                sender_id,
+                receiver_id,
+                amount,
+            } => {
+                is_registered(contract, receiver_id)
+                    && contract
+                        .transfer(&Nep141Transfer {
+                            sender_id,
+                            receiver_id,
+                            amount: *amount,
+                            memo: None,
+                            msg: None,
+                            revert: false,
+                            origin: ActionOrigin::Internal,
+                        })
+                        .is_ok()
+            }
+            Self::Burn { owner_id, amount } => contract
+                .burn(&Nep141Burn {
+                    amount: *amount,
+                    owner_id,
+                    memo: None,
+                    origin: ActionOrigin::Internal,
+                })
+                .is_ok(),
+            Self::StorageDeposit { account_id, amount } => contract
+                .deposit_to_storage_account(account_id, U128(*amount))
+                .is_ok(),
+            Self::StorageForceUnregister { account_id } => contract
+                .force_unregister_storage_account(account_id)
+                .is_ok(),
+        }
+    }
+}
+
+/// Strategy that generates [`FungibleTokenOp`]s over the given accounts.
+/// Token amounts are at most `max_amount`; storage deposits are between
+/// `min_storage_deposit` and twice that.
+pub fn fungible_token_op(
+    accounts: Vec<AccountId>,
+    max_amount: u128,
+    min_storage_deposit: u128,
+) -> impl Strategy<Value = FungibleTokenOp> {
+    let account = account_id(accounts);
+    let amount = 0..=max_amount;
+
+    prop_oneof![
+        (account.clone(), amount.clone()).prop_map(|(receiver_id, amount)| {
+            FungibleTokenOp::Mint {
+                receiver_id,
+                amount,
+            }
+        }),
+        (account.clone(), account.clone(), amount.clone()).prop_map(
+            |(sender_id, receiver_id, amount)| FungibleTokenOp::Transfer {
+                sender_id,
+                receiver_id,
+                amount,
+            }
+        ),
+        (account.clone(), amount)
+            .prop_map(|(owner_id, amount)| FungibleTokenOp::Burn { owner_id, amount }),
+        (
+            account.clone(),
+            min_storage_deposit..=min_storage_deposit.saturating_mul(2)
+        )
+            .prop_map(|(account_id, amount)| FungibleTokenOp::StorageDeposit {
+                account_id,
+                amount,
+            }),
+        account.prop_map(|account_id| FungibleTokenOp::StorageForceUnregister { account_id }),
+    ]
+}
+
+/// An operation against a non-fungible token contract implementing
+/// [`Nep171Controller`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NonFungibleTokenOp {
+    /// Mint a token.
+    Mint {
+        /// Token to mint.
+        token_id: TokenId,
+        /// Receiving account.
+        receiver_id: AccountId,
+    },
+    /// Transfer a token, authorized as the owner.
+    Transfer {
+        /// Token to transfer.
+        token_id: TokenId,
+        /// Sending account.
+        sender_id: AccountId,
+        /// Receiving account.
+        receiver_id: AccountId,
+    },
+    /// Burn a token.
+    Burn {
+        /// Token to burn.
+        token_id: TokenId,
+        /// Expected owner.
+        owner_id: AccountId,
+    },
+}
+
+impl NonFungibleTokenOp {
+    /// Applies this operation to the contract. Returns `true` if the operation
+    /// succeeded, `false` if it was rejected.
+    pub fn apply<C: Nep171Controller>(&self, contract: &mut C) -> bool {
+        match self {
+            Self::Mint {
+                token_id,
+                receiver_id,
+            } => contract
+                .mint(&Nep171Mint {
+                    token_ids: std::array::from_ref(token_id),
+                    receiver_id,
+                    memo: None,
+                    origin: ActionOrigin::Internal,
+                })
+                .is_ok(),
+            Self::Transfer {
+                token_id,
+                sender_id,
+                receiver_id,
+            } => contract
+                .external_transfer(&Nep171Transfer {
+                    authorization: Nep171TransferAuthorization::Owner,
+                    sender_id,
+                    receiver_id,
+                    token_id,
+                    memo: None,
+                    msg: None,
+                    revert: false,
+                    origin: ActionOrigin::Internal,
+                })
+                .is_ok(),
+            Self::Burn { token_id, owner_id } => contract
+                .burn(&Nep171Burn {
+                    token_ids: std::array::from_ref(token_id),
+                    owner_id,
+                    authorized_id: None,
+                    memo: None,
+                    origin: ActionOrigin::Internal,
+                })
+                .is_ok(),
+        }
+    }
+}
+
+/// Strategy that generates [`NonFungibleTokenOp`]s over the given accounts
+/// and token IDs.
+pub fn non_fungible_token_op(
+    accounts: Vec<AccountId>,
+    token_ids: Vec<TokenId>,
+) -> impl Strategy<Value = NonFungibleTokenOp> {
+    let account = account_id(accounts);
+    let token = token_id(token_ids);
+
+    prop_oneof![
+        (token.clone(), account.clone()).prop_map(|(token_id, receiver_id)| {
+            NonFungibleTokenOp::Mint {
+                token_id,
+                receiver_id,
+            }
+        }),
+        (token.clone(), account.clone(), account.clone()).prop_map(
+            |(token_id, sender_id, receiver_id)| NonFungibleTokenOp::Transfer {
+                token_id,
+                sender_id,
+                receiver_id,
+            }
+        ),
+        (token, account)
+            .prop_map(|(token_id, owner_id)| NonFungibleTokenOp::Burn { token_id, owner_id }),
+    ]
+}
+
+/// A violated controller invariant.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// Total supply does not match the sum of all balances.
+    #[error("Total supply {total_supply} does not equal sum of balances {sum_of_balances}")]
+    TotalSupplyMismatch {
+        /// Recorded total supply.
+        total_supply: u128,
+        /// Sum of the balances of all accounts.
+        sum_of_balances: u128,
+    },
+    /// A token is enumerated for an account that does not own it.
+    #[error("Token `{token_id}` is enumerated for `{account_id}` but owned by {owner_id:?}")]
+    EnumeratedForNonOwner {
+        /// Token ID.
+        token_id: TokenId,
+        /// Account the token is enumerated for.
+        account_id: AccountId,
+        /// Actual owner of the token.
+        owner_id: Option<AccountId>,
+    },
+    /// A token that exists is missing from an enumeration set.
+    #[error("Token `{token_id}` owned by `{owner_id}` is missing from enumeration")]
+    NotEnumerated {
+        /// Token ID.
+        token_id: TokenId,
+        /// Owner of the token.
+        owner_id: AccountId,
+    },
+    /// A token that does not exist is present in the enumeration of all tokens.
+    #[error("Token `{token_id}` does not exist but is enumerated")]
+    EnumeratedNonexistent {
+        /// Token ID.
+        token_id: TokenId,
+    },
+}
+
+/// Checks that the total supply equals the sum of the balances of `accounts`.
+/// `accounts` must include every account that may hold a balance.
+pub fn check_nep141_total_supply<C: Nep141Controller>(
+    contract: &C,
+    accounts: &[AccountId],
+) -> Result<(), InvariantViolation> {
+    let total_supply = contract.total_supply();
+    let sum_of_balances = accounts
+        .iter()
+        .map(|account_id| contract.balance_of(account_id))
+        .sum();
+
+    if total_supply == sum_of_balances {
+        Ok(())
+    } else {
+        Err(InvariantViolation::TotalSupplyMismatch {
+            total_supply,
+            sum_of_balances,
+        })
+    }
+}
+
+/// Checks that every token has at most one owner and that the NEP-181
+/// enumeration sets agree with NEP-171 ownership. `accounts` and `token_ids`
+/// must include every account and token that may exist.
+pub fn check_nep171_ownership<C: Nep171Controller + Nep181Controller>(
+    contract: &C,
+    accounts: &[AccountId],
+    token_ids: &[TokenId],
+) -> Result<(), InvariantViolation> {
+    for token_id in token_ids {
+        let owner_id = contract.token_owner(token_id);
+        let enumerated = contract.with_tokens(|tokens| tokens.contains(token_id));
+
+        for account_id in accounts {
+            let enumerated_for_account =
+                contract.with_tokens_for_owner(account_id, |tokens| tokens.contains(token_id));
+            let owns = owner_id.as_ref() == Some(account_id);
+
+            if enumerated_for_account && !owns {
+                return Err(InvariantViolation::EnumeratedForNonOwner {
+                    token_id: token_id.clone(),
+                    account_id: account_id.clone(),
+                    owner_id,
+                });
+            }
+
+            if owns && !(enumerated_for_account && enumerated) {
+                return Err(InvariantViolation::NotEnumerated {
+                    token_id: token_id.clone(),
+                    owner_id: account_id.clone(),
+                });
+            }
+        }
+
+        if owner_id.is_none() && enumerated {
+            return Err(InvariantViolation::EnumeratedNonexistent {
+                token_id: token_id.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
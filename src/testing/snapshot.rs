@@ -0,0 +1,208 @@
+//! Contract state snapshots and diffs, for regression-testing upgrades and
+//! migrations.
+//!
+//! Take a [`StateSnapshot`] of the raw contract state before and after an
+//! upgrade (e.g. from `near_workspaces::Contract::view_state`), narrow both
+//! to the storage prefixes of interest, and compare them with
+//! [`StateSnapshot::diff`]:
+//!
+//! ```
+//! use near_sdk_contract_tools::testing::snapshot::StateSnapshot;
+//!
+//! let before = StateSnapshot::new([
+//!     (b"STATE".to_vec(), vec![1, 0, 0, 0]),
+//!     (b"~o".to_vec(), b"alice".to_vec()),
+//! ]);
+//! let after = StateSnapshot::new([
+//!     (b"STATE".to_vec(), vec![1, 0, 0, 0, 0, 0, 0, 0]),
+//!     (b"~o".to_vec(), b"alice".to_vec()),
+//! ]);
+//!
+//! let diff = before.diff(&after);
+//! diff.assert_unchanged(&[b"~o"]);
+//! assert_eq!(diff.changed.len(), 1);
+//! ```
+
+use std::{collections::BTreeMap, fmt};
+
+/// Raw contract state: storage keys and their values.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateSnapshot {
+    /// Storage entries, ordered by key.
+    pub entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl StateSnapshot {
+    /// Creates a snapshot from storage entries.
+    pub fn new(entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>) -> Self {
+        Self {
+            entries: entries.into_iter().collect(),
+        }
+    }
+
+    /// Keeps only the entries whose keys start with one of `prefixes`.
+    pub fn filter(&self, prefixes: &[&[u8]]) -> Self {
+        Self {
+            entries: self
+                .entries
+                .iter()
+                .filter(|(key, _)| has_prefix(key, prefixes))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        }
+    }
+
+    /// The value stored under `key`, if any.
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(Vec::as_slice)
+    }
+
+    /// The changes from this snapshot to `after`.
+    pub fn diff(&self, after: &StateSnapshot) -> StateDiff {
+        let mut diff = StateDiff::default();
+
+        for (key, value) in &self.entries {
+            match after.entries.get(key) {
+                None => {
+                    diff.removed.insert(key.clone(), value.clone());
+                }
+                Some(new) if new != value => {
+                    diff.changed
+                        .insert(key.clone(), (value.clone(), new.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (key, value) in &after.entries {
+            if !self.entries.contains_key(key) {
+                diff.added.insert(key.clone(), value.clone());
+            }
+        }
+
+        diff
+    }
+}
+
+impl<K: Into<Vec<u8>>, V: Into<Vec<u8>>> FromIterator<(K, V)> for StateSnapshot {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self::new(iter.into_iter().map(|(k, v)| (k.into(), v.into())))
+    }
+}
+
+/// The changes between two [`StateSnapshot`]s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    /// Entries present only after.
+    pub added: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Entries present only before.
+    pub removed: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Entries whose value changed, as `(before, after)`.
+    pub changed: BTreeMap<Vec<u8>, (Vec<u8>, Vec<u8>)>,
+}
+
+impl StateDiff {
+    /// Whether the snapshots were identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Keeps only the changes to keys that start with one of `prefixes`.
+    pub fn filter(&self, prefixes: &[&[u8]]) -> Self {
+        fn keep<V: Clone>(map: &BTreeMap<Vec<u8>, V>, prefixes: &[&[u8]]) -> BTreeMap<Vec<u8>, V> {
+            map.iter()
+                .filter(|(key, _)| has_prefix(key, prefixes))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        }
+
+        Self {
+            added: keep(&self.added, prefixes),
+            removed: keep(&self.removed, prefixes),
+            changed: keep(&self.changed, prefixes),
+        }
+    }
+
+    /// Panics, listing the changes, if any key that starts with one of
+    /// `prefixes` was added, removed, or changed.
+    pub fn assert_unchanged(&self, prefixes: &[&[u8]]) {
+        let diff = self.filter(prefixes);
+        assert!(diff.is_empty(), "Unexpected state changes:\n{diff}");
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.added {
+            writeln!(f, "+ {}: {}", render(key), render(value))?;
+        }
+        for (key, value) in &self.removed {
+            writeln!(f, "- {}: {}", render(key), render(value))?;
+        }
+        for (key, (before, after)) in &self.changed {
+            writeln!(
+                f,
+                "~ {}: {} -> {}",
+                render(key),
+                render(before),
+                render(after)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn has_prefix(key: &[u8], prefixes: &[&[u8]]) -> bool {
+    prefixes.iter().any(|prefix| key.starts_with(prefix))
+}
+
+fn render(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|b| std::ascii::escape_default(*b))
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, &str)]) -> StateSnapshot {
+        entries
+            .iter()
+            .map(|(k, v)| (k.as_bytes(), v.as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn diff() {
+        let before = snapshot(&[("a1", "x"), ("a2", "y"), ("b1", "z")]);
+        let after = snapshot(&[("a1", "x"), ("a2", "w"), ("c1", "v")]);
+
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.added, snapshot(&[("c1", "v")]).entries);
+        assert_eq!(diff.removed, snapshot(&[("b1", "z")]).entries);
+        assert_eq!(
+            diff.changed,
+            [(b"a2".to_vec(), (b"y".to_vec(), b"w".to_vec()))].into(),
+        );
+        assert_eq!(diff.to_string(), "+ c1: v\n- b1: z\n~ a2: y -> w\n");
+
+        diff.assert_unchanged(&[b"d"]);
+        assert_eq!(
+            before.filter(&[b"a"]).diff(&after.filter(&[b"a1"])).removed,
+            snapshot(&[("a2", "y")]).entries,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected state changes:\n~ a2: y -> w")]
+    fn assert_unchanged() {
+        let before = snapshot(&[("a1", "x"), ("a2", "y")]);
+        let after = snapshot(&[("a1", "x"), ("a2", "w")]);
+
+        before.diff(&after).assert_unchanged(&[b"a"]);
+    }
+}
@@ -0,0 +1,231 @@
+//! Atomic groups of storage writes.
+//!
+//! A failed operation does not revert the storage writes it made before it
+//! failed. On chain this rarely matters, because the generated methods panic
+//! on error, which reverts the whole receipt. It does matter when a contract
+//! handles the error itself: if a transfer hook collects a fee and the
+//! transfer then fails, the fee stays collected.
+//!
+//! [`Transaction::run`] makes such an operation atomic. While a transaction
+//! is active, the value of each [`Slot`](crate::slot::Slot) is recorded
+//! before its first write (or removal). If the operation returns an error,
+//! the recorded values are restored.
+//!
+//! ```
+//! use near_sdk_contract_tools::{slot::Slot, transaction::Transaction};
+//!
+//! let mut fee = Slot::<u128>::new(b"fee".as_slice());
+//! let result: Result<(), &str> = Transaction::run(|| {
+//!     fee.write(&10);
+//!     Err("transfer failed")
+//! });
+//!
+//! assert!(result.is_err());
+//! assert!(!fee.exists());
+//! ```
+//!
+//! # Limits
+//!
+//! A transaction is a journal, not a staging area: writes go to storage
+//! immediately, and rollback writes the recorded values back. This keeps
+//! reads consistent without an in-memory overlay, and lets hooks that
+//! measure storage usage (e.g. NEP-145 storage accounting) see the writes.
+//!
+//! Only writes made through [`Slot`](crate::slot::Slot) are recorded. Writes
+//! made directly with `near_sdk::env` or through `near_sdk` collections
+//! (e.g. the NEP-178 approvals and the NEP-181 enumeration index) are not
+//! rolled back, so a rollback of an operation that makes such writes is
+//! partial. Do not rely on a transaction to undo them.
+//!
+//! For these reasons, only NEP-141 transfers run in a transaction. NEP-171
+//! transfers do not: `Nep171Controller::external_transfer` checks the
+//! transfer before it writes anything and cannot fail afterwards, so there is
+//! nothing to roll back, and the NEP-178 and NEP-181 hooks it runs write
+//! through collections, which a transaction would not restore.
+//!
+//! Transactions may be nested. A nested transaction that returns an error
+//! restores only its own writes.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use near_sdk::env;
+
+/// Values of the keys written in a transaction, before it first wrote them.
+type Journal = HashMap<Vec<u8>, Option<Vec<u8>>>;
+
+thread_local! {
+    /// Journals of the active transactions, innermost last.
+    static JOURNALS: RefCell<Vec<Journal>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An active transaction. Finish it with [`Transaction::commit`] or
+/// [`Transaction::rollback`], in reverse order of creation. Dropping it
+/// without finishing it rolls it back.
+///
+/// Only restores writes made through [`Slot`](crate::slot::Slot), not those
+/// made through `near_sdk` collections. See the [module
+/// documentation](self#limits).
+#[derive(Debug)]
+#[must_use]
+pub struct Transaction {
+    depth: usize,
+}
+
+impl Transaction {
+    /// Starts recording storage writes.
+    pub fn begin() -> Self {
+        let depth = JOURNALS.with(|journals| {
+            let mut journals = journals.borrow_mut();
+            journals.push(Journal::new());
+            journals.len()
+        });
+
+        Self { depth }
+    }
+
+    /// Runs `f` in a transaction, which is committed if `f` returns `Ok` and
+    /// rolled back otherwise.
+    pub fn run<T, E>(f: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+        let transaction = Self::begin();
+        let result = f();
+
+        if result.is_ok() {
+            transaction.commit();
+        } else {
+            transaction.rollback();
+        }
+
+        result
+    }
+
+    /// Returns whether a transaction is active.
+    pub fn is_active() -> bool {
+        JOURNALS.with(|journals| !journals.borrow().is_empty())
+    }
+
+    /// Keeps the writes made in this transaction. If it is nested, they are
+    /// rolled back if the enclosing transaction is.
+    pub fn commit(self) {
+        let journal = self.pop();
+
+        JOURNALS.with(|journals| {
+            if let Some(parent) = journals.borrow_mut().last_mut() {
+                for (key, value) in journal {
+                    parent.entry(key).or_insert(value);
+                }
+            }
+        });
+    }
+
+    /// Restores the values of the keys written in this transaction.
+    pub fn rollback(self) {
+        restore(self.pop());
+    }
+
+    fn pop(mut self) -> Journal {
+        let journal = self.take();
+        std::mem::forget(self);
+        journal
+    }
+
+    fn take(&mut self) -> Journal {
+        JOURNALS.with(|journals| {
+            let mut journals = journals.borrow_mut();
+            if journals.len() != self.depth {
+                env::panic_str("Transactions must be finished in reverse order of creation");
+            }
+            journals.pop().unwrap_or_default()
+        })
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            // The receipt is reverted anyway.
+            let _ = JOURNALS.try_with(|journals| journals.borrow_mut().truncate(self.depth - 1));
+        } else {
+            restore(self.take());
+        }
+    }
+}
+
+fn restore(journal: Journal) {
+    for (key, value) in journal {
+        match value {
+            Some(value) => {
+                env::storage_write(&key, &value);
+            }
+            None => {
+                #[cfg(feature = "debug")]
+                crate::debug::unregister(&key);
+
+                env::storage_remove(&key);
+            }
+        }
+    }
+}
+
+/// Records the value of `key` before its first write in the innermost
+/// active transaction.
+pub(crate) fn record(key: &[u8]) {
+    JOURNALS.with(|journals| {
+        if let Some(journal) = journals.borrow_mut().last_mut() {
+            if !journal.contains_key(key) {
+                journal.insert(key.to_vec(), env::storage_read(key));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::slot::Slot;
+
+    use super::*;
+
+    #[test]
+    fn nested() {
+        let mut a = Slot::<u32>::new(b"a".as_slice());
+        let mut b = Slot::<u32>::new(b"b".as_slice());
+        a.write(&1);
+
+        let result: Result<(), ()> = Transaction::run(|| {
+            a.write(&2);
+
+            let inner: Result<(), ()> = Transaction::run(|| {
+                a.write(&3);
+                b.write(&3);
+                Err(())
+            });
+            assert!(inner.is_err());
+            assert_eq!(a.read(), Some(2));
+            assert!(!b.exists());
+
+            Transaction::run(|| {
+                b.write(&4);
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+            assert_eq!(b.read(), Some(4));
+
+            Err(())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(a.read(), Some(1));
+        assert!(!b.exists());
+        assert!(!Transaction::is_active());
+
+        {
+            let _transaction = Transaction::begin();
+            a.remove();
+        }
+        assert_eq!(a.read(), Some(1));
+
+        let transaction = Transaction::begin();
+        a.write(&5);
+        transaction.commit();
+        assert_eq!(a.read(), Some(5));
+    }
+}
@@ -0,0 +1,297 @@
+//! Treasury guard pattern enforces a minimum native balance, so that
+//! transfers of NEAR out of the contract cannot drop its balance below what
+//! it needs to stake for storage.
+//!
+//! The minimum reserve is the cost of the contract's current storage usage
+//! plus a configurable operational buffer (e.g. to cover future storage
+//! growth or gas for callbacks). Before creating a `Promise` that transfers
+//! NEAR out, flows call [`Treasury::check_transfer`] or
+//! [`Treasury::require_transfer`], which return a typed error (or panic)
+//! instead of creating the `Promise`.
+//!
+//! Transfers are already deducted from the contract's balance when their
+//! `Promise` is created, so several transfers in the same function call are
+//! checked against the remaining balance.
+//!
+//! Integrations:
+//!
+//! * `#[nep145(treasury)]` checks NEP-145 storage refunds and withdrawals.
+//! * [`ReserveGuarded`] wraps an approval action (e.g.
+//!     [`NativeTransactionAction`]) and checks its outgoing deposits before
+//!     executing it.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::Treasury)
+//! derives a default implementation.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The treasury root storage slot is not used or modified. The
+//!     default key is `~tr`.
+//! * (ERR) [`Treasury::require_transfer`] may only be called when the
+//!     contract's balance after the transfer is at least
+//!     [`Treasury::min_reserve`].
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    serde::{Deserialize, Serialize},
+    AccountId, Balance, Promise,
+};
+use thiserror::Error;
+
+use crate::{
+    approval::{
+        native_transaction_action::{NativeTransactionAction, PromiseAction},
+        Action,
+    },
+    slot::Slot,
+    DefaultStorageKey,
+};
+
+/// A transfer would drop the contract's balance below the minimum reserve.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Transfer of {amount} yoctoNEAR exceeds the balance available above the minimum reserve ({available} yoctoNEAR)")]
+pub struct InsufficientReserveError {
+    /// Amount of the transfer.
+    pub amount: Balance,
+    /// Balance available above the minimum reserve.
+    pub available: Balance,
+}
+
+/// Internal functions for [`Treasury`]. Using these methods may result in unexpected behavior.
+pub trait TreasuryInternal {
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Treasury)
+    }
+
+    /// Storage slot for the operational buffer
+    fn slot_buffer() -> Slot<Balance> {
+        Self::root().transmute()
+    }
+}
+
+/// Enforces a minimum native balance.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{env, json_types::U128, near_bindgen, AccountId, Promise};
+/// use near_sdk_contract_tools::{treasury::Treasury, Treasury};
+///
+/// #[derive(Treasury)]
+/// #[near_bindgen]
+/// struct Contract {}
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn pay(&mut self, receiver_id: AccountId, amount: U128) -> Promise {
+///         Self::require_transfer(amount.0);
+///         Promise::new(receiver_id).transfer(amount.0)
+///     }
+/// }
+/// ```
+pub trait Treasury {
+    /// Returns the operational buffer kept in addition to the storage
+    /// staking requirement.
+    fn reserve_buffer() -> Balance;
+
+    /// Sets the operational buffer.
+    fn set_reserve_buffer(&mut self, buffer: Balance);
+
+    /// Returns the minimum balance of the contract: the cost of its current
+    /// storage usage plus the operational buffer.
+    fn min_reserve() -> Balance {
+        Balance::from(env::storage_usage())
+            .saturating_mul(env::storage_byte_cost())
+            .saturating_add(Self::reserve_buffer())
+    }
+
+    /// Returns the balance that may be transferred out of the contract.
+    fn available_balance() -> Balance {
+        env::account_balance().saturating_sub(Self::min_reserve())
+    }
+
+    /// Checks that transferring `amount` out of the contract keeps its
+    /// balance above the minimum reserve.
+    fn check_transfer(amount: Balance) -> Result<(), InsufficientReserveError> {
+        let available = Self::available_balance();
+
+        if amount > available {
+            return Err(InsufficientReserveError { amount, available });
+        }
+
+        Ok(())
+    }
+
+    /// Rejects if transferring `amount` out of the contract would drop its
+    /// balance below the minimum reserve.
+    fn require_transfer(amount: Balance) {
+        Self::check_transfer(amount).unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    /// Creates a `Promise` transferring `amount` to `receiver_id`, if the
+    /// contract's balance remains above the minimum reserve.
+    fn transfer_above_reserve(
+        receiver_id: AccountId,
+        amount: Balance,
+    ) -> Result<Promise, InsufficientReserveError> {
+        Self::check_transfer(amount)?;
+        Ok(Promise::new(receiver_id).transfer(amount))
+    }
+}
+
+impl<T: TreasuryInternal> Treasury for T {
+    fn reserve_buffer() -> Balance {
+        Self::slot_buffer().read().unwrap_or(0)
+    }
+
+    fn set_reserve_buffer(&mut self, buffer: Balance) {
+        Self::slot_buffer().set(Some(&buffer).filter(|b| **b > 0));
+    }
+}
+
+/// Actions that may transfer NEAR out of the contract.
+pub trait OutgoingDeposit {
+    /// Total amount of NEAR the action transfers out of the contract.
+    fn outgoing_deposit(&self) -> Balance;
+}
+
+impl OutgoingDeposit for NativeTransactionAction {
+    fn outgoing_deposit(&self) -> Balance {
+        self.actions
+            .iter()
+            .map(|action| match action {
+                PromiseAction::Transfer { amount } => amount.0,
+                PromiseAction::FunctionCall { amount, .. } => amount.0,
+                _ => 0,
+            })
+            .fold(0, Balance::saturating_add)
+    }
+}
+
+/// Approval action wrapper that checks the outgoing deposit of the wrapped
+/// action against the minimum reserve before executing it. Execution panics
+/// if the check fails.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde", transparent)]
+pub struct ReserveGuarded<A> {
+    /// The wrapped action.
+    pub action: A,
+}
+
+impl<C: Treasury, A: Action<C> + OutgoingDeposit> Action<C> for ReserveGuarded<A> {
+    type Output = A::Output;
+
+    fn execute(self, contract: &mut C) -> Self::Output {
+        C::require_transfer(self.action.outgoing_deposit());
+        self.action.execute(contract)
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::{ext_contract, json_types::U128};
+
+    /// External (public) methods for [`Treasury`]
+    #[ext_contract(ext_treasury)]
+    pub trait TreasuryExternal {
+        /// Returns the minimum balance of the contract
+        fn treasury_min_reserve(&self) -> U128;
+
+        /// Returns the balance that may be transferred out of the contract
+        fn treasury_available_balance(&self) -> U128;
+    }
+}
+
+pub use ext::*;
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{
+        json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env, ONE_NEAR,
+    };
+    use near_sdk_contract_tools_macros::Treasury;
+
+    use super::*;
+
+    #[derive(Treasury)]
+    #[treasury(crate = "crate")]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn setup(balance: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .account_balance(balance)
+            .storage_usage(1_000)
+            .build());
+    }
+
+    #[test]
+    fn min_reserve() {
+        setup(10 * ONE_NEAR);
+        let mut c = Contract {};
+
+        let storage_cost = || Balance::from(env::storage_usage()) * env::storage_byte_cost();
+        assert_eq!(Contract::min_reserve(), 1_000 * env::storage_byte_cost());
+
+        c.set_reserve_buffer(ONE_NEAR);
+        assert_eq!(Contract::min_reserve(), storage_cost() + ONE_NEAR);
+        assert_eq!(Contract::available_balance(), 9 * ONE_NEAR - storage_cost(),);
+    }
+
+    #[test]
+    fn check_transfer() {
+        setup(10 * ONE_NEAR);
+        let mut c = Contract {};
+        c.set_reserve_buffer(ONE_NEAR);
+
+        let available = Contract::available_balance();
+        assert_eq!(Contract::check_transfer(available), Ok(()));
+        assert_eq!(
+            Contract::check_transfer(available + 1),
+            Err(InsufficientReserveError {
+                amount: available + 1,
+                available,
+            }),
+        );
+    }
+
+    #[test]
+    fn transfers_deduct_balance() {
+        setup(10 * ONE_NEAR);
+        let available = Contract::available_balance();
+        let alice: AccountId = "alice".parse().unwrap();
+
+        Contract::transfer_above_reserve(alice.clone(), available - 1).unwrap();
+        assert_eq!(Contract::available_balance(), 1);
+        assert!(Contract::transfer_above_reserve(alice, 2).is_err());
+    }
+
+    #[test]
+    #[should_panic = "exceeds the balance available above the minimum reserve"]
+    fn reserve_guarded_action() {
+        setup(ONE_NEAR);
+        let mut c = Contract {};
+
+        let action = ReserveGuarded {
+            action: NativeTransactionAction {
+                receiver_id: "alice".parse().unwrap(),
+                actions: vec![
+                    PromiseAction::Transfer {
+                        amount: U128(ONE_NEAR / 2),
+                    },
+                    PromiseAction::Transfer {
+                        amount: U128(ONE_NEAR / 2),
+                    },
+                ],
+            },
+        };
+
+        action.execute(&mut c);
+    }
+}
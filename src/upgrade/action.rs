@@ -0,0 +1,100 @@
+//! Approval action that upgrades the contract.
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::Base64VecU8,
+    serde::{Deserialize, Serialize},
+    Promise,
+};
+
+use crate::approval::Action;
+
+use super::{serialized::upgrade, PostUpgrade, PostUpgradeArgsError};
+
+/// Deploys new code to the current contract, then calls the post-upgrade
+/// function (usually `migrate`) with the JSON arguments carried by the
+/// action, if any. The arguments are forwarded verbatim.
+///
+/// Since approval of the request is the authorization, executing the action
+/// does not invoke the [`UpgradeHook`](super::serialized::UpgradeHook).
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::serde::Serialize;
+/// use near_sdk_contract_tools::upgrade::action::UpgradeAction;
+///
+/// #[derive(Serialize)]
+/// #[serde(crate = "near_sdk::serde")]
+/// struct MigrateArgs {
+///     fee_basis_points: u16,
+/// }
+///
+/// let action = UpgradeAction::new(vec![0, 1, 2])
+///     .with_migrate_args(&MigrateArgs { fee_basis_points: 30 })
+///     .unwrap();
+///
+/// assert_eq!(
+///     action.migrate_args.as_deref(),
+///     Some(r#"{"fee_basis_points":30}"#),
+/// );
+/// ```
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeAction {
+    /// WASM binary blob
+    pub code: Base64VecU8,
+    /// JSON-encoded arguments for the post-upgrade function. Must be a JSON
+    /// object.
+    pub migrate_args: Option<String>,
+}
+
+impl UpgradeAction {
+    /// Upgrades to the given code without post-upgrade arguments
+    pub fn new(code: Vec<u8>) -> Self {
+        Self {
+            code: code.into(),
+            migrate_args: None,
+        }
+    }
+
+    /// Serializes the given arguments to JSON and attaches them to the
+    /// action
+    pub fn with_migrate_args(self, args: &impl Serialize) -> Result<Self, PostUpgradeArgsError> {
+        let PostUpgrade { args, .. } = PostUpgrade::with_json_args(args)?;
+
+        Ok(Self {
+            // serde_json always produces valid UTF-8
+            migrate_args: Some(String::from_utf8(args).unwrap()),
+            ..self
+        })
+    }
+
+    /// Checks the post-upgrade arguments, if any
+    pub fn validate(&self) -> Result<(), PostUpgradeArgsError> {
+        match &self.migrate_args {
+            Some(args) => PostUpgrade::validate_json_args(args.as_bytes()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<C> Action<C> for UpgradeAction {
+    type Output = Promise;
+
+    fn execute(self, _contract: &mut C) -> Self::Output {
+        self.validate()
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+
+        let post_upgrade = match self.migrate_args {
+            Some(args) => PostUpgrade {
+                args: args.into_bytes(),
+                ..Default::default()
+            },
+            None => PostUpgrade::default(),
+        };
+
+        upgrade(self.code.into(), post_upgrade)
+    }
+}
@@ -0,0 +1,276 @@
+//! History of upgrades, and throttling of upgrades based on it.
+//!
+//! Each upgrade records the hash of the deployed code and the time of the
+//! upgrade. An [`UpgradeThrottle`] policy rejects upgrades that come too
+//! soon after the last one, or too many upgrades in a period of time. This
+//! gives users time to react (e.g. to withdraw their funds) to a malicious
+//! upgrade, even if the keys or roles authorized to upgrade the contract are
+//! compromised.
+//!
+//! The policy is part of the contract code, not its state, so it cannot be
+//! relaxed without an upgrade, which is itself throttled.
+//!
+//! Enable with `#[upgrade(history)]` or `#[upgrade(throttle = "...")]`.
+//! Upgrades approved through an approval manager should use
+//! [`ThrottledUpgradeAction`] instead of [`UpgradeAction`].
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::near_bindgen;
+//! use near_sdk_contract_tools::{owner::*, upgrade::history::*, Owner, Upgrade};
+//!
+//! const DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+//!
+//! #[derive(Owner, Upgrade)]
+//! #[upgrade(
+//!     hook = "owner",
+//!     throttle = "UpgradeThrottle::min_interval(DAY).max_per_window(3, 30 * DAY)",
+//! )]
+//! #[near_bindgen]
+//! struct Contract {}
+//! ```
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The upgrade history root storage slot is not used or modified. The
+//!     default key is `~up`.
+//! * (ERR) [`UpgradeHistory::record_upgrade`] may only be called when the
+//!     [`UpgradeHistoryInternal::THROTTLE`] policy allows an upgrade.
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::{Base58CryptoHash, U64},
+    serde::{Deserialize, Serialize},
+    Promise,
+};
+use thiserror::Error;
+
+use crate::{approval::Action, slot::Slot, DefaultStorageKey};
+
+use super::action::UpgradeAction;
+
+pub use ext::*;
+
+/// Number of upgrades kept in the history, unless the throttle policy needs
+/// more.
+pub const UPGRADE_HISTORY_LENGTH: usize = 16;
+
+/// An upgrade of the contract.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeRecord {
+    /// SHA-256 hash of the deployed code.
+    pub code_hash: Base58CryptoHash,
+    /// Block timestamp of the upgrade, in nanoseconds.
+    pub timestamp: U64,
+}
+
+/// Limits on how often the contract may be upgraded. The default policy
+/// does not limit upgrades.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UpgradeThrottle {
+    /// Minimum time since the last upgrade, in nanoseconds.
+    pub min_interval: u64,
+    /// Maximum number of upgrades in any period of
+    /// [`UpgradeThrottle::window`] nanoseconds, or 0 for no maximum.
+    pub max_per_window: u32,
+    /// Length of the period limited by [`UpgradeThrottle::max_per_window`],
+    /// in nanoseconds.
+    pub window: u64,
+}
+
+impl UpgradeThrottle {
+    /// A policy that does not limit upgrades.
+    pub const NONE: Self = Self {
+        min_interval: 0,
+        max_per_window: 0,
+        window: 0,
+    };
+
+    /// Requires `min_interval` nanoseconds between upgrades.
+    pub const fn min_interval(min_interval: u64) -> Self {
+        Self {
+            min_interval,
+            ..Self::NONE
+        }
+    }
+
+    /// Allows at most `max` upgrades in any period of `window` nanoseconds.
+    pub const fn max_per_window(self, max: u32, window: u64) -> Self {
+        Self {
+            max_per_window: max,
+            window,
+            ..self
+        }
+    }
+
+    /// Returns when an upgrade is allowed, given the history, oldest
+    /// upgrade first. `None` if it is allowed at any time.
+    fn allowed_at(&self, history: &[UpgradeRecord]) -> Option<(u64, UpgradeThrottledError)> {
+        let min_interval = history.last().map(|last| {
+            let allowed_at = last.timestamp.0.saturating_add(self.min_interval);
+            (
+                allowed_at,
+                UpgradeThrottledError::MinInterval {
+                    min_interval: self.min_interval,
+                    allowed_at,
+                },
+            )
+        });
+
+        let max = self.max_per_window as usize;
+        let max_per_window = (max > 0 && history.len() >= max).then(|| {
+            let allowed_at = history[history.len() - max]
+                .timestamp
+                .0
+                .saturating_add(self.window);
+            (
+                allowed_at,
+                UpgradeThrottledError::MaxPerWindow {
+                    max_per_window: self.max_per_window,
+                    window: self.window,
+                    allowed_at,
+                },
+            )
+        });
+
+        match (min_interval, max_per_window) {
+            (Some(a), Some(b)) => Some(if b.0 > a.0 { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// The throttle policy does not allow an upgrade yet.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum UpgradeThrottledError {
+    /// The minimum interval since the last upgrade has not elapsed.
+    #[error("Upgrade not allowed until {allowed_at}: minimum interval of {min_interval} ns since the last upgrade")]
+    MinInterval {
+        /// Minimum interval between upgrades, in nanoseconds.
+        min_interval: u64,
+        /// Timestamp from which an upgrade is allowed.
+        allowed_at: u64,
+    },
+    /// The maximum number of upgrades in the window has been reached.
+    #[error(
+        "Upgrade not allowed until {allowed_at}: at most {max_per_window} upgrades per {window} ns"
+    )]
+    MaxPerWindow {
+        /// Maximum number of upgrades per window.
+        max_per_window: u32,
+        /// Length of the window, in nanoseconds.
+        window: u64,
+        /// Timestamp from which an upgrade is allowed.
+        allowed_at: u64,
+    },
+}
+
+/// Internal functions for [`UpgradeHistory`]. Using these methods may result
+/// in unexpected behavior.
+pub trait UpgradeHistoryInternal {
+    /// Limits on how often the contract may be upgraded.
+    const THROTTLE: UpgradeThrottle = UpgradeThrottle::NONE;
+
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::UpgradeHistory)
+    }
+
+    /// Storage slot for the upgrade history, oldest upgrade first
+    fn slot_history() -> Slot<Vec<UpgradeRecord>> {
+        Self::root().transmute()
+    }
+}
+
+/// Records upgrades and enforces the throttle policy.
+pub trait UpgradeHistory {
+    /// Returns the most recent upgrades, oldest first.
+    fn upgrade_history() -> Vec<UpgradeRecord>;
+
+    /// Returns the most recent upgrade.
+    fn last_upgrade() -> Option<UpgradeRecord> {
+        Self::upgrade_history().pop()
+    }
+
+    /// Checks that the throttle policy allows an upgrade now.
+    fn check_upgrade_allowed() -> Result<(), UpgradeThrottledError>;
+
+    /// Checks that the throttle policy allows an upgrade now, and records
+    /// an upgrade to `code`.
+    fn record_upgrade(&mut self, code: &[u8]) -> Result<(), UpgradeThrottledError>;
+}
+
+impl<T: UpgradeHistoryInternal> UpgradeHistory for T {
+    fn upgrade_history() -> Vec<UpgradeRecord> {
+        Self::slot_history().read().unwrap_or_default()
+    }
+
+    fn check_upgrade_allowed() -> Result<(), UpgradeThrottledError> {
+        match Self::THROTTLE.allowed_at(&Self::upgrade_history()) {
+            Some((allowed_at, error)) if env::block_timestamp() < allowed_at => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    fn record_upgrade(&mut self, code: &[u8]) -> Result<(), UpgradeThrottledError> {
+        Self::check_upgrade_allowed()?;
+
+        let mut history = Self::upgrade_history();
+        history.push(UpgradeRecord {
+            code_hash: env::sha256_array(code).into(),
+            timestamp: env::block_timestamp().into(),
+        });
+
+        let length = UPGRADE_HISTORY_LENGTH.max(Self::THROTTLE.max_per_window as usize);
+        if history.len() > length {
+            history.drain(..history.len() - length);
+        }
+
+        Self::slot_history().write(&history);
+
+        Ok(())
+    }
+}
+
+/// [`UpgradeAction`] that records the upgrade in the [`UpgradeHistory`].
+/// Execution panics if the throttle policy does not allow an upgrade.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde", transparent)]
+pub struct ThrottledUpgradeAction {
+    /// The upgrade.
+    pub action: UpgradeAction,
+}
+
+impl<C: UpgradeHistory> Action<C> for ThrottledUpgradeAction {
+    type Output = Promise;
+
+    fn execute(self, contract: &mut C) -> Self::Output {
+        contract
+            .record_upgrade(&self.action.code.0)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+
+        self.action.execute(contract)
+    }
+}
+
+mod ext {
+    #![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+    use near_sdk::ext_contract;
+
+    use super::UpgradeRecord;
+
+    /// External (public) methods for [`UpgradeHistory`](super::UpgradeHistory)
+    #[ext_contract(ext_upgrade_history)]
+    pub trait UpgradeHistoryExternal {
+        /// Returns the most recent upgrades, oldest first.
+        fn upgrade_history(&self) -> Vec<UpgradeRecord>;
+    }
+}
@@ -14,6 +14,13 @@
 //! migrated. This behaviour can be changed by providing a
 //! custom [`PostUpgrade`].
 //!
+//! Upgrades approved through an approval manager (e.g. a multisig) can use
+//! [`action::UpgradeAction`], which carries JSON arguments for the
+//! post-upgrade migrate function.
+//!
+//! [`history`] records upgrades and can throttle them, e.g. to require a
+//! minimum interval between upgrades.
+//!
 //! The
 #![cfg_attr(feature = "unstable", doc = "[`raw`]")]
 #![cfg_attr(not(feature = "unstable"), doc = "`raw` (feature: `unstable`)")]
@@ -29,7 +36,8 @@
 //! schema. If the new contract has a different storage schema from the old
 //! contract and does not migrate the state schema, the contract may become
 //! unusable.
-use near_sdk::Gas;
+use near_sdk::{serde::Serialize, serde_json, Gas};
+use thiserror::Error;
 
 /// Default value for the name of the function that will be called after
 /// upgrade (usually a migrate function).
@@ -39,7 +47,14 @@ pub const DEFAULT_POST_UPGRADE_METHOD_ARGS: Vec<u8> = vec![];
 /// Guarantee the post-upgrade function receives at least this much gas by
 /// default.
 pub const DEFAULT_POST_UPGRADE_MINIMUM_GAS: Gas = Gas(15_000_000_000_000);
+/// Maximum length of JSON arguments for the post-upgrade function, in bytes.
+pub const MAX_POST_UPGRADE_ARGS_LENGTH: usize = 4096;
+/// Error message emitted when an account that does not satisfy any of the
+/// authorizers in an `any(...)` upgrade hook attempts to upgrade.
+pub const UNAUTHORIZED_UPGRADE_MESSAGE: &str = "Unauthorized upgrade";
 
+pub mod action;
+pub mod history;
 #[cfg(feature = "unstable")]
 pub mod raw;
 pub mod serialized;
@@ -64,3 +79,42 @@ impl Default for PostUpgrade {
         }
     }
 }
+
+/// Errors that may occur when validating arguments for the post-upgrade
+/// function
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum PostUpgradeArgsError {
+    /// The arguments exceed [`MAX_POST_UPGRADE_ARGS_LENGTH`]
+    #[error("Post-upgrade arguments of {0} bytes exceed maximum length of {MAX_POST_UPGRADE_ARGS_LENGTH} bytes")]
+    TooLong(usize),
+    /// The arguments are not a JSON object
+    #[error("Post-upgrade arguments must be a JSON object")]
+    NotJsonObject,
+}
+
+impl PostUpgrade {
+    /// Checks that JSON arguments for the post-upgrade function are a JSON
+    /// object that does not exceed [`MAX_POST_UPGRADE_ARGS_LENGTH`].
+    pub fn validate_json_args(args: &[u8]) -> Result<(), PostUpgradeArgsError> {
+        if args.len() > MAX_POST_UPGRADE_ARGS_LENGTH {
+            return Err(PostUpgradeArgsError::TooLong(args.len()));
+        }
+
+        match serde_json::from_slice::<serde_json::Value>(args) {
+            Ok(serde_json::Value::Object(_)) => Ok(()),
+            _ => Err(PostUpgradeArgsError::NotJsonObject),
+        }
+    }
+
+    /// Calls the default post-upgrade function with the given arguments,
+    /// serialized to JSON.
+    pub fn with_json_args(args: &impl Serialize) -> Result<Self, PostUpgradeArgsError> {
+        let args = serde_json::to_vec(args).map_err(|_| PostUpgradeArgsError::NotJsonObject)?;
+        Self::validate_json_args(&args)?;
+
+        Ok(Self {
+            args,
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,406 @@
+//! Decimal-aware token amounts
+//!
+//! Fungible token amounts are stored on-chain as integers of the token's
+//! smallest unit. The [`Amount`] type converts between those raw integers and
+//! whole-token quantities using the decimals value from NEP-148 metadata,
+//! with explicit rounding and overflow checks.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk_contract_tools::utils::amount::{Amount, Rounding};
+//!
+//! let five = Amount::from_whole(5, 24).unwrap();
+//! assert_eq!(five.raw(), 5_000_000_000_000_000_000_000_000);
+//!
+//! let half = Amount::new(500_000_000_000_000_000_000_000);
+//! assert_eq!(half.to_whole(24, Rounding::Down).unwrap(), 0);
+//! assert_eq!(half.to_whole(24, Rounding::Nearest).unwrap(), 1);
+//! ```
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+};
+use thiserror::Error;
+
+/// The largest number of decimals whose scaling factor fits in a `u128`.
+pub const MAX_DECIMALS: u8 = 38;
+
+/// Errors that can occur when converting or operating on amounts.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum AmountError {
+    /// The scaling factor for this many decimals does not fit in a `u128`.
+    #[error("Decimals value {0} exceeds maximum of {MAX_DECIMALS}")]
+    DecimalsTooLarge(u8),
+    /// The result of the operation does not fit in a `u128`.
+    #[error("Amount arithmetic overflow")]
+    Overflow,
+    /// The result of the operation would be negative.
+    #[error("Amount arithmetic underflow")]
+    Underflow,
+    /// Division by zero.
+    #[error("Division by zero")]
+    DivisionByZero,
+    /// The fractional part is not less than one whole token.
+    #[error("Fraction {fraction} is not less than one whole token ({scale})")]
+    InvalidFraction {
+        /// The fractional part, in the smallest unit.
+        fraction: u128,
+        /// One whole token, in the smallest unit.
+        scale: u128,
+    },
+}
+
+/// How to handle remainders when converting to whole tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round toward zero.
+    Down,
+    /// Round away from zero.
+    Up,
+    /// Round to the nearest whole value, ties away from zero.
+    Nearest,
+}
+
+/// Returns `10^decimals`.
+///
+/// # Errors
+///
+/// - If `decimals` is greater than [`MAX_DECIMALS`].
+pub fn scale(decimals: u8) -> Result<u128, AmountError> {
+    10u128
+        .checked_pow(decimals.into())
+        .ok_or(AmountError::DecimalsTooLarge(decimals))
+}
+
+/// An amount of a fungible token, denominated in its smallest unit.
+///
+/// Serializes to JSON as a [`U128`] string, so it can be used directly in
+/// public method signatures.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[serde(crate = "near_sdk::serde", from = "U128", into = "U128")]
+pub struct Amount(u128);
+
+impl Amount {
+    /// Zero amount.
+    pub const ZERO: Self = Self(0);
+
+    /// Creates an amount from a raw (smallest unit) value.
+    pub const fn new(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    /// Raw (smallest unit) value of this amount.
+    pub const fn raw(&self) -> u128 {
+        self.0
+    }
+
+    /// Creates an amount from a number of whole tokens.
+    ///
+    /// # Errors
+    ///
+    /// - If `decimals` is too large.
+    /// - If the resulting amount overflows.
+    pub fn from_whole(whole: u128, decimals: u8) -> Result<Self, AmountError> {
+        whole
+            .checked_mul(scale(decimals)?)
+            .map(Self)
+            .ok_or(AmountError::Overflow)
+    }
+
+    /// Creates an amount from a whole part and a fractional part expressed in
+    /// the smallest unit, e.g. `from_parts(1, 5, 1)` is `1.5` with one decimal.
+    ///
+    /// # Errors
+    ///
+    /// - If `decimals` is too large.
+    /// - If `fraction` is not less than `10^decimals`.
+    /// - If the resulting amount overflows.
+    pub fn from_parts(whole: u128, fraction: u128, decimals: u8) -> Result<Self, AmountError> {
+        let scale = scale(decimals)?;
+
+        if fraction >= scale {
+            return Err(AmountError::InvalidFraction { fraction, scale });
+        }
+
+        Self::from_whole(whole, decimals)?.checked_add(Self(fraction))
+    }
+
+    /// Converts this amount to a number of whole tokens.
+    ///
+    /// # Errors
+    ///
+    /// - If `decimals` is too large.
+    pub fn to_whole(&self, decimals: u8, rounding: Rounding) -> Result<u128, AmountError> {
+        let scale = scale(decimals)?;
+        let whole = self.0 / scale;
+        let remainder = self.0 % scale;
+
+        let round_up = match rounding {
+            Rounding::Down => false,
+            Rounding::Up => remainder > 0,
+            Rounding::Nearest => remainder >= scale - scale / 2,
+        };
+
+        // whole <= u128::MAX / scale, so whole + 1 cannot overflow when scale > 1
+        Ok(if round_up { whole + 1 } else { whole })
+    }
+
+    /// The fractional part of this amount in the smallest unit.
+    ///
+    /// # Errors
+    ///
+    /// - If `decimals` is too large.
+    pub fn fraction(&self, decimals: u8) -> Result<u128, AmountError> {
+        Ok(self.0 % scale(decimals)?)
+    }
+
+    /// Formats this amount as a decimal number of whole tokens, e.g. `"1.5"`.
+    /// Trailing zeros in the fractional part are omitted.
+    pub fn to_decimal_string(&self, decimals: u8) -> String {
+        let decimals = decimals as usize;
+        if decimals == 0 {
+            return self.0.to_string();
+        }
+
+        let digits = format!("{:0>width$}", self.0, width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let fraction = fraction.trim_end_matches('0');
+
+        if fraction.is_empty() {
+            whole.to_string()
+        } else {
+            format!("{whole}.{fraction}")
+        }
+    }
+
+    /// Checked addition.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, AmountError> {
+        self.0
+            .checked_add(rhs.0)
+            .map(Self)
+            .ok_or(AmountError::Overflow)
+    }
+
+    /// Checked subtraction.
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, AmountError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Self)
+            .ok_or(AmountError::Underflow)
+    }
+
+    /// Checked multiplication by a scalar.
+    pub fn checked_mul(self, rhs: u128) -> Result<Self, AmountError> {
+        self.0
+            .checked_mul(rhs)
+            .map(Self)
+            .ok_or(AmountError::Overflow)
+    }
+
+    /// Checked division by a scalar, rounding toward zero.
+    pub fn checked_div(self, rhs: u128) -> Result<Self, AmountError> {
+        self.0
+            .checked_div(rhs)
+            .map(Self)
+            .ok_or(AmountError::DivisionByZero)
+    }
+
+    /// Computes `self * numerator / denominator` without intermediate
+    /// overflow, as long as the result itself fits in a `u128`. Useful for
+    /// percentages and exchange rates.
+    pub fn mul_div(
+        self,
+        numerator: u128,
+        denominator: u128,
+        rounding: Rounding,
+    ) -> Result<Self, AmountError> {
+        if denominator == 0 {
+            return Err(AmountError::DivisionByZero);
+        }
+
+        let (q, r) = (self.0 / denominator, self.0 % denominator);
+
+        // self * n / d = q * n + r * n / d, with r < d
+        let high = q.checked_mul(numerator).ok_or(AmountError::Overflow)?;
+        let (low, low_remainder) = mul_div_small(r, numerator, denominator)?;
+
+        let round_up = match rounding {
+            Rounding::Down => false,
+            Rounding::Up => low_remainder > 0,
+            Rounding::Nearest => low_remainder >= denominator - denominator / 2,
+        };
+
+        high.checked_add(low)
+            .and_then(|v| v.checked_add(round_up.into()))
+            .map(Self)
+            .ok_or(AmountError::Overflow)
+    }
+}
+
+/// Computes `(a * b / d, a * b % d)` for `a < d` using long multiplication.
+fn mul_div_small(a: u128, b: u128, d: u128) -> Result<(u128, u128), AmountError> {
+    if let Some(p) = a.checked_mul(b) {
+        return Ok((p / d, p % d));
+    }
+
+    // Binary long multiplication modulo d, tracking the quotient.
+    let mut quotient: u128 = 0;
+    let mut remainder: u128 = 0;
+    for i in (0..128).rev() {
+        // quotient, remainder = 2 * (quotient, remainder)
+        quotient = quotient.checked_mul(2).ok_or(AmountError::Overflow)?;
+        let (doubled, carry) = remainder.overflowing_add(remainder);
+        if carry || doubled >= d {
+            remainder = doubled.wrapping_sub(d);
+            quotient = quotient.checked_add(1).ok_or(AmountError::Overflow)?;
+        } else {
+            remainder = doubled;
+        }
+
+        if (b >> i) & 1 == 1 {
+            // quotient, remainder += (0, a), a < d
+            let (sum, carry) = remainder.overflowing_add(a);
+            if carry || sum >= d {
+                remainder = sum.wrapping_sub(d);
+                quotient = quotient.checked_add(1).ok_or(AmountError::Overflow)?;
+            } else {
+                remainder = sum;
+            }
+        }
+    }
+
+    Ok((quotient, remainder))
+}
+
+impl From<u128> for Amount {
+    fn from(value: u128) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Amount> for u128 {
+    fn from(value: Amount) -> Self {
+        value.0
+    }
+}
+
+impl From<U128> for Amount {
+    fn from(value: U128) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<Amount> for U128 {
+    fn from(value: Amount) -> Self {
+        U128(value.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_round_trip() {
+        let a = Amount::from_whole(5, 24).unwrap();
+        assert_eq!(a.raw(), 5 * 10u128.pow(24));
+        assert_eq!(a.to_whole(24, Rounding::Down).unwrap(), 5);
+        assert_eq!(a.to_whole(24, Rounding::Up).unwrap(), 5);
+
+        assert_eq!(
+            Amount::from_whole(1, 39),
+            Err(AmountError::DecimalsTooLarge(39)),
+        );
+        assert_eq!(Amount::from_whole(u128::MAX, 1), Err(AmountError::Overflow),);
+    }
+
+    #[test]
+    fn decimal_string() {
+        assert_eq!(Amount::new(15).to_decimal_string(1), "1.5");
+        assert_eq!(Amount::new(1_500).to_decimal_string(3), "1.5");
+        assert_eq!(Amount::new(5).to_decimal_string(3), "0.005");
+        assert_eq!(Amount::new(2_000).to_decimal_string(3), "2");
+        assert_eq!(Amount::ZERO.to_decimal_string(24), "0");
+        assert_eq!(Amount::new(42).to_decimal_string(0), "42");
+        assert_eq!(
+            Amount::new(u128::MAX).to_decimal_string(40),
+            "0.0340282366920938463463374607431768211455",
+        );
+    }
+
+    #[test]
+    fn rounding() {
+        let a = Amount::from_parts(1, 5, 1).unwrap();
+        assert_eq!(a.raw(), 15);
+        assert_eq!(
+            Amount::from_parts(1, 10, 1),
+            Err(AmountError::InvalidFraction {
+                fraction: 10,
+                scale: 10
+            }),
+        );
+        assert_eq!(a.to_whole(1, Rounding::Down).unwrap(), 1);
+        assert_eq!(a.to_whole(1, Rounding::Up).unwrap(), 2);
+        assert_eq!(a.to_whole(1, Rounding::Nearest).unwrap(), 2);
+
+        let b = Amount::new(14);
+        assert_eq!(b.to_whole(1, Rounding::Nearest).unwrap(), 1);
+        assert_eq!(
+            Amount::new(u128::MAX).to_whole(0, Rounding::Up).unwrap(),
+            u128::MAX
+        );
+    }
+
+    #[test]
+    fn mul_div() {
+        let a = Amount::new(u128::MAX);
+        assert_eq!(a.mul_div(3, 3, Rounding::Down).unwrap(), a);
+        assert_eq!(
+            a.mul_div(1, 2, Rounding::Down).unwrap().raw(),
+            u128::MAX / 2
+        );
+        assert_eq!(
+            a.mul_div(1, 2, Rounding::Up).unwrap().raw(),
+            u128::MAX / 2 + 1
+        );
+        assert_eq!(
+            Amount::new(10)
+                .mul_div(1, 3, Rounding::Nearest)
+                .unwrap()
+                .raw(),
+            3
+        );
+        assert_eq!(a.mul_div(2, 1, Rounding::Down), Err(AmountError::Overflow));
+        assert_eq!(
+            a.mul_div(1, 0, Rounding::Down),
+            Err(AmountError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn serde_as_u128() {
+        let a = Amount::new(1234);
+        assert_eq!(near_sdk::serde_json::to_string(&a).unwrap(), "\"1234\"");
+        assert_eq!(
+            near_sdk::serde_json::from_str::<Amount>("\"1234\"").unwrap(),
+            a
+        );
+    }
+}
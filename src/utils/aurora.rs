@@ -0,0 +1,518 @@
+//! Calls to the [Aurora](https://aurora.dev) engine, an EVM deployed as a
+//! NEAR contract.
+//!
+//! * [`Engine::call`] calls an EVM contract as this contract's EVM address,
+//!     with input built by [`encode_call`].
+//! * [`Engine::submit`] submits a signed Ethereum transaction (e.g. one
+//!     signed with NEAR chain signatures).
+//! * [`Engine::ft_transfer_call`] deposits NEP-141 tokens to an EVM address,
+//!     with the `msg` format the engine expects ([`deposit_msg`]).
+//! * [`SubmitResult`] parses the result of `call` and `submit`, and
+//!     [`decode_uint`], [`decode_address`], and [`decode_bool`] parse the
+//!     return data of the EVM call.
+//!
+//! Hashing uses the `near_sdk::env` host functions, so these helpers must be
+//! called from within a contract (or a mocked blockchain in tests).
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{Gas, Promise};
+//! use near_sdk_contract_tools::utils::{
+//!     aurora::{encode_call, AbiToken, CallArgs, Engine},
+//!     foreign_address::EvmAddress,
+//! };
+//!
+//! fn transfer_erc20(token: EvmAddress, recipient: EvmAddress, amount: u128) -> Promise {
+//!     let input = encode_call(
+//!         "transfer(address,uint256)",
+//!         &[AbiToken::Address(recipient), AbiToken::Uint(amount)],
+//!     );
+//!
+//!     Engine::mainnet().call(&CallArgs::new(token, input), Gas(100_000_000_000_000))
+//! }
+//! ```
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    serde_json::json,
+    AccountId, Gas, Promise,
+};
+use thiserror::Error;
+
+use super::foreign_address::{EvmAddress, EVM_ADDRESS_LENGTH};
+
+/// Account ID of the Aurora engine on mainnet.
+pub const AURORA_MAINNET_ENGINE_ID: &str = "aurora";
+
+/// Length of an ABI word in bytes.
+pub const ABI_WORD_LENGTH: usize = 32;
+
+/// Selector of the `Error(string)` revert reason.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// An Aurora engine contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Engine {
+    /// Account ID of the engine.
+    pub account_id: AccountId,
+}
+
+impl Engine {
+    /// The engine deployed at `account_id`.
+    pub fn new(account_id: AccountId) -> Self {
+        Self { account_id }
+    }
+
+    /// The engine on mainnet.
+    pub fn mainnet() -> Self {
+        Self::new(AURORA_MAINNET_ENGINE_ID.parse().unwrap())
+    }
+
+    /// Calls an EVM contract as the EVM address of this contract. The
+    /// promise resolves to a Borsh-serialized [`SubmitResult`].
+    pub fn call(&self, args: &CallArgs, gas: Gas) -> Promise {
+        Promise::new(self.account_id.clone()).function_call(
+            "call".to_string(),
+            args.to_borsh(),
+            0,
+            gas,
+        )
+    }
+
+    /// Submits an RLP-encoded, signed Ethereum transaction. The promise
+    /// resolves to a Borsh-serialized [`SubmitResult`].
+    pub fn submit(&self, signed_transaction: Vec<u8>, gas: Gas) -> Promise {
+        Promise::new(self.account_id.clone()).function_call(
+            "submit".to_string(),
+            signed_transaction,
+            0,
+            gas,
+        )
+    }
+
+    /// Deposits `amount` of the NEP-141 token `token_id` to `recipient` in
+    /// the EVM, with `ft_transfer_call` from this contract. The token must
+    /// be bridged to the engine.
+    pub fn ft_transfer_call(
+        &self,
+        token_id: AccountId,
+        amount: u128,
+        recipient: &EvmAddress,
+        memo: Option<String>,
+        gas: Gas,
+    ) -> Promise {
+        let args = json!({
+            "receiver_id": self.account_id,
+            "amount": U128(amount),
+            "memo": memo,
+            "msg": deposit_msg(recipient),
+        });
+
+        Promise::new(token_id).function_call(
+            "ft_transfer_call".to_string(),
+            args.to_string().into_bytes(),
+            1,
+            gas,
+        )
+    }
+}
+
+/// The `msg` of an `ft_transfer_call` to the engine that deposits the
+/// tokens to `recipient`: the address in lowercase hex, without the `0x`
+/// prefix.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk_contract_tools::utils::{aurora::deposit_msg, foreign_address::EvmAddress};
+///
+/// let recipient = EvmAddress::from_bytes([0xab; 20]);
+/// assert_eq!(deposit_msg(&recipient), "ab".repeat(20));
+/// ```
+pub fn deposit_msg(recipient: &EvmAddress) -> String {
+    recipient.to_lowercase_string()[2..].to_string()
+}
+
+/// Arguments of the engine's `call` method.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallArgs {
+    /// The EVM contract to call.
+    pub contract: EvmAddress,
+    /// Amount of ETH (in wei) to send.
+    pub value: u128,
+    /// ABI-encoded input, e.g. from [`encode_call`].
+    pub input: Vec<u8>,
+}
+
+impl CallArgs {
+    /// Calls `contract` with `input`, without sending ETH.
+    pub fn new(contract: EvmAddress, input: Vec<u8>) -> Self {
+        Self {
+            contract,
+            value: 0,
+            input,
+        }
+    }
+
+    /// Sends `value` wei with the call.
+    pub fn with_value(self, value: u128) -> Self {
+        Self { value, ..self }
+    }
+
+    /// Serializes the arguments as the engine expects (`CallArgs::V2`).
+    pub fn to_borsh(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + EVM_ADDRESS_LENGTH + ABI_WORD_LENGTH + 4);
+        bytes.push(0);
+        bytes.extend_from_slice(self.contract.as_bytes());
+        bytes.extend_from_slice(&uint_word(self.value));
+        bytes.extend_from_slice(&(self.input.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.input);
+        bytes
+    }
+}
+
+/// Result of the engine's `call` and `submit` methods.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SubmitResult {
+    /// Version of the result format.
+    pub version: u8,
+    /// Status of the EVM transaction.
+    pub status: TransactionStatus,
+    /// EVM gas used.
+    pub gas_used: u64,
+    /// Logs emitted by the EVM transaction.
+    pub logs: Vec<ResultLog>,
+}
+
+impl SubmitResult {
+    /// Parses the result of the promise at `index` (e.g. in a callback).
+    /// Returns `None` if the promise failed or its result is not a
+    /// [`SubmitResult`].
+    pub fn from_promise_result(index: u64) -> Option<Self> {
+        match env::promise_result(index) {
+            near_sdk::PromiseResult::Successful(bytes) => Self::try_from_slice(&bytes).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the output of the EVM transaction, or why it failed.
+    pub fn into_output(self) -> Result<Vec<u8>, EvmError> {
+        match self.status {
+            TransactionStatus::Succeed(output) => Ok(output),
+            TransactionStatus::Revert(data) => Err(EvmError::Revert {
+                reason: revert_reason(&data),
+                data,
+            }),
+            TransactionStatus::OutOfGas => Err(EvmError::OutOfGas),
+            TransactionStatus::OutOfFund => Err(EvmError::OutOfFund),
+            TransactionStatus::OutOfOffset => Err(EvmError::OutOfOffset),
+            TransactionStatus::CallTooDeep => Err(EvmError::CallTooDeep),
+        }
+    }
+}
+
+/// Status of an EVM transaction.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// The transaction succeeded, with this output.
+    Succeed(Vec<u8>),
+    /// The transaction reverted, with this data.
+    Revert(Vec<u8>),
+    /// The transaction ran out of gas.
+    OutOfGas,
+    /// The sender could not pay for the transaction.
+    OutOfFund,
+    /// The transaction read out of bounds.
+    OutOfOffset,
+    /// The call stack was too deep.
+    CallTooDeep,
+}
+
+/// A log emitted by an EVM transaction.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ResultLog {
+    /// Address of the contract that emitted the log.
+    pub address: EvmAddress,
+    /// Indexed topics.
+    pub topics: Vec<[u8; ABI_WORD_LENGTH]>,
+    /// Non-indexed data.
+    pub data: Vec<u8>,
+}
+
+/// An EVM transaction did not succeed.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum EvmError {
+    /// The transaction reverted.
+    #[error("EVM transaction reverted{}", .reason.as_ref().map(|r| format!(": {r}")).unwrap_or_default())]
+    Revert {
+        /// Revert reason, if the data is an `Error(string)`.
+        reason: Option<String>,
+        /// Revert data.
+        data: Vec<u8>,
+    },
+    /// The transaction ran out of gas.
+    #[error("EVM transaction ran out of gas")]
+    OutOfGas,
+    /// The sender could not pay for the transaction.
+    #[error("EVM transaction sender is out of funds")]
+    OutOfFund,
+    /// The transaction read out of bounds.
+    #[error("EVM transaction read out of bounds")]
+    OutOfOffset,
+    /// The call stack was too deep.
+    #[error("EVM call stack too deep")]
+    CallTooDeep,
+}
+
+/// A value to ABI-encode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiToken {
+    /// `address`
+    Address(EvmAddress),
+    /// `uint<N>`, for values that fit in 128 bits.
+    Uint(u128),
+    /// `bool`
+    Bool(bool),
+    /// `bytes32`
+    FixedBytes([u8; ABI_WORD_LENGTH]),
+    /// `bytes`
+    Bytes(Vec<u8>),
+    /// `string`
+    String(String),
+}
+
+impl AbiToken {
+    fn is_dynamic(&self) -> bool {
+        matches!(self, Self::Bytes(_) | Self::String(_))
+    }
+}
+
+/// Returns the selector of a function, e.g. `"transfer(address,uint256)"`:
+/// the first 4 bytes of the Keccak-256 hash of its signature.
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let hash = env::keccak256_array(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// ABI-encodes a call of the function with `signature` (e.g.
+/// `"transfer(address,uint256)"`) with `tokens` as arguments.
+pub fn encode_call(signature: &str, tokens: &[AbiToken]) -> Vec<u8> {
+    [&function_selector(signature)[..], &encode(tokens)].concat()
+}
+
+/// ABI-encodes `tokens` as a tuple.
+pub fn encode(tokens: &[AbiToken]) -> Vec<u8> {
+    let mut head = Vec::with_capacity(tokens.len() * ABI_WORD_LENGTH);
+    let mut tail = Vec::new();
+
+    for token in tokens {
+        if token.is_dynamic() {
+            let offset = tokens.len() * ABI_WORD_LENGTH + tail.len();
+            head.extend_from_slice(&uint_word(offset as u128));
+        }
+
+        match token {
+            AbiToken::Address(address) => {
+                head.extend_from_slice(&[0; ABI_WORD_LENGTH - EVM_ADDRESS_LENGTH]);
+                head.extend_from_slice(address.as_bytes());
+            }
+            AbiToken::Uint(value) => head.extend_from_slice(&uint_word(*value)),
+            AbiToken::Bool(value) => head.extend_from_slice(&uint_word(*value as u128)),
+            AbiToken::FixedBytes(bytes) => head.extend_from_slice(bytes),
+            AbiToken::Bytes(bytes) => encode_bytes(&mut tail, bytes),
+            AbiToken::String(string) => encode_bytes(&mut tail, string.as_bytes()),
+        }
+    }
+
+    head.extend(tail);
+    head
+}
+
+fn encode_bytes(tail: &mut Vec<u8>, bytes: &[u8]) {
+    tail.extend_from_slice(&uint_word(bytes.len() as u128));
+    tail.extend_from_slice(bytes);
+    let padding = (ABI_WORD_LENGTH - bytes.len() % ABI_WORD_LENGTH) % ABI_WORD_LENGTH;
+    tail.resize(tail.len() + padding, 0);
+}
+
+fn uint_word(value: u128) -> [u8; ABI_WORD_LENGTH] {
+    let mut word = [0; ABI_WORD_LENGTH];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Errors that can occur when decoding ABI-encoded data.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum AbiDecodeError {
+    /// The data does not contain the requested word.
+    #[error("ABI word {0} is out of bounds")]
+    OutOfBounds(usize),
+    /// The value does not fit in the requested type.
+    #[error("ABI word {0} is not a valid value of the requested type")]
+    InvalidValue(usize),
+}
+
+fn word(data: &[u8], index: usize) -> Result<&[u8], AbiDecodeError> {
+    data.get(index * ABI_WORD_LENGTH..(index + 1) * ABI_WORD_LENGTH)
+        .ok_or(AbiDecodeError::OutOfBounds(index))
+}
+
+/// Decodes the `uint<N>` at word `index` of `data`, e.g. the return value
+/// of `balanceOf(address)`. Fails if it does not fit in 128 bits.
+pub fn decode_uint(data: &[u8], index: usize) -> Result<u128, AbiDecodeError> {
+    let word = word(data, index)?;
+    if word[..16].iter().any(|b| *b != 0) {
+        return Err(AbiDecodeError::InvalidValue(index));
+    }
+
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&word[16..]);
+    Ok(u128::from_be_bytes(bytes))
+}
+
+/// Decodes the `address` at word `index` of `data`.
+pub fn decode_address(data: &[u8], index: usize) -> Result<EvmAddress, AbiDecodeError> {
+    let word = word(data, index)?;
+    let (padding, address) = word.split_at(ABI_WORD_LENGTH - EVM_ADDRESS_LENGTH);
+    if padding.iter().any(|b| *b != 0) {
+        return Err(AbiDecodeError::InvalidValue(index));
+    }
+
+    let mut bytes = [0; EVM_ADDRESS_LENGTH];
+    bytes.copy_from_slice(address);
+    Ok(EvmAddress::from_bytes(bytes))
+}
+
+/// Decodes the `bool` at word `index` of `data`.
+pub fn decode_bool(data: &[u8], index: usize) -> Result<bool, AbiDecodeError> {
+    match decode_uint(data, index) {
+        Ok(0) => Ok(false),
+        Ok(1) => Ok(true),
+        _ => Err(AbiDecodeError::InvalidValue(index)),
+    }
+}
+
+/// Decodes the reason of a revert with `Error(string)` data, e.g. from
+/// `require(condition, "reason")`.
+pub fn revert_reason(data: &[u8]) -> Option<String> {
+    let data = data.strip_prefix(&ERROR_SELECTOR)?;
+    let offset = decode_uint(data, 0).ok()? as usize;
+    let length = decode_uint(data.get(offset..)?, 0).ok()? as usize;
+    let start = offset.checked_add(ABI_WORD_LENGTH)?;
+    let bytes = data.get(start..start.checked_add(length)?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn encode_transfer() {
+        let recipient: EvmAddress = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+            .parse()
+            .unwrap();
+        let input = encode_call(
+            "transfer(address,uint256)",
+            &[AbiToken::Address(recipient), AbiToken::Uint(1000)],
+        );
+
+        assert_eq!(
+            input,
+            hex(concat!(
+                "a9059cbb",
+                "0000000000000000000000005aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+                "00000000000000000000000000000000000000000000000000000000000003e8",
+            )),
+        );
+    }
+
+    #[test]
+    fn encode_dynamic() {
+        // Example from the Solidity ABI specification.
+        let encoded = encode(&[
+            AbiToken::Bytes(b"dave".to_vec()),
+            AbiToken::Bool(true),
+            AbiToken::Uint(3),
+        ]);
+
+        assert_eq!(
+            encoded,
+            hex(concat!(
+                "0000000000000000000000000000000000000000000000000000000000000060",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "0000000000000000000000000000000000000000000000000000000000000004",
+                "6461766500000000000000000000000000000000000000000000000000000000",
+            )),
+        );
+    }
+
+    #[test]
+    fn decode() {
+        let data = encode(&[
+            AbiToken::Uint(u128::MAX),
+            AbiToken::Bool(true),
+            AbiToken::Address(EvmAddress::from_bytes([1; 20])),
+        ]);
+
+        assert_eq!(decode_uint(&data, 0), Ok(u128::MAX));
+        assert_eq!(decode_bool(&data, 1), Ok(true));
+        assert_eq!(
+            decode_address(&data, 2),
+            Ok(EvmAddress::from_bytes([1; 20])),
+        );
+        assert_eq!(decode_bool(&data, 0), Err(AbiDecodeError::InvalidValue(0)));
+        assert_eq!(decode_uint(&data, 3), Err(AbiDecodeError::OutOfBounds(3)));
+    }
+
+    #[test]
+    fn submit_result() {
+        let revert = [
+            &ERROR_SELECTOR[..],
+            &encode(&[AbiToken::String("Insufficient balance".to_string())]),
+        ]
+        .concat();
+        let result = SubmitResult {
+            version: 7,
+            status: TransactionStatus::Revert(revert.clone()),
+            gas_used: 21_000,
+            logs: vec![],
+        };
+
+        let result = SubmitResult::try_from_slice(&result.try_to_vec().unwrap()).unwrap();
+        let error = result.into_output().unwrap_err();
+        assert_eq!(
+            error,
+            EvmError::Revert {
+                reason: Some("Insufficient balance".to_string()),
+                data: revert,
+            },
+        );
+        assert_eq!(
+            error.to_string(),
+            "EVM transaction reverted: Insufficient balance",
+        );
+    }
+
+    #[test]
+    fn call_args() {
+        let args = CallArgs::new(EvmAddress::from_bytes([2; 20]), vec![9]).with_value(1);
+        let bytes = args.to_borsh();
+
+        assert_eq!(bytes[0], 0);
+        assert_eq!(&bytes[1..21], &[2; 20]);
+        assert_eq!(bytes[52], 1);
+        assert_eq!(&bytes[53..], &[1, 0, 0, 0, 9]);
+    }
+}
@@ -0,0 +1,190 @@
+//! Gas-aware batch processing.
+//!
+//! Operations that walk a collection the contract itself enumerates, like
+//! the state migration in [`crate::blue_green`], cannot be completed in a
+//! single function call once the collection grows large enough.
+//! [`Resumable`] runs a step function over a cursor until the remaining gas
+//! falls below a reserve, then persists the cursor so that the next call
+//! picks up where the last one left off.
+//!
+//! Operations whose work is bounded by their arguments do not need a cursor:
+//! `Nep141Controller::sweep_zero_balances` and `Nep141Dust::sweep_dust`
+//! process the accounts they are given, and
+//! `Nep181Controller::compact_owner_index` refuses indices longer than its
+//! `limit`. Callers split that work across calls themselves.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::Gas;
+//! use near_sdk_contract_tools::{
+//!     slot::Slot,
+//!     utils::batch::{BatchStatus, Resumable, Step},
+//! };
+//!
+//! const TOTAL: u32 = 100;
+//!
+//! let mut airdrop = Resumable::new(Slot::new(b"a"), Gas(5_000_000_000_000));
+//!
+//! let status = airdrop.run(
+//!     || 0u32,
+//!     |next| {
+//!         // ... process recipient `next` ...
+//!         *next += 1;
+//!         if *next == TOTAL {
+//!             Step::Done
+//!         } else {
+//!             Step::Continue
+//!         }
+//!     },
+//! );
+//!
+//! assert_eq!(status, BatchStatus::Done);
+//! assert!(!airdrop.is_in_progress());
+//! ```
+
+use near_sdk::{
+    borsh::{BorshDeserialize, BorshSerialize},
+    env, Gas,
+};
+
+use crate::slot::Slot;
+
+/// Returned by the step function passed to [`Resumable::run`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// There is more work to do.
+    Continue,
+    /// The batch operation is complete.
+    Done,
+}
+
+/// Result of a call to [`Resumable::run`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchStatus {
+    /// The batch operation completed and the cursor was removed.
+    Done,
+    /// The gas reserve was reached. The cursor was persisted, and the
+    /// operation will resume from it on the next call.
+    Paused,
+}
+
+/// Runs a batch operation over a persisted cursor, pausing before the
+/// remaining gas falls below a reserve.
+#[derive(Clone, Debug)]
+pub struct Resumable<C> {
+    cursor: Slot<C>,
+    gas_reserve: Gas,
+}
+
+impl<C: BorshSerialize + BorshDeserialize> Resumable<C> {
+    /// Creates a batch operation that persists its cursor in `cursor` and
+    /// leaves at least `gas_reserve` unused when pausing.
+    pub fn new(cursor: Slot<C>, gas_reserve: Gas) -> Self {
+        Self {
+            cursor,
+            gas_reserve,
+        }
+    }
+
+    /// The persisted cursor, if an operation is in progress.
+    pub fn cursor(&self) -> Option<C> {
+        self.cursor.read()
+    }
+
+    /// Whether an operation has been paused and not yet completed.
+    pub fn is_in_progress(&self) -> bool {
+        self.cursor.exists()
+    }
+
+    /// Abandons an operation in progress.
+    pub fn reset(&mut self) {
+        self.cursor.remove();
+    }
+
+    fn has_gas_for_step(&self) -> bool {
+        env::prepaid_gas().0.saturating_sub(env::used_gas().0) > self.gas_reserve.0
+    }
+
+    /// Calls `step` repeatedly until it returns [`Step::Done`] or the gas
+    /// reserve is reached. Starts from the persisted cursor if an operation
+    /// is in progress, or from `start()` otherwise.
+    ///
+    /// Each call to `step` should do a bounded amount of work, and must
+    /// advance the cursor: a step that does not complete is not retried
+    /// from its starting point.
+    pub fn run(
+        &mut self,
+        start: impl FnOnce() -> C,
+        mut step: impl FnMut(&mut C) -> Step,
+    ) -> BatchStatus {
+        let mut cursor = self.cursor.read().unwrap_or_else(start);
+
+        while self.has_gas_for_step() {
+            if step(&mut cursor) == Step::Done {
+                self.cursor.remove();
+                return BatchStatus::Done;
+            }
+        }
+
+        self.cursor.write(&cursor);
+        BatchStatus::Paused
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+
+    fn with_prepaid_gas(gas: Gas) {
+        testing_env!(VMContextBuilder::new().prepaid_gas(gas).build());
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let mut batch = Resumable::new(Slot::new(b"c"), Gas(5_000_000_000_000));
+        let step = |i: &mut u32| {
+            env::storage_write(&i.to_le_bytes(), &[0; 100]);
+            *i += 1;
+            if *i == 2_000 {
+                Step::Done
+            } else {
+                Step::Continue
+            }
+        };
+
+        with_prepaid_gas(Gas(20_000_000_000_000));
+        assert_eq!(batch.run(|| 0, step), BatchStatus::Paused);
+        let first = batch.cursor().unwrap();
+        assert!(first > 0);
+        assert!(env::storage_has_key(&(first - 1).to_le_bytes()));
+        assert!(!env::storage_has_key(&first.to_le_bytes()));
+
+        with_prepaid_gas(Gas(20_000_000_000_000));
+        assert_eq!(batch.run(|| 0, step), BatchStatus::Paused);
+        assert!(batch.cursor().unwrap() > first);
+
+        loop {
+            with_prepaid_gas(Gas(20_000_000_000_000));
+            if batch.run(|| 0, step) == BatchStatus::Done {
+                break;
+            }
+        }
+        assert!(!batch.is_in_progress());
+        assert!(env::storage_has_key(&1_999u32.to_le_bytes()));
+    }
+
+    #[test]
+    fn reserve_exceeds_prepaid_gas() {
+        with_prepaid_gas(Gas(10_000_000_000_000));
+        let mut batch = Resumable::new(Slot::new(b"c"), Gas(20_000_000_000_000));
+
+        assert_eq!(batch.run(|| 7u32, |_| unreachable!()), BatchStatus::Paused);
+        assert_eq!(batch.cursor(), Some(7));
+
+        batch.reset();
+        assert!(!batch.is_in_progress());
+    }
+}
@@ -0,0 +1,313 @@
+//! Typed addresses for foreign (non-NEAR) chains.
+//!
+//! Contracts that bridge to, or sign transactions for, other chains can use
+//! [`EvmAddress`] instead of passing raw strings around, and
+//! [`DerivationPath`] to describe keys derived by the NEAR chain signatures
+//! MPC service.
+//!
+//! Hashing uses the `near_sdk::env` host functions, so these helpers must be
+//! called from within a contract (or a mocked blockchain in tests).
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk_contract_tools::utils::foreign_address::EvmAddress;
+//!
+//! let address: EvmAddress = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(
+//!     address.to_string(),
+//!     "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+//! );
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
+    AccountId,
+};
+use thiserror::Error;
+
+/// Length of an EVM address in bytes.
+pub const EVM_ADDRESS_LENGTH: usize = 20;
+
+/// Prefix hashed together with the predecessor and path by the NEAR chain
+/// signatures MPC service to derive a child key.
+pub const EPSILON_DERIVATION_PREFIX: &str = "near-mpc-recovery v0.1.0 epsilon derivation:";
+
+/// Errors that can occur when parsing an [`EvmAddress`].
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum EvmAddressParseError {
+    /// The address does not contain exactly 40 hexadecimal digits.
+    #[error("Invalid EVM address length: expected 40 hex digits, got {0}")]
+    InvalidLength(usize),
+    /// The address contains a non-hexadecimal character.
+    #[error("Invalid hex character `{0}` in EVM address")]
+    InvalidCharacter(char),
+    /// The address is mixed-case but does not match its EIP-55 checksum.
+    #[error("EVM address checksum mismatch: expected `{expected}`")]
+    InvalidChecksum {
+        /// Correctly checksummed address.
+        expected: String,
+    },
+}
+
+/// A 20-byte EVM account address. Formats using the EIP-55 mixed-case
+/// checksum, and serializes to JSON as a checksummed string.
+#[derive(
+    BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+pub struct EvmAddress(pub [u8; EVM_ADDRESS_LENGTH]);
+
+impl EvmAddress {
+    /// Creates an address from raw bytes.
+    pub const fn from_bytes(bytes: [u8; EVM_ADDRESS_LENGTH]) -> Self {
+        Self(bytes)
+    }
+
+    /// Raw bytes of the address.
+    pub const fn as_bytes(&self) -> &[u8; EVM_ADDRESS_LENGTH] {
+        &self.0
+    }
+
+    /// Derives the address of an uncompressed secp256k1 public key, given
+    /// either as 64 bytes (`x || y`) or as 65 bytes with the `0x04` prefix.
+    /// Returns `None` if the key has any other length.
+    pub fn from_public_key(public_key: &[u8]) -> Option<Self> {
+        let key = match public_key.len() {
+            64 => public_key,
+            65 if public_key[0] == 0x04 => &public_key[1..],
+            _ => return None,
+        };
+
+        let hash = env::keccak256_array(key);
+        let mut bytes = [0u8; EVM_ADDRESS_LENGTH];
+        bytes.copy_from_slice(&hash[32 - EVM_ADDRESS_LENGTH..]);
+        Some(Self(bytes))
+    }
+
+    /// Formats the address in lowercase hex, with the `0x` prefix.
+    pub fn to_lowercase_string(&self) -> String {
+        let mut s = String::with_capacity(2 + EVM_ADDRESS_LENGTH * 2);
+        s.push_str("0x");
+        for b in self.0 {
+            s.push_str(&format!("{b:02x}"));
+        }
+        s
+    }
+
+    /// Formats the address with its EIP-55 checksum, with the `0x` prefix.
+    pub fn to_checksum_string(&self) -> String {
+        let lower = self.to_lowercase_string();
+        let hash = env::keccak256_array(&lower.as_bytes()[2..]);
+
+        let mut s = String::with_capacity(lower.len());
+        s.push_str("0x");
+        for (i, c) in lower[2..].chars().enumerate() {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0xf;
+            if nibble >= 8 {
+                s.push(c.to_ascii_uppercase());
+            } else {
+                s.push(c);
+            }
+        }
+        s
+    }
+}
+
+impl FromStr for EvmAddress {
+    type Err = EvmAddressParseError;
+
+    /// Parses a hex address, with or without the `0x` prefix. All-lowercase
+    /// and all-uppercase addresses are accepted as-is; mixed-case addresses
+    /// must carry a valid EIP-55 checksum.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+
+        if digits.len() != EVM_ADDRESS_LENGTH * 2 {
+            return Err(EvmAddressParseError::InvalidLength(digits.len()));
+        }
+
+        let mut bytes = [0u8; EVM_ADDRESS_LENGTH];
+        let mut chars = digits.chars();
+        for byte in bytes.iter_mut() {
+            let mut next = || {
+                let c = chars.next().unwrap();
+                c.to_digit(16)
+                    .map(|d| d as u8)
+                    .ok_or(EvmAddressParseError::InvalidCharacter(c))
+            };
+            *byte = (next()? << 4) | next()?;
+        }
+
+        let address = Self(bytes);
+
+        let has_lower = digits.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = digits.chars().any(|c| c.is_ascii_uppercase());
+        if has_lower && has_upper {
+            let expected = address.to_checksum_string();
+            if expected[2..] != *digits {
+                return Err(EvmAddressParseError::InvalidChecksum { expected });
+            }
+        }
+
+        Ok(address)
+    }
+}
+
+impl Display for EvmAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_checksum_string())
+    }
+}
+
+impl Serialize for EvmAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_checksum_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for EvmAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <String as Deserialize>::deserialize(deserializer)?;
+        s.parse().map_err(near_sdk::serde::de::Error::custom)
+    }
+}
+
+/// Derivation path for a key derived by the NEAR chain signatures MPC
+/// service, e.g. `"ethereum-1"`.
+///
+/// The MPC service derives a distinct key for every `(predecessor, path)`
+/// pair, so a contract can control many foreign addresses by varying the
+/// path.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DerivationPath(pub String);
+
+impl DerivationPath {
+    /// Creates a new derivation path.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self(path.into())
+    }
+
+    /// Creates a path of the conventional form `"<chain>-<index>"`.
+    pub fn indexed(chain: &str, index: u32) -> Self {
+        Self(format!("{chain}-{index}"))
+    }
+
+    /// The path string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Computes the epsilon (tweak) the MPC service adds to its root key to
+    /// derive the child key for `predecessor_id` and this path.
+    pub fn epsilon(&self, predecessor_id: &AccountId) -> [u8; 32] {
+        env::sha256_array(
+            format!("{EPSILON_DERIVATION_PREFIX}{predecessor_id},{}", self.0).as_bytes(),
+        )
+    }
+}
+
+impl Display for DerivationPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for DerivationPath {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for DerivationPath {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum() {
+        // Test vectors from EIP-55
+        for s in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let address: EvmAddress = s.parse().unwrap();
+            assert_eq!(address.to_string(), s);
+            assert_eq!(
+                address.to_lowercase_string().parse::<EvmAddress>().unwrap(),
+                address,
+            );
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "0x1234".parse::<EvmAddress>(),
+            Err(EvmAddressParseError::InvalidLength(4)),
+        );
+        assert_eq!(
+            "0xzaaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse::<EvmAddress>(),
+            Err(EvmAddressParseError::InvalidCharacter('z')),
+        );
+        assert!(matches!(
+            "0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse::<EvmAddress>(),
+            Err(EvmAddressParseError::InvalidChecksum { .. }),
+        ));
+    }
+
+    #[test]
+    fn serde() {
+        let address: EvmAddress = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+            .parse()
+            .unwrap();
+        let json = near_sdk::serde_json::to_string(&address).unwrap();
+        assert_eq!(json, "\"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed\"");
+        assert_eq!(
+            near_sdk::serde_json::from_str::<EvmAddress>(&json).unwrap(),
+            address,
+        );
+    }
+
+    #[test]
+    fn derivation_path() {
+        let path = DerivationPath::indexed("ethereum", 1);
+        assert_eq!(path.as_str(), "ethereum-1");
+
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let bob: AccountId = "bob.near".parse().unwrap();
+        assert_ne!(path.epsilon(&alice), path.epsilon(&bob));
+        assert_ne!(
+            path.epsilon(&alice),
+            DerivationPath::indexed("ethereum", 2).epsilon(&alice),
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Log messages with levels that are enabled at compile time.
+//!
+//! [`info!`] and [`debug!`] format their arguments like [`format!`] and log
+//! them prefixed with the level and the name of the component (the last
+//! segment of the module path, or an explicit `target:`), e.g.
+//! `DEBUG [nep141] transfer of 10 from alice to bob`.
+//!
+//! Both compile to nothing unless their level is enabled with a feature of
+//! this crate, so they cost no gas in production:
+//!
+//! * `log-info` enables [`info!`].
+//! * `log-debug` enables [`debug!`] and [`info!`].
+//!
+//! Messages are logged with [`env::log_str`](near_sdk::env::log_str), so they
+//! appear in the receipt logs of sandbox tests, and in
+//! [`get_logs`](near_sdk::test_utils::get_logs) in unit tests.
+//!
+//! The components of this crate log their operations at the debug level.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk_contract_tools::utils::log::{debug, info};
+//!
+//! let amount = 10;
+//! info!("minted {amount} tokens");
+//! debug!(target: "my_component", "minted {} tokens", amount);
+//! ```
+
+use std::fmt;
+
+/// Whether [`info!`] is enabled.
+pub const INFO_ENABLED: bool = cfg!(any(feature = "log-info", feature = "log-debug"));
+
+/// Whether [`debug!`] is enabled.
+pub const DEBUG_ENABLED: bool = cfg!(feature = "log-debug");
+
+/// Log level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    /// Operations of interest to operators.
+    Info,
+    /// Detailed tracing.
+    Debug,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Info => write!(f, "INFO"),
+            Level::Debug => write!(f, "DEBUG"),
+        }
+    }
+}
+
+/// Formats a message of `target` (a module path or component name).
+pub fn format_message(level: Level, target: &str, args: fmt::Arguments<'_>) -> String {
+    let component = target.rsplit("::").next().unwrap_or(target);
+    format!("{level} [{component}] {args}")
+}
+
+/// Logs a message. Used by [`info!`] and [`debug!`], which check that the
+/// level is enabled.
+pub fn write(level: Level, target: &str, args: fmt::Arguments<'_>) {
+    near_sdk::env::log_str(&format_message(level, target, args));
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_info {
+    (target: $target:expr, $($arg:tt)+) => {
+        if $crate::utils::log::INFO_ENABLED {
+            $crate::utils::log::write(
+                $crate::utils::log::Level::Info,
+                $target,
+                ::std::format_args!($($arg)+),
+            );
+        }
+    };
+    ($($arg:tt)+) => {
+        $crate::__log_info!(target: ::std::module_path!(), $($arg)+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        if $crate::utils::log::DEBUG_ENABLED {
+            $crate::utils::log::write(
+                $crate::utils::log::Level::Debug,
+                $target,
+                ::std::format_args!($($arg)+),
+            );
+        }
+    };
+    ($($arg:tt)+) => {
+        $crate::__log_debug!(target: ::std::module_path!(), $($arg)+)
+    };
+}
+
+/// Logs a message at the info level, if enabled with the `log-info` or
+/// `log-debug` feature. See the [module documentation](self).
+pub use crate::__log_info as info;
+
+/// Logs a message at the debug level, if enabled with the `log-debug`
+/// feature. See the [module documentation](self).
+pub use crate::__log_debug as debug;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format() {
+        assert_eq!(
+            format_message(
+                Level::Debug,
+                "near_sdk_contract_tools::standard::nep141",
+                format_args!("transfer of {}", 10),
+            ),
+            "DEBUG [nep141] transfer of 10",
+        );
+        assert_eq!(
+            format_message(Level::Info, "vault", format_args!("paused")),
+            "INFO [vault] paused",
+        );
+    }
+}
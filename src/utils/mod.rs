@@ -0,0 +1,229 @@
+//! Utility functions for storage key generation, storage fee management
+
+use near_sdk::{env, require, AccountId, Promise};
+use thiserror::Error;
+
+pub mod amount;
+pub mod aurora;
+pub mod batch;
+pub mod foreign_address;
+pub mod log;
+pub mod preflight;
+pub mod reference;
+pub mod rng;
+
+/// Concatenate bytes to form a key. Useful for generating storage keys.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk_contract_tools::utils::prefix_key;
+///
+/// assert_eq!(prefix_key(b"p", b"key"), b"pkey");
+/// ```
+pub fn prefix_key(prefix: &[u8], key: &[u8]) -> Vec<u8> {
+    [prefix, key].concat()
+}
+
+/// Hashes an account ID into a fixed-length storage key fragment.
+///
+/// Account IDs may be up to 64 bytes long, so keys that embed them directly
+/// vary in size. The hash is always 32 bytes, which bounds the size of
+/// per-account keys and saves storage for long account IDs (e.g. implicit
+/// accounts). With the `hashed-account-keys` feature, the NEP-141 and NEP-145
+/// per-account keys use this fragment.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk_contract_tools::utils::account_key;
+///
+/// let key = account_key(&"alice.near".parse().unwrap());
+/// assert_eq!(key.len(), 32);
+/// assert_ne!(key, account_key(&"bob.near".parse().unwrap()));
+/// ```
+pub fn account_key(account_id: &AccountId) -> [u8; 32] {
+    env::sha256_array(account_id.as_bytes())
+}
+
+/// Calculates the storage fee of an action, given an initial storage amount,
+/// and refunds the predecessor a portion of the attached deposit if necessary.
+/// Returns refund Promise if refund was applied.
+///
+/// # Warning
+///
+/// New collections (those in `near_sdk::store`) cache writes, only applying
+/// state changes on drop. However, this function only accounts for actual
+/// changes to storage usage. You can force writes (allowing this function to
+/// detect storage changes) by calling `.flush()` on `near_sdk::store::*`
+/// collections.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk_contract_tools::utils::apply_storage_fee_and_refund;
+///
+/// let initial_storage_usage = near_sdk::env::storage_usage();
+/// let additional_fees = 0;
+///
+/// // Action that consumes storage.
+/// near_sdk::env::storage_write(b"key", b"value");
+///
+/// near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+///     .attached_deposit(near_sdk::ONE_NEAR)
+///     .build());
+/// // Attached deposit must cover storage fee or this function will panic
+/// apply_storage_fee_and_refund(initial_storage_usage, additional_fees);
+/// ```
+pub fn apply_storage_fee_and_refund(
+    initial_storage_usage: u64,
+    additional_fees: u128,
+) -> Option<Promise> {
+    // Storage consumption after storage event
+    let storage_usage_end = env::storage_usage();
+
+    // Storage fee incurred by storage event, clamped >= 0
+    let storage_fee = u128::from(storage_usage_end.saturating_sub(initial_storage_usage))
+        * env::storage_byte_cost();
+
+    let total_required_deposit = storage_fee
+        .checked_add(additional_fees)
+        .expect("Required deposit overflows u128");
+
+    let attached_deposit = env::attached_deposit();
+
+    require!(
+        attached_deposit >= total_required_deposit,
+        format!(
+            "Insufficient deposit: attached {attached_deposit} yoctoNEAR < required {total_required_deposit} yoctoNEAR ({storage_fee} storage + {additional_fees} additional)",
+        )
+    );
+
+    let refund = attached_deposit - total_required_deposit;
+
+    // Send refund transfer if required
+    if refund > 0 {
+        Some(Promise::new(env::predecessor_account_id()).transfer(refund))
+    } else {
+        None
+    }
+}
+
+/// Asserts that the attached deposit is greater than zero.
+pub fn assert_nonzero_deposit() {
+    require!(
+        env::attached_deposit() > 0,
+        "Attached deposit must be greater than zero"
+    );
+}
+
+/// A component that is initialized once was initialized again. Returned by
+/// the `try_init` functions of the components that require initialization.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("{component} already initialized")]
+pub struct AlreadyInitializedError {
+    /// The component, e.g. `"Owner"`.
+    pub component: &'static str,
+}
+
+/// A callback was not called by the contract itself.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Callback must be called by {current_account_id}, not {predecessor_id}")]
+pub struct NotSelfCallbackError {
+    /// The account that called the callback.
+    pub predecessor_id: AccountId,
+    /// The account of the contract.
+    pub current_account_id: AccountId,
+}
+
+/// Checks that the predecessor is the current account, i.e. that the current
+/// method was called as a callback of the contract's own promise.
+///
+/// `#[private]` performs the same check, but only in the wrapper generated
+/// by `#[near_bindgen]`. This function makes the check explicit in the method
+/// body, where it survives the attribute being removed from a manual
+/// implementation.
+pub fn check_self_callback() -> Result<(), NotSelfCallbackError> {
+    let predecessor_id = env::predecessor_account_id();
+    let current_account_id = env::current_account_id();
+
+    if predecessor_id != current_account_id {
+        return Err(NotSelfCallbackError {
+            predecessor_id,
+            current_account_id,
+        });
+    }
+
+    Ok(())
+}
+
+/// Like [`check_self_callback`], but panics with the error.
+///
+/// # Examples
+///
+/// ```should_panic
+/// use near_sdk::{test_utils::VMContextBuilder, testing_env};
+/// use near_sdk_contract_tools::utils::require_self_callback;
+///
+/// testing_env!(VMContextBuilder::new()
+///     .predecessor_account_id("alice".parse().unwrap())
+///     .build());
+///
+/// // Panics with "Callback must be called by alice.near, not alice"
+/// require_self_callback();
+/// ```
+pub fn require_self_callback() {
+    check_self_callback().unwrap_or_else(|e| env::panic_str(&e.to_string()));
+}
+
+/// Panics with `message`, prefixed with `[prefix] ` if a prefix is given.
+/// Used by [`tools_panic!`](crate::tools_panic).
+pub fn panic_with_prefix(prefix: Option<&str>, message: &str) -> ! {
+    match prefix {
+        Some(prefix) => env::panic_str(&format!("[{prefix}] {message}")),
+        None => env::panic_str(message),
+    }
+}
+
+/// Panics with a message formatted like [`format!`], prefixed with a contract
+/// tag (an `Option<&str>`), if any. Code generated by the derive macros
+/// passes the tag configured with
+/// `#[contract_tools(panic_prefix = "...")]`, which makes panics easier to
+/// attribute in transactions that span several contracts.
+///
+/// Only panics raised by the generated code itself are tagged. Library
+/// functions do not know the contract's tag, so panics from the functions a
+/// generated method calls (e.g. [`Owner::require_owner`](crate::owner::Owner::require_owner),
+/// [`Rbac::require_role`](crate::rbac::Rbac::require_role), the
+/// [`preflight`](crate::utils::preflight) helpers, controller checks, and
+/// hooks) keep their bare messages.
+///
+/// # Examples
+///
+/// ```should_panic
+/// use near_sdk_contract_tools::tools_panic;
+///
+/// // Panics with "[my-token] Invalid amount: 0"
+/// tools_panic!(Some("my-token"), "Invalid amount: {}", 0);
+/// ```
+#[macro_export]
+macro_rules! tools_panic {
+    ($prefix:expr, $($arg:tt)+) => {
+        $crate::utils::panic_with_prefix($prefix, &::std::format!($($arg)+))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prefix_key;
+
+    #[test]
+    fn test_prefix_key() {
+        assert_eq!(prefix_key(b"a", b"b"), b"ab");
+        assert_eq!(prefix_key("a".as_ref(), "b".as_ref()), b"ab");
+        assert_eq!(prefix_key("a".as_ref(), b"b"), b"ab");
+        assert_eq!(prefix_key(&[], "abc".as_ref()), b"abc");
+        assert_eq!(prefix_key(&[], b""), [0u8; 0]);
+        assert_eq!(prefix_key("abc".as_ref(), b""), b"abc");
+    }
+}
@@ -0,0 +1,115 @@
+//! Gas and deposit checks for the start of a function call.
+//!
+//! Generated methods use these helpers instead of ad-hoc checks, so that
+//! insufficient gas or deposit always produces the same error message, and
+//! surplus deposits are returned to the caller.
+//!
+//! Methods that require exactly one yoctoNEAR (to ensure that the call was
+//! signed with a full-access key) should continue to use
+//! [`near_sdk::assert_one_yocto`].
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::{near_bindgen, Gas, ONE_NEAR};
+//! use near_sdk_contract_tools::utils::preflight::*;
+//!
+//! #[near_bindgen]
+//! struct Contract {}
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[payable]
+//!     pub fn buy_ticket(&mut self) {
+//!         require_gas(Gas(10_000_000_000_000));
+//!         require_deposit(ONE_NEAR);
+//!         // ... issue ticket ...
+//!         refund_excess_deposit(ONE_NEAR);
+//!     }
+//! }
+//! ```
+
+use near_sdk::{env, Balance, Gas, Promise};
+
+/// Error message prefix for insufficient prepaid gas.
+pub const INSUFFICIENT_GAS_MESSAGE: &str = "Insufficient gas";
+/// Error message prefix for insufficient attached deposit.
+pub const INSUFFICIENT_DEPOSIT_MESSAGE: &str = "Insufficient deposit";
+
+/// Panics if less than `required` gas was attached to the call.
+pub fn require_gas(required: Gas) {
+    let prepaid = env::prepaid_gas();
+
+    if prepaid < required {
+        env::panic_str(&format!(
+            "{INSUFFICIENT_GAS_MESSAGE}: prepaid {} gas < required {} gas",
+            prepaid.0, required.0,
+        ));
+    }
+}
+
+/// Panics if less than `required` yoctoNEAR was attached to the call.
+pub fn require_deposit(required: Balance) {
+    let attached = env::attached_deposit();
+
+    if attached < required {
+        env::panic_str(&format!(
+            "{INSUFFICIENT_DEPOSIT_MESSAGE}: attached {attached} yoctoNEAR < required {required} yoctoNEAR",
+        ));
+    }
+}
+
+/// Refunds the predecessor the portion of the attached deposit that exceeds
+/// `required`. Returns the refund promise, if a refund was necessary. Panics
+/// if less than `required` yoctoNEAR was attached.
+pub fn refund_excess_deposit(required: Balance) -> Option<Promise> {
+    require_deposit(required);
+
+    let excess = env::attached_deposit() - required;
+
+    if excess > 0 {
+        Some(Promise::new(env::predecessor_account_id()).transfer(excess))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+
+    #[test]
+    fn sufficient() {
+        testing_env!(VMContextBuilder::new()
+            .prepaid_gas(Gas(10_000_000_000_000))
+            .attached_deposit(10)
+            .build());
+
+        require_gas(Gas(10_000_000_000_000));
+        require_deposit(10);
+        assert!(refund_excess_deposit(10).is_none());
+        assert!(refund_excess_deposit(4).is_some());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Insufficient gas: prepaid 5000000000000 gas < required 10000000000000 gas"
+    )]
+    fn insufficient_gas() {
+        testing_env!(VMContextBuilder::new()
+            .prepaid_gas(Gas(5_000_000_000_000))
+            .build());
+
+        require_gas(Gas(10_000_000_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient deposit: attached 9 yoctoNEAR < required 10 yoctoNEAR")]
+    fn insufficient_deposit() {
+        testing_env!(VMContextBuilder::new().attached_deposit(9).build());
+
+        refund_excess_deposit(10);
+    }
+}
@@ -0,0 +1,132 @@
+//! Checks of the `reference` and `reference_hash` fields of NEP-148 and
+//! NEP-177 metadata.
+//!
+//! `reference_hash` is the SHA-256 hash of the JSON file at `reference`, and
+//! lets wallets and indexers detect a reference whose content has changed. A
+//! contract cannot fetch the reference, so these helpers check what it can:
+//! [`validate_reference`] checks that a reference comes with a well-formed
+//! hash, and [`verify_reference`] checks the hash against the content, e.g.
+//! content attached to a call, or fetched by an off-chain tool before it
+//! submits the metadata.
+//!
+//! Hashing uses the `near_sdk::env` host functions, so these helpers must be
+//! called from within a contract (or a mocked blockchain in tests).
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk_contract_tools::utils::reference::*;
+//!
+//! let content = br#"{"description":"My token"}"#;
+//! let reference_hash = hash_reference(content);
+//!
+//! assert_eq!(
+//!     validate_reference(Some("ipfs://bafy.../token.json"), Some(&reference_hash)),
+//!     Ok(()),
+//! );
+//! assert_eq!(verify_reference(Some(&reference_hash), content), Ok(()));
+//! assert!(verify_reference(Some(&reference_hash), b"{}").is_err());
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+
+use near_sdk::{base64, env, CryptoHash};
+use thiserror::Error;
+
+/// Length of a reference hash (SHA-256), in bytes.
+pub const REFERENCE_HASH_LENGTH: usize = 32;
+
+/// Error returned when a reference hash is malformed or does not match.
+///
+/// The same checks apply to other linked fields, like NEP-177 `media` and
+/// `media_hash`; [`ReferenceError::describe`] names those fields in the
+/// message.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ReferenceError {
+    /// `reference` is set, but `reference_hash` is not.
+    MissingHash,
+    /// `reference_hash` is not valid base64.
+    InvalidBase64,
+    /// `reference_hash` does not decode to a SHA-256 hash.
+    InvalidHashLength {
+        /// The decoded length.
+        length: usize,
+    },
+    /// The hash of the content is not `reference_hash`.
+    HashMismatch {
+        /// `reference_hash`, base64-encoded.
+        expected: String,
+        /// The hash of the content, base64-encoded.
+        actual: String,
+    },
+}
+
+impl ReferenceError {
+    /// Describes the error for the URL field `field` and its hash field
+    /// `hash_field`.
+    pub fn describe(&self, field: &str, hash_field: &str) -> String {
+        match self {
+            Self::MissingHash => format!("`{field}` requires `{hash_field}`"),
+            Self::InvalidBase64 => format!("`{hash_field}` is not valid base64"),
+            Self::InvalidHashLength { length } => format!(
+                "`{hash_field}` must decode to {REFERENCE_HASH_LENGTH} bytes, but decodes to {length}"
+            ),
+            Self::HashMismatch { expected, actual } => format!(
+                "`{field}` content hashes to {actual}, but `{hash_field}` is {expected}"
+            ),
+        }
+    }
+}
+
+impl Display for ReferenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe("reference", "reference_hash"))
+    }
+}
+
+/// Returns the reference hash of `content`.
+pub fn hash_reference(content: &[u8]) -> CryptoHash {
+    env::sha256_array(content)
+}
+
+/// Decodes a base64-encoded reference hash, as stored in NEP-177 metadata.
+pub fn decode_reference_hash(reference_hash: &str) -> Result<Vec<u8>, ReferenceError> {
+    base64::decode(reference_hash).map_err(|_| ReferenceError::InvalidBase64)
+}
+
+/// Checks that `reference`, if set, comes with a `reference_hash`, and that
+/// `reference_hash`, if set, is a SHA-256 hash.
+pub fn validate_reference(
+    reference: Option<&str>,
+    reference_hash: Option<&[u8]>,
+) -> Result<(), ReferenceError> {
+    if reference.is_some() && reference_hash.is_none() {
+        return Err(ReferenceError::MissingHash);
+    }
+
+    match reference_hash {
+        Some(hash) if hash.len() != REFERENCE_HASH_LENGTH => {
+            Err(ReferenceError::InvalidHashLength { length: hash.len() })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `content` hashes to `reference_hash`.
+pub fn verify_reference(
+    reference_hash: Option<&[u8]>,
+    content: &[u8],
+) -> Result<(), ReferenceError> {
+    let expected = reference_hash.ok_or(ReferenceError::MissingHash)?;
+    validate_reference(None, Some(expected))?;
+
+    let actual = hash_reference(content);
+    if expected != actual {
+        return Err(ReferenceError::HashMismatch {
+            expected: base64::encode(expected),
+            actual: base64::encode(actual),
+        });
+    }
+
+    Ok(())
+}
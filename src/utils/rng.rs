@@ -0,0 +1,339 @@
+//! Randomness derived from the block's random seed.
+//!
+//! [`Rng`] expands a 32-byte seed into an arbitrarily long stream by hashing
+//! the seed together with a counter (SHA-256 in counter mode), so a single
+//! function call can draw as many values as it needs without reusing bytes.
+//! [`Rng::from_seed`] produces the same stream for the same seed, which is
+//! useful for tests and simulations.
+//!
+//! # Manipulation
+//!
+//! `env::random_seed` is known to the block producer before the block is
+//! published, and a caller can observe it (e.g. by simulating the call) and
+//! decide whether to submit the transaction. For outcomes that matter (e.g.
+//! raffles), use [`CommitReveal`]: participants commit to a secret in one
+//! block and reveal it in a later block, and the secret is mixed into the
+//! seed.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk_contract_tools::utils::rng::Rng;
+//!
+//! let mut rng = Rng::from_seed([7; 32]);
+//! let roll = rng.gen_range(1..7);
+//! assert!((1..7).contains(&roll));
+//!
+//! let mut order = vec![1, 2, 3, 4, 5];
+//! rng.shuffle(&mut order);
+//! ```
+
+use std::ops::Range;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, AccountId, BlockHeight, BorshStorageKey, IntoStorageKey,
+};
+use thiserror::Error;
+
+use crate::slot::Slot;
+
+/// A stream of pseudorandom values expanded from a 32-byte seed.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    seed: [u8; 32],
+    counter: u64,
+    buffer: [u8; 32],
+    position: usize,
+}
+
+impl Rng {
+    /// Creates a stream seeded by the current block's random seed.
+    pub fn new() -> Self {
+        Self::from_seed(env::random_seed_array())
+    }
+
+    /// Creates a deterministic stream from the given seed.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self {
+            seed,
+            counter: 0,
+            buffer: [0; 32],
+            position: 32,
+        }
+    }
+
+    /// Creates a stream seeded by the current block's random seed mixed with
+    /// `entropy`, e.g. a secret revealed with [`CommitReveal`].
+    pub fn with_entropy(entropy: &[u8]) -> Self {
+        Self::from_seed(env::sha256_array(
+            &[&env::random_seed_array()[..], entropy].concat(),
+        ))
+    }
+
+    fn refill(&mut self) {
+        self.buffer =
+            env::sha256_array(&[&self.seed[..], &self.counter.to_le_bytes()[..]].concat());
+        self.counter += 1;
+        self.position = 0;
+    }
+
+    /// Fills `dest` with pseudorandom bytes.
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.position == self.buffer.len() {
+                self.refill();
+            }
+            *byte = self.buffer[self.position];
+            self.position += 1;
+        }
+    }
+
+    /// Returns a pseudorandom `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Returns a pseudorandom `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Returns a pseudorandom `u128`.
+    pub fn next_u128(&mut self) -> u128 {
+        let mut bytes = [0; 16];
+        self.fill_bytes(&mut bytes);
+        u128::from_le_bytes(bytes)
+    }
+
+    /// Returns a uniformly distributed value in `range`. Panics if the range
+    /// is empty.
+    pub fn gen_range_u128(&mut self, range: Range<u128>) -> u128 {
+        assert!(range.start < range.end, "Cannot sample from an empty range");
+
+        let span = range.end - range.start;
+        let zone = u128::MAX - (u128::MAX - span + 1) % span;
+
+        loop {
+            let value = self.next_u128();
+            if value <= zone {
+                return range.start + value % span;
+            }
+        }
+    }
+
+    /// Returns a uniformly distributed value in `range`. Panics if the range
+    /// is empty.
+    pub fn gen_range(&mut self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "Cannot sample from an empty range");
+
+        let span = range.end - range.start;
+        // Reject values from the incomplete final span to avoid modulo bias.
+        let zone = u64::MAX - (u64::MAX - span + 1) % span;
+
+        loop {
+            let value = self.next_u64();
+            if value <= zone {
+                return range.start + value % span;
+            }
+        }
+    }
+
+    /// Returns `true` with probability `numerator / denominator`.
+    pub fn gen_ratio(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.gen_range(0..denominator) < numerator
+    }
+
+    /// Shuffles `items` in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.gen_range(0..i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// Returns a reference to a uniformly chosen element of `items`, or
+    /// `None` if `items` is empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.gen_range(0..items.len() as u64) as usize])
+        }
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum CommitRevealStorageKey<'a> {
+    Commitment(&'a AccountId),
+}
+
+/// A commitment to a secret, made at a certain block height.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Commitment {
+    /// SHA-256 hash of the secret.
+    pub hash: [u8; 32],
+    /// Block height at which the commitment was made.
+    pub block_height: BlockHeight,
+}
+
+/// Errors that may occur when revealing a secret.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RevealError {
+    /// The account has not made a commitment.
+    #[error("Account '{0}' has no commitment")]
+    NoCommitment(AccountId),
+    /// The secret was revealed in the same block as the commitment.
+    #[error("Secret must be revealed after block {0}")]
+    TooEarly(BlockHeight),
+    /// The secret does not match the commitment.
+    #[error("Secret does not match commitment")]
+    Mismatch,
+}
+
+/// Commit-reveal scheme for mixing participant-supplied entropy into the
+/// random seed.
+///
+/// An account commits to `sha256(secret)` with [`CommitReveal::commit`], and
+/// reveals `secret` in a later block with [`CommitReveal::reveal`], which
+/// returns an [`Rng`] seeded by both the secret and the random seed of the
+/// revealing block. Since the secret is fixed before the revealing block's
+/// seed is known, neither the participant nor the block producer controls
+/// the outcome alone.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct CommitReveal {
+    root: Slot<()>,
+}
+
+impl CommitReveal {
+    /// Creates a handle to commitments stored under `root`.
+    pub fn new(root: impl IntoStorageKey) -> Self {
+        Self {
+            root: Slot::root(root),
+        }
+    }
+
+    fn slot_commitment(&self, account_id: &AccountId) -> Slot<Commitment> {
+        self.root
+            .field(CommitRevealStorageKey::Commitment(account_id))
+    }
+
+    /// Records a commitment for `account_id`, replacing any previous one.
+    pub fn commit(&mut self, account_id: &AccountId, hash: [u8; 32]) {
+        self.slot_commitment(account_id).write(&Commitment {
+            hash,
+            block_height: env::block_height(),
+        });
+    }
+
+    /// The current commitment of `account_id`, if any.
+    pub fn commitment_of(&self, account_id: &AccountId) -> Option<Commitment> {
+        self.slot_commitment(account_id).read()
+    }
+
+    /// Verifies `secret` against the commitment of `account_id`, removes the
+    /// commitment, and returns a stream seeded by the secret and the current
+    /// block's random seed.
+    pub fn reveal(&mut self, account_id: &AccountId, secret: &[u8]) -> Result<Rng, RevealError> {
+        let mut slot = self.slot_commitment(account_id);
+        let commitment = slot
+            .read()
+            .ok_or_else(|| RevealError::NoCommitment(account_id.clone()))?;
+
+        if env::block_height() <= commitment.block_height {
+            return Err(RevealError::TooEarly(commitment.block_height));
+        }
+
+        if env::sha256_array(secret) != commitment.hash {
+            return Err(RevealError::Mismatch);
+        }
+
+        slot.remove();
+
+        Ok(Rng::with_entropy(secret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let mut a = Rng::from_seed([1; 32]);
+        let mut b = Rng::from_seed([1; 32]);
+        let mut c = Rng::from_seed([2; 32]);
+
+        let a: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let b: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+        let c: Vec<u64> = (0..10).map(|_| c.next_u64()).collect();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::from_seed([3; 32]);
+
+        let mut seen = [false; 6];
+        for _ in 0..200 {
+            let roll = rng.gen_range(1..7);
+            assert!((1..7).contains(&roll));
+            seen[roll as usize - 1] = true;
+        }
+        assert!(seen.iter().all(|s| *s));
+
+        assert_eq!(rng.gen_range(5..6), 5);
+        assert!(rng.gen_ratio(1, 1));
+        assert!(!rng.gen_ratio(0, 1));
+        assert_eq!(rng.choose::<u8>(&[]), None);
+
+        let mut items: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+        assert_ne!(items, sorted);
+    }
+
+    #[test]
+    fn commit_reveal() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let mut commit_reveal = CommitReveal::new(b"cr".to_vec());
+
+        testing_env!(VMContextBuilder::new().block_index(10).build());
+        commit_reveal.commit(&alice, env::sha256_array(b"secret"));
+
+        assert_eq!(
+            commit_reveal.reveal(&alice, b"secret").unwrap_err(),
+            RevealError::TooEarly(10),
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .block_index(11)
+            .random_seed([9; 32])
+            .build());
+        assert_eq!(
+            commit_reveal.reveal(&alice, b"wrong").unwrap_err(),
+            RevealError::Mismatch,
+        );
+
+        let mut rng = commit_reveal.reveal(&alice, b"secret").unwrap();
+        let mut expected = Rng::from_seed(env::sha256_array(&[&[9; 32][..], b"secret"].concat()));
+        assert_eq!(rng.next_u64(), expected.next_u64());
+        assert!(commit_reveal.commitment_of(&alice).is_none());
+    }
+}
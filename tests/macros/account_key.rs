@@ -0,0 +1,87 @@
+use near_sdk::{json_types::U128, near_bindgen, AccountId};
+use near_sdk_contract_tools::{
+    ft::*,
+    slot::Slot,
+    standard::nep145::{
+        Nep145Controller, Nep145ControllerInternal, StorageBalance, StorageBalanceBounds,
+    },
+    utils::account_key,
+    FungibleToken,
+};
+
+#[derive(FungibleToken)]
+#[near_bindgen]
+struct Contract {}
+
+#[test]
+fn hashed_keys_are_fixed_length() {
+    let short: AccountId = "a.near".parse().unwrap();
+    let long: AccountId = "a".repeat(64).parse().unwrap();
+
+    assert_eq!(
+        <Contract as Nep141ControllerInternal>::slot_account(&short)
+            .key
+            .len(),
+        <Contract as Nep141ControllerInternal>::slot_account(&long)
+            .key
+            .len(),
+    );
+    assert!(<Contract as Nep141ControllerInternal>::slot_account(&long)
+        .key
+        .ends_with(&account_key(&long)));
+}
+
+#[test]
+fn nep141_balances_migrate_on_change() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    // State written before the feature was enabled.
+    <Contract as Nep141ControllerInternal>::slot_account_legacy(&alice).write(&100);
+    Contract::slot_total_supply().write(&100);
+
+    assert_eq!(contract.ft_balance_of(alice.clone()), U128(100));
+    assert!(!<Contract as Nep141ControllerInternal>::slot_account(&alice).exists());
+
+    contract.transfer_unchecked(&alice, &bob, 30).unwrap();
+
+    assert!(!<Contract as Nep141ControllerInternal>::slot_account_legacy(&alice).exists());
+    assert_eq!(
+        <Contract as Nep141ControllerInternal>::slot_account(&alice).read(),
+        Some(70),
+    );
+    assert_eq!(contract.ft_balance_of(alice), U128(70));
+    assert_eq!(contract.ft_balance_of(bob), U128(30));
+}
+
+#[test]
+fn nep145_balances_migrate_on_change() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let balance = StorageBalance {
+        total: U128(1000),
+        available: U128(1000),
+    };
+
+    contract.set_storage_balance_bounds(&StorageBalanceBounds {
+        min: U128(0),
+        max: None,
+    });
+    let mut legacy: Slot<StorageBalance> =
+        <Contract as Nep145ControllerInternal>::slot_account_legacy(&alice);
+    legacy.write(&balance);
+
+    assert_eq!(contract.get_storage_balance(&alice).unwrap(), balance);
+
+    contract.lock_storage(&alice, U128(400)).unwrap();
+
+    assert!(!legacy.exists());
+    assert_eq!(
+        contract.get_storage_balance(&alice).unwrap(),
+        StorageBalance {
+            total: U128(1000),
+            available: U128(600),
+        },
+    );
+}
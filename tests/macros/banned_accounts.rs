@@ -0,0 +1,232 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    json_types::U128,
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, BorshStorageKey, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    banned_accounts::{hooks::BannedAccountsHook, BannedAccounts, BannedAccountsExternal},
+    ft::*,
+    rbac::Rbac,
+    BannedAccounts, Rbac,
+};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum Role {
+    Compliance,
+}
+
+#[derive(BannedAccounts, FungibleToken, Rbac)]
+#[banned_accounts(role = "Role::Compliance")]
+#[fungible_token(
+    all_hooks = "BannedAccountsHook",
+    storage_register_hook = "BannedAccountsHook"
+)]
+#[rbac(roles = "Role")]
+#[near_bindgen]
+struct Contract {}
+
+fn compliance() -> AccountId {
+    "compliance".parse().unwrap()
+}
+
+fn alice() -> AccountId {
+    "alice".parse().unwrap()
+}
+
+fn bob() -> AccountId {
+    "bob".parse().unwrap()
+}
+
+fn call(predecessor: &AccountId, deposit: u128) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.clone())
+        .attached_deposit(deposit)
+        .build());
+}
+
+fn setup() -> Contract {
+    let mut contract = Contract {};
+    contract.add_role(compliance(), &Role::Compliance);
+
+    for account_id in [alice(), bob()] {
+        call(&account_id, ONE_NEAR);
+        contract.storage_deposit(None, None);
+    }
+
+    contract.deposit_unchecked(&alice(), 100).unwrap();
+    contract
+}
+
+#[test]
+fn ban_and_unban() {
+    let mut contract = setup();
+
+    call(&compliance(), 1);
+    assert!(contract.ban_add(bob()));
+    assert!(!contract.ban_add(bob()));
+    assert_eq!(
+        near_sdk::test_utils::get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"x-banned-accounts","version":"1.0.0","event":"ban","data":{"account_id":"bob"}}"#
+        ],
+    );
+    assert!(contract.ban_is_banned(bob()));
+    assert_eq!(contract.ban_count(), 1);
+
+    call(&compliance(), 1);
+    assert!(contract.ban_remove(bob()));
+    assert!(!contract.ban_remove(bob()));
+    assert!(!contract.ban_is_banned(bob()));
+    assert_eq!(contract.ban_count(), 0);
+
+    call(&alice(), 1);
+    contract.ft_transfer(bob(), U128(10), None);
+    assert_eq!(contract.ft_balance_of(bob()), U128(10));
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized role")]
+fn ban_requires_role() {
+    let mut contract = setup();
+
+    call(&alice(), 1);
+    contract.ban_add(bob());
+}
+
+#[test]
+#[should_panic(expected = "Account bob is banned")]
+fn banned_receiver() {
+    let mut contract = setup();
+    contract.ban(&bob());
+
+    call(&alice(), 1);
+    contract.ft_transfer(bob(), U128(10), None);
+}
+
+#[test]
+#[should_panic(expected = "Account alice is banned")]
+fn banned_sender() {
+    let mut contract = setup();
+    contract.ban(&alice());
+
+    call(&alice(), 1);
+    contract.ft_transfer(bob(), U128(10), None);
+}
+
+#[test]
+#[should_panic(expected = "Account bob is banned")]
+fn banned_mint() {
+    let mut contract = setup();
+    contract.ban(&bob());
+
+    contract
+        .mint(&Nep141Mint {
+            amount: 10,
+            receiver_id: &bob(),
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+}
+
+#[test]
+fn banned_burn() {
+    let mut contract = setup();
+    contract.ban(&alice());
+
+    contract
+        .burn(&Nep141Burn {
+            amount: 100,
+            owner_id: &alice(),
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+    assert_eq!(contract.ft_balance_of(alice()), U128(0));
+}
+
+#[test]
+#[should_panic(expected = "Account carol is banned")]
+fn banned_storage_registration() {
+    let mut contract = setup();
+    let carol: AccountId = "carol".parse().unwrap();
+    contract.ban(&carol);
+
+    call(&carol, ONE_NEAR);
+    contract.storage_deposit(None, None);
+}
+
+mod non_fungible_token {
+    use near_sdk::{json_types::U128, near_bindgen, AccountId, ONE_NEAR};
+    use near_sdk_contract_tools::{
+        banned_accounts::{hooks::BannedAccountsHook, BannedAccounts},
+        nft::*,
+        standard::nep171::Nep171TransferAuthorization,
+        BannedAccounts, Rbac,
+    };
+
+    use super::Role;
+
+    #[derive(BannedAccounts, NonFungibleToken, Rbac)]
+    #[banned_accounts(role = "Role::Compliance")]
+    #[non_fungible_token(all_hooks = "BannedAccountsHook")]
+    #[rbac(roles = "Role")]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn setup() -> Contract {
+        let mut contract = Contract {};
+        contract
+            .deposit_to_storage_account(&"alice".parse().unwrap(), U128(ONE_NEAR))
+            .unwrap();
+        contract
+            .mint_with_metadata(
+                "t".to_string(),
+                "alice".parse().unwrap(),
+                TokenMetadata::new().title("T"),
+            )
+            .unwrap();
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "Account bob is banned")]
+    fn banned_approval() {
+        let mut contract = setup();
+        let bob: AccountId = "bob".parse().unwrap();
+        contract.ban(&bob);
+
+        contract
+            .approve(&Nep178Approve {
+                token_id: &"t".to_string(),
+                current_owner_id: &"alice".parse().unwrap(),
+                account_id: &bob,
+                origin: ActionOrigin::Internal,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Account bob is banned")]
+    fn banned_transfer() {
+        let mut contract = setup();
+        let bob: AccountId = "bob".parse().unwrap();
+        contract.ban(&bob);
+
+        contract
+            .external_transfer(&Nep171Transfer {
+                token_id: &"t".to_string(),
+                authorization: Nep171TransferAuthorization::Owner,
+                sender_id: &"alice".parse().unwrap(),
+                receiver_id: &bob,
+                memo: None,
+                msg: None,
+                revert: false,
+                origin: ActionOrigin::Internal,
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,58 @@
+use near_sdk::{near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId, Gas};
+use near_sdk_contract_tools::{
+    blue_green::{BlueGreen, Step},
+    ft::*,
+    slot::Slot,
+    utils::batch::BatchStatus,
+    DefaultStorageKey,
+};
+
+#[derive(Nep141)]
+#[nep141(
+    storage_key = "near_sdk_contract_tools::blue_green::BlueGreen::new(near_sdk_contract_tools::DefaultStorageKey::Nep141).active()"
+)]
+#[near_bindgen]
+struct Contract {}
+
+#[test]
+fn component_follows_active_namespace() {
+    testing_env!(VMContextBuilder::new()
+        .prepaid_gas(Gas(300_000_000_000_000))
+        .build());
+
+    let mut contract = Contract {};
+    let accounts: Vec<AccountId> = vec!["alice".parse().unwrap(), "bob".parse().unwrap()];
+    contract.deposit_unchecked(&accounts[0], 100).unwrap();
+    contract.deposit_unchecked(&accounts[1], 50).unwrap();
+
+    let mut switch = BlueGreen::new(DefaultStorageKey::Nep141);
+    assert_eq!(Contract::root(), switch.active());
+
+    // Migrate the known balances into the new namespace, doubling them.
+    let status = switch.migrate(
+        Gas(5_000_000_000_000),
+        || 0usize,
+        |i, from, to| {
+            let slot = match accounts.get(*i) {
+                Some(account_id) => Contract::slot_account(account_id),
+                None => Contract::slot_total_supply(),
+            };
+            let value = slot.read().unwrap_or(0);
+            BlueGreen::rebase(&slot, from, to).write(&(value * 2));
+
+            *i += 1;
+            if *i > accounts.len() {
+                Step::Done
+            } else {
+                Step::Continue
+            }
+        },
+    );
+
+    assert_eq!(status, BatchStatus::Done);
+    assert_eq!(Contract::root(), switch.active());
+    assert_ne!(Contract::root(), Slot::root(DefaultStorageKey::Nep141));
+    assert_eq!(contract.balance_of(&accounts[0]), 200);
+    assert_eq!(contract.balance_of(&accounts[1]), 100);
+    assert_eq!(contract.total_supply(), 300);
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    env, near_bindgen,
+    serde_json::json,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools::{
+    approval::{
+        simple_multisig::{ApprovalState, Configuration},
+        ApprovalManager,
+    },
+    config::*,
+    hook::Hook,
+    rbac::Rbac,
+    Config, Rbac, SimpleMultisig,
+};
+
+use crate::macros::logs_without_debug;
+
+const FEE_BPS: Parameter<u16> = Parameter::new("fee_bps");
+const TREASURY: Parameter<AccountId> = Parameter::new("treasury");
+
+#[derive(BorshSerialize, BorshStorageKey, Debug)]
+enum Role {
+    Council,
+}
+
+#[derive(Config, Rbac, SimpleMultisig)]
+#[config(change_hook = "ValidateChange")]
+#[rbac(roles = "Role")]
+#[simple_multisig(action = "ConfigChange", role = "Role::Council")]
+#[near_bindgen]
+struct Contract {}
+
+struct ValidateChange;
+
+impl Hook<Contract, ConfigChange> for ValidateChange {
+    fn hook<R>(
+        contract: &mut Contract,
+        change: &ConfigChange,
+        f: impl FnOnce(&mut Contract) -> R,
+    ) -> R {
+        match &change.value {
+            Some(value) if change.name == FEE_BPS.name => {
+                let fee_bps = FEE_BPS
+                    .parse(value)
+                    .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+                if fee_bps > 10_000 {
+                    env::panic_str("Fee cannot exceed 100%");
+                }
+            }
+            _ => {}
+        }
+        f(contract)
+    }
+}
+
+fn predecessor(account_id: &str) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id.parse().unwrap())
+        .build());
+}
+
+fn setup() -> Contract {
+    let mut contract = Contract {};
+    <Contract as ApprovalManager<ConfigChange, ApprovalState, _>>::init(Configuration::new(2, 0));
+    contract.add_role("alice".parse().unwrap(), &Role::Council);
+    contract.add_role("bob".parse().unwrap(), &Role::Council);
+    contract
+}
+
+fn approve_and_execute(contract: &mut Contract, change: ConfigChange) {
+    predecessor("alice");
+    let request_id = contract
+        .create_request(change, ApprovalState::new())
+        .unwrap();
+    contract.approve_request(request_id).unwrap();
+    predecessor("bob");
+    contract.approve_request(request_id).unwrap();
+    contract.execute_request(request_id).unwrap();
+}
+
+#[test]
+fn change_through_approval() {
+    let mut contract = setup();
+
+    assert_eq!(contract.config_get("fee_bps".to_string()), None);
+    assert!(contract.config_list().is_empty());
+
+    approve_and_execute(&mut contract, FEE_BPS.change(&30));
+    assert_eq!(
+        logs_without_debug(),
+        [
+            r#"EVENT_JSON:{"standard":"x-config","version":"1.0.0","event":"change","data":{"name":"fee_bps","old_value":null,"new_value":30}}"#
+        ],
+    );
+
+    approve_and_execute(
+        &mut contract,
+        TREASURY.change(&"treasury.near".parse().unwrap()),
+    );
+    approve_and_execute(&mut contract, FEE_BPS.change(&25));
+    assert_eq!(
+        logs_without_debug(),
+        [
+            r#"EVENT_JSON:{"standard":"x-config","version":"1.0.0","event":"change","data":{"name":"fee_bps","old_value":30,"new_value":25}}"#
+        ],
+    );
+
+    assert_eq!(Contract::get(&FEE_BPS), Some(25));
+    assert_eq!(
+        Contract::get(&TREASURY),
+        Some("treasury.near".parse().unwrap()),
+    );
+    assert_eq!(
+        contract.config_list(),
+        BTreeMap::from([
+            ("fee_bps".to_string(), json!(25)),
+            ("treasury".to_string(), json!("treasury.near")),
+        ]),
+    );
+
+    approve_and_execute(&mut contract, TREASURY.remove());
+    assert_eq!(Contract::get(&TREASURY), None);
+    assert_eq!(contract.config_list().len(), 1);
+}
+
+#[test]
+fn change_requires_approval() {
+    let mut contract = setup();
+
+    predecessor("alice");
+    let request_id = contract
+        .create_request(FEE_BPS.change(&30), ApprovalState::new())
+        .unwrap();
+    contract.approve_request(request_id).unwrap();
+
+    assert!(contract.execute_request(request_id).is_err());
+    assert_eq!(Contract::get(&FEE_BPS), None);
+}
+
+#[test]
+#[should_panic(expected = "Fee cannot exceed 100%")]
+fn change_hook_validates() {
+    let mut contract = setup();
+
+    approve_and_execute(&mut contract, FEE_BPS.change(&10_001));
+}
+
+#[test]
+fn typed_get_rejects_mismatched_value() {
+    let mut contract = setup();
+
+    contract
+        .apply_change(&ConfigChange {
+            name: TREASURY.name.to_string(),
+            value: Some(json!(1)),
+        })
+        .unwrap();
+
+    assert!(Contract::try_get(&TREASURY).is_err());
+    assert_eq!(
+        contract.apply_change(&ConfigChange {
+            name: String::new(),
+            value: None,
+        }),
+        Err(InvalidParameterNameError),
+    );
+}
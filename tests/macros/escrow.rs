@@ -88,3 +88,245 @@ fn test_cannot_unlock_until_ready() {
 
     assert!(contract.get_locked(&ID).is_none());
 }
+
+mod storage_accounting {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        env,
+        json_types::{U128, U64},
+        near_bindgen,
+        test_utils::{get_logs, VMContextBuilder},
+        testing_env, AccountId, PanicOnDefault,
+    };
+    use near_sdk_contract_tools::{
+        escrow::{Escrow, Nep145Escrow},
+        standard::nep145::{Nep145Controller, StorageBalanceBounds},
+        Escrow, Nep145,
+    };
+
+    #[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Escrow, Nep145)]
+    #[escrow(id = "U64", state = "String")]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[near_bindgen]
+    impl Contract {
+        #[init]
+        pub fn new() -> Self {
+            let mut contract = Self {};
+
+            contract.set_storage_balance_bounds(&StorageBalanceBounds {
+                min: U128(0),
+                max: None,
+            });
+
+            contract
+        }
+    }
+
+    fn alice() -> AccountId {
+        "alice".parse().unwrap()
+    }
+
+    fn setup(deposit: u128) -> Contract {
+        testing_env!(VMContextBuilder::new().build());
+        let mut contract = Contract::new();
+        contract
+            .deposit_to_storage_account(&alice(), U128(deposit))
+            .unwrap();
+        contract
+    }
+
+    #[test]
+    fn lock_and_unlock() {
+        let mut contract = setup(env::storage_byte_cost() * 1_000);
+
+        contract
+            .lock_with_storage(&alice(), &U64(1), &"ready".to_string())
+            .unwrap();
+
+        let balance = contract.get_storage_balance(&alice()).unwrap();
+        let locked = balance.total.0 - balance.available.0;
+        assert!(locked > 0);
+        assert_eq!(contract.locker_of(&U64(1)), Some(alice()));
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"x-escrow","version":"1.0.0","event":"escrow_locked","data":{"id":"1","locker_id":"alice","state":"ready"}}"#,
+        );
+
+        let refund = contract.unlock_with_storage(&U64(1), |state| state == "ready");
+
+        assert_eq!(refund.0, locked);
+        assert!(!contract.is_locked(&U64(1)));
+        assert_eq!(contract.locker_of(&U64(1)), None);
+        let balance = contract.get_storage_balance(&alice()).unwrap();
+        assert_eq!(balance.available, balance.total);
+        assert_eq!(
+            get_logs().last().unwrap(),
+            &format!(
+                r#"EVENT_JSON:{{"standard":"x-escrow","version":"1.0.0","event":"escrow_unlocked","data":{{"id":"1","locker_id":"alice","storage_refund":"{locked}"}}}}"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn insufficient_storage_balance() {
+        let mut contract = setup(1);
+
+        assert!(contract
+            .lock_with_storage(&alice(), &U64(1), &"ready".to_string())
+            .is_err());
+        assert!(!contract.is_locked(&U64(1)));
+        assert_eq!(contract.locker_of(&U64(1)), None);
+    }
+}
+
+mod accrual {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        json_types::{U128, U64},
+        near_bindgen,
+        test_utils::{get_logs, VMContextBuilder},
+        testing_env, AccountId, PanicOnDefault,
+    };
+    use near_sdk_contract_tools::{
+        escrow::{accrual::*, Escrow},
+        ft::Nep141Controller,
+        Escrow, Nep141,
+    };
+
+    const DAY: u64 = 86_400_000_000_000;
+
+    #[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Escrow, Nep141)]
+    #[escrow(id = "U64", state = "bool", accrual)]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn alice() -> AccountId {
+        "alice".parse().unwrap()
+    }
+
+    fn at(timestamp: u64) {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+    }
+
+    fn setup(policy: AccrualConfig) -> Contract {
+        at(0);
+        let mut contract = Contract {};
+        contract.init_accrual_policy(&policy);
+        contract.deposit_unchecked(&alice(), 1_000).unwrap();
+        contract
+            .lock_with_accrual(&U64(1), &true, &alice(), 1_000)
+            .unwrap();
+        contract
+    }
+
+    #[test]
+    fn linear_interest() {
+        let mut contract = setup(AccrualConfig::Linear(LinearAccrual {
+            kind: AccrualKind::Interest,
+            rate_bps: 1_000,
+            period_ns: U64(DAY),
+        }));
+
+        at(DAY * 3 / 2);
+        let accrual = Accrual {
+            kind: AccrualKind::Interest,
+            amount: U128(150),
+        };
+        assert_eq!(contract.accrued(&U64(1)).unwrap(), Some(accrual));
+
+        assert_eq!(
+            contract
+                .unlock_with_accrual(&U64(1), |ready| *ready)
+                .unwrap(),
+            Some(accrual),
+        );
+        assert!(!contract.is_locked(&U64(1)));
+        assert_eq!(contract.accrual_of(&U64(1)), None);
+        assert_eq!(contract.balance_of(&alice()), 1_150);
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"x-escrow","version":"1.0.0","event":"accrual_settled","data":{"id":"1","beneficiary_id":"alice","accrual":{"kind":"interest","amount":"150"}}}"#,
+        );
+    }
+
+    #[test]
+    fn stepwise_penalty() {
+        let mut contract = setup(AccrualConfig::Stepwise(StepwiseAccrual {
+            kind: AccrualKind::Penalty,
+            steps: vec![AccrualStep {
+                after_ns: U64(DAY),
+                rate_bps: 200,
+            }],
+        }));
+
+        at(DAY);
+        contract
+            .unlock_with_accrual(&U64(1), |ready| *ready)
+            .unwrap();
+        assert_eq!(contract.balance_of(&alice()), 980);
+    }
+
+    #[test]
+    fn penalty_exceeds_balance() {
+        let mut contract = setup(AccrualConfig::Stepwise(StepwiseAccrual {
+            kind: AccrualKind::Penalty,
+            steps: vec![AccrualStep {
+                after_ns: U64(0),
+                rate_bps: 20_000,
+            }],
+        }));
+
+        contract.withdraw_unchecked(&alice(), 400).unwrap();
+
+        assert_eq!(
+            contract
+                .unlock_with_accrual(&U64(1), |ready| *ready)
+                .unwrap(),
+            Some(Accrual {
+                kind: AccrualKind::Penalty,
+                amount: U128(600),
+            }),
+        );
+        assert!(!contract.is_locked(&U64(1)));
+        assert_eq!(contract.balance_of(&alice()), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Accrual period must be nonzero")]
+    fn linear_zero_period() {
+        at(0);
+        Contract {}.init_accrual_policy(&AccrualConfig::Linear(LinearAccrual {
+            kind: AccrualKind::Interest,
+            rate_bps: 1,
+            period_ns: U64(0),
+        }));
+    }
+
+    #[test]
+    fn lock_without_policy() {
+        at(0);
+        let mut contract = Contract {};
+
+        assert!(matches!(
+            contract.lock_with_accrual(&U64(1), &true, &alice(), 1_000),
+            Err(AccrualError::NoPolicy),
+        ));
+        assert!(!contract.is_locked(&U64(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Accrual policy already initialized")]
+    fn init_policy_twice() {
+        setup(AccrualConfig::Linear(LinearAccrual {
+            kind: AccrualKind::Interest,
+            rate_bps: 1,
+            period_ns: U64(DAY),
+        }))
+        .init_accrual_policy(&AccrualConfig::Stepwise(StepwiseAccrual {
+            kind: AccrualKind::Interest,
+            steps: vec![],
+        }));
+    }
+}
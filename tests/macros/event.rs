@@ -154,3 +154,30 @@ mod event_attribute_macro {
         assert_eq!(e.to_event_string(), f.to_event_string());
     }
 }
+
+#[test]
+fn emit_many() {
+    use near_sdk::test_utils::get_logs;
+
+    let mint = |owner_id: &str, token_id: &str| {
+        test_events::NftMint(vec![Nep171NftMintData {
+            owner_id: owner_id.to_string(),
+            token_ids: vec![token_id.to_string()],
+        }])
+    };
+
+    Event::emit_many(&[mint("alice", "1"), mint("bob", "2"), mint("carol", "3")]);
+    Event::emit_many(&[
+        test_events::EnumEvent::VariantOne,
+        test_events::EnumEvent::VariantOne,
+    ]);
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"alice","token_ids":["1"]},{"owner_id":"bob","token_ids":["2"]},{"owner_id":"carol","token_ids":["3"]}]}"#,
+            r#"EVENT_JSON:{"standard":"enum-event","version":"1.0.0","event":"VariantOne","data":"VariantOne"}"#,
+            r#"EVENT_JSON:{"standard":"enum-event","version":"1.0.0","event":"VariantOne","data":"VariantOne"}"#,
+        ],
+    );
+}
@@ -0,0 +1,116 @@
+use near_sdk::{
+    json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId,
+};
+use near_sdk_contract_tools::{
+    escrow::Escrow as _,
+    fractionalize::{
+        FractionalVault, Fractionalize as _, FractionalizeError, FractionalizeExternal, RedeemError,
+    },
+    ft::*,
+    nft::*,
+    Escrow, Fractionalize,
+};
+
+#[derive(Nep141, Nep171, Escrow, Fractionalize)]
+#[escrow(id = "TokenId", state = "FractionalVault")]
+#[near_bindgen]
+struct Contract {}
+
+fn call(predecessor: &AccountId) {
+    testing_env!(VMContextBuilder::new()
+        .current_account_id("vault".parse().unwrap())
+        .predecessor_account_id(predecessor.clone())
+        .attached_deposit(1)
+        .build());
+}
+
+#[test]
+fn fractionalize_and_redeem() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let token_id = "token".to_string();
+
+    contract.mint_unchecked(std::array::from_ref(&token_id), &alice);
+
+    call(&alice);
+    contract.fractionalize_lock(token_id.clone(), U128(1000));
+
+    assert_eq!(
+        contract.fractionalize_vault(),
+        Some(FractionalVault {
+            token_id: token_id.clone(),
+            curator_id: alice.clone(),
+            shares: U128(1000),
+        }),
+    );
+    assert!(contract.is_locked(&token_id));
+    assert_eq!(
+        contract.token_owner(&token_id),
+        Some("vault".parse().unwrap())
+    );
+    assert_eq!(contract.ft_balance_of(alice.clone()), U128(1000));
+    assert!(matches!(
+        contract.fractionalize(&alice, &"other".to_string(), 1),
+        Err(FractionalizeError::AlreadyFractionalized(t)) if t == token_id,
+    ));
+
+    contract.ft_transfer(bob.clone(), U128(400), None);
+
+    assert!(matches!(
+        contract.redeem(&alice),
+        Err(RedeemError::InsufficientShares {
+            balance: 600,
+            shares: 1000,
+            ..
+        }),
+    ));
+
+    call(&bob);
+    contract.ft_transfer(alice.clone(), U128(400), None);
+
+    call(&alice);
+    assert_eq!(contract.fractionalize_redeem(), token_id);
+
+    assert_eq!(contract.fractionalize_vault(), None);
+    assert!(!contract.is_locked(&token_id));
+    assert_eq!(contract.token_owner(&token_id), Some(alice.clone()));
+    assert_eq!(contract.ft_total_supply(), U128(0));
+    assert!(matches!(
+        contract.redeem(&alice),
+        Err(RedeemError::NotFractionalized),
+    ));
+
+    // The token can be fractionalized again.
+    contract.fractionalize_lock(token_id.clone(), U128(10));
+    assert_eq!(contract.ft_balance_of(alice), U128(10));
+}
+
+#[test]
+fn fractionalize_errors() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let token_id = "token".to_string();
+
+    call(&alice);
+    contract.mint_unchecked(std::array::from_ref(&token_id), &alice);
+
+    assert!(matches!(
+        contract.fractionalize(&alice, &token_id, 0),
+        Err(FractionalizeError::ZeroShares),
+    ));
+    assert!(matches!(
+        contract.fractionalize(&bob, &token_id, 10),
+        Err(FractionalizeError::Transfer(_)),
+    ));
+
+    contract.deposit_unchecked(&bob, 5).unwrap();
+    assert!(matches!(
+        contract.fractionalize(&alice, &token_id, 10),
+        Err(FractionalizeError::SharesOutstanding(5)),
+    ));
+
+    assert_eq!(contract.vault(), None);
+    assert_eq!(contract.token_owner(&token_id), Some(alice));
+}
@@ -0,0 +1,97 @@
+use near_sdk::{
+    json_types::{U128, U64},
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, Gas,
+};
+use near_sdk_contract_tools::{
+    ft::*,
+    gas::{GasConfigExternal, GasKey},
+    manifest::{component, HasManifest},
+    owner::Owner,
+    standard::nep141::GAS_FOR_FT_TRANSFER_CALL,
+    GasConfig, Owner,
+};
+
+#[derive(Owner, GasConfig, Nep141)]
+#[nep141(gas_config)]
+#[near_bindgen]
+struct Contract {}
+
+fn call(predecessor: &AccountId, gas: Gas) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.clone())
+        .attached_deposit(1)
+        .prepaid_gas(gas)
+        .build());
+}
+
+fn setup() -> (Contract, AccountId) {
+    let mut contract = Contract {};
+    let owner: AccountId = "owner".parse().unwrap();
+    Contract::init(&mut contract, &owner);
+    (contract, owner)
+}
+
+#[test]
+fn owner_overrides_gas() {
+    let (mut contract, owner) = setup();
+
+    assert_eq!(
+        contract.gas_config_get(GasKey::FtTransferCall),
+        U64(GAS_FOR_FT_TRANSFER_CALL.0),
+    );
+
+    call(&owner, Gas(300_000_000_000_000));
+    contract.gas_config_set(GasKey::FtTransferCall, Some(U64(50_000_000_000_000)));
+
+    assert_eq!(
+        contract.gas_config_get(GasKey::FtTransferCall),
+        U64(50_000_000_000_000),
+    );
+}
+
+#[test]
+#[should_panic = "Owner only"]
+fn non_owner_cannot_override_gas() {
+    let (mut contract, _) = setup();
+
+    call(&"alice".parse().unwrap(), Gas(300_000_000_000_000));
+    contract.gas_config_set(GasKey::FtTransferCall, Some(U64(50_000_000_000_000)));
+}
+
+#[test]
+#[should_panic = "must exceed gas for"]
+fn rejects_transfer_call_below_resolve() {
+    let (mut contract, owner) = setup();
+
+    call(&owner, Gas(300_000_000_000_000));
+    contract.gas_config_set(GasKey::FtTransferCall, Some(U64(1_000_000_000_000)));
+}
+
+#[test]
+#[should_panic = "Insufficient gas: prepaid 40000000000000 gas < required 50000000000000 gas"]
+fn transfer_call_requires_overridden_gas() {
+    let (mut contract, owner) = setup();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    call(&owner, Gas(300_000_000_000_000));
+    contract.gas_config_set(GasKey::FtTransferCall, Some(U64(50_000_000_000_000)));
+    contract.deposit_unchecked(&alice, 100).unwrap();
+
+    // Enough for the default, but not for the override.
+    call(&alice, Gas(40_000_000_000_000));
+    contract.ft_transfer_call(owner, U128(10), None, "".to_string());
+}
+
+#[test]
+fn manifest_describes_default_gas() {
+    let manifest = <Contract as HasManifest<component::Nep141>>::MANIFEST;
+    let method = manifest
+        .methods
+        .iter()
+        .find(|m| m.name == "ft_transfer_call")
+        .unwrap();
+
+    assert_eq!(method.gas, Some(GAS_FOR_FT_TRANSFER_CALL));
+}
@@ -0,0 +1,290 @@
+use near_sdk::{log, near_bindgen, AccountId};
+use near_sdk_contract_tools::{ft::*, hook::Hook, Nep141};
+
+use crate::macros::logs_without_debug;
+
+macro_rules! logging_hook {
+    ($name:ident) => {
+        struct $name;
+
+        impl<C, A> Hook<C, A> for $name {
+            fn hook<R>(contract: &mut C, _args: &A, f: impl FnOnce(&mut C) -> R) -> R {
+                log!("{} before", stringify!($name));
+                let r = f(contract);
+                log!("{} after", stringify!($name));
+                r
+            }
+        }
+    };
+}
+
+logging_hook!(First);
+logging_hook!(Second);
+logging_hook!(Third);
+
+fn hook_logs() -> Vec<String> {
+    logs_without_debug()
+        .into_iter()
+        .filter(|l| !l.starts_with("EVENT_JSON:"))
+        .collect()
+}
+
+fn alice() -> AccountId {
+    "alice".parse().unwrap()
+}
+
+fn bob() -> AccountId {
+    "bob".parse().unwrap()
+}
+
+#[test]
+fn tuple_order() {
+    <(First, Second, Third) as Hook<(), ()>>::hook(&mut (), &(), |_| log!("action"));
+
+    assert_eq!(
+        hook_logs(),
+        vec![
+            "First before",
+            "Second before",
+            "Third before",
+            "action",
+            "Third after",
+            "Second after",
+            "First after",
+        ],
+    );
+}
+
+#[derive(Nep141)]
+#[nep141(transfer_hook = "First", all_hooks = "(Second, Third)")]
+#[near_bindgen]
+struct ActionFirst {}
+
+#[test]
+fn action_hook_runs_before_all_hooks() {
+    let mut contract = ActionFirst {};
+    contract.deposit_unchecked(&alice(), 10).unwrap();
+
+    contract
+        .transfer(&Nep141Transfer {
+            sender_id: &alice(),
+            receiver_id: &bob(),
+            amount: 10,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+    assert_eq!(
+        hook_logs(),
+        vec![
+            "First before",
+            "Second before",
+            "Third before",
+            "Third after",
+            "Second after",
+            "First after",
+        ],
+    );
+}
+
+#[derive(Nep141)]
+#[nep141(
+    transfer_hook = "Third",
+    all_hooks = "(First, Second)",
+    all_hooks_first
+)]
+#[near_bindgen]
+struct AllFirst {}
+
+#[test]
+fn all_hooks_first() {
+    let mut contract = AllFirst {};
+    contract.deposit_unchecked(&alice(), 10).unwrap();
+
+    contract
+        .transfer(&Nep141Transfer {
+            sender_id: &alice(),
+            receiver_id: &bob(),
+            amount: 10,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+    assert_eq!(
+        hook_logs(),
+        vec![
+            "First before",
+            "Second before",
+            "Third before",
+            "Third after",
+            "Second after",
+            "First after",
+        ],
+    );
+}
+
+#[derive(FungibleToken)]
+#[fungible_token(mint_hook = "Second", all_hooks = "First", all_hooks_first)]
+#[near_bindgen]
+struct FungibleTokenAllFirst {}
+
+#[test]
+fn fungible_token_all_hooks_first() {
+    let mut contract = FungibleTokenAllFirst {};
+    contract
+        .deposit_to_storage_account(&alice(), near_sdk::ONE_NEAR.into())
+        .unwrap();
+
+    contract
+        .mint(&Nep141Mint {
+            amount: 10,
+            receiver_id: &alice(),
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+    assert_eq!(
+        hook_logs(),
+        vec![
+            "First before",
+            "Second before",
+            "Second after",
+            "First after"
+        ],
+    );
+    assert_eq!(contract.ft_balance_of(alice()).0, 10);
+}
+
+mod non_fungible_token {
+    use near_sdk::{near_bindgen, test_utils::VMContextBuilder, testing_env, ONE_NEAR};
+    use near_sdk_contract_tools::nft::*;
+
+    use super::*;
+
+    #[derive(NonFungibleToken)]
+    #[non_fungible_token(approve_hook = "Second", all_hooks = "First", all_hooks_first)]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn approve_all_hooks_first() {
+        let mut contract = Contract {};
+        contract
+            .deposit_to_storage_account(&alice(), ONE_NEAR.into())
+            .unwrap();
+        contract
+            .mint_with_metadata("t".to_string(), alice(), TokenMetadata::new().title("T"))
+            .unwrap();
+        testing_env!(VMContextBuilder::new().build());
+
+        contract
+            .approve(&Nep178Approve {
+                token_id: &"t".to_string(),
+                current_owner_id: &alice(),
+                account_id: &bob(),
+                origin: ActionOrigin::Internal,
+            })
+            .unwrap();
+
+        assert_eq!(
+            hook_logs(),
+            vec![
+                "First before",
+                "Second before",
+                "Second after",
+                "First after"
+            ],
+        );
+    }
+}
+
+mod origin {
+    use near_sdk::{
+        env, json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env,
+        PromiseResult, RuntimeFeesConfig, VMConfig,
+    };
+    use near_sdk_contract_tools::{ft::*, hook::ActionOrigin};
+
+    use super::*;
+
+    struct OriginHook;
+
+    impl<C> Hook<C, Nep141Transfer<'_>> for OriginHook {
+        fn hook<R>(
+            contract: &mut C,
+            transfer: &Nep141Transfer<'_>,
+            f: impl FnOnce(&mut C) -> R,
+        ) -> R {
+            log!("{:?}", transfer.origin);
+            f(contract)
+        }
+    }
+
+    #[derive(Nep141)]
+    #[nep141(transfer_hook = "OriginHook")]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn transfer(contract: &mut Contract) {
+        contract
+            .transfer(&Nep141Transfer {
+                sender_id: &alice(),
+                receiver_id: &bob(),
+                amount: 1,
+                memo: None,
+                msg: None,
+                revert: false,
+                origin: ActionOrigin::Internal,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn origin() {
+        let mut contract = Contract {};
+        contract.deposit_unchecked(&alice(), 10).unwrap();
+
+        transfer(&mut contract);
+        let mut logs = hook_logs();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+        contract.ft_transfer(bob(), U128(1), None);
+        let _ = contract.ft_transfer_call(bob(), U128(1), None, "".to_string());
+        logs.extend(hook_logs());
+
+        testing_env!(
+            VMContextBuilder::new()
+                .predecessor_account_id(env::current_account_id())
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"\"1\"".to_vec())],
+        );
+        contract.ft_resolve_transfer(alice(), bob(), U128(1));
+
+        transfer(&mut contract);
+        logs.extend(hook_logs());
+
+        assert_eq!(
+            logs,
+            vec![
+                "Internal",
+                "External(\"ft_transfer\")",
+                "External(\"ft_transfer_call\")",
+                "Callback(\"ft_resolve_transfer\")",
+                "Internal",
+            ],
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use near_sdk::{
+    json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId,
+};
+use near_sdk_contract_tools::{
+    hook::ActionOrigin,
+    invariant::*,
+    standard::nep141::{Nep141, Nep141Controller, Nep141ControllerInternal, Nep141Mint},
+    Nep141,
+};
+
+#[derive(Nep141)]
+#[contract_tools(invariants)]
+#[near_bindgen]
+struct Contract {}
+
+impl Invariants for Contract {
+    fn invariants() -> &'static [Invariant<Self>] {
+        &[Invariant {
+            name: "sampled balances within total supply",
+            check: |contract| nep141_balances_within_supply(contract, &[alice(), bob()]),
+        }]
+    }
+}
+
+fn alice() -> AccountId {
+    "alice".parse().unwrap()
+}
+
+fn bob() -> AccountId {
+    "bob".parse().unwrap()
+}
+
+fn setup() -> Contract {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice())
+        .attached_deposit(1)
+        .build());
+
+    let mut contract = Contract {};
+    contract
+        .mint(&Nep141Mint {
+            amount: 100,
+            receiver_id: &alice(),
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+    contract
+}
+
+#[test]
+fn invariants_hold() {
+    let mut contract = setup();
+
+    contract.ft_transfer(bob(), U128(40), None);
+
+    assert_eq!(contract.check_invariants(), Ok(()));
+}
+
+#[test]
+fn detects_violation() {
+    let contract = setup();
+
+    // Bypasses total supply accounting.
+    Contract::slot_account(&bob()).write(&1);
+
+    assert_eq!(
+        contract.check_invariants(),
+        Err(InvariantViolationError {
+            name: "sampled balances within total supply",
+            message: "sum of sampled balances 101 exceeds total supply 100".to_string(),
+        }),
+    );
+}
+
+#[cfg(feature = "debug")]
+#[test]
+#[should_panic(
+    expected = "Invariant violated: sampled balances within total supply: sum of sampled balances 101 exceeds total supply 100"
+)]
+fn generated_methods_check_invariants() {
+    let mut contract = setup();
+
+    Contract::slot_account(&bob()).write(&1);
+
+    contract.ft_transfer(bob(), U128(40), None);
+}
@@ -0,0 +1,59 @@
+use near_sdk::{env, near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId};
+use near_sdk_contract_tools::{
+    ft::*,
+    owner::{Owner, OwnerInternal},
+    pause::PauseInternal,
+    rbac::RbacInternal,
+    FungibleToken, Owner, Pause, Rbac,
+};
+
+use super::Role;
+
+#[derive(Owner, Pause, Rbac, FungibleToken)]
+#[contract_tools(key_prefix = "myapp")]
+#[pause(storage_key = "b\"paused\".to_vec()")]
+#[rbac(roles = "Role")]
+#[near_bindgen]
+struct Contract {}
+
+#[test]
+fn prefixed_default_keys() {
+    assert_eq!(<Contract as OwnerInternal>::root().key, b"myapp~o");
+    assert_eq!(<Contract as RbacInternal>::root().key, b"myapp~r");
+    assert_eq!(
+        <Contract as Nep141ControllerInternal>::root().key,
+        b"myapp~$141",
+    );
+    assert_eq!(
+        <Contract as Nep145ControllerInternal>::root().key,
+        b"myapp~$145",
+    );
+    assert_eq!(
+        <Contract as Nep148ControllerInternal>::root().key,
+        b"myapp~$148",
+    );
+}
+
+#[test]
+fn storage_key_takes_precedence() {
+    assert_eq!(<Contract as PauseInternal>::root().key, b"paused");
+}
+
+#[test]
+fn state_written_under_prefix() {
+    let owner: AccountId = "owner".parse().unwrap();
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(owner.clone())
+        .build());
+
+    let mut contract = Contract {};
+    Owner::init(&mut contract, &owner);
+
+    assert_eq!(Contract::slot_owner().read(), Some(owner));
+    assert!(env::storage_has_key(
+        &<Contract as OwnerInternal>::slot_owner().key
+    ));
+    assert!(<Contract as OwnerInternal>::slot_owner()
+        .key
+        .starts_with(b"myapp~o"));
+}
@@ -0,0 +1,133 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen, serde_json,
+};
+use near_sdk_contract_tools::{
+    ft::*,
+    manifest::{ArgumentManifest, Deposit, MethodKind, StandardManifest},
+    standard::nep141::GAS_FOR_FT_TRANSFER_CALL,
+    Manifest, Owner,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, FungibleToken, Owner, Manifest)]
+#[manifest(components(FungibleToken, Owner))]
+#[near_bindgen]
+struct Contract {}
+
+#[test]
+fn describes_generated_methods() {
+    let names = Contract::MANIFEST
+        .components
+        .iter()
+        .map(|c| c.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Nep141", "Nep145", "Nep148", "Owner"]);
+
+    let balance_of = Contract::MANIFEST.method("ft_balance_of").unwrap();
+    assert_eq!(balance_of.kind, MethodKind::View);
+    assert_eq!(balance_of.deposit, Deposit::None);
+    assert_eq!(
+        balance_of.args,
+        [ArgumentManifest {
+            name: "account_id",
+            ty: "AccountId",
+        }],
+    );
+    assert_eq!(balance_of.returns, Some("U128"));
+
+    let transfer_call = Contract::MANIFEST.method("ft_transfer_call").unwrap();
+    assert_eq!(transfer_call.kind, MethodKind::Call);
+    assert_eq!(transfer_call.deposit, Deposit::OneYocto);
+    assert_eq!(transfer_call.gas, Some(GAS_FOR_FT_TRANSFER_CALL));
+
+    let storage_deposit = Contract::MANIFEST.method("storage_deposit").unwrap();
+    assert!(storage_deposit.payable);
+    assert_eq!(storage_deposit.deposit, Deposit::Attached);
+    assert_eq!(storage_deposit.args[0].ty, "Option<AccountId>");
+
+    let resolve = Contract::MANIFEST.method("ft_resolve_transfer").unwrap();
+    assert!(resolve.private);
+}
+
+#[test]
+fn manifest_view() {
+    let contract = Contract {};
+    let json = serde_json::to_value(contract.__tools_manifest()).unwrap();
+
+    let transfer = &json["components"][0]["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["name"] == "ft_transfer")
+        .unwrap();
+
+    assert_eq!(transfer["kind"], "call");
+    assert_eq!(transfer["deposit"], "one_yocto");
+    assert_eq!(transfer["gas"], serde_json::Value::Null);
+    assert_eq!(transfer["args"][1]["type"], "U128");
+}
+
+#[test]
+fn supported_standards() {
+    let contract = Contract {};
+
+    assert_eq!(
+        contract.supported_standards(),
+        [
+            StandardManifest {
+                standard: "nep141",
+                version: "1.0.0",
+            },
+            StandardManifest {
+                standard: "nep297",
+                version: "1.0.0",
+            },
+            StandardManifest {
+                standard: "nep145",
+                version: "1.0.0",
+            },
+            StandardManifest {
+                standard: "nep148",
+                version: "1.0.0",
+            },
+        ],
+    );
+
+    let json = serde_json::to_value(contract.supported_standards()).unwrap();
+    assert_eq!(json[0]["standard"], "nep141");
+    assert_eq!(json[0]["version"], "1.0.0");
+}
+
+mod non_fungible_token {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        near_bindgen,
+    };
+    use near_sdk_contract_tools::{nft::*, Manifest};
+
+    #[derive(BorshSerialize, BorshDeserialize, NonFungibleToken, Manifest)]
+    #[manifest(components(NonFungibleToken))]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn supported_standards() {
+        let standards = Contract {}
+            .supported_standards()
+            .into_iter()
+            .map(|s| format!("{}@{}", s.standard, s.version))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            standards,
+            [
+                "nep145@1.0.0",
+                "nep171@1.2.0",
+                "nep297@1.0.0",
+                "nep177@2.0.0",
+                "nep178@1.0.0",
+                "nep181@1.0.0",
+            ],
+        );
+    }
+}
@@ -47,3 +47,47 @@ fn default_from() {
 
     assert_eq!(migrated.bar, 99);
 }
+
+mod with_args {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        env, near_bindgen,
+        serde::{Deserialize, Serialize},
+    };
+    use near_sdk_contract_tools::{migrate::MigrateHookWithArgs, Migrate};
+
+    use super::Old;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(crate = "near_sdk::serde")]
+    pub struct MigrateArgs {
+        pub baz: u64,
+    }
+
+    #[derive(Migrate, BorshSerialize, BorshDeserialize)]
+    #[migrate(from = "Old", args = "MigrateArgs")]
+    #[near_bindgen]
+    struct MyContract {
+        pub bar: u64,
+        pub baz: u64,
+    }
+
+    impl MigrateHookWithArgs<MigrateArgs> for MyContract {
+        fn on_migrate_with_args(old: Old, args: MigrateArgs) -> Self {
+            Self {
+                bar: old.foo,
+                baz: args.baz,
+            }
+        }
+    }
+
+    #[test]
+    fn migrate_with_args() {
+        env::state_write(&Old::new(99));
+
+        let migrated = MyContract::migrate(MigrateArgs { baz: 7 });
+
+        assert_eq!(migrated.bar, 99);
+        assert_eq!(migrated.baz, 7);
+    }
+}
@@ -0,0 +1,67 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    near_bindgen, AccountId, ONE_NEAR,
+};
+use near_sdk_contract_tools::{ft::*, owner::Owner, sim::Env, FungibleToken, Owner, Pause};
+
+#[derive(FungibleToken, Owner, Pause, BorshSerialize, BorshDeserialize)]
+#[near_bindgen]
+struct Contract {}
+
+fn contract_id() -> AccountId {
+    "contract".parse().unwrap()
+}
+
+fn alice() -> AccountId {
+    "alice".parse().unwrap()
+}
+
+fn bob() -> AccountId {
+    "bob".parse().unwrap()
+}
+
+#[test]
+fn sim_owner_and_pause() {
+    let mut c = Contract {};
+    Owner::init(&mut c, &alice());
+
+    let env = Env::new(contract_id(), alice());
+    assert_eq!(c.sim_own_get_owner(&env), Some(alice()));
+    assert!(!c.sim_paus_is_paused(&env));
+
+    c.sim_own_propose_owner(&env.clone().attached_deposit(1), Some(bob()));
+    c.sim_own_accept_owner(&Env::new(contract_id(), bob()).attached_deposit(1));
+    assert_eq!(c.sim_own_get_owner(&env), Some(bob()));
+}
+
+#[test]
+fn sim_fungible_token_transfer() {
+    let mut c = Contract {};
+    c.set_metadata(&FungibleTokenMetadata::new(
+        "Test".into(),
+        "TEST".into(),
+        24,
+    ));
+    c.set_storage_balance_bounds(&StorageBalanceBounds {
+        min: U128(0),
+        max: None,
+    });
+
+    let env = Env::new(contract_id(), alice());
+    env.with(|| {
+        c.deposit_unchecked(&alice(), 100).unwrap();
+    });
+    c.sim_storage_deposit(&env.clone().attached_deposit(ONE_NEAR), None, None);
+    c.sim_storage_deposit(
+        &Env::new(contract_id(), bob()).attached_deposit(ONE_NEAR),
+        None,
+        None,
+    );
+
+    c.sim_ft_transfer(&env.clone().attached_deposit(1), bob(), U128(40), None);
+
+    assert_eq!(c.sim_ft_balance_of(&env, alice()), U128(60));
+    assert_eq!(c.sim_ft_balance_of(&env, bob()), U128(40));
+    assert_eq!(c.sim_ft_metadata(&env).symbol, "TEST");
+}
@@ -14,12 +14,48 @@ use near_sdk_contract_tools::{
     Escrow, Migrate, Owner, Pause, Rbac,
 };
 
+#[cfg(feature = "hashed-account-keys")]
+mod account_key;
+mod banned_accounts;
+mod blue_green;
+mod config;
 mod escrow;
 mod event;
+mod fractionalize;
+mod gas_config;
+mod hook_order;
+mod invariant;
+mod key_prefix;
+mod manifest;
 mod migrate;
+#[cfg(feature = "mocked-sim")]
+mod mocked_sim;
+mod oracle;
 mod owner;
+mod panic_prefix;
 mod pause;
+mod proxy_admin;
+mod raffle;
+mod rbac;
+mod recovery;
+mod replay;
+mod sale;
+mod simple_multisig;
+mod staking;
 mod standard;
+mod stats;
+mod storage_report;
+mod storage_version;
+mod upgrade;
+
+/// [`near_sdk::test_utils::get_logs`], without the messages of the
+/// `utils::log` macros, which are logged when testing with `--all-features`.
+pub fn logs_without_debug() -> Vec<String> {
+    near_sdk::test_utils::get_logs()
+        .into_iter()
+        .filter(|log| !log.starts_with("DEBUG [") && !log.starts_with("INFO ["))
+        .collect()
+}
 
 mod my_event {
     use near_sdk::{serde::Serialize, AccountId};
@@ -539,6 +575,7 @@ mod owned_fungible_token {
                     amount: amount.into(),
                     receiver_id: &env::predecessor_account_id(),
                     memo: None,
+                    origin: ActionOrigin::Internal,
                 },
             )
             .unwrap();
@@ -0,0 +1,86 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    json_types::U128,
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools::{
+    oracle::{AssetPrice, Oracle, OracleExternal, Price},
+    rbac::Rbac,
+    Oracle, Rbac,
+};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Oracle,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum Role {
+    Relayer,
+}
+
+#[derive(Oracle, Rbac)]
+#[oracle(storage_key = "StorageKey::Oracle", role = "Role::Relayer")]
+#[rbac(roles = "Role")]
+#[near_bindgen]
+struct Contract {}
+
+fn oracle() -> AccountId {
+    "oracle".parse().unwrap()
+}
+
+fn relayer() -> AccountId {
+    "relayer".parse().unwrap()
+}
+
+fn set_predecessor(account_id: AccountId) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id)
+        .build());
+}
+
+fn update(multiplier: u128) -> Vec<AssetPrice> {
+    vec![AssetPrice {
+        asset_id: "usdt.near".to_string(),
+        price: Price {
+            multiplier: U128(multiplier),
+            decimals: 6,
+        },
+    }]
+}
+
+#[test]
+fn oracle_and_role_can_update() {
+    let mut c = Contract {};
+    assert!(!c.oracle_is_initialized());
+    c.init(&oracle(), 1_000);
+    assert!(c.oracle_is_initialized());
+    c.add_role(relayer(), &Role::Relayer);
+
+    set_predecessor(oracle());
+    c.oracle_on_price_update(update(1_000_000));
+    assert_eq!(
+        c.get_price_or_err("usdt.near").unwrap().multiplier,
+        U128(1_000_000),
+    );
+
+    set_predecessor(relayer());
+    c.oracle_on_price_update(update(999_000));
+    assert_eq!(
+        c.get_price_or_err("usdt.near").unwrap().multiplier,
+        U128(999_000),
+    );
+    assert_eq!(c.oracle_get_account_id(), Some(oracle()));
+}
+
+#[test]
+#[should_panic = "Oracle only"]
+fn other_account_cannot_update() {
+    let mut c = Contract {};
+    c.init(&oracle(), 1_000);
+
+    set_predecessor("alice".parse().unwrap());
+    c.oracle_on_price_update(update(1));
+}
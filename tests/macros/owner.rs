@@ -1,11 +1,11 @@
 use near_sdk::{
     borsh::{self, BorshSerialize},
     env, near_bindgen,
-    test_utils::VMContextBuilder,
+    test_utils::{get_logs, VMContextBuilder},
     testing_env, AccountId, BorshStorageKey,
 };
 use near_sdk_contract_tools::{
-    owner::{Owner, OwnerExternal},
+    owner::{Ownable2StepExternal, Owner, OwnerExternal},
     Owner,
 };
 
@@ -187,3 +187,82 @@ fn derive_owner_ex_unauthorized() {
     // Alice is not authorized to call owner-only method
     c.set_permissioned_item(4);
 }
+
+#[derive(Owner)]
+#[owner(ownable2step)]
+#[near_bindgen]
+pub struct OwnedStructOwnable2Step {}
+
+#[near_bindgen]
+impl OwnedStructOwnable2Step {
+    #[init]
+    pub fn new() -> Self {
+        let mut contract = Self {};
+
+        Owner::init(&mut contract, &env::predecessor_account_id());
+
+        contract
+    }
+}
+
+#[test]
+fn derive_owner_ownable2step() {
+    let owner: AccountId = "owner".parse().unwrap();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(owner.clone())
+        .attached_deposit(1)
+        .build());
+    let mut c = OwnedStructOwnable2Step::new();
+
+    c.transfer_ownership(alice.clone());
+
+    assert_eq!(c.owner(), Some(owner.clone()));
+    assert_eq!(c.pending_owner(), Some(alice.clone()));
+    assert_eq!(
+        get_logs().last().unwrap(),
+        r#"EVENT_JSON:{"standard":"x-ownable2step","version":"1.0.0","event":"ownership_transfer_started","data":{"previous_owner":"owner","new_owner":"alice"}}"#,
+    );
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .build());
+
+    c.accept_ownership();
+
+    assert_eq!(c.owner(), Some(alice.clone()));
+    assert_eq!(c.pending_owner(), None);
+    assert_eq!(
+        get_logs().last().unwrap(),
+        r#"EVENT_JSON:{"standard":"x-ownable2step","version":"1.0.0","event":"ownership_transferred","data":{"previous_owner":"owner","new_owner":"alice"}}"#,
+    );
+
+    c.renounce_ownership();
+
+    assert_eq!(c.owner(), None);
+    assert_eq!(
+        get_logs().last().unwrap(),
+        r#"EVENT_JSON:{"standard":"x-ownable2step","version":"1.0.0","event":"ownership_transferred","data":{"previous_owner":"alice","new_owner":null}}"#,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Owner only")]
+fn derive_owner_ownable2step_unauthorized() {
+    let owner: AccountId = "owner".parse().unwrap();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(owner)
+        .build());
+    let mut c = OwnedStructOwnable2Step::new();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .build());
+
+    c.transfer_ownership(alice);
+}
@@ -0,0 +1,63 @@
+use near_sdk::{json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env};
+use near_sdk_contract_tools::{standard::nep141::*, Nep141};
+
+#[derive(Nep141)]
+#[contract_tools(panic_prefix = "mytoken")]
+#[near_bindgen]
+struct Contract {}
+
+#[test]
+#[should_panic = "[mytoken] Balance of the sender is insufficient"]
+fn prefixes_generated_panics() {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id("alice".parse().unwrap())
+        .attached_deposit(1)
+        .build());
+
+    let mut contract = Contract {};
+    contract
+        .deposit_unchecked(&"alice".parse().unwrap(), 10)
+        .unwrap();
+
+    contract.ft_transfer("bob".parse().unwrap(), U128(11), None);
+}
+
+mod upgrade {
+    use near_sdk::{
+        json_types::Base64VecU8, near_bindgen, test_utils::VMContextBuilder, testing_env,
+    };
+    use near_sdk_contract_tools::Upgrade;
+
+    #[derive(Upgrade)]
+    #[upgrade(hook = "any(self)")]
+    #[contract_tools(panic_prefix = "mytoken")]
+    #[near_bindgen]
+    struct AnyContract {}
+
+    #[derive(Upgrade)]
+    #[upgrade(hook = "self")]
+    #[contract_tools(panic_prefix = "mytoken")]
+    #[near_bindgen]
+    struct SelfContract {}
+
+    fn context() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("contract".parse().unwrap())
+            .predecessor_account_id("alice".parse().unwrap())
+            .build());
+    }
+
+    #[test]
+    #[should_panic = "[mytoken] Unauthorized upgrade"]
+    fn prefixes_unauthorized_upgrade() {
+        context();
+        AnyContract {}.upgrade(Base64VecU8(vec![1]));
+    }
+
+    #[test]
+    #[should_panic = "[mytoken] Method is private"]
+    fn prefixes_private_upgrade() {
+        context();
+        SelfContract {}.upgrade(Base64VecU8(vec![1]));
+    }
+}
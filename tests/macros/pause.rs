@@ -98,3 +98,99 @@ fn derive_pause_methods_fail_paused() {
 
     contract.only_when_unpaused(5);
 }
+
+mod views {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        json_types::U128,
+        near_bindgen,
+        serde::Serialize,
+        BorshStorageKey,
+    };
+    use near_sdk_contract_tools::{
+        pause::{Pause, PauseExternal},
+        standard::nep141::*,
+        Manifest, Nep141, Pause, Rbac,
+    };
+
+    #[derive(Nep141, Pause)]
+    #[contract_tools(pause_views)]
+    #[near_bindgen]
+    struct AllViews {}
+
+    #[derive(Nep141, Pause)]
+    #[contract_tools(pause_views(ft_balance_of))]
+    #[near_bindgen]
+    struct SelectedViews {}
+
+    #[test]
+    fn views_work_unpaused() {
+        let mut contract = AllViews {};
+        contract
+            .deposit_unchecked(&"alice".parse().unwrap(), 10)
+            .unwrap();
+
+        assert_eq!(contract.ft_balance_of("alice".parse().unwrap()), U128(10));
+        assert_eq!(contract.ft_total_supply(), U128(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "Disallowed while contract is paused")]
+    fn views_fail_paused() {
+        let mut contract = AllViews {};
+        contract.pause();
+
+        assert!(contract.paus_is_paused());
+        contract.ft_total_supply();
+    }
+
+    #[test]
+    fn selected_views() {
+        let mut contract = SelectedViews {};
+        contract.pause();
+
+        assert_eq!(contract.ft_total_supply(), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Disallowed while contract is paused")]
+    fn selected_views_fail_paused() {
+        let mut contract = SelectedViews {};
+        contract.pause();
+
+        contract.ft_balance_of("alice".parse().unwrap());
+    }
+
+    #[derive(
+        BorshSerialize, BorshDeserialize, BorshStorageKey, Serialize, Debug, PartialEq, Eq,
+    )]
+    #[serde(crate = "near_sdk::serde")]
+    enum Role {
+        Minter,
+    }
+
+    #[derive(Manifest, Nep141, Pause, Rbac)]
+    #[contract_tools(pause_views)]
+    #[manifest(components(Nep141, Pause, Rbac))]
+    #[rbac(roles = "Role", roles_of_view)]
+    #[near_bindgen]
+    struct Components {}
+
+    #[test]
+    #[should_panic(expected = "Disallowed while contract is paused")]
+    fn rbac_view_fails_paused() {
+        let mut contract = Components {};
+        contract.pause();
+
+        contract.rbac_roles_of("alice".parse().unwrap(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Disallowed while contract is paused")]
+    fn manifest_view_fails_paused() {
+        let mut contract = Components {};
+        contract.pause();
+
+        contract.supported_standards();
+    }
+}
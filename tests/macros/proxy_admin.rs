@@ -0,0 +1,75 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    near_bindgen,
+    test_utils::{get_created_receipts, VMContextBuilder},
+    testing_env, BorshStorageKey,
+};
+use near_sdk_contract_tools::{
+    approval::{
+        simple_multisig::{ApprovalState, Configuration},
+        ApprovalManager,
+    },
+    proxy_admin::*,
+    rbac::Rbac,
+    Rbac, SimpleMultisig,
+};
+
+#[derive(BorshSerialize, BorshStorageKey, Debug)]
+enum Role {
+    Admin,
+}
+
+#[derive(Rbac, SimpleMultisig)]
+#[rbac(roles = "Role")]
+#[simple_multisig(action = "ProxyAdminAction", role = "Role::Admin")]
+#[near_bindgen]
+struct Hub {}
+
+fn predecessor(account_id: &str) {
+    testing_env!(VMContextBuilder::new()
+        .current_account_id("hub".parse().unwrap())
+        .predecessor_account_id(account_id.parse().unwrap())
+        .build());
+}
+
+fn approve_and_execute(hub: &mut Hub, call: ChildCall) -> String {
+    predecessor("alice");
+    let request_id = hub
+        .create_request(
+            ProxyAdminAction::new("token".parse().unwrap(), call),
+            ApprovalState::new(),
+        )
+        .unwrap();
+    hub.approve_request(request_id).unwrap();
+    predecessor("bob");
+    hub.approve_request(request_id).unwrap();
+    hub.execute_request(request_id).unwrap();
+
+    format!("{:?}", get_created_receipts())
+}
+
+#[test]
+fn administer_child() {
+    let mut hub = Hub {};
+    <Hub as ApprovalManager<ProxyAdminAction, ApprovalState, _>>::init(Configuration::new(2, 0));
+    hub.add_role("alice".parse().unwrap(), &Role::Admin);
+    hub.add_role("bob".parse().unwrap(), &Role::Admin);
+
+    let receipts = approve_and_execute(&mut hub, ChildCall::AcceptOwner);
+    assert!(receipts.contains(r#"receiver_id: AccountId("token")"#));
+    assert!(receipts.contains(r#"function_name: "own_accept_owner""#));
+    assert!(receipts.contains("deposit: 1"));
+
+    let receipts = approve_and_execute(&mut hub, ChildCall::Pause);
+    assert!(receipts.contains(r#"function_name: "pause""#));
+    assert!(receipts.contains("deposit: 0"));
+
+    let receipts = approve_and_execute(
+        &mut hub,
+        ChildCall::Upgrade {
+            code: vec![1, 2, 3].into(),
+        },
+    );
+    assert!(receipts.contains(r#"function_name: "upgrade""#));
+    assert!(receipts.contains(&format!("{:?}", br#"{"code":"AQID"}"#.to_vec())));
+}
@@ -0,0 +1,218 @@
+use near_sdk::{env, near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId};
+use near_sdk_contract_tools::{
+    ft::*,
+    raffle::{
+        CloseError, DrawError, EntryError, Nep141RafflePrize, Nep141WeightedRaffle, PayoutError,
+        Raffle as _, RaffleBalanceLock, RaffleExternal, RaffleStatus,
+    },
+    standard::nep141::TransferError,
+    utils::rng::RevealError,
+    Raffle,
+};
+
+#[derive(Nep141, Raffle)]
+#[near_bindgen]
+struct Contract {}
+
+#[derive(Nep141, Raffle)]
+#[nep141(transfer_hook = "RaffleBalanceLock", burn_hook = "RaffleBalanceLock")]
+#[near_bindgen]
+struct LockedContract {}
+
+fn context(predecessor: &AccountId, block_height: u64) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.clone())
+        .block_index(block_height)
+        .random_seed([block_height as u8; 32])
+        .build());
+}
+
+fn accounts() -> Vec<AccountId> {
+    ["alice", "bob", "charlie", "dave"]
+        .iter()
+        .map(|a| a.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn weighted_draw_and_payout() {
+    let mut contract = Contract {};
+    let organizer: AccountId = "organizer".parse().unwrap();
+    let accounts = accounts();
+
+    for (i, account_id) in accounts.iter().enumerate() {
+        Nep141Controller::mint(
+            &mut contract,
+            &Nep141Mint {
+                amount: (i as u128 + 1) * 100,
+                receiver_id: account_id,
+                memo: None,
+                origin: ActionOrigin::Internal,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            contract.enter_with_balance(account_id).unwrap(),
+            (i as u128 + 1) * 100,
+        );
+    }
+    Nep141Controller::mint(
+        &mut contract,
+        &Nep141Mint {
+            amount: 1000,
+            receiver_id: &organizer,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        contract.enter(&accounts[0], 1),
+        Err(EntryError::AlreadyEntered(accounts[0].clone())),
+    );
+    assert_eq!(contract.raffle_entry_count(), 4);
+    assert_eq!(contract.total_weight(), 1000);
+    assert_eq!(contract.weight_of(&accounts[2]), Some(300));
+
+    context(&organizer, 10);
+    contract.close(env::sha256_array(b"secret")).unwrap();
+    assert_eq!(contract.close([0; 32]), Err(CloseError::NotOpen));
+    assert_eq!(contract.enter(&organizer, 1), Err(EntryError::NotOpen));
+    assert_eq!(
+        contract.draw(b"secret", 2),
+        Err(DrawError::Reveal(RevealError::TooEarly(10))),
+    );
+
+    context(&accounts[0], 11);
+    assert_eq!(
+        contract.draw(b"secret", 2),
+        Err(DrawError::WrongCommitter(organizer.clone())),
+    );
+
+    context(&organizer, 11);
+    let winners = contract.draw(b"secret", 2).unwrap();
+    assert_eq!(winners.len(), 2);
+    assert_ne!(winners[0], winners[1]);
+    assert_eq!(contract.raffle_winners(), winners);
+    assert_eq!(contract.raffle_get_status(), RaffleStatus::Drawn);
+
+    let before = contract.balance_of(&winners[0]);
+    assert!(matches!(
+        contract.pay_ft_prizes(&organizer, &[100]),
+        Err(PayoutError::PrizeCountMismatch {
+            expected: 2,
+            actual: 1,
+        }),
+    ));
+    contract.pay_ft_prizes(&organizer, &[100, 50]).unwrap();
+    assert_eq!(contract.balance_of(&winners[0]), before + 100);
+    assert_eq!(contract.balance_of(&organizer), 850);
+    assert_eq!(contract.raffle_get_status(), RaffleStatus::PaidOut);
+    assert!(matches!(
+        contract.pay_ft_prizes(&organizer, &[100, 50]),
+        Err(PayoutError::NotDrawn),
+    ));
+}
+
+#[test]
+fn draw_everyone() {
+    let mut contract = Contract {};
+    let organizer: AccountId = "organizer".parse().unwrap();
+    let accounts = accounts();
+
+    for account_id in &accounts {
+        contract.enter(account_id, 1).unwrap();
+    }
+
+    context(&organizer, 1);
+    contract.close(env::sha256_array(b"s")).unwrap();
+    context(&organizer, 2);
+    let mut winners = contract.draw(b"s", 10).unwrap();
+    winners.sort();
+    assert_eq!(winners, accounts);
+}
+
+#[test]
+fn failed_payout_pays_nothing() {
+    let mut contract = Contract {};
+    let organizer: AccountId = "organizer".parse().unwrap();
+    let accounts = accounts();
+
+    for account_id in &accounts[..2] {
+        contract.enter(account_id, 1).unwrap();
+    }
+    contract.deposit_unchecked(&organizer, 120).unwrap();
+
+    context(&organizer, 1);
+    contract.close(env::sha256_array(b"s")).unwrap();
+    context(&organizer, 2);
+    contract.draw(b"s", 2).unwrap();
+
+    assert!(matches!(
+        contract.pay_ft_prizes(&organizer, &[100, 50]),
+        Err(PayoutError::Transfer(
+            TransferError::SenderBalanceUnderflow(_)
+        )),
+    ));
+    assert_eq!(contract.balance_of(&organizer), 120);
+    assert_eq!(contract.balance_of(&accounts[0]), 0);
+    assert_eq!(contract.balance_of(&accounts[1]), 0);
+    assert_eq!(contract.raffle_get_status(), RaffleStatus::Drawn);
+
+    contract.pay_ft_prizes(&organizer, &[100, 20]).unwrap();
+    assert_eq!(contract.balance_of(&organizer), 0);
+    assert_eq!(contract.raffle_get_status(), RaffleStatus::PaidOut);
+}
+
+fn locked_contract() -> LockedContract {
+    let mut contract = LockedContract {};
+    let alice: AccountId = "alice".parse().unwrap();
+
+    contract.deposit_unchecked(&alice, 100).unwrap();
+    contract.enter_with_balance(&alice).unwrap();
+    contract
+}
+
+fn transfer(contract: &mut LockedContract, amount: u128) -> Result<(), TransferError> {
+    Nep141Controller::transfer(
+        contract,
+        &Nep141Transfer {
+            sender_id: &"alice".parse().unwrap(),
+            receiver_id: &"bob".parse().unwrap(),
+            amount,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        },
+    )
+}
+
+#[test]
+#[should_panic(expected = "Account 'alice' has 100 tokens locked in a raffle until it is drawn")]
+fn entered_balance_is_locked() {
+    let mut contract = locked_contract();
+
+    transfer(&mut contract, 1).unwrap();
+}
+
+#[test]
+fn balance_above_entry_is_unlocked() {
+    let mut contract = locked_contract();
+    let alice: AccountId = "alice".parse().unwrap();
+    let organizer: AccountId = "organizer".parse().unwrap();
+
+    contract.deposit_unchecked(&alice, 10).unwrap();
+    transfer(&mut contract, 10).unwrap();
+    assert_eq!(contract.locked_balance(&alice), 100);
+
+    context(&organizer, 1);
+    contract.close(env::sha256_array(b"s")).unwrap();
+    context(&organizer, 2);
+    contract.draw(b"s", 1).unwrap();
+
+    assert_eq!(contract.locked_balance(&alice), 0);
+    transfer(&mut contract, 100).unwrap();
+    assert_eq!(contract.balance_of(&alice), 0);
+}
@@ -0,0 +1,48 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+    serde::Serialize,
+    AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools::{rbac::Rbac, Rbac};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshStorageKey, Serialize, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+enum Role {
+    Admin,
+    Minter,
+    Burner,
+}
+
+#[derive(Rbac, BorshSerialize, BorshDeserialize)]
+#[rbac(roles = "Role", roles_of_view)]
+#[near_bindgen]
+struct Contract {}
+
+#[test]
+fn roles_of_view() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    contract.add_role(alice.clone(), &Role::Minter);
+    contract.add_role(alice.clone(), &Role::Burner);
+    contract.add_role(bob.clone(), &Role::Admin);
+
+    assert_eq!(
+        contract.rbac_roles_of(alice.clone(), None, None),
+        vec![Role::Minter, Role::Burner],
+    );
+    assert_eq!(
+        contract.rbac_roles_of(alice.clone(), Some(1), Some(5)),
+        vec![Role::Burner],
+    );
+    assert_eq!(contract.rbac_roles_of(bob, None, None), vec![Role::Admin]);
+
+    contract.remove_role(&alice, &Role::Minter);
+
+    assert_eq!(
+        contract.rbac_roles_of(alice, None, None),
+        vec![Role::Burner],
+    );
+}
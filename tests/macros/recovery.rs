@@ -0,0 +1,150 @@
+use near_sdk::{
+    json_types::U64, near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId,
+};
+use near_sdk_contract_tools::{
+    owner::{Owner, OwnerExternal},
+    recovery::{Recovery, RecoveryAction, RecoveryConfig, RecoveryError, RecoveryExternal},
+    Owner, Recovery,
+};
+
+const WINDOW: u64 = 1_000;
+
+#[derive(Owner, Recovery)]
+#[near_bindgen]
+struct Contract {}
+
+fn owner() -> AccountId {
+    "owner".parse().unwrap()
+}
+
+fn rescuer() -> AccountId {
+    "rescuer".parse().unwrap()
+}
+
+fn call(predecessor: &AccountId, timestamp: u64) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.clone())
+        .attached_deposit(1)
+        .block_timestamp(timestamp)
+        .build());
+}
+
+fn setup() -> Contract {
+    call(&owner(), 0);
+    let mut contract = Contract {};
+    Owner::init(&mut contract, &owner());
+    contract.recovery_set_config(Some(RecoveryConfig {
+        recovery_id: rescuer(),
+        window_ns: U64(WINDOW),
+    }));
+    contract
+}
+
+#[test]
+fn claim_after_inactivity() {
+    let mut contract = setup();
+
+    call(&owner(), 500);
+    contract.recovery_heartbeat();
+    assert_eq!(contract.recovery_get_last_heartbeat(), Some(U64(500)));
+    assert_eq!(contract.recovery_get_available_at(), Some(U64(1_500)));
+
+    call(&rescuer(), 1_499);
+    assert_eq!(
+        contract.recover(&rescuer()),
+        Err(RecoveryError::OwnerActive {
+            available_at: 1_500,
+        }),
+    );
+
+    call(&rescuer(), 1_500);
+    contract.recovery_claim();
+    assert_eq!(contract.own_get_owner(), Some(rescuer()));
+    assert_eq!(contract.recovery_get_last_heartbeat(), Some(U64(1_500)));
+}
+
+#[test]
+fn only_recovery_account_can_claim() {
+    let mut contract = setup();
+
+    call(&owner(), WINDOW);
+    assert_eq!(
+        contract.recover(&owner()),
+        Err(RecoveryError::Unauthorized(owner())),
+    );
+}
+
+#[test]
+#[should_panic = "Owner only"]
+fn non_owner_cannot_heartbeat() {
+    let mut contract = setup();
+
+    call(&rescuer(), 1);
+    contract.recovery_heartbeat();
+}
+
+#[test]
+fn disabled_recovery() {
+    let mut contract = setup();
+
+    contract.recovery_set_config(None);
+    assert_eq!(contract.recovery_get_available_at(), None);
+
+    call(&rescuer(), WINDOW * 10);
+    assert_eq!(
+        contract.recover(&rescuer()),
+        Err(RecoveryError::NotConfigured),
+    );
+}
+
+#[test]
+#[should_panic = "Recovery window must be nonzero"]
+fn rejects_zero_window() {
+    let mut contract = setup();
+
+    contract.recovery_set_config(Some(RecoveryConfig {
+        recovery_id: rescuer(),
+        window_ns: U64(0),
+    }));
+}
+
+mod custom_action {
+    use near_sdk::{log, AccountId};
+
+    use super::*;
+
+    pub struct LogRecovery;
+
+    impl RecoveryAction<CustomContract> for LogRecovery {
+        fn recover(_contract: &mut CustomContract, recovery_id: &AccountId) {
+            log!("recovered by {}", recovery_id);
+        }
+    }
+
+    #[derive(Owner, Recovery)]
+    #[recovery(action = "LogRecovery")]
+    #[near_bindgen]
+    pub struct CustomContract {}
+
+    #[test]
+    fn runs_custom_action() {
+        call(&owner(), 0);
+        let mut contract = CustomContract {};
+        Owner::init(&mut contract, &owner());
+        contract
+            .set_recovery_config(Some(RecoveryConfig {
+                recovery_id: rescuer(),
+                window_ns: U64(WINDOW),
+            }))
+            .unwrap();
+
+        call(&rescuer(), WINDOW);
+        contract.recovery_claim();
+
+        assert_eq!(contract.own_get_owner(), Some(owner()));
+        assert_eq!(
+            near_sdk::test_utils::get_logs().first().unwrap(),
+            "recovered by rescuer",
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use near_sdk::{near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId};
+use near_sdk_contract_tools::{
+    replay::{AlreadyProcessedError, ReplayGuard, ReplayGuardExternal, ReplayKey},
+    ReplayGuard,
+};
+
+const TTL: u64 = 1_000;
+
+#[derive(ReplayGuard)]
+#[replay_guard(ttl = "TTL", cleanup_limit = "1")]
+#[near_bindgen]
+struct Receiver {}
+
+fn at(timestamp: u64) {
+    testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+}
+
+#[test]
+fn reject_duplicates() {
+    let mut receiver = Receiver {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let key = ReplayKey::new(&alice, "1");
+
+    at(100);
+    assert!(!receiver.replay_is_processed(alice.clone(), "1".to_string()));
+    receiver.record_processed(&key).unwrap();
+    assert!(receiver.replay_is_processed(alice.clone(), "1".to_string()));
+    assert!(!receiver.replay_is_processed(alice.clone(), "2".to_string()));
+    assert!(!receiver.replay_is_processed("bob".parse().unwrap(), "1".to_string()));
+
+    assert_eq!(
+        receiver.record_processed(&key),
+        Err(AlreadyProcessedError { expires_at: 1_100 }),
+    );
+    assert_eq!(
+        Receiver::check_not_processed(&key),
+        Err(AlreadyProcessedError { expires_at: 1_100 }),
+    );
+
+    receiver
+        .record_processed(&ReplayKey::new(&alice, "2"))
+        .unwrap();
+    assert_eq!(receiver.replay_processed_count().0, 2);
+}
+
+#[test]
+fn expiry_and_cleanup() {
+    let mut receiver = Receiver {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let first = ReplayKey::new(&alice, "1");
+    let second = ReplayKey::new(&alice, "2");
+
+    at(0);
+    receiver.record_processed(&first).unwrap();
+    at(500);
+    receiver.record_processed(&second).unwrap();
+    assert_eq!(Receiver::processed_count(), 2);
+
+    at(1_000);
+    assert!(!Receiver::is_processed(&first));
+    assert!(Receiver::is_processed(&second));
+    assert_eq!(Receiver::processed_count(), 2);
+
+    // The expired key may be processed again. Recording it cleans up its
+    // old record, but keeps the new one.
+    receiver.record_processed(&first).unwrap();
+    assert!(Receiver::is_processed(&first));
+    assert_eq!(Receiver::processed_count(), 2);
+
+    at(1_500);
+    assert_eq!(receiver.replay_cleanup(10), 1);
+    assert!(!Receiver::is_processed(&second));
+    assert!(Receiver::is_processed(&first));
+    assert_eq!(Receiver::processed_count(), 1);
+
+    at(2_000);
+    assert_eq!(receiver.replay_cleanup(10), 1);
+    assert_eq!(Receiver::processed_count(), 0);
+    assert_eq!(receiver.replay_cleanup(10), 0);
+}
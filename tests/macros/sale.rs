@@ -0,0 +1,263 @@
+use near_sdk::{
+    json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    nft::*,
+    sale::{Payout, RoyaltiesExceedPriceError, SaleCurrency, SaleExternal, SaleRoyalties},
+    standard::nep141::{Nep141, Nep141Controller},
+    Nep141, Sale,
+};
+
+struct ArtistRoyalty;
+
+impl<C> SaleRoyalties<C> for ArtistRoyalty {
+    fn royalties(_contract: &C, _token_id: &TokenId) -> Vec<(AccountId, u16)> {
+        vec![(artist(), 1_000)]
+    }
+}
+
+#[derive(Nep141, NonFungibleToken, Sale)]
+#[sale(royalties = "ArtistRoyalty")]
+#[near_bindgen]
+struct Contract {}
+
+fn alice() -> AccountId {
+    "alice".parse().unwrap()
+}
+
+fn bob() -> AccountId {
+    "bob".parse().unwrap()
+}
+
+fn artist() -> AccountId {
+    "artist".parse().unwrap()
+}
+
+fn call(predecessor: &AccountId, deposit: u128) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.clone())
+        .attached_deposit(deposit)
+        .build());
+}
+
+fn setup() -> Contract {
+    let mut contract = Contract {};
+    for account_id in [alice(), bob()] {
+        contract
+            .deposit_to_storage_account(&account_id, U128(ONE_NEAR))
+            .unwrap();
+    }
+    contract
+        .mint_with_metadata("t".to_string(), alice(), TokenMetadata::new().title("T"))
+        .unwrap();
+    contract.deposit_unchecked(&bob(), 1_000).unwrap();
+    contract
+}
+
+#[test]
+fn ft_sale() {
+    let mut contract = setup();
+
+    call(&alice(), 1);
+    contract.sale_list("t".to_string(), U128(100), SaleCurrency::Ft);
+    assert_eq!(
+        contract.sale_get_listing("t".to_string()).unwrap().price,
+        U128(100),
+    );
+
+    call(&bob(), 1);
+    let payout = contract.sale_buy_ft("t".to_string(), U128(100));
+
+    assert_eq!(
+        payout,
+        Payout {
+            payout: [(alice(), U128(90)), (artist(), U128(10))].into(),
+        },
+    );
+    assert_eq!(contract.ft_balance_of(alice()), U128(90));
+    assert_eq!(contract.ft_balance_of(artist()), U128(10));
+    assert_eq!(contract.ft_balance_of(bob()), U128(900));
+    assert_eq!(
+        contract.nft_token("t".to_string(), None).unwrap().owner_id,
+        bob()
+    );
+    assert!(contract.sale_get_listing("t".to_string()).is_none());
+    assert_eq!(
+        near_sdk::test_utils::get_logs().last().unwrap(),
+        r#"EVENT_JSON:{"standard":"x-sale","version":"1.0.0","event":"sale_completed","data":{"token_id":"t","seller_id":"alice","buyer_id":"bob","price":"100","currency":"ft"}}"#,
+    );
+}
+
+#[test]
+fn near_sale() {
+    let mut contract = setup();
+
+    call(&alice(), 1);
+    contract.sale_list("t".to_string(), U128(ONE_NEAR), SaleCurrency::Near);
+
+    call(&bob(), 2 * ONE_NEAR);
+    let payout = contract.sale_buy("t".to_string());
+
+    assert_eq!(
+        payout,
+        Payout {
+            payout: [
+                (alice(), U128(ONE_NEAR / 10 * 9)),
+                (artist(), U128(ONE_NEAR / 10)),
+            ]
+            .into(),
+        },
+    );
+    assert_eq!(
+        contract.nft_token("t".to_string(), None).unwrap().owner_id,
+        bob()
+    );
+}
+
+#[test]
+#[should_panic(expected = "Token 't' is not owned by 'bob'")]
+fn list_requires_owner() {
+    let mut contract = setup();
+
+    call(&bob(), 1);
+    contract.sale_list("t".to_string(), U128(100), SaleCurrency::Ft);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient payment: required 100 but got 99")]
+fn ft_sale_above_max_price() {
+    let mut contract = setup();
+
+    call(&alice(), 1);
+    contract.sale_list("t".to_string(), U128(100), SaleCurrency::Ft);
+
+    call(&bob(), 1);
+    contract.sale_buy_ft("t".to_string(), U128(99));
+}
+
+#[test]
+#[should_panic(expected = "Token 't' is not priced in Ft")]
+fn wrong_currency() {
+    let mut contract = setup();
+
+    call(&alice(), 1);
+    contract.sale_list("t".to_string(), U128(100), SaleCurrency::Near);
+
+    call(&bob(), 1);
+    contract.sale_buy_ft("t".to_string(), U128(100));
+}
+
+#[test]
+#[should_panic(expected = "Listing of token 't' is stale")]
+fn stale_listing() {
+    let mut contract = setup();
+
+    call(&alice(), 1);
+    contract.sale_list("t".to_string(), U128(100), SaleCurrency::Ft);
+    contract.nft_transfer(bob(), "t".to_string(), None, None);
+
+    call(&bob(), 1);
+    contract.sale_buy_ft("t".to_string(), U128(100));
+}
+
+#[test]
+fn delist() {
+    let mut contract = setup();
+
+    call(&alice(), 1);
+    contract.sale_list("t".to_string(), U128(100), SaleCurrency::Ft);
+    contract.sale_delist("t".to_string());
+    assert!(contract.sale_get_listing("t".to_string()).is_none());
+}
+
+#[test]
+fn payout_split() {
+    let payout = Payout::split(&alice(), 999, &[(artist(), 1_000), (bob(), 1)]).unwrap();
+    assert_eq!(
+        payout,
+        Payout {
+            payout: [(alice(), U128(900)), (artist(), U128(99))].into(),
+        },
+    );
+
+    assert_eq!(
+        Payout::split(&alice(), 100, &[(artist(), 6_000), (bob(), 5_000)]),
+        Err(RoyaltiesExceedPriceError(11_000)),
+    );
+}
+
+mod listing_hook {
+    use near_sdk::test_utils::get_logs;
+    use near_sdk_contract_tools::sale::Sale as _;
+
+    use super::*;
+
+    #[derive(Nep141, NonFungibleToken, Sale)]
+    #[non_fungible_token(sale)]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn setup() -> Contract {
+        let mut contract = Contract {};
+        for account_id in [alice(), bob()] {
+            contract
+                .deposit_to_storage_account(&account_id, U128(ONE_NEAR))
+                .unwrap();
+        }
+        contract
+            .mint_with_metadata("t".to_string(), alice(), TokenMetadata::new().title("T"))
+            .unwrap();
+        contract
+    }
+
+    #[test]
+    fn transfer_removes_listing() {
+        let mut contract = setup();
+
+        call(&alice(), 1);
+        contract.sale_list("t".to_string(), U128(100), SaleCurrency::Ft);
+        contract.nft_transfer(bob(), "t".to_string(), None, None);
+        assert!(contract.sale_get_listing("t".to_string()).is_none());
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"x-sale","version":"1.0.0","event":"sale_delisted","data":{"token_id":"t"}}"#,
+        );
+
+        call(&bob(), 1);
+        contract.nft_transfer(alice(), "t".to_string(), None, None);
+        assert!(contract.sale_get_listing("t".to_string()).is_none());
+    }
+
+    #[test]
+    fn burn_removes_listing() {
+        let mut contract = setup();
+
+        call(&alice(), 1);
+        contract.sale_list("t".to_string(), U128(100), SaleCurrency::Ft);
+        Nep171Controller::burn(
+            &mut contract,
+            &Nep171Burn {
+                token_ids: &["t".to_string()],
+                owner_id: &alice(),
+                authorized_id: None,
+                memo: None,
+                origin: ActionOrigin::Internal,
+            },
+        )
+        .unwrap();
+        assert!(contract.listing(&"t".to_string()).is_none());
+    }
+
+    #[test]
+    fn sale_does_not_delist() {
+        let mut contract = setup();
+        contract.deposit_unchecked(&bob(), 1_000).unwrap();
+
+        call(&alice(), 1);
+        contract.sale_list("t".to_string(), U128(100), SaleCurrency::Ft);
+
+        call(&bob(), 1);
+        contract.sale_buy_ft("t".to_string(), U128(100));
+        assert!(!get_logs().iter().any(|log| log.contains("sale_delisted")));
+    }
+}
@@ -0,0 +1,400 @@
+use std::fmt;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+    serde::{Deserialize, Serialize},
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools::{
+    approval::{
+        simple_multisig::{ApprovalState, Configuration, MemberAction},
+        Action, ApprovalManager, RequestMetadata,
+    },
+    rbac::{Rbac, RbacAction},
+    Rbac, SimpleMultisig,
+};
+
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    BorshStorageKey,
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+enum Role {
+    Multisig,
+    Minter,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Noop;
+
+impl Action<Contract> for Noop {
+    type Output = ();
+
+    fn execute(self, _contract: &mut Contract) {}
+}
+
+#[derive(Rbac, SimpleMultisig, BorshSerialize, BorshDeserialize)]
+#[rbac(roles = "Role")]
+#[simple_multisig(
+    action = "Noop",
+    role = "Role::Multisig",
+    rbac_actions,
+    member_actions,
+    request_views
+)]
+#[near_bindgen]
+struct Contract {}
+
+fn predecessor(account_id: &AccountId) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id.clone())
+        .build());
+}
+
+#[test]
+fn rbac_actions() {
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let charlie: AccountId = "charlie".parse().unwrap();
+
+    let mut contract = Contract {};
+    <Contract as ApprovalManager<RbacAction<Role>, ApprovalState, _>>::init(Configuration::new(
+        2, 0,
+    ));
+    contract.add_role(alice.clone(), &Role::Multisig);
+    contract.add_role(bob.clone(), &Role::Multisig);
+
+    predecessor(&alice);
+    let grant = contract.rbac_request(
+        RbacAction::Grant {
+            account_id: charlie.clone(),
+            role: Role::Minter,
+        },
+        None,
+    );
+    contract.rbac_approve(grant);
+    assert_eq!(
+        contract.rbac_get_request(grant).unwrap().action,
+        RbacAction::Grant {
+            account_id: charlie.clone(),
+            role: Role::Minter,
+        },
+    );
+
+    predecessor(&bob);
+    contract.rbac_approve(grant);
+    assert!(!Contract::has_role(&charlie, &Role::Minter));
+    contract.rbac_execute(grant);
+    assert!(Contract::has_role(&charlie, &Role::Minter));
+    assert!(contract.rbac_get_request(grant).is_none());
+
+    let revoke = contract.rbac_request(
+        RbacAction::Revoke {
+            account_id: charlie.clone(),
+            role: Role::Minter,
+        },
+        None,
+    );
+    assert_ne!(grant, revoke);
+    contract.rbac_approve(revoke);
+    predecessor(&alice);
+    contract.rbac_approve(revoke);
+    contract.rbac_execute(revoke);
+    assert!(!Contract::has_role(&charlie, &Role::Minter));
+}
+
+#[test]
+#[should_panic(expected = "Missing role")]
+fn rbac_request_requires_role() {
+    let mut contract = Contract {};
+    <Contract as ApprovalManager<RbacAction<Role>, ApprovalState, _>>::init(Configuration::new(
+        2, 0,
+    ));
+
+    predecessor(&"mallory".parse().unwrap());
+    contract.rbac_request(
+        RbacAction::Grant {
+            account_id: "mallory".parse().unwrap(),
+            role: Role::Multisig,
+        },
+        None,
+    );
+}
+
+#[test]
+fn request_summaries() {
+    let alice: AccountId = "alice".parse().unwrap();
+
+    let mut contract = Contract {};
+    <Contract as ApprovalManager<Noop, ApprovalState, _>>::init(Configuration::new(2, 0));
+    <Contract as ApprovalManager<RbacAction<Role>, ApprovalState, _>>::init(Configuration::new(
+        1, 0,
+    ));
+    contract.add_role(alice.clone(), &Role::Multisig);
+
+    let metadata = RequestMetadata {
+        title: "Pause minting".to_string(),
+        description: Some("Noop for now".to_string()),
+        url: None,
+    };
+
+    predecessor(&alice);
+    let first = contract
+        .create_request_with_metadata(Noop, ApprovalState::new(), Some(metadata.clone()))
+        .unwrap();
+    let second = contract.create_request(Noop, ApprovalState::new()).unwrap();
+
+    let summary = contract.multisig_get_request_summary(first).unwrap();
+    assert_eq!(summary.request_id, first);
+    assert_eq!(summary.created_by, alice);
+    assert_eq!(summary.metadata, Some(metadata));
+
+    let summaries = contract.multisig_list_request_summaries(None, None);
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[1].request_id, second);
+    assert_eq!(summaries[1].metadata, None);
+
+    assert_eq!(
+        contract.multisig_list_request_summaries(Some(1), Some(1)),
+        vec![summaries[1].clone()],
+    );
+
+    let keyed = contract
+        .create_request_idempotent("pause".to_string(), Noop, ApprovalState::new(), None)
+        .unwrap();
+    assert_eq!(
+        contract.multisig_find_request_by_key("pause".to_string()),
+        Some(keyed),
+    );
+    assert_eq!(
+        contract.multisig_find_request_by_key("other".to_string()),
+        None
+    );
+
+    let grant = contract.rbac_request(
+        RbacAction::Grant {
+            account_id: alice.clone(),
+            role: Role::Minter,
+        },
+        Some(RequestMetadata {
+            title: "Make alice a minter".to_string(),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(
+        contract
+            .rbac_get_request_summary(grant)
+            .unwrap()
+            .metadata
+            .unwrap()
+            .title,
+        "Make alice a minter",
+    );
+    contract.rbac_approve(grant);
+    contract.rbac_execute(grant);
+    assert!(contract.rbac_get_request_summary(grant).is_none());
+    assert!(contract.rbac_list_request_summaries(None, None).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Request metadata field 'title' exceeds maximum length")]
+fn request_metadata_too_long() {
+    let alice: AccountId = "alice".parse().unwrap();
+
+    let mut contract = Contract {};
+    <Contract as ApprovalManager<RbacAction<Role>, ApprovalState, _>>::init(Configuration::new(
+        1, 0,
+    ));
+    contract.add_role(alice.clone(), &Role::Multisig);
+
+    predecessor(&alice);
+    contract.rbac_request(
+        RbacAction::Grant {
+            account_id: alice,
+            role: Role::Minter,
+        },
+        Some(RequestMetadata {
+            title: "x".repeat(129),
+            ..Default::default()
+        }),
+    );
+}
+
+fn init_members(threshold: u8, members: &[&str]) -> Contract {
+    let mut contract = Contract {};
+    <Contract as ApprovalManager<Noop, ApprovalState, _>>::init(Configuration::new(threshold, 0));
+    <Contract as ApprovalManager<MemberAction, ApprovalState, _>>::init(Configuration::new(
+        threshold, 0,
+    ));
+    for member in members {
+        contract.add_role(member.parse().unwrap(), &Role::Multisig);
+    }
+    contract
+}
+
+fn approve_and_execute(contract: &mut Contract, request_id: u32, approvers: &[&str]) {
+    for approver in approvers {
+        predecessor(&approver.parse().unwrap());
+        contract.multisig_member_approve(request_id);
+    }
+    contract.multisig_member_execute(request_id);
+}
+
+#[test]
+fn member_actions() {
+    let charlie: AccountId = "charlie".parse().unwrap();
+
+    let contract = Contract {};
+    assert!(!contract.multisig_is_initialized());
+    assert!(!contract.multisig_member_is_initialized());
+    assert!(!contract.rbac_actions_is_initialized());
+
+    let mut contract = init_members(2, &["alice", "bob"]);
+    <Contract as ApprovalManager<RbacAction<Role>, ApprovalState, _>>::init(Configuration::new(
+        2, 0,
+    ));
+    assert!(contract.multisig_is_initialized());
+    assert!(contract.multisig_member_is_initialized());
+    assert!(contract.rbac_actions_is_initialized());
+
+    predecessor(&"alice".parse().unwrap());
+    let add = contract.multisig_member_request(
+        MemberAction::AddMember {
+            account_id: charlie.clone(),
+        },
+        None,
+    );
+    assert_eq!(
+        contract.multisig_member_get_request(add).unwrap().action,
+        MemberAction::AddMember {
+            account_id: charlie.clone(),
+        },
+    );
+    approve_and_execute(&mut contract, add, &["alice", "bob"]);
+    assert!(Contract::has_role(&charlie, &Role::Multisig));
+    assert_eq!(contract.multisig_members().len(), 3);
+
+    let set_threshold =
+        contract.multisig_member_request(MemberAction::SetThreshold { threshold: 3 }, None);
+    approve_and_execute(&mut contract, set_threshold, &["bob", "charlie"]);
+    assert_eq!(contract.multisig_threshold(), 3);
+    assert_eq!(
+        <Contract as ApprovalManager<MemberAction, ApprovalState, _>>::get_config().threshold,
+        3,
+    );
+    assert_eq!(
+        <Contract as ApprovalManager<RbacAction<Role>, ApprovalState, _>>::get_config().threshold,
+        3,
+    );
+
+    let set_threshold =
+        contract.multisig_member_request(MemberAction::SetThreshold { threshold: 2 }, None);
+    approve_and_execute(&mut contract, set_threshold, &["alice", "bob", "charlie"]);
+    let remove = contract.multisig_member_request(
+        MemberAction::RemoveMember {
+            account_id: charlie.clone(),
+        },
+        None,
+    );
+    approve_and_execute(&mut contract, remove, &["alice", "bob"]);
+    assert!(!Contract::has_role(&charlie, &Role::Multisig));
+    assert_eq!(contract.multisig_threshold(), 2);
+}
+
+#[test]
+fn removed_member_approvals_do_not_count() {
+    let mut contract = init_members(2, &["alice", "bob", "charlie"]);
+
+    predecessor(&"alice".parse().unwrap());
+    let noop = contract.create_request(Noop, ApprovalState::new()).unwrap();
+    <Contract as ApprovalManager<Noop, ApprovalState, _>>::approve_request(&mut contract, noop)
+        .unwrap();
+    predecessor(&"charlie".parse().unwrap());
+    <Contract as ApprovalManager<Noop, ApprovalState, _>>::approve_request(&mut contract, noop)
+        .unwrap();
+
+    predecessor(&"alice".parse().unwrap());
+    let remove = contract.multisig_member_request(
+        MemberAction::RemoveMember {
+            account_id: "charlie".parse().unwrap(),
+        },
+        None,
+    );
+    approve_and_execute(&mut contract, remove, &["alice", "bob"]);
+
+    assert!(<Contract as ApprovalManager<Noop, _, _>>::is_approved_for_execution(noop).is_err());
+
+    predecessor(&"bob".parse().unwrap());
+    <Contract as ApprovalManager<Noop, ApprovalState, _>>::approve_request(&mut contract, noop)
+        .unwrap();
+    assert!(<Contract as ApprovalManager<Noop, _, _>>::is_approved_for_execution(noop).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "Threshold 2 cannot be reached by 1 members")]
+fn remove_member_below_threshold() {
+    let mut contract = init_members(2, &["alice", "bob"]);
+
+    predecessor(&"alice".parse().unwrap());
+    contract.multisig_member_request(
+        MemberAction::RemoveMember {
+            account_id: "bob".parse().unwrap(),
+        },
+        None,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Threshold 2 cannot be reached by 1 members")]
+fn remove_member_below_threshold_at_execution() {
+    let mut contract = init_members(1, &["alice", "bob"]);
+
+    predecessor(&"alice".parse().unwrap());
+    let remove = contract.multisig_member_request(
+        MemberAction::RemoveMember {
+            account_id: "bob".parse().unwrap(),
+        },
+        None,
+    );
+    let set_threshold =
+        contract.multisig_member_request(MemberAction::SetThreshold { threshold: 2 }, None);
+    approve_and_execute(&mut contract, set_threshold, &["alice"]);
+    approve_and_execute(&mut contract, remove, &["alice", "bob"]);
+}
+
+#[test]
+#[should_panic(expected = "Threshold 3 cannot be reached by 2 members")]
+fn threshold_above_members() {
+    let mut contract = init_members(2, &["alice", "bob"]);
+
+    predecessor(&"alice".parse().unwrap());
+    contract.multisig_member_request(MemberAction::SetThreshold { threshold: 3 }, None);
+}
+
+#[test]
+#[should_panic(expected = "Missing role")]
+fn member_request_requires_role() {
+    let mut contract = init_members(2, &["alice", "bob"]);
+
+    predecessor(&"mallory".parse().unwrap());
+    contract.multisig_member_request(
+        MemberAction::AddMember {
+            account_id: "mallory".parse().unwrap(),
+        },
+        None,
+    );
+}
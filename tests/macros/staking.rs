@@ -0,0 +1,157 @@
+use near_sdk::{near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId};
+use near_sdk_contract_tools::{
+    nft::*,
+    staking::nft::{
+        NftStaking as _, NftStakingError, NftStakingExternal, NftStakingHook, StakeInfo,
+        TokenLockedError,
+    },
+    NftStaking,
+};
+
+const EPOCH: u64 = 1_000;
+const COOLDOWN: u64 = 500;
+
+#[derive(Nep171, NftStaking)]
+#[nep171(transfer_hook = "NftStakingHook", burn_hook = "NftStakingHook")]
+#[nft_staking(epoch = "EPOCH", reward_per_epoch = "10", cooldown = "COOLDOWN")]
+#[near_bindgen]
+struct Contract {}
+
+fn call(predecessor: &AccountId, timestamp: u64) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.clone())
+        .block_timestamp(timestamp)
+        .attached_deposit(1)
+        .build());
+}
+
+fn setup() -> (Contract, AccountId, TokenId) {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let token_id = "token".to_string();
+
+    call(&alice, 0);
+    contract.mint_unchecked(std::array::from_ref(&token_id), &alice);
+    contract.nft_stake(token_id.clone());
+
+    (contract, alice, token_id)
+}
+
+#[test]
+fn stake_and_claim_rewards() {
+    let (mut contract, alice, token_id) = setup();
+
+    assert_eq!(
+        contract.nft_stake_info(token_id.clone()),
+        Some(StakeInfo {
+            owner_id: alice.clone(),
+            staked_at: 0.into(),
+            rewards_from: 0.into(),
+            unstaked_at: None,
+        }),
+    );
+    assert!(Contract::is_locked(&token_id));
+    assert_eq!(
+        contract.token_owner(&token_id),
+        Some(alice.clone()),
+        "Staked token stays with its owner",
+    );
+
+    call(&alice, 2_500);
+    assert_eq!(contract.nft_pending_staking_rewards(token_id.clone()).0, 20);
+    assert_eq!(contract.nft_claim_staking_rewards(token_id.clone()).0, 20);
+    assert_eq!(contract.nft_pending_staking_rewards(token_id.clone()).0, 0);
+    assert_eq!(contract.nft_staking_reward_balance(alice.clone()).0, 20);
+
+    // The partial epoch is not lost by claiming.
+    call(&alice, 3_000);
+    assert_eq!(contract.nft_pending_staking_rewards(token_id.clone()).0, 10);
+    assert_eq!(contract.nft_claim_staking_rewards(token_id).0, 10);
+
+    assert_eq!(contract.take_rewards(&alice), 30);
+    assert_eq!(contract.nft_staking_reward_balance(alice).0, 0);
+}
+
+#[test]
+fn unstake_and_cooldown() {
+    let (mut contract, alice, token_id) = setup();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    call(&alice, 1_200);
+    assert_eq!(contract.nft_unstake(token_id.clone()).0, 1_700);
+    assert_eq!(contract.nft_staking_reward_balance(alice.clone()).0, 10);
+    assert_eq!(
+        Contract::check_unlocked(&token_id),
+        Err(TokenLockedError {
+            token_id: token_id.clone(),
+            available_at: Some(1_700),
+        }),
+    );
+
+    // No rewards accrue during the cooldown.
+    call(&alice, 1_699);
+    assert_eq!(contract.nft_pending_staking_rewards(token_id.clone()).0, 0);
+    assert!(matches!(
+        contract.unstake(&alice, &token_id),
+        Err(NftStakingError::NotStaked(_)),
+    ));
+
+    call(&alice, 1_700);
+    assert!(!Contract::is_locked(&token_id));
+    assert_eq!(contract.nft_stake_info(token_id.clone()), None);
+
+    contract.nft_transfer(bob.clone(), token_id.clone(), None, None);
+    assert_eq!(contract.token_owner(&token_id), Some(bob));
+}
+
+#[test]
+fn stake_errors() {
+    let (mut contract, alice, token_id) = setup();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    assert!(matches!(
+        contract.stake(&alice, &token_id),
+        Err(NftStakingError::Locked(_)),
+    ));
+    assert!(matches!(
+        contract.stake(&alice, &"missing".to_string()),
+        Err(NftStakingError::TokenDoesNotExist(_)),
+    ));
+    assert!(matches!(
+        contract.unstake(&bob, &token_id),
+        Err(NftStakingError::NotOwner(_)),
+    ));
+
+    contract.mint_unchecked(&["other".to_string()], &alice);
+    assert!(matches!(
+        contract.stake(&bob, &"other".to_string()),
+        Err(NftStakingError::NotOwner(_)),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Token `token` is staked")]
+fn staked_token_cannot_be_transferred() {
+    let (mut contract, alice, token_id) = setup();
+
+    call(&alice, 100);
+    contract.nft_transfer("bob".parse().unwrap(), token_id, None, None);
+}
+
+#[test]
+#[should_panic(expected = "Token `token` is staked until 600")]
+fn cooling_down_token_cannot_be_burned() {
+    let (mut contract, alice, token_id) = setup();
+
+    call(&alice, 100);
+    contract.nft_unstake(token_id.clone());
+    contract
+        .burn(&Nep171Burn {
+            token_ids: &[token_id],
+            owner_id: &alice,
+            authorized_id: None,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+}
@@ -2,6 +2,7 @@ use near_sdk::{json_types::Base64VecU8, near_bindgen};
 use near_sdk_contract_tools::ft::*;
 
 #[derive(FungibleToken)]
+#[fungible_token(human_views)]
 #[near_bindgen]
 struct MyFungibleTokenContract {}
 
@@ -91,4 +92,148 @@ mod tests {
             Some(Base64VecU8::from([97, 115, 100, 102].to_vec()))
         );
     }
+
+    #[test]
+    fn human_views() {
+        let mut ft = MyFungibleTokenContract::new();
+        let alice: AccountId = "alice".parse().unwrap();
+
+        ft.deposit_to_storage_account(&alice, (ONE_NEAR / 100).into())
+            .unwrap();
+        ft.deposit_unchecked(&alice, 1_500_000_000_000_000_000_000_000)
+            .unwrap();
+        ft.deposit_unchecked(&"bob".parse().unwrap(), 5).unwrap();
+
+        assert_eq!(ft.ft_balance_of_human(alice), "1.5");
+        assert_eq!(ft.ft_total_supply_human(), "1.500000000000000000000005");
+        assert_eq!(ft.ft_balance_of_human("carol".parse().unwrap()), "0");
+
+        assert_eq!(ft.ft_metadata_version(), 1);
+        let metadata = ft.get_metadata().decimals(2);
+        ft.set_metadata(&metadata);
+        assert_eq!(ft.ft_metadata_version(), 2);
+        assert_eq!(ft.ft_balance_of_human("bob".parse().unwrap()), "0.05");
+    }
+}
+
+mod extra {
+    use near_sdk::{near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId, ONE_NEAR};
+    use near_sdk_contract_tools::{ft::*, hook::Hook, slot::Slot};
+
+    /// A standard implemented outside the crate: counts transfers.
+    struct TransferCount;
+
+    impl TransferCount {
+        fn slot() -> Slot<u32> {
+            Slot::new(b"transfer_count".to_vec())
+        }
+    }
+
+    impl<C> Hook<C, Nep141Mint<'_>> for TransferCount {}
+
+    impl<C> Hook<C, Nep141Transfer<'_>> for TransferCount {
+        fn hook<R>(contract: &mut C, _: &Nep141Transfer<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+            let r = f(contract);
+            Self::slot().write(&(Self::slot().read().unwrap_or(0) + 1));
+            r
+        }
+    }
+
+    impl<C> Hook<C, Nep141Burn<'_>> for TransferCount {}
+
+    #[derive(FungibleToken)]
+    #[fungible_token(extra = "TransferCount")]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn extra_standard() {
+        let mut ft = Contract {};
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        ft.deposit_to_storage_account(&alice, ONE_NEAR.into())
+            .unwrap();
+        ft.deposit_to_storage_account(&bob, ONE_NEAR.into())
+            .unwrap();
+        ft.deposit_unchecked(&alice, 100).unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(1)
+            .build());
+        ft.ft_transfer(bob.clone(), 50.into(), None);
+        ft.ft_transfer(bob, 10.into(), None);
+
+        assert_eq!(TransferCount::slot().read(), Some(2));
+    }
+}
+
+mod zero_balance_sweep {
+    use near_sdk::{
+        borsh::{self, BorshSerialize},
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId, BorshStorageKey, ONE_NEAR,
+    };
+    use near_sdk_contract_tools::{
+        ft::*, rbac::Rbac, standard::nep141::Nep141ZeroBalanceSweep, Rbac,
+    };
+
+    #[derive(BorshSerialize, BorshStorageKey)]
+    enum Role {
+        Maintainer,
+    }
+
+    #[derive(FungibleToken, Rbac)]
+    #[fungible_token(zero_balance_sweep_role = "Role::Maintainer")]
+    #[rbac(roles = "Role")]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn sweep_refunds_storage() {
+        let mut ft = Contract {};
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        ft.add_role("maintainer".parse().unwrap(), &Role::Maintainer);
+        ft.deposit_to_storage_account(&alice, ONE_NEAR.into())
+            .unwrap();
+        ft.deposit_to_storage_account(&bob, ONE_NEAR.into())
+            .unwrap();
+        ft.mint(&Nep141Mint {
+            amount: 10,
+            receiver_id: &bob,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+        let registered = ft.storage_balance_of(alice.clone()).unwrap();
+
+        ft.mint(&Nep141Mint {
+            amount: 100,
+            receiver_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+        ft.burn(&Nep141Burn {
+            amount: 100,
+            owner_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+        let after_burn = ft.storage_balance_of(alice.clone()).unwrap();
+        assert!(after_burn.available.0 < registered.available.0);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("maintainer".parse().unwrap())
+            .attached_deposit(1)
+            .build());
+        assert_eq!(ft.ft_sweep_zero_balances(vec![alice.clone(), bob]), 1);
+
+        assert_eq!(ft.storage_balance_of(alice).unwrap(), registered);
+    }
 }
@@ -1,5 +1,7 @@
 pub mod fungible_token;
 pub mod nep141;
+pub mod nep141_bridge;
+pub mod nep141_subscription;
 pub mod nep145;
 pub mod nep148;
 pub mod nep171;
@@ -7,7 +7,11 @@ use near_sdk::{
     test_utils::VMContextBuilder,
     testing_env, AccountId, PromiseOrValue,
 };
-use near_sdk_contract_tools::{hook::Hook, standard::nep141::*, Nep141};
+use near_sdk_contract_tools::{
+    hook::{ActionOrigin, Hook},
+    standard::nep141::*,
+    Nep141,
+};
 
 #[derive(Nep141, BorshDeserialize, BorshSerialize)]
 #[nep141(transfer_hook = "TransferHook")]
@@ -104,6 +108,7 @@ fn nep141_transfer() {
                 memo: None,
                 msg: None,
                 revert: false,
+                origin: ActionOrigin::Internal,
             }
             .try_to_vec()
             .unwrap()
@@ -118,3 +123,1021 @@ fn nep141_transfer() {
     assert_eq!(ft.ft_balance_of(bob.clone()).0, 70);
     assert_eq!(ft.ft_total_supply().0, 120);
 }
+
+#[test]
+#[should_panic = "Callback must be called by alice.near, not bob"]
+fn resolve_transfer_requires_self_callback() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    testing_env!(
+        VMContextBuilder::new()
+            .predecessor_account_id("bob".parse().unwrap())
+            .build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"\"0\"".to_vec())],
+    );
+
+    ft.ft_resolve_transfer("alice".parse().unwrap(), "bob".parse().unwrap(), U128(10));
+}
+
+mod balance_cache {
+    use near_sdk::{env, near_bindgen, AccountId};
+    use near_sdk_contract_tools::{
+        hook::{ActionOrigin, Hook},
+        standard::nep141::{cache::*, *},
+        Nep141,
+    };
+
+    #[derive(Nep141)]
+    #[nep141(transfer_hook = "BalanceCheckHook")]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    struct BalanceCheckHook;
+
+    impl Hook<FungibleToken, Nep141Transfer<'_>> for BalanceCheckHook {
+        fn hook<R>(
+            contract: &mut FungibleToken,
+            args: &Nep141Transfer,
+            f: impl FnOnce(&mut FungibleToken) -> R,
+        ) -> R {
+            assert!(is_balance_cache_active());
+            let receiver_before = contract.balance_of(args.receiver_id);
+            let r = f(contract);
+            assert_eq!(
+                contract.balance_of(args.receiver_id),
+                receiver_before + args.amount,
+            );
+            r
+        }
+    }
+
+    #[test]
+    fn transfer_hooks_see_cached_balances() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        let mut ft = FungibleToken {};
+
+        ft.deposit_unchecked(&alice, 100).unwrap();
+        ft.transfer(&Nep141Transfer {
+            sender_id: &alice,
+            receiver_id: &bob,
+            amount: 40,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+        assert!(!is_balance_cache_active());
+        assert_eq!(ft.balance_of(&alice), 60);
+        assert_eq!(ft.balance_of(&bob), 40);
+        assert_eq!(ft.holder_count(), 2);
+    }
+
+    #[test]
+    fn reads_storage_once_per_scope() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let mut ft = FungibleToken {};
+        ft.deposit_unchecked(&alice, 100).unwrap();
+
+        with_balance_cache(|| {
+            assert_eq!(ft.balance_of(&alice), 100);
+            // Bypasses the cache, so is not seen until the scope ends.
+            FungibleToken::slot_account(&alice).write(&1);
+            assert_eq!(ft.balance_of(&alice), 100);
+
+            // Writes go to storage immediately.
+            let storage_usage = env::storage_usage();
+            ft.deposit_unchecked(&"bob".parse().unwrap(), 1).unwrap();
+            assert!(env::storage_usage() > storage_usage);
+        });
+
+        assert_eq!(ft.balance_of(&alice), 1);
+    }
+}
+
+mod result_errors {
+    use near_sdk::{json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env};
+    use near_sdk_contract_tools::{hook::ActionOrigin, standard::nep141::*, Nep141};
+
+    #[derive(Nep141)]
+    #[nep141(errors = "result")]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    #[test]
+    fn transfer_returns_error() {
+        let mut ft = FungibleToken {};
+
+        let alice: near_sdk::AccountId = "alice".parse().unwrap();
+        let bob: near_sdk::AccountId = "bob".parse().unwrap();
+
+        Nep141Controller::mint(
+            &mut ft,
+            &Nep141Mint {
+                amount: 100,
+                receiver_id: &alice,
+                memo: None,
+                origin: ActionOrigin::Internal,
+            },
+        )
+        .unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(1)
+            .build());
+
+        assert_eq!(ft.ft_transfer(bob.clone(), U128(60), None), Ok(()));
+        assert!(ft
+            .ft_transfer(bob.clone(), U128(60), None)
+            .unwrap_err()
+            .contains("does not have enough balance"));
+        assert_eq!(ft.ft_balance_of(alice), U128(40));
+        assert_eq!(ft.ft_balance_of(bob), U128(60));
+    }
+}
+
+mod holder_count {
+    use near_sdk::{json_types::U64, near_bindgen, AccountId};
+    use near_sdk_contract_tools::{standard::nep141::*, Nep141};
+
+    #[derive(Nep141)]
+    #[nep141(holder_count)]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    #[test]
+    fn holder_count() {
+        let mut ft = FungibleToken {};
+
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        ft.deposit_unchecked(&alice, 100).unwrap();
+        assert_eq!(ft.holder_count(), 1);
+
+        ft.transfer_unchecked(&alice, &bob, 50).unwrap();
+        assert_eq!(ft.holder_count(), 2);
+
+        ft.transfer_unchecked(&alice, &alice, 50).unwrap();
+        assert_eq!(ft.holder_count(), 2);
+
+        ft.transfer_unchecked(&alice, &bob, 50).unwrap();
+        assert_eq!(ft.holder_count(), 1);
+
+        ft.withdraw_unchecked(&bob, 60).unwrap();
+        ft.deposit_unchecked(&alice, 0).unwrap();
+        assert_eq!(ft.ft_holder_count(), U64(1));
+
+        ft.withdraw_unchecked(&bob, 40).unwrap();
+        assert_eq!(ft.ft_holder_count(), U64(0));
+    }
+}
+
+mod zero_balances {
+    use near_sdk::{
+        borsh::{self, BorshSerialize},
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId, BorshStorageKey,
+    };
+    use near_sdk_contract_tools::{rbac::Rbac, standard::nep141::*, Nep141, Rbac};
+
+    #[derive(BorshSerialize, BorshStorageKey)]
+    enum Role {
+        Maintainer,
+    }
+
+    #[derive(Nep141, Rbac)]
+    #[nep141(remove_zero_balances, zero_balance_sweep_role = "Role::Maintainer")]
+    #[rbac(roles = "Role")]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    #[derive(Nep141)]
+    #[near_bindgen]
+    struct KeepZeroBalances {}
+
+    #[test]
+    fn zero_balances_are_removed() {
+        let mut ft = FungibleToken {};
+
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        ft.deposit_unchecked(&alice, 100).unwrap();
+        ft.transfer_unchecked(&alice, &bob, 100).unwrap();
+        assert!(!FungibleToken::slot_account(&alice).exists());
+        assert_eq!(ft.balance_of(&alice), 0);
+
+        ft.withdraw_unchecked(&bob, 100).unwrap();
+        assert!(!FungibleToken::slot_account(&bob).exists());
+        assert_eq!(ft.total_supply(), 0);
+    }
+
+    #[test]
+    fn keep_zero_balances() {
+        let mut ft = KeepZeroBalances {};
+
+        let alice: AccountId = "alice".parse().unwrap();
+
+        ft.deposit_unchecked(&alice, 100).unwrap();
+        ft.withdraw_unchecked(&alice, 100).unwrap();
+        assert_eq!(KeepZeroBalances::slot_account(&alice).read(), Some(0));
+    }
+
+    #[test]
+    fn sweep_zero_balances() {
+        let mut ft = FungibleToken {};
+
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        let carol: AccountId = "carol".parse().unwrap();
+
+        FungibleToken::slot_account(&alice).write(&0);
+        ft.deposit_unchecked(&bob, 10).unwrap();
+        ft.add_role("maintainer".parse().unwrap(), &Role::Maintainer);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("maintainer".parse().unwrap())
+            .attached_deposit(1)
+            .build());
+        assert_eq!(
+            ft.ft_sweep_zero_balances(vec![alice.clone(), bob.clone(), carol]),
+            1,
+        );
+        assert!(!FungibleToken::slot_account(&alice).exists());
+        assert_eq!(ft.balance_of(&bob), 10);
+    }
+
+    #[test]
+    #[should_panic = "Unauthorized role"]
+    fn sweep_zero_balances_unauthorized() {
+        let mut ft = FungibleToken {};
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("alice".parse().unwrap())
+            .attached_deposit(1)
+            .build());
+        ft.ft_sweep_zero_balances(vec!["alice".parse().unwrap()]);
+    }
+}
+
+mod transfer_check {
+    use near_sdk::{json_types::U128, near_bindgen, AccountId};
+    use near_sdk_contract_tools::{hook::ActionOrigin, standard::nep141::*, Nep141};
+
+    #[derive(Nep141)]
+    #[nep141(transfer_check)]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    #[test]
+    fn transfer_check() {
+        let mut ft = FungibleToken {};
+
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        ft.deposit_unchecked(&alice, 100).unwrap();
+        // Only reachable if the total supply is inconsistent.
+        FungibleToken::slot_account(&bob).write(&(u128::MAX - 50));
+
+        let transfer = |amount| Nep141Transfer {
+            sender_id: &alice,
+            receiver_id: &bob,
+            amount,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        };
+
+        ft.can_transfer(&transfer(50)).unwrap();
+        assert!(matches!(
+            ft.can_transfer(&transfer(101)),
+            Err(TransferError::SenderBalanceUnderflow(_)),
+        ));
+        assert!(matches!(
+            ft.can_transfer(&transfer(51)),
+            Err(TransferError::ReceiverBalanceOverflow(_)),
+        ));
+        assert_eq!(ft.balance_of(&alice), 100);
+
+        assert_eq!(
+            ft.ft_transfer_check(alice.clone(), alice.clone(), U128(100)),
+            None,
+        );
+        assert_eq!(
+            ft.ft_transfer_check(alice.clone(), bob.clone(), U128(101)),
+            Some(ft.can_transfer(&transfer(101)).unwrap_err().to_string()),
+        );
+        assert_eq!(ft.ft_transfer_check(bob, alice, U128(10)), None);
+    }
+}
+
+mod allowance_views {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        json_types::U128,
+        near_bindgen,
+        store::UnorderedMap,
+        AccountId, PanicOnDefault,
+    };
+    use near_sdk_contract_tools::{
+        standard::nep141::allowance::{Allowance, Nep141AllowanceViews, Nep141Allowances},
+        Nep141,
+    };
+
+    #[derive(Nep141, BorshSerialize, BorshDeserialize, PanicOnDefault)]
+    #[nep141(allowance_views)]
+    #[near_bindgen]
+    struct FungibleToken {
+        allowances: UnorderedMap<(AccountId, AccountId), u128>,
+    }
+
+    impl Nep141Allowances for FungibleToken {
+        fn allowance(&self, owner_id: &AccountId, spender_id: &AccountId) -> u128 {
+            self.allowances
+                .get(&(owner_id.clone(), spender_id.clone()))
+                .copied()
+                .unwrap_or(0)
+        }
+
+        fn allowances_for(&self, owner_id: &AccountId, offset: u32, limit: u32) -> Vec<Allowance> {
+            self.allowances
+                .iter()
+                .filter(|((owner, _), _)| owner == owner_id)
+                .skip(offset as usize)
+                .take(limit as usize)
+                .map(|((_, spender_id), amount)| Allowance {
+                    spender_id: spender_id.clone(),
+                    amount: U128(*amount),
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn allowance_views() {
+        let mut ft = FungibleToken {
+            allowances: UnorderedMap::new(b"a"),
+        };
+
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        let charlie: AccountId = "charlie".parse().unwrap();
+
+        ft.allowances.insert((alice.clone(), bob.clone()), 10);
+        ft.allowances.insert((alice.clone(), charlie.clone()), 20);
+
+        assert_eq!(ft.ft_allowance(alice.clone(), bob.clone()), U128(10));
+        assert_eq!(ft.ft_allowance(bob.clone(), alice.clone()), U128(0));
+
+        assert_eq!(
+            ft.ft_allowances_for(alice.clone(), None, None),
+            vec![
+                Allowance {
+                    spender_id: bob,
+                    amount: U128(10),
+                },
+                Allowance {
+                    spender_id: charlie.clone(),
+                    amount: U128(20),
+                },
+            ],
+        );
+        assert_eq!(
+            ft.ft_allowances_for(alice, Some(1), Some(1)),
+            vec![Allowance {
+                spender_id: charlie,
+                amount: U128(20),
+            }],
+        );
+    }
+}
+
+mod owned_transfers {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        log, near_bindgen, store, AccountId,
+    };
+    use near_sdk_contract_tools::{
+        hook::{ActionOrigin, Hook},
+        standard::nep141::*,
+        Nep141,
+    };
+
+    use crate::macros::logs_without_debug;
+
+    struct LogTransfer;
+
+    impl<C, T: Nep141TransferAction> Hook<C, T> for LogTransfer {
+        fn hook<R>(contract: &mut C, args: &T, f: impl FnOnce(&mut C) -> R) -> R {
+            log!(
+                "{} -> {}: {}",
+                args.sender_id(),
+                args.receiver_id(),
+                args.amount(),
+            );
+            f(contract)
+        }
+    }
+
+    #[derive(Nep141, BorshSerialize, BorshDeserialize)]
+    #[nep141(transfer_hook = "LogTransfer")]
+    #[near_bindgen]
+    struct FungibleToken {
+        queue: store::Vector<Nep141TransferOwned>,
+    }
+
+    impl FungibleToken {
+        fn execute_queue(&mut self) {
+            let queue = self.queue.drain(..).collect::<Vec<_>>();
+            for transfer in queue {
+                self.transfer(&transfer.as_transfer()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn queue_and_execute() {
+        let mut ft = FungibleToken {
+            queue: store::Vector::new(b"q"),
+        };
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        ft.deposit_unchecked(&alice, 100).unwrap();
+
+        let transfer = Nep141Transfer {
+            sender_id: &alice,
+            receiver_id: &bob,
+            amount: 30,
+            memo: Some("deferred"),
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        };
+        let owned = Nep141TransferOwned::from(&transfer);
+        assert_eq!(Nep141Transfer::from(&owned), transfer);
+
+        ft.queue.push(owned.clone());
+        ft.queue.push(owned);
+        assert_eq!(ft.balance_of(&bob), 0);
+
+        ft.execute_queue();
+
+        assert!(ft.queue.is_empty());
+        assert_eq!(ft.balance_of(&alice), 40);
+        assert_eq!(ft.balance_of(&bob), 60);
+        let hook_logs = logs_without_debug()
+            .into_iter()
+            .filter(|log| !log.starts_with("EVENT_JSON:"))
+            .collect::<Vec<_>>();
+        assert_eq!(hook_logs, vec!["alice -> bob: 30", "alice -> bob: 30"]);
+    }
+}
+
+mod pending_transfers {
+    use near_sdk::{
+        env,
+        json_types::{U128, U64},
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId,
+    };
+    use near_sdk_contract_tools::{
+        hook::ActionOrigin,
+        standard::nep141::{pending::*, *},
+        Nep141,
+    };
+
+    #[derive(Nep141)]
+    #[nep141(pending_transfers)]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    fn context(predecessor: &AccountId, timestamp: u64, deposit: u128) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor.clone())
+            .block_timestamp(timestamp)
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    #[test]
+    fn claim() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        context(&alice, 0, near_sdk::ONE_NEAR);
+        let mut ft = FungibleToken {};
+        ft.deposit_unchecked(&alice, 100).unwrap();
+
+        let id = ft.ft_transfer_escrowed(bob.clone(), U128(40), U64(1_000), None);
+        let escrow_id = env::current_account_id();
+
+        assert_eq!(ft.balance_of(&alice), 60);
+        assert_eq!(ft.balance_of(&escrow_id), 40);
+        assert_eq!(ft.balance_of(&bob), 0);
+        assert_eq!(
+            ft.ft_pending_transfer(id),
+            Some(PendingTransfer {
+                sender_id: alice.clone(),
+                receiver_id: bob.clone(),
+                amount: U128(40),
+                expiry: U64(1_000),
+                memo: None,
+            }),
+        );
+
+        context(&alice, 10, 1);
+        assert!(matches!(
+            ft.claim_pending_transfer(id.0, &alice, ActionOrigin::Internal),
+            Err(PendingTransferError::NotReceiver { .. }),
+        ));
+
+        context(&bob, 10, 1);
+        assert_eq!(ft.ft_pending_claim(id), U128(40));
+
+        assert_eq!(ft.balance_of(&escrow_id), 0);
+        assert_eq!(ft.balance_of(&bob), 40);
+        assert_eq!(ft.ft_pending_transfer(id), None);
+        assert!(matches!(
+            ft.claim_pending_transfer(id.0, &bob, ActionOrigin::Internal),
+            Err(PendingTransferError::NotFound(_)),
+        ));
+    }
+
+    #[test]
+    fn sweep_expired() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        let charlie: AccountId = "charlie".parse().unwrap();
+
+        context(&alice, 0, near_sdk::ONE_NEAR);
+        let mut ft = FungibleToken {};
+        ft.deposit_unchecked(&alice, 100).unwrap();
+
+        let first = ft.ft_transfer_escrowed(bob.clone(), U128(10), U64(1_000), None);
+        let second = ft.ft_transfer_escrowed(bob.clone(), U128(20), U64(2_000), None);
+
+        context(&charlie, 1_000, 0);
+        assert_eq!(ft.ft_pending_sweep(vec![first, second, U64(99)]), [first],);
+        assert_eq!(ft.balance_of(&alice), 80);
+
+        context(&bob, 1_500, 1);
+        assert!(matches!(
+            ft.claim_pending_transfer(first.0, &bob, ActionOrigin::Internal),
+            Err(PendingTransferError::NotFound(_)),
+        ));
+
+        context(&bob, 2_000, 1);
+        assert!(matches!(
+            ft.claim_pending_transfer(second.0, &bob, ActionOrigin::Internal),
+            Err(PendingTransferError::Expired(_)),
+        ));
+
+        assert_eq!(ft.ft_pending_sweep(vec![second]), [second]);
+        assert_eq!(ft.balance_of(&alice), 100);
+        assert_eq!(ft.balance_of(&env::current_account_id()), 0);
+    }
+
+    #[test]
+    #[should_panic = "is not in the future"]
+    fn expiry_in_past() {
+        let alice: AccountId = "alice".parse().unwrap();
+
+        context(&alice, 1_000, near_sdk::ONE_NEAR);
+        let mut ft = FungibleToken {};
+        ft.deposit_unchecked(&alice, 100).unwrap();
+
+        ft.ft_transfer_escrowed("bob".parse().unwrap(), U128(10), U64(1_000), None);
+    }
+}
+
+mod dust {
+    use near_sdk::{
+        borsh::{self, BorshSerialize},
+        json_types::U128,
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId, BorshStorageKey,
+    };
+    use near_sdk_contract_tools::{
+        rbac::Rbac,
+        standard::nep141::{dust::*, *},
+        Nep141, Rbac,
+    };
+
+    #[derive(BorshSerialize, BorshStorageKey)]
+    enum Role {
+        Maintainer,
+    }
+
+    #[derive(Nep141, Rbac)]
+    #[nep141(dust_role = "Role::Maintainer", transfer_check)]
+    #[rbac(roles = "Role")]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    fn context(predecessor: &AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor.clone())
+            .attached_deposit(1)
+            .build());
+    }
+
+    fn setup() -> FungibleToken {
+        let mut ft = FungibleToken {};
+        ft.add_role("maintainer".parse().unwrap(), &Role::Maintainer);
+        ft.deposit_unchecked(&"alice".parse().unwrap(), 100)
+            .unwrap();
+        ft.deposit_unchecked(&"bob".parse().unwrap(), 5).unwrap();
+
+        context(&"maintainer".parse().unwrap());
+        ft.ft_set_min_transfer(U128(10));
+        ft
+    }
+
+    #[test]
+    fn min_transfer() {
+        let mut ft = setup();
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        assert_eq!(ft.ft_min_transfer(), U128(10));
+        assert_eq!(
+            ft.check_transfer_amount(9),
+            Err(DustTransferError {
+                amount: 9,
+                minimum: 10,
+            }),
+        );
+
+        context(&alice);
+        ft.ft_transfer(bob.clone(), U128(10), None);
+        assert_eq!(ft.ft_balance_of(bob), U128(15));
+
+        context(&"maintainer".parse().unwrap());
+        ft.ft_set_min_transfer(U128(0));
+        assert_eq!(ft.ft_min_transfer(), U128(0));
+        assert!(ft.check_transfer_amount(1).is_ok());
+    }
+
+    #[test]
+    fn transfer_check_rejects_dust() {
+        let ft = setup();
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        assert_eq!(
+            ft.ft_transfer_check(alice.clone(), bob.clone(), U128(9)),
+            Some("Transfer amount 9 is below the minimum of 10".to_string()),
+        );
+        assert_eq!(ft.ft_transfer_check(alice, bob, U128(10)), None);
+    }
+
+    #[test]
+    #[should_panic = "Transfer amount 9 is below the minimum of 10"]
+    fn dust_transfer() {
+        let mut ft = setup();
+
+        context(&"alice".parse().unwrap());
+        ft.ft_transfer("bob".parse().unwrap(), U128(9), None);
+    }
+
+    #[test]
+    fn sweep() {
+        let mut ft = setup();
+        let treasury: AccountId = "treasury".parse().unwrap();
+        ft.deposit_unchecked(&treasury, 1).unwrap();
+
+        assert_eq!(
+            ft.ft_sweep_dust(
+                vec![
+                    "alice".parse().unwrap(),
+                    "bob".parse().unwrap(),
+                    "carol".parse().unwrap(),
+                    treasury.clone(),
+                ],
+                treasury.clone(),
+            ),
+            U128(5),
+        );
+        assert_eq!(ft.ft_balance_of("alice".parse().unwrap()), U128(100));
+        assert_eq!(ft.ft_balance_of("bob".parse().unwrap()), U128(0));
+        assert_eq!(ft.ft_balance_of(treasury), U128(6));
+    }
+
+    #[test]
+    #[should_panic = "Unauthorized role"]
+    fn sweep_requires_role() {
+        let mut ft = setup();
+
+        context(&"alice".parse().unwrap());
+        ft.ft_sweep_dust(vec!["bob".parse().unwrap()], "alice".parse().unwrap());
+    }
+}
+
+mod batch {
+    use near_sdk::{
+        borsh::{self, BorshSerialize},
+        json_types::U128,
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId, BorshStorageKey,
+    };
+    use near_sdk_contract_tools::{
+        hook::ActionOrigin, rbac::Rbac, standard::nep141::*, Nep141, Rbac,
+    };
+
+    use crate::macros::logs_without_debug;
+
+    #[derive(BorshSerialize, BorshStorageKey)]
+    enum Role {
+        Minter,
+        Burner,
+    }
+
+    #[derive(Nep141, Rbac)]
+    #[nep141(batch_mint_role = "Role::Minter", batch_burn_role = "Role::Burner")]
+    #[rbac(roles = "Role")]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    fn context(predecessor: &str) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor.parse().unwrap())
+            .attached_deposit(1)
+            .build());
+    }
+
+    fn setup() -> FungibleToken {
+        let mut ft = FungibleToken {};
+        ft.add_role("minter".parse().unwrap(), &Role::Minter);
+        ft.add_role("burner".parse().unwrap(), &Role::Burner);
+        ft
+    }
+
+    fn account(account_id: &str) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    #[test]
+    fn batch_mint_and_burn() {
+        let mut ft = setup();
+
+        context("minter");
+        ft.ft_batch_mint(
+            vec![
+                (account("alice"), U128(100)),
+                (account("bob"), U128(50)),
+                (account("alice"), U128(1)),
+            ],
+            Some("genesis".to_string()),
+        );
+
+        assert_eq!(ft.ft_total_supply(), U128(151));
+        assert_eq!(ft.ft_balance_of(account("alice")), U128(101));
+        assert_eq!(ft.ft_balance_of(account("bob")), U128(50));
+        assert_eq!(
+            logs_without_debug(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"alice","amount":"100","memo":"genesis"},{"owner_id":"bob","amount":"50","memo":"genesis"},{"owner_id":"alice","amount":"1","memo":"genesis"}]}"#,
+            ],
+        );
+
+        context("burner");
+        ft.ft_batch_burn(
+            vec![(account("alice"), U128(1)), (account("bob"), U128(50))],
+            None,
+        );
+
+        assert_eq!(ft.ft_total_supply(), U128(100));
+        assert_eq!(ft.ft_balance_of(account("bob")), U128(0));
+        assert_eq!(
+            logs_without_debug(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"alice","amount":"1"},{"owner_id":"bob","amount":"50"}]}"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn batch_total_supply_overflow() {
+        let mut ft = setup();
+        ft.deposit_unchecked(&account("alice"), 1).unwrap();
+
+        assert!(matches!(
+            ft.mint_batch(
+                &[(account("bob"), 1), (account("carol"), u128::MAX)],
+                None,
+                ActionOrigin::Internal,
+            ),
+            Err(DepositError::TotalSupplyOverflow(_)),
+        ));
+        assert_eq!(ft.total_supply(), 1);
+        assert_eq!(ft.balance_of(&account("bob")), 0);
+
+        assert!(matches!(
+            ft.burn_batch(
+                &[(account("alice"), 1), (account("alice"), 1)],
+                None,
+                ActionOrigin::Internal,
+            ),
+            Err(WithdrawError::TotalSupplyUnderflow(_)),
+        ));
+        assert_eq!(ft.balance_of(&account("alice")), 1);
+    }
+
+    #[test]
+    #[should_panic = "The account bob does not have enough balance to withdraw 1 (current balance: 0)."]
+    fn batch_burn_insufficient_balance() {
+        let mut ft = setup();
+        ft.deposit_unchecked(&account("alice"), 10).unwrap();
+
+        context("burner");
+        ft.ft_batch_burn(vec![(account("bob"), U128(1))], None);
+    }
+
+    #[test]
+    #[should_panic = "Unauthorized role"]
+    fn batch_mint_unauthorized() {
+        let mut ft = setup();
+
+        context("burner");
+        ft.ft_batch_mint(vec![(account("alice"), U128(1))], None);
+    }
+}
+
+mod rollback {
+    use near_sdk::{near_bindgen, AccountId};
+    use near_sdk_contract_tools::{
+        hook::{ActionOrigin, Hook},
+        slot::Slot,
+        standard::nep141::*,
+        Nep141,
+    };
+
+    struct FeeHook;
+
+    impl FeeHook {
+        fn slot_fees() -> Slot<u128> {
+            Slot::new(b"fees".as_slice())
+        }
+    }
+
+    impl<C> Hook<C, Nep141Transfer<'_>> for FeeHook {
+        fn hook<R>(
+            contract: &mut C,
+            transfer: &Nep141Transfer<'_>,
+            f: impl FnOnce(&mut C) -> R,
+        ) -> R {
+            let mut fees = Self::slot_fees();
+            fees.write(&(fees.read().unwrap_or(0) + transfer.amount / 10));
+            f(contract)
+        }
+    }
+
+    #[derive(Nep141)]
+    #[nep141(transfer_hook = "FeeHook")]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    #[test]
+    fn failed_transfer_rolls_back_hook_writes() {
+        let mut ft = FungibleToken {};
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        ft.deposit_unchecked(&alice, 100).unwrap();
+
+        let transfer = |amount| Nep141Transfer {
+            sender_id: &alice,
+            receiver_id: &bob,
+            amount,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        };
+
+        ft.transfer(&transfer(50)).unwrap();
+        assert_eq!(FeeHook::slot_fees().read(), Some(5));
+
+        assert!(matches!(
+            ft.transfer(&transfer(60)),
+            Err(TransferError::SenderBalanceUnderflow(_)),
+        ));
+        assert_eq!(FeeHook::slot_fees().read(), Some(5));
+        assert_eq!(ft.balance_of(&alice), 50);
+        assert_eq!(ft.balance_of(&bob), 50);
+    }
+}
+
+mod commitment {
+    use near_sdk::{near_bindgen, AccountId};
+    use near_sdk_contract_tools::{
+        hook::ActionOrigin,
+        standard::nep141::{commitment::*, *},
+        Nep141,
+    };
+
+    #[derive(Nep141)]
+    #[nep141(balance_commitment)]
+    #[near_bindgen]
+    struct FungibleToken {}
+
+    #[test]
+    fn proofs_follow_balances() {
+        let mut ft = FungibleToken {};
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        let empty = ft.ft_balances_commitment();
+        assert_eq!(empty.leaf_count, 0);
+        assert_eq!(empty.depth, DEFAULT_COMMITMENT_TREE_DEPTH);
+        assert_eq!(ft.ft_balance_proof(alice.clone()), None);
+
+        ft.mint(&Nep141Mint {
+            amount: 100,
+            receiver_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+        ft.transfer(&Nep141Transfer {
+            sender_id: &alice,
+            receiver_id: &bob,
+            amount: 30,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+        let commitment = ft.ft_balances_commitment();
+        assert_eq!(commitment.leaf_count, 2);
+        assert_ne!(commitment.root, empty.root);
+
+        let root = commitment.root.into();
+        let alice_proof = ft.ft_balance_proof(alice.clone()).unwrap();
+        let bob_proof = ft.ft_balance_proof(bob.clone()).unwrap();
+        assert_eq!(alice_proof.balance.0, 70);
+        assert_eq!(alice_proof.leaf_index, 0);
+        assert_eq!(bob_proof.balance.0, 30);
+        assert_eq!(bob_proof.leaf_index, 1);
+        assert!(alice_proof.verify(&root));
+        assert!(bob_proof.verify(&root));
+
+        let forged = BalanceProof {
+            balance: 1000.into(),
+            ..alice_proof.clone()
+        };
+        assert!(!forged.verify(&root));
+
+        ft.burn(&Nep141Burn {
+            amount: 70,
+            owner_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+        let root = ft.ft_balances_commitment().root.into();
+        assert!(!alice_proof.verify(&root));
+        let alice_proof = ft.ft_balance_proof(alice).unwrap();
+        assert_eq!(alice_proof.balance.0, 0);
+        assert!(alice_proof.verify(&root));
+        assert!(ft.ft_balance_proof(bob).unwrap().verify(&root));
+    }
+
+    #[test]
+    fn commit_existing_balances() {
+        let mut ft = FungibleToken {};
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        ft.deposit_unchecked(&alice, 50).unwrap();
+        assert_eq!(ft.ft_balance_proof(alice.clone()), None);
+
+        ft.commit_balances(&[alice.clone(), bob.clone()]).unwrap();
+
+        assert_eq!(ft.ft_balances_commitment().leaf_count, 1);
+        assert_eq!(ft.ft_balance_proof(bob), None);
+
+        let root = ft.ft_balances_commitment().root.into();
+        let proof = ft.ft_balance_proof(alice).unwrap();
+        assert_eq!(proof.balance.0, 50);
+        assert!(proof.verify(&root));
+    }
+}
@@ -0,0 +1,106 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    json_types::U128,
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools::{
+    pause::Pause,
+    rbac::Rbac,
+    standard::nep141::{bridge::*, Nep141Controller},
+    Nep141, Nep141Bridge, Pause, Rbac,
+};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum Role {
+    Bridge,
+}
+
+#[derive(Nep141, Nep141Bridge, Pause, Rbac)]
+#[nep141_bridge(role = "Role::Bridge", pausable)]
+#[rbac(roles = "Role")]
+#[near_bindgen]
+struct BridgedToken {}
+
+fn bridge() -> AccountId {
+    "bridge".parse().unwrap()
+}
+
+fn alice() -> AccountId {
+    "alice".parse().unwrap()
+}
+
+fn context(predecessor: AccountId, epoch_height: u64) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor)
+        .epoch_height(epoch_height)
+        .build());
+}
+
+fn setup() -> BridgedToken {
+    let mut c = BridgedToken {};
+    c.add_role(bridge(), &Role::Bridge);
+    c.set_bridge_caps(BridgeCaps {
+        mint: Some(U128(100)),
+        burn: None,
+    });
+    c
+}
+
+#[test]
+fn mint_and_burn() {
+    let mut c = setup();
+    context(bridge(), 1);
+
+    c.ft_bridge_mint(alice(), U128(60), "0xabc".to_string(), None);
+    c.ft_bridge_burn(alice(), U128(20), "0xdef".to_string(), None);
+
+    assert_eq!(c.balance_of(&alice()), 40);
+    assert_eq!(
+        c.ft_bridge_epoch_volume(),
+        EpochVolume {
+            epoch_height: 1,
+            minted: U128(60),
+            burned: U128(20),
+        },
+    );
+}
+
+#[test]
+fn cap_resets_each_epoch() {
+    let mut c = setup();
+    context(bridge(), 1);
+    c.ft_bridge_mint(alice(), U128(100), "0x1".to_string(), None);
+
+    let err = c
+        .bridge_mint(&Nep141BridgeMint {
+            amount: 1,
+            receiver_id: &alice(),
+            foreign_tx_hash: "0x2",
+            memo: None,
+        })
+        .unwrap_err();
+    assert!(matches!(err, BridgeMintError::CapExceeded(_)));
+
+    context(bridge(), 2);
+    c.ft_bridge_mint(alice(), U128(100), "0x3".to_string(), None);
+    assert_eq!(c.balance_of(&alice()), 200);
+}
+
+#[test]
+#[should_panic = "Unauthorized role"]
+fn only_bridge_role() {
+    let mut c = setup();
+    context(alice(), 1);
+    c.ft_bridge_mint(alice(), U128(1), "0x1".to_string(), None);
+}
+
+#[test]
+#[should_panic = "Disallowed while contract is paused"]
+fn not_while_paused() {
+    let mut c = setup();
+    context(bridge(), 1);
+    Pause::pause(&mut c);
+    c.ft_bridge_mint(alice(), U128(1), "0x1".to_string(), None);
+}
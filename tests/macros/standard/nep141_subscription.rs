@@ -0,0 +1,144 @@
+use near_sdk::{
+    json_types::{U128, U64},
+    near_bindgen,
+    test_utils::{get_logs, VMContextBuilder},
+    testing_env, AccountId, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    hook::{ActionOrigin, Hook},
+    standard::nep141::{subscription::*, Nep141Controller, Nep141Mint, Nep141Transfer},
+    Nep141, Nep141Subscription,
+};
+
+const PERIOD: u64 = 1_000;
+
+#[derive(Nep141, Nep141Subscription)]
+#[nep141(transfer_hook = "TransferHook")]
+#[near_bindgen]
+struct Token {
+    transfers: u32,
+}
+
+struct TransferHook;
+
+impl Hook<Token, Nep141Transfer<'_>> for TransferHook {
+    fn hook<R>(
+        contract: &mut Token,
+        _args: &Nep141Transfer<'_>,
+        f: impl FnOnce(&mut Token) -> R,
+    ) -> R {
+        contract.transfers += 1;
+        f(contract)
+    }
+}
+
+fn alice() -> AccountId {
+    "alice".parse().unwrap()
+}
+
+fn merchant() -> AccountId {
+    "merchant".parse().unwrap()
+}
+
+fn context(predecessor: AccountId, deposit: u128, timestamp: u64) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor)
+        .attached_deposit(deposit)
+        .block_timestamp(timestamp)
+        .build());
+}
+
+fn setup() -> Token {
+    context(alice(), ONE_NEAR, 0);
+
+    let mut c = Token { transfers: 0 };
+    c.mint(&Nep141Mint {
+        amount: 1_000,
+        receiver_id: &alice(),
+        memo: None,
+        origin: ActionOrigin::Internal,
+    })
+    .unwrap();
+    c.ft_subscription_authorize(merchant(), U128(100), U64(PERIOD));
+    c
+}
+
+#[test]
+fn charge_within_period() {
+    let mut c = setup();
+
+    context(merchant(), 1, 10);
+    assert_eq!(
+        c.ft_subscription_charge(alice(), Some(U128(30)), None),
+        U128(30)
+    );
+    assert_eq!(c.ft_subscription_remaining(alice(), merchant()), U128(70));
+    assert_eq!(
+        get_logs().last().unwrap(),
+        r#"EVENT_JSON:{"standard":"x-nep141-subscription","version":"1.0.0","event":"subscription_charge","data":{"account_id":"alice","merchant_id":"merchant","amount":"30","period_index":"0"}}"#,
+    );
+
+    // Charges the remainder of the period.
+    assert_eq!(c.ft_subscription_charge(alice(), None, None), U128(70));
+
+    assert_eq!(c.balance_of(&alice()), 900);
+    assert_eq!(c.balance_of(&merchant()), 100);
+    assert_eq!(c.transfers, 2);
+}
+
+#[test]
+fn cap_resets_each_period() {
+    let mut c = setup();
+
+    context(merchant(), 1, 10);
+    c.ft_subscription_charge(alice(), Some(U128(100)), None);
+
+    let err = c
+        .charge_subscription(&Nep141SubscriptionCharge {
+            account_id: &alice(),
+            merchant_id: &merchant(),
+            amount: 1,
+            memo: None,
+        })
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        SubscriptionChargeError::CapExceeded(PeriodCapExceededError {
+            charged: 100,
+            cap: 100,
+            amount: 1,
+        }),
+    ));
+
+    context(merchant(), 1, PERIOD + 10);
+    assert_eq!(c.ft_subscription_remaining(alice(), merchant()), U128(100));
+    c.ft_subscription_charge(alice(), Some(U128(100)), None);
+
+    assert_eq!(c.balance_of(&merchant()), 200);
+    assert_eq!(
+        c.ft_subscription(alice(), merchant()).unwrap().period_index,
+        U64(1),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Account alice has no subscription with merchant merchant")]
+fn revoked_subscription_cannot_be_charged() {
+    let mut c = setup();
+
+    context(alice(), 1, 10);
+    assert!(c.ft_subscription_revoke(merchant()));
+    assert!(c.ft_subscription(alice(), merchant()).is_none());
+
+    context(merchant(), 1, 20);
+    c.ft_subscription_charge(alice(), Some(U128(1)), None);
+}
+
+#[test]
+#[should_panic(expected = "Subscription period must be greater than zero")]
+fn zero_period() {
+    let mut c = setup();
+
+    context(alice(), ONE_NEAR, 10);
+    c.ft_subscription_authorize(merchant(), U128(100), U64(0));
+}
@@ -6,10 +6,18 @@ use near_sdk::{
     store::LookupMap,
     AccountId, PanicOnDefault,
 };
-use near_sdk_contract_tools::{hook::Hook, standard::nep145::*, Nep145};
+use near_sdk_contract_tools::{
+    hook::Hook, owner::*, standard::nep145::*, treasury::*, Nep145, Owner, Treasury,
+};
 
-#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Nep145)]
-#[nep145(force_unregister_hook = "ForceUnregisterHook")]
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Nep145, Owner, Treasury)]
+#[nep145(
+    force_unregister_hook = "ForceUnregisterHook",
+    withdraw_to,
+    owner_sweep,
+    treasury,
+    set_bounds
+)]
 #[near_bindgen]
 pub struct Contract {
     pub storage: LookupMap<AccountId, Vec<u64>>,
@@ -46,6 +54,8 @@ impl Contract {
             },
         );
 
+        Owner::init(&mut contract, &"owner.near".parse().unwrap());
+
         contract
     }
 
@@ -115,4 +125,307 @@ mod tests {
         assert_eq!(second.total.0, ONE_NEAR);
         assert_eq!(second.available.0, first.available.0 - 8 * 1000 * byte_cost);
     }
+
+    #[test]
+    fn storage_events() {
+        let mut contract = Contract::new();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(ONE_NEAR)
+            .build());
+
+        Nep145::storage_deposit(&mut contract, None, None);
+
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"x-storage","version":"1.0.0","event":"storage_deposit","data":[{{"account_id":"alice.near","amount":"{ONE_NEAR}","balance":{{"total":"{ONE_NEAR}","available":"{ONE_NEAR}"}}}}]}}"#,
+            )],
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+
+        let balance = Nep145::storage_withdraw(&mut contract, Some(U128(ONE_NEAR / 2)));
+
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"x-storage","version":"1.0.0","event":"storage_withdraw","data":[{{"account_id":"alice.near","amount":"{}","balance":{}}}]}}"#,
+                ONE_NEAR / 2,
+                near_sdk::serde_json::to_string(&balance).unwrap(),
+            )],
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+
+        assert!(Nep145::storage_unregister(&mut contract, None));
+
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"x-storage","version":"1.0.0","event":"storage_unregister","data":[{{"account_id":"alice.near","refund":"{}","force":false}}]}}"#,
+                ONE_NEAR / 2,
+            )],
+        );
+    }
+
+    #[test]
+    fn deposit_refunds() {
+        let mut contract = Contract::new();
+        Nep145Controller::set_storage_balance_bounds(
+            &mut contract,
+            &StorageBalanceBounds {
+                min: U128(ONE_NEAR),
+                max: Some(U128(2 * ONE_NEAR)),
+            },
+        );
+
+        let deposit = |contract: &mut Contract, amount: u128, registration_only: bool| {
+            testing_env!(VMContextBuilder::new()
+                .predecessor_account_id(alice())
+                .attached_deposit(amount)
+                .build());
+            let balance = Nep145::storage_deposit(contract, None, Some(registration_only));
+            let refunds = near_sdk::test_utils::get_created_receipts().len();
+            (balance.total.0, refunds)
+        };
+
+        assert_eq!(
+            deposit(&mut contract, 3 * ONE_NEAR / 2, true),
+            (ONE_NEAR, 1)
+        );
+        assert_eq!(
+            deposit(&mut contract, 3 * ONE_NEAR / 2, false),
+            (2 * ONE_NEAR, 1),
+        );
+        assert_eq!(deposit(&mut contract, ONE_NEAR, true), (2 * ONE_NEAR, 1));
+        assert_eq!(deposit(&mut contract, 0, false), (2 * ONE_NEAR, 0));
+    }
+
+    #[test]
+    fn withdraw_to_beneficiary() {
+        let mut contract = Contract::new();
+        let beneficiary: AccountId = "custodian.near".parse().unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(3 * ONE_NEAR)
+            .build());
+        Nep145::storage_deposit(&mut contract, None, None);
+
+        let withdraw_receivers = || {
+            near_sdk::test_utils::get_created_receipts()
+                .into_iter()
+                .map(|r| r.receiver_id)
+                .collect::<Vec<_>>()
+        };
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+        let balance = Nep145WithdrawTo::storage_withdraw_to(
+            &mut contract,
+            beneficiary.clone(),
+            Some(U128(ONE_NEAR)),
+        );
+        assert_eq!(balance.available.0, 2 * ONE_NEAR);
+        assert_eq!(withdraw_receivers(), vec![beneficiary.clone()]);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.near".parse().unwrap())
+            .attached_deposit(1)
+            .build());
+        let balance =
+            Nep145OwnerSweep::storage_sweep(&mut contract, alice(), beneficiary.clone(), None);
+        assert_eq!(balance.available.0, 0);
+        assert_eq!(withdraw_receivers(), vec![beneficiary]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner only")]
+    fn sweep_requires_owner() {
+        let mut contract = Contract::new();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+        Nep145OwnerSweep::storage_sweep(&mut contract, alice(), alice(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the balance available above the minimum reserve")]
+    fn withdraw_respects_reserve() {
+        let mut contract = Contract::new();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .account_balance(10 * ONE_NEAR)
+            .attached_deposit(3 * ONE_NEAR)
+            .build());
+        Nep145::storage_deposit(&mut contract, None, None);
+
+        contract.set_reserve_buffer(9 * ONE_NEAR);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .account_balance(10 * ONE_NEAR)
+            .attached_deposit(1)
+            .build());
+        Nep145::storage_withdraw(&mut contract, Some(U128(ONE_NEAR)));
+    }
+
+    #[test]
+    fn set_bounds() {
+        let mut contract = Contract::new();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.near".parse().unwrap())
+            .attached_deposit(1)
+            .build());
+        let bounds = Nep145SetBounds::storage_set_bounds(
+            &mut contract,
+            U128(ONE_NEAR),
+            Some(U128(2 * ONE_NEAR)),
+        );
+
+        assert_eq!(Nep145::storage_balance_bounds(&contract), bounds);
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"x-storage","version":"1.0.0","event":"storage_bounds_update","data":{{"updated_by":"owner.near","old_bounds":{{"min":"0","max":null}},"new_bounds":{{"min":"{ONE_NEAR}","max":"{}"}}}}}}"#,
+                2 * ONE_NEAR,
+            )],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner only")]
+    fn set_bounds_requires_owner() {
+        let mut contract = Contract::new();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+        Nep145SetBounds::storage_set_bounds(&mut contract, U128(ONE_NEAR), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid storage balance bounds")]
+    fn set_bounds_rejects_max_below_min() {
+        let mut contract = Contract::new();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.near".parse().unwrap())
+            .attached_deposit(1)
+            .build());
+        Nep145SetBounds::storage_set_bounds(&mut contract, U128(2), Some(U128(1)));
+    }
+}
+
+mod bounds_role {
+    use near_sdk::{
+        borsh::{self, BorshSerialize},
+        json_types::U128,
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId, BorshStorageKey,
+    };
+    use near_sdk_contract_tools::{rbac::Rbac, standard::nep145::*, Nep145, Rbac};
+
+    #[derive(BorshSerialize, BorshStorageKey)]
+    enum Role {
+        StorageAdmin,
+    }
+
+    #[derive(Nep145, Rbac)]
+    #[nep145(set_bounds, bounds_role = "Role::StorageAdmin")]
+    #[rbac(roles = "Role")]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn admin() -> AccountId {
+        "admin.near".parse().unwrap()
+    }
+
+    fn set_bounds_as(contract: &mut Contract, account_id: AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id)
+            .attached_deposit(1)
+            .build());
+        Nep145SetBounds::storage_set_bounds(contract, U128(100), None);
+    }
+
+    #[test]
+    fn role_may_set_bounds() {
+        let mut contract = Contract {};
+        contract.add_role(admin(), &Role::StorageAdmin);
+
+        set_bounds_as(&mut contract, admin());
+
+        assert_eq!(Nep145::storage_balance_bounds(&contract).min, U128(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized role")]
+    fn others_may_not_set_bounds() {
+        let mut contract = Contract {};
+
+        set_bounds_as(&mut contract, "alice.near".parse().unwrap());
+    }
+}
+
+mod byte_cost {
+    use near_sdk::{env, json_types::U128, near_bindgen, AccountId};
+    use near_sdk_contract_tools::{standard::nep145::*, Nep145};
+
+    #[derive(Nep145)]
+    #[nep145(byte_cost = "FixedStorageByteCost<10>")]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn accounting_uses_fixed_price() {
+        let mut contract = Contract {};
+        let alice: AccountId = "alice.near".parse().unwrap();
+
+        contract
+            .deposit_to_storage_account(&alice, U128(1_000))
+            .unwrap();
+
+        let storage_usage_start = env::storage_usage();
+        env::storage_write(b"data", &[0; 50]);
+        let used = env::storage_usage() - storage_usage_start;
+
+        contract
+            .storage_accounting(&alice, storage_usage_start)
+            .unwrap();
+        assert_eq!(
+            contract.get_storage_balance(&alice).unwrap().available,
+            U128(1_000 - 10 * u128::from(used)),
+        );
+
+        let storage_usage_start = env::storage_usage();
+        env::storage_remove(b"data");
+
+        assert_eq!(
+            contract
+                .storage_refund(&alice, storage_usage_start)
+                .unwrap(),
+            U128(10 * u128::from(used)),
+        );
+        assert_eq!(
+            contract.get_storage_balance(&alice).unwrap().available,
+            U128(1_000),
+        );
+    }
 }
@@ -1,5 +1,9 @@
 use near_sdk::{json_types::Base64VecU8, near_bindgen};
-use near_sdk_contract_tools::{standard::nep148::*, Nep148};
+use near_sdk_contract_tools::{
+    standard::nep148::*,
+    utils::reference::{hash_reference, ReferenceError},
+    Nep148,
+};
 
 #[derive(Nep148)]
 #[near_bindgen]
@@ -40,3 +44,83 @@ fn test() {
         Some(Base64VecU8::from([97, 115, 100, 102].to_vec()))
     );
 }
+
+#[test]
+fn bridged_asset() {
+    let mut ft = DerivesFTMetadata::new();
+    assert_eq!(ft.get_bridged_asset(), None);
+    assert!(ft.ft_metadata().extensions.is_empty());
+
+    let bridged_asset = BridgedAsset {
+        origin_chain: "ethereum".into(),
+        origin_address: "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".into(),
+        bridge_contract: "factory.bridge.near".parse().unwrap(),
+    };
+
+    ft.set_metadata(
+        &ft.ft_metadata()
+            .bridged_asset(bridged_asset.clone())
+            .extension("chain_id".into(), "1".into()),
+    );
+
+    assert_eq!(ft.get_bridged_asset(), Some(bridged_asset));
+
+    let json = near_sdk::serde_json::to_value(ft.ft_metadata()).unwrap();
+    assert_eq!(json["name"], "Test Fungible Token");
+    assert_eq!(json["origin_chain"], "ethereum");
+    assert_eq!(
+        json["origin_address"],
+        "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+    );
+    assert_eq!(json["bridge_contract"], "factory.bridge.near");
+    assert_eq!(json["chain_id"], "1");
+
+    let meta: FungibleTokenMetadata = near_sdk::serde_json::from_value(json).unwrap();
+    assert_eq!(meta, ft.ft_metadata());
+}
+
+#[derive(Nep148)]
+#[nep148(strict_reference)]
+#[near_bindgen]
+struct StrictReference {}
+
+fn metadata() -> FungibleTokenMetadata {
+    FungibleTokenMetadata::new("Test Fungible Token".into(), "TFT".into(), 18)
+        .reference("https://example.com/metadata.json".into())
+}
+
+#[test]
+fn reference() {
+    let content = br#"{"description":"Test Fungible Token"}"#;
+    let hash = hash_reference(content).to_vec();
+    let meta = metadata().reference_hash(hash.clone().into());
+
+    assert_eq!(meta.validate_reference(), Ok(()));
+    assert_eq!(meta.verify_reference(content), Ok(()));
+    assert!(matches!(
+        meta.verify_reference(b"{}"),
+        Err(ReferenceError::HashMismatch { .. }),
+    ));
+
+    assert_eq!(
+        metadata().validate_reference(),
+        Err(ReferenceError::MissingHash),
+    );
+    assert_eq!(
+        metadata()
+            .reference_hash(hash[..4].to_vec().into())
+            .validate_reference(),
+        Err(ReferenceError::InvalidHashLength { length: 4 }),
+    );
+
+    let mut contract = StrictReference {};
+    contract.set_metadata(&meta);
+    assert_eq!(contract.get_metadata(), meta);
+}
+
+#[test]
+#[should_panic(expected = "Invalid token metadata: `reference` requires `reference_hash`")]
+fn strict_reference_rejects_missing_hash() {
+    let mut contract = StrictReference {};
+    contract.set_metadata(&metadata());
+}
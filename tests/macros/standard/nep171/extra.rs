@@ -0,0 +1,105 @@
+use std::error::Error;
+
+use near_sdk::{env, near_bindgen, serde_json::Value, AccountId, ONE_NEAR};
+use near_sdk_contract_tools::{hook::Hook, nft::*, slot::Slot};
+
+/// A standard implemented outside the crate: numbers tokens in the order
+/// they are minted.
+struct Editions;
+
+impl Editions {
+    fn slot_count() -> Slot<u32> {
+        Slot::new(b"editions".to_vec())
+    }
+
+    fn slot_edition(token_id: &TokenId) -> Slot<u32> {
+        Self::slot_count().field(token_id.as_bytes())
+    }
+}
+
+impl<C> Hook<C, Nep171Mint<'_>> for Editions {
+    fn hook<R>(contract: &mut C, mint: &Nep171Mint<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let r = f(contract);
+        let mut count = Self::slot_count().read().unwrap_or(0);
+        for token_id in mint.token_ids {
+            count += 1;
+            Self::slot_edition(token_id).write(&count);
+        }
+        Self::slot_count().write(&count);
+        r
+    }
+}
+
+impl<C> Hook<C, Nep171Transfer<'_>> for Editions {}
+
+impl<C> Hook<C, Nep171Burn<'_>> for Editions {
+    fn hook<R>(contract: &mut C, burn: &Nep171Burn<'_>, f: impl FnOnce(&mut C) -> R) -> R {
+        let r = f(contract);
+        for token_id in burn.token_ids {
+            Self::slot_edition(token_id).remove();
+        }
+        r
+    }
+}
+
+impl<C> LoadTokenMetadata<C> for Editions {
+    fn load(
+        _contract: &C,
+        token_id: &TokenId,
+        metadata: &mut std::collections::HashMap<String, Value>,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(edition) = Self::slot_edition(token_id).read() {
+            metadata.insert("edition".to_string(), edition.into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(NonFungibleToken)]
+#[non_fungible_token(extra = "Editions")]
+#[near_bindgen]
+struct Contract {}
+
+#[derive(NonFungibleToken)]
+#[non_fungible_token(extra = "Editions", extra = "()")]
+#[near_bindgen]
+struct ContractMultipleExtras {}
+
+#[test]
+fn extra_standard() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    Nep145Controller::deposit_to_storage_account(&mut contract, &alice, ONE_NEAR.into()).unwrap();
+
+    // The storage used by the extra standard is charged to the owner.
+    let balance = contract.get_storage_balance(&alice).unwrap().available.0;
+    let storage_usage = env::storage_usage();
+    contract
+        .mint(&Nep171Mint {
+            token_ids: &["a".to_string()],
+            receiver_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+    assert_eq!(
+        balance - contract.get_storage_balance(&alice).unwrap().available.0,
+        u128::from(env::storage_usage() - storage_usage) * env::storage_byte_cost(),
+    );
+    contract
+        .mint_with_metadata("b".to_string(), alice.clone(), TokenMetadata::new())
+        .unwrap();
+
+    let token = contract.nft_token("b".to_string(), None).unwrap();
+    assert_eq!(token.extensions_metadata["edition"], 2);
+    assert!(token.extensions_metadata.contains_key("metadata"));
+    assert_eq!(Editions::slot_edition(&"a".to_string()).read(), Some(1));
+    assert!(token
+        .extensions_metadata
+        .contains_key("approved_account_ids"));
+
+    contract
+        .burn_with_metadata("b".to_string(), &alice)
+        .unwrap();
+    assert_eq!(Editions::slot_edition(&"b".to_string()).read(), None);
+}
@@ -0,0 +1,119 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, BorshStorageKey, PanicOnDefault,
+};
+use near_sdk_contract_tools::{nft::*, rbac::Rbac, Rbac};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum Role {
+    Curator,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Nep171, Nep181, Rbac)]
+#[nep171(all_hooks = "TokenEnumeration")]
+#[nep181(hide_role = "Role::Curator")]
+#[rbac(roles = "Role")]
+#[near_bindgen]
+struct Contract {}
+
+fn context(predecessor: &str) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.parse().unwrap())
+        .attached_deposit(1)
+        .build());
+}
+
+fn token_ids(tokens: Vec<Token>) -> Vec<TokenId> {
+    tokens.into_iter().map(|token| token.token_id).collect()
+}
+
+fn setup() -> Contract {
+    let mut contract = Contract {};
+    contract.add_role("curator".parse().unwrap(), &Role::Curator);
+
+    let alice: AccountId = "alice".parse().unwrap();
+    Nep171Controller::mint(
+        &mut contract,
+        &Nep171Mint {
+            token_ids: &["a".to_string(), "b".to_string()],
+            receiver_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+
+    contract
+}
+
+#[test]
+fn hide_and_reveal() {
+    let mut contract = setup();
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    context("curator");
+    contract.nft_hide_token("a".to_string());
+
+    assert!(contract.nft_is_token_hidden("a".to_string()));
+    assert_eq!(token_ids(contract.nft_tokens(None, None)), ["b"]);
+    assert_eq!(
+        token_ids(contract.nft_tokens_for_owner(alice.clone(), None, None)),
+        ["b"],
+    );
+    assert_eq!(contract.nft_total_supply().0, 1);
+    assert_eq!(
+        contract.nft_token("a".to_string(), None).unwrap().owner_id,
+        alice,
+    );
+
+    // Transfers keep the token hidden.
+    context("alice");
+    contract.nft_transfer(bob.clone(), "a".to_string(), None, None);
+    assert!(contract.nft_is_token_hidden("a".to_string()));
+    assert_eq!(contract.nft_supply_for_owner(bob.clone()).0, 0);
+
+    context("curator");
+    contract.nft_reveal_token("a".to_string());
+
+    assert!(!contract.nft_is_token_hidden("a".to_string()));
+    assert_eq!(
+        token_ids(contract.nft_tokens_for_owner(bob, None, None)),
+        ["a"]
+    );
+    assert_eq!(contract.nft_total_supply().0, 2);
+}
+
+#[test]
+fn burn_clears_hidden() {
+    let mut contract = setup();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    contract.hide_token(&"a".to_string()).unwrap();
+    Nep171Controller::burn(
+        &mut contract,
+        &Nep171Burn {
+            token_ids: &["a".to_string()],
+            owner_id: &alice,
+            authorized_id: None,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+
+    assert!(!contract.is_token_hidden(&"a".to_string()));
+    assert!(contract.hide_token(&"a".to_string()).is_err());
+    assert_eq!(token_ids(contract.nft_tokens(None, None)), ["b"]);
+}
+
+#[test]
+#[should_panic = "Unauthorized role"]
+fn hide_requires_role() {
+    let mut contract = setup();
+
+    context("alice");
+    contract.nft_hide_token("a".to_string());
+}
@@ -0,0 +1,96 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen, AccountId, PanicOnDefault,
+};
+use near_sdk_contract_tools::{hook::Hook, nft::*};
+
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Nep171, Nep181)]
+#[nep171(
+    all_hooks = "TokenEnumeration",
+    mint_hook = "Self",
+    mint_limit_per_account = "2"
+)]
+#[near_bindgen]
+pub struct Contract {
+    counts_seen: Vec<Option<u64>>,
+}
+
+impl Hook<Contract, Nep171Mint<'_>> for Contract {
+    fn hook<R>(
+        contract: &mut Contract,
+        args: &Nep171Mint<'_>,
+        f: impl FnOnce(&mut Contract) -> R,
+    ) -> R {
+        contract
+            .counts_seen
+            .push(contract.owned_token_count(args.receiver_id));
+        f(contract)
+    }
+}
+
+fn mint(contract: &mut Contract, token_ids: &[TokenId], receiver_id: &AccountId) {
+    Nep171Controller::mint(
+        contract,
+        &Nep171Mint {
+            token_ids,
+            receiver_id,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn hooks_receive_token_count() {
+    let mut contract = Contract {
+        counts_seen: vec![],
+    };
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    mint(&mut contract, &["a".to_string()], &alice);
+    mint(&mut contract, &["b".to_string()], &alice);
+    mint(&mut contract, &["c".to_string(), "d".to_string()], &bob);
+
+    assert_eq!(contract.counts_seen, vec![Some(0), Some(1), Some(0)]);
+}
+
+#[test]
+#[should_panic(expected = "Account alice may hold at most 2 tokens")]
+fn mint_over_limit() {
+    let mut contract = Contract {
+        counts_seen: vec![],
+    };
+    let alice: AccountId = "alice".parse().unwrap();
+
+    mint(&mut contract, &["a".to_string()], &alice);
+    mint(&mut contract, &["b".to_string(), "c".to_string()], &alice);
+}
+
+#[test]
+#[should_panic(expected = "Account alice may hold at most 2 tokens")]
+fn transferred_tokens_count_toward_limit() {
+    let mut contract = Contract {
+        counts_seen: vec![],
+    };
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    mint(&mut contract, &["a".to_string(), "b".to_string()], &bob);
+    Nep171Controller::external_transfer(
+        &mut contract,
+        &Nep171Transfer {
+            token_id: &"a".to_string(),
+            authorization: nep171::Nep171TransferAuthorization::Owner,
+            sender_id: &bob,
+            receiver_id: &alice,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+    mint(&mut contract, &["c".to_string(), "d".to_string()], &alice);
+}
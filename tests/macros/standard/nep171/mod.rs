@@ -6,10 +6,17 @@ use near_sdk::{
 };
 use near_sdk_contract_tools::{hook::Hook, nft::*};
 
+use crate::macros::logs_without_debug;
+
+mod extra;
+mod hidden_tokens;
 mod hooks;
 mod manual_integration;
+mod mint_limit;
 mod no_hooks;
 mod non_fungible_token;
+mod payouts;
+mod strict_metadata;
 
 #[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, PartialOrd)]
 struct TokenRecord {
@@ -48,10 +55,128 @@ fn t() {
     n.mint_with_metadata(token_id.clone(), alice, TokenMetadata::new().title("Title"))
         .unwrap();
 
-    let nft_tok = n.nft_token(token_id);
+    let nft_tok = n.nft_token(token_id, None);
     dbg!(nft_tok);
 }
 
+#[test]
+fn token_owners() {
+    let mut n = NonFungibleTokenNoHooks {
+        before_nft_transfer_balance_record: store::Vector::new(b"a"),
+        after_nft_transfer_balance_record: store::Vector::new(b"b"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    n.mint_unchecked(&["token1".to_string()], &alice);
+    n.mint_unchecked(&["token2".to_string()], &bob);
+
+    assert!(n.token_exists(&"token1".to_string()));
+    assert!(!n.token_exists(&"token3".to_string()));
+    assert_eq!(
+        n.nft_token_owners(vec![
+            "token2".to_string(),
+            "token3".to_string(),
+            "token1".to_string(),
+        ]),
+        vec![Some(bob), None, Some(alice)],
+    );
+}
+
+mod transfer_check {
+    use near_sdk::{near_bindgen, AccountId};
+    use near_sdk_contract_tools::nft::*;
+
+    #[derive(NonFungibleToken)]
+    #[non_fungible_token(transfer_check)]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn transfer_check() {
+        let mut n = Contract {};
+
+        let token_id = "token1".to_string();
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        let charlie: AccountId = "charlie".parse().unwrap();
+
+        assert_eq!(
+            n.nft_transfer_check(alice.clone(), bob.clone(), token_id.clone(), None),
+            Some(format!("Token `{token_id}` does not exist")),
+        );
+
+        n.mint_unchecked(std::array::from_ref(&token_id), &alice);
+        let approval_id = n.approve_unchecked(&token_id, &charlie);
+
+        assert_eq!(
+            n.nft_transfer_check(alice.clone(), bob.clone(), token_id.clone(), None),
+            None,
+        );
+        assert_eq!(
+            n.nft_transfer_check(
+                charlie.clone(),
+                bob.clone(),
+                token_id.clone(),
+                Some(approval_id)
+            ),
+            None,
+        );
+        assert!(n
+            .nft_transfer_check(charlie.clone(), bob.clone(), token_id.clone(), None)
+            .is_some());
+        assert!(n
+            .nft_transfer_check(bob.clone(), charlie, token_id.clone(), Some(approval_id))
+            .is_some());
+        assert!(n
+            .nft_transfer_check(alice.clone(), alice.clone(), token_id.clone(), None)
+            .is_some());
+
+        assert_eq!(n.token_owner(&token_id), Some(alice));
+    }
+}
+
+mod no_approval_events {
+    use near_sdk::{near_bindgen, AccountId};
+    use near_sdk_contract_tools::nft::*;
+
+    use crate::macros::logs_without_debug;
+
+    #[derive(Nep171, Nep178)]
+    #[nep171(check_external_transfer = "TokenApprovals")]
+    #[nep178(no_events)]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn no_approval_events() {
+        let mut n = Contract {};
+
+        let token_id = "token1".to_string();
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        n.mint_unchecked(std::array::from_ref(&token_id), &alice);
+
+        let approve = Nep178Approve {
+            token_id: &token_id,
+            current_owner_id: &alice,
+            account_id: &bob,
+            origin: ActionOrigin::Internal,
+        };
+        n.approve(&approve).unwrap();
+        n.revoke_all(&Nep178RevokeAll {
+            token_id: &token_id,
+            current_owner_id: &alice,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+        assert!(logs_without_debug().is_empty());
+    }
+}
+
 #[derive(Nep171, BorshDeserialize, BorshSerialize)]
 #[nep171(transfer_hook = "Self")]
 #[near_bindgen]
@@ -66,12 +191,16 @@ impl Hook<NonFungibleToken, Nep171Transfer<'_>> for NonFungibleToken {
         args: &Nep171Transfer<'_>,
         f: impl FnOnce(&mut NonFungibleToken) -> R,
     ) -> R {
-        let before_nft_transfer = contract.nft_token(args.token_id.clone()).map(Into::into);
+        let before_nft_transfer = contract
+            .nft_token(args.token_id.clone(), None)
+            .map(Into::into);
         contract
             .before_nft_transfer_balance_record
             .push(before_nft_transfer);
         let r = f(contract);
-        let after_nft_transfer = contract.nft_token(args.token_id.clone()).map(Into::into);
+        let after_nft_transfer = contract
+            .nft_token(args.token_id.clone(), None)
+            .map(Into::into);
         contract
             .after_nft_transfer_balance_record
             .push(after_nft_transfer);
@@ -94,6 +223,7 @@ impl NonFungibleToken {
             token_ids: &[token_id],
             receiver_id: &receiver_id,
             memo: None,
+            origin: ActionOrigin::Internal,
         };
         Nep171Controller::mint(self, &action).unwrap_or_else(|e| {
             env::panic_str(&format!("Mint failed: {e:?}"));
@@ -102,10 +232,7 @@ impl NonFungibleToken {
 }
 
 mod tests {
-    use near_sdk::{
-        test_utils::{get_logs, VMContextBuilder},
-        testing_env, AccountId,
-    };
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId};
     use near_sdk_contract_tools::standard::{
         nep171::{
             event::{Nep171Event, NftTransferLog},
@@ -161,7 +288,7 @@ mod tests {
         );
 
         assert_eq!(
-            get_logs(),
+            logs_without_debug(),
             vec![Nep171Event::NftTransfer(vec![NftTransferLog {
                 memo: None,
                 authorized_id: None,
@@ -173,3 +300,312 @@ mod tests {
         );
     }
 }
+
+#[test]
+fn metadata_freeze() {
+    let mut n = NonFungibleTokenNoHooks {
+        before_nft_transfer_balance_record: store::Vector::new(b"a"),
+        after_nft_transfer_balance_record: store::Vector::new(b"b"),
+    };
+
+    let token_id = "token1".to_string();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    Nep145Controller::deposit_to_storage_account(&mut n, &alice, ONE_NEAR.into()).unwrap();
+    n.mint_with_metadata(token_id.clone(), alice, TokenMetadata::new().title("Title"))
+        .unwrap();
+    n.set_contract_metadata(ContractMetadata::new(
+        "Name".to_string(),
+        "SYM".to_string(),
+        None,
+    ));
+
+    assert!(!n.nft_is_metadata_frozen(Some(token_id.clone())));
+    assert!(!n.nft_is_metadata_frozen(None));
+    assert!(n
+        .freeze_token_metadata(&"does_not_exist".to_string())
+        .is_err());
+
+    n.freeze_token_metadata(&token_id).unwrap();
+    n.freeze_contract_metadata();
+
+    assert!(n.nft_is_metadata_frozen(Some(token_id.clone())));
+    assert!(n.nft_is_metadata_frozen(None));
+    assert!(matches!(
+        n.set_token_metadata(token_id.clone(), TokenMetadata::new().title("New")),
+        Err(nep177::UpdateTokenMetadataError::MetadataFrozen(_)),
+    ));
+    assert_eq!(
+        n.token_metadata(&token_id).unwrap().title.as_deref(),
+        Some("Title"),
+    );
+}
+
+#[test]
+#[should_panic = "Contract metadata is frozen"]
+fn contract_metadata_freeze() {
+    let mut n = NonFungibleTokenNoHooks {
+        before_nft_transfer_balance_record: store::Vector::new(b"a"),
+        after_nft_transfer_balance_record: store::Vector::new(b"b"),
+    };
+
+    n.freeze_contract_metadata();
+    n.set_contract_metadata(ContractMetadata::new(
+        "Name".to_string(),
+        "SYM".to_string(),
+        None,
+    ));
+}
+
+#[test]
+fn enumeration_cleanup_and_compaction() {
+    let mut n = NonFungibleTokenNoHooks {
+        before_nft_transfer_balance_record: store::Vector::new(b"a"),
+        after_nft_transfer_balance_record: store::Vector::new(b"b"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    Nep145Controller::deposit_to_storage_account(&mut n, &alice, ONE_NEAR.into()).unwrap();
+    Nep145Controller::deposit_to_storage_account(&mut n, &bob, ONE_NEAR.into()).unwrap();
+
+    let token_ids = ["t1", "t2", "t3"].map(String::from);
+    Nep171Controller::mint(
+        &mut n,
+        &Nep171Mint {
+            token_ids: &token_ids,
+            receiver_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+    let available_after_mint = n.get_storage_balance(&alice).unwrap().available;
+
+    // Burning refunds the owner
+    Nep171Controller::burn(
+        &mut n,
+        &Nep171Burn {
+            token_ids: &token_ids[..1],
+            owner_id: &alice,
+            authorized_id: None,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+    assert!(n.get_storage_balance(&alice).unwrap().available.0 > available_after_mint.0);
+
+    for token_id in &token_ids[1..2] {
+        n.external_transfer(&Nep171Transfer {
+            authorization: nep171::Nep171TransferAuthorization::Owner,
+            sender_id: &alice,
+            receiver_id: &bob,
+            token_id,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+    }
+
+    assert!(n.compact_owner_index(&alice, 0).is_err());
+
+    let storage_usage_before = env::storage_usage();
+    let available_before = n.get_storage_balance(&alice).unwrap().available;
+    n.nft_compact_owner_index(alice.clone(), 10);
+    assert!(env::storage_usage() < storage_usage_before);
+    assert!(n.get_storage_balance(&alice).unwrap().available.0 > available_before.0);
+    assert_eq!(n.nft_supply_for_owner(alice.clone()).0, 1);
+
+    // Burning the last token removes the owner's index entirely
+    let storage_usage_before = env::storage_usage();
+    Nep171Controller::burn(
+        &mut n,
+        &Nep171Burn {
+            token_ids: &token_ids[2..],
+            owner_id: &alice,
+            authorized_id: None,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+    assert!(env::storage_usage() < storage_usage_before);
+    assert_eq!(n.nft_supply_for_owner(alice).0, 0);
+    assert_eq!(n.nft_total_supply().0, 1);
+}
+
+#[test]
+fn approved_transfer_event_authorized_id() {
+    let mut n = NonFungibleTokenNoHooks {
+        before_nft_transfer_balance_record: store::Vector::new(b"a"),
+        after_nft_transfer_balance_record: store::Vector::new(b"b"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let token_id = "t1".to_string();
+
+    Nep145Controller::deposit_to_storage_account(&mut n, &alice, ONE_NEAR.into()).unwrap();
+    Nep145Controller::deposit_to_storage_account(&mut n, &bob, ONE_NEAR.into()).unwrap();
+    Nep171Controller::mint(
+        &mut n,
+        &Nep171Mint {
+            token_ids: std::slice::from_ref(&token_id),
+            receiver_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+    let approval_id = Nep178Controller::approve(
+        &mut n,
+        &Nep178Approve {
+            token_id: &token_id,
+            current_owner_id: &alice,
+            account_id: &bob,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+
+    n.external_transfer(&Nep171Transfer {
+        authorization: nep171::Nep171TransferAuthorization::ApprovalId(approval_id),
+        sender_id: &bob,
+        receiver_id: &bob,
+        token_id: &token_id,
+        memo: None,
+        msg: None,
+        revert: false,
+        origin: ActionOrigin::Internal,
+    })
+    .unwrap();
+
+    assert_eq!(
+        logs_without_debug().last().unwrap(),
+        r#"EVENT_JSON:{"standard":"nep171","version":"1.2.0","event":"nft_transfer","data":[{"authorized_id":"bob","old_owner_id":"alice","new_owner_id":"bob","token_ids":["t1"]}]}"#,
+    );
+}
+
+#[test]
+fn resolve_transfer_revert_carries_memo() {
+    let mut n = NonFungibleToken::new();
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let token_id = "t1".to_string();
+
+    n.mint(token_id.clone(), bob.clone());
+
+    near_sdk::testing_env!(
+        near_sdk::test_utils::VMContextBuilder::new()
+            .predecessor_account_id(env::current_account_id())
+            .build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"true".to_vec())],
+    );
+
+    assert!(!n.nft_resolve_transfer(
+        alice.clone(),
+        bob,
+        token_id.clone(),
+        None,
+        Some("gift".to_string()),
+    ));
+
+    assert_eq!(n.nft_token(token_id, None).unwrap().owner_id, alice);
+    assert_eq!(
+        logs_without_debug().last().unwrap(),
+        r#"EVENT_JSON:{"standard":"nep171","version":"1.2.0","event":"nft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"alice","token_ids":["t1"],"memo":"gift"}]}"#,
+    );
+}
+
+#[test]
+fn owned_transfer_round_trip() {
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let token_id = "token".to_string();
+
+    let transfer = Nep171Transfer {
+        authorization: nep171::Nep171TransferAuthorization::ApprovalId(3),
+        sender_id: &alice,
+        receiver_id: &bob,
+        token_id: &token_id,
+        memo: None,
+        msg: Some("msg"),
+        revert: false,
+        origin: ActionOrigin::Internal,
+    };
+    let owned = Nep171TransferOwned::from(&transfer);
+
+    let bytes = owned.try_to_vec().unwrap();
+    let owned = Nep171TransferOwned::try_from_slice(&bytes).unwrap();
+
+    assert_eq!(owned.token_id(), &token_id);
+    assert_eq!(owned.as_transfer(), transfer);
+}
+
+#[test]
+fn token_borsh_round_trip() {
+    let mut n = NonFungibleTokenNoHooks {
+        before_nft_transfer_balance_record: store::Vector::new(b"a"),
+        after_nft_transfer_balance_record: store::Vector::new(b"b"),
+    };
+    let alice: AccountId = "alice".parse().unwrap();
+    let token_id = "token1".to_string();
+
+    Nep145Controller::deposit_to_storage_account(&mut n, &alice, ONE_NEAR.into()).unwrap();
+    n.mint_with_metadata(
+        token_id.clone(),
+        alice.clone(),
+        TokenMetadata::new().title("Title"),
+    )
+    .unwrap();
+
+    let token = n.load_token(&token_id).unwrap();
+    assert!(!token.extensions_metadata.is_empty());
+
+    let bytes = token.try_to_vec().unwrap();
+    assert_eq!(Token::try_from_slice(&bytes).unwrap(), token);
+    assert_eq!(Token::try_from_slice_compat(&bytes).unwrap(), token);
+
+    let v1 = TokenV1::from(token);
+    assert_eq!(v1.owner_id, alice);
+    let v1_token = Token::try_from_slice_compat(&v1.try_to_vec().unwrap()).unwrap();
+    assert_eq!(v1_token, Token::from(v1));
+}
+
+#[test]
+fn token_borsh_schema() {
+    let token = Token {
+        token_id: "1".to_string(),
+        owner_id: "alice".parse().unwrap(),
+        extensions_metadata: [
+            (
+                "b".to_string(),
+                near_sdk::serde_json::json!({ "y": 2, "x": 1 }),
+            ),
+            ("a".to_string(), near_sdk::serde_json::json!(null)),
+        ]
+        .into_iter()
+        .collect(),
+    };
+
+    let expected = (
+        "1".to_string(),
+        "alice".to_string(),
+        vec![
+            ("a".to_string(), "null".to_string()),
+            ("b".to_string(), r#"{"x":1,"y":2}"#.to_string()),
+        ],
+    )
+        .try_to_vec()
+        .unwrap();
+
+    assert_eq!(token.try_to_vec().unwrap(), expected);
+}
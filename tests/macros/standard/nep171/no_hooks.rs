@@ -21,6 +21,7 @@ impl Contract {
             token_ids: &token_ids,
             receiver_id: &env::predecessor_account_id(),
             memo: None,
+            origin: ActionOrigin::Internal,
         };
         Nep171Controller::mint(self, &action)
             .unwrap_or_else(|e| env::panic_str(&format!("Minting failed: {e}")));
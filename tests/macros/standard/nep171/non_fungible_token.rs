@@ -50,3 +50,123 @@ impl Contract {
         token_id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{
+        test_utils::{get_created_receipts, get_logs, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use super::*;
+
+    fn setup(deposit: u128) -> (Contract, TokenId) {
+        let alice: AccountId = "alice".parse().unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(ONE_NEAR)
+            .build());
+        let mut contract = Contract::new();
+        contract.storage_deposit(None, None);
+        let token_id = contract.mint();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice)
+            .attached_deposit(deposit)
+            .build());
+
+        (contract, token_id)
+    }
+
+    #[test]
+    fn approve_charges_storage_cost() {
+        let (mut contract, token_id) = setup(ONE_NEAR);
+
+        let storage_usage_start = env::storage_usage();
+        contract.nft_approve(token_id.clone(), "bob".parse().unwrap(), None);
+        let storage_fee =
+            u128::from(env::storage_usage() - storage_usage_start) * env::storage_byte_cost();
+
+        let refunds = get_created_receipts();
+        assert_eq!(refunds.len(), 1);
+        assert_eq!(
+            refunds[0].actions,
+            [near_sdk::mock::VmAction::Transfer {
+                deposit: ONE_NEAR - storage_fee,
+            }],
+        );
+        assert!(contract.nft_is_approved(token_id, "bob".parse().unwrap(), None));
+    }
+
+    #[test]
+    fn approval_events() {
+        let (mut contract, token_id) = setup(ONE_NEAR);
+
+        contract.nft_approve(token_id.clone(), "bob".parse().unwrap(), None);
+        contract.nft_approve(token_id.clone(), "charlie".parse().unwrap(), None);
+
+        assert_eq!(
+            get_logs(),
+            [
+                format!(
+                    r#"EVENT_JSON:{{"standard":"nep178","version":"1.0.0","event":"nft_approve","data":[{{"token_id":"{token_id}","owner_id":"alice","account_id":"bob","approval_id":0}}]}}"#
+                ),
+                format!(
+                    r#"EVENT_JSON:{{"standard":"nep178","version":"1.0.0","event":"nft_approve","data":[{{"token_id":"{token_id}","owner_id":"alice","account_id":"charlie","approval_id":1}}]}}"#
+                ),
+            ],
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("alice".parse().unwrap())
+            .attached_deposit(1)
+            .build());
+        contract.nft_revoke(token_id.clone(), "bob".parse().unwrap());
+        contract.nft_revoke_all(token_id.clone());
+
+        assert_eq!(
+            get_logs(),
+            [
+                format!(
+                    r#"EVENT_JSON:{{"standard":"nep178","version":"1.0.0","event":"nft_revoke","data":[{{"token_id":"{token_id}","owner_id":"alice","account_id":"bob"}}]}}"#
+                ),
+                format!(
+                    r#"EVENT_JSON:{{"standard":"nep178","version":"1.0.0","event":"nft_revoke_all","data":[{{"token_id":"{token_id}","owner_id":"alice"}}]}}"#
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn nft_token_fields() {
+        let (mut contract, token_id) = setup(ONE_NEAR);
+        contract.nft_approve(token_id.clone(), "bob".parse().unwrap(), None);
+
+        let token = contract.nft_token(token_id.clone(), None).unwrap();
+        assert!(token.extensions_metadata.contains_key("metadata"));
+        assert!(token
+            .extensions_metadata
+            .contains_key("approved_account_ids"));
+
+        let token = contract
+            .nft_token(token_id.clone(), Some(vec!["metadata".to_string()]))
+            .unwrap();
+        assert_eq!(token.token_id, token_id);
+        assert_eq!(token.owner_id.as_str(), "alice");
+        assert!(token.extensions_metadata.contains_key("metadata"));
+        assert!(!token
+            .extensions_metadata
+            .contains_key("approved_account_ids"));
+
+        let token = contract.nft_token(token_id, Some(vec![])).unwrap();
+        assert!(token.extensions_metadata.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient deposit for approval: attached 1 yoctoNEAR < required")]
+    fn approve_insufficient_deposit() {
+        let (mut contract, token_id) = setup(1);
+
+        contract.nft_approve(token_id, "bob".parse().unwrap(), None);
+    }
+}
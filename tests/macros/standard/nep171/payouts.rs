@@ -0,0 +1,203 @@
+use near_sdk::{
+    json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    nft::*,
+    standard::nep199::{SetRoyaltiesError, ROYALTY_BASIS_POINTS},
+};
+
+#[derive(NonFungibleToken)]
+#[non_fungible_token(payouts, payouts_max_royalties = "3")]
+#[near_bindgen]
+struct Contract {}
+
+fn call(predecessor: &AccountId, deposit: u128) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.clone())
+        .attached_deposit(deposit)
+        .build());
+}
+
+fn payout(entries: &[(&AccountId, u128)]) -> Payout {
+    Payout {
+        payout: entries
+            .iter()
+            .map(|(account_id, amount)| ((*account_id).clone(), U128(*amount)))
+            .collect(),
+    }
+}
+
+fn setup() -> (Contract, AccountId, TokenId) {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let token_id = "token".to_string();
+
+    call(&alice, ONE_NEAR);
+    contract.storage_deposit(None, None);
+    contract
+        .mint_with_metadata(token_id.clone(), alice.clone(), TokenMetadata::new())
+        .unwrap();
+    contract
+        .set_royalties(
+            &token_id,
+            &[
+                ("bob".parse().unwrap(), 1_000),
+                ("carol".parse().unwrap(), 500),
+            ],
+        )
+        .unwrap();
+
+    (contract, alice, token_id)
+}
+
+#[test]
+fn nft_payout() {
+    let (contract, alice, token_id) = setup();
+    let bob: AccountId = "bob".parse().unwrap();
+    let carol: AccountId = "carol".parse().unwrap();
+
+    assert_eq!(
+        contract.nft_payout(token_id.clone(), U128(1_000), None),
+        payout(&[(&alice, 850), (&bob, 100), (&carol, 50)]),
+    );
+    assert_eq!(
+        contract.nft_payout(token_id, U128(1_000), Some(3)),
+        payout(&[(&alice, 850), (&bob, 100), (&carol, 50)]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Payout to 3 accounts exceeds `max_len_payout` of 2")]
+fn nft_payout_too_long() {
+    let (contract, _, token_id) = setup();
+
+    contract.nft_payout(token_id, U128(1_000), Some(2));
+}
+
+#[test]
+fn nft_transfer_payout() {
+    let (mut contract, alice, token_id) = setup();
+    let market: AccountId = "market".parse().unwrap();
+    let dave: AccountId = "dave".parse().unwrap();
+
+    call(&dave, ONE_NEAR);
+    contract.storage_deposit(None, None);
+
+    let approval_id = contract
+        .approve(&Nep178Approve {
+            token_id: &token_id,
+            current_owner_id: &alice,
+            account_id: &market,
+            origin: ActionOrigin::Internal,
+        })
+        .unwrap();
+
+    call(&market, 1);
+    let result = contract.nft_transfer_payout(
+        dave.clone(),
+        token_id.clone(),
+        Some(approval_id),
+        None,
+        U128(2_000),
+        Some(10),
+    );
+
+    assert_eq!(
+        result,
+        payout(&[
+            (&alice, 1_700),
+            (&"bob".parse().unwrap(), 200),
+            (&"carol".parse().unwrap(), 100),
+        ]),
+    );
+    assert_eq!(contract.token_owner(&token_id), Some(dave.clone()));
+    // Royalties stay with the token.
+    assert_eq!(
+        contract.nft_payout(token_id, U128(1_000), None).payout[&dave],
+        U128(850),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+fn nft_transfer_payout_requires_one_yocto() {
+    let (mut contract, alice, token_id) = setup();
+
+    call(&alice, 0);
+    contract.nft_transfer_payout(
+        "dave".parse().unwrap(),
+        token_id,
+        None,
+        None,
+        U128(1_000),
+        None,
+    );
+}
+
+#[test]
+fn set_royalties_errors() {
+    let (mut contract, _, token_id) = setup();
+    let account = |i: u32| -> AccountId { format!("account{i}").parse().unwrap() };
+
+    assert!(matches!(
+        contract.set_royalties(
+            &token_id,
+            &(0..4).map(|i| (account(i), 100)).collect::<Vec<_>>(),
+        ),
+        Err(SetRoyaltiesError::TooManyRecipients { count: 4, limit: 3 }),
+    ));
+    assert!(matches!(
+        contract.set_royalties(
+            &token_id,
+            &[(account(0), 6_000), (account(1), 5_000)],
+        ),
+        Err(SetRoyaltiesError::RoyaltiesExceedPrice(e)) if e.0 == 11_000,
+    ));
+    assert!(matches!(
+        contract.set_royalties(&"missing".to_string(), &[]),
+        Err(SetRoyaltiesError::TokenDoesNotExist(_)),
+    ));
+
+    // The whole payout may go to royalties.
+    contract
+        .set_royalties(&token_id, &[(account(0), ROYALTY_BASIS_POINTS as u16)])
+        .unwrap();
+    assert_eq!(
+        contract.nft_payout(token_id, U128(1_000), Some(1)),
+        payout(&[(&account(0), 1_000)]),
+    );
+}
+
+#[test]
+fn burn_removes_royalties() {
+    let (mut contract, alice, token_id) = setup();
+
+    contract
+        .burn_with_metadata(token_id.clone(), &alice)
+        .unwrap();
+    assert!(contract.royalties(&token_id).is_empty());
+}
+
+mod standalone {
+    use near_sdk::{json_types::U128, near_bindgen, AccountId};
+    use near_sdk_contract_tools::{nft::*, Nep171, Nep199};
+
+    #[derive(Nep171, Nep199)]
+    #[nep171(burn_hook = "TokenRoyalties")]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[test]
+    fn owner_receives_everything_without_royalties() {
+        let mut contract = Contract {};
+        let alice: AccountId = "alice".parse().unwrap();
+        let token_id = "token".to_string();
+
+        contract.mint_unchecked(std::array::from_ref(&token_id), &alice);
+
+        assert_eq!(
+            contract.nft_payout(token_id, U128(1_000), Some(1)).payout,
+            [(alice, U128(1_000))].into_iter().collect(),
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use near_sdk::{near_bindgen, AccountId, ONE_NEAR};
+use near_sdk_contract_tools::{nft::*, utils::reference::ReferenceError};
+
+// base64 of 32 zero bytes
+const HASH: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+
+#[derive(NonFungibleToken)]
+#[non_fungible_token(strict_metadata)]
+#[near_bindgen]
+struct Contract {}
+
+fn setup() -> (Contract, AccountId) {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    Nep145Controller::deposit_to_storage_account(&mut contract, &alice, ONE_NEAR.into()).unwrap();
+    (contract, alice)
+}
+
+#[test]
+fn validate() {
+    assert_eq!(TokenMetadata::new().title("Title").validate(), Ok(()));
+    assert_eq!(
+        TokenMetadata::new()
+            .media("ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi")
+            .media_hash(HASH)
+            .reference("reference.json")
+            .reference_hash(HASH)
+            .validate(),
+        Ok(()),
+    );
+
+    assert_eq!(
+        TokenMetadata::new()
+            .media("https://example.com/a.png")
+            .validate(),
+        Err(InvalidTokenMetadataError::InvalidHash {
+            field: "media",
+            hash_field: "media_hash",
+            error: ReferenceError::MissingHash,
+        }),
+    );
+    assert_eq!(
+        TokenMetadata::new()
+            .reference("not a url")
+            .reference_hash(HASH)
+            .validate(),
+        Err(InvalidTokenMetadataError::InvalidUrl {
+            field: "reference",
+            value: "not a url".to_string(),
+        }),
+    );
+    assert_eq!(
+        TokenMetadata::new()
+            .media("1://x")
+            .media_hash(HASH)
+            .validate(),
+        Err(InvalidTokenMetadataError::InvalidUrl {
+            field: "media",
+            value: "1://x".to_string(),
+        }),
+    );
+    assert_eq!(
+        TokenMetadata::new().media_hash("not base64!").validate(),
+        Err(InvalidTokenMetadataError::InvalidHash {
+            field: "media",
+            hash_field: "media_hash",
+            error: ReferenceError::InvalidBase64,
+        }),
+    );
+    assert_eq!(
+        TokenMetadata::new().reference_hash("AAAA").validated(),
+        Err(InvalidTokenMetadataError::InvalidHash {
+            field: "reference",
+            hash_field: "reference_hash",
+            error: ReferenceError::InvalidHashLength { length: 3 },
+        }),
+    );
+}
+
+#[test]
+fn strict_mint_accepts_valid_metadata() {
+    let (mut contract, alice) = setup();
+
+    contract
+        .mint_with_metadata(
+            "t".to_string(),
+            alice,
+            TokenMetadata::new()
+                .media("https://example.com/a.png")
+                .media_hash(HASH),
+        )
+        .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Invalid token metadata: `media_hash` must decode to 32 bytes")]
+fn strict_mint_rejects_invalid_metadata() {
+    let (mut contract, alice) = setup();
+
+    let _ = contract.mint_with_metadata(
+        "t".to_string(),
+        alice,
+        TokenMetadata::new()
+            .media("https://example.com/a.png")
+            .media_hash("AAAA"),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid token metadata: `media` requires `media_hash`")]
+fn strict_update_rejects_invalid_metadata() {
+    let (mut contract, alice) = setup();
+
+    contract
+        .mint_with_metadata("t".to_string(), alice, TokenMetadata::new())
+        .unwrap();
+    let _ = contract.set_token_metadata(
+        "t".to_string(),
+        TokenMetadata::new().media("https://example.com/a.png"),
+    );
+}
+
+#[test]
+fn verify_reference() {
+    let content = br#"{"description":"Token"}"#;
+    let hash = near_sdk::base64::encode(near_sdk::env::sha256(content));
+    let metadata = TokenMetadata::new()
+        .reference("reference.json")
+        .reference_hash(hash);
+
+    assert_eq!(metadata.validate(), Ok(()));
+    assert_eq!(metadata.verify_reference(content), Ok(()));
+    assert!(matches!(
+        metadata.verify_reference(b"{}"),
+        Err(ReferenceError::HashMismatch { .. }),
+    ));
+    assert_eq!(
+        TokenMetadata::new().verify_reference(content),
+        Err(ReferenceError::MissingHash),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid contract metadata: `reference_hash` is not valid base64")]
+fn strict_contract_metadata_rejects_invalid_reference() {
+    let (mut contract, _) = setup();
+
+    let mut metadata = ContractMetadata::new("Name".into(), "SYM".into(), None);
+    metadata.reference = Some("reference.json".into());
+    metadata.reference_hash = Some("not base64!".into());
+    contract.set_contract_metadata(metadata);
+}
@@ -0,0 +1,177 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    json_types::{U128, U64},
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools::{
+    nft::*,
+    rbac::Rbac,
+    standard::nep141::{
+        Nep141BatchBurn, Nep141BatchMint, Nep141Burn, Nep141Controller, Nep141Mint, Nep141Transfer,
+    },
+    stats::{ContractStats, Nep141StatsHook, Nep171StatsHook, StatsExternal},
+    Nep141, Rbac, Stats,
+};
+
+#[derive(Nep141, Stats)]
+#[nep141(all_hooks = "Nep141StatsHook")]
+#[near_bindgen]
+struct FungibleToken {}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum Role {
+    Minter,
+}
+
+#[derive(Nep141, Rbac, Stats)]
+#[nep141(
+    all_hooks = "Nep141StatsHook",
+    batch_mint_role = "Role::Minter",
+    batch_burn_role = "Role::Minter"
+)]
+#[rbac(roles = "Role")]
+#[near_bindgen]
+struct BatchFungibleToken {}
+
+#[derive(Nep171, Stats)]
+#[nep171(all_hooks = "Nep171StatsHook")]
+#[near_bindgen]
+struct NonFungibleToken {}
+
+fn stats(transfers: u64, mints: u64, burns: u64, holders: u64) -> ContractStats {
+    ContractStats {
+        transfers: U64(transfers),
+        mints: U64(mints),
+        burns: U64(burns),
+        holders: U64(holders),
+    }
+}
+
+#[test]
+fn nep141_stats() {
+    let mut ft = FungibleToken {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.mint(&Nep141Mint {
+        amount: 100,
+        receiver_id: &alice,
+        memo: None,
+        origin: ActionOrigin::Internal,
+    })
+    .unwrap();
+    assert_eq!(ft.contract_stats(), stats(0, 1, 0, 1));
+
+    let transfer = |amount| Nep141Transfer {
+        sender_id: &alice,
+        receiver_id: &bob,
+        amount,
+        memo: None,
+        msg: None,
+        revert: false,
+        origin: ActionOrigin::Internal,
+    };
+
+    ft.transfer(&transfer(40)).unwrap();
+    assert_eq!(ft.contract_stats(), stats(1, 1, 0, 2));
+
+    // Failed operations are not counted
+    ft.transfer(&transfer(1000)).unwrap_err();
+    assert_eq!(ft.contract_stats(), stats(1, 1, 0, 2));
+
+    // Neither are zero-amount operations
+    ft.transfer(&transfer(0)).unwrap();
+    ft.mint(&Nep141Mint {
+        amount: 0,
+        receiver_id: &alice,
+        memo: None,
+        origin: ActionOrigin::Internal,
+    })
+    .unwrap();
+    assert_eq!(ft.contract_stats(), stats(1, 1, 0, 2));
+
+    ft.transfer(&transfer(60)).unwrap();
+    assert_eq!(ft.contract_stats(), stats(2, 1, 0, 1));
+
+    ft.burn(&Nep141Burn {
+        amount: 100,
+        owner_id: &bob,
+        memo: None,
+        origin: ActionOrigin::Internal,
+    })
+    .unwrap();
+    assert_eq!(ft.contract_stats(), stats(2, 1, 1, 0));
+}
+
+#[test]
+fn nep141_batch_stats() {
+    let mut ft = BatchFungibleToken {};
+    let minter: AccountId = "minter".parse().unwrap();
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.add_role(minter.clone(), &Role::Minter);
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(minter)
+        .attached_deposit(1)
+        .build());
+
+    ft.ft_batch_mint(
+        vec![
+            (alice.clone(), U128(100)),
+            (bob.clone(), U128(50)),
+            (alice.clone(), U128(1)),
+        ],
+        None,
+    );
+    assert_eq!(ft.contract_stats(), stats(0, 3, 0, 2));
+
+    ft.ft_batch_burn(vec![(alice, U128(1)), (bob, U128(50))], None);
+    assert_eq!(ft.contract_stats(), stats(0, 3, 2, 1));
+}
+
+#[test]
+fn nep171_stats() {
+    let mut nft = NonFungibleToken {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let token_ids = ["token1".to_string()];
+
+    let mint = Nep171Mint {
+        token_ids: &token_ids,
+        receiver_id: &alice,
+        memo: None,
+        origin: ActionOrigin::Internal,
+    };
+    Nep171Controller::mint(&mut nft, &mint).unwrap();
+    Nep171Controller::mint(&mut nft, &mint).unwrap_err();
+    assert_eq!(nft.contract_stats(), stats(0, 1, 0, 0));
+
+    nft.external_transfer(&Nep171Transfer {
+        authorization: nep171::Nep171TransferAuthorization::Owner,
+        sender_id: &alice,
+        receiver_id: &bob,
+        token_id: &token_ids[0],
+        memo: None,
+        msg: None,
+        revert: false,
+        origin: ActionOrigin::Internal,
+    })
+    .unwrap();
+    assert_eq!(nft.contract_stats(), stats(1, 1, 0, 0));
+
+    Nep171Controller::burn(
+        &mut nft,
+        &Nep171Burn {
+            token_ids: &token_ids,
+            owner_id: &bob,
+            authorized_id: None,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+    assert_eq!(nft.contract_stats(), stats(1, 1, 1, 0));
+}
@@ -0,0 +1,97 @@
+use near_sdk::{
+    env,
+    json_types::{U128, U64},
+    near_bindgen, AccountId,
+};
+use near_sdk_contract_tools::{
+    nft::*,
+    standard::nep141::{Nep141Controller, Nep141Mint, Nep141Transfer},
+    storage_report::{
+        Nep141StorageUsageHook, Nep171StorageUsageHook, StorageReport as _, StorageReportExternal,
+    },
+    Nep141, StorageReport,
+};
+
+#[derive(Nep141, Nep171, StorageReport)]
+#[nep141(all_hooks = "Nep141StorageUsageHook")]
+#[nep171(all_hooks = "Nep171StorageUsageHook")]
+#[near_bindgen]
+struct Contract {}
+
+#[test]
+fn tracks_usage_per_component() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+
+    assert!(contract.storage_report().components.is_empty());
+
+    Nep141Controller::mint(
+        &mut contract,
+        &Nep141Mint {
+            amount: 100,
+            receiver_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+
+    Nep171Controller::mint(
+        &mut contract,
+        &Nep171Mint {
+            token_ids: &["token".to_string()],
+            receiver_id: &alice,
+            memo: None,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+
+    // The counters themselves are not attributed to either component.
+    let nep141_bytes = contract.component_bytes("Nep141");
+    let nep171_bytes = contract.component_bytes("Nep171");
+    assert!(nep141_bytes > 0);
+    assert!(nep171_bytes > 0);
+
+    let report = contract.storage_report();
+    let total = env::storage_usage();
+    assert_eq!(report.total_bytes, U64(total));
+    assert_eq!(
+        report.locked,
+        U128(u128::from(total) * env::storage_byte_cost()),
+    );
+    assert_eq!(
+        report
+            .components
+            .iter()
+            .map(|c| (c.component.as_str(), c.bytes.0))
+            .collect::<Vec<_>>(),
+        vec![("Nep141", nep141_bytes), ("Nep171", nep171_bytes)],
+    );
+    assert_eq!(
+        report.components[0].locked,
+        U128(u128::from(nep141_bytes) * env::storage_byte_cost()),
+    );
+    assert_eq!(
+        report.untracked_bytes,
+        U64(total - nep141_bytes - nep171_bytes),
+    );
+
+    let bob: AccountId = "bob".parse().unwrap();
+    Nep141Controller::transfer(
+        &mut contract,
+        &Nep141Transfer {
+            sender_id: &alice,
+            receiver_id: &bob,
+            amount: 50,
+            memo: None,
+            msg: None,
+            revert: false,
+            origin: ActionOrigin::Internal,
+        },
+    )
+    .unwrap();
+
+    assert!(contract.component_bytes("Nep141") > nep141_bytes);
+    assert_eq!(contract.component_bytes("Nep171"), nep171_bytes);
+}
@@ -0,0 +1,57 @@
+use near_sdk::{json_types::U128, near_bindgen, test_utils::VMContextBuilder, testing_env};
+use near_sdk_contract_tools::{
+    owner::{self, Owner, OwnerExternal, OwnerInternal},
+    standard::nep141::{self, *},
+    storage_version::StorageVersion,
+    Nep141, Owner,
+};
+
+#[derive(Nep141, Owner)]
+#[contract_tools(storage_versions)]
+#[near_bindgen]
+struct Contract {}
+
+fn setup() -> Contract {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id("alice".parse().unwrap())
+        .attached_deposit(1)
+        .build());
+
+    let mut contract = Contract {};
+    Owner::init(&mut contract, &"alice".parse().unwrap());
+    contract
+        .deposit_unchecked(&"alice".parse().unwrap(), 10)
+        .unwrap();
+    contract
+}
+
+#[test]
+fn written_on_first_use() {
+    let mut contract = setup();
+    let root = <Contract as Nep141ControllerInternal>::root();
+
+    assert_eq!(contract.ft_balance_of("alice".parse().unwrap()), U128(10));
+    assert!(!StorageVersion::slot(&root).exists());
+
+    contract.ft_transfer("bob".parse().unwrap(), U128(1), None);
+    assert_eq!(
+        StorageVersion::slot(&root).read(),
+        Some(nep141::STORAGE_VERSION.version),
+    );
+    assert!(!StorageVersion::slot(&<Contract as OwnerInternal>::root()).exists());
+
+    contract.own_renounce_owner();
+    assert_eq!(
+        StorageVersion::slot(&<Contract as OwnerInternal>::root()).read(),
+        Some(owner::STORAGE_VERSION.version),
+    );
+}
+
+#[test]
+#[should_panic = "Nep141 component storage v2 found, code expects v1 — run migration"]
+fn mismatch() {
+    let contract = setup();
+    StorageVersion::new("Nep141", 2).init(&<Contract as Nep141ControllerInternal>::root());
+
+    contract.ft_balance_of("alice".parse().unwrap());
+}
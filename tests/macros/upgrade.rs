@@ -0,0 +1,289 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, BorshStorageKey,
+};
+use near_sdk_contract_tools::{
+    owner::Owner, rbac::Rbac, upgrade::serialized::UpgradeHook, Owner, Rbac, Upgrade,
+};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum Role {
+    Upgrader,
+}
+
+#[derive(Owner, Rbac, Upgrade)]
+#[rbac(roles = "Role")]
+#[upgrade(hook = "any(owner, role(Role::Upgrader), self)")]
+#[near_bindgen]
+struct Contract {}
+
+fn call_from(account_id: &str) {
+    testing_env!(VMContextBuilder::new()
+        .current_account_id("contract".parse().unwrap())
+        .predecessor_account_id(account_id.parse().unwrap())
+        .build());
+}
+
+fn setup() -> Contract {
+    let mut contract = Contract {};
+    Owner::init(&mut contract, &"owner".parse().unwrap());
+    contract.add_role("upgrader".parse().unwrap(), &Role::Upgrader);
+    contract
+}
+
+#[test]
+fn any_hook_authorized() {
+    let contract = setup();
+
+    for account_id in ["owner", "upgrader", "contract"] {
+        call_from(account_id);
+        contract.on_upgrade();
+    }
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized upgrade")]
+fn any_hook_unauthorized() {
+    let contract = setup();
+
+    call_from("alice");
+    contract.on_upgrade();
+}
+
+mod busy_guard {
+    use near_sdk::{
+        json_types::{Base64VecU8, U128},
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId, PromiseResult, ONE_NEAR,
+    };
+    use near_sdk_contract_tools::{busy::BusyGuard, ft::*, BusyGuard, Upgrade};
+
+    #[derive(BusyGuard, FungibleToken, Upgrade)]
+    #[fungible_token(busy_guard)]
+    #[upgrade(hook = "empty", busy_guard)]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn alice() -> AccountId {
+        "alice".parse().unwrap()
+    }
+
+    fn bob() -> AccountId {
+        "bob".parse().unwrap()
+    }
+
+    fn setup() -> Contract {
+        let mut contract = Contract {};
+        for account_id in [alice(), bob()] {
+            contract
+                .deposit_to_storage_account(&account_id, (ONE_NEAR / 100).into())
+                .unwrap();
+        }
+        contract.deposit_unchecked(&alice(), 100).unwrap();
+        contract
+    }
+
+    fn transfer_call(contract: &mut Contract) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .prepaid_gas(near_sdk::Gas(300_000_000_000_000))
+            .build());
+        contract.ft_transfer_call(bob(), U128(10), None, "".into());
+    }
+
+    fn resolve(contract: &mut Contract) {
+        testing_env!(
+            VMContextBuilder::new()
+                .predecessor_account_id("contract.near".parse().unwrap())
+                .current_account_id("contract.near".parse().unwrap())
+                .build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"\"0\"".to_vec())],
+        );
+        contract.ft_resolve_transfer(alice(), bob(), U128(10));
+    }
+
+    #[test]
+    fn transfer_call_tracked() {
+        let mut contract = setup();
+
+        transfer_call(&mut contract);
+        transfer_call(&mut contract);
+        assert_eq!(Contract::busy_count(), 2);
+
+        resolve(&mut contract);
+        resolve(&mut contract);
+        assert!(!Contract::is_busy());
+    }
+
+    #[test]
+    #[should_panic(expected = "Disallowed while operations are pending")]
+    fn upgrade_blocked_while_busy() {
+        let mut contract = setup();
+
+        transfer_call(&mut contract);
+        contract.upgrade(Base64VecU8(vec![0]));
+    }
+
+    #[test]
+    fn upgrade_allowed_when_idle() {
+        let mut contract = setup();
+
+        transfer_call(&mut contract);
+        resolve(&mut contract);
+        contract.upgrade(Base64VecU8(vec![0]));
+    }
+}
+
+mod action {
+    use near_sdk::{
+        borsh::{self, BorshSerialize},
+        near_bindgen,
+        serde::Serialize,
+        test_utils::{get_created_receipts, VMContextBuilder},
+        testing_env,
+    };
+    use near_sdk_contract_tools::{
+        approval::Action,
+        upgrade::{action::UpgradeAction, PostUpgradeArgsError, MAX_POST_UPGRADE_ARGS_LENGTH},
+    };
+
+    #[derive(BorshSerialize)]
+    #[near_bindgen]
+    struct Contract {}
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct MigrateArgs {
+        note: String,
+    }
+
+    #[test]
+    fn forwards_migrate_args() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("contract".parse().unwrap())
+            .build());
+
+        let action = UpgradeAction::new(vec![0])
+            .with_migrate_args(&MigrateArgs {
+                note: "hello".to_string(),
+            })
+            .unwrap();
+        action.execute(&mut Contract {});
+
+        let receipts = format!("{:?}", get_created_receipts());
+        assert!(receipts.contains("migrate"));
+        assert!(receipts.contains(&format!("{:?}", br#"{"note":"hello"}"#.to_vec())));
+    }
+
+    #[test]
+    fn migrate_args_too_long() {
+        assert_eq!(
+            UpgradeAction::new(vec![0]).with_migrate_args(&MigrateArgs {
+                note: "x".repeat(MAX_POST_UPGRADE_ARGS_LENGTH),
+            }),
+            Err(PostUpgradeArgsError::TooLong(
+                MAX_POST_UPGRADE_ARGS_LENGTH + r#"{"note":""}"#.len()
+            )),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Post-upgrade arguments must be a JSON object")]
+    fn invalid_migrate_args() {
+        UpgradeAction {
+            code: vec![0].into(),
+            migrate_args: Some("[1, 2]".to_string()),
+        }
+        .execute(&mut Contract {});
+    }
+}
+
+mod throttle {
+    use near_sdk::{
+        json_types::Base64VecU8, near_bindgen, test_utils::VMContextBuilder, testing_env,
+    };
+    use near_sdk_contract_tools::{upgrade::history::*, Upgrade};
+
+    const DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+    #[derive(Upgrade)]
+    #[upgrade(
+        hook = "empty",
+        throttle = "UpgradeThrottle::min_interval(DAY).max_per_window(2, 7 * DAY)"
+    )]
+    #[contract_tools(panic_prefix = "upgrader")]
+    #[near_bindgen]
+    struct Contract {}
+
+    fn at(timestamp: u64) {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+    }
+
+    #[test]
+    fn history() {
+        let mut contract = Contract {};
+
+        at(DAY);
+        contract.upgrade(Base64VecU8(vec![1]));
+
+        let history = contract.upgrade_history();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].timestamp.0, DAY);
+        assert_eq!(
+            history[0].code_hash,
+            near_sdk::env::sha256_array(&[1]).into(),
+        );
+    }
+
+    #[test]
+    fn throttle() {
+        let mut contract = Contract {};
+
+        at(0);
+        assert_eq!(Contract::check_upgrade_allowed(), Ok(()));
+        contract.upgrade(Base64VecU8(vec![1]));
+
+        at(DAY - 1);
+        assert_eq!(
+            Contract::check_upgrade_allowed(),
+            Err(UpgradeThrottledError::MinInterval {
+                min_interval: DAY,
+                allowed_at: DAY,
+            }),
+        );
+
+        at(DAY);
+        contract.upgrade(Base64VecU8(vec![2]));
+
+        at(3 * DAY);
+        assert_eq!(
+            Contract::check_upgrade_allowed(),
+            Err(UpgradeThrottledError::MaxPerWindow {
+                max_per_window: 2,
+                window: 7 * DAY,
+                allowed_at: 7 * DAY,
+            }),
+        );
+
+        at(7 * DAY);
+        contract.upgrade(Base64VecU8(vec![3]));
+        assert_eq!(contract.upgrade_history().len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "[upgrader] Upgrade not allowed until")]
+    fn upgrade_too_soon() {
+        let mut contract = Contract {};
+
+        at(0);
+        contract.upgrade(Base64VecU8(vec![1]));
+        contract.upgrade(Base64VecU8(vec![2]));
+    }
+}
@@ -0,0 +1,26 @@
+//! Property-based invariant tests. Run with `--features testing`.
+
+use near_sdk::{env, test_utils::VMContextBuilder, MockedBlockchain, RuntimeFeesConfig, VMConfig};
+
+mod nep141;
+mod nep171;
+
+/// Starts a new execution context (resetting gas usage), keeping storage.
+fn refresh_env() {
+    near_sdk::testing_env!(VMContextBuilder::new()
+        .storage_usage(env::storage_usage())
+        .build());
+}
+
+/// Resets the mocked blockchain, including storage.
+fn reset_env() {
+    env::set_blockchain_interface(MockedBlockchain::new(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        vec![],
+        Default::default(),
+        Default::default(),
+        None,
+    ));
+}
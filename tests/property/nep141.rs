@@ -0,0 +1,45 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    near_bindgen, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    ft::*,
+    testing::{accounts, check_nep141_total_supply, fungible_token_op},
+    FungibleToken,
+};
+use proptest::prelude::*;
+
+use crate::{refresh_env, reset_env};
+
+#[derive(FungibleToken, BorshSerialize, BorshDeserialize)]
+#[near_bindgen]
+struct Contract {}
+
+const ACCOUNTS: usize = 4;
+
+proptest! {
+    #[test]
+    fn total_supply_equals_sum_of_balances(
+        ops in prop::collection::vec(
+            fungible_token_op(accounts(ACCOUNTS), 1_000_000, ONE_NEAR / 10),
+            1..64,
+        ),
+    ) {
+        reset_env();
+        let accounts = accounts(ACCOUNTS);
+        let mut contract = Contract {};
+        contract.set_storage_balance_bounds(&StorageBalanceBounds {
+            min: U128(ONE_NEAR / 10),
+            max: None,
+        });
+
+        for op in ops {
+            refresh_env();
+            op.apply(&mut contract);
+            if let Err(e) = check_nep141_total_supply(&contract, &accounts) {
+                prop_assert!(false, "{} after {:?}", e, op);
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+};
+use near_sdk_contract_tools::{
+    nft::*,
+    testing::{accounts, check_nep171_ownership, non_fungible_token_op, token_ids},
+    Nep171, Nep181,
+};
+use proptest::prelude::*;
+
+use crate::{refresh_env, reset_env};
+
+#[derive(Nep171, Nep181, BorshSerialize, BorshDeserialize)]
+#[nep171(all_hooks = "TokenEnumeration")]
+#[near_bindgen]
+struct Contract {}
+
+const ACCOUNTS: usize = 4;
+const TOKENS: usize = 6;
+
+proptest! {
+    #[test]
+    fn enumeration_matches_ownership(
+        ops in prop::collection::vec(
+            non_fungible_token_op(accounts(ACCOUNTS), token_ids(TOKENS)),
+            1..64,
+        ),
+    ) {
+        reset_env();
+        let accounts = accounts(ACCOUNTS);
+        let token_ids = token_ids(TOKENS);
+        let mut contract = Contract {};
+
+        for op in ops {
+            refresh_env();
+            op.apply(&mut contract);
+            if let Err(e) = check_nep171_ownership(&contract, &accounts, &token_ids) {
+                prop_assert!(false, "{} after {:?}", e, op);
+            }
+        }
+    }
+}
@@ -5,6 +5,8 @@ use near_sdk::{json_types::U128, serde::de::DeserializeOwned, serde_json::json};
 use near_workspaces::{result::ExecutionFinalResult, Account, AccountId, Contract};
 use pretty_assertions::assert_eq;
 
+pub mod upgrade;
+
 pub async fn nft_token<T: DeserializeOwned>(contract: &Contract, token_id: &str) -> Option<T> {
     contract
         .view("nft_token")
@@ -0,0 +1,114 @@
+//! Harness for regression-testing contract upgrades in the sandbox: deploy
+//! the old version, populate its state, upgrade through the contract's own
+//! upgrade method, and compare the state before and after.
+
+use near_sdk::borsh::{self, BorshSerialize};
+use near_sdk_contract_tools::testing::snapshot::{StateDiff, StateSnapshot};
+use near_workspaces::{network::Sandbox, result::ExecutionFinalResult, Account, Contract, Worker};
+
+/// How the new code is passed to the upgrade method, matching the
+/// `serializer` of the `Upgrade` derive macro.
+pub enum UpgradeArgs {
+    /// `#[upgrade(serializer = "borsh")]`
+    Borsh,
+    /// `#[upgrade(serializer = "jsonbase64")]`
+    JsonBase64,
+    /// `#[upgrade(serializer = "raw")]`
+    Raw,
+}
+
+impl UpgradeArgs {
+    fn encode(&self, code: &[u8]) -> Vec<u8> {
+        #[derive(BorshSerialize)]
+        struct ArgsBorsh<'a> {
+            code: &'a [u8],
+        }
+
+        match self {
+            Self::Borsh => ArgsBorsh { code }.try_to_vec().unwrap(),
+            Self::JsonBase64 => near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+                "code": near_sdk::json_types::Base64VecU8::from(code.to_vec()),
+            }))
+            .unwrap(),
+            Self::Raw => code.to_vec(),
+        }
+    }
+}
+
+/// The state of a contract before and after an upgrade.
+pub struct UpgradeOutcome {
+    /// Result of the upgrade transaction.
+    pub result: ExecutionFinalResult,
+    /// State before the upgrade.
+    pub before: StateSnapshot,
+    /// State after the upgrade (and migration).
+    pub after: StateSnapshot,
+}
+
+impl UpgradeOutcome {
+    /// The changes made to the state by the upgrade.
+    pub fn diff(&self) -> StateDiff {
+        self.before.diff(&self.after)
+    }
+}
+
+/// A contract deployed to a sandbox by `owner`, ready to be upgraded.
+pub struct UpgradeHarness {
+    pub worker: Worker<Sandbox>,
+    pub owner: Account,
+    pub contract: Contract,
+}
+
+impl UpgradeHarness {
+    /// Deploys `wasm` from a new `owner` account and calls `init_method`
+    /// (e.g. `"new"`) as the owner, if given.
+    pub async fn deploy(wasm: &[u8], init_method: Option<&str>) -> Self {
+        let worker = near_workspaces::sandbox().await.unwrap();
+        let owner = worker.dev_create_account().await.unwrap();
+        let contract = owner.deploy(wasm).await.unwrap().unwrap();
+
+        if let Some(init_method) = init_method {
+            owner
+                .call(contract.id(), init_method)
+                .transact()
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        Self {
+            worker,
+            owner,
+            contract,
+        }
+    }
+
+    /// The current raw state of the contract.
+    pub async fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot::new(self.contract.view_state().await.unwrap())
+    }
+
+    /// Calls `method` (usually `"upgrade"`) as the owner with the new `code`,
+    /// and snapshots the state before and after. Does not fail if the
+    /// upgrade transaction fails; check [`UpgradeOutcome::result`].
+    pub async fn upgrade(&self, method: &str, code: &[u8], args: UpgradeArgs) -> UpgradeOutcome {
+        let before = self.snapshot().await;
+
+        let result = self
+            .owner
+            .call(self.contract.id(), method)
+            .max_gas()
+            .args(args.encode(code))
+            .transact()
+            .await
+            .unwrap();
+
+        let after = self.snapshot().await;
+
+        UpgradeOutcome {
+            result,
+            before,
+            after,
+        }
+    }
+}
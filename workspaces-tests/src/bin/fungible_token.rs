@@ -43,6 +43,7 @@ impl Contract {
                 amount: amount.into(),
                 receiver_id: &env::predecessor_account_id(),
                 memo: None,
+                origin: ActionOrigin::Internal,
             },
         )
         .unwrap();
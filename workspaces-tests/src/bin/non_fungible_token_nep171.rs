@@ -7,7 +7,11 @@ use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     env, log, near_bindgen, PanicOnDefault,
 };
-use near_sdk_contract_tools::{hook::Hook, standard::nep171::*, Nep171};
+use near_sdk_contract_tools::{
+    hook::{ActionOrigin, Hook},
+    standard::nep171::*,
+    Nep171,
+};
 
 #[derive(PanicOnDefault, BorshSerialize, BorshDeserialize, Nep171)]
 #[nep171(transfer_hook = "Self")]
@@ -39,6 +43,7 @@ impl Contract {
             token_ids: &token_ids,
             receiver_id: &env::predecessor_account_id(),
             memo: None,
+            origin: ActionOrigin::Internal,
         };
         Nep171Controller::mint(self, &action)
             .unwrap_or_else(|e| env::panic_str(&format!("Failed to mint: {:#?}", e)));
@@ -71,7 +71,7 @@ impl Contract {
 
     pub fn count_members(&self, role: String) -> u32 {
         let role: Role = Role::from_str(&role).expect("Invalid role identifier");
-        Self::iter_members_of(&role).count() as u32
+        <Self as Rbac>::count_members(&role)
     }
 
     pub fn requires_alpha(&mut self) {
@@ -0,0 +1,38 @@
+#![allow(missing_docs)]
+
+// Ignore
+pub fn main() {}
+
+use near_contract_standards::fungible_token::FungibleToken;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    near_bindgen, AccountId, PanicOnDefault, PromiseOrValue,
+};
+
+/// Reference NEP-141 implementation from `near-contract-standards`, for
+/// conformance tests.
+#[derive(PanicOnDefault, BorshSerialize, BorshDeserialize)]
+#[near_bindgen]
+pub struct Contract {
+    token: FungibleToken,
+}
+
+near_contract_standards::impl_fungible_token_core!(Contract, token);
+near_contract_standards::impl_fungible_token_storage!(Contract, token);
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            token: FungibleToken::new(b"t"),
+        }
+    }
+
+    pub fn mint(&mut self, amount: U128) {
+        self.token
+            .internal_deposit(&env::predecessor_account_id(), amount.into());
+    }
+}
@@ -0,0 +1,76 @@
+#![allow(missing_docs)]
+
+// Ignore
+pub fn main() {}
+
+use near_contract_standards::non_fungible_token::{
+    metadata::TokenMetadata, NonFungibleToken, Token, TokenId,
+};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId, PanicOnDefault, Promise, PromiseOrValue,
+};
+
+/// Reference NEP-171 implementation from `near-contract-standards`, for
+/// conformance tests.
+#[derive(PanicOnDefault, BorshSerialize, BorshDeserialize)]
+#[near_bindgen]
+pub struct Contract {
+    tokens: NonFungibleToken,
+}
+
+near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
+near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
+near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            tokens: NonFungibleToken::new(
+                b"o",
+                env::current_account_id(),
+                Some(b"m"),
+                Some(b"e"),
+                Some(b"a"),
+            ),
+        }
+    }
+
+    /// The reference implementation does not implement NEP-145, but tests
+    /// register accounts with both contracts in the same way.
+    #[payable]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) {
+        let _ = (account_id, registration_only);
+    }
+
+    pub fn mint(&mut self, token_ids: Vec<TokenId>) {
+        let receiver = env::predecessor_account_id();
+        for token_id in token_ids {
+            self.tokens.internal_mint_with_refund(
+                token_id.clone(),
+                receiver.clone(),
+                Some(TokenMetadata {
+                    title: Some(token_id),
+                    description: Some("description".to_string()),
+                    media: None,
+                    media_hash: None,
+                    copies: None,
+                    issued_at: None,
+                    expires_at: None,
+                    starts_at: None,
+                    updated_at: None,
+                    extra: None,
+                    reference: None,
+                    reference_hash: None,
+                }),
+                None,
+            );
+        }
+    }
+}
@@ -0,0 +1,210 @@
+#![cfg(not(windows))]
+
+use near_sdk::serde_json::{json, Value};
+use near_sdk_contract_tools::conformance::*;
+use near_workspaces::{network::Sandbox, Account, Contract, Worker};
+
+const FT_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/fungible_token.wasm");
+
+const FT_REFERENCE_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/reference_fungible_token.wasm");
+
+const FT_RECEIVER_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/fungible_token_receiver.wasm");
+
+const NFT_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/non_fungible_token_full.wasm");
+
+const NFT_REFERENCE_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/reference_non_fungible_token.wasm");
+
+const NFT_RECEIVER_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/non_fungible_token_receiver.wasm");
+
+/// Both token contracts, deployed in the same sandbox so that they share
+/// the same accounts.
+struct Deployment {
+    reference: Contract,
+    subject: Contract,
+    owner: Account,
+    receiver: Account,
+    accounts: Accounts,
+    _worker: Worker<Sandbox>,
+}
+
+async fn deploy(reference_wasm: &[u8], subject_wasm: &[u8], receiver_wasm: &[u8]) -> Deployment {
+    let worker = near_workspaces::sandbox().await.unwrap();
+
+    let reference = worker.dev_deploy(reference_wasm).await.unwrap();
+    let subject = worker.dev_deploy(subject_wasm).await.unwrap();
+    let receiver_contract = worker.dev_deploy(receiver_wasm).await.unwrap();
+    for contract in [&reference, &subject, &receiver_contract] {
+        contract.call("new").transact().await.unwrap().unwrap();
+    }
+
+    let owner = worker.dev_create_account().await.unwrap();
+    let receiver = worker.dev_create_account().await.unwrap();
+    let unregistered = worker.dev_create_account().await.unwrap();
+
+    let accounts = Accounts {
+        owner: owner.id().as_str().parse().unwrap(),
+        receiver: receiver.id().as_str().parse().unwrap(),
+        receiver_contract: receiver_contract.id().as_str().parse().unwrap(),
+        unregistered: unregistered.id().as_str().parse().unwrap(),
+    };
+
+    Deployment {
+        reference,
+        subject,
+        owner,
+        receiver,
+        accounts,
+        _worker: worker,
+    }
+}
+
+fn storage_deposits(accounts: &Accounts) -> Vec<Call> {
+    vec![
+        Call {
+            signer: Signer::Owner,
+            method: "storage_deposit",
+            args: json!({}),
+            deposit: STORAGE_DEPOSIT,
+        },
+        Call {
+            signer: Signer::Receiver,
+            method: "storage_deposit",
+            args: json!({}),
+            deposit: STORAGE_DEPOSIT,
+        },
+        Call {
+            signer: Signer::Owner,
+            method: "storage_deposit",
+            args: json!({ "account_id": accounts.receiver_contract }),
+            deposit: STORAGE_DEPOSIT,
+        },
+    ]
+}
+
+fn ft_setup(accounts: &Accounts) -> Vec<Call> {
+    let mut calls = storage_deposits(accounts);
+    calls.push(Call {
+        signer: Signer::Owner,
+        method: "mint",
+        args: json!({ "amount": FT_INITIAL_BALANCE.to_string() }),
+        deposit: 0,
+    });
+    calls
+}
+
+fn nft_setup(accounts: &Accounts) -> Vec<Call> {
+    let mut calls = storage_deposits(accounts);
+    calls.push(Call {
+        signer: Signer::Owner,
+        method: "mint",
+        args: json!({ "token_ids": [NFT_TOKEN_ID, NFT_OTHER_TOKEN_ID] }),
+        deposit: 0,
+    });
+    calls
+}
+
+impl Deployment {
+    async fn call(&self, token: &Contract, call: &Call) -> bool {
+        let signer = match call.signer {
+            Signer::Owner => &self.owner,
+            Signer::Receiver => &self.receiver,
+        };
+
+        signer
+            .call(token.id(), call.method)
+            .args_json(&call.args)
+            .deposit(call.deposit)
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+            .is_success()
+    }
+
+    async fn observe(&self, token: &Contract, setup: &[Call], case: &Case) -> Observation {
+        for call in setup {
+            assert!(
+                self.call(token, call).await,
+                "setup call `{}` failed",
+                call.method,
+            );
+        }
+
+        let mut succeeded = vec![];
+        for call in &case.calls {
+            succeeded.push(self.call(token, call).await);
+        }
+
+        let mut views = vec![];
+        for view in &case.views {
+            let result = token
+                .view(view.method)
+                .args_json(&view.args)
+                .await
+                .unwrap()
+                .json::<Value>()
+                .unwrap();
+            views.push(view.select(result));
+        }
+
+        Observation { succeeded, views }
+    }
+}
+
+async fn check_all(
+    reference_wasm: &[u8],
+    subject_wasm: &[u8],
+    receiver_wasm: &[u8],
+    setup: fn(&Accounts) -> Vec<Call>,
+    cases: fn(&Accounts) -> Vec<Case>,
+) {
+    let mut index = 0;
+
+    loop {
+        // Each case runs against fresh contracts.
+        let deployment = deploy(reference_wasm, subject_wasm, receiver_wasm).await;
+        let setup = setup(&deployment.accounts);
+        let Some(case) = cases(&deployment.accounts).into_iter().nth(index) else {
+            break;
+        };
+
+        let reference = deployment
+            .observe(&deployment.reference, &setup, &case)
+            .await;
+        let subject = deployment.observe(&deployment.subject, &setup, &case).await;
+
+        assert_conforms(&case, &reference, &subject);
+
+        index += 1;
+    }
+}
+
+#[tokio::test]
+async fn fungible_token_conforms() {
+    check_all(
+        FT_REFERENCE_WASM,
+        FT_WASM,
+        FT_RECEIVER_WASM,
+        ft_setup,
+        ft_cases,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn non_fungible_token_conforms() {
+    check_all(
+        NFT_REFERENCE_WASM,
+        NFT_WASM,
+        NFT_RECEIVER_WASM,
+        nft_setup,
+        nft_cases,
+    )
+    .await;
+}
@@ -26,6 +26,9 @@ const WASM_171_ONLY: &[u8] =
 const WASM_FULL: &[u8] =
     include_bytes!("../../target/wasm32-unknown-unknown/release/non_fungible_token_full.wasm");
 
+/// Covers the storage cost of an approval; the excess is refunded.
+const APPROVE_DEPOSIT: u128 = ONE_NEAR / 100;
+
 const RECEIVER_WASM: &[u8] =
     include_bytes!("../../target/wasm32-unknown-unknown/release/non_fungible_token_receiver.wasm");
 
@@ -790,7 +793,7 @@ async fn transfer_approval_success() {
             "token_id": "token_0",
             "account_id": bob.id(),
         }))
-        .deposit(1)
+        .deposit(APPROVE_DEPOSIT)
         .transact()
         .await
         .unwrap()
@@ -869,7 +872,7 @@ async fn transfer_approval_unapproved_fail() {
             "token_id": "token_0",
             "account_id": debbie.id(),
         }))
-        .deposit(1)
+        .deposit(APPROVE_DEPOSIT)
         .transact()
         .await
         .unwrap()
@@ -914,7 +917,7 @@ async fn transfer_approval_unapproved_fail() {
 }
 
 #[tokio::test]
-#[should_panic = "Attached deposit must be greater than zero"]
+#[should_panic = "Insufficient deposit for approval: attached 0 yoctoNEAR"]
 async fn transfer_approval_no_deposit_fail() {
     let Setup { contract, accounts } =
         setup_balances(WASM_FULL, 2, |i| vec![format!("token_{i}")], true).await;
@@ -946,7 +949,7 @@ async fn transfer_approval_double_approval_fail() {
             "token_id": "token_0",
             "account_id": bob.id(),
         }))
-        .deposit(1)
+        .deposit(APPROVE_DEPOSIT)
         .transact()
         .await
         .unwrap()
@@ -958,7 +961,7 @@ async fn transfer_approval_double_approval_fail() {
             "token_id": "token_0",
             "account_id": bob.id(),
         }))
-        .deposit(1)
+        .deposit(APPROVE_DEPOSIT)
         .transact()
         .await
         .unwrap();
@@ -987,7 +990,7 @@ async fn transfer_approval_unauthorized_approval_fail() {
             "token_id": "token_0",
             "account_id": bob.id(),
         }))
-        .deposit(1)
+        .deposit(APPROVE_DEPOSIT)
         .transact()
         .await
         .unwrap();
@@ -1022,7 +1025,7 @@ async fn transfer_approval_too_many_approvals_fail() {
                     "token_id": "token_0",
                     "account_id": format!("account_{}", i),
                 }))
-                .deposit(1)
+                .deposit(APPROVE_DEPOSIT)
                 .transact()
                 .await
                 .unwrap()
@@ -1038,7 +1041,7 @@ async fn transfer_approval_too_many_approvals_fail() {
             "token_id": "token_0",
             "account_id": bob.id(),
         }))
-        .deposit(1)
+        .deposit(APPROVE_DEPOSIT)
         .transact()
         .await
         .unwrap();
@@ -1067,7 +1070,7 @@ async fn transfer_approval_approved_but_wrong_approval_id_fail() {
             "token_id": "token_0",
             "account_id": bob.id(),
         }))
-        .deposit(1)
+        .deposit(APPROVE_DEPOSIT)
         .transact()
         .await
         .unwrap()
@@ -6,6 +6,7 @@ use near_sdk::{
 };
 use near_workspaces::{Account, Contract};
 use pretty_assertions::assert_eq;
+use workspaces_tests_utils::upgrade::{UpgradeArgs, UpgradeHarness};
 
 const WASM_BORSH: &[u8] =
     include_bytes!("../../target/wasm32-unknown-unknown/release/upgrade_old_borsh.wasm");
@@ -221,3 +222,28 @@ async fn upgrade_failure_not_owner_jsonbase64() {
 async fn upgrade_failure_not_owner_raw() {
     fail_owner(WASM_RAW, NEW_WASM.to_vec()).await;
 }
+
+#[tokio::test]
+async fn upgrade_state_diff() {
+    let harness = UpgradeHarness::deploy(WASM_BORSH, Some("new")).await;
+
+    harness
+        .owner
+        .call(harness.contract.id(), "increment_foo")
+        .transact()
+        .await
+        .unwrap()
+        .unwrap();
+
+    let outcome = harness
+        .upgrade("upgrade", NEW_WASM, UpgradeArgs::Borsh)
+        .await;
+    assert!(outcome.result.is_success());
+
+    let diff = outcome.diff();
+    diff.assert_unchanged(&[b"~o"]);
+    assert_eq!(
+        diff.changed.get(&b"STATE".to_vec()),
+        Some(&(1u32.to_le_bytes().to_vec(), 1u64.to_le_bytes().to_vec())),
+    );
+}